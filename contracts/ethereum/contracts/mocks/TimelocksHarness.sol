@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.23;
+
+/**
+ * @title TimelocksHarness
+ * @notice Exposes the packing of 1inch TimelocksLib (cross-chain-swap) so the
+ * shared fixtures in contracts/shared/fixtures/timelocks.json can be generated
+ * and checked against Solidity. Stage i sits at bits i * 32, deployedAt at 224.
+ */
+contract TimelocksHarness {
+    uint256 private constant _DEPLOYED_AT_OFFSET = 224;
+    uint256 private constant _DEPLOYED_AT_MASK = 0xffffffff00000000000000000000000000000000000000000000000000000000;
+
+    function pack(uint32[7] calldata stages, uint32 deployedAt) external pure returns (uint256 timelocks) {
+        for (uint256 i = 0; i < 7; i++) {
+            timelocks |= uint256(stages[i]) << (i * 32);
+        }
+        timelocks = setDeployedAt(timelocks, deployedAt);
+    }
+
+    function setDeployedAt(uint256 timelocks, uint256 value) public pure returns (uint256) {
+        return (timelocks & ~_DEPLOYED_AT_MASK) | (value << _DEPLOYED_AT_OFFSET);
+    }
+
+    function get(uint256 timelocks, uint256 stage) external pure returns (uint256) {
+        uint256 bitShift = stage * 32;
+        return (timelocks >> _DEPLOYED_AT_OFFSET) + uint32(timelocks >> bitShift);
+    }
+
+    function rescueStart(uint256 timelocks, uint256 rescueDelay) external pure returns (uint256) {
+        return rescueDelay + (timelocks >> _DEPLOYED_AT_OFFSET);
+    }
+}
@@ -61,7 +61,7 @@ async function executeNearSide() {
         resolver: NEAR_CONFIG.resolverId,
         amount: NEAR_CONFIG.amount,
         resolver_fee: NEAR_CONFIG.resolverFee,
-        timelocks: "0",
+        timelocks: "570442770169111509339128526274560", // stages [0, 60, 3600, 7200]
         source_chain_id: 11155111
     };
     
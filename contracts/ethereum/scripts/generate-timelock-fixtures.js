@@ -0,0 +1,49 @@
+const fs = require("fs");
+const path = require("path");
+const { ethers } = require("hardhat");
+
+// Regenerate contracts/shared/fixtures/timelocks.json from TimelocksHarness
+// Usage: npx hardhat run scripts/generate-timelock-fixtures.js
+const CASES = [
+    { name: "zero", stages: [0, 0, 0, 0, 0, 0, 0], deployed_at: 0 },
+    { name: "typical_src_and_dst", stages: [300, 600, 3600, 7200, 240, 540, 3000], deployed_at: 1700000000 },
+    { name: "not_deployed", stages: [60, 120, 1800, 3600, 30, 90, 1200], deployed_at: 0 },
+    { name: "max_values", stages: Array(7).fill(0xffffffff), deployed_at: 0xffffffff },
+    {
+        name: "distinct_bytes",
+        stages: [0x01020304, 0x05060708, 0x090a0b0c, 0x0d0e0f10, 0x11121314, 0x15161718, 0x191a1b1c],
+        deployed_at: 0x1d1e1f20,
+    },
+];
+
+async function main() {
+    const TimelocksHarness = await ethers.getContractFactory("TimelocksHarness");
+    const harness = await TimelocksHarness.deploy();
+    await harness.waitForDeployment();
+
+    const cases = [];
+    for (const { name, stages, deployed_at } of CASES) {
+        const packed = await harness.pack(stages, deployed_at);
+        const stageStarts = [];
+        for (let stage = 0; stage < 7; stage++) {
+            stageStarts.push(Number(await harness.get(packed, stage)));
+        }
+        cases.push({
+            name,
+            stages,
+            deployed_at,
+            packed: ethers.toBeHex(packed, 32),
+            decimal: packed.toString(),
+            stage_starts: stageStarts,
+        });
+    }
+
+    const output = path.join(__dirname, "../../shared/fixtures/timelocks.json");
+    fs.writeFileSync(output, JSON.stringify({ cases }, null, 2) + "\n");
+    console.log(`Wrote ${cases.length} cases to ${output}`);
+}
+
+main().catch((error) => {
+    console.error(error);
+    process.exitCode = 1;
+});
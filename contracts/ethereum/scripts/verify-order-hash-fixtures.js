@@ -0,0 +1,46 @@
+const { ethers, network } = require("hardhat");
+const fixtures = require("../../shared/fixtures/order_hashes.json");
+
+// Check contracts/shared/fixtures/order_hashes.json against hashOrder of the
+// deployed 1inch Aggregation Router v6 on the selected network
+// Usage: npx hardhat run scripts/verify-order-hash-fixtures.js --network mainnet
+const ROUTER_ABI = [
+    "function hashOrder((uint256 salt, uint256 maker, uint256 receiver, uint256 makerAsset, uint256 takerAsset, uint256 makingAmount, uint256 takingAmount, uint256 makerTraits) order) view returns (bytes32)",
+];
+
+async function main() {
+    const { chainId } = await ethers.provider.getNetwork();
+    const cases = fixtures.cases.filter((fixture) => BigInt(fixture.chain_id) === chainId);
+    if (cases.length === 0) {
+        console.log(`No fixtures for chain ${chainId} (${network.name})`);
+        return;
+    }
+
+    let failures = 0;
+    for (const fixture of cases) {
+        const router = new ethers.Contract(fixture.verifying_contract, ROUTER_ABI, ethers.provider);
+        // The router takes addresses as uint256 (the 1inch `Address` type)
+        const order = fixture.order;
+        const hash = await router.hashOrder([
+            BigInt(order.salt),
+            BigInt(order.maker),
+            BigInt(order.receiver),
+            BigInt(order.maker_asset),
+            BigInt(order.taker_asset),
+            BigInt(order.making_amount),
+            BigInt(order.taking_amount),
+            BigInt(order.maker_traits),
+        ]);
+        const ok = hash === fixture.order_hash;
+        failures += ok ? 0 : 1;
+        console.log(`${ok ? "OK      " : "MISMATCH"} ${fixture.name}: ${hash}`);
+    }
+    if (failures > 0) {
+        throw new Error(`${failures} order hash fixture(s) differ from the deployed router`);
+    }
+}
+
+main().catch((error) => {
+    console.error(error);
+    process.exitCode = 1;
+});
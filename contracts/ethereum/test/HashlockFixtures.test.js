@@ -0,0 +1,20 @@
+const { expect } = require("chai");
+const { ethers } = require("hardhat");
+const fixtures = require("../../shared/fixtures/hashlocks.json");
+
+// Same secret, same hashlock: the Rust contracts hash preimages with the
+// fusion-hashlock crate, which is tested against the same fixtures
+describe("Shared hashlock fixtures", function () {
+    for (const fixture of fixtures.cases) {
+        it(`Should hash ${fixture.name} like the Rust contracts`, function () {
+            const preimage = ethers.getBytes(fixture.preimage);
+            expect(ethers.sha256(preimage)).to.equal(fixture.sha256);
+            expect(ethers.keccak256(preimage)).to.equal(fixture.keccak256);
+        });
+    }
+
+    it("Should hash the secret used across these tests as UTF-8 bytes", function () {
+        const fixture = fixtures.cases.find((c) => c.name === "utf8_secret123");
+        expect(ethers.keccak256(ethers.toUtf8Bytes("secret123"))).to.equal(fixture.keccak256);
+    });
+});
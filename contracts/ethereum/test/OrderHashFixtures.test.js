@@ -0,0 +1,50 @@
+const { expect } = require("chai");
+const { ethers } = require("hardhat");
+const fixtures = require("../../shared/fixtures/order_hashes.json");
+
+// The Rust contracts and relayer hash orders with the fusion-order-hash
+// crate, which is tested against the same fixtures.
+// scripts/verify-order-hash-fixtures.js checks them against the deployed router
+const ORDER_TYPES = {
+    Order: [
+        { name: "salt", type: "uint256" },
+        { name: "maker", type: "address" },
+        { name: "receiver", type: "address" },
+        { name: "makerAsset", type: "address" },
+        { name: "takerAsset", type: "address" },
+        { name: "makingAmount", type: "uint256" },
+        { name: "takingAmount", type: "uint256" },
+        { name: "makerTraits", type: "uint256" },
+    ],
+};
+
+function toTypedOrder(order) {
+    return {
+        salt: BigInt(order.salt),
+        maker: ethers.getAddress(order.maker),
+        receiver: ethers.getAddress(order.receiver),
+        makerAsset: ethers.getAddress(order.maker_asset),
+        takerAsset: ethers.getAddress(order.taker_asset),
+        makingAmount: BigInt(order.making_amount),
+        takingAmount: BigInt(order.taking_amount),
+        makerTraits: BigInt(order.maker_traits),
+    };
+}
+
+describe("Shared order hash fixtures", function () {
+    for (const fixture of fixtures.cases) {
+        it(`Should hash ${fixture.name} like the Limit Order Protocol`, function () {
+            const domain = {
+                name: "1inch Aggregation Router",
+                version: "6",
+                chainId: fixture.chain_id,
+                verifyingContract: ethers.getAddress(fixture.verifying_contract),
+            };
+            const order = toTypedOrder(fixture.order);
+
+            expect(ethers.TypedDataEncoder.hashDomain(domain)).to.equal(fixture.domain_separator);
+            expect(ethers.TypedDataEncoder.hashStruct("Order", ORDER_TYPES, order)).to.equal(fixture.struct_hash);
+            expect(ethers.TypedDataEncoder.hash(domain, ORDER_TYPES, order)).to.equal(fixture.order_hash);
+        });
+    }
+});
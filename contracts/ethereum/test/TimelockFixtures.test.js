@@ -0,0 +1,27 @@
+const { expect } = require("chai");
+const { ethers } = require("hardhat");
+const fixtures = require("../../shared/fixtures/timelocks.json");
+
+// The Rust contracts unpack timelocks with the fusion-timelocks crate, which
+// is tested against the same fixtures
+describe("Shared timelock fixtures", function () {
+    let harness;
+
+    before(async function () {
+        const TimelocksHarness = await ethers.getContractFactory("TimelocksHarness");
+        harness = await TimelocksHarness.deploy();
+        await harness.waitForDeployment();
+    });
+
+    for (const fixture of fixtures.cases) {
+        it(`Should pack and unpack ${fixture.name} like TimelocksLib`, async function () {
+            const packed = await harness.pack(fixture.stages, fixture.deployed_at);
+            expect(packed).to.equal(BigInt(fixture.packed));
+            expect(packed.toString()).to.equal(fixture.decimal);
+
+            for (let stage = 0; stage < 7; stage++) {
+                expect(await harness.get(packed, stage)).to.equal(BigInt(fixture.stage_starts[stage]));
+            }
+        });
+    }
+});
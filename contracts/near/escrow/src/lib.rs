@@ -0,0 +1,85 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{env, near_bindgen, AccountId, NearToken, PanicOnDefault, Promise};
+
+/// Per-order escrow deployed by FusionPlusNear on `{hash8}.<factory>`
+/// Holds one order's NEAR; only the factory can move it
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct FusionPlusEscrow {
+    /// FusionPlusNear contract that created this escrow
+    pub factory: AccountId,
+    /// Order whose funds this escrow holds
+    pub order_hash: String,
+}
+
+#[near_bindgen]
+impl FusionPlusEscrow {
+    #[init]
+    pub fn new(order_hash: String) -> Self {
+        assert!(!env::state_exists(), "Already initialized");
+        Self {
+            factory: env::predecessor_account_id(),
+            order_hash,
+        }
+    }
+
+    /// Pay `amount` of the escrowed NEAR to `receiver`
+    pub fn release(&mut self, receiver: AccountId, amount: U128) -> Promise {
+        self.assert_factory();
+        Promise::new(receiver).transfer(NearToken::from_yoctonear(amount.0))
+    }
+
+    /// Delete the escrow account, sending what is left (storage stake) to `beneficiary`
+    pub fn close(&mut self, beneficiary: AccountId) -> Promise {
+        self.assert_factory();
+        Promise::new(env::current_account_id()).delete_account(beneficiary)
+    }
+
+    pub fn get_order_hash(&self) -> String {
+        self.order_hash.clone()
+    }
+
+    fn assert_factory(&self) {
+        assert_eq!(env::predecessor_account_id(), self.factory, "Only factory");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id("1a2b3c4d.escrow.testnet".parse().unwrap())
+            .predecessor_account_id(predecessor_account_id);
+        builder
+    }
+
+    #[test]
+    fn test_factory_releases_funds() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut escrow = FusionPlusEscrow::new("0x1a2b3c4d".to_string());
+        assert_eq!(escrow.factory, accounts(0));
+
+        let _ = escrow.release(accounts(3), U128(1_000));
+        let receivers: Vec<AccountId> = near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .collect();
+        assert_eq!(receivers, vec![accounts(3)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only factory")]
+    fn test_release_only_by_factory() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut escrow = FusionPlusEscrow::new("0x1a2b3c4d".to_string());
+
+        testing_env!(get_context(accounts(3)).build());
+        let _ = escrow.release(accounts(3), U128(1_000));
+    }
+}
@@ -0,0 +1,96 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::events::{FusionEvent, SolverAttestationEvent};
+use crate::hashlock::normalize_hex32;
+use crate::{FusionPlusNear, FusionPlusNearExt};
+
+/// TEE attestation of a solver account, verified off-chain before registration
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SolverAttestation {
+    /// SHA-256 of the attestation report (TDX quote)
+    pub report_hash: String,
+    /// Code hash of the attested solver image
+    pub code_measurement: String,
+    /// Unix timestamp (seconds) of the registration
+    pub registered_at: u64,
+    pub revoked: bool,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Register or replace the TEE attestation of `solver` (owner only)
+    /// `report_hash` and `code_measurement` are 32-byte hex values of a
+    /// quote the owner has verified off-chain, e.g. by the TEE solver's
+    /// `AttestationVerifier`
+    #[payable]
+    pub fn register_solver_attestation(&mut self, solver: AccountId, report_hash: String, code_measurement: String) {
+        self.assert_owner();
+        let report_hash = normalize_hex32(&report_hash).expect("Invalid report hash");
+        let code_measurement = normalize_hex32(&code_measurement).expect("Invalid code measurement");
+        self.solver_attestations.insert(
+            &solver,
+            &SolverAttestation {
+                report_hash,
+                code_measurement: code_measurement.clone(),
+                registered_at: Self::now_seconds(),
+                revoked: false,
+            },
+        );
+        FusionEvent::SolverAttestationRegistered(vec![SolverAttestationEvent {
+            solver,
+            code_measurement,
+        }])
+        .emit();
+    }
+
+    /// Revoke the attestation of `solver` (owner only), e.g. after a TCB
+    /// advisory; the record stays queryable
+    #[payable]
+    pub fn revoke_solver_attestation(&mut self, solver: AccountId) {
+        self.assert_owner();
+        let mut attestation = self.solver_attestations.get(&solver).expect("No attestation registered");
+        assert!(!attestation.revoked, "Attestation already revoked");
+        attestation.revoked = true;
+        self.solver_attestations.insert(&solver, &attestation);
+        FusionEvent::SolverAttestationRevoked(vec![SolverAttestationEvent {
+            solver,
+            code_measurement: attestation.code_measurement,
+        }])
+        .emit();
+    }
+
+    /// Require resolvers executing or matching orders to hold a valid attestation
+    #[payable]
+    pub fn set_require_attestation(&mut self, require_attestation: bool) {
+        self.assert_owner();
+        self.require_attestation = require_attestation;
+    }
+
+    pub fn get_require_attestation(&self) -> bool {
+        self.require_attestation
+    }
+
+    pub fn get_solver_attestation(&self, solver: AccountId) -> Option<SolverAttestation> {
+        self.solver_attestations.get(&solver)
+    }
+}
+
+impl FusionPlusNear {
+    /// Panic if attestations are required and `resolver` has none or a revoked one
+    pub(crate) fn assert_attested_resolver(&self, resolver: &AccountId) {
+        assert!(self.is_attested_resolver(resolver), "Resolver has no valid TEE attestation");
+    }
+
+    /// Whether `resolver` meets the attestation requirement, always true when none is required
+    pub(crate) fn is_attested_resolver(&self, resolver: &AccountId) -> bool {
+        !self.require_attestation
+            || self
+                .solver_attestations
+                .get(resolver)
+                .is_some_and(|attestation| !attestation.revoked)
+    }
+}
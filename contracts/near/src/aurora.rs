@@ -0,0 +1,49 @@
+use near_sdk::{near_bindgen, AccountId};
+
+use crate::{FusionPlusNear, FusionPlusNearExt};
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Set the Aurora engine (e.g. `aurora`), `None` disables Aurora addressing
+    /// EVM contracts reach this contract through Aurora's cross-contract call
+    /// (XCC) precompile, which calls from a per-address router sub-account of the engine
+    #[payable]
+    pub fn set_aurora_engine(&mut self, aurora_engine: Option<AccountId>) {
+        self.assert_owner();
+        self.aurora_engine = aurora_engine;
+    }
+
+    pub fn get_aurora_engine(&self) -> Option<AccountId> {
+        self.aurora_engine.clone()
+    }
+
+    /// NEAR account an Aurora EVM address calls from via XCC
+    /// (`<address hex>.<engine>`), to use as maker, resolver, operator,
+    /// claim prover or payout beneficiary. `None` without an engine or for a
+    /// malformed address
+    pub fn get_aurora_xcc_account(&self, evm_address: String) -> Option<AccountId> {
+        let engine = self.aurora_engine.as_ref()?;
+        let digits = normalize_evm_address(&evm_address)?;
+        format!("{}.{}", digits, engine).parse().ok()
+    }
+
+    /// EVM address behind an XCC router account of the Aurora engine, if any
+    pub fn get_aurora_evm_address(&self, account_id: AccountId) -> Option<String> {
+        let engine = self.aurora_engine.as_ref()?;
+        let digits = account_id.as_str().strip_suffix(&format!(".{}", engine))?;
+        normalize_evm_address(digits).map(|digits| format!("0x{}", digits))
+    }
+}
+
+/// Lowercase 20-byte hex address without `0x`, `None` if malformed
+pub(crate) fn normalize_evm_address(address: &str) -> Option<String> {
+    let digits = address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+        .unwrap_or(address);
+    if digits.len() == 40 && digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        Some(digits.to_ascii_lowercase())
+    } else {
+        None
+    }
+}
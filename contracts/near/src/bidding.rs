@@ -0,0 +1,82 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::events::{FusionEvent, HtlcBidPlacedEvent};
+use crate::htlc::HTLCOrder;
+use crate::{FusionPlusNear, FusionPlusNearExt};
+
+/// Resolver bid on an HTLC order taking bids
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Bid {
+    #[schemars(with = "String")]
+    pub resolver: AccountId,
+    /// Resolver fee the bidder accepts
+    #[schemars(with = "String")]
+    pub fee: U128,
+    /// Safety deposit attached to the bid, refunded when outbid
+    #[schemars(with = "String")]
+    pub safety_deposit: U128,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Bid `fee` (at most the order's resolver fee) for an HTLC order during
+    /// its bidding window, attaching the safety deposit
+    /// Only the lowest bid is kept; the bid it replaces gets its deposit back
+    #[payable]
+    pub fn place_bid(&mut self, order_id: String, fee: U128) -> Bid {
+        assert!(!self.paused, "Contract is paused");
+        let resolver = env::predecessor_account_id();
+        self.assert_bonded_resolver(&resolver, "Not an authorized resolver");
+
+        let mut order = self.htlc_orders.get(&order_id).expect("Order not found");
+        let bidding_ends_at = order.bidding_ends_at.expect("Order does not take bids");
+        assert!(Self::now_seconds() < bidding_ends_at, "Bidding window closed");
+        assert!(!Self::is_expired(&order), "Order expired");
+        assert!(fee.0 <= order.resolver_fee.0, "Bid above resolver fee");
+        if let Some(best_bid) = &order.best_bid {
+            assert!(fee.0 < best_bid.fee.0, "Bid must be lower than the best bid");
+        }
+
+        let safety_deposit = env::attached_deposit().as_yoctonear();
+        self.assert_htlc_safety_deposit(&order, safety_deposit);
+
+        let bid = Bid {
+            resolver: resolver.clone(),
+            fee,
+            safety_deposit: U128(safety_deposit),
+        };
+        let outbid = order.best_bid.replace(bid.clone());
+        self.htlc_orders.insert(&order_id, &order);
+        self.liabilities.htlc_deposits += safety_deposit;
+
+        FusionEvent::HtlcBidPlaced(vec![HtlcBidPlacedEvent {
+            order_id: order_id.clone(),
+            resolver,
+            fee,
+        }])
+        .emit();
+
+        if let Some(outbid) = outbid {
+            self.liabilities.htlc_deposits = self.liabilities.htlc_deposits.saturating_sub(outbid.safety_deposit.0);
+            let _ = Self::internal_htlc_payout(&order_id, outbid.resolver, outbid.safety_deposit.0);
+        }
+        bid
+    }
+
+    /// Match an HTLC order to its lowest bidder once the bidding window closed
+    /// Callable by anyone; the maker gets back the fee above the winning bid
+    pub fn close_bidding(&mut self, order_id: String) -> HTLCOrder {
+        let mut order = self.htlc_orders.get(&order_id).expect("Order not found");
+        let bidding_ends_at = order.bidding_ends_at.expect("Order does not take bids");
+        assert!(Self::now_seconds() >= bidding_ends_at, "Bidding window still open");
+        assert!(!order.is_claimed && !order.is_refunded, "Order already settled");
+        let bid = order.best_bid.take().expect("No bids");
+
+        self.internal_match_htlc_order(&order_id, order, bid.resolver, bid.safety_deposit.0, bid.fee.0)
+    }
+}
@@ -0,0 +1,183 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, AccountId, Gas, NearToken, Promise, PromiseError};
+use schemars::JsonSchema;
+
+use crate::events::{FusionEvent, ResolverBondEvent};
+use crate::{FusionPlusNear, FusionPlusNearExt};
+
+/// Bond a resolver must hold to execute or match orders, unless changed by the owner
+pub const DEFAULT_MIN_RESOLVER_BOND: u128 = NearToken::from_near(5).as_yoctonear();
+/// Seconds between `start_unbonding` and `withdraw_bond`, unless changed by the owner
+pub const DEFAULT_UNBONDING_DELAY: u64 = 7 * 24 * 60 * 60;
+/// Gas reserved for the bond withdrawal callback
+pub const GAS_FOR_ON_BOND_WITHDRAWN: Gas = Gas::from_tgas(10);
+
+/// Per-resolver bond record
+#[derive(BorshDeserialize, BorshSerialize, Clone, Default)]
+pub struct ResolverBond {
+    /// Active bond counted towards authorization
+    pub amount: u128,
+    /// Bond waiting for the unbonding delay to pass
+    pub unbonding: u128,
+    /// Timestamp (seconds) from which `unbonding` can be withdrawn
+    pub unbonding_at: u64,
+}
+
+/// Resolver bond as returned by views
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ResolverBondView {
+    #[schemars(with = "String")]
+    pub amount: U128,
+    #[schemars(with = "String")]
+    pub unbonding: U128,
+    pub unbonding_at: u64,
+}
+
+/// Bond requirements set by the owner
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BondConfig {
+    #[schemars(with = "String")]
+    pub min_resolver_bond: U128,
+    pub unbonding_delay: u64,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Add the attached NEAR to the caller's resolver bond
+    #[payable]
+    pub fn bond_resolver(&mut self) -> ResolverBondView {
+        let amount = env::attached_deposit().as_yoctonear();
+        assert!(amount > 0, "Bond amount must be positive");
+        let resolver = env::predecessor_account_id();
+
+        let mut bond = self.resolver_bonds.get(&resolver).unwrap_or_default();
+        bond.amount += amount;
+        self.resolver_bonds.insert(&resolver, &bond);
+        self.liabilities.resolver_bonds += amount;
+
+        FusionEvent::ResolverBonded(vec![ResolverBondEvent {
+            resolver,
+            amount: U128(amount),
+            total: U128(bond.amount),
+        }])
+        .emit();
+        Self::bond_view(&bond)
+    }
+
+    /// Move `amount` of the caller's bond into unbonding
+    /// The whole unbonding balance becomes withdrawable after the unbonding delay
+    /// Requires 1 yoctoNEAR, like every method moving a resolver's bond out
+    #[payable]
+    pub fn start_unbonding(&mut self, amount: U128) -> ResolverBondView {
+        assert_one_yocto();
+        let resolver = env::predecessor_account_id();
+        let mut bond = self.resolver_bonds.get(&resolver).expect("No resolver bond");
+        assert!(amount.0 > 0 && amount.0 <= bond.amount, "Unbonding amount exceeds bond");
+
+        bond.amount -= amount.0;
+        bond.unbonding += amount.0;
+        bond.unbonding_at = Self::now_seconds() + self.unbonding_delay;
+        self.resolver_bonds.insert(&resolver, &bond);
+
+        FusionEvent::ResolverUnbonding(vec![ResolverBondEvent {
+            resolver,
+            amount,
+            total: U128(bond.amount),
+        }])
+        .emit();
+        Self::bond_view(&bond)
+    }
+
+    /// Withdraw the caller's unbonded balance once the delay has passed (attach 1 yoctoNEAR)
+    #[payable]
+    pub fn withdraw_bond(&mut self) -> Promise {
+        assert_one_yocto();
+        let resolver = env::predecessor_account_id();
+        let mut bond = self.resolver_bonds.get(&resolver).expect("No resolver bond");
+        assert!(bond.unbonding > 0, "Nothing to withdraw");
+        assert!(Self::now_seconds() >= bond.unbonding_at, "Unbonding period not over");
+
+        let amount = std::mem::take(&mut bond.unbonding);
+        if bond.amount == 0 {
+            self.resolver_bonds.remove(&resolver);
+        } else {
+            self.resolver_bonds.insert(&resolver, &bond);
+        }
+        self.liabilities.resolver_bonds = self.liabilities.resolver_bonds.saturating_sub(amount);
+
+        Promise::new(resolver.clone())
+            .transfer(NearToken::from_yoctonear(amount))
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_BOND_WITHDRAWN)
+                    .on_bond_withdrawn(resolver, U128(amount)),
+            )
+    }
+
+    /// Callback after a bond withdrawal, restores the unbonding balance if the transfer failed
+    #[private]
+    pub fn on_bond_withdrawn(
+        &mut self,
+        resolver: AccountId,
+        amount: U128,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        if result.is_ok() {
+            FusionEvent::ResolverBondWithdrawn(vec![ResolverBondEvent {
+                resolver,
+                amount,
+                total: U128(0),
+            }])
+            .emit();
+            return true;
+        }
+
+        let mut bond = self.resolver_bonds.get(&resolver).unwrap_or_default();
+        bond.unbonding += amount.0;
+        self.resolver_bonds.insert(&resolver, &bond);
+        self.liabilities.resolver_bonds += amount.0;
+        false
+    }
+
+    /// Set the minimum resolver bond and the unbonding delay (seconds)
+    #[payable]
+    pub fn set_bond_config(&mut self, min_resolver_bond: U128, unbonding_delay: u64) {
+        self.assert_owner();
+        self.min_resolver_bond = min_resolver_bond.0;
+        self.unbonding_delay = unbonding_delay;
+    }
+
+    pub fn get_bond_config(&self) -> BondConfig {
+        BondConfig {
+            min_resolver_bond: U128(self.min_resolver_bond),
+            unbonding_delay: self.unbonding_delay,
+        }
+    }
+
+    pub fn get_resolver_bond(&self, account_id: AccountId) -> Option<ResolverBondView> {
+        self.resolver_bonds.get(&account_id).map(|bond| Self::bond_view(&bond))
+    }
+}
+
+impl FusionPlusNear {
+    /// Panic unless `resolver` is allowlisted, holds at least the minimum bond
+    /// and, when required, a valid TEE attestation
+    pub(crate) fn assert_bonded_resolver(&self, resolver: &AccountId, message: &str) {
+        assert!(self.authorized_resolvers.get(resolver).unwrap_or(false), "{}", message);
+        let bonded = self.resolver_bonds.get(resolver).map_or(0, |bond| bond.amount);
+        assert!(bonded >= self.min_resolver_bond, "Resolver bond below minimum");
+        self.assert_attested_resolver(resolver);
+    }
+
+    fn bond_view(bond: &ResolverBond) -> ResolverBondView {
+        ResolverBondView {
+            amount: U128(bond.amount),
+            unbonding: U128(bond.unbonding),
+            unbonding_at: bond.unbonding_at,
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, ext_contract, near_bindgen, AccountId, Gas, Promise, PromiseError};
+use schemars::JsonSchema;
+
+use crate::events::{FusionEvent, WithdrawalSignedEvent};
+use crate::{FusionPlusNear, FusionPlusNearExt};
+
+/// Gas attached to the MPC signer's `sign` call
+pub const GAS_FOR_MPC_SIGN: Gas = Gas::from_tgas(250);
+/// Gas reserved for the signature callback
+pub const GAS_FOR_ON_SIGNATURE: Gas = Gas::from_tgas(10);
+/// MPC root key version used for derivation
+pub const MPC_KEY_VERSION: u32 = 0;
+
+/// `withdraw(bytes32)` selector of the Ethereum escrow
+const WITHDRAW_SELECTOR: [u8; 4] = [0x8e, 0x19, 0x89, 0x9e];
+/// EIP-2718 type of EIP-1559 transactions
+const EIP1559_TX_TYPE: u8 = 0x02;
+
+/// Request accepted by the NEAR Chain Signatures MPC contract
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignRequest {
+    pub payload: [u8; 32],
+    pub path: String,
+    pub key_version: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SerializableAffinePoint {
+    pub affine_point: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SerializableScalar {
+    pub scalar: String,
+}
+
+/// secp256k1 signature returned by the MPC contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignatureResponse {
+    pub big_r: SerializableAffinePoint,
+    pub s: SerializableScalar,
+    pub recovery_id: u8,
+}
+
+/// NEAR Chain Signatures MPC interface
+#[ext_contract(ext_mpc_signer)]
+pub trait MpcSigner {
+    fn sign(&mut self, request: SignRequest) -> SignatureResponse;
+}
+
+/// Unsigned EIP-1559 transaction calling `withdraw(secret)` on an Ethereum escrow
+/// Nonce and fees come from the caller; the calldata is built from the revealed preimage
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EthWithdrawalTx {
+    pub chain_id: u64,
+    #[schemars(with = "String")]
+    pub nonce: U64,
+    #[schemars(with = "String")]
+    pub max_priority_fee_per_gas: U128,
+    #[schemars(with = "String")]
+    pub max_fee_per_gas: U128,
+    #[schemars(with = "String")]
+    pub gas_limit: U64,
+    /// 20-byte hex address of the escrow holding the source-chain funds
+    pub escrow: String,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Set the Chain Signatures MPC contract (e.g. `v1.signer-prod.testnet`), `None` disables signing
+    #[payable]
+    pub fn set_mpc_signer(&mut self, mpc_signer: Option<AccountId>) {
+        self.assert_owner();
+        self.mpc_signer = mpc_signer;
+    }
+
+    pub fn get_mpc_signer(&self) -> Option<AccountId> {
+        self.mpc_signer.clone()
+    }
+
+    /// Ask the MPC network to sign the Ethereum transaction withdrawing the
+    /// escrow with the preimage revealed on NEAR
+    /// Only the order's resolver can request it, keys are derived from path `resolver`
+    /// The attached deposit pays the MPC signature fee
+    #[payable]
+    pub fn request_withdrawal_signature(&mut self, order_hash: String, tx: EthWithdrawalTx) -> Promise {
+        let mpc_signer = self.mpc_signer.clone().expect("Chain signatures not configured");
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let order = self.orders.get(&order_hash).expect("Order not found");
+        assert_eq!(
+            env::predecessor_account_id(),
+            order.resolver,
+            "Only resolver can request withdrawal signature"
+        );
+        let metadata = self.order_metadata.get(&order_hash).expect("Order not found");
+        let secret = metadata.preimage.expect("Preimage not revealed");
+        let payload = withdrawal_payload(&tx, &secret);
+
+        ext_mpc_signer::ext(mpc_signer)
+            .with_attached_deposit(env::attached_deposit())
+            .with_static_gas(GAS_FOR_MPC_SIGN)
+            .sign(SignRequest {
+                payload,
+                path: order.resolver.to_string(),
+                key_version: MPC_KEY_VERSION,
+            })
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_SIGNATURE)
+                    .on_withdrawal_signature(order_hash, hex::encode(payload)),
+            )
+    }
+
+    /// Callback with the MPC signature, published as an event for relayers
+    #[private]
+    pub fn on_withdrawal_signature(
+        &mut self,
+        order_hash: String,
+        payload: String,
+        #[callback_result] result: Result<SignatureResponse, PromiseError>,
+    ) -> Option<SignatureResponse> {
+        let signature = result.ok()?;
+        FusionEvent::WithdrawalSigned(vec![WithdrawalSignedEvent {
+            order_hash,
+            payload,
+            big_r: signature.big_r.affine_point.clone(),
+            s: signature.s.scalar.clone(),
+            recovery_id: signature.recovery_id,
+        }])
+        .emit();
+        Some(signature)
+    }
+}
+
+/// Signing hash of the EIP-1559 transaction `escrow.withdraw(secret)`:
+/// `keccak256(0x02 || rlp([chain_id, nonce, max_priority_fee, max_fee, gas_limit, to, 0, data, []]))`
+pub fn withdrawal_payload(tx: &EthWithdrawalTx, secret: &[u8]) -> [u8; 32] {
+    let escrow = tx
+        .escrow
+        .strip_prefix("0x")
+        .and_then(|digits| hex::decode(digits).ok())
+        .filter(|address| address.len() == 20)
+        .expect("Invalid escrow address");
+    let data = [&WITHDRAW_SELECTOR[..], secret].concat();
+
+    let fields = [
+        rlp_uint(tx.chain_id as u128),
+        rlp_uint(tx.nonce.0 as u128),
+        rlp_uint(tx.max_priority_fee_per_gas.0),
+        rlp_uint(tx.max_fee_per_gas.0),
+        rlp_uint(tx.gas_limit.0 as u128),
+        rlp_bytes(&escrow),
+        rlp_uint(0),
+        rlp_bytes(&data),
+        rlp_list(&[]),
+    ];
+    let mut unsigned = vec![EIP1559_TX_TYPE];
+    unsigned.extend(rlp_list(&fields));
+    env::keccak256_array(&unsigned[..])
+}
+
+fn rlp_length_prefix(len: usize, short_offset: u8) -> Vec<u8> {
+    if len < 56 {
+        return vec![short_offset + len as u8];
+    }
+    let len_bytes: Vec<u8> = (len as u64).to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+    let mut prefix = vec![short_offset + 55 + len_bytes.len() as u8];
+    prefix.extend(len_bytes);
+    prefix
+}
+
+fn rlp_bytes(bytes: &[u8]) -> Vec<u8> {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        return bytes.to_vec();
+    }
+    let mut encoded = rlp_length_prefix(bytes.len(), 0x80);
+    encoded.extend_from_slice(bytes);
+    encoded
+}
+
+fn rlp_uint(value: u128) -> Vec<u8> {
+    let bytes: Vec<u8> = value.to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+    rlp_bytes(&bytes)
+}
+
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload = items.concat();
+    let mut encoded = rlp_length_prefix(payload.len(), 0xc0);
+    encoded.extend(payload);
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rlp_encoding() {
+        assert_eq!(rlp_bytes(b"dog"), vec![0x83, b'd', b'o', b'g']);
+        assert_eq!(rlp_uint(0), vec![0x80]);
+        assert_eq!(rlp_uint(15), vec![0x0f]);
+        assert_eq!(rlp_uint(1024), vec![0x82, 0x04, 0x00]);
+        assert_eq!(
+            rlp_list(&[rlp_bytes(b"cat"), rlp_bytes(b"dog")]),
+            hex::decode("c88363617483646f67").unwrap()
+        );
+        assert_eq!(rlp_list(&[]), vec![0xc0]);
+
+        let long = [0x61u8; 56];
+        assert_eq!(rlp_bytes(&long)[..2], [0xb8, 56]);
+    }
+
+    #[test]
+    fn test_withdraw_selector() {
+        assert_eq!(env::keccak256(b"withdraw(bytes32)")[..4], WITHDRAW_SELECTOR);
+    }
+}
@@ -0,0 +1,36 @@
+use near_sdk::near_bindgen;
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use crate::{FusionPlusNear, FusionPlusNearExt};
+
+pub use fusion_core::{ChainFamily, KNOWN_CHAINS};
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ChainInfo {
+    pub chain_id: u64,
+    pub name: String,
+    pub family: ChainFamily,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Name and family of a known chain ID, `None` for unregistered IDs
+    pub fn get_chain(&self, chain_id: u64) -> Option<ChainInfo> {
+        KNOWN_CHAINS.iter().find(|(id, _, _)| *id == chain_id).map(chain_info)
+    }
+
+    /// All known chains, in registry order
+    pub fn get_chains(&self) -> Vec<ChainInfo> {
+        KNOWN_CHAINS.iter().map(chain_info).collect()
+    }
+}
+
+fn chain_info(&(chain_id, name, family): &(u64, &str, ChainFamily)) -> ChainInfo {
+    ChainInfo {
+        chain_id,
+        name: name.to_string(),
+        family,
+    }
+}
@@ -0,0 +1,211 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, AccountId, Promise};
+use schemars::JsonSchema;
+
+use crate::events::{ChallengeResolvedEvent, DisputeFlaggedEvent, FusionEvent, SettlementChallengedEvent};
+use crate::hashlock::normalize_hex32;
+use crate::{FusionPlusNear, FusionPlusNearExt, OrderState};
+
+/// Seconds a claimed order's safety deposit is held for challenges, unless changed by the owner
+pub const DEFAULT_CHALLENGE_WINDOW: u64 = 10 * 60;
+/// Longest evidence URI kept with a dispute (bytes)
+pub const MAX_EVIDENCE_URI_LEN: usize = 256;
+
+/// Safety deposit of a claimed order waiting out its challenge window
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HeldDeposit {
+    #[schemars(with = "String")]
+    pub resolver: AccountId,
+    #[schemars(with = "String")]
+    pub amount: U128,
+    /// Unix timestamp (seconds) from which an unchallenged deposit can be released
+    pub release_at: u64,
+    /// Maker, resolver or guardian that flagged the settlement, which freezes the deposit
+    #[schemars(with = "Option<String>")]
+    pub challenged_by: Option<AccountId>,
+    /// Evidence given with `flag_dispute`
+    pub evidence_uri: Option<String>,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Flag the settlement of a claimed order as invalid (maker or guardian)
+    /// while its safety deposit is held, freezing the deposit until the owner
+    /// resolves the challenge
+    pub fn challenge_settlement(&mut self, order_hash: String) {
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let order = self.orders.get(&order_hash).expect("Order not found");
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == order.maker || self.guardian.as_ref() == Some(&caller),
+            "Only maker or guardian can challenge"
+        );
+        self.internal_challenge(&order_hash, caller.clone(), None);
+        FusionEvent::SettlementChallenged(vec![SettlementChallengedEvent {
+            order_hash,
+            challenged_by: caller,
+        }])
+        .emit();
+    }
+
+    /// Dispute the settlement of a claimed order (maker or resolver) while its
+    /// safety deposit is held, pointing to off-chain evidence, e.g. an IPFS
+    /// document. Freezes the deposit until the arbitrator or owner resolves it
+    pub fn flag_dispute(&mut self, order_hash: String, evidence_uri: String) {
+        assert!(
+            !evidence_uri.is_empty() && evidence_uri.len() <= MAX_EVIDENCE_URI_LEN,
+            "Invalid evidence URI"
+        );
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let order = self.orders.get(&order_hash).expect("Order not found");
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == order.maker || caller == order.resolver,
+            "Only maker or resolver can flag a dispute"
+        );
+        self.internal_challenge(&order_hash, caller.clone(), Some(evidence_uri.clone()));
+        FusionEvent::DisputeFlagged(vec![DisputeFlaggedEvent {
+            order_hash,
+            flagged_by: caller,
+            evidence_uri,
+        }])
+        .emit();
+    }
+
+    /// Pay an unchallenged safety deposit to the resolver once its challenge
+    /// window has passed. Callable by anyone
+    pub fn release_safety_deposit(&mut self, order_hash: String) -> Promise {
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let held = self.held_deposits.get(&order_hash).expect("No held safety deposit");
+        assert!(held.challenged_by.is_none(), "Settlement challenged");
+        assert!(Self::now_seconds() >= held.release_at, "Challenge window not over");
+
+        let order = self.orders.get(&order_hash).expect("Order not found");
+        self.internal_remove_held_deposit(&order_hash, &order, held.amount.0);
+        self.internal_pay_held_deposit(&order_hash, &order, held.resolver, held.amount.0)
+    }
+
+    /// Settle a challenged or disputed safety deposit (arbitrator or owner):
+    /// to the resolver if the settlement was valid, otherwise to the maker
+    /// (or its payout beneficiary)
+    #[payable]
+    pub fn resolve_challenge(&mut self, order_hash: String, valid_settlement: bool) -> Promise {
+        assert_one_yocto();
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == self.owner || self.arbitrator.as_ref() == Some(&caller),
+            "Only owner or arbitrator"
+        );
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let held = self.held_deposits.get(&order_hash).expect("No held safety deposit");
+        assert!(held.challenged_by.is_some(), "Settlement not challenged");
+
+        let order = self.orders.get(&order_hash).expect("Order not found");
+        let metadata = self.order_metadata.get(&order_hash).expect("Order not found");
+        let receiver = if valid_settlement {
+            held.resolver
+        } else {
+            Self::maker_beneficiary(&order, &metadata)
+        };
+        self.internal_remove_held_deposit(&order_hash, &order, held.amount.0);
+        FusionEvent::ChallengeResolved(vec![ChallengeResolvedEvent {
+            order_hash: order_hash.clone(),
+            valid_settlement,
+            receiver: receiver.clone(),
+            amount: held.amount,
+        }])
+        .emit();
+        self.internal_pay_held_deposit(&order_hash, &order, receiver, held.amount.0)
+    }
+
+    /// Set how long (seconds) safety deposits are held after a claim, 0 releases them immediately
+    #[payable]
+    pub fn set_challenge_window(&mut self, challenge_window: u64) {
+        self.assert_owner();
+        self.challenge_window = challenge_window;
+    }
+
+    pub fn get_challenge_window(&self) -> u64 {
+        self.challenge_window
+    }
+
+    /// Set or clear the account that resolves challenges and disputes alongside the owner
+    #[payable]
+    pub fn set_arbitrator(&mut self, arbitrator: Option<AccountId>) {
+        self.assert_owner();
+        self.arbitrator = arbitrator;
+    }
+
+    pub fn get_arbitrator(&self) -> Option<AccountId> {
+        self.arbitrator.clone()
+    }
+
+    /// Held safety deposit of a claimed order, `None` for unknown or malformed hashes
+    pub fn get_held_deposit(&self, order_hash: String) -> Option<HeldDeposit> {
+        normalize_hex32(&order_hash).and_then(|digits| self.held_deposits.get(&format!("0x{}", digits)))
+    }
+}
+
+impl FusionPlusNear {
+    /// Hold `amount` of a just claimed order's safety deposit for the
+    /// challenge window. Returns the amount held, 0 when the window is disabled
+    /// A token safety deposit is held too, even when no NEAR is
+    pub(crate) fn internal_hold_safety_deposit(&mut self, order_hash: &String, order: &OrderState, amount: u128) -> u128 {
+        let holds_token = self.has_token_safety_deposit(&order.resolver, order_hash);
+        if self.challenge_window == 0 || (amount == 0 && !holds_token) {
+            return 0;
+        }
+        self.held_deposits.insert(
+            order_hash,
+            &HeldDeposit {
+                resolver: order.resolver.clone(),
+                amount: U128(amount),
+                release_at: Self::now_seconds() + self.challenge_window,
+                challenged_by: None,
+                evidence_uri: None,
+            },
+        );
+        if Self::pays_native(order) {
+            self.liabilities.held_deposits += amount;
+        }
+        amount
+    }
+
+    /// Freeze a held deposit within its challenge window
+    fn internal_challenge(&mut self, order_hash: &String, caller: AccountId, evidence_uri: Option<String>) {
+        let mut held = self.held_deposits.get(order_hash).expect("No held safety deposit");
+        assert!(held.challenged_by.is_none(), "Settlement already challenged");
+        assert!(Self::now_seconds() < held.release_at, "Challenge window closed");
+
+        held.challenged_by = Some(caller);
+        held.evidence_uri = evidence_uri;
+        self.held_deposits.insert(order_hash, &held);
+    }
+
+    /// Pay a released held deposit, with the rest of any token safety deposit
+    fn internal_pay_held_deposit(
+        &mut self,
+        order_hash: &str,
+        order: &OrderState,
+        receiver: AccountId,
+        amount: u128,
+    ) -> Promise {
+        let token_payouts = self.internal_settle_token_deposit(order_hash, order, None, 0, Some(receiver.clone()));
+        let payout = (amount > 0).then(|| self.internal_payout(order_hash, order, receiver, amount));
+        payout
+            .into_iter()
+            .chain(token_payouts)
+            .reduce(|all, next| all.and(next))
+            .expect("No held safety deposit")
+    }
+
+    fn internal_remove_held_deposit(&mut self, order_hash: &String, order: &OrderState, amount: u128) {
+        self.held_deposits.remove(order_hash);
+        if Self::pays_native(order) {
+            self.liabilities.held_deposits = self.liabilities.held_deposits.saturating_sub(amount);
+        }
+    }
+}
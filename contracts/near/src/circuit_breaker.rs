@@ -0,0 +1,58 @@
+use near_sdk::json_types::U128;
+use near_sdk::near_bindgen;
+
+use crate::events::{CircuitBreakerTrippedEvent, FusionEvent};
+use crate::{FusionPlusNear, FusionPlusNearExt};
+
+/// Consecutive failed payouts that pause the contract
+pub const PAYOUT_FAILURE_LIMIT: u32 = 5;
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Pause the contract if it owes more NEAR than it holds, e.g. from a
+    /// keeper polling after every block. Callable by anyone, returns
+    /// whether the contract is solvent
+    pub fn check_solvency(&mut self) -> bool {
+        let solvency = self.get_solvency();
+        if !solvency.solvent {
+            self.internal_trip_circuit_breaker("insolvent", solvency.deficit);
+        }
+        solvency.solvent
+    }
+
+    /// Payouts that failed in a row since the last success or trip
+    pub fn get_consecutive_payout_failures(&self) -> u32 {
+        self.consecutive_payout_failures
+    }
+}
+
+impl FusionPlusNear {
+    /// Track a payout outcome, pausing after `PAYOUT_FAILURE_LIMIT` failures in a row
+    /// Solvency is not checked here: the refund of a failed transfer may
+    /// arrive after this callback
+    pub(crate) fn internal_record_payout_outcome(&mut self, succeeded: bool) {
+        if succeeded {
+            self.consecutive_payout_failures = 0;
+            return;
+        }
+        self.consecutive_payout_failures += 1;
+        if self.consecutive_payout_failures >= PAYOUT_FAILURE_LIMIT {
+            self.internal_trip_circuit_breaker("payout_failures", U128(0));
+        }
+    }
+
+    /// Pause new orders and alert monitoring; only the owner can unpause
+    fn internal_trip_circuit_breaker(&mut self, reason: &str, deficit: U128) {
+        self.consecutive_payout_failures = 0;
+        if self.paused {
+            return;
+        }
+        self.paused = true;
+        self.paused_at = Self::now_seconds();
+        FusionEvent::CircuitBreakerTripped(vec![CircuitBreakerTrippedEvent {
+            reason: reason.to_string(),
+            deficit,
+        }])
+        .emit();
+    }
+}
@@ -0,0 +1,116 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{env, near_bindgen, AccountId, Gas, GasWeight, Promise, PromiseError, YieldId};
+
+use crate::events::{FusionEvent, FusionClaimRequestedEvent};
+use crate::{FusionPlusNear, FusionPlusNearExt, OrderStatus};
+
+/// Gas reserved for `on_claim_proof`, which settles the claim and schedules its payouts
+pub const GAS_FOR_ON_CLAIM_PROOF: Gas = Gas::from_tgas(50);
+
+/// Claim suspended until the claim prover submits the preimage revealed on Ethereum
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct PendingClaim {
+    pub yield_id: YieldId,
+    /// Account that requested the claim, authorized as if it claimed itself
+    pub requested_by: AccountId,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Set the prover (e.g. a TEE-backed relayer) confirming Ethereum-side
+    /// secret reveals, `None` disables proof claims
+    #[payable]
+    pub fn set_claim_prover(&mut self, claim_prover: Option<AccountId>) {
+        self.assert_owner();
+        self.claim_prover = claim_prover;
+    }
+
+    pub fn get_claim_prover(&self) -> Option<AccountId> {
+        self.claim_prover.clone()
+    }
+
+    /// Start a claim without knowing the preimage
+    /// The call yields until the claim prover submits the secret revealed on
+    /// Ethereum with `submit_claim_proof`, then settles the order exactly as
+    /// `claim_fusion_order` from the requester would. If no proof arrives
+    /// before the yield times out (about 200 blocks) the claim can be requested again.
+    pub fn request_claim(&mut self, order_hash: String) -> Promise {
+        assert!(self.claim_prover.is_some(), "Claim prover not configured");
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let order = self.orders.get(&order_hash).expect("Order not found");
+        assert_eq!(order.status, OrderStatus::Matched, "Order not claimable");
+        assert_eq!(order.parts, 0, "Use claim_partial_fill for multi-part orders");
+        assert!(self.pending_claims.get(&order_hash).is_none(), "Claim already requested");
+
+        let requested_by = env::predecessor_account_id();
+        assert!(
+            self.is_resolver_or_operator(&order, &requested_by) || requested_by == order.maker,
+            "Only resolver or maker can request a claim"
+        );
+        Self::assert_claim_window(&order, true);
+
+        let (promise, yield_id) = Promise::new_yield(
+            "on_claim_proof",
+            serde_json::json!({ "order_hash": order_hash }).to_string().into_bytes(),
+            GAS_FOR_ON_CLAIM_PROOF,
+            GasWeight(0),
+        );
+        self.pending_claims.insert(
+            &order_hash,
+            &PendingClaim {
+                yield_id,
+                requested_by: requested_by.clone(),
+            },
+        );
+        FusionEvent::FusionClaimRequested(vec![FusionClaimRequestedEvent {
+            order_hash,
+            requested_by,
+        }])
+        .emit();
+        promise
+    }
+
+    /// Resume a requested claim with the preimage revealed on Ethereum (claim prover only)
+    pub fn submit_claim_proof(&mut self, order_hash: String, preimage: String) {
+        assert_eq!(
+            Some(env::predecessor_account_id()),
+            self.claim_prover,
+            "Only claim prover"
+        );
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let pending = self.pending_claims.get(&order_hash).expect("No pending claim");
+        assert!(
+            self.check_preimage(order_hash.clone(), preimage.clone()),
+            "Preimage doesn't match hashlock"
+        );
+        pending
+            .yield_id
+            .resume(serde_json::to_vec(&preimage).unwrap())
+            .expect("Claim request expired");
+    }
+
+    /// Yield callback of `request_claim`, receiving the prover's preimage
+    /// Returns whether the order was claimed; a timed out or stale request
+    /// is dropped so the claim can be requested again
+    #[private]
+    pub fn on_claim_proof(
+        &mut self,
+        order_hash: String,
+        #[callback_result] preimage: Result<String, PromiseError>,
+    ) -> bool {
+        let Some(pending) = self.pending_claims.remove(&order_hash) else {
+            return false;
+        };
+        let claimable = self
+            .orders
+            .get(&order_hash)
+            .is_some_and(|order| order.status == OrderStatus::Matched);
+        match preimage {
+            Ok(preimage) if claimable && self.check_preimage(order_hash.clone(), preimage.clone()) => {
+                let _ = self.internal_claim_order(order_hash, preimage, pending.requested_by);
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use near_sdk::json_types::U128;
+use near_sdk::{env, near_bindgen};
+
+use crate::events::{FusionEvent, FusionOrderCleanedUpEvent, HtlcOrderCleanedUpEvent};
+use crate::hashlock::normalize_hex32;
+use crate::{FusionPlusNear, FusionPlusNearExt, OrderMetadata, OrderState, OrderStatus};
+
+/// Seconds a finalized order is kept before it can be cleaned up, unless changed by the owner
+pub const DEFAULT_ORDER_RETENTION: u64 = 30 * 24 * 60 * 60;
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Delete Claimed or Refunded orders created at least the retention
+    /// period ago and release their storage to the resolver that paid it
+    /// Escrow sub-accounts of deleted orders are closed, also in favour of the resolver
+    /// Callable by anyone; unknown, open, recent or payout-pending orders and
+    /// orders still holding a safety deposit are skipped.
+    /// Returns the hashes of the deleted orders.
+    pub fn cleanup_orders(&mut self, order_hashes: Vec<String>) -> Vec<String> {
+        let mut events = Vec::new();
+        for order_hash in order_hashes {
+            let Some(order_hash) = normalize_hex32(&order_hash).map(|digits| format!("0x{}", digits)) else {
+                continue;
+            };
+            let (Some(state), Some(metadata)) =
+                (self.orders.get(&order_hash), self.order_metadata.get(&order_hash))
+            else {
+                continue;
+            };
+            let finalized = matches!(state.status, OrderStatus::Claimed | OrderStatus::Refunded);
+            let expired = Self::now_seconds() >= state.created_at + self.order_retention;
+            let held = self.held_deposits.get(&order_hash).is_some();
+            let payouts_pending = !metadata.failed_payouts.is_empty()
+                || self.has_failed_token_deposit_payouts(&state.resolver, &order_hash);
+            if !finalized || !expired || payouts_pending || held {
+                continue;
+            }
+
+            let initial_storage_usage = env::storage_usage();
+            self.internal_remove_order(&order_hash, &state, &metadata);
+            self.order_history.remove(&order_hash);
+            let freed_bytes = initial_storage_usage - env::storage_usage();
+            let refund = self.internal_release_storage(&state.resolver, freed_bytes);
+            self.timelock_extensions.remove(&order_hash);
+            self.resolver_reassignments.remove(&order_hash);
+            self.internal_remove_claim_swap(&order_hash, &state.maker);
+            self.internal_remove_source_proof(&order_hash);
+            self.internal_remove_token_deposit(&order_hash, &state.resolver);
+            self.internal_remove_release_key(&order_hash, &state.resolver);
+            self.internal_remove_partial_fill(&order_hash, &state.resolver);
+            if state.escrowed {
+                let _ = self.internal_close_escrow(&order_hash, state.resolver.clone());
+            }
+
+            events.push(FusionOrderCleanedUpEvent {
+                order_hash,
+                storage_payer: state.resolver,
+                freed_bytes,
+                refund: U128(refund),
+            });
+        }
+
+        let cleaned = events.iter().map(|event| event.order_hash.clone()).collect();
+        if !events.is_empty() {
+            FusionEvent::FusionOrderCleanedUp(events).emit();
+        }
+        cleaned
+    }
+
+    /// Delete claimed or cancelled HTLC orders created at least the retention
+    /// period ago and release their storage to the maker that paid it
+    /// Callable by anyone; unknown, open, recent or payout-pending orders are skipped.
+    /// Returns the ids of the deleted orders.
+    pub fn cleanup_htlc_orders(&mut self, order_ids: Vec<String>) -> Vec<String> {
+        let mut events = Vec::new();
+        for order_id in order_ids {
+            let Some(order) = self.htlc_orders.get(&order_id) else {
+                continue;
+            };
+            let settled = order.is_claimed || order.is_refunded;
+            let expired = Self::now_seconds() >= order.created_at + self.order_retention;
+            let payouts_pending = !order.failed_payouts.is_empty() || !order.failed_fee_payouts.is_empty();
+            if !settled || !expired || payouts_pending {
+                continue;
+            }
+
+            let initial_storage_usage = env::storage_usage();
+            self.htlc_orders.remove(&order_id);
+            let freed_bytes = initial_storage_usage - env::storage_usage();
+            let refund = self.internal_release_storage(&order.maker, freed_bytes);
+
+            events.push(HtlcOrderCleanedUpEvent {
+                order_id,
+                storage_payer: order.maker,
+                freed_bytes,
+                refund: U128(refund),
+            });
+        }
+
+        let cleaned = events.iter().map(|event| event.order_id.clone()).collect();
+        if !events.is_empty() {
+            FusionEvent::HtlcOrderCleanedUp(events).emit();
+        }
+        cleaned
+    }
+
+    /// Set how long (seconds) finalized orders are kept before cleanup
+    #[payable]
+    pub fn set_order_retention(&mut self, order_retention: u64) {
+        self.assert_owner();
+        self.order_retention = order_retention;
+    }
+
+    pub fn get_order_retention(&self) -> u64 {
+        self.order_retention
+    }
+}
+
+impl FusionPlusNear {
+    /// Delete an order and drop it from the enumeration, maker and status indexes
+    fn internal_remove_order(&mut self, order_hash: &String, state: &OrderState, metadata: &OrderMetadata) {
+        self.orders.remove(order_hash);
+        self.order_metadata.remove(order_hash);
+
+        // The last hash takes the removed slot
+        self.order_hashes.swap_remove(metadata.hash_index);
+        if let Some(moved_hash) = self.order_hashes.get(metadata.hash_index) {
+            let mut moved = self.order_metadata.get(&moved_hash).expect("Order not found");
+            moved.hash_index = metadata.hash_index;
+            self.order_metadata.insert(&moved_hash, &moved);
+        }
+
+        if let Some(mut order_hashes) = self.orders_by_maker.get(&state.maker) {
+            order_hashes.remove(order_hash);
+            if order_hashes.is_empty() {
+                self.orders_by_maker.remove(&state.maker);
+            } else {
+                self.orders_by_maker.insert(&state.maker, &order_hashes);
+            }
+        }
+        if let Some(mut order_hashes) = self.orders_by_status.get(&state.status) {
+            order_hashes.remove(order_hash);
+            self.orders_by_status.insert(&state.status, &order_hashes);
+        }
+    }
+}
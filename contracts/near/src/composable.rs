@@ -0,0 +1,81 @@
+use near_sdk::{env, ext_contract, near_bindgen, AccountId, Gas, Promise, PromiseError};
+
+use crate::events::{FusionEvent, OrderCreationNotifiedEvent};
+use crate::ft::FusionOrderParams;
+use crate::{FusionPlusNear, FusionPlusNearExt, FusionPlusOrder};
+
+/// Gas attached to the creating contract's `on_fusion_order_created`
+pub const GAS_FOR_ON_FUSION_ORDER_CREATED: Gas = Gas::from_tgas(20);
+/// Gas reserved for the callback resolving the notification
+pub const GAS_FOR_ON_CREATION_NOTIFIED: Gas = Gas::from_tgas(10);
+
+/// Interface of contracts (vaults, DAOs) creating orders with `execute_fusion_order_call`
+#[ext_contract(ext_fusion_order_receiver)]
+pub trait FusionOrderReceiver {
+    /// Called once `order` is created, with the `msg` the contract passed
+    fn on_fusion_order_created(&mut self, order: FusionPlusOrder, msg: String);
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Create a native NEAR Fusion+ order from another contract, which acts
+    /// as the resolver (authorized and bonded) and is called back with
+    /// `on_fusion_order_created(order, msg)` to continue its workflow, like
+    /// `ft_transfer_call`. Any surplus deposit is refunded. The order stands
+    /// even if the callback fails: the escrow can't be undone before its
+    /// cancellation stage. The contract then claims or cancels the order
+    /// through the usual methods.
+    #[payable]
+    pub fn execute_fusion_order_call(&mut self, params: FusionOrderParams, msg: String) -> Promise {
+        let resolver = env::predecessor_account_id();
+        let deposit = env::attached_deposit().as_yoctonear();
+        let order = self.internal_create_fusion_order(params, resolver.clone(), None, deposit);
+        Self::internal_refund_excess(resolver.clone(), deposit - self.internal_order_deposit(&order));
+
+        let order_hash = order.order_hash.clone();
+        let mut in_flight = self.calls_in_flight.get(&resolver).unwrap_or_default();
+        in_flight.push(order_hash.clone());
+        self.calls_in_flight.insert(&resolver, &in_flight);
+
+        ext_fusion_order_receiver::ext(resolver.clone())
+            .with_static_gas(GAS_FOR_ON_FUSION_ORDER_CREATED)
+            .on_fusion_order_created(order, msg)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_CREATION_NOTIFIED)
+                    .on_creation_notified(resolver, order_hash),
+            )
+    }
+
+    /// Callback after `on_fusion_order_created`, returns whether it succeeded
+    #[private]
+    pub fn on_creation_notified(
+        &mut self,
+        receiver_id: AccountId,
+        order_hash: String,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        let mut in_flight = self.calls_in_flight.get(&receiver_id).unwrap_or_default();
+        in_flight.retain(|hash| *hash != order_hash);
+        if in_flight.is_empty() {
+            self.calls_in_flight.remove(&receiver_id);
+        } else {
+            self.calls_in_flight.insert(&receiver_id, &in_flight);
+        }
+
+        let delivered = result.is_ok();
+        FusionEvent::OrderCreationNotified(vec![OrderCreationNotifiedEvent {
+            order_hash,
+            receiver_id,
+            delivered,
+        }])
+        .emit();
+        delivered
+    }
+
+    /// Orders created by `account_id` with `execute_fusion_order_call` whose
+    /// callback has not resolved yet
+    pub fn get_calls_in_flight(&self, account_id: AccountId) -> Vec<String> {
+        self.calls_in_flight.get(&account_id).unwrap_or_default()
+    }
+}
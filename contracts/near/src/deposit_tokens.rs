@@ -0,0 +1,280 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, NearToken, Promise, PromiseError};
+use schemars::JsonSchema;
+
+use crate::events::{FusionEvent, PayoutEvent, TokenSafetyDepositEvent};
+use crate::ft::{ext_ft, GAS_FOR_FT_TRANSFER};
+use crate::hashlock::normalize_hex32;
+use crate::payout::{Payout, GAS_FOR_ON_PAYOUT};
+use crate::{FusionPlusNear, FusionPlusNearExt, OrderState};
+
+/// Safety deposit a resolver posted in a whitelisted NEP-141 token instead of NEAR
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenSafetyDeposit {
+    #[schemars(with = "String")]
+    pub token: AccountId,
+    /// Amount not paid out yet
+    #[schemars(with = "String")]
+    pub amount: U128,
+    /// Transfers that failed, re-sent by `retry_token_deposit_payouts`
+    pub failed_payouts: Vec<Payout>,
+}
+
+/// `msg` of an `ft_transfer_call` posting the safety deposit of an order
+/// the sender is about to execute
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct SafetyDepositMessage {
+    pub safety_deposit_for: String,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Accept `token` for safety deposits (owner only), `None` stops accepting it
+    /// `collateral_bps` is the token amount required per unit of the NEAR
+    /// safety deposit, e.g. 12_000 asks 1.2 stNEAR per NEAR
+    /// Deposits already posted keep settling in the token
+    #[payable]
+    pub fn set_safety_deposit_token(&mut self, token: AccountId, collateral_bps: Option<u16>) {
+        self.assert_owner();
+        match collateral_bps {
+            Some(collateral_bps) => {
+                assert!(collateral_bps > 0, "Invalid collateral ratio");
+                self.safety_deposit_tokens.insert(&token, &collateral_bps);
+            }
+            None => {
+                self.safety_deposit_tokens.remove(&token);
+            }
+        }
+    }
+
+    /// Collateral ratio of a token accepted for safety deposits
+    pub fn get_safety_deposit_token(&self, token: AccountId) -> Option<u16> {
+        self.safety_deposit_tokens.get(&token)
+    }
+
+    /// Token safety deposit `resolver` posted for an order, `None` for unknown or malformed hashes
+    pub fn get_token_safety_deposit(&self, resolver: AccountId, order_hash: String) -> Option<TokenSafetyDeposit> {
+        normalize_hex32(&order_hash)
+            .and_then(|digits| self.token_safety_deposits.get(&(resolver, format!("0x{}", digits))))
+    }
+
+    /// Take back a token safety deposit posted for an order that was never executed
+    pub fn withdraw_token_safety_deposit(&mut self, order_hash: String) -> Promise {
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let resolver = env::predecessor_account_id();
+        assert!(self.orders.get(&order_hash).is_none(), "Order already executed");
+        let key = (resolver.clone(), order_hash.to_string());
+        let deposit = self.token_safety_deposits.get(&key).expect("No token safety deposit");
+        assert!(deposit.amount.0 > 0, "No token safety deposit");
+
+        let initial_storage_usage = env::storage_usage();
+        self.token_safety_deposits.remove(&key);
+        self.internal_release_storage(&resolver, initial_storage_usage - env::storage_usage());
+        self.internal_transfer_token_deposit(&order_hash, &resolver, deposit.token, resolver.clone(), deposit.amount.0)
+    }
+
+    /// Re-send token safety deposit transfers that failed
+    /// Callable by the resolver that posted the deposit or the order's maker
+    pub fn retry_token_deposit_payouts(&mut self, resolver: AccountId, order_hash: String) -> Promise {
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == resolver || self.orders.get(&order_hash).is_some_and(|order| order.maker == caller),
+            "Only maker or resolver can retry payouts"
+        );
+        let key = (resolver.clone(), order_hash.to_string());
+        let mut deposit = self.token_safety_deposits.get(&key).expect("No token safety deposit");
+        assert!(!deposit.failed_payouts.is_empty(), "No failed payouts");
+
+        let payouts = std::mem::take(&mut deposit.failed_payouts);
+        self.token_safety_deposits.insert(&key, &deposit);
+        payouts
+            .into_iter()
+            .map(|payout| {
+                self.internal_transfer_token_deposit(
+                    &order_hash,
+                    &resolver,
+                    deposit.token.clone(),
+                    payout.receiver,
+                    payout.amount.0,
+                )
+            })
+            .reduce(|all, next| all.and(next))
+            .unwrap()
+    }
+
+    /// Callback after each token safety deposit transfer
+    /// A failed transfer is kept on the deposit so it can be retried
+    #[private]
+    pub fn on_token_deposit_payout(
+        &mut self,
+        order_hash: String,
+        resolver: AccountId,
+        token: AccountId,
+        receiver: AccountId,
+        amount: U128,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        let event = vec![PayoutEvent {
+            order_hash: order_hash.clone(),
+            receiver: receiver.clone(),
+            amount,
+        }];
+        if result.is_ok() {
+            FusionEvent::PayoutSent(event).emit();
+            return true;
+        }
+
+        // A withdrawn deposit is gone, keep the failed transfer on a fresh record
+        let key = (resolver, order_hash);
+        let mut deposit = self.token_safety_deposits.get(&key).unwrap_or(TokenSafetyDeposit {
+            token,
+            amount: U128(0),
+            failed_payouts: Vec::new(),
+        });
+        deposit.failed_payouts.push(Payout { receiver, amount });
+        self.token_safety_deposits.insert(&key, &deposit);
+        FusionEvent::PayoutFailed(event).emit();
+        false
+    }
+}
+
+impl FusionPlusNear {
+    /// Record tokens sent with `{"safety_deposit_for": order_hash}` as the
+    /// safety deposit of an order `resolver` executes next
+    /// The deposit is charged to the resolver's storage balance; posting again tops it up
+    pub(crate) fn internal_post_token_safety_deposit(
+        &mut self,
+        order_hash: String,
+        token: AccountId,
+        resolver: AccountId,
+        amount: u128,
+    ) {
+        assert!(
+            self.safety_deposit_tokens.get(&token).is_some(),
+            "Token not accepted for safety deposits"
+        );
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        assert!(self.orders.get(&order_hash).is_none(), "Order already exists");
+        let key = (resolver.clone(), order_hash.to_string());
+        let mut deposit = self.token_safety_deposits.get(&key).unwrap_or(TokenSafetyDeposit {
+            token: token.clone(),
+            amount: U128(0),
+            failed_payouts: Vec::new(),
+        });
+        assert_eq!(deposit.token, token, "Safety deposit posted in another token");
+        deposit.amount = U128(deposit.amount.0 + amount);
+
+        let initial_storage_usage = env::storage_usage();
+        self.token_safety_deposits.insert(&key, &deposit);
+        self.internal_charge_storage(&resolver, initial_storage_usage);
+        FusionEvent::TokenSafetyDepositPosted(vec![TokenSafetyDepositEvent {
+            order_hash,
+            resolver,
+            token,
+            amount: deposit.amount,
+        }])
+        .emit();
+    }
+
+    /// Whether `resolver` posted a token safety deposit for the order,
+    /// which then replaces the NEAR safety deposit
+    pub(crate) fn has_token_safety_deposit(&self, resolver: &AccountId, order_hash: &str) -> bool {
+        self.token_safety_deposits
+            .get(&(resolver.clone(), order_hash.to_string()))
+            .is_some_and(|deposit| deposit.amount.0 > 0)
+    }
+
+    /// Whether the resolver's token safety deposit covers a NEAR safety deposit of `required`
+    pub(crate) fn token_safety_deposit_covers(&self, resolver: &AccountId, order_hash: &str, required: u128) -> bool {
+        let Some(deposit) = self.token_safety_deposits.get(&(resolver.clone(), order_hash.to_string())) else {
+            return false;
+        };
+        self.safety_deposit_tokens
+            .get(&deposit.token)
+            .is_some_and(|collateral_bps| deposit.amount.0 >= required * collateral_bps as u128 / 10_000)
+    }
+
+    /// Pay out an order's token safety deposit the way the NEAR one is split:
+    /// `reward_bps` to a public caller, `compensation_bps` of the rest to the
+    /// maker and the remainder to `rest_to`, or kept while the deposit is held
+    pub(crate) fn internal_settle_token_deposit(
+        &mut self,
+        order_hash: &str,
+        order: &OrderState,
+        reward: Option<(AccountId, u128)>,
+        compensation_bps: u16,
+        rest_to: Option<AccountId>,
+    ) -> Option<Promise> {
+        let key = (order.resolver.clone(), order_hash.to_string());
+        let deposit = self.token_safety_deposits.get(&key)?;
+        let mut remaining = deposit.amount.0;
+        let mut shares = Vec::new();
+        if let Some((caller, reward_bps)) = reward {
+            let reward = deposit.amount.0 * reward_bps / 10_000;
+            shares.push((caller, reward));
+            remaining -= reward;
+        }
+        let compensation = remaining * compensation_bps as u128 / 10_000;
+        shares.push((order.maker.clone(), compensation));
+        remaining -= compensation;
+        if let Some(receiver) = rest_to {
+            shares.push((receiver, remaining));
+            remaining = 0;
+        }
+
+        self.token_safety_deposits.insert(
+            &key,
+            &TokenSafetyDeposit {
+                amount: U128(remaining),
+                ..deposit.clone()
+            },
+        );
+        shares
+            .into_iter()
+            .filter(|(_, amount)| *amount > 0)
+            .map(|(receiver, amount)| {
+                self.internal_transfer_token_deposit(order_hash, &order.resolver, deposit.token.clone(), receiver, amount)
+            })
+            .reduce(|all, next| all.and(next))
+    }
+
+    /// Send `amount` of a safety deposit token, resolving through `on_token_deposit_payout`
+    fn internal_transfer_token_deposit(
+        &self,
+        order_hash: &str,
+        resolver: &AccountId,
+        token: AccountId,
+        receiver: AccountId,
+        amount: u128,
+    ) -> Promise {
+        ext_ft::ext(token.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(GAS_FOR_FT_TRANSFER)
+            .ft_transfer(receiver.clone(), U128(amount), Some(order_hash.to_string()))
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_PAYOUT)
+                    .on_token_deposit_payout(order_hash.to_string(), resolver.clone(), token, receiver, U128(amount)),
+            )
+    }
+
+    /// Whether transfers of the resolver's token safety deposit wait for a retry
+    pub(crate) fn has_failed_token_deposit_payouts(&self, resolver: &AccountId, order_hash: &str) -> bool {
+        self.token_safety_deposits
+            .get(&(resolver.clone(), order_hash.to_string()))
+            .is_some_and(|deposit| !deposit.failed_payouts.is_empty())
+    }
+
+    /// Drop a settled order's token safety deposit, returning its storage to the resolver
+    pub(crate) fn internal_remove_token_deposit(&mut self, order_hash: &str, resolver: &AccountId) {
+        let initial_storage_usage = env::storage_usage();
+        if self.token_safety_deposits.remove(&(resolver.clone(), order_hash.to_string())).is_some() {
+            self.internal_release_storage(resolver, initial_storage_usage - env::storage_usage());
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Promise};
+use schemars::JsonSchema;
+
+use crate::events::{EmergencyCancelledEvent, FusionEvent};
+use crate::hashlock::normalize_hex32;
+use crate::{FusionPlusNear, FusionPlusNearExt, OrderStatus};
+
+/// Seconds the contract must have been paused before the owner can force-cancel orders
+pub const EMERGENCY_CANCEL_DELAY: u64 = 24 * 60 * 60;
+/// Longest reason kept in a dispute record (bytes)
+pub const MAX_DISPUTE_REASON_LEN: usize = 256;
+
+/// Why and when the owner force-cancelled an order
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DisputeRecord {
+    pub reason: String,
+    #[schemars(with = "String")]
+    pub cancelled_by: AccountId,
+    /// Unix timestamp (seconds) of the cancellation
+    pub cancelled_at: u64,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Force-refund a broken Matched order (e.g. a malformed hashlock) to its
+    /// resolver, ignoring its timelocks and without maker compensation.
+    /// Owner only, while the contract has been paused for at least
+    /// `EMERGENCY_CANCEL_DELAY`; `reason` is kept as a dispute record that
+    /// survives the order's cleanup
+    #[payable]
+    pub fn emergency_cancel(&mut self, order_hash: String, reason: String) -> Promise {
+        self.assert_owner();
+        assert!(self.paused, "Contract not paused");
+        assert!(
+            Self::now_seconds() >= self.paused_at + EMERGENCY_CANCEL_DELAY,
+            "Emergency delay not over"
+        );
+        assert!(
+            !reason.is_empty() && reason.len() <= MAX_DISPUTE_REASON_LEN,
+            "Invalid dispute reason"
+        );
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let order = self.orders.get(&order_hash).expect("Order not found");
+        assert_eq!(order.status, OrderStatus::Matched, "Order not cancellable");
+
+        let owner = env::predecessor_account_id();
+        self.disputes.insert(
+            &order_hash,
+            &DisputeRecord {
+                reason: reason.clone(),
+                cancelled_by: owner.clone(),
+                cancelled_at: Self::now_seconds(),
+            },
+        );
+        FusionEvent::EmergencyCancelled(vec![EmergencyCancelledEvent {
+            order_hash: order_hash.clone(),
+            reason,
+        }])
+        .emit();
+        self.internal_refund_order(&order_hash, order, owner, true, 0)
+    }
+
+    /// Dispute record of a force-cancelled order, `None` for unknown or malformed hashes
+    pub fn get_dispute(&self, order_hash: String) -> Option<DisputeRecord> {
+        normalize_hex32(&order_hash).and_then(|digits| self.disputes.get(&format!("0x{}", digits)))
+    }
+}
@@ -0,0 +1,159 @@
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Gas, NearToken, Promise, PromiseError};
+use schemars::JsonSchema;
+
+use crate::events::{EscrowCreatedEvent, FusionEvent};
+use crate::{FusionPlusNear, FusionPlusNearExt, OrderState};
+
+/// Storage key holding the escrow contract code (`escrow/` crate)
+pub const ESCROW_CODE_KEY: &[u8] = b"ESCROW_CODE";
+/// Bytes an escrow account needs besides its code: account record, access key and state
+pub const ESCROW_ACCOUNT_BYTES: u64 = 500;
+/// Gas attached to the escrow's `new`
+pub const GAS_FOR_ESCROW_INIT: Gas = Gas::from_tgas(10);
+/// Gas attached to the escrow's `release` and `close`, which forward NEAR
+pub const GAS_FOR_ESCROW_RELEASE: Gas = Gas::from_tgas(15);
+/// Gas reserved for the escrow creation callback
+pub const GAS_FOR_ON_ESCROW_CREATED: Gas = Gas::from_tgas(10);
+
+/// Escrow factory settings
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EscrowConfig {
+    pub enabled: bool,
+    /// Hex sha256 of the escrow code, `None` until the owner uploads it
+    pub code_hash: Option<String>,
+    /// Extra deposit native orders attach to fund their escrow account's storage
+    #[schemars(with = "String")]
+    pub storage_cost: U128,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Upload the escrow contract code, passed as the raw call input
+    #[payable]
+    pub fn set_escrow_code(&mut self) {
+        self.assert_owner();
+        let code = env::input().expect("No escrow code");
+        assert!(!code.is_empty(), "No escrow code");
+        env::storage_write(ESCROW_CODE_KEY, &code);
+    }
+
+    /// Enable or disable escrow mode for new native NEAR orders
+    /// In escrow mode each order's funds are held by its own sub-account
+    /// `{hash8}.<this contract>`, like the clone-per-escrow pattern on Ethereum
+    #[payable]
+    pub fn set_escrow_mode(&mut self, enabled: bool) {
+        self.assert_owner();
+        assert!(!enabled || env::storage_has_key(ESCROW_CODE_KEY), "Escrow code not set");
+        self.escrow_mode = enabled;
+    }
+
+    pub fn get_escrow_config(&self) -> EscrowConfig {
+        EscrowConfig {
+            enabled: self.escrow_mode,
+            code_hash: env::storage_read(ESCROW_CODE_KEY).map(|code| hex::encode(env::sha256(&code[..]))),
+            storage_cost: U128(self.internal_escrow_storage_cost()),
+        }
+    }
+
+    /// Callback after creating an order's escrow account
+    /// On failure the order's funds are refunded to this contract, which then
+    /// pays the order directly; the escrow storage deposit goes back to the resolver
+    #[private]
+    pub fn on_escrow_created(
+        &mut self,
+        order_hash: String,
+        resolver: AccountId,
+        storage_cost: U128,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        if result.is_ok() {
+            FusionEvent::EscrowCreated(vec![EscrowCreatedEvent {
+                escrow: Self::escrow_account_id(&order_hash),
+                order_hash,
+            }])
+            .emit();
+            return true;
+        }
+
+        if let Some(mut order) = self.orders.get(&order_hash) {
+            self.internal_record_unescrowed(&order);
+            order.escrowed = false;
+            self.orders.insert(&order_hash, &order);
+        }
+        let _ = Promise::new(resolver).transfer(NearToken::from_yoctonear(storage_cost.0));
+        false
+    }
+}
+
+impl FusionPlusNear {
+    /// Escrow sub-account of an order: first 8 hex digits of its hash
+    pub(crate) fn escrow_account_id(order_hash: &str) -> AccountId {
+        format!("{}.{}", &order_hash[2..10], env::current_account_id())
+            .parse()
+            .expect("Invalid escrow account")
+    }
+
+    /// NEAR locked by an escrow account for its code and state
+    pub(crate) fn internal_escrow_storage_cost(&self) -> u128 {
+        let code_len = env::storage_read(ESCROW_CODE_KEY).map_or(0, |code| code.len() as u64);
+        (code_len + ESCROW_ACCOUNT_BYTES) as u128 * env::storage_byte_cost().as_yoctonear()
+    }
+
+    /// Create the order's escrow account holding `amount` plus its storage cost
+    pub(crate) fn internal_create_escrow(
+        &self,
+        order_hash: &str,
+        resolver: &AccountId,
+        amount: u128,
+        storage_cost: u128,
+    ) -> Promise {
+        let code = env::storage_read(ESCROW_CODE_KEY).expect("Escrow code not set");
+        Promise::new(Self::escrow_account_id(order_hash))
+            .create_account()
+            .transfer(NearToken::from_yoctonear(amount + storage_cost))
+            .deploy_contract(code)
+            .function_call(
+                "new".to_string(),
+                serde_json::json!({ "order_hash": order_hash }).to_string().into_bytes(),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_ESCROW_INIT,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_ESCROW_CREATED)
+                    .on_escrow_created(order_hash.to_string(), resolver.clone(), U128(storage_cost)),
+            )
+    }
+
+    /// Have the order's escrow pay `amount` to `receiver`
+    pub(crate) fn internal_escrow_release(&self, order_hash: &str, receiver: AccountId, amount: u128) -> Promise {
+        Promise::new(Self::escrow_account_id(order_hash)).function_call(
+            "release".to_string(),
+            serde_json::json!({ "receiver": receiver, "amount": U128(amount) })
+                .to_string()
+                .into_bytes(),
+            NearToken::from_yoctonear(0),
+            GAS_FOR_ESCROW_RELEASE,
+        )
+    }
+
+    /// Move a protocol fee taken from an escrowed order into this contract
+    pub(crate) fn internal_collect_escrowed_fee(&self, order_hash: &str, order: &OrderState, fee: u128) {
+        if order.escrowed && fee > 0 {
+            let _ = self.internal_escrow_release(order_hash, env::current_account_id(), fee);
+        }
+    }
+
+    /// Delete the order's escrow account, returning its storage stake to `beneficiary`
+    pub(crate) fn internal_close_escrow(&self, order_hash: &str, beneficiary: AccountId) -> Promise {
+        Promise::new(Self::escrow_account_id(order_hash)).function_call(
+            "close".to_string(),
+            serde_json::json!({ "beneficiary": beneficiary }).to_string().into_bytes(),
+            NearToken::from_yoctonear(0),
+            GAS_FOR_ESCROW_RELEASE,
+        )
+    }
+}
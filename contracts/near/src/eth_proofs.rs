@@ -0,0 +1,238 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Gas, NearToken, Promise, PromiseError};
+use schemars::JsonSchema;
+
+use crate::events::{FusionEvent, SourceEscrowProvenEvent};
+use crate::hashlock::normalize_hex32;
+use crate::{FusionPlusNear, FusionPlusNearExt, OrderStatus};
+
+/// Gas attached to the prover's `verify_log_entry`, which calls the Ethereum light client
+pub const GAS_FOR_VERIFY_LOG_ENTRY: Gas = Gas::from_tgas(50);
+/// Gas reserved for the proof verification callback
+pub const GAS_FOR_ON_SOURCE_ESCROW_PROVEN: Gas = Gas::from_tgas(10);
+/// Event signature of `EscrowSrcCreated` in `IOneInchEscrowFactory.sol`
+pub const ESCROW_SRC_CREATED_SIGNATURE: &str = "EscrowSrcCreated(bytes32,address,address,address,bytes32)";
+
+/// Rainbow Bridge Ethereum prover and the escrow factory whose logs it proves
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EthProofConfig {
+    /// Prover contract, e.g. `prover.bridge.near`
+    #[schemars(with = "String")]
+    pub prover: AccountId,
+    /// Escrow factory address, 20-byte hex without `0x`
+    pub escrow_factory: String,
+    /// Claims of orders without a proven source escrow are rejected
+    pub require_proof: bool,
+}
+
+/// Borsh arguments of the prover's `verify_log_entry`
+#[derive(BorshSerialize)]
+struct VerifyLogEntryArgs {
+    log_index: u64,
+    log_entry_data: Vec<u8>,
+    receipt_index: u64,
+    receipt_data: Vec<u8>,
+    header_data: Vec<u8>,
+    proof: Vec<Vec<u8>>,
+    skip_bridge_call: bool,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Set the Ethereum prover and escrow factory, `None` disables source proofs
+    #[payable]
+    pub fn set_eth_proof_config(&mut self, config: Option<EthProofConfig>) {
+        self.assert_owner();
+        self.eth_proof_config = config.map(|config| {
+            let escrow_factory = config.escrow_factory.trim_start_matches("0x").to_lowercase();
+            assert!(
+                escrow_factory.len() == 40 && hex::decode(&escrow_factory).is_ok(),
+                "Invalid escrow factory address"
+            );
+            EthProofConfig { escrow_factory, ..config }
+        });
+    }
+
+    pub fn get_eth_proof_config(&self) -> Option<EthProofConfig> {
+        self.eth_proof_config.clone()
+    }
+
+    /// Prove with the Ethereum light client that the factory emitted
+    /// `EscrowSrcCreated` for the order with the same hashlock, so the claim
+    /// no longer trusts the parameters the resolver submitted. Callable by
+    /// anyone registered for storage, which pays for the proof record.
+    /// Arguments are those of the Rainbow Bridge prover's `verify_log_entry`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_source_escrow(
+        &mut self,
+        order_hash: String,
+        log_index: u64,
+        log_entry_data: Base64VecU8,
+        receipt_index: u64,
+        receipt_data: Base64VecU8,
+        header_data: Base64VecU8,
+        proof: Vec<Base64VecU8>,
+    ) -> Promise {
+        let config = self.eth_proof_config.clone().expect("Ethereum prover not configured");
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let order = self.orders.get(&order_hash).expect("Order not found");
+        let metadata = self.order_metadata.get(&order_hash).expect("Order not found");
+        assert_eq!(order.status, OrderStatus::Matched, "Order not claimable");
+        assert!(self.source_proofs.get(&order_hash).is_none(), "Source escrow already proven");
+        let prover = env::predecessor_account_id();
+        assert!(self.storage_accounts.get(&prover).is_some(), "Account not registered for storage");
+
+        let log = parse_log_entry(&log_entry_data.0).expect("Invalid log entry");
+        let factory = hex::decode(&config.escrow_factory).expect("Invalid escrow factory address");
+        assert_eq!(log.address, factory, "Log not emitted by the escrow factory");
+        assert!(
+            log.topics.len() == 4
+                && log.topics[0] == env::keccak256(ESCROW_SRC_CREATED_SIGNATURE.as_bytes())
+                && log.topics[1] == hex::decode(&order_hash[2..]).unwrap(),
+            "Log is not the order's EscrowSrcCreated"
+        );
+        assert!(
+            log.data.len() == 64 && log.data[32..] == metadata.hashlock,
+            "Source escrow hashlock mismatch"
+        );
+
+        let args = VerifyLogEntryArgs {
+            log_index,
+            log_entry_data: log_entry_data.0,
+            receipt_index,
+            receipt_data: receipt_data.0,
+            header_data: header_data.0,
+            proof: proof.into_iter().map(|node| node.0).collect(),
+            skip_bridge_call: false,
+        };
+        Promise::new(config.prover)
+            .function_call(
+                "verify_log_entry".to_string(),
+                borsh::to_vec(&args).unwrap(),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_VERIFY_LOG_ENTRY,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_SOURCE_ESCROW_PROVEN)
+                    .on_source_escrow_proven(order_hash, prover),
+            )
+    }
+
+    /// Callback after the prover checked the log, records the proof if it holds
+    #[private]
+    pub fn on_source_escrow_proven(
+        &mut self,
+        order_hash: String,
+        prover: AccountId,
+        #[callback_result]
+        #[serializer(borsh)]
+        result: Result<bool, PromiseError>,
+    ) -> bool {
+        if !matches!(result, Ok(true)) || self.source_proofs.get(&order_hash).is_some() {
+            return false;
+        }
+        let initial_storage_usage = env::storage_usage();
+        self.source_proofs.insert(&order_hash, &prover);
+        self.internal_charge_storage(&prover, initial_storage_usage);
+        FusionEvent::SourceEscrowProven(vec![SourceEscrowProvenEvent {
+            order_hash,
+            proven_by: prover,
+        }])
+        .emit();
+        true
+    }
+
+    /// Whether the order's Ethereum source escrow was proven, false for unknown or malformed hashes
+    pub fn is_source_escrow_proven(&self, order_hash: String) -> bool {
+        normalize_hex32(&order_hash).is_some_and(|digits| self.source_proofs.get(&format!("0x{}", digits)).is_some())
+    }
+}
+
+impl FusionPlusNear {
+    /// Panic if source proofs are required and the order has none
+    pub(crate) fn assert_source_proven(&self, order_hash: &String) {
+        if self.eth_proof_config.as_ref().is_some_and(|config| config.require_proof) {
+            assert!(self.source_proofs.get(order_hash).is_some(), "Source escrow not proven");
+        }
+    }
+
+    /// Drop an order's source proof, returning its storage to the account that paid it
+    pub(crate) fn internal_remove_source_proof(&mut self, order_hash: &String) {
+        let initial_storage_usage = env::storage_usage();
+        if let Some(prover) = self.source_proofs.remove(order_hash) {
+            self.internal_release_storage(&prover, initial_storage_usage - env::storage_usage());
+        }
+    }
+}
+
+/// Ethereum log entry: `rlp([address, [topics...], data])`
+#[derive(PartialEq, Debug)]
+pub(crate) struct LogEntry {
+    pub address: Vec<u8>,
+    pub topics: Vec<Vec<u8>>,
+    pub data: Vec<u8>,
+}
+
+pub(crate) fn parse_log_entry(rlp: &[u8]) -> Option<LogEntry> {
+    let (fields, rest) = rlp_list(rlp)?;
+    if !rest.is_empty() {
+        return None;
+    }
+    let (address, fields) = rlp_bytes(fields)?;
+    let (mut topics_rlp, fields) = rlp_list(fields)?;
+    let (data, fields) = rlp_bytes(fields)?;
+    if !fields.is_empty() || address.len() != 20 {
+        return None;
+    }
+    let mut topics = Vec::new();
+    while !topics_rlp.is_empty() {
+        let (topic, rest) = rlp_bytes(topics_rlp)?;
+        topics.push(topic.to_vec());
+        topics_rlp = rest;
+    }
+    Some(LogEntry {
+        address: address.to_vec(),
+        topics,
+        data: data.to_vec(),
+    })
+}
+
+/// Split the first RLP item off `input`: (is_list, payload, rest)
+fn rlp_item(input: &[u8]) -> Option<(bool, &[u8], &[u8])> {
+    let (&prefix, tail) = input.split_first()?;
+    let (is_list, header_len, payload_len) = match prefix {
+        0x00..=0x7f => return Some((false, &input[..1], tail)),
+        0x80..=0xb7 => (false, 1, (prefix - 0x80) as usize),
+        0xb8..=0xbf => (false, 1 + (prefix - 0xb7) as usize, be_len(tail.get(..(prefix - 0xb7) as usize)?)?),
+        0xc0..=0xf7 => (true, 1, (prefix - 0xc0) as usize),
+        0xf8..=0xff => (true, 1 + (prefix - 0xf7) as usize, be_len(tail.get(..(prefix - 0xf7) as usize)?)?),
+    };
+    let end = header_len.checked_add(payload_len)?;
+    let payload = input.get(header_len..end)?;
+    Some((is_list, payload, &input[end..]))
+}
+
+fn rlp_bytes(input: &[u8]) -> Option<(&[u8], &[u8])> {
+    match rlp_item(input)? {
+        (false, payload, rest) => Some((payload, rest)),
+        _ => None,
+    }
+}
+
+fn rlp_list(input: &[u8]) -> Option<(&[u8], &[u8])> {
+    match rlp_item(input)? {
+        (true, payload, rest) => Some((payload, rest)),
+        _ => None,
+    }
+}
+
+fn be_len(bytes: &[u8]) -> Option<usize> {
+    if bytes.len() > std::mem::size_of::<usize>() {
+        return None;
+    }
+    Some(bytes.iter().fold(0, |len, &byte| (len << 8) | byte as usize))
+}
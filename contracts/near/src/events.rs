@@ -0,0 +1,440 @@
+use near_sdk::env;
+use near_sdk::json_types::U128;
+use near_sdk::serde::Serialize;
+use near_sdk::json_types::U64;
+use near_sdk::AccountId;
+
+use crate::htlc::TimelockMode;
+use crate::signed_release::ReleaseKey;
+use crate::OrderStatus;
+
+/// NEP-297 standard name for Fusion+ NEAR events
+pub const EVENT_STANDARD: &str = "fusion_plus";
+/// NEP-297 standard version for Fusion+ NEAR events
+pub const EVENT_STANDARD_VERSION: &str = "1.0.0";
+/// Storage key holding the nonce of the last emitted event, outside the
+/// contract state so events can be emitted from `&self` methods too
+pub const EVENT_NONCE_KEY: &[u8] = b"EVENT_NONCE";
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FusionOrderCreatedEvent {
+    pub order_hash: String,
+    pub maker: AccountId,
+    pub resolver: AccountId,
+    pub token: Option<AccountId>,
+    pub amount: U128,
+    pub source_chain_id: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FusionClaimRequestedEvent {
+    pub order_hash: String,
+    pub requested_by: AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SourceEscrowProvenEvent {
+    pub order_hash: String,
+    pub proven_by: AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OrderCreationNotifiedEvent {
+    pub order_hash: String,
+    pub receiver_id: AccountId,
+    pub delivered: bool,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ReleaseKeySetEvent {
+    pub order_hash: String,
+    pub release_key: ReleaseKey,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FusionOrderReleasedEvent {
+    pub order_hash: String,
+    pub resolver: AccountId,
+    pub released_by: AccountId,
+    pub release_key: ReleaseKey,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TimelockExtendedEvent {
+    pub order_hash: String,
+    /// New start (unix seconds) of the cancellation stage
+    pub cancellation_at: u64,
+    pub public_cancellation_at: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EmergencyCancelledEvent {
+    pub order_hash: String,
+    pub reason: String,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SettlementChallengedEvent {
+    pub order_hash: String,
+    pub challenged_by: AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DisputeFlaggedEvent {
+    pub order_hash: String,
+    pub flagged_by: AccountId,
+    pub evidence_uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ChallengeResolvedEvent {
+    pub order_hash: String,
+    pub valid_settlement: bool,
+    pub receiver: AccountId,
+    pub amount: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FusionOrderClaimedEvent {
+    pub order_hash: String,
+    pub resolver: AccountId,
+    pub claimed_by: AccountId,
+    pub preimage: String,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FusionOrderPartiallyFilledEvent {
+    pub order_hash: String,
+    pub resolver: AccountId,
+    pub filled_by: AccountId,
+    pub secret_index: u32,
+    pub secret: String,
+    pub fill_amount: U128,
+    pub filled_amount: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PartialFillExecutedEvent {
+    pub order_hash: String,
+    pub fill_order_hash: String,
+    pub resolver: AccountId,
+    pub secret_index: u32,
+    pub fill_amount: U128,
+    pub filled_amount: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FusionOrderRefundedEvent {
+    pub order_hash: String,
+    pub resolver: AccountId,
+    pub cancelled_by: AccountId,
+    pub amount: U128,
+    /// Part of the safety deposit paid to the maker
+    pub maker_compensation: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FusionOrderCleanedUpEvent {
+    pub order_hash: String,
+    /// Account whose storage balance was credited
+    pub storage_payer: AccountId,
+    pub freed_bytes: u64,
+    pub refund: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FusionOrderStatusChangedEvent {
+    pub order_hash: String,
+    pub old_status: OrderStatus,
+    pub new_status: OrderStatus,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EscrowCreatedEvent {
+    pub order_hash: String,
+    pub escrow: AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ResolverEvent {
+    pub resolver: AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenSafetyDepositEvent {
+    pub order_hash: String,
+    pub resolver: AccountId,
+    pub token: AccountId,
+    /// Total posted for the order so far
+    pub amount: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MakerEvent {
+    pub maker: AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ResolverOperatorEvent {
+    pub resolver: AccountId,
+    pub operator: Option<AccountId>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ResolverReassignedEvent {
+    pub order_hash: String,
+    pub previous_resolver: AccountId,
+    pub resolver: AccountId,
+    /// Safety deposit refunded to the previous resolver
+    pub safety_deposit: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SolverAttestationEvent {
+    pub solver: AccountId,
+    pub code_measurement: String,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ResolverBondEvent {
+    pub resolver: AccountId,
+    pub amount: U128,
+    /// Active bond after the change
+    pub total: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CircuitBreakerTrippedEvent {
+    /// `insolvent` or `payout_failures`
+    pub reason: String,
+    /// NEAR owed beyond the balance, 0 unless insolvent
+    pub deficit: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseEvent {
+    pub account_id: AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OwnershipEvent {
+    pub owner: AccountId,
+    pub new_owner: AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PayoutEvent {
+    pub order_hash: String,
+    pub receiver: AccountId,
+    pub amount: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HtlcOrderCreatedEvent {
+    pub order_id: String,
+    pub maker: AccountId,
+    pub amount: U128,
+    pub hashlock: String,
+    pub timelock: U64,
+    pub timelock_mode: TimelockMode,
+    pub destination_chain: String,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HtlcBidPlacedEvent {
+    pub order_id: String,
+    pub resolver: AccountId,
+    pub fee: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HtlcOrderMatchedEvent {
+    pub order_id: String,
+    pub resolver: AccountId,
+    pub safety_deposit: U128,
+    pub resolver_fee: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HtlcOrderClaimedEvent {
+    pub order_id: String,
+    pub resolver: AccountId,
+    pub preimage: String,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HtlcFeeDepositedEvent {
+    pub order_id: String,
+    pub token: AccountId,
+    pub amount: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HtlcOrderCancelledEvent {
+    pub order_id: String,
+    pub maker: AccountId,
+    pub amount: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HtlcOrderCleanedUpEvent {
+    pub order_id: String,
+    /// Maker whose storage balance was credited
+    pub storage_payer: AccountId,
+    pub freed_bytes: u64,
+    pub refund: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WithdrawalSignedEvent {
+    pub order_hash: String,
+    pub payload: String,
+    pub big_r: String,
+    pub s: String,
+    pub recovery_id: u8,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProtocolFeesWithdrawnEvent {
+    pub token: Option<AccountId>,
+    pub receiver: AccountId,
+    pub amount: U128,
+}
+
+/// Events for 1inch integration monitoring
+/// Serialized as `{"event": ..., "data": [...]}` per NEP-297
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum FusionEvent {
+    FusionOrderCreated(Vec<FusionOrderCreatedEvent>),
+    OrderCreationNotified(Vec<OrderCreationNotifiedEvent>),
+    FusionClaimRequested(Vec<FusionClaimRequestedEvent>),
+    SourceEscrowProven(Vec<SourceEscrowProvenEvent>),
+    FusionOrderClaimed(Vec<FusionOrderClaimedEvent>),
+    ReleaseKeySet(Vec<ReleaseKeySetEvent>),
+    FusionOrderReleased(Vec<FusionOrderReleasedEvent>),
+    TimelockExtended(Vec<TimelockExtendedEvent>),
+    SettlementChallenged(Vec<SettlementChallengedEvent>),
+    DisputeFlagged(Vec<DisputeFlaggedEvent>),
+    EmergencyCancelled(Vec<EmergencyCancelledEvent>),
+    ChallengeResolved(Vec<ChallengeResolvedEvent>),
+    FusionOrderPartiallyFilled(Vec<FusionOrderPartiallyFilledEvent>),
+    PartialFillExecuted(Vec<PartialFillExecutedEvent>),
+    FusionOrderRefunded(Vec<FusionOrderRefundedEvent>),
+    FusionOrderStatusChanged(Vec<FusionOrderStatusChangedEvent>),
+    FusionOrderCleanedUp(Vec<FusionOrderCleanedUpEvent>),
+    EscrowCreated(Vec<EscrowCreatedEvent>),
+    ResolverAdded(Vec<ResolverEvent>),
+    ResolverRemoved(Vec<ResolverEvent>),
+    MakerAdded(Vec<MakerEvent>),
+    MakerRemoved(Vec<MakerEvent>),
+    ResolverBonded(Vec<ResolverBondEvent>),
+    ResolverUnbonding(Vec<ResolverBondEvent>),
+    ResolverBondWithdrawn(Vec<ResolverBondEvent>),
+    ResolverOperatorSet(Vec<ResolverOperatorEvent>),
+    ResolverReassigned(Vec<ResolverReassignedEvent>),
+    TokenSafetyDepositPosted(Vec<TokenSafetyDepositEvent>),
+    SolverAttestationRegistered(Vec<SolverAttestationEvent>),
+    SolverAttestationRevoked(Vec<SolverAttestationEvent>),
+    PayoutSent(Vec<PayoutEvent>),
+    PayoutFailed(Vec<PayoutEvent>),
+    ContractPaused(Vec<PauseEvent>),
+    CircuitBreakerTripped(Vec<CircuitBreakerTrippedEvent>),
+    ContractUnpaused(Vec<PauseEvent>),
+    OwnershipProposed(Vec<OwnershipEvent>),
+    OwnershipTransferred(Vec<OwnershipEvent>),
+    HtlcOrderCreated(Vec<HtlcOrderCreatedEvent>),
+    HtlcBidPlaced(Vec<HtlcBidPlacedEvent>),
+    HtlcOrderMatched(Vec<HtlcOrderMatchedEvent>),
+    HtlcOrderClaimed(Vec<HtlcOrderClaimedEvent>),
+    HtlcFeeDeposited(Vec<HtlcFeeDepositedEvent>),
+    HtlcOrderCancelled(Vec<HtlcOrderCancelledEvent>),
+    HtlcOrderCleanedUp(Vec<HtlcOrderCleanedUpEvent>),
+    WithdrawalSigned(Vec<WithdrawalSignedEvent>),
+    ProtocolFeesWithdrawn(Vec<ProtocolFeesWithdrawnEvent>),
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    nonce: u64,
+    #[serde(flatten)]
+    event: &'a FusionEvent,
+}
+
+impl FusionEvent {
+    /// Serialize the event in NEP-297 format, with its sequence number
+    pub fn to_json(&self, nonce: u64) -> String {
+        let log = EventLog {
+            standard: EVENT_STANDARD,
+            version: EVENT_STANDARD_VERSION,
+            nonce,
+            event: self,
+        };
+        format!("EVENT_JSON:{}", serde_json::to_string(&log).unwrap())
+    }
+
+    /// Log the event so NEAR indexers pick it up
+    /// Each event takes the next nonce, so consumers can spot missed events
+    pub fn emit(&self) {
+        let nonce = event_nonce() + 1;
+        env::storage_write(EVENT_NONCE_KEY, &nonce.to_le_bytes());
+        env::log_str(&self.to_json(nonce));
+    }
+}
+
+/// Nonce of the last emitted event, 0 before the first one
+pub fn event_nonce() -> u64 {
+    env::storage_read(EVENT_NONCE_KEY)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().expect("Invalid event nonce")))
+        .unwrap_or(0)
+}
+
+/// Create the nonce key up front, so the first event does not grow the
+/// storage charged to the account whose call emits it
+pub(crate) fn init_event_nonce() {
+    if !env::storage_has_key(EVENT_NONCE_KEY) {
+        env::storage_write(EVENT_NONCE_KEY, &0u64.to_le_bytes());
+    }
+}
@@ -0,0 +1,95 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::events::{FusionEvent, TimelockExtendedEvent};
+use crate::hashlock::normalize_hex32;
+use crate::timelocks::{TimelockStage, Timelocks};
+use crate::{FusionPlusNear, FusionPlusNearExt, OrderState, OrderStatus};
+
+/// Pending consent of one party to push back an order's cancellation stage
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TimelockExtension {
+    #[schemars(with = "String")]
+    pub approved_by: AccountId,
+    /// Unix timestamp (seconds) the cancellation stage would start at
+    pub new_deadline: u64,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Consent to moving the order's cancellation stage to `new_deadline`
+    /// (maker or resolver), e.g. when the Ethereum side is delayed by congestion.
+    /// Replaces the caller's earlier approval; the other party completes the
+    /// extension with `extend_timelock`
+    pub fn approve_timelock_extension(&mut self, order_hash: String, new_deadline: u64) {
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let order = self.orders.get(&order_hash).expect("Order not found");
+        let caller = env::predecessor_account_id();
+        Self::assert_extension_party(&order, &caller);
+        Self::assert_extends(&order, new_deadline);
+
+        self.timelock_extensions.insert(
+            &order_hash,
+            &TimelockExtension {
+                approved_by: caller,
+                new_deadline,
+            },
+        );
+    }
+
+    /// Move the order's cancellation stage to `new_deadline`, shifting public
+    /// cancellation by the same delay. Requires the counterparty's approval of
+    /// exactly this deadline; timelocks can never be shortened.
+    /// Returns the new packed timelocks
+    pub fn extend_timelock(&mut self, order_hash: String, new_deadline: u64) -> U128 {
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let mut order = self.orders.get(&order_hash).expect("Order not found");
+        let caller = env::predecessor_account_id();
+        Self::assert_extension_party(&order, &caller);
+        Self::assert_extends(&order, new_deadline);
+        let approved = self.timelock_extensions.get(&order_hash).is_some_and(|extension| {
+            extension.approved_by != caller && extension.new_deadline == new_deadline
+        });
+        assert!(approved, "Counterparty has not approved this extension");
+
+        let timelocks = Timelocks(order.timelocks.0).with_cancellation_at(new_deadline, order.created_at);
+        self.internal_unindex_expiry(&order_hash, &order);
+        order.timelocks = U128(timelocks.0);
+        self.internal_index_expiry(&order_hash, &order);
+        self.orders.insert(&order_hash, &order);
+        self.timelock_extensions.remove(&order_hash);
+
+        FusionEvent::TimelockExtended(vec![TimelockExtendedEvent {
+            order_hash,
+            cancellation_at: new_deadline,
+            public_cancellation_at: timelocks.stage_start(TimelockStage::PublicCancellation, order.created_at),
+        }])
+        .emit();
+        order.timelocks
+    }
+
+    /// Extension awaiting the counterparty, `None` for unknown or malformed hashes
+    pub fn get_timelock_extension(&self, order_hash: String) -> Option<TimelockExtension> {
+        normalize_hex32(&order_hash).and_then(|digits| self.timelock_extensions.get(&format!("0x{}", digits)))
+    }
+}
+
+impl FusionPlusNear {
+    fn assert_extension_party(order: &OrderState, account: &AccountId) {
+        assert!(
+            *account == order.maker || *account == order.resolver,
+            "Only maker or resolver can extend the timelock"
+        );
+    }
+
+    fn assert_extends(order: &OrderState, new_deadline: u64) {
+        assert_eq!(order.status, OrderStatus::Matched, "Order not extendable");
+        let cancellation_at =
+            Timelocks(order.timelocks.0).stage_start(TimelockStage::Cancellation, order.created_at);
+        assert!(new_deadline > cancellation_at, "Timelock can only be extended");
+    }
+}
@@ -0,0 +1,102 @@
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, NearToken, Promise, PromiseError};
+
+use crate::events::{FusionEvent, HtlcFeeDepositedEvent, PayoutEvent};
+use crate::ft::{ext_ft, GAS_FOR_FT_TRANSFER};
+use crate::payout::{Payout, GAS_FOR_ON_PAYOUT};
+use crate::{FusionPlusNear, FusionPlusNearExt};
+
+/// `ft_transfer_call` message depositing the NEP-141 resolver fee of an HTLC order
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde", deny_unknown_fields)]
+pub struct HtlcFeeDeposit {
+    pub htlc_order_id: String,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Callback after each NEP-141 resolver fee transfer of an HTLC order
+    /// A failed transfer is recorded on the order so it can be retried
+    #[private]
+    pub fn on_htlc_fee_payout(
+        &mut self,
+        order_id: String,
+        receiver: AccountId,
+        amount: U128,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        if result.is_ok() {
+            FusionEvent::PayoutSent(vec![PayoutEvent {
+                order_hash: order_id,
+                receiver,
+                amount,
+            }])
+            .emit();
+            return true;
+        }
+
+        let mut order = self.htlc_orders.get(&order_id).expect("Order not found");
+        order.failed_fee_payouts.push(Payout {
+            receiver: receiver.clone(),
+            amount,
+        });
+        self.htlc_orders.insert(&order_id, &order);
+
+        FusionEvent::PayoutFailed(vec![PayoutEvent {
+            order_hash: order_id,
+            receiver,
+            amount,
+        }])
+        .emit();
+        false
+    }
+}
+
+impl FusionPlusNear {
+    /// Fund the resolver fee of an HTLC order priced in `token`
+    /// Returns the surplus over the fee, which the token contract refunds
+    pub(crate) fn internal_deposit_htlc_fee(
+        &mut self,
+        order_id: String,
+        token: AccountId,
+        sender: AccountId,
+        amount: u128,
+    ) -> u128 {
+        let mut order = self.htlc_orders.get(&order_id).expect("Order not found");
+        assert_eq!(order.fee_token.as_ref(), Some(&token), "Wrong fee token");
+        assert_eq!(order.maker, sender, "Only maker can deposit the fee");
+        assert!(!order.is_claimed && !order.is_refunded, "Order already settled");
+        assert!(!order.fee_deposited, "Fee already deposited");
+        assert!(amount >= order.resolver_fee.0, "Insufficient fee deposit");
+
+        order.fee_deposited = true;
+        self.htlc_orders.insert(&order_id, &order);
+
+        FusionEvent::HtlcFeeDeposited(vec![HtlcFeeDepositedEvent {
+            order_id,
+            token,
+            amount: order.resolver_fee,
+        }])
+        .emit();
+        amount - order.resolver_fee.0
+    }
+
+    /// Transfer `amount` of the NEP-141 fee `token` to `receiver`, resolving through `on_htlc_fee_payout`
+    pub(crate) fn internal_htlc_fee_payout(
+        order_id: &str,
+        token: AccountId,
+        receiver: AccountId,
+        amount: u128,
+    ) -> Promise {
+        ext_ft::ext(token)
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(GAS_FOR_FT_TRANSFER)
+            .ft_transfer(receiver.clone(), U128(amount), Some(order_id.to_string()))
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_PAYOUT)
+                    .on_htlc_fee_payout(order_id.to_string(), receiver, U128(amount)),
+            )
+    }
+}
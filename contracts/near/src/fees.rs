@@ -0,0 +1,113 @@
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, AccountId, Gas, NearToken, Promise, PromiseError};
+use schemars::JsonSchema;
+
+use crate::events::{FusionEvent, ProtocolFeesWithdrawnEvent};
+use crate::ft::{ext_ft, GAS_FOR_FT_TRANSFER};
+use crate::{FusionPlusNear, FusionPlusNearExt};
+
+/// Upper bound for the protocol fee (basis points)
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 1_000;
+/// Gas reserved for the fee withdrawal callback
+pub const GAS_FOR_ON_FEES_WITHDRAWN: Gas = Gas::from_tgas(10);
+
+/// Protocol fee settings
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProtocolFeeConfig {
+    pub protocol_fee_bps: u16,
+    #[schemars(with = "String")]
+    pub fee_account: AccountId,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Set the protocol fee taken from claimed amounts and the account
+    /// receiving withdrawn fees (defaults to the owner)
+    #[payable]
+    pub fn set_protocol_fee(&mut self, protocol_fee_bps: u16, fee_account: Option<AccountId>) {
+        self.assert_owner();
+        assert!(protocol_fee_bps <= MAX_PROTOCOL_FEE_BPS, "Protocol fee too high");
+        self.protocol_fee_bps = protocol_fee_bps;
+        self.fee_account = fee_account;
+    }
+
+    pub fn get_protocol_fee(&self) -> ProtocolFeeConfig {
+        ProtocolFeeConfig {
+            protocol_fee_bps: self.protocol_fee_bps,
+            fee_account: self.internal_fee_account(),
+        }
+    }
+
+    /// Protocol fees accrued in NEAR (`token == None`) or the given NEP-141 token
+    pub fn get_accrued_fees(&self, token: Option<AccountId>) -> U128 {
+        U128(self.accrued_fees.get(&token).unwrap_or(0))
+    }
+
+    /// Send all accrued fees of one asset to the fee account
+    #[payable]
+    pub fn withdraw_fees(&mut self, token: Option<AccountId>) -> Promise {
+        assert_one_yocto();
+        self.assert_owner();
+        let amount = self.accrued_fees.remove(&token).unwrap_or(0);
+        assert!(amount > 0, "No accrued fees");
+
+        let fee_account = self.internal_fee_account();
+        let transfer = match &token {
+            None => Promise::new(fee_account.clone()).transfer(NearToken::from_yoctonear(amount)),
+            Some(token) => ext_ft::ext(token.clone())
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(GAS_FOR_FT_TRANSFER)
+                .ft_transfer(fee_account.clone(), U128(amount), None),
+        };
+        transfer.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_ON_FEES_WITHDRAWN)
+                .on_fees_withdrawn(token, fee_account, U128(amount)),
+        )
+    }
+
+    /// Callback after a fee withdrawal, re-accrues the amount if the transfer failed
+    #[private]
+    pub fn on_fees_withdrawn(
+        &mut self,
+        token: Option<AccountId>,
+        receiver: AccountId,
+        amount: U128,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        if result.is_err() {
+            self.internal_accrue_fee(&token, amount.0);
+            return false;
+        }
+
+        FusionEvent::ProtocolFeesWithdrawn(vec![ProtocolFeesWithdrawnEvent {
+            token,
+            receiver,
+            amount,
+        }])
+        .emit();
+        true
+    }
+}
+
+impl FusionPlusNear {
+    /// Take the protocol fee out of a claimed `amount`, returning the fee
+    pub(crate) fn internal_take_protocol_fee(&mut self, token: &Option<AccountId>, amount: u128) -> u128 {
+        let fee = amount * self.protocol_fee_bps as u128 / 10_000;
+        if fee > 0 {
+            self.internal_accrue_fee(token, fee);
+        }
+        fee
+    }
+
+    fn internal_accrue_fee(&mut self, token: &Option<AccountId>, amount: u128) {
+        let accrued = self.accrued_fees.get(token).unwrap_or(0);
+        self.accrued_fees.insert(token, &(accrued + amount));
+    }
+
+    fn internal_fee_account(&self) -> AccountId {
+        self.fee_account.clone().unwrap_or_else(|| self.owner.clone())
+    }
+}
@@ -0,0 +1,98 @@
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, ext_contract, near_bindgen, AccountId, Gas, PromiseOrValue};
+
+use crate::deposit_tokens::SafetyDepositMessage;
+use crate::fee_token::HtlcFeeDeposit;
+use crate::hashlock::HashAlgorithm;
+use crate::intents::IntentMessage;
+use crate::partial_fills::PartialFillMessage;
+use crate::{DestinationInfo, FusionPlusNear, FusionPlusNearExt};
+
+/// Gas attached to `ft_transfer` calls on the token contract
+pub const GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(10);
+
+/// NEP-141 fungible token interface used for escrow payouts
+#[ext_contract(ext_ft)]
+pub trait FungibleToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+    fn ft_transfer_call(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>, msg: String) -> U128;
+}
+
+/// Fusion+ order parameters passed as the `msg` of `ft_transfer_call`
+/// The token contract and resolver are taken from the transfer itself
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FusionOrderParams {
+    pub order_hash: String,
+    pub hashlock: String,
+    pub maker: AccountId,
+    pub amount: U128,
+    pub resolver_fee: U128,
+    pub timelocks: U128,
+    pub source_chain_id: u64,
+    /// Defaults to SHA-256
+    #[serde(default)]
+    pub hash_algorithm: Option<HashAlgorithm>,
+    /// Number of parts for partial fills, `hashlock` is then a Merkle root
+    #[serde(default)]
+    pub parts: Option<u32>,
+    /// What the maker gives in exchange, for display
+    #[serde(default)]
+    pub destination: Option<DestinationInfo>,
+    /// Account receiving the maker's payouts, `maker` when unset
+    #[serde(default)]
+    pub payout_to: Option<AccountId>,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// NEP-141 receiver hook
+    /// Resolvers fund a Fusion+ order with fungible tokens via `ft_transfer_call`,
+    /// passing `FusionOrderParams` as JSON in `msg`. Any surplus over
+    /// amount + resolver fee + safety deposit is returned to the resolver.
+    /// Makers deposit the NEP-141 resolver fee of an HTLC order the same way,
+    /// with `{"htlc_order_id": ...}` as `msg`, and solvers fill NEAR Intents
+    /// orders paying the token with `{"intent": ...}` (see `FusionIntent`).
+    /// Resolvers post a safety deposit in an accepted token before executing
+    /// an order with `{"safety_deposit_for": order_hash}`, and take a share of a
+    /// multi-part order with `{"partial_fill_of": order_hash, "fill_amount": ...}`.
+    pub fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        let token = env::predecessor_account_id();
+        if let Ok(deposit) = serde_json::from_str::<HtlcFeeDeposit>(&msg) {
+            let surplus = self.internal_deposit_htlc_fee(deposit.htlc_order_id, token, sender_id, amount.0);
+            return PromiseOrValue::Value(U128(surplus));
+        }
+        if let Ok(message) = serde_json::from_str::<SafetyDepositMessage>(&msg) {
+            self.internal_post_token_safety_deposit(message.safety_deposit_for, token, sender_id, amount.0);
+            return PromiseOrValue::Value(U128(0));
+        }
+        if let Ok(message) = serde_json::from_str::<PartialFillMessage>(&msg) {
+            let fill = self.internal_execute_partial_fill(
+                message.partial_fill_of,
+                message.fill_amount,
+                sender_id,
+                Some(token),
+                amount.0,
+            );
+            return PromiseOrValue::Value(U128(amount.0 - self.internal_order_deposit(&fill)));
+        }
+        let params = match serde_json::from_str::<IntentMessage>(&msg) {
+            Ok(message) => {
+                let (params, intent_token) = Self::intent_order_params(message.intent);
+                assert_eq!(intent_token.as_ref(), Some(&token), "Intent asset does not match the transferred token");
+                params
+            }
+            Err(_) => serde_json::from_str(&msg).expect("Invalid fusion order message"),
+        };
+
+        let order = self.internal_create_fusion_order(params, sender_id, Some(token), amount.0);
+
+        PromiseOrValue::Value(U128(amount.0 - self.internal_order_deposit(&order)))
+    }
+}
@@ -0,0 +1,85 @@
+use near_sdk::env;
+
+/// Shared with the other escrows through the `fusion-hashlock` crate
+pub use fusion_hashlock::{decode_hex32, HashAlgorithm};
+
+/// NEAR host hash functions, cheaper than hashing in wasm
+pub struct NearHasher;
+
+impl fusion_hashlock::Hasher for NearHasher {
+    fn sha256(&self, data: &[u8]) -> [u8; 32] {
+        env::sha256_array(data)
+    }
+
+    fn keccak256(&self, data: &[u8]) -> [u8; 32] {
+        env::keccak256_array(data)
+    }
+}
+
+/// `HashAlgorithm` hashing with the host functions
+pub trait HashlockExt {
+    /// Hash raw preimage bytes
+    fn hash(&self, preimage: &[u8]) -> [u8; 32];
+
+    /// Hex-encoded hashlock for a hex-encoded preimage (optional `0x` prefix)
+    fn hashlock_for(&self, preimage: &str) -> String;
+}
+
+impl HashlockExt for HashAlgorithm {
+    fn hash(&self, preimage: &[u8]) -> [u8; 32] {
+        self.hashlock(&NearHasher, preimage)
+    }
+
+    fn hashlock_for(&self, preimage: &str) -> String {
+        let preimage_bytes = fusion_hashlock::decode_hex(preimage).expect("Invalid preimage hex");
+        hex::encode(self.hash(&preimage_bytes))
+    }
+}
+
+/// Canonical form of a 32-byte hex value: lowercase, without `0x` prefix
+/// Returns `None` unless the value is exactly 64 hex digits
+pub fn normalize_hex32(value: &str) -> Option<String> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    if digits.len() == 64 && digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        Some(digits.to_ascii_lowercase())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_hex32() {
+        let digits = "ab".repeat(32);
+        assert_eq!(normalize_hex32(&digits), Some(digits.clone()));
+        assert_eq!(normalize_hex32(&format!("0x{}", "AB".repeat(32))), Some(digits));
+        assert_eq!(normalize_hex32(&"z".repeat(64)), None);
+        assert_eq!(normalize_hex32(&"a".repeat(63)), None);
+        assert_eq!(normalize_hex32(&format!("0x{}", "a".repeat(62))), None);
+    }
+
+    #[test]
+    fn test_shared_fixtures() {
+        let fixtures: serde_json::Value =
+            serde_json::from_str(include_str!("../../shared/fixtures/hashlocks.json")).unwrap();
+        for case in fixtures["cases"].as_array().unwrap() {
+            let preimage = case["preimage"].as_str().unwrap();
+            for (algorithm, key) in [(HashAlgorithm::Sha256, "sha256"), (HashAlgorithm::Keccak256, "keccak256")] {
+                let expected = case[key].as_str().unwrap().trim_start_matches("0x");
+                assert_eq!(algorithm.hashlock_for(preimage), expected, "{} {}", case["name"], key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_hex32() {
+        assert_eq!(decode_hex32(&format!("0x{}", "AB".repeat(32))), Some([0xab; 32]));
+        assert_eq!(decode_hex32(&"a".repeat(62)), None);
+    }
+}
@@ -0,0 +1,1245 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, NearToken, Promise, PromiseError};
+use schemars::JsonSchema;
+
+use crate::events::{
+    FusionEvent, HtlcOrderCancelledEvent, HtlcOrderClaimedEvent, HtlcOrderCreatedEvent,
+    HtlcOrderMatchedEvent, PayoutEvent,
+};
+use crate::hashlock::{normalize_hex32, HashAlgorithm, HashlockExt};
+use crate::payout::{Payout, GAS_FOR_ON_PAYOUT};
+use crate::bidding::Bid;
+use crate::{FusionPlusNear, FusionPlusNearExt};
+
+/// Open HTLC orders a maker may have unless changed by the owner
+pub const DEFAULT_MAX_OPEN_HTLC_ORDERS: u32 = 20;
+/// Smallest locked amount of an HTLC order unless changed by the owner
+pub const DEFAULT_MIN_HTLC_ORDER_AMOUNT: u128 = NearToken::from_millinear(10).as_yoctonear();
+
+/// Anti-spam limits on maker-created HTLC orders
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HtlcLimits {
+    /// Unsettled orders one maker may have at a time
+    pub max_open_orders_per_maker: u32,
+    /// Minimum locked amount, excluding the resolver fee
+    #[schemars(with = "String")]
+    pub min_order_amount: U128,
+}
+
+/// How an HTLC order's timelock is interpreted
+/// Timestamp mode lets deadlines line up with the Ethereum and Cosmos escrows
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, JsonSchema, PartialEq, Debug, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub enum TimelockMode {
+    /// Timelock is a block height
+    #[default]
+    BlockHeight,
+    /// Timelock is a unix timestamp in seconds
+    Timestamp,
+}
+
+/// Dutch auction on an HTLC order's resolver fee
+/// The fee decays linearly from the order's `resolver_fee` to `floor_fee`
+/// and is fixed when a resolver matches the order
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeAuction {
+    #[schemars(with = "String")]
+    pub floor_fee: U128,
+    /// Seconds from order creation until the fee reaches `floor_fee`
+    pub duration: u64,
+}
+
+impl FeeAuction {
+    /// Fee `elapsed` seconds into the auction, starting from `start_fee`
+    pub fn fee_at(&self, start_fee: u128, elapsed: u64) -> u128 {
+        let elapsed = elapsed.min(self.duration) as u128;
+        start_fee - (start_fee - self.floor_fee.0) * elapsed / self.duration as u128
+    }
+}
+
+/// Window in which only the resolver that quoted an HTLC order may match it
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Exclusivity {
+    #[schemars(with = "String")]
+    pub resolver: AccountId,
+    /// Unix timestamp (seconds) after which any authorized resolver may match
+    pub deadline: u64,
+}
+
+/// HTLC order created by a maker locking NEAR (NEAR as source chain)
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HTLCOrder {
+    pub id: String,
+    #[schemars(with = "String")]
+    pub maker: AccountId,
+    #[schemars(with = "Option<String>")]
+    pub resolver: Option<AccountId>,
+    /// Locked NEAR, excluding the resolver fee
+    #[schemars(with = "String")]
+    pub amount: U128,
+    /// 32-byte hex hash of the preimage
+    pub hashlock: String,
+    pub hash_algorithm: HashAlgorithm,
+    /// Block height or unix timestamp (seconds), see `timelock_mode`
+    #[schemars(with = "String")]
+    pub timelock: U64,
+    pub timelock_mode: TimelockMode,
+    pub destination_chain: String,
+    pub destination_token: String,
+    #[schemars(with = "String")]
+    pub destination_amount: U128,
+    pub destination_address: String,
+    #[schemars(with = "String")]
+    pub resolver_fee: U128,
+    /// Deposited by the resolver when matching
+    #[schemars(with = "String")]
+    pub safety_deposit: U128,
+    pub is_claimed: bool,
+    pub is_refunded: bool,
+    /// 32-byte hex preimage when revealed
+    pub preimage: Option<String>,
+    /// Payouts whose transfer failed and can be retried
+    pub failed_payouts: Vec<Payout>,
+    /// Decaying resolver fee, `None` for a fixed fee
+    pub fee_auction: Option<FeeAuction>,
+    /// Block timestamp (seconds) the fee auction starts from
+    pub created_at: u64,
+    /// Preferred resolver and the end of its exclusive matching window
+    pub exclusivity: Option<Exclusivity>,
+    /// Unix timestamp (seconds) the bidding window closes, `None` without bidding
+    pub bidding_ends_at: Option<u64>,
+    /// Lowest bid so far, holding the bidder's safety deposit
+    pub best_bid: Option<Bid>,
+    /// NEP-141 token the resolver fee is paid in, `None` for NEAR
+    #[schemars(with = "Option<String>")]
+    pub fee_token: Option<AccountId>,
+    /// Whether the maker deposited the NEP-141 resolver fee
+    pub fee_deposited: bool,
+    /// NEP-141 fee transfers that failed and can be retried
+    pub failed_fee_payouts: Vec<Payout>,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Create an HTLC order locking the attached NEAR minus `resolver_fee`
+    /// timelock_mode defaults to BlockHeight, hash_algorithm to Sha256.
+    /// With `fee_auction`, `resolver_fee` is the starting fee of a Dutch
+    /// auction; the part not earned by the matching resolver goes back to the maker.
+    /// With `exclusivity`, only the preferred resolver may match before its deadline.
+    /// With `bidding_window` (seconds), resolvers bid for the order with
+    /// `place_bid` and the lowest fee wins once the window closes.
+    /// With `fee_token`, `resolver_fee` is an amount of that NEP-141 token, the
+    /// attached NEAR is locked in full and the maker deposits the fee with
+    /// `ft_transfer_call` before the order can be matched.
+    /// Order storage is paid from the maker's NEP-145 balance.
+    /// The locked amount must reach `min_order_amount` and the maker may have
+    /// at most `max_open_orders_per_maker` unsettled orders (see `get_htlc_limits`).
+    #[payable]
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_order(
+        &mut self,
+        order_id: String,
+        hashlock: String,
+        timelock: U64,
+        destination_chain: String,
+        destination_token: String,
+        destination_amount: U128,
+        destination_address: String,
+        resolver_fee: U128,
+        timelock_mode: Option<TimelockMode>,
+        hash_algorithm: Option<HashAlgorithm>,
+        fee_auction: Option<FeeAuction>,
+        exclusivity: Option<Exclusivity>,
+        bidding_window: Option<u64>,
+        fee_token: Option<AccountId>,
+    ) -> HTLCOrder {
+        assert!(!self.paused, "Contract is paused");
+        let deposit_amount = env::attached_deposit().as_yoctonear();
+        let native_fee = if fee_token.is_some() {
+            assert!(
+                fee_auction.is_none() && bidding_window.is_none(),
+                "Token fees cannot be combined with a fee auction or bidding"
+            );
+            0
+        } else {
+            assert!(deposit_amount > resolver_fee.0, "Insufficient deposit for resolver fee");
+            resolver_fee.0
+        };
+        if let Some(auction) = &fee_auction {
+            assert!(auction.duration > 0, "Auction duration must be positive");
+            assert!(auction.floor_fee.0 <= resolver_fee.0, "Floor fee above resolver fee");
+        }
+        if let Some(bidding_window) = bidding_window {
+            assert!(bidding_window > 0, "Bidding window must be positive");
+            assert!(
+                fee_auction.is_none() && exclusivity.is_none(),
+                "Bidding cannot be combined with a fee auction or exclusivity"
+            );
+        }
+
+        let timelock_mode = timelock_mode.unwrap_or_default();
+        assert!(timelock.0 > Self::current_time(timelock_mode), "Timelock must be in the future");
+        let hashlock = normalize_hex32(&hashlock).expect("Hashlock must be 32 bytes (64 hex chars)");
+        assert!(self.htlc_orders.get(&order_id).is_none(), "Order ID already exists");
+        assert!(
+            deposit_amount - native_fee >= self.min_htlc_order_amount,
+            "Order amount below minimum"
+        );
+        let maker = env::predecessor_account_id();
+        self.assert_allowed_maker(&maker);
+        let open_orders = self.open_htlc_orders.get(&maker).unwrap_or(0);
+        assert!(open_orders < self.max_open_htlc_orders, "Too many open orders");
+
+        let order = HTLCOrder {
+            id: order_id.clone(),
+            maker,
+            resolver: None,
+            amount: U128(deposit_amount - native_fee),
+            hashlock,
+            hash_algorithm: hash_algorithm.unwrap_or_default(),
+            timelock,
+            timelock_mode,
+            destination_chain,
+            destination_token,
+            destination_amount,
+            destination_address,
+            resolver_fee,
+            safety_deposit: U128(0),
+            is_claimed: false,
+            is_refunded: false,
+            preimage: None,
+            failed_payouts: Vec::new(),
+            fee_auction,
+            created_at: Self::now_seconds(),
+            exclusivity,
+            bidding_ends_at: bidding_window.map(|bidding_window| Self::now_seconds() + bidding_window),
+            best_bid: None,
+            fee_token,
+            fee_deposited: false,
+            failed_fee_payouts: Vec::new(),
+        };
+
+        self.assert_tvl_cap(&None, deposit_amount);
+        self.htlc_locked += deposit_amount;
+
+        let initial_storage_usage = env::storage_usage();
+        self.htlc_orders.insert(&order_id, &order);
+        self.open_htlc_orders.insert(&order.maker, &(open_orders + 1));
+        self.internal_charge_storage(&order.maker, initial_storage_usage);
+
+        FusionEvent::HtlcOrderCreated(vec![HtlcOrderCreatedEvent {
+            order_id,
+            maker: order.maker.clone(),
+            amount: order.amount,
+            hashlock: order.hashlock.clone(),
+            timelock: order.timelock,
+            timelock_mode: order.timelock_mode,
+            destination_chain: order.destination_chain.clone(),
+        }])
+        .emit();
+
+        order
+    }
+
+    /// Resolver commits to fulfilling an HTLC order by attaching a safety deposit
+    /// of at least `min_safety_deposit_bps` of the order amount
+    /// During an exclusivity window only the preferred resolver may match
+    /// An auctioned resolver fee is fixed at its current value
+    /// Orders taking bids can only be matched once bidding closed without bids
+    #[payable]
+    pub fn match_order(&mut self, order_id: String) -> HTLCOrder {
+        assert!(!self.paused, "Contract is paused");
+        let resolver = env::predecessor_account_id();
+        self.assert_bonded_resolver(&resolver, "Not an authorized resolver");
+
+        let order = self.htlc_orders.get(&order_id).expect("Order not found");
+        assert!(order.resolver.is_none(), "Order already matched");
+        assert!(!order.is_claimed && !order.is_refunded, "Order already settled");
+        assert!(!Self::is_expired(&order), "Order expired");
+        assert!(order.fee_token.is_none() || order.fee_deposited, "Resolver fee not deposited");
+        if let Some(exclusivity) = &order.exclusivity {
+            assert!(
+                exclusivity.resolver == resolver || Self::now_seconds() >= exclusivity.deadline,
+                "Order is exclusive to another resolver"
+            );
+        }
+        if let Some(bidding_ends_at) = order.bidding_ends_at {
+            assert!(
+                Self::now_seconds() >= bidding_ends_at && order.best_bid.is_none(),
+                "Order is matched through bidding"
+            );
+        }
+
+        let safety_deposit = env::attached_deposit().as_yoctonear();
+        self.assert_htlc_safety_deposit(&order, safety_deposit);
+
+        let resolver_fee = Self::current_resolver_fee(&order);
+        self.liabilities.htlc_deposits += safety_deposit;
+        self.internal_match_htlc_order(&order_id, order, resolver, safety_deposit, resolver_fee)
+    }
+
+    /// Resolver reveals the preimage before expiry and receives the locked
+    /// amount, the resolver fee and its safety deposit
+    pub fn claim_order(&mut self, order_id: String, preimage: String) -> Promise {
+        let resolver = env::predecessor_account_id();
+        let mut order = self.htlc_orders.get(&order_id).expect("Order not found");
+
+        assert_eq!(order.resolver.as_ref(), Some(&resolver), "Not the resolver");
+        assert!(!order.is_claimed && !order.is_refunded, "Order already settled");
+        assert!(!Self::is_expired(&order), "Order expired");
+        let preimage = normalize_hex32(&preimage).expect("Preimage must be 32 bytes (64 hex chars)");
+        assert_eq!(
+            order.hash_algorithm.hashlock_for(&preimage),
+            order.hashlock,
+            "Preimage doesn't match hashlock"
+        );
+
+        order.is_claimed = true;
+        order.preimage = Some(preimage.clone());
+        self.htlc_orders.insert(&order_id, &order);
+        self.internal_close_htlc_order(&order);
+
+        FusionEvent::HtlcOrderClaimed(vec![HtlcOrderClaimedEvent {
+            order_id: order_id.clone(),
+            resolver: resolver.clone(),
+            preimage,
+        }])
+        .emit();
+
+        let protocol_fee = self.internal_take_protocol_fee(&None, order.amount.0);
+        match order.fee_token {
+            Some(fee_token) => {
+                let total_payout = order.amount.0 - protocol_fee + order.safety_deposit.0;
+                Self::internal_htlc_payout(&order_id, resolver.clone(), total_payout).and(
+                    Self::internal_htlc_fee_payout(&order_id, fee_token, resolver, order.resolver_fee.0),
+                )
+            }
+            None => {
+                let total_payout = order.amount.0 - protocol_fee + order.resolver_fee.0 + order.safety_deposit.0;
+                Self::internal_htlc_payout(&order_id, resolver, total_payout)
+            }
+        }
+    }
+
+    /// Maker cancels after the timelock expires; the resolver's safety
+    /// deposit is returned if the order was matched, and a deposited
+    /// NEP-141 fee goes back to the maker
+    pub fn cancel_order(&mut self, order_id: String) -> Promise {
+        let maker = env::predecessor_account_id();
+        let mut order = self.htlc_orders.get(&order_id).expect("Order not found");
+
+        assert_eq!(order.maker, maker, "Not the order maker");
+        assert!(!order.is_claimed && !order.is_refunded, "Order already settled");
+        assert!(Self::is_expired(&order), "Timelock not yet expired");
+
+        order.is_refunded = true;
+        self.htlc_orders.insert(&order_id, &order);
+        self.internal_close_htlc_order(&order);
+
+        let native_fee = if order.fee_token.is_some() { 0 } else { order.resolver_fee.0 };
+        let refund_amount = order.amount.0 + native_fee;
+        FusionEvent::HtlcOrderCancelled(vec![HtlcOrderCancelledEvent {
+            order_id: order_id.clone(),
+            maker: maker.clone(),
+            amount: U128(refund_amount),
+        }])
+        .emit();
+
+        let mut refund = Self::internal_htlc_payout(&order_id, maker.clone(), refund_amount);
+        if let (Some(fee_token), true) = (order.fee_token, order.fee_deposited) {
+            refund = refund.and(Self::internal_htlc_fee_payout(&order_id, fee_token, maker, order.resolver_fee.0));
+        }
+        match (order.resolver, order.best_bid) {
+            (Some(resolver), _) => refund.and(Self::internal_htlc_payout(
+                &order_id,
+                resolver,
+                order.safety_deposit.0,
+            )),
+            // Bidding never closed, return the best bidder's deposit
+            (None, Some(bid)) => refund.and(Self::internal_htlc_payout(
+                &order_id,
+                bid.resolver,
+                bid.safety_deposit.0,
+            )),
+            (None, None) => refund,
+        }
+    }
+
+    /// Callback after each HTLC payout transfer
+    /// A failed transfer is recorded on the order so it can be retried
+    #[private]
+    pub fn on_htlc_payout(
+        &mut self,
+        order_id: String,
+        receiver: AccountId,
+        amount: U128,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        if result.is_ok() {
+            FusionEvent::PayoutSent(vec![PayoutEvent {
+                order_hash: order_id,
+                receiver,
+                amount,
+            }])
+            .emit();
+            return true;
+        }
+
+        let mut order = self.htlc_orders.get(&order_id).expect("Order not found");
+        order.failed_payouts.push(Payout {
+            receiver: receiver.clone(),
+            amount,
+        });
+        self.htlc_orders.insert(&order_id, &order);
+        self.internal_record_failed_payout(amount.0);
+
+        FusionEvent::PayoutFailed(vec![PayoutEvent {
+            order_hash: order_id,
+            receiver,
+            amount,
+        }])
+        .emit();
+        false
+    }
+
+    /// Re-send HTLC payouts that failed (maker or resolver)
+    pub fn retry_htlc_payouts(&mut self, order_id: String) -> Promise {
+        let mut order = self.htlc_orders.get(&order_id).expect("Order not found");
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == order.maker || order.resolver.as_ref() == Some(&caller),
+            "Only maker or resolver can retry payouts"
+        );
+        assert!(
+            !order.failed_payouts.is_empty() || !order.failed_fee_payouts.is_empty(),
+            "No failed payouts"
+        );
+
+        let payouts = std::mem::take(&mut order.failed_payouts);
+        let fee_payouts = std::mem::take(&mut order.failed_fee_payouts);
+        self.htlc_orders.insert(&order_id, &order);
+        self.internal_record_retried_payouts(&payouts);
+
+        let fee_token = order.fee_token;
+        payouts
+            .into_iter()
+            .map(|payout| Self::internal_htlc_payout(&order_id, payout.receiver, payout.amount.0))
+            .chain(fee_payouts.into_iter().map(|payout| {
+                let fee_token = fee_token.clone().expect("No fee token");
+                Self::internal_htlc_fee_payout(&order_id, fee_token, payout.receiver, payout.amount.0)
+            }))
+            .reduce(|all, next| all.and(next))
+            .unwrap()
+    }
+
+    pub fn get_htlc_order(&self, order_id: String) -> Option<HTLCOrder> {
+        self.htlc_orders.get(&order_id)
+    }
+
+    /// Resolver fee a resolver matching the order now would earn
+    pub fn get_htlc_resolver_fee(&self, order_id: String) -> Option<U128> {
+        let order = self.htlc_orders.get(&order_id)?;
+        Some(U128(Self::current_resolver_fee(&order)))
+    }
+
+    /// Set the open order cap per maker and the minimum order amount
+    #[payable]
+    pub fn set_htlc_limits(&mut self, max_open_orders_per_maker: u32, min_order_amount: U128) {
+        self.assert_owner();
+        self.max_open_htlc_orders = max_open_orders_per_maker;
+        self.min_htlc_order_amount = min_order_amount.0;
+    }
+
+    pub fn get_htlc_limits(&self) -> HtlcLimits {
+        HtlcLimits {
+            max_open_orders_per_maker: self.max_open_htlc_orders,
+            min_order_amount: U128(self.min_htlc_order_amount),
+        }
+    }
+
+    /// Unsettled HTLC orders of `maker`
+    pub fn get_open_htlc_orders(&self, maker: AccountId) -> u32 {
+        self.open_htlc_orders.get(&maker).unwrap_or(0)
+    }
+
+    pub fn get_resolver_count(&self) -> u64 {
+        self.authorized_resolvers.len()
+    }
+}
+
+impl FusionPlusNear {
+    /// Drop a settled order from its maker's open order count and the locked
+    /// totals, including the deposit of its resolver or best bidder
+    /// Storage freed by the open order count goes back to the maker
+    fn internal_close_htlc_order(&mut self, order: &HTLCOrder) {
+        let native_fee = if order.fee_token.is_some() { 0 } else { order.resolver_fee.0 };
+        self.htlc_locked = self.htlc_locked.saturating_sub(order.amount.0 + native_fee);
+        let best_bid_deposit = order.best_bid.as_ref().map_or(0, |bid| bid.safety_deposit.0);
+        self.liabilities.htlc_deposits = self
+            .liabilities
+            .htlc_deposits
+            .saturating_sub(order.safety_deposit.0 + best_bid_deposit);
+        let initial_storage_usage = env::storage_usage();
+        match self.open_htlc_orders.get(&order.maker).unwrap_or(0) {
+            0 | 1 => {
+                self.open_htlc_orders.remove(&order.maker);
+            }
+            open_orders => {
+                self.open_htlc_orders.insert(&order.maker, &(open_orders - 1));
+            }
+        }
+        self.internal_release_storage(&order.maker, initial_storage_usage.saturating_sub(env::storage_usage()));
+    }
+
+    fn current_time(mode: TimelockMode) -> u64 {
+        match mode {
+            TimelockMode::BlockHeight => env::block_height(),
+            TimelockMode::Timestamp => Self::now_seconds(),
+        }
+    }
+
+    /// Panic unless `safety_deposit` covers `min_safety_deposit_bps` of the order amount
+    pub(crate) fn assert_htlc_safety_deposit(&self, order: &HTLCOrder, safety_deposit: u128) {
+        let required_deposit = order.amount.0 * self.min_safety_deposit_bps as u128 / 10_000;
+        assert!(safety_deposit >= required_deposit, "Insufficient safety deposit");
+    }
+
+    /// Assign `resolver` to the order at `resolver_fee`, refunding the rest
+    /// of the fee the maker deposited
+    pub(crate) fn internal_match_htlc_order(
+        &mut self,
+        order_id: &str,
+        mut order: HTLCOrder,
+        resolver: AccountId,
+        safety_deposit: u128,
+        resolver_fee: u128,
+    ) -> HTLCOrder {
+        let fee_refund = order.resolver_fee.0 - resolver_fee;
+        order.resolver = Some(resolver.clone());
+        order.safety_deposit = U128(safety_deposit);
+        order.resolver_fee = U128(resolver_fee);
+        self.htlc_orders.insert(&order_id.to_string(), &order);
+
+        FusionEvent::HtlcOrderMatched(vec![HtlcOrderMatchedEvent {
+            order_id: order_id.to_string(),
+            resolver,
+            safety_deposit: order.safety_deposit,
+            resolver_fee: order.resolver_fee,
+        }])
+        .emit();
+
+        if fee_refund > 0 {
+            self.htlc_locked = self.htlc_locked.saturating_sub(fee_refund);
+            let _ = Self::internal_htlc_payout(order_id, order.maker.clone(), fee_refund);
+        }
+        order
+    }
+
+    /// Auctioned fee at the current time, the stored fee once matched or without auction
+    fn current_resolver_fee(order: &HTLCOrder) -> u128 {
+        match (&order.fee_auction, &order.resolver) {
+            (Some(auction), None) => {
+                auction.fee_at(order.resolver_fee.0, Self::now_seconds().saturating_sub(order.created_at))
+            }
+            _ => order.resolver_fee.0,
+        }
+    }
+
+    pub(crate) fn is_expired(order: &HTLCOrder) -> bool {
+        Self::current_time(order.timelock_mode) >= order.timelock.0
+    }
+
+    pub(crate) fn internal_htlc_payout(order_id: &str, receiver: AccountId, amount: u128) -> Promise {
+        Promise::new(receiver.clone())
+            .transfer(NearToken::from_yoctonear(amount))
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_PAYOUT)
+                    .on_htlc_payout(order_id.to_string(), receiver, U128(amount)),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+    use crate::cleanup::DEFAULT_ORDER_RETENTION;
+
+    const DESTINATION_ADDRESS: &str = "0x742d35Cc6Bf8f4A1b7BE8b6F8f8f8f8f8f8f8f8f";
+
+    fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id(accounts(0))
+            .signer_account_id(predecessor_account_id.clone())
+            .predecessor_account_id(predecessor_account_id)
+            .attached_deposit(NearToken::from_yoctonear(1));
+        builder
+    }
+
+    /// Contract with accounts(2) as bonded resolver and accounts(1) registered as maker
+    fn setup_contract() -> FusionPlusNear {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = FusionPlusNear::new(1000); // 10% safety deposit
+        contract.add_resolver(accounts(2));
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(5))
+            .build());
+        contract.bond_resolver();
+
+        let mut context = get_context(accounts(1));
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(100))
+            .build());
+        contract.storage_deposit(None, None);
+        contract
+    }
+
+    fn create_test_order(
+        contract: &mut FusionPlusNear,
+        order_id: &str,
+        hashlock: String,
+        timelock_mode: Option<TimelockMode>,
+        hash_algorithm: Option<HashAlgorithm>,
+    ) -> HTLCOrder {
+        contract.create_order(
+            order_id.to_string(),
+            hashlock,
+            U64(match timelock_mode {
+                Some(TimelockMode::Timestamp) => 1_700_003_600,
+                _ => 200,
+            }),
+            "ethereum".to_string(),
+            "USDC".to_string(),
+            U128(100_000_000), // 100 USDC (6 decimals)
+            DESTINATION_ADDRESS.to_string(),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            timelock_mode,
+            hash_algorithm,
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+
+    fn maker_context(deposit: NearToken) {
+        let mut context = get_context(accounts(1));
+        testing_env!(context
+            .attached_deposit(deposit)
+            .block_height(100)
+            .block_timestamp(1_700_000_000 * 1_000_000_000)
+            .build());
+    }
+
+    #[test]
+    fn test_create_order() {
+        let mut contract = setup_contract();
+        maker_context(NearToken::from_near(1));
+
+        let order = create_test_order(&mut contract, "test-order", "a".repeat(64), None, None);
+
+        assert_eq!(order.id, "test-order");
+        assert_eq!(order.maker, accounts(1));
+        assert_eq!(order.amount.0, NearToken::from_millinear(900).as_yoctonear()); // 1 NEAR - 0.1 NEAR fee
+        assert!(!order.is_claimed);
+        assert!(!order.is_refunded);
+        assert!(order.resolver.is_none());
+        assert_eq!(contract.get_htlc_order("test-order".to_string()).unwrap().destination_chain, "ethereum");
+        assert!(contract.get_htlc_order("nonexistent".to_string()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient deposit for resolver fee")]
+    fn test_create_order_insufficient_deposit() {
+        let mut contract = setup_contract();
+        maker_context(NearToken::from_millinear(50)); // Less than the 0.1 NEAR fee
+
+        create_test_order(&mut contract, "test-order", "a".repeat(64), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Hashlock must be 32 bytes (64 hex chars)")]
+    fn test_create_order_non_hex_hashlock() {
+        let mut contract = setup_contract();
+        maker_context(NearToken::from_near(1));
+
+        create_test_order(&mut contract, "test-order", "z".repeat(64), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Account not registered for storage")]
+    fn test_create_order_requires_storage_registration() {
+        let mut contract = setup_contract();
+        let mut context = get_context(accounts(3));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(1))
+            .block_height(100)
+            .build());
+
+        create_test_order(&mut contract, "test-order", "a".repeat(64), None, None);
+    }
+
+    #[test]
+    fn test_match_order() {
+        let mut contract = setup_contract();
+        maker_context(NearToken::from_near(1));
+        create_test_order(&mut contract, "test-order", "a".repeat(64), None, None);
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(90))
+            .block_height(150)
+            .build());
+        let matched_order = contract.match_order("test-order".to_string());
+
+        assert_eq!(matched_order.resolver, Some(accounts(2)));
+        assert_eq!(matched_order.safety_deposit.0, NearToken::from_millinear(90).as_yoctonear());
+    }
+
+    #[test]
+    #[should_panic(expected = "Order expired")]
+    fn test_match_order_expired_by_timestamp() {
+        let mut contract = setup_contract();
+        maker_context(NearToken::from_near(1));
+        let order = create_test_order(
+            &mut contract,
+            "ts-order",
+            "a".repeat(64),
+            Some(TimelockMode::Timestamp),
+            None,
+        );
+        assert_eq!(order.timelock_mode, TimelockMode::Timestamp);
+
+        // Block height barely moved but the deadline has passed
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(90))
+            .block_height(101)
+            .block_timestamp(1_700_003_600 * 1_000_000_000)
+            .build());
+        contract.match_order("ts-order".to_string());
+    }
+
+    #[test]
+    fn test_claim_order_keccak256() {
+        let mut contract = setup_contract();
+        maker_context(NearToken::from_near(1));
+        let preimage = "22".repeat(32);
+        let hashlock = hex::encode(env::keccak256(&hex::decode(&preimage).unwrap()[..]));
+        create_test_order(
+            &mut contract,
+            "keccak-order",
+            hashlock,
+            None,
+            Some(HashAlgorithm::Keccak256),
+        );
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(90))
+            .block_height(150)
+            .build());
+        contract.match_order("keccak-order".to_string());
+        let _ = contract.claim_order("keccak-order".to_string(), preimage.clone());
+
+        let order = contract.get_htlc_order("keccak-order".to_string()).unwrap();
+        assert!(order.is_claimed);
+        assert_eq!(order.preimage, Some(preimage));
+    }
+
+    #[test]
+    fn test_failed_refund_is_recorded_and_retried() {
+        let mut contract = setup_contract();
+        maker_context(NearToken::from_near(1));
+        create_test_order(&mut contract, "test-order", "a".repeat(64), None, None);
+
+        // Maker cancels after expiry
+        let mut context = get_context(accounts(1));
+        testing_env!(context.block_height(200).build());
+        let _ = contract.cancel_order("test-order".to_string());
+
+        // Refund transfer fails
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let refund = U128(NearToken::from_near(1).as_yoctonear());
+        assert!(!contract.on_htlc_payout("test-order".to_string(), accounts(1), refund, Err(PromiseError::Failed)));
+
+        let order = contract.get_htlc_order("test-order".to_string()).unwrap();
+        assert!(order.is_refunded);
+        assert_eq!(order.failed_payouts, vec![Payout { receiver: accounts(1), amount: refund }]);
+
+        // Maker retries the refund
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        let _ = contract.retry_htlc_payouts("test-order".to_string());
+        assert!(contract.get_htlc_order("test-order".to_string()).unwrap().failed_payouts.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn test_create_order_while_paused() {
+        let mut contract = setup_contract();
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        contract.pause();
+
+        maker_context(NearToken::from_near(1));
+        create_test_order(&mut contract, "paused-order", "a".repeat(64), None, None);
+    }
+
+    #[test]
+    fn test_fee_auction_decay() {
+        let auction = FeeAuction {
+            floor_fee: U128(20),
+            duration: 100,
+        };
+        assert_eq!(auction.fee_at(100, 0), 100);
+        assert_eq!(auction.fee_at(100, 50), 60);
+        assert_eq!(auction.fee_at(100, 100), 20);
+        assert_eq!(auction.fee_at(100, 1_000), 20);
+    }
+
+    #[test]
+    fn test_match_fixes_auctioned_fee() {
+        let mut contract = setup_contract();
+        maker_context(NearToken::from_near(1));
+        contract.create_order(
+            "auction-order".to_string(),
+            "a".repeat(64),
+            U64(200),
+            "ethereum".to_string(),
+            "USDC".to_string(),
+            U128(100_000_000),
+            DESTINATION_ADDRESS.to_string(),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            None,
+            None,
+            Some(FeeAuction {
+                floor_fee: U128(NearToken::from_millinear(20).as_yoctonear()),
+                duration: 600,
+            }),
+            None,
+            None,
+            None,
+        );
+
+        // Halfway through the auction the fee is 60 mNEAR, the other 40 mNEAR go back to the maker
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(90))
+            .block_height(150)
+            .block_timestamp(1_700_000_300 * 1_000_000_000)
+            .build());
+        assert_eq!(
+            contract.get_htlc_resolver_fee("auction-order".to_string()),
+            Some(U128(NearToken::from_millinear(60).as_yoctonear()))
+        );
+        let order = contract.match_order("auction-order".to_string());
+
+        assert_eq!(order.resolver_fee.0, NearToken::from_millinear(60).as_yoctonear());
+        let refunds: Vec<AccountId> = near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .filter(|receiver| *receiver == accounts(1))
+            .collect();
+        assert_eq!(refunds.len(), 1);
+
+        // The fee stays fixed once matched
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .block_timestamp(1_700_000_600 * 1_000_000_000)
+            .build());
+        assert_eq!(
+            contract.get_htlc_resolver_fee("auction-order".to_string()),
+            Some(U128(NearToken::from_millinear(60).as_yoctonear()))
+        );
+    }
+
+    fn create_exclusive_order(contract: &mut FusionPlusNear) {
+        maker_context(NearToken::from_near(1));
+        contract.create_order(
+            "exclusive-order".to_string(),
+            "a".repeat(64),
+            U64(200),
+            "ethereum".to_string(),
+            "USDC".to_string(),
+            U128(100_000_000),
+            DESTINATION_ADDRESS.to_string(),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            None,
+            None,
+            None,
+            Some(Exclusivity {
+                resolver: accounts(3),
+                deadline: 1_700_000_060,
+            }),
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Order is exclusive to another resolver")]
+    fn test_match_during_exclusivity_rejected() {
+        let mut contract = setup_contract();
+        create_exclusive_order(&mut contract);
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(90))
+            .block_height(150)
+            .block_timestamp(1_700_000_030 * 1_000_000_000)
+            .build());
+        contract.match_order("exclusive-order".to_string());
+    }
+
+    #[test]
+    fn test_match_after_exclusivity() {
+        let mut contract = setup_contract();
+        create_exclusive_order(&mut contract);
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(90))
+            .block_height(150)
+            .block_timestamp(1_700_000_060 * 1_000_000_000)
+            .build());
+        let order = contract.match_order("exclusive-order".to_string());
+        assert_eq!(order.resolver, Some(accounts(2)));
+    }
+
+    fn create_bidding_order(contract: &mut FusionPlusNear) {
+        // accounts(3) bids against accounts(2)
+        testing_env!(get_context(accounts(1)).build());
+        contract.add_resolver(accounts(3));
+        let mut context = get_context(accounts(3));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(5))
+            .build());
+        contract.bond_resolver();
+
+        maker_context(NearToken::from_near(1));
+        contract.create_order(
+            "bidding-order".to_string(),
+            "a".repeat(64),
+            U64(200),
+            "ethereum".to_string(),
+            "USDC".to_string(),
+            U128(100_000_000),
+            DESTINATION_ADDRESS.to_string(),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            None,
+            None,
+            None,
+            None,
+            Some(60),
+            None,
+        );
+    }
+
+    fn bid(contract: &mut FusionPlusNear, resolver: AccountId, fee_millinear: u128) -> Bid {
+        let mut context = get_context(resolver);
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(90))
+            .block_height(110)
+            .block_timestamp(1_700_000_030 * 1_000_000_000)
+            .build());
+        contract.place_bid(
+            "bidding-order".to_string(),
+            U128(NearToken::from_millinear(fee_millinear).as_yoctonear()),
+        )
+    }
+
+    #[test]
+    fn test_lowest_bid_wins() {
+        let mut contract = setup_contract();
+        create_bidding_order(&mut contract);
+
+        bid(&mut contract, accounts(2), 80);
+        bid(&mut contract, accounts(3), 50);
+        // The outbid resolver gets its safety deposit back
+        let refunds: Vec<AccountId> = near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .filter(|receiver| *receiver != accounts(0))
+            .collect();
+        assert_eq!(refunds, vec![accounts(2)]);
+
+        let mut context = get_context(accounts(4));
+        testing_env!(context
+            .block_height(120)
+            .block_timestamp(1_700_000_060 * 1_000_000_000)
+            .build());
+        let order = contract.close_bidding("bidding-order".to_string());
+        assert_eq!(order.resolver, Some(accounts(3)));
+        assert_eq!(order.resolver_fee.0, NearToken::from_millinear(50).as_yoctonear());
+        assert_eq!(order.safety_deposit.0, NearToken::from_millinear(90).as_yoctonear());
+        assert!(order.best_bid.is_none());
+        // The maker gets back the 50 mNEAR above the winning bid
+        let refunds: Vec<AccountId> = near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .filter(|receiver| *receiver != accounts(0))
+            .collect();
+        assert_eq!(refunds, vec![accounts(1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bid must be lower than the best bid")]
+    fn test_bid_must_undercut() {
+        let mut contract = setup_contract();
+        create_bidding_order(&mut contract);
+
+        bid(&mut contract, accounts(2), 80);
+        bid(&mut contract, accounts(3), 80);
+    }
+
+    #[test]
+    #[should_panic(expected = "Order is matched through bidding")]
+    fn test_match_during_bidding_rejected() {
+        let mut contract = setup_contract();
+        create_bidding_order(&mut contract);
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(90))
+            .block_height(110)
+            .block_timestamp(1_700_000_030 * 1_000_000_000)
+            .build());
+        contract.match_order("bidding-order".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Bidding window still open")]
+    fn test_close_bidding_before_window_ends() {
+        let mut contract = setup_contract();
+        create_bidding_order(&mut contract);
+
+        bid(&mut contract, accounts(2), 80);
+        contract.close_bidding("bidding-order".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Too many open orders")]
+    fn test_open_order_cap() {
+        let mut contract = setup_contract();
+        testing_env!(get_context(accounts(1)).build());
+        contract.set_htlc_limits(2, U128(DEFAULT_MIN_HTLC_ORDER_AMOUNT));
+
+        maker_context(NearToken::from_near(1));
+        create_test_order(&mut contract, "order-1", "a".repeat(64), None, None);
+        create_test_order(&mut contract, "order-2", "b".repeat(64), None, None);
+        assert_eq!(contract.get_open_htlc_orders(accounts(1)), 2);
+        create_test_order(&mut contract, "order-3", "c".repeat(64), None, None);
+    }
+
+    #[test]
+    fn test_settled_orders_free_cap() {
+        let mut contract = setup_contract();
+        testing_env!(get_context(accounts(1)).build());
+        contract.set_htlc_limits(1, U128(DEFAULT_MIN_HTLC_ORDER_AMOUNT));
+
+        maker_context(NearToken::from_near(1));
+        create_test_order(&mut contract, "order-1", "a".repeat(64), None, None);
+
+        let mut context = get_context(accounts(1));
+        testing_env!(context.block_height(300).build());
+        let _ = contract.cancel_order("order-1".to_string());
+        assert_eq!(contract.get_open_htlc_orders(accounts(1)), 0);
+
+        maker_context(NearToken::from_near(1));
+        create_test_order(&mut contract, "order-2", "b".repeat(64), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Order amount below minimum")]
+    fn test_create_order_below_minimum() {
+        let mut contract = setup_contract();
+        // 0.105 NEAR deposit locks 5 mNEAR after the 0.1 NEAR fee
+        maker_context(NearToken::from_millinear(105));
+        create_test_order(&mut contract, "dust-order", "a".repeat(64), None, None);
+    }
+
+    fn create_token_fee_order(contract: &mut FusionPlusNear) {
+        maker_context(NearToken::from_near(1));
+        contract.create_order(
+            "token-fee-order".to_string(),
+            hex::encode(env::sha256(hex::decode("11".repeat(32)).unwrap())),
+            U64(200),
+            "ethereum".to_string(),
+            "USDC".to_string(),
+            U128(100_000_000),
+            DESTINATION_ADDRESS.to_string(),
+            U128(5_000_000), // 5 USDC
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(accounts(4)),
+        );
+    }
+
+    #[test]
+    fn test_resolver_fee_in_token() {
+        let mut contract = setup_contract();
+        create_token_fee_order(&mut contract);
+        let order = contract.get_htlc_order("token-fee-order".to_string()).unwrap();
+        assert_eq!(order.amount.0, NearToken::from_near(1).as_yoctonear());
+
+        // Maker deposits the fee through the USDC contract, the surplus is refunded
+        testing_env!(get_context(accounts(4)).build());
+        let surplus = contract.ft_on_transfer(
+            accounts(1),
+            U128(6_000_000),
+            r#"{"htlc_order_id": "token-fee-order"}"#.to_string(),
+        );
+        assert!(matches!(surplus, near_sdk::PromiseOrValue::Value(U128(1_000_000))));
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(100))
+            .block_height(110)
+            .build());
+        contract.match_order("token-fee-order".to_string());
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context.block_height(120).build());
+        let _ = contract.claim_order("token-fee-order".to_string(), "11".repeat(32));
+
+        // NEAR goes to the resolver directly, the fee through the token contract
+        let receivers: Vec<AccountId> = near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .filter(|receiver| *receiver != accounts(0))
+            .collect();
+        assert_eq!(receivers, vec![accounts(2), accounts(4)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Resolver fee not deposited")]
+    fn test_match_requires_token_fee() {
+        let mut contract = setup_contract();
+        create_token_fee_order(&mut contract);
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(100))
+            .block_height(110)
+            .build());
+        contract.match_order("token-fee-order".to_string());
+    }
+
+    #[test]
+    fn test_htlc_orders_count_towards_native_tvl() {
+        let mut contract = setup_contract();
+        maker_context(NearToken::from_near(1));
+        create_test_order(&mut contract, "tvl_order", "a".repeat(64), None, None);
+        assert_eq!(contract.get_tvl(None).locked.0, NearToken::from_near(1).as_yoctonear());
+
+        // Amount and fee are released when the maker cancels
+        let mut context = get_context(accounts(1));
+        testing_env!(context.block_height(300).build());
+        let _ = contract.cancel_order("tvl_order".to_string());
+        assert_eq!(contract.get_tvl(None).locked.0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "TVL cap exceeded")]
+    fn test_htlc_order_above_tvl_cap() {
+        let mut contract = setup_contract();
+        testing_env!(get_context(accounts(1)).build());
+        contract.set_tvl_cap(None, Some(U128(NearToken::from_millinear(500).as_yoctonear())));
+
+        maker_context(NearToken::from_near(1));
+        create_test_order(&mut contract, "capped_order", "a".repeat(64), None, None);
+    }
+
+    #[test]
+    fn test_solvency_follows_bidding_order() {
+        let mut contract = setup_contract();
+        create_bidding_order(&mut contract);
+        bid(&mut contract, accounts(2), 80);
+        bid(&mut contract, accounts(3), 50);
+        // Only the best bid's deposit is still held
+        let solvency = contract.get_solvency();
+        assert_eq!(solvency.htlc_locked.0, NearToken::from_near(1).as_yoctonear());
+        assert_eq!(solvency.htlc_deposits.0, NearToken::from_millinear(90).as_yoctonear());
+        assert_eq!(solvency.resolver_bonds.0, NearToken::from_near(10).as_yoctonear());
+
+        // The fee refunded to the maker at close is no longer owed
+        let mut context = get_context(accounts(4));
+        testing_env!(context
+            .block_height(120)
+            .block_timestamp(1_700_000_060 * 1_000_000_000)
+            .build());
+        contract.close_bidding("bidding-order".to_string());
+        let solvency = contract.get_solvency();
+        assert_eq!(solvency.htlc_locked.0, NearToken::from_millinear(950).as_yoctonear());
+        assert_eq!(solvency.htlc_deposits.0, NearToken::from_millinear(90).as_yoctonear());
+
+        let mut context = get_context(accounts(1));
+        testing_env!(context.block_height(200).build());
+        let _ = contract.cancel_order("bidding-order".to_string());
+        let solvency = contract.get_solvency();
+        assert_eq!(solvency.htlc_locked.0, 0);
+        assert_eq!(solvency.htlc_deposits.0, 0);
+    }
+
+    #[test]
+    fn test_cleanup_settled_order_refunds_storage() {
+        let mut contract = setup_contract();
+        let available = contract.storage_balance_of(accounts(1)).unwrap().available;
+        maker_context(NearToken::from_near(1));
+        create_test_order(&mut contract, "test-order", "a".repeat(64), None, None);
+        assert!(contract.storage_balance_of(accounts(1)).unwrap().available.0 < available.0);
+
+        // Open orders are kept
+        assert!(contract.cleanup_htlc_orders(vec!["test-order".to_string()]).is_empty());
+
+        let mut context = get_context(accounts(1));
+        testing_env!(context.block_height(200).build());
+        let _ = contract.cancel_order("test-order".to_string());
+        // Settled orders are kept for the retention period
+        assert!(contract.cleanup_htlc_orders(vec!["test-order".to_string()]).is_empty());
+
+        let mut context = get_context(accounts(4));
+        testing_env!(context
+            .block_timestamp((1_700_000_000 + DEFAULT_ORDER_RETENTION) * 1_000_000_000)
+            .build());
+        let cleaned = contract.cleanup_htlc_orders(vec!["test-order".to_string(), "unknown".to_string()]);
+        assert_eq!(cleaned, vec!["test-order".to_string()]);
+        assert!(contract.get_htlc_order("test-order".to_string()).is_none());
+        assert_eq!(contract.storage_balance_of(accounts(1)).unwrap().available, available);
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs[0].contains("\"event\":\"htlc_order_cleaned_up\""));
+    }
+
+    #[test]
+    #[should_panic(expected = "Maker not allowlisted")]
+    fn test_create_order_requires_allowlisted_maker() {
+        let mut contract = setup_contract();
+        testing_env!(get_context(accounts(1)).build());
+        contract.set_maker_allowlist_enabled(true);
+        contract.add_maker(accounts(1));
+        maker_context(NearToken::from_near(1));
+        create_test_order(&mut contract, "listed", "a".repeat(64), None, None);
+
+        testing_env!(get_context(accounts(1)).build());
+        contract.remove_maker(accounts(1));
+        maker_context(NearToken::from_near(1));
+        create_test_order(&mut contract, "unlisted", "b".repeat(64), None, None);
+    }
+}
@@ -0,0 +1,110 @@
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::ft::FusionOrderParams;
+use crate::hashlock::HashAlgorithm;
+use crate::{DestinationInfo, FusionPlusNear, FusionPlusNearExt, FusionPlusOrder};
+
+/// Asset identifier of native NEAR in `defuse_asset_identifier_out`
+pub const NATIVE_NEAR_ASSET: &str = "near";
+/// Prefix of NEP-141 asset identifiers, as used by NEAR Intents (`nep141:<token>`)
+pub const NEP141_ASSET_PREFIX: &str = "nep141:";
+
+/// Fusion+ order in the NEAR Intents quote format published on the solver bus
+/// The maker gives `defuse_asset_identifier_in` on the source chain and
+/// receives `defuse_asset_identifier_out` here; the solver filling it is the resolver.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FusionIntent {
+    pub order_hash: String,
+    pub hashlock: String,
+    /// Maker, paid on NEAR
+    pub signer_id: AccountId,
+    /// Source asset as `<chain_id>:<token>`, e.g. `11155111:0xa0b8...`
+    pub defuse_asset_identifier_in: String,
+    pub exact_amount_in: U128,
+    /// NEAR asset, `nep141:<token>` or `near` for native NEAR
+    pub defuse_asset_identifier_out: String,
+    pub exact_amount_out: U128,
+    /// Maker's address on the source chain
+    pub source_address: String,
+    pub solver_fee: U128,
+    pub timelocks: U128,
+    /// Intent is rejected from this block timestamp (ms) on
+    #[serde(default)]
+    pub deadline_ms: Option<U64>,
+    /// Defaults to SHA-256
+    #[serde(default)]
+    pub hash_algorithm: Option<HashAlgorithm>,
+    /// Account receiving the maker's payouts, `signer_id` when unset
+    #[serde(default)]
+    pub receiver_id: Option<AccountId>,
+}
+
+/// `msg` of an `ft_transfer_call` filling an intent with NEP-141 tokens
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct IntentMessage {
+    pub intent: FusionIntent,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Fill a NEAR Intents order paying native NEAR, like `execute_fusion_order`
+    /// Intents paying a NEP-141 token are filled with `ft_transfer_call`
+    /// and `{"intent": ...}` as `msg`.
+    #[payable]
+    pub fn execute_intent_order(&mut self, resolver: AccountId, intent: FusionIntent) -> FusionPlusOrder {
+        Self::assert_resolver_is_caller(&resolver);
+        let (params, token) = Self::intent_order_params(intent);
+        assert!(token.is_none(), "Intent pays a NEP-141 token");
+        let deposit = env::attached_deposit().as_yoctonear();
+        let order = self.internal_create_fusion_order(params, resolver.clone(), None, deposit);
+        Self::internal_refund_excess(resolver, deposit - self.internal_order_deposit(&order));
+        order
+    }
+}
+
+impl FusionPlusNear {
+    /// Map an intent to the order parameters and the NEAR token it pays
+    pub(crate) fn intent_order_params(intent: FusionIntent) -> (FusionOrderParams, Option<AccountId>) {
+        if let Some(deadline) = intent.deadline_ms {
+            assert!(env::block_timestamp_ms() < deadline.0, "Intent expired");
+        }
+        let (chain_id, source_token) = intent
+            .defuse_asset_identifier_in
+            .split_once(':')
+            .and_then(|(chain_id, token)| Some((chain_id.parse::<u64>().ok()?, token)))
+            .filter(|(_, token)| !token.is_empty())
+            .expect("Invalid source asset identifier");
+        let token = match intent.defuse_asset_identifier_out.as_str() {
+            NATIVE_NEAR_ASSET => None,
+            asset => Some(
+                asset
+                    .strip_prefix(NEP141_ASSET_PREFIX)
+                    .and_then(|token| token.parse().ok())
+                    .expect("Invalid NEAR asset identifier"),
+            ),
+        };
+        let params = FusionOrderParams {
+            order_hash: intent.order_hash,
+            hashlock: intent.hashlock,
+            maker: intent.signer_id,
+            amount: intent.exact_amount_out,
+            resolver_fee: intent.solver_fee,
+            timelocks: intent.timelocks,
+            source_chain_id: chain_id,
+            hash_algorithm: intent.hash_algorithm,
+            parts: None,
+            destination: Some(DestinationInfo {
+                chain_id,
+                token: source_token.to_string(),
+                address: intent.source_address,
+                amount: intent.exact_amount_in,
+            }),
+            payout_to: intent.receiver_id,
+        };
+        (params, token)
+    }
+}
@@ -0,0 +1,55 @@
+use near_sdk::{env, near_bindgen};
+
+use crate::timelocks::{TimelockStage, Timelocks};
+use crate::{FusionPlusNear, FusionPlusNearExt, OrderState, OrderStatus};
+
+/// Most orders refunded by one `process_expired` call, bounded by the gas of their payouts
+pub const MAX_EXPIRED_PER_CALL: u64 = 5;
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Refund up to `limit` Matched orders past their public cancellation stage
+    /// Meant for Croncat tasks or any other keeper: the caller earns the public
+    /// cancellation reward of each refunded order as its bounty.
+    /// Returns the hashes of the refunded orders.
+    pub fn process_expired(&mut self, limit: Option<u64>) -> Vec<String> {
+        let limit = limit.unwrap_or(MAX_EXPIRED_PER_CALL).min(MAX_EXPIRED_PER_CALL) as usize;
+        let now = Self::now_seconds();
+        // The index is ordered by deadline, so only its head is read
+        let expired: Vec<String> = self
+            .expiring_orders
+            .iter()
+            .take_while(|((expires_at, _), _)| now >= *expires_at)
+            .take(limit)
+            .map(|((_, order_hash), _)| order_hash)
+            .collect();
+
+        let caller = env::predecessor_account_id();
+        for order_hash in &expired {
+            let order = self.orders.get(order_hash).expect("Order not found");
+            let is_party = caller == order.maker || self.is_resolver_or_operator(&order, &caller);
+            let _ = self.internal_refund_order(order_hash, order, caller.clone(), is_party, self.maker_compensation_bps);
+        }
+        expired
+    }
+}
+
+impl FusionPlusNear {
+    /// Key of a Matched order in `expiring_orders`: its public cancellation time
+    fn expiry_key(order_hash: &str, order: &OrderState) -> (u64, String) {
+        let expires_at = Timelocks(order.timelocks.0).stage_start(TimelockStage::PublicCancellation, order.created_at);
+        (expires_at, order_hash.to_string())
+    }
+
+    /// Track a Matched order for `process_expired`
+    pub(crate) fn internal_index_expiry(&mut self, order_hash: &str, order: &OrderState) {
+        if order.status == OrderStatus::Matched {
+            self.expiring_orders.insert(&Self::expiry_key(order_hash, order), &());
+        }
+    }
+
+    /// Stop tracking an order, once it leaves Matched or before its timelocks change
+    pub(crate) fn internal_unindex_expiry(&mut self, order_hash: &str, order: &OrderState) {
+        self.expiring_orders.remove(&Self::expiry_key(order_hash, order));
+    }
+}
@@ -1,13 +1,111 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::UnorderedMap;
+use near_sdk::collections::{LookupMap, TreeMap, UnorderedMap, UnorderedSet, Vector};
 use near_sdk::json_types::U128;
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
-    env, near_bindgen, AccountId, NearToken, Promise,
+    assert_one_yocto, env, near_bindgen, AccountId, NearToken, Promise,
     PanicOnDefault,
 };
 use schemars::JsonSchema;
 
+pub mod attestation;
+pub mod aurora;
+pub mod bidding;
+pub mod bonding;
+pub mod chains;
+pub mod challenge;
+pub mod chain_signatures;
+pub mod circuit_breaker;
+pub mod claim_proofs;
+pub mod cleanup;
+pub mod composable;
+pub mod deposit_tokens;
+pub mod emergency;
+pub mod escrow_factory;
+pub mod eth_proofs;
+pub mod events;
+pub mod extension;
+pub mod fee_token;
+pub mod fees;
+pub mod ft;
+pub mod hashlock;
+pub mod htlc;
+pub mod intents;
+pub mod keeper;
+pub mod makers;
+pub mod merkle;
+pub mod migration;
+pub mod operators;
+pub mod order_hash;
+pub mod partial_fills;
+pub mod payout;
+pub mod reassign;
+pub mod signed_release;
+pub mod simulate;
+pub mod solvency;
+pub mod stats;
+pub mod storage;
+pub mod swap;
+pub mod timelocks;
+pub mod tvl;
+pub mod views;
+pub mod wnear;
+
+use attestation::SolverAttestation;
+use bonding::{ResolverBond, DEFAULT_MIN_RESOLVER_BOND, DEFAULT_UNBONDING_DELAY};
+use challenge::{HeldDeposit, DEFAULT_CHALLENGE_WINDOW};
+use claim_proofs::PendingClaim;
+use cleanup::DEFAULT_ORDER_RETENTION;
+use emergency::DisputeRecord;
+use deposit_tokens::TokenSafetyDeposit;
+use eth_proofs::EthProofConfig;
+use extension::TimelockExtension;
+use events::{
+    FusionEvent, FusionOrderClaimedEvent, FusionOrderCreatedEvent, FusionOrderPartiallyFilledEvent,
+    FusionOrderRefundedEvent, OwnershipEvent, PauseEvent, ResolverEvent,
+};
+use ft::FusionOrderParams;
+use hashlock::{decode_hex32, normalize_hex32, HashAlgorithm, HashlockExt};
+use htlc::{HTLCOrder, DEFAULT_MAX_OPEN_HTLC_ORDERS, DEFAULT_MIN_HTLC_ORDER_AMOUNT};
+use partial_fills::PartialFill;
+use payout::Payout;
+use signed_release::ReleaseKey;
+use solvency::Liabilities;
+use stats::{OrderTotals, ResolverCounters};
+use storage::StorageAccount;
+use swap::ClaimSwap;
+use timelocks::{TimelockStage, Timelocks};
+use views::StatusChange;
+use wnear::NearAsset;
+
+/// Share of the safety deposit paid to a non-resolver account that
+/// completes a claim during the public withdrawal stage (basis points)
+pub const PUBLIC_WITHDRAWAL_REWARD_BPS: u128 = 1_000;
+/// Share of the safety deposit paid to a non-resolver account that
+/// cancels an expired order during the public cancellation stage (basis points)
+pub const PUBLIC_CANCELLATION_REWARD_BPS: u128 = 1_000;
+/// Share of the safety deposit (after any public cancellation reward) paid
+/// to the maker when the resolver fails to complete an order (basis points)
+pub const DEFAULT_MAKER_COMPENSATION_BPS: u16 = 10_000;
+
+/// Other leg of a swap, shown to the maker by NEAR-side indexers and wallets
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DestinationInfo {
+    /// Chain the maker's side of the order lives on (e.g. Ethereum = 11155111)
+    pub chain_id: u64,
+    /// Token the maker gives in exchange (e.g. an ERC-20 address)
+    pub token: String,
+    /// Maker's address on that chain
+    pub address: String,
+    /// Amount of `token` the maker gives, in its smallest unit
+    #[schemars(with = "String")]
+    pub amount: U128,
+}
+
+/// Longest `token` or `address` accepted in `DestinationInfo`
+pub const MAX_DESTINATION_FIELD_LEN: usize = 128;
+
 /// 1inch Fusion+ Order Structure for NEAR
 /// Compatible with 1inch Fusion+ protocol extension
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
@@ -16,7 +114,10 @@ pub struct FusionPlusOrder {
     /// 1inch Fusion+ order hash from Ethereum
     pub order_hash: String,
     /// Hash for HTLC atomic coordination
+    /// Merkle root of the secret hashes when the order allows partial fills
     pub hashlock: String,
+    /// Hash function the hashlock was derived with
+    pub hash_algorithm: HashAlgorithm,
     /// Packed timelock stages (1inch format)
     #[schemars(with = "String")]
     pub timelocks: U128, // Using U128 to store packed uint256
@@ -26,7 +127,10 @@ pub struct FusionPlusOrder {
     /// 1inch resolver executing the order
     #[schemars(with = "String")]
     pub resolver: AccountId,
-    /// Amount of NEAR tokens to transfer
+    /// NEP-141 token contract holding the escrow (None for native NEAR)
+    #[schemars(with = "Option<String>")]
+    pub token: Option<AccountId>,
+    /// Amount of NEAR (or NEP-141) tokens to transfer
     #[schemars(with = "String")]
     pub amount: U128,
     /// Resolver fee from the 1inch order
@@ -40,49 +144,264 @@ pub struct FusionPlusOrder {
     /// Preimage when revealed
     pub preimage: Option<String>,
     /// Source chain ID (e.g., Ethereum = 11155111)
-    pub source_chain_id: u32,
-}
-
-#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
-#[serde(crate = "near_sdk::serde")]
-pub enum OrderStatus {
-    Pending,
-    Matched,
-    Claimed,
-    Refunded,
+    pub source_chain_id: u64,
+    /// Block timestamp (seconds) the timelock offsets are relative to
+    pub created_at: u64,
+    /// Payouts whose transfer failed and can be retried
+    pub failed_payouts: Vec<Payout>,
+    /// Number of parts for partial fills (0 = single fill with one secret)
+    /// A multi-part order commits to `parts + 1` secrets in a Merkle tree
+    pub parts: u32,
+    /// Amount already released to the maker by partial fills or taken by fill orders
+    #[schemars(with = "String")]
+    pub filled_amount: U128,
+    /// Index of the last secret revealed by a partial fill
+    pub last_fill_index: Option<u32>,
+    /// Sub-account holding the order's NEAR when created in escrow mode
+    #[schemars(with = "Option<String>")]
+    pub escrow_account: Option<AccountId>,
+    /// What the maker gives in exchange, if the resolver provided it
+    pub destination: Option<DestinationInfo>,
+    /// Account receiving the maker's payouts instead of `maker` (e.g. a cold
+    /// wallet or vault); cancellation compensation still goes to `maker`
+    #[schemars(with = "Option<String>")]
+    pub payout_to: Option<AccountId>,
 }
 
-/// Events for 1inch integration monitoring
-#[derive(Serialize, Deserialize)]
-#[serde(crate = "near_sdk::serde")]
-pub struct FusionOrderCreatedEvent {
-    pub order_hash: String,
+/// Order fields read or written by every transition (claim, fill, cancel)
+/// Stored apart from `OrderMetadata` so transitions load as few bytes as possible
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct OrderState {
     pub maker: AccountId,
+    pub resolver: AccountId,
+    pub token: Option<AccountId>,
     pub amount: U128,
-    pub source_chain_id: u32,
+    pub resolver_fee: U128,
+    pub safety_deposit: U128,
+    pub status: OrderStatus,
+    pub timelocks: U128,
+    pub created_at: u64,
+    pub parts: u32,
+    pub filled_amount: U128,
+    pub last_fill_index: Option<u32>,
+    /// Funds are held by the order's escrow sub-account
+    pub escrowed: bool,
 }
 
-#[derive(Serialize, Deserialize)]
-#[serde(crate = "near_sdk::serde")]
-pub struct FusionOrderClaimedEvent {
-    pub order_hash: String,
-    pub resolver: AccountId,
-    pub preimage: String,
+/// Order fields only needed when revealing a secret or retrying payouts
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+/// Hashlock and preimage are kept as raw bytes, half the size of their hex form
+pub struct OrderMetadata {
+    pub hashlock: [u8; 32],
+    pub hash_algorithm: HashAlgorithm,
+    pub source_chain_id: u64,
+    pub preimage: Option<[u8; 32]>,
+    pub failed_payouts: Vec<Payout>,
+    /// Position of the order hash in `order_hashes`
+    pub hash_index: u64,
+    pub destination: Option<DestinationInfo>,
+    pub payout_to: Option<AccountId>,
+}
+
+impl FusionPlusOrder {
+    /// Assemble the full order view from its stored parts
+    pub fn from_parts(order_hash: String, state: OrderState, metadata: OrderMetadata) -> Self {
+        Self {
+            order_hash: order_hash.clone(),
+            hashlock: hex::encode(metadata.hashlock),
+            hash_algorithm: metadata.hash_algorithm,
+            timelocks: state.timelocks,
+            maker: state.maker,
+            resolver: state.resolver,
+            token: state.token,
+            amount: state.amount,
+            resolver_fee: state.resolver_fee,
+            safety_deposit: state.safety_deposit,
+            status: state.status,
+            preimage: metadata.preimage.map(hex::encode),
+            source_chain_id: metadata.source_chain_id,
+            created_at: state.created_at,
+            failed_payouts: metadata.failed_payouts,
+            parts: state.parts,
+            filled_amount: state.filled_amount,
+            last_fill_index: state.last_fill_index,
+            escrow_account: state
+                .escrowed
+                .then(|| FusionPlusNear::escrow_account_id(&order_hash)),
+            destination: metadata.destination,
+            payout_to: metadata.payout_to,
+        }
+    }
+
+    /// Split the order into its hot and cold stored parts, the hash being
+    /// stored at `hash_index` of `order_hashes`
+    pub fn into_parts(self, hash_index: u64) -> (OrderState, OrderMetadata) {
+        (
+            OrderState {
+                maker: self.maker,
+                resolver: self.resolver,
+                token: self.token,
+                amount: self.amount,
+                resolver_fee: self.resolver_fee,
+                safety_deposit: self.safety_deposit,
+                status: self.status,
+                timelocks: self.timelocks,
+                created_at: self.created_at,
+                parts: self.parts,
+                filled_amount: self.filled_amount,
+                last_fill_index: self.last_fill_index,
+                escrowed: self.escrow_account.is_some(),
+            },
+            OrderMetadata {
+                // Malformed legacy hashlocks become all zeros, which no preimage hashes to
+                hashlock: decode_hex32(&self.hashlock).unwrap_or_default(),
+                hash_algorithm: self.hash_algorithm,
+                source_chain_id: self.source_chain_id,
+                preimage: self.preimage.as_deref().and_then(decode_hex32),
+                failed_payouts: self.failed_payouts,
+                hash_index,
+                destination: self.destination,
+                payout_to: self.payout_to,
+            },
+        )
+    }
 }
 
+pub use fusion_core::OrderStatus;
+
 /// 1inch Fusion+ NEAR Extension Contract
 /// Enables NEAR as a destination chain for 1inch Fusion+ atomic swaps
+/// (`orders`) and as a source chain through maker-created HTLCs (`htlc_orders`)
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct FusionPlusNear {
-    /// Fusion+ orders indexed by 1inch order hash
-    pub orders: UnorderedMap<String, FusionPlusOrder>,
+    /// Hot part of Fusion+ orders indexed by 1inch order hash
+    pub orders: LookupMap<String, OrderState>,
+    /// Cold part of Fusion+ orders indexed by 1inch order hash
+    pub order_metadata: LookupMap<String, OrderMetadata>,
+    /// All order hashes in creation order, for enumeration
+    pub order_hashes: Vector<String>,
     /// 1inch authorized resolvers (compatibility with 1inch network)
     pub authorized_resolvers: UnorderedMap<AccountId, bool>,
     /// Contract owner for management
     pub owner: AccountId,
+    /// Proposed owner that still has to accept ownership
+    pub pending_owner: Option<AccountId>,
     /// Minimum safety deposit ratio (basis points)
     pub min_safety_deposit_bps: u16,
+    /// NEP-145 storage deposits of order creators
+    pub storage_accounts: LookupMap<AccountId, StorageAccount>,
+    /// Bytes required to register one storage account
+    pub account_storage_usage: u64,
+    /// Order hashes indexed by maker
+    pub orders_by_maker: LookupMap<AccountId, UnorderedSet<String>>,
+    /// Order hashes indexed by current status
+    pub orders_by_status: LookupMap<OrderStatus, UnorderedSet<String>>,
+    /// Running NEAR totals reported by `get_stats`
+    pub totals: OrderTotals,
+    /// Account allowed to pause (but not unpause) alongside the owner
+    pub guardian: Option<AccountId>,
+    /// New orders are rejected while paused; claims and refunds still work
+    pub paused: bool,
+    /// When (unix seconds) the contract was last paused
+    pub paused_at: u64,
+    /// Maker-created HTLC orders (NEAR as source chain) indexed by order id
+    pub htlc_orders: LookupMap<String, HTLCOrder>,
+    /// Chain Signatures MPC contract signing Ethereum withdrawals
+    pub mpc_signer: Option<AccountId>,
+    /// Resolver bonds backing their authorization
+    pub resolver_bonds: LookupMap<AccountId, ResolverBond>,
+    /// Bond a resolver must hold to execute or match orders
+    pub min_resolver_bond: u128,
+    /// Seconds an unbonding resolver waits before withdrawing
+    pub unbonding_delay: u64,
+    /// Protocol fee taken from claimed amounts (basis points)
+    pub protocol_fee_bps: u16,
+    /// Receiver of withdrawn protocol fees, the owner when unset
+    pub fee_account: Option<AccountId>,
+    /// Protocol fees accrued per asset (`None` for native NEAR)
+    pub accrued_fees: LookupMap<Option<AccountId>, u128>,
+    /// wNEAR contract used to wrap or unwrap payouts
+    pub wnear_contract: Option<AccountId>,
+    /// Accounts receiving NEAR-denominated payouts in the other form
+    pub payout_preferences: LookupMap<AccountId, NearAsset>,
+    /// Seconds finalized orders are kept before `cleanup_orders` may delete them
+    pub order_retention: u64,
+    /// Native NEAR orders are moved to a per-order escrow sub-account
+    pub escrow_mode: bool,
+    /// Share of the safety deposit paid to the maker when an order is cancelled (basis points)
+    pub maker_compensation_bps: u16,
+    /// Operator account each resolver lets claim and cancel its orders
+    pub resolver_operators: LookupMap<AccountId, AccountId>,
+    /// Account confirming Ethereum-side secret reveals for `request_claim`
+    pub claim_prover: Option<AccountId>,
+    /// Claims waiting for the claim prover, by order hash
+    pub pending_claims: LookupMap<String, PendingClaim>,
+    /// Status transitions per order hash
+    pub order_history: LookupMap<String, Vec<StatusChange>>,
+    /// Unsettled HTLC orders per maker
+    pub open_htlc_orders: LookupMap<AccountId, u32>,
+    /// Unsettled HTLC orders a maker may have at a time
+    pub max_open_htlc_orders: u32,
+    /// Minimum locked amount of an HTLC order
+    pub min_htlc_order_amount: u128,
+    /// Timelock extensions approved by one party of an order
+    pub timelock_extensions: LookupMap<String, TimelockExtension>,
+    /// Resolver approved to take over a stalled order, by order hash
+    pub resolver_reassignments: LookupMap<String, AccountId>,
+    /// Aurora engine whose XCC router accounts map to EVM addresses
+    pub aurora_engine: Option<AccountId>,
+    /// Seconds a claimed order's safety deposit is held for challenges
+    pub challenge_window: u64,
+    /// Safety deposits held after a claim, by order hash
+    pub held_deposits: LookupMap<String, HeldDeposit>,
+    /// Dispute records of force-cancelled orders, by order hash
+    pub disputes: LookupMap<String, DisputeRecord>,
+    /// Value locked in NEP-141 Fusion+ orders per token
+    pub token_locked: LookupMap<AccountId, u128>,
+    /// Maker NEAR locked in open HTLC orders
+    pub htlc_locked: u128,
+    /// Maximum value locked per asset (`None` for native NEAR)
+    pub tvl_caps: LookupMap<Option<AccountId>, u128>,
+    /// TEE attestations of solver accounts
+    pub solver_attestations: LookupMap<AccountId, SolverAttestation>,
+    /// Resolvers must hold a non-revoked attestation to execute or match orders
+    pub require_attestation: bool,
+    /// NEAR owed outside of the order totals, reported by `get_solvency`
+    pub liabilities: Liabilities,
+    /// Ref Finance exchange used for claim swaps
+    pub ref_exchange: Option<AccountId>,
+    /// Swaps makers asked for on their claim payout, by order hash
+    pub claim_swaps: LookupMap<String, ClaimSwap>,
+    /// Ethereum light-client prover checking source escrows
+    pub eth_proof_config: Option<EthProofConfig>,
+    /// Orders whose Ethereum source escrow was proven, with the account that paid the record
+    pub source_proofs: LookupMap<String, AccountId>,
+    /// Only allowlisted makers can be named in new orders
+    pub maker_allowlist_enabled: bool,
+    /// Makers approved while the allowlist is enabled
+    pub allowed_makers: LookupMap<AccountId, bool>,
+    /// NEP-141 tokens accepted for safety deposits, with their collateral ratio (bps)
+    pub safety_deposit_tokens: LookupMap<AccountId, u16>,
+    /// Safety deposits posted in tokens, by resolver and order hash
+    pub token_safety_deposits: LookupMap<(AccountId, String), TokenSafetyDeposit>,
+    /// Every order hash ever created, at its creation index
+    /// Append-only, unlike `order_hashes` which cleanup compacts
+    pub order_log: Vector<String>,
+    /// Resolves challenged and disputed settlements alongside the owner
+    pub arbitrator: Option<AccountId>,
+    /// Keys whose signature releases an order instead of its preimage, by order hash
+    pub release_keys: LookupMap<String, ReleaseKey>,
+    /// Orders created by contracts whose `on_fusion_order_created` callback is pending
+    pub calls_in_flight: LookupMap<AccountId, Vec<String>>,
+    /// Per-resolver performance counters
+    pub resolver_stats: LookupMap<AccountId, ResolverCounters>,
+    /// Payouts that failed in a row, see `circuit_breaker`
+    pub consecutive_payout_failures: u32,
+    /// Matched orders by public cancellation time, for `process_expired`
+    pub expiring_orders: TreeMap<(u64, String), ()>,
+    /// Fill orders taken from multi-part orders, by fill order hash
+    pub partial_fills: LookupMap<String, PartialFill>,
 }
 
 #[near_bindgen]
@@ -92,32 +411,184 @@ impl FusionPlusNear {
         assert!(!env::state_exists(), "Already initialized");
         assert!(min_safety_deposit_bps > 0 && min_safety_deposit_bps <= 10000, "Invalid deposit ratio");
         
-        Self {
-            orders: UnorderedMap::new(b"o"),
+        let mut this = Self {
+            orders: LookupMap::new(b"o"),
+            order_metadata: LookupMap::new(b"c"),
+            order_hashes: Vector::new(b"i"),
             authorized_resolvers: UnorderedMap::new(b"r"),
             owner: env::predecessor_account_id(),
+            pending_owner: None,
             min_safety_deposit_bps,
-        }
+            storage_accounts: LookupMap::new(b"s"),
+            account_storage_usage: 0,
+            orders_by_maker: LookupMap::new(b"m"),
+            orders_by_status: LookupMap::new(b"t"),
+            totals: OrderTotals::default(),
+            guardian: None,
+            paused: false,
+            paused_at: 0,
+            htlc_orders: LookupMap::new(b"h"),
+            mpc_signer: None,
+            resolver_bonds: LookupMap::new(b"b"),
+            min_resolver_bond: DEFAULT_MIN_RESOLVER_BOND,
+            unbonding_delay: DEFAULT_UNBONDING_DELAY,
+            protocol_fee_bps: 0,
+            fee_account: None,
+            accrued_fees: LookupMap::new(b"f"),
+            wnear_contract: None,
+            payout_preferences: LookupMap::new(b"w"),
+            order_retention: DEFAULT_ORDER_RETENTION,
+            escrow_mode: false,
+            maker_compensation_bps: DEFAULT_MAKER_COMPENSATION_BPS,
+            resolver_operators: LookupMap::new(b"p"),
+            claim_prover: None,
+            pending_claims: LookupMap::new(b"y"),
+            order_history: LookupMap::new(b"l"),
+            open_htlc_orders: LookupMap::new(b"n"),
+            max_open_htlc_orders: DEFAULT_MAX_OPEN_HTLC_ORDERS,
+            min_htlc_order_amount: DEFAULT_MIN_HTLC_ORDER_AMOUNT,
+            timelock_extensions: LookupMap::new(b"x"),
+            resolver_reassignments: LookupMap::new(b"a"),
+            aurora_engine: None,
+            challenge_window: DEFAULT_CHALLENGE_WINDOW,
+            held_deposits: LookupMap::new(b"d"),
+            disputes: LookupMap::new(b"e"),
+            token_locked: LookupMap::new(b"k"),
+            htlc_locked: 0,
+            tvl_caps: LookupMap::new(b"v"),
+            solver_attestations: LookupMap::new(b"q"),
+            require_attestation: false,
+            liabilities: Liabilities::default(),
+            ref_exchange: None,
+            claim_swaps: LookupMap::new(b"g"),
+            eth_proof_config: None,
+            source_proofs: LookupMap::new(b"u"),
+            maker_allowlist_enabled: false,
+            allowed_makers: LookupMap::new(b"j"),
+            safety_deposit_tokens: LookupMap::new(b"Z"),
+            token_safety_deposits: LookupMap::new(b"z"),
+            order_log: Vector::new(b"C"),
+            arbitrator: None,
+            release_keys: LookupMap::new(b"R"),
+            calls_in_flight: LookupMap::new(b"F"),
+            resolver_stats: LookupMap::new(b"S"),
+            consecutive_payout_failures: 0,
+            expiring_orders: TreeMap::new(b"E"),
+            partial_fills: LookupMap::new(b"P"),
+        };
+        this.measure_account_storage_usage();
+        Self::write_state_version();
+        events::init_event_nonce();
+        this
     }
 
     /// Add a 1inch resolver to the authorized list
     /// Only resolvers from 1inch network can execute orders
-    pub fn add_resolver(&mut self, resolver: AccountId) {
+    /// Idempotent, so a repeated DAO proposal does not fail; returns whether
+    /// the resolver was newly added
+    #[payable]
+    pub fn add_resolver(&mut self, resolver: AccountId) -> bool {
         self.assert_owner();
-        self.authorized_resolvers.insert(&resolver, &true);
-        env::log_str(&format!("RESOLVER_ADDED:{}", resolver));
+        let added = self.authorized_resolvers.insert(&resolver, &true).is_none();
+        if added {
+            FusionEvent::ResolverAdded(vec![ResolverEvent { resolver }]).emit();
+        }
+        added
     }
 
     /// Remove a resolver from 1inch network
-    pub fn remove_resolver(&mut self, resolver: AccountId) {
+    /// Returns whether the resolver was authorized
+    #[payable]
+    pub fn remove_resolver(&mut self, resolver: AccountId) -> bool {
+        self.assert_owner();
+        let removed = self.authorized_resolvers.remove(&resolver).is_some();
+        if removed {
+            FusionEvent::ResolverRemoved(vec![ResolverEvent { resolver }]).emit();
+        }
+        removed
+    }
+
+    /// Propose a new owner, who must call `accept_ownership` to take over
+    #[payable]
+    pub fn propose_owner(&mut self, new_owner: AccountId) {
+        self.assert_owner();
+        self.pending_owner = Some(new_owner.clone());
+        FusionEvent::OwnershipProposed(vec![OwnershipEvent {
+            owner: self.owner.clone(),
+            new_owner,
+        }])
+        .emit();
+    }
+
+    /// Complete an ownership transfer (proposed owner only)
+    pub fn accept_ownership(&mut self) {
+        let caller = env::predecessor_account_id();
+        assert_eq!(
+            self.pending_owner.as_ref(),
+            Some(&caller),
+            "Only proposed owner can accept"
+        );
+        let previous_owner = std::mem::replace(&mut self.owner, caller.clone());
+        self.pending_owner = None;
+        FusionEvent::OwnershipTransferred(vec![OwnershipEvent {
+            owner: previous_owner,
+            new_owner: caller,
+        }])
+        .emit();
+    }
+
+    /// Set or clear the guardian account
+    #[payable]
+    pub fn set_guardian(&mut self, guardian: Option<AccountId>) {
+        self.assert_owner();
+        self.guardian = guardian;
+    }
+
+    /// Set the share of the safety deposit paid to the maker on cancellation (basis points)
+    #[payable]
+    pub fn set_maker_compensation_bps(&mut self, maker_compensation_bps: u16) {
+        self.assert_owner();
+        assert!(maker_compensation_bps <= 10_000, "Invalid compensation ratio");
+        self.maker_compensation_bps = maker_compensation_bps;
+    }
+
+    /// Stop accepting new orders (owner or guardian)
+    /// Returns whether the contract was running; pausing twice is a no-op
+    pub fn pause(&mut self) -> bool {
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == self.owner || self.guardian.as_ref() == Some(&caller),
+            "Only owner or guardian"
+        );
+        if self.paused {
+            return false;
+        }
+        self.paused = true;
+        self.paused_at = Self::now_seconds();
+        FusionEvent::ContractPaused(vec![PauseEvent { account_id: caller }]).emit();
+        true
+    }
+
+    /// Resume accepting new orders (owner only)
+    /// Returns whether the contract was paused; unpausing twice is a no-op
+    #[payable]
+    pub fn unpause(&mut self) -> bool {
         self.assert_owner();
-        self.authorized_resolvers.remove(&resolver);
-        env::log_str(&format!("RESOLVER_REMOVED:{}", resolver));
+        if !self.paused {
+            return false;
+        }
+        self.paused = false;
+        FusionEvent::ContractUnpaused(vec![PauseEvent {
+            account_id: env::predecessor_account_id(),
+        }])
+        .emit();
+        true
     }
 
     /// Execute a Fusion+ order on NEAR side
-    /// Called by 1inch resolvers to complete atomic swaps
+    /// Called by 1inch resolvers to complete atomic swaps; `resolver` must be the caller
     #[payable]
+    #[allow(clippy::too_many_arguments)]
     pub fn execute_fusion_order(
         &mut self,
         order_hash: String,
@@ -127,174 +598,221 @@ impl FusionPlusNear {
         amount: U128,
         resolver_fee: U128,
         timelocks: U128,
-        source_chain_id: u32,
+        source_chain_id: u64,
+        hash_algorithm: Option<HashAlgorithm>,
+        parts: Option<u32>,
+        destination: Option<DestinationInfo>,
+        payout_to: Option<AccountId>,
     ) -> FusionPlusOrder {
-        // Verify resolver is authorized by 1inch
-        assert!(
-            self.authorized_resolvers.get(&resolver).unwrap_or(false),
-            "Not a 1inch authorized resolver"
-        );
-
-        // Verify order doesn't exist
-        assert!(!self.orders.get(&order_hash).is_some(), "Order already exists");
-
-        // Verify attached deposit covers amount + resolver fee + safety deposit
-        let total_required = amount.0 + resolver_fee.0;
-        let attached = env::attached_deposit().as_yoctonear();
-        assert!(attached >= total_required, "Insufficient deposit");
-
-        // Calculate safety deposit (resolver's stake)
-        let safety_deposit = (amount.0 * self.min_safety_deposit_bps as u128) / 10000;
-        assert!(attached >= total_required + safety_deposit, "Insufficient safety deposit");
-
-        // Validate hashlock format (64 hex chars = 32 bytes)
-        assert!(hashlock.len() == 64, "Invalid hashlock format");
-
-        // Create Fusion+ order
-        let order = FusionPlusOrder {
-            order_hash: order_hash.clone(),
+        let params = FusionOrderParams {
+            order_hash,
             hashlock,
-            timelocks,
-            maker: maker.clone(),
-            resolver: resolver.clone(),
+            maker,
             amount,
             resolver_fee,
-            safety_deposit: U128(safety_deposit),
-            status: OrderStatus::Matched,
-            preimage: None,
+            timelocks,
             source_chain_id,
+            hash_algorithm,
+            parts,
+            destination,
+            payout_to,
         };
-
-        self.orders.insert(&order_hash, &order);
-
-        // Emit event for 1inch monitoring
-        env::log_str(&format!(
-            "FUSION_ORDER_CREATED:{}",
-            serde_json::to_string(&FusionOrderCreatedEvent {
-                order_hash: order_hash.clone(),
-                maker: maker.clone(),
-                amount,
-                source_chain_id,
-            }).unwrap()
-        ));
-
+        Self::assert_resolver_is_caller(&resolver);
+        let deposit = env::attached_deposit().as_yoctonear();
+        let order = self.internal_create_fusion_order(params, resolver.clone(), None, deposit);
+        Self::internal_refund_excess(resolver, deposit - self.internal_order_deposit(&order));
         order
     }
 
+    /// Execute several native NEAR Fusion+ orders in one transaction
+    /// The attached deposit must cover all orders together, any surplus is
+    /// refunded; if any order is invalid none of them is created.
+    #[payable]
+    pub fn execute_fusion_orders(
+        &mut self,
+        resolver: AccountId,
+        orders: Vec<FusionOrderParams>,
+    ) -> Vec<FusionPlusOrder> {
+        assert!(!orders.is_empty(), "No orders");
+        Self::assert_resolver_is_caller(&resolver);
+        let mut remaining = env::attached_deposit().as_yoctonear();
+        let orders = orders
+            .into_iter()
+            .map(|params| {
+                let order = self.internal_create_fusion_order(params, resolver.clone(), None, remaining);
+                remaining -= self.internal_order_deposit(&order);
+                order
+            })
+            .collect();
+        Self::internal_refund_excess(resolver, remaining);
+        orders
+    }
+
     /// Claim Fusion+ order with preimage revelation
-    /// Completes the atomic swap by revealing the secret
-    pub fn claim_fusion_order(&mut self, order_hash: String, preimage: String) {
+    /// Completes the atomic swap by revealing the secret, paying the maker
+    /// (minus the protocol fee) and returning the resolver fee + safety
+    /// deposit in the same call.
+    /// The resolver or the maker may claim from the withdrawal stage; from
+    /// the public withdrawal stage anyone may, earning part of the safety
+    /// deposit. Authorization uses the predecessor, so the resolver or maker
+    /// can sign the claim as a NEP-366 delegate action for a relayer to submit.
+    /// The resolver's operator (see `set_operator`) may claim on its behalf.
+    pub fn claim_fusion_order(&mut self, order_hash: String, preimage: String) -> Promise {
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        self.internal_claim_order(order_hash, preimage, env::predecessor_account_id())
+    }
+
+    /// Fill part of a multi-part order by revealing the secret at `index`
+    /// `proof` proves the secret's leaf against the order's Merkle root.
+    /// The maker is paid `fill_amount`; the resolver fee and safety deposit
+    /// are returned with the fill that completes the order.
+    /// A fill order taken with `execute_partial_fill` is filled whole with
+    /// the secret fixed when it was taken.
+    /// Fills follow the windows of `claim_fusion_order`: the resolver, its
+    /// operator or the maker from the withdrawal stage, anyone from public
+    /// withdrawal. A public account completing the order earns the public
+    /// withdrawal reward from the safety deposit.
+    pub fn claim_partial_fill(
+        &mut self,
+        order_hash: String,
+        fill_amount: U128,
+        index: u32,
+        secret: String,
+        proof: Vec<String>,
+    ) -> Promise {
+        let order_hash = Self::normalize_order_hash(&order_hash);
         let mut order = self.orders.get(&order_hash).expect("Order not found");
-        
-        // Only resolver can claim
-        assert_eq!(
-            env::predecessor_account_id(), 
-            order.resolver, 
-            "Only resolver can claim"
-        );
-        
-        // Check order status
+        let mut metadata = self.order_metadata.get(&order_hash).expect("Order not found");
+
+        let caller = env::predecessor_account_id();
+        let is_party = self.is_resolver_or_operator(&order, &caller) || caller == order.maker;
+        Self::assert_claim_window(&order, is_party);
         assert_eq!(order.status, OrderStatus::Matched, "Order not claimable");
-        
-        // Validate preimage format
-        assert!(preimage.len() == 64, "Invalid preimage format");
-        
-        // Verify preimage matches hashlock
-        let preimage_bytes = hex::decode(&preimage).expect("Invalid preimage hex");
-        let hash = env::sha256(&preimage_bytes);
-        let computed_hash = hex::encode(hash);
-        assert_eq!(computed_hash, order.hashlock, "Preimage doesn't match hashlock");
+        assert!(order.parts > 0, "Order does not allow partial fills");
+        let partial_fill = self.partial_fills.get(&order_hash);
+        self.assert_source_proven(partial_fill.as_ref().map_or(&order_hash, |fill| &fill.order_hash));
 
-        // Update order status
-        order.status = OrderStatus::Claimed;
-        order.preimage = Some(preimage.clone());
-        self.orders.insert(&order_hash, &order);
+        let remaining = order.amount.0 - order.filled_amount.0;
+        assert!(
+            fill_amount.0 > 0 && fill_amount.0 <= remaining,
+            "Invalid fill amount"
+        );
 
-        // Emit event for 1inch monitoring
-        env::log_str(&format!(
-            "FUSION_ORDER_CLAIMED:{}",
-            serde_json::to_string(&FusionOrderClaimedEvent {
-                order_hash: order_hash.clone(),
-                resolver: order.resolver.clone(),
-                preimage: preimage.clone(),
-            }).unwrap()
-        ));
-    }
+        // Each fill must reveal the secret for the part it ends in, and
+        // every secret can only be used once
+        let expected_index = match &partial_fill {
+            Some(fill) => {
+                assert_eq!(fill_amount, order.amount, "Fill orders are filled whole");
+                fill.secret_index
+            }
+            None => merkle::expected_secret_index(
+                order.amount.0,
+                order.filled_amount.0,
+                fill_amount.0,
+                order.parts,
+            ),
+        };
+        assert_eq!(index, expected_index, "Invalid secret index for fill amount");
+        assert!(
+            order.last_fill_index.is_none_or(|last| index > last),
+            "Secret already used"
+        );
 
-    /// Transfer tokens to maker after successful claim
-    /// Separate function to avoid promise issues
-    pub fn transfer_to_maker(&self, order_hash: String) -> Promise {
-        let order = self.orders.get(&order_hash).expect("Order not found");
-        
-        // Only resolver can trigger transfer
-        assert_eq!(
-            env::predecessor_account_id(), 
-            order.resolver, 
-            "Only resolver can transfer"
+        // Verify the secret against the Merkle root
+        let secret = normalize_hex32(&secret).expect("Invalid preimage format");
+        let secret_hash = metadata
+            .hash_algorithm
+            .hash(&hex::decode(&secret).expect("Invalid preimage hex"));
+        let proof: Vec<merkle::Node> = proof
+            .iter()
+            .map(|node| decode_hex32(node).expect("Invalid proof hex"))
+            .collect();
+        assert!(
+            merkle::verify_proof(
+                merkle::secret_leaf(index as u64, &secret_hash),
+                &proof,
+                &metadata.hashlock,
+            ),
+            "Invalid Merkle proof"
         );
-        
-        // Order must be claimed first
-        assert_eq!(order.status, OrderStatus::Claimed, "Order not claimed yet");
-        
-        // Transfer to maker (user receives their tokens)
-        Promise::new(order.maker.clone())
-            .transfer(NearToken::from_yoctonear(order.amount.0))
-    }
 
-    /// Claim resolver fee and safety deposit return
-    /// Called by resolver after successful claim
-    pub fn claim_resolver_payment(&mut self, order_hash: String) -> Promise {
-        let order = self.orders.get(&order_hash).expect("Order not found");
-        
-        // Only resolver can claim their payment
-        assert_eq!(
-            env::predecessor_account_id(), 
-            order.resolver, 
-            "Only resolver can claim payment"
+        order.filled_amount = U128(order.filled_amount.0 + fill_amount.0);
+        order.last_fill_index = Some(index);
+        metadata.preimage = decode_hex32(&secret);
+        let completed = order.filled_amount == order.amount;
+        if completed {
+            self.internal_set_status(&order_hash, &mut order, OrderStatus::Claimed);
+        }
+        self.orders.insert(&order_hash, &order);
+        self.order_metadata.insert(&order_hash, &metadata);
+        self.internal_record_claim(&order, fill_amount.0);
+
+        FusionEvent::FusionOrderPartiallyFilled(vec![FusionOrderPartiallyFilledEvent {
+            order_hash: order_hash.clone(),
+            resolver: order.resolver.clone(),
+            filled_by: caller.clone(),
+            secret_index: index,
+            secret,
+            fill_amount,
+            filled_amount: order.filled_amount,
+        }])
+        .emit();
+
+        let protocol_fee = self.internal_take_protocol_fee(&order.token, fill_amount.0);
+        self.internal_collect_escrowed_fee(&order_hash, &order, protocol_fee);
+        let maker_payout = self.internal_payout(
+            &order_hash,
+            &order,
+            Self::maker_beneficiary(&order, &metadata),
+            fill_amount.0 - protocol_fee,
         );
-        
-        // Order must be claimed first
-        assert_eq!(order.status, OrderStatus::Claimed, "Order not claimed yet");
-        
-        // Transfer resolver fee + return safety deposit to resolver  
-        let resolver_amount = order.resolver_fee.0 + order.safety_deposit.0;
-        Promise::new(order.resolver.clone())
-            .transfer(NearToken::from_yoctonear(resolver_amount))
+        if !completed {
+            return maker_payout;
+        }
+        match self.internal_pay_resolver_share(&order_hash, &order, caller, is_party) {
+            Some(share) => maker_payout.and(share),
+            None => maker_payout,
+        }
     }
 
     /// Cancel expired Fusion+ order
-    /// Returns funds if timelock has expired
+    /// Returns funds to the resolver once the cancellation stage is reached.
+    /// The resolver or the maker may cancel from the cancellation stage, so a
+    /// vanished resolver cannot leave the order open.
+    /// From the public cancellation stage anyone may cancel, earning part of
+    /// the safety deposit. The maker is compensated from the rest of the
+    /// safety deposit, so a resolver failing to complete loses its stake.
     pub fn cancel_fusion_order(&mut self, order_hash: String) -> Promise {
-        let mut order = self.orders.get(&order_hash).expect("Order not found");
-        
-        // Only resolver can cancel (they locked the funds)
-        assert_eq!(
-            env::predecessor_account_id(),
-            order.resolver,
-            "Only resolver can cancel"
-        );
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let order = self.orders.get(&order_hash).expect("Order not found");
         
         assert_eq!(order.status, OrderStatus::Matched, "Order not cancellable");
         
-        // Check if cancellation timelock has passed
-        // TODO: Unpack timelocks and verify cancellation stage
-        // For now, using simple block height check
-        let current_block = env::block_height();
-        // This is simplified - should unpack timelocks properly
-        assert!(current_block > 1000000, "Cancellation timelock not reached");
-
-        order.status = OrderStatus::Refunded;
-        self.orders.insert(&order_hash, &order);
+        // Resolver (who locked the funds), its operator or maker may cancel first, then anyone
+        let caller = env::predecessor_account_id();
+        let is_party = self.is_resolver_or_operator(&order, &caller) || caller == order.maker;
+        let timelocks = Timelocks(order.timelocks.0);
+        if is_party {
+            assert!(
+                Self::now_seconds() >= timelocks.stage_start(TimelockStage::Cancellation, order.created_at),
+                "Cancellation timelock not reached"
+            );
+        } else {
+            assert!(
+                Self::now_seconds()
+                    >= timelocks.stage_start(TimelockStage::PublicCancellation, order.created_at),
+                "Only resolver or maker can cancel before public cancellation"
+            );
+        }
 
-        // Return all funds to resolver
-        let refund_amount = order.amount.0 + order.resolver_fee.0 + order.safety_deposit.0;
-        Promise::new(order.resolver).transfer(NearToken::from_yoctonear(refund_amount))
+        self.internal_refund_order(&order_hash, order, caller, is_party, self.maker_compensation_bps)
     }
 
-    /// View functions for 1inch integration
+    // View functions for 1inch integration
 
+    /// Order by hash, `None` for unknown or malformed hashes
     pub fn get_order(&self, order_hash: String) -> Option<FusionPlusOrder> {
-        self.orders.get(&order_hash)
+        normalize_hex32(&order_hash).and_then(|digits| self.internal_get_order(&format!("0x{}", digits)))
     }
 
     pub fn is_authorized_resolver(&self, resolver: AccountId) -> bool {
@@ -305,127 +823,582 @@ impl FusionPlusNear {
         self.min_safety_deposit_bps
     }
 
+    pub fn get_maker_compensation_bps(&self) -> u16 {
+        self.maker_compensation_bps
+    }
+
     pub fn get_owner(&self) -> AccountId {
         self.owner.clone()
     }
 
-    // Internal functions
-    
-    fn assert_owner(&self) {
-        assert_eq!(env::predecessor_account_id(), self.owner, "Only owner");
+    pub fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner.clone()
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use near_sdk::test_utils::{accounts, VMContextBuilder};
-    use near_sdk::testing_env;
+    pub fn get_guardian(&self) -> Option<AccountId> {
+        self.guardian.clone()
+    }
 
-    fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
-        let mut builder = VMContextBuilder::new();
-        builder
-            .current_account_id(accounts(0))
-            .signer_account_id(predecessor_account_id.clone())
-            .predecessor_account_id(predecessor_account_id);
-        builder
+    pub fn is_paused(&self) -> bool {
+        self.paused
     }
 
-    #[test]
-    fn test_contract_initialization() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        
-        let contract = FusionPlusNear::new(500); // 5% min safety deposit
-        
-        assert_eq!(contract.get_owner(), accounts(1));
-        assert_eq!(contract.get_min_safety_deposit_bps(), 500);
-        assert!(!contract.is_authorized_resolver(accounts(2)));
+    // Internal functions
+    
+    /// Owner methods also require 1 yoctoNEAR, so they cannot be called
+    /// with a function-call access key
+    fn assert_owner(&self) {
+        assert_one_yocto();
+        assert_eq!(env::predecessor_account_id(), self.owner, "Only owner");
     }
 
-    #[test]
-    fn test_add_resolver() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
+    /// Settle a single-fill order with `preimage`, authorizing `caller` as
+    /// the account that claims it
+    pub(crate) fn internal_claim_order(&mut self, order_hash: String, preimage: String, caller: AccountId) -> Promise {
+        let mut order = self.orders.get(&order_hash).expect("Order not found");
+        let mut metadata = self.order_metadata.get(&order_hash).expect("Order not found");
+
+        // Resolver and maker window, then public withdrawal
+        let is_party = self.is_resolver_or_operator(&order, &caller) || caller == order.maker;
+        Self::assert_claim_window(&order, is_party);
         
-        let mut contract = FusionPlusNear::new(500);
+        // Check order status
+        assert_eq!(order.status, OrderStatus::Matched, "Order not claimable");
+        assert_eq!(order.parts, 0, "Use claim_partial_fill for multi-part orders");
+        self.assert_source_proven(&order_hash);
         
-        // Add 1inch resolver
-        contract.add_resolver(accounts(2));
+        // Validate preimage format (32 bytes hex, optional 0x prefix)
+        let preimage = normalize_hex32(&preimage).expect("Invalid preimage format");
         
-        assert!(contract.is_authorized_resolver(accounts(2)));
+        // Verify preimage matches hashlock
+        let computed_hash = metadata.hash_algorithm.hashlock_for(&preimage);
+        assert_eq!(computed_hash, hex::encode(metadata.hashlock), "Preimage doesn't match hashlock");
+
+        // Update order status
+        self.internal_set_status(&order_hash, &mut order, OrderStatus::Claimed);
+        metadata.preimage = decode_hex32(&preimage);
+        self.orders.insert(&order_hash, &order);
+        self.order_metadata.insert(&order_hash, &metadata);
+        self.internal_record_claim(&order, order.amount.0);
+
+        // Emit event for 1inch monitoring
+        FusionEvent::FusionOrderClaimed(vec![FusionOrderClaimedEvent {
+            order_hash: order_hash.clone(),
+            resolver: order.resolver.clone(),
+            claimed_by: caller.clone(),
+            preimage,
+        }])
+        .emit();
+        self.internal_pay_claim(&order_hash, &order, &metadata, caller, is_party)
     }
 
-    #[test]
-    fn test_execute_fusion_order() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        
-        let mut contract = FusionPlusNear::new(500);
-        
-        // Add resolver
-        contract.add_resolver(accounts(2));
-        
-        // Switch to resolver account
-        let mut context = get_context(accounts(2));
-        let deposit = NearToken::from_near(1).as_yoctonear() + // amount
-                     NearToken::from_millinear(100).as_yoctonear() + // resolver fee
-                     NearToken::from_millinear(50).as_yoctonear(); // safety deposit
-        testing_env!(context
-            .attached_deposit(NearToken::from_yoctonear(deposit))
-            .build());
-        
-        let order = contract.execute_fusion_order(
-            "0x1234567890abcdef".to_string(),
-            "a".repeat(64),
-            accounts(3), // maker
-            accounts(2), // resolver
-            U128(NearToken::from_near(1).as_yoctonear()),
-            U128(NearToken::from_millinear(100).as_yoctonear()),
-            U128(0), // packed timelocks
-            11155111, // Ethereum Sepolia
+    /// Claims open at the withdrawal stage (public withdrawal for anyone but
+    /// the resolver, its operator or the maker) and close once cancellation
+    /// starts, as on 1inch destination escrows
+    pub(crate) fn assert_claim_window(order: &OrderState, is_party: bool) {
+        let timelocks = Timelocks(order.timelocks.0);
+        let now = Self::now_seconds();
+        if is_party {
+            assert!(
+                now >= timelocks.stage_start(TimelockStage::Withdrawal, order.created_at),
+                "Withdrawal timelock not reached"
+            );
+        } else {
+            assert!(
+                now >= timelocks.stage_start(TimelockStage::PublicWithdrawal, order.created_at),
+                "Only resolver or maker can claim before public withdrawal"
+            );
+        }
+        assert!(
+            now < timelocks.stage_start(TimelockStage::Cancellation, order.created_at),
+            "Claim window closed, order is cancellable"
         );
-        
-        assert_eq!(order.order_hash, "0x1234567890abcdef");
-        assert_eq!(order.maker, accounts(3));
-        assert_eq!(order.resolver, accounts(2));
-        assert_eq!(order.status, OrderStatus::Matched);
     }
 
-    #[test]
-    #[should_panic(expected = "Not a 1inch authorized resolver")]
-    fn test_execute_fusion_order_unauthorized() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
-        
-        let mut contract = FusionPlusNear::new(500);
-        
-        // Don't add resolver - should fail
-        let mut context = get_context(accounts(2));
-        testing_env!(context
-            .attached_deposit(NearToken::from_near(2))
-            .build());
-        
-        contract.execute_fusion_order(
-            "0xunauthorized".to_string(),
-            "a".repeat(64),
-            accounts(3),
-            accounts(2),
-            U128(NearToken::from_near(1).as_yoctonear()),
-            U128(NearToken::from_millinear(100).as_yoctonear()),
-            U128(0),
-            11155111,
+    /// Pay out a just claimed single-fill order: the maker, then the
+    /// resolver fee and safety deposit, less the public withdrawal reward of
+    /// a `caller` that is not a party; failed transfers are kept for retry
+    pub(crate) fn internal_pay_claim(
+        &mut self,
+        order_hash: &String,
+        order: &OrderState,
+        metadata: &OrderMetadata,
+        caller: AccountId,
+        is_party: bool,
+    ) -> Promise {
+        let protocol_fee = self.internal_take_protocol_fee(&order.token, order.amount.0);
+        self.internal_collect_escrowed_fee(order_hash, order, protocol_fee);
+        let maker_payout = self.internal_maker_payout(
+            order_hash,
+            order,
+            Self::maker_beneficiary(order, metadata),
+            order.amount.0 - protocol_fee,
         );
+        match self.internal_pay_resolver_share(order_hash, order, caller, is_party) {
+            Some(share) => maker_payout.and(share),
+            None => maker_payout,
+        }
     }
 
-    #[test]
-    #[should_panic(expected = "Order already exists")]
+    /// Pay the resolver fee and safety deposit of a completed order to its
+    /// resolver, less the public withdrawal reward of a `caller` that is not a
+    /// party and the part held for the challenge window
+    /// `None` when nothing is paid out now
+    pub(crate) fn internal_pay_resolver_share(
+        &mut self,
+        order_hash: &String,
+        order: &OrderState,
+        caller: AccountId,
+        is_party: bool,
+    ) -> Option<Promise> {
+        let reward = if is_party {
+            0
+        } else {
+            order.safety_deposit.0 * PUBLIC_WITHDRAWAL_REWARD_BPS / 10_000
+        };
+        let held = self.internal_hold_safety_deposit(order_hash, order, order.safety_deposit.0 - reward);
+        let resolver_amount = order.resolver_fee.0 + order.safety_deposit.0 - reward - held;
+        let mut payouts = Vec::new();
+        if resolver_amount > 0 {
+            payouts.push(self.internal_payout(order_hash, order, order.resolver.clone(), resolver_amount));
+        }
+        if reward > 0 {
+            payouts.push(self.internal_payout(order_hash, order, caller.clone(), reward));
+        }
+        // A token safety deposit is split the same way, its rest held along with the NEAR one
+        let token_reward = (!is_party).then_some((caller, PUBLIC_WITHDRAWAL_REWARD_BPS));
+        let rest_to = self.held_deposits.get(order_hash).is_none().then(|| order.resolver.clone());
+        payouts.extend(self.internal_settle_token_deposit(order_hash, order, token_reward, 0, rest_to));
+        payouts.into_iter().reduce(|all, next| all.and(next))
+    }
+
+    /// Orders are bound to the resolver that funds them, never to a third party
+    fn assert_resolver_is_caller(resolver: &AccountId) {
+        assert_eq!(&env::predecessor_account_id(), resolver, "Resolver must be the caller");
+    }
+
+    /// Canonical order hash: lowercase, 0x-prefixed 32-byte hex
+    /// Matches the `bytes32` order hash of the Ethereum-side order
+    pub(crate) fn normalize_order_hash(order_hash: &str) -> String {
+        let digits = normalize_hex32(order_hash).expect("Invalid order hash format");
+        format!("0x{}", digits)
+    }
+
+    /// Current block timestamp in seconds
+    pub(crate) fn now_seconds() -> u64 {
+        env::block_timestamp() / 1_000_000_000
+    }
+
+    /// Validate and store a new Fusion+ order funded with `deposit`
+    /// of native NEAR (`token == None`) or of the given NEP-141 token
+    pub(crate) fn internal_create_fusion_order(
+        &mut self,
+        params: FusionOrderParams,
+        resolver: AccountId,
+        token: Option<AccountId>,
+        deposit: u128,
+    ) -> FusionPlusOrder {
+        let FusionOrderParams {
+            order_hash,
+            hashlock,
+            maker,
+            amount,
+            resolver_fee,
+            timelocks,
+            source_chain_id,
+            hash_algorithm,
+            parts,
+            destination,
+            payout_to,
+        } = params;
+
+        assert!(!self.paused, "Contract is paused");
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        self.assert_allowed_maker(&maker);
+
+        // Verify resolver is authorized by 1inch and bonded
+        self.assert_bonded_resolver(&resolver, "Not a 1inch authorized resolver");
+
+        // Verify order doesn't exist
+        assert!(self.orders.get(&order_hash).is_none(), "Order already exists");
+
+        // Verify deposit covers amount + resolver fee + safety deposit
+        let total_required = amount.0 + resolver_fee.0;
+        assert!(deposit >= total_required, "Insufficient deposit");
+
+        // Calculate safety deposit (resolver's stake), unless posted in a token
+        let mut safety_deposit = (amount.0 * self.min_safety_deposit_bps as u128) / 10000;
+        if self.has_token_safety_deposit(&resolver, &order_hash) {
+            assert!(
+                self.token_safety_deposit_covers(&resolver, &order_hash, safety_deposit),
+                "Insufficient token safety deposit"
+            );
+            safety_deposit = 0;
+        }
+        assert!(deposit >= total_required + safety_deposit, "Insufficient safety deposit");
+
+        // In escrow mode native orders also fund their escrow account's storage
+        let escrow_storage_cost = (self.escrow_mode && token.is_none()).then(|| self.internal_escrow_storage_cost());
+        if let Some(storage_cost) = escrow_storage_cost {
+            assert!(
+                deposit >= total_required + safety_deposit + storage_cost,
+                "Insufficient escrow storage deposit"
+            );
+        }
+
+        // Validate hashlock format (32 bytes hex, optional 0x prefix)
+        let hashlock = normalize_hex32(&hashlock).expect("Invalid hashlock format");
+        assert!(Timelocks(timelocks.0).is_ordered(), "Invalid timelock stages");
+
+        if let Some(destination) = &destination {
+            assert!(
+                destination.token.len() <= MAX_DESTINATION_FIELD_LEN
+                    && destination.address.len() <= MAX_DESTINATION_FIELD_LEN,
+                "Destination field too long"
+            );
+        }
+
+        self.assert_tvl_cap(&token, total_required + safety_deposit);
+
+        // Create Fusion+ order
+        let order = FusionPlusOrder {
+            order_hash: order_hash.clone(),
+            hashlock,
+            hash_algorithm: hash_algorithm.unwrap_or_default(),
+            timelocks,
+            maker: maker.clone(),
+            resolver,
+            token: token.clone(),
+            amount,
+            resolver_fee,
+            safety_deposit: U128(safety_deposit),
+            status: OrderStatus::Matched,
+            preimage: None,
+            source_chain_id,
+            created_at: Self::now_seconds(),
+            failed_payouts: Vec::new(),
+            parts: parts.unwrap_or(0),
+            filled_amount: U128(0),
+            last_fill_index: None,
+            escrow_account: escrow_storage_cost.map(|_| Self::escrow_account_id(&order_hash)),
+            destination,
+            payout_to,
+        };
+
+        // Order storage is paid from the resolver's NEP-145 balance
+        let initial_storage_usage = env::storage_usage();
+        let state = self.internal_insert_order(order.clone());
+        self.internal_record_locked(&state);
+        self.internal_record_executed(&state);
+        self.internal_record_history(&order_hash, &state.status);
+        self.internal_charge_storage(&order.resolver, initial_storage_usage);
+
+        if let Some(storage_cost) = escrow_storage_cost {
+            let _ = self.internal_create_escrow(
+                &order_hash,
+                &order.resolver,
+                total_required + safety_deposit,
+                storage_cost,
+            );
+        }
+
+        // Emit event for 1inch monitoring
+        FusionEvent::FusionOrderCreated(vec![FusionOrderCreatedEvent {
+            order_hash,
+            maker,
+            resolver: order.resolver.clone(),
+            token,
+            amount,
+            source_chain_id,
+        }])
+        .emit();
+
+        order
+    }
+
+    /// Deposit an order locks: amount, resolver fee, safety deposit and,
+    /// for escrowed orders, the escrow account's storage
+    pub(crate) fn internal_order_deposit(&self, order: &FusionPlusOrder) -> u128 {
+        let escrow_storage_cost = if order.escrow_account.is_some() {
+            self.internal_escrow_storage_cost()
+        } else {
+            0
+        };
+        order.amount.0 + order.resolver_fee.0 + order.safety_deposit.0 + escrow_storage_cost
+    }
+
+    /// Return NEAR attached beyond what the created orders lock
+    fn internal_refund_excess(resolver: AccountId, excess: u128) {
+        if excess > 0 {
+            let _ = Promise::new(resolver).transfer(NearToken::from_yoctonear(excess));
+        }
+    }
+
+    /// Refund a cancelled order to its resolver
+    /// Release all unfilled funds, the safety deposit is split between
+    /// the public canceller (unless a party cancels), the maker (`compensation_bps`
+    /// of the rest) and the resolver
+    pub(crate) fn internal_refund_order(
+        &mut self,
+        order_hash: &String,
+        mut order: OrderState,
+        caller: AccountId,
+        is_party: bool,
+        compensation_bps: u16,
+    ) -> Promise {
+        self.internal_set_status(order_hash, &mut order, OrderStatus::Refunded);
+        self.orders.insert(order_hash, &order);
+
+        let refund_amount = order.amount.0 - order.filled_amount.0
+            + order.resolver_fee.0
+            + order.safety_deposit.0;
+        let reward = if is_party {
+            0
+        } else {
+            order.safety_deposit.0 * PUBLIC_CANCELLATION_REWARD_BPS / 10_000
+        };
+        let compensation =
+            (order.safety_deposit.0 - reward) * compensation_bps as u128 / 10_000;
+        self.internal_record_refund(&order, refund_amount);
+        FusionEvent::FusionOrderRefunded(vec![FusionOrderRefundedEvent {
+            order_hash: order_hash.clone(),
+            resolver: order.resolver.clone(),
+            cancelled_by: caller.clone(),
+            amount: U128(refund_amount),
+            maker_compensation: U128(compensation),
+        }])
+        .emit();
+
+        let mut payouts = self.internal_payout(
+            order_hash,
+            &order,
+            order.resolver.clone(),
+            refund_amount - reward - compensation,
+        );
+        if compensation > 0 {
+            payouts = payouts.and(self.internal_payout(order_hash, &order, order.maker.clone(), compensation));
+        }
+        if reward > 0 {
+            payouts = payouts.and(self.internal_payout(order_hash, &order, caller.clone(), reward));
+        }
+        let token_reward = (!is_party).then_some((caller, PUBLIC_CANCELLATION_REWARD_BPS));
+        let rest_to = Some(order.resolver.clone());
+        if let Some(token_payouts) =
+            self.internal_settle_token_deposit(order_hash, &order, token_reward, compensation_bps, rest_to)
+        {
+            payouts = payouts.and(token_payouts);
+        }
+        payouts
+    }
+
+    /// Receiver of the maker's share of a claim: `payout_to` if set, else the maker
+    fn maker_beneficiary(order: &OrderState, metadata: &OrderMetadata) -> AccountId {
+        metadata.payout_to.clone().unwrap_or_else(|| order.maker.clone())
+    }
+
+    /// Full order by canonical hash, reading both the hot and cold parts
+    pub(crate) fn internal_get_order(&self, order_hash: &String) -> Option<FusionPlusOrder> {
+        let state = self.orders.get(order_hash)?;
+        let metadata = self.order_metadata.get(order_hash)?;
+        Some(FusionPlusOrder::from_parts(order_hash.clone(), state, metadata))
+    }
+
+    /// Store a new order and add it to the enumeration, maker and status indexes
+    /// Returns the hot part for further bookkeeping
+    pub(crate) fn internal_insert_order(&mut self, order: FusionPlusOrder) -> OrderState {
+        let order_hash = order.order_hash.clone();
+        let (state, metadata) = order.into_parts(self.order_hashes.len());
+        self.orders.insert(&order_hash, &state);
+        self.order_metadata.insert(&order_hash, &metadata);
+        self.order_hashes.push(&order_hash);
+        self.order_log.push(&order_hash);
+        self.internal_index_by_maker(&order_hash, &state.maker);
+        self.internal_index_by_status(&order_hash, &state.status);
+        self.internal_index_expiry(&order_hash, &state);
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::{NearToken, PromiseError};
+    use crate::hashlock::NearHasher;
+    use crate::timelocks::TimelockStages;
+    use crate::views::StageCountdown;
+    use crate::signed_release::ReleaseKey;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id(accounts(0))
+            .signer_account_id(predecessor_account_id.clone())
+            .predecessor_account_id(predecessor_account_id)
+            .attached_deposit(NearToken::from_yoctonear(1));
+        builder
+    }
+
+    /// Canonical order hash derived from a readable test label
+    fn order_hash_of(label: &str) -> String {
+        format!("0x{}", hex::encode(env::sha256(label.as_bytes())))
+    }
+
+    /// Stages of a test order: the parties may claim at once, anyone after
+    /// a minute, and it is cancellable after an hour
+    fn open_timelocks() -> U128 {
+        U128(Timelocks::pack([0, 60, 3600, 7200]).0)
+    }
+
+    fn register_storage(contract: &mut FusionPlusNear, account_id: AccountId) {
+        let mut context = get_context(account_id);
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(100))
+            .build());
+        contract.storage_deposit(None, None);
+    }
+
+    /// Add `account_id` as a resolver (called as owner), then register its storage and bond
+    fn register_resolver(contract: &mut FusionPlusNear, account_id: AccountId) {
+        contract.add_resolver(account_id.clone());
+        register_storage(contract, account_id.clone());
+        bond_resolver(contract, account_id);
+    }
+
+    fn bond_resolver(contract: &mut FusionPlusNear, account_id: AccountId) {
+        let mut context = get_context(account_id);
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(5))
+            .build());
+        contract.bond_resolver();
+    }
+
+    #[test]
+    fn test_contract_initialization() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let contract = FusionPlusNear::new(500); // 5% min safety deposit
+        
+        assert_eq!(contract.get_owner(), accounts(1));
+        assert_eq!(contract.get_min_safety_deposit_bps(), 500);
+        assert!(!contract.is_authorized_resolver(accounts(2)));
+    }
+
+    #[test]
+    fn test_add_resolver() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        
+        // Add 1inch resolver
+        assert!(contract.add_resolver(accounts(2)));
+        
+        assert!(contract.is_authorized_resolver(accounts(2)));
+    }
+
+    #[test]
+    fn test_admin_operations_are_idempotent() {
+        // A DAO proposal executed twice must not fail
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        assert!(contract.add_resolver(accounts(2)));
+        assert!(!contract.add_resolver(accounts(2)));
+        assert_eq!(contract.get_resolver_count(), 1);
+
+        assert!(contract.remove_resolver(accounts(2)));
+        assert!(!contract.remove_resolver(accounts(2)));
+        assert_eq!(contract.get_resolvers(None, None).total, 0);
+
+        assert!(contract.pause());
+        assert!(!contract.pause());
+        assert!(contract.unpause());
+        assert!(!contract.unpause());
+        assert!(!contract.is_paused());
+    }
+
+    #[test]
+    fn test_execute_fusion_order() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        
+        // Add resolver
+        register_resolver(&mut contract, accounts(2));
+        
+        // Switch to resolver account
+        let mut context = get_context(accounts(2));
+        let deposit = NearToken::from_near(1).as_yoctonear() + // amount
+                     NearToken::from_millinear(100).as_yoctonear() + // resolver fee
+                     NearToken::from_millinear(50).as_yoctonear(); // safety deposit
+        testing_env!(context
+            .attached_deposit(NearToken::from_yoctonear(deposit))
+            .build());
+        
+        let order = contract.execute_fusion_order(
+            order_hash_of("1234567890abcdef"),
+            "a".repeat(64),
+            accounts(3), // maker
+            accounts(2), // resolver
+            U128(NearToken::from_near(1).as_yoctonear()),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            open_timelocks(),
+            11155111, // Ethereum Sepolia
+            None,
+            None,
+            None,
+            None,
+        );
+        
+        assert_eq!(order.order_hash, order_hash_of("1234567890abcdef"));
+        assert_eq!(order.maker, accounts(3));
+        assert_eq!(order.resolver, accounts(2));
+        assert_eq!(order.status, OrderStatus::Matched);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not a 1inch authorized resolver")]
+    fn test_execute_fusion_order_unauthorized() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        
+        // Don't add resolver - should fail
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(2))
+            .build());
+        
+        contract.execute_fusion_order(
+            order_hash_of("unauthorized"),
+            "a".repeat(64),
+            accounts(3),
+            accounts(2),
+            U128(NearToken::from_near(1).as_yoctonear()),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            open_timelocks(),
+            11155111,
+            None,
+            None,
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Order already exists")]
     fn test_duplicate_order_fails() {
         let context = get_context(accounts(1));
         testing_env!(context.build());
         
         let mut contract = FusionPlusNear::new(500);
-        contract.add_resolver(accounts(2));
+        register_resolver(&mut contract, accounts(2));
         
         let mut context = get_context(accounts(2));
         let deposit = NearToken::from_near(2);
@@ -435,26 +1408,34 @@ mod tests {
         
         // First order succeeds
         contract.execute_fusion_order(
-            "0xduplicate".to_string(),
+            order_hash_of("duplicate"),
             "a".repeat(64),
             accounts(3),
             accounts(2),
             U128(NearToken::from_near(1).as_yoctonear()),
             U128(NearToken::from_millinear(100).as_yoctonear()),
-            U128(0),
+            open_timelocks(),
             11155111,
+            None,
+            None,
+            None,
+            None,
         );
         
         // Second order with same hash should fail
         contract.execute_fusion_order(
-            "0xduplicate".to_string(),
+            order_hash_of("duplicate"),
             "b".repeat(64),
             accounts(3),
             accounts(2),
             U128(NearToken::from_near(1).as_yoctonear()),
             U128(NearToken::from_millinear(100).as_yoctonear()),
-            U128(0),
+            open_timelocks(),
             11155111,
+            None,
+            None,
+            None,
+            None,
         );
     }
 
@@ -465,7 +1446,7 @@ mod tests {
         testing_env!(context.build());
         
         let mut contract = FusionPlusNear::new(500);
-        contract.add_resolver(accounts(2));
+        register_resolver(&mut contract, accounts(2));
         
         let mut context = get_context(accounts(2));
         testing_env!(context
@@ -473,14 +1454,18 @@ mod tests {
             .build());
         
         contract.execute_fusion_order(
-            "0xinvalidhash".to_string(),
+            order_hash_of("invalidhash"),
             "tooshort".to_string(), // Invalid hashlock
             accounts(3),
             accounts(2),
             U128(NearToken::from_near(1).as_yoctonear()),
             U128(NearToken::from_millinear(100).as_yoctonear()),
-            U128(0),
+            open_timelocks(),
             11155111,
+            None,
+            None,
+            None,
+            None,
         );
     }
 
@@ -491,7 +1476,7 @@ mod tests {
         testing_env!(context.build());
         
         let mut contract = FusionPlusNear::new(500);
-        contract.add_resolver(accounts(2));
+        register_resolver(&mut contract, accounts(2));
         
         let mut context = get_context(accounts(2));
         testing_env!(context
@@ -499,14 +1484,18 @@ mod tests {
             .build());
         
         contract.execute_fusion_order(
-            "0xinsufficient".to_string(),
+            order_hash_of("insufficient"),
             "a".repeat(64),
             accounts(3),
             accounts(2),
             U128(NearToken::from_near(1).as_yoctonear()),
             U128(NearToken::from_millinear(100).as_yoctonear()),
-            U128(0),
+            open_timelocks(),
             11155111,
+            None,
+            None,
+            None,
+            None,
         );
     }
 
@@ -517,7 +1506,7 @@ mod tests {
         testing_env!(context.build());
         
         let mut contract = FusionPlusNear::new(500); // 5% safety deposit
-        contract.add_resolver(accounts(2));
+        register_resolver(&mut contract, accounts(2));
         
         let mut context = get_context(accounts(2));
         // Enough for amount + fee but not safety deposit
@@ -528,14 +1517,18 @@ mod tests {
             .build());
         
         contract.execute_fusion_order(
-            "0xnosafety".to_string(),
+            order_hash_of("nosafety"),
             "a".repeat(64),
             accounts(3),
             accounts(2),
             U128(NearToken::from_near(1).as_yoctonear()),
             U128(NearToken::from_millinear(100).as_yoctonear()),
-            U128(0),
+            open_timelocks(),
             11155111,
+            None,
+            None,
+            None,
+            None,
         );
     }
 
@@ -575,7 +1568,7 @@ mod tests {
         testing_env!(context.build());
         
         let mut contract = FusionPlusNear::new(500);
-        contract.add_resolver(accounts(2));
+        register_resolver(&mut contract, accounts(2));
         
         // Check non-existent order
         assert!(contract.get_order("nonexistent".to_string()).is_none());
@@ -587,19 +1580,3995 @@ mod tests {
             .build());
         
         contract.execute_fusion_order(
-            "0xgetorder".to_string(),
+            order_hash_of("getorder"),
             "a".repeat(64),
             accounts(3),
             accounts(2),
             U128(NearToken::from_near(1).as_yoctonear()),
             U128(NearToken::from_millinear(100).as_yoctonear()),
-            U128(0),
+            open_timelocks(),
             11155111,
+            None,
+            None,
+            None,
+            None,
         );
         
         // Verify order exists
-        let order = contract.get_order("0xgetorder".to_string()).unwrap();
-        assert_eq!(order.order_hash, "0xgetorder");
+        let order = contract.get_order(order_hash_of("getorder")).unwrap();
+        assert_eq!(order.order_hash, order_hash_of("getorder"));
         assert_eq!(order.source_chain_id, 11155111);
     }
+
+    #[test]
+    fn test_ft_on_transfer_creates_token_order() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        
+        // Token contract forwards the resolver's ft_transfer_call
+        let context = get_context(accounts(4));
+        testing_env!(context.build());
+        
+        let msg = serde_json::json!({
+            "order_hash": order_hash_of("fttoken"),
+            "hashlock": "a".repeat(64),
+            "maker": accounts(3),
+            "amount": "1000000",
+            "resolver_fee": "10000",
+            "timelocks": open_timelocks(),
+            "source_chain_id": 11155111,
+        }).to_string();
+        
+        // 1_000_000 + 10_000 fee + 50_000 safety deposit, plus 5_000 surplus
+        let result = contract.ft_on_transfer(accounts(2), U128(1_065_000), msg);
+        match result {
+            near_sdk::PromiseOrValue::Value(unused) => assert_eq!(unused.0, 5_000),
+            _ => panic!("Expected unused amount to be returned"),
+        }
+        
+        let order = contract.get_order(order_hash_of("fttoken")).unwrap();
+        assert_eq!(order.token, Some(accounts(4)));
+        assert_eq!(order.resolver, accounts(2));
+        assert_eq!(order.safety_deposit.0, 50_000);
+        assert_eq!(order.status, OrderStatus::Matched);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not a 1inch authorized resolver")]
+    fn test_ft_on_transfer_unauthorized_sender() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        
+        let context = get_context(accounts(4));
+        testing_env!(context.build());
+        
+        let msg = serde_json::json!({
+            "order_hash": order_hash_of("ftunauthorized"),
+            "hashlock": "a".repeat(64),
+            "maker": accounts(3),
+            "amount": "1000000",
+            "resolver_fee": "10000",
+            "timelocks": open_timelocks(),
+            "source_chain_id": 11155111,
+        }).to_string();
+        
+        let _ = contract.ft_on_transfer(accounts(2), U128(2_000_000), msg);
+    }
+
+    #[test]
+    fn test_events_use_nep297_format() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(2))
+            .build());
+        
+        contract.execute_fusion_order(
+            order_hash_of("events"),
+            "a".repeat(64),
+            accounts(3),
+            accounts(2),
+            U128(NearToken::from_near(1).as_yoctonear()),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            open_timelocks(),
+            11155111,
+            None,
+            None,
+            None,
+            None,
+        );
+        
+        let logs = near_sdk::test_utils::get_logs();
+        let event_json = logs.last().unwrap().strip_prefix("EVENT_JSON:").expect("Not a NEP-297 event");
+        let event: serde_json::Value = serde_json::from_str(event_json).unwrap();
+        assert_eq!(event["standard"], "fusion_plus");
+        assert_eq!(event["version"], "1.0.0");
+        assert_eq!(event["event"], "fusion_order_created");
+        assert_eq!(event["data"][0]["order_hash"], order_hash_of("events"));
+        assert_eq!(event["data"][0]["resolver"], accounts(2).to_string());
+    }
+
+    #[test]
+    fn test_events_carry_sequential_nonce() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        assert_eq!(contract.get_event_nonce(), 0);
+        register_resolver(&mut contract, accounts(2));
+        let before = contract.get_event_nonce();
+        assert!(before > 0);
+
+        create_claimable_order(&mut contract, &order_hash_of("nonce"));
+        let nonces: Vec<u64> = near_sdk::test_utils::get_logs()
+            .iter()
+            .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+            .map(|json| serde_json::from_str::<serde_json::Value>(json).unwrap()["nonce"].as_u64().unwrap())
+            .collect();
+        // Nonces continue across calls without gaps
+        assert!(!nonces.is_empty());
+        assert_eq!(nonces, (before + 1..=before + nonces.len() as u64).collect::<Vec<_>>());
+        assert_eq!(contract.get_event_nonce(), before + nonces.len() as u64);
+    }
+
+    #[test]
+    fn test_storage_deposit_and_order_charge() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        contract.add_resolver(accounts(2));
+        assert!(contract.storage_balance_of(accounts(2)).is_none());
+        
+        register_storage(&mut contract, accounts(2));
+        let min = contract.storage_balance_bounds().min.0;
+        let balance = contract.storage_balance_of(accounts(2)).unwrap();
+        assert_eq!(balance.total.0, NearToken::from_millinear(100).as_yoctonear());
+        assert_eq!(balance.available.0, balance.total.0 - min);
+        bond_resolver(&mut contract, accounts(2));
+        
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(2))
+            .build());
+        
+        contract.execute_fusion_order(
+            order_hash_of("storage"),
+            "a".repeat(64),
+            accounts(3),
+            accounts(2),
+            U128(NearToken::from_near(1).as_yoctonear()),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            open_timelocks(),
+            11155111,
+            None,
+            None,
+            None,
+            None,
+        );
+        
+        // Order storage is locked from the resolver's available balance
+        let after = contract.storage_balance_of(accounts(2)).unwrap();
+        assert_eq!(after.total, balance.total);
+        assert!(after.available.0 < balance.available.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Account not registered for storage")]
+    fn test_execute_without_storage_registration() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        contract.add_resolver(accounts(2));
+        bond_resolver(&mut contract, accounts(2));
+        
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(2))
+            .build());
+        
+        contract.execute_fusion_order(
+            order_hash_of("nostorage"),
+            "a".repeat(64),
+            accounts(3),
+            accounts(2),
+            U128(NearToken::from_near(1).as_yoctonear()),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            open_timelocks(),
+            11155111,
+            None,
+            None,
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient storage deposit")]
+    fn test_storage_deposit_below_minimum() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .build());
+        contract.storage_deposit(None, None);
+    }
+
+    fn create_order_with_timelocks(contract: &mut FusionPlusNear, order_hash: &str, timelocks: Timelocks) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(2))
+            .block_timestamp(1_000 * 1_000_000_000)
+            .build());
+        
+        contract.execute_fusion_order(
+            order_hash.to_string(),
+            "a".repeat(64),
+            accounts(3),
+            accounts(2),
+            U128(NearToken::from_near(1).as_yoctonear()),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            U128(timelocks.0),
+            11155111,
+            None,
+            None,
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Cancellation timelock not reached")]
+    fn test_cancel_before_cancellation_stage() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_order_with_timelocks(&mut contract, &order_hash_of("earlycancel"), Timelocks::pack([0, 60, 3600, 7200]));
+        
+        // 10 minutes after creation, cancellation opens after 1 hour
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .block_timestamp(1_600 * 1_000_000_000)
+            .build());
+        let _ = contract.cancel_fusion_order(order_hash_of("earlycancel"));
+    }
+
+    #[test]
+    fn test_cancel_after_cancellation_stage() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_order_with_timelocks(&mut contract, &order_hash_of("latecancel"), Timelocks::pack([0, 60, 3600, 7200]));
+        
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .block_timestamp(4_600 * 1_000_000_000)
+            .build());
+        let _ = contract.cancel_fusion_order(order_hash_of("latecancel"));
+        
+        let order = contract.get_order(order_hash_of("latecancel")).unwrap();
+        assert_eq!(order.status, OrderStatus::Refunded);
+        assert_eq!(order.created_at, 1_000);
+    }
+
+    fn create_claimable_order(contract: &mut FusionPlusNear, order_hash: &str) -> String {
+        let preimage = "11".repeat(32);
+        let hashlock = hex::encode(env::sha256(&hex::decode(&preimage).unwrap()[..]));
+        
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(2))
+            .build());
+        
+        contract.execute_fusion_order(
+            order_hash.to_string(),
+            hashlock,
+            accounts(3),
+            accounts(2),
+            U128(NearToken::from_near(1).as_yoctonear()),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            open_timelocks(),
+            11155111,
+            None,
+            None,
+            None,
+            None,
+        );
+        preimage
+    }
+
+    #[test]
+    fn test_claim_pays_maker_and_resolver() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("atomicclaim"));
+        
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_fusion_order(order_hash_of("atomicclaim"), preimage.clone());
+        
+        let order = contract.get_order(order_hash_of("atomicclaim")).unwrap();
+        assert_eq!(order.status, OrderStatus::Claimed);
+        assert_eq!(order.preimage, Some(preimage));
+        
+        // Maker payout, resolver payout and the finalizing callback
+        let receivers: Vec<AccountId> = near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .collect();
+        assert!(receivers.contains(&accounts(3)));
+        assert!(receivers.contains(&accounts(2)));
+        assert!(receivers.contains(&accounts(0)));
+    }
+
+    #[test]
+    fn test_failed_payout_is_recorded_and_retried() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("failedpayout"));
+        
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_fusion_order(order_hash_of("failedpayout"), preimage);
+        
+        // Maker transfer fails (e.g. deleted account)
+        let maker_amount = U128(NearToken::from_near(1).as_yoctonear());
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let paid = contract.on_payout(
+            order_hash_of("failedpayout"),
+            accounts(3),
+            maker_amount,
+            true,
+            Err(PromiseError::Failed),
+        );
+        
+        assert!(!paid);
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs[0].contains("\"event\":\"payout_failed\""));
+        let order = contract.get_order(order_hash_of("failedpayout")).unwrap();
+        assert_eq!(order.status, OrderStatus::Claimed);
+        assert_eq!(order.failed_payouts, vec![Payout { receiver: accounts(3), amount: maker_amount }]);
+        
+        // Maker retries the stuck payout
+        let context = get_context(accounts(3));
+        testing_env!(context.build());
+        let _ = contract.retry_payouts(order_hash_of("failedpayout"));
+        let order = contract.get_order(order_hash_of("failedpayout")).unwrap();
+        assert!(order.failed_payouts.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "No failed payouts")]
+    fn test_retry_payouts_without_failures() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_claimable_order(&mut contract, &order_hash_of("nofailures"));
+        
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.retry_payouts(order_hash_of("nofailures"));
+    }
+
+    fn create_keccak_order(contract: &mut FusionPlusNear, order_hash: &str, preimage: &str) {
+        let hashlock = hex::encode(env::keccak256(&hex::decode(preimage).unwrap()[..]));
+        
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(2))
+            .build());
+        
+        contract.execute_fusion_order(
+            order_hash.to_string(),
+            hashlock,
+            accounts(3),
+            accounts(2),
+            U128(NearToken::from_near(1).as_yoctonear()),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            open_timelocks(),
+            11155111,
+            Some(HashAlgorithm::Keccak256),
+            None,
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn test_claim_keccak256_hashlock() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = "22".repeat(32);
+        create_keccak_order(&mut contract, &order_hash_of("keccak"), &preimage);
+        
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_fusion_order(order_hash_of("keccak"), preimage);
+        
+        let order = contract.get_order(order_hash_of("keccak")).unwrap();
+        assert_eq!(order.hash_algorithm, HashAlgorithm::Keccak256);
+        assert_eq!(order.status, OrderStatus::Claimed);
+    }
+
+    #[test]
+    #[should_panic(expected = "Preimage doesn't match hashlock")]
+    fn test_keccak256_order_rejects_wrong_preimage() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_keccak_order(&mut contract, &order_hash_of("keccakwrong"), &"22".repeat(32));
+        
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_fusion_order(order_hash_of("keccakwrong"), "33".repeat(32));
+    }
+    /// Merkle tree over `parts + 1` secrets; returns (root, secrets, proofs)
+    fn partial_fill_tree() -> (String, Vec<String>, Vec<Vec<String>>) {
+        let secrets: Vec<String> = ["11", "22", "33"].iter().map(|b| b.repeat(32)).collect();
+        let secret_bytes: Vec<[u8; 32]> = secrets.iter().map(|secret| decode_hex32(secret).unwrap()).collect();
+        let tree = fusion_merkle::SecretTree::from_secrets(&NearHasher, HashAlgorithm::Sha256, &secret_bytes);
+        let proofs = (0..secrets.len())
+            .map(|i| tree.proof(i).iter().map(hex::encode).collect())
+            .collect();
+        (hex::encode(tree.root()), secrets, proofs)
+    }
+
+    fn create_partial_fill_order(contract: &mut FusionPlusNear, order_hash: &str, merkle_root: String) {
+        create_timelocked_partial_fill_order(contract, order_hash, merkle_root, Timelocks(open_timelocks().0), 0);
+    }
+
+    /// Three-part order created at `created_at` seconds with `timelocks`
+    fn create_timelocked_partial_fill_order(
+        contract: &mut FusionPlusNear,
+        order_hash: &str,
+        merkle_root: String,
+        timelocks: Timelocks,
+        created_at: u64,
+    ) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(2))
+            .block_timestamp(created_at * 1_000_000_000)
+            .build());
+        
+        contract.execute_fusion_order(
+            order_hash.to_string(),
+            merkle_root,
+            accounts(3),
+            accounts(2),
+            U128(NearToken::from_near(1).as_yoctonear()),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            U128(timelocks.0),
+            11155111,
+            None,
+            Some(2),
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn test_partial_fills_with_merkle_secrets() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let (root, secrets, proofs) = partial_fill_tree();
+        create_partial_fill_order(&mut contract, &order_hash_of("partial"), root);
+        
+        // First 30% ends in part 0
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_partial_fill(
+            order_hash_of("partial"),
+            U128(NearToken::from_millinear(300).as_yoctonear()),
+            0,
+            secrets[0].clone(),
+            proofs[0].clone(),
+        );
+        let order = contract.get_order(order_hash_of("partial")).unwrap();
+        assert_eq!(order.status, OrderStatus::Matched);
+        assert_eq!(order.filled_amount.0, NearToken::from_millinear(300).as_yoctonear());
+        assert_eq!(order.last_fill_index, Some(0));
+        
+        // Completing the order reveals the extra secret
+        let _ = contract.claim_partial_fill(
+            order_hash_of("partial"),
+            U128(NearToken::from_millinear(700).as_yoctonear()),
+            2,
+            secrets[2].clone(),
+            proofs[2].clone(),
+        );
+        let order = contract.get_order(order_hash_of("partial")).unwrap();
+        assert_eq!(order.status, OrderStatus::Claimed);
+        assert_eq!(order.filled_amount, order.amount);
+        assert_eq!(order.preimage, Some(secrets[2].clone()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Withdrawal timelock not reached")]
+    fn test_partial_fill_before_withdrawal_stage() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let (root, secrets, proofs) = partial_fill_tree();
+        create_timelocked_partial_fill_order(
+            &mut contract,
+            &order_hash_of("partialfinality"),
+            root,
+            Timelocks::pack([600, 1200, 3600, 7200]),
+            1_000,
+        );
+
+        // Still in the finality lock, which ends at 1_600
+        let mut context = get_context(accounts(2));
+        testing_env!(context.block_timestamp(1_300 * 1_000_000_000).build());
+        let _ = contract.claim_partial_fill(
+            order_hash_of("partialfinality"),
+            U128(NearToken::from_millinear(300).as_yoctonear()),
+            0,
+            secrets[0].clone(),
+            proofs[0].clone(),
+        );
+    }
+
+    #[test]
+    fn test_public_partial_fills() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let (root, secrets, proofs) = partial_fill_tree();
+        create_timelocked_partial_fill_order(
+            &mut contract,
+            &order_hash_of("partialpublic"),
+            root,
+            Timelocks::pack([0, 600, 3600, 7200]),
+            1_000,
+        );
+
+        // Resolver is offline, another account fills from public withdrawal
+        let mut context = get_context(accounts(4));
+        testing_env!(context.block_timestamp(1_700 * 1_000_000_000).build());
+        let _ = contract.claim_partial_fill(
+            order_hash_of("partialpublic"),
+            U128(NearToken::from_millinear(300).as_yoctonear()),
+            0,
+            secrets[0].clone(),
+            proofs[0].clone(),
+        );
+        // Only the maker is paid until the order completes
+        assert!(transfers_to(&accounts(4)).is_empty());
+        assert!(transfers_to(&accounts(2)).is_empty());
+        assert!(near_sdk::test_utils::get_logs()[0].contains(&format!("\"filled_by\":\"{}\"", accounts(4))));
+
+        let mut context = get_context(accounts(4));
+        testing_env!(context.block_timestamp(1_700 * 1_000_000_000).build());
+        let _ = contract.claim_partial_fill(
+            order_hash_of("partialpublic"),
+            U128(NearToken::from_millinear(700).as_yoctonear()),
+            2,
+            secrets[2].clone(),
+            proofs[2].clone(),
+        );
+        let order = contract.get_order(order_hash_of("partialpublic")).unwrap();
+        assert_eq!(order.status, OrderStatus::Claimed);
+        // 10% of the 50 mNEAR safety deposit to the filler, the fee to the
+        // resolver and the rest of the deposit held for the challenge window
+        assert_eq!(transfers_to(&accounts(4)), vec![NearToken::from_millinear(5).as_yoctonear()]);
+        assert_eq!(transfers_to(&accounts(2)), vec![NearToken::from_millinear(100).as_yoctonear()]);
+        let held = contract.get_held_deposit(order_hash_of("partialpublic")).unwrap();
+        assert_eq!(held.amount.0, NearToken::from_millinear(45).as_yoctonear());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid secret index for fill amount")]
+    fn test_partial_fill_wrong_secret_index() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let (root, secrets, proofs) = partial_fill_tree();
+        create_partial_fill_order(&mut contract, &order_hash_of("partialindex"), root);
+        
+        // 60% ends in part 1, secret 0 is not valid for it
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_partial_fill(
+            order_hash_of("partialindex"),
+            U128(NearToken::from_millinear(600).as_yoctonear()),
+            0,
+            secrets[0].clone(),
+            proofs[0].clone(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Secret already used")]
+    fn test_partial_fill_secret_reuse() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let (root, secrets, proofs) = partial_fill_tree();
+        create_partial_fill_order(&mut contract, &order_hash_of("partialreuse"), root);
+        
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        for _ in 0..2 {
+            let _ = contract.claim_partial_fill(
+                order_hash_of("partialreuse"),
+                U128(NearToken::from_millinear(100).as_yoctonear()),
+                0,
+                secrets[0].clone(),
+                proofs[0].clone(),
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid Merkle proof")]
+    fn test_partial_fill_invalid_proof() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let (root, secrets, proofs) = partial_fill_tree();
+        create_partial_fill_order(&mut contract, &order_hash_of("partialproof"), root);
+        
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_partial_fill(
+            order_hash_of("partialproof"),
+            U128(NearToken::from_millinear(300).as_yoctonear()),
+            0,
+            secrets[1].clone(),
+            proofs[0].clone(),
+        );
+    }
+
+    #[test]
+    fn test_partial_fill_taken_by_another_resolver() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        testing_env!(get_context(accounts(1)).build());
+        register_resolver(&mut contract, accounts(5));
+        let (root, secrets, proofs) = partial_fill_tree();
+        create_partial_fill_order(&mut contract, &order_hash_of("shared"), root);
+
+        // accounts(5) funds the first half with its own 5% safety deposit
+        let half = NearToken::from_millinear(500).as_yoctonear();
+        testing_env!(get_context(accounts(5)).attached_deposit(NearToken::from_near(1)).build());
+        let fill = contract.execute_partial_fill(order_hash_of("shared"), U128(half));
+        let fill_hash = FusionPlusNear::partial_fill_hash(&order_hash_of("shared"), 0);
+        assert_eq!(fill.order_hash, fill_hash);
+        assert_eq!((fill.resolver.clone(), fill.amount.0), (accounts(5), half));
+        assert_eq!(fill.safety_deposit.0, NearToken::from_millinear(25).as_yoctonear());
+        assert_eq!(
+            contract.get_partial_fill(fill_hash.clone()),
+            Some(PartialFill {
+                order_hash: order_hash_of("shared"),
+                secret_index: 0,
+            })
+        );
+        // The taken share goes back to the order's resolver, the surplus to the filler
+        assert_eq!(transfers_to(&accounts(2)), vec![half]);
+        assert_eq!(transfers_to(&accounts(5)), vec![NearToken::from_millinear(475).as_yoctonear()]);
+        let order = contract.get_order(order_hash_of("shared")).unwrap();
+        assert_eq!((order.status, order.filled_amount.0), (OrderStatus::Matched, half));
+
+        // The fill is claimed whole with its secret, its safety deposit held for its own resolver
+        let _ = contract.claim_partial_fill(fill_hash.clone(), U128(half), 0, secrets[0].clone(), proofs[0].clone());
+        assert_eq!(transfers_to(&accounts(3)), vec![half]);
+        assert_eq!(contract.get_order(fill_hash.clone()).unwrap().status, OrderStatus::Claimed);
+        let held = contract.get_held_deposit(fill_hash).unwrap();
+        assert_eq!((held.resolver, held.amount.0), (accounts(5), NearToken::from_millinear(25).as_yoctonear()));
+
+        // The order's resolver fills the rest
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.claim_partial_fill(order_hash_of("shared"), U128(half), 2, secrets[2].clone(), proofs[2].clone());
+        assert_eq!(contract.get_order(order_hash_of("shared")).unwrap().status, OrderStatus::Claimed);
+        assert_eq!(transfers_to(&accounts(3)), vec![half]);
+        assert_eq!(transfers_to(&accounts(2)), vec![NearToken::from_millinear(100).as_yoctonear()]);
+    }
+
+    #[test]
+    fn test_partial_fill_taking_the_rest_settles_order() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        testing_env!(get_context(accounts(1)).build());
+        register_resolver(&mut contract, accounts(5));
+        let (root, _, _) = partial_fill_tree();
+        create_partial_fill_order(&mut contract, &order_hash_of("takenall"), root);
+
+        let all = NearToken::from_near(1).as_yoctonear();
+        testing_env!(get_context(accounts(5)).attached_deposit(NearToken::from_millinear(1_050)).build());
+        let fill = contract.execute_partial_fill(order_hash_of("takenall"), U128(all));
+        assert_eq!(fill.order_hash, FusionPlusNear::partial_fill_hash(&order_hash_of("takenall"), 2));
+
+        // Nothing is left to fill: the order settles and its resolver gets the share and fee back
+        let order = contract.get_order(order_hash_of("takenall")).unwrap();
+        assert_eq!(order.status, OrderStatus::Claimed);
+        assert_eq!(transfers_to(&accounts(2)), vec![all, NearToken::from_millinear(100).as_yoctonear()]);
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs.iter().any(|log| log.contains("\"event\":\"partial_fill_executed\"")));
+
+        // The fill order lives on its own and is cancelled like any order
+        testing_env!(get_context(accounts(5)).block_timestamp(3_600 * 1_000_000_000).build());
+        let _ = contract.cancel_fusion_order(fill.order_hash.clone());
+        assert_eq!(contract.get_order(fill.order_hash).unwrap().status, OrderStatus::Refunded);
+    }
+
+    #[test]
+    #[should_panic(expected = "Fill orders cannot be split")]
+    fn test_partial_fill_cannot_split_fill_order() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        testing_env!(get_context(accounts(1)).build());
+        register_resolver(&mut contract, accounts(5));
+        let (root, _, _) = partial_fill_tree();
+        create_partial_fill_order(&mut contract, &order_hash_of("splitfill"), root);
+
+        testing_env!(get_context(accounts(5)).attached_deposit(NearToken::from_near(1)).build());
+        let fill = contract.execute_partial_fill(order_hash_of("splitfill"), U128(NearToken::from_millinear(500).as_yoctonear()));
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_near(1)).build());
+        contract.execute_partial_fill(fill.order_hash, U128(NearToken::from_millinear(100).as_yoctonear()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Use claim_partial_fill for multi-part orders")]
+    fn test_claim_rejects_multi_part_order() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let (root, secrets, _) = partial_fill_tree();
+        create_partial_fill_order(&mut contract, &order_hash_of("partialclaim"), root);
+        
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_fusion_order(order_hash_of("partialclaim"), secrets[2].clone());
+    }
+    #[test]
+    fn test_get_orders_paginated() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        for label in ["page1", "page2", "page3"] {
+            create_claimable_order(&mut contract, &order_hash_of(label));
+        }
+        
+        let page = contract.get_orders(Some(1), Some(5));
+        assert_eq!(page.total, 3);
+        let hashes: Vec<String> = page.orders.into_iter().map(|order| order.order_hash).collect();
+        assert_eq!(hashes, vec![order_hash_of("page2"), order_hash_of("page3")]);
+        
+        let page = contract.get_orders(None, Some(1));
+        assert_eq!(page.orders.len(), 1);
+        assert!(contract.get_orders(Some(3), None).orders.is_empty());
+    }
+    #[test]
+    fn test_get_orders_by_maker() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_claimable_order(&mut contract, &order_hash_of("maker1"));
+        create_claimable_order(&mut contract, &order_hash_of("maker2"));
+        
+        let page = contract.get_orders_by_maker(accounts(3), None, None);
+        assert_eq!(page.total, 2);
+        assert!(page.orders.iter().all(|order| order.maker == accounts(3)));
+        assert_eq!(contract.get_orders_by_maker(accounts(3), Some(1), None).orders.len(), 1);
+        assert_eq!(contract.get_orders_by_maker(accounts(4), None, None).total, 0);
+    }
+    #[test]
+    fn test_get_orders_by_status() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("status1"));
+        create_claimable_order(&mut contract, &order_hash_of("status2"));
+        assert_eq!(contract.get_orders_by_status(OrderStatus::Matched, None, None).total, 2);
+        
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_fusion_order(order_hash_of("status1"), preimage);
+        
+        let matched = contract.get_orders_by_status(OrderStatus::Matched, None, None);
+        assert_eq!(matched.total, 1);
+        assert_eq!(matched.orders[0].order_hash, order_hash_of("status2"));
+        let claimed = contract.get_orders_by_status(OrderStatus::Claimed, None, None);
+        assert_eq!(claimed.total, 1);
+        assert_eq!(claimed.orders[0].order_hash, order_hash_of("status1"));
+        assert_eq!(contract.get_orders_by_status(OrderStatus::Refunded, None, None).total, 0);
+    }
+    #[test]
+    fn test_get_stats() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("stats1"));
+        create_claimable_order(&mut contract, &order_hash_of("stats2"));
+        
+        // Each order escrows 1 NEAR + 0.1 NEAR fee + 0.05 NEAR safety deposit
+        let escrow = NearToken::from_millinear(1_150).as_yoctonear();
+        let stats = contract.get_stats();
+        assert_eq!(stats.matched, 2);
+        assert_eq!(stats.total_locked.0, 2 * escrow);
+        assert_eq!(stats.claimed_volume.0, 0);
+        
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_fusion_order(order_hash_of("stats1"), preimage);
+        
+        let stats = contract.get_stats();
+        assert_eq!(stats.matched, 1);
+        assert_eq!(stats.claimed, 1);
+        assert_eq!(stats.total_locked.0, escrow);
+        assert_eq!(stats.claimed_volume.0, NearToken::from_near(1).as_yoctonear());
+        assert_eq!(stats.total_fees.0, NearToken::from_millinear(100).as_yoctonear());
+    }
+    #[test]
+    fn test_batch_get_orders() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_claimable_order(&mut contract, &order_hash_of("batch1"));
+        create_claimable_order(&mut contract, &order_hash_of("batch2"));
+        
+        let orders = contract.batch_get_orders(vec![
+            order_hash_of("batch2"),
+            order_hash_of("missing"),
+            order_hash_of("batch1"),
+        ]);
+        assert_eq!(orders.len(), 3);
+        assert_eq!(orders[0].as_ref().unwrap().order_hash, order_hash_of("batch2"));
+        assert!(orders[1].is_none());
+        assert_eq!(orders[2].as_ref().unwrap().order_hash, order_hash_of("batch1"));
+    }
+    fn write_v0_state() {
+        let mut old = migration::FusionPlusNearV0 {
+            orders: UnorderedMap::new(b"o"),
+            authorized_resolvers: UnorderedMap::new(b"r"),
+            owner: accounts(1),
+            min_safety_deposit_bps: 500,
+        };
+        old.authorized_resolvers.insert(&accounts(2), &true);
+        // Version 0 accepted any order hash string
+        let legacy_hashes = [
+            (order_hash_of("v0matched").to_uppercase().replace("0X", "0x"), OrderStatus::Matched),
+            ("0xfusion1234567890".to_string(), OrderStatus::Claimed),
+        ];
+        for (order_hash, status) in legacy_hashes {
+            old.orders.insert(
+                &order_hash,
+                &migration::FusionPlusOrderV0 {
+                    order_hash: order_hash.clone(),
+                    hashlock: "a".repeat(64),
+                    timelocks: U128(0),
+                    maker: accounts(3),
+                    resolver: accounts(2),
+                    amount: U128(NearToken::from_near(1).as_yoctonear()),
+                    resolver_fee: U128(NearToken::from_millinear(100).as_yoctonear()),
+                    safety_deposit: U128(NearToken::from_millinear(50).as_yoctonear()),
+                    status,
+                    preimage: None,
+                    source_chain_id: 11155111,
+                },
+            );
+        }
+        env::state_write(&old);
+    }
+
+    #[test]
+    fn test_migrate_from_v0() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        write_v0_state();
+        assert_eq!(FusionPlusNear::state_version(), 0);
+        
+        let contract = FusionPlusNear::migrate();
+        
+        assert_eq!(FusionPlusNear::state_version(), migration::CURRENT_STATE_VERSION);
+        assert_eq!(contract.get_owner(), accounts(1));
+        assert!(contract.is_authorized_resolver(accounts(2)));
+        let order = contract.get_order(order_hash_of("v0matched")).unwrap();
+        assert_eq!(order.order_hash, order_hash_of("v0matched"));
+        assert_eq!(order.hash_algorithm, HashAlgorithm::Sha256);
+        assert_eq!(order.token, None);
+        assert_eq!(order.source_chain_id, 11155111);
+        assert_eq!(contract.get_orders(None, None).total, 2);
+        assert_eq!(contract.get_orders_by_maker(accounts(3), None, None).total, 2);
+        let stats = contract.get_stats();
+        assert_eq!(stats.matched, 1);
+        assert_eq!(stats.claimed, 1);
+        assert_eq!(stats.total_locked.0, NearToken::from_millinear(1_150).as_yoctonear());
+    }
+
+    #[test]
+    #[should_panic(expected = "State already at current version")]
+    fn test_migrate_current_state() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let contract = FusionPlusNear::new(500);
+        env::state_write(&contract);
+        
+        FusionPlusNear::migrate();
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown state version")]
+    fn test_migrate_unknown_version() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        write_v0_state();
+        env::storage_write(migration::STATE_VERSION_KEY, &99u32.to_le_bytes());
+        
+        FusionPlusNear::migrate();
+    }
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn test_guardian_pause_blocks_new_orders() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        contract.add_resolver(accounts(2));
+        contract.set_guardian(Some(accounts(4)));
+        register_storage(&mut contract, accounts(2));
+        
+        let context = get_context(accounts(4));
+        testing_env!(context.build());
+        contract.pause();
+        assert!(contract.is_paused());
+        
+        create_claimable_order(&mut contract, &order_hash_of("paused"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner")]
+    fn test_guardian_cannot_unpause() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        contract.set_guardian(Some(accounts(4)));
+        
+        let context = get_context(accounts(4));
+        testing_env!(context.build());
+        contract.pause();
+        contract.unpause();
+    }
+
+    #[test]
+    fn test_refund_while_paused() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_order_with_timelocks(&mut contract, &order_hash_of("pausedrefund"), Timelocks::pack([0, 60, 3600, 7200]));
+        
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        contract.pause();
+        
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .block_timestamp(4_600 * 1_000_000_000)
+            .build());
+        let _ = contract.cancel_fusion_order(order_hash_of("pausedrefund"));
+        assert_eq!(
+            contract.get_order(order_hash_of("pausedrefund")).unwrap().status,
+            OrderStatus::Refunded
+        );
+        
+        // Owner resumes new orders
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        contract.unpause();
+        assert!(!contract.is_paused());
+    }
+    #[test]
+    fn test_two_step_ownership_transfer() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        contract.propose_owner(accounts(4));
+        assert_eq!(contract.get_owner(), accounts(1));
+        assert_eq!(contract.get_pending_owner(), Some(accounts(4)));
+        
+        let context = get_context(accounts(4));
+        testing_env!(context.build());
+        contract.accept_ownership();
+        
+        assert_eq!(contract.get_owner(), accounts(4));
+        assert_eq!(contract.get_pending_owner(), None);
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs[0].contains("\"event\":\"ownership_transferred\""));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only proposed owner can accept")]
+    fn test_accept_ownership_not_proposed() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        contract.propose_owner(accounts(4));
+        
+        let context = get_context(accounts(3));
+        testing_env!(context.build());
+        contract.accept_ownership();
+    }
+    fn create_timelocked_claimable_order(contract: &mut FusionPlusNear, order_hash: &str, timelocks: Timelocks) -> String {
+        let preimage = "11".repeat(32);
+        let hashlock = hex::encode(env::sha256(&hex::decode(&preimage).unwrap()[..]));
+        
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(2))
+            .block_timestamp(1_000 * 1_000_000_000)
+            .build());
+        
+        contract.execute_fusion_order(
+            order_hash.to_string(),
+            hashlock,
+            accounts(3),
+            accounts(2),
+            U128(NearToken::from_near(1).as_yoctonear()),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            U128(timelocks.0),
+            11155111,
+            None,
+            None,
+            None,
+            None,
+        );
+        preimage
+    }
+
+    #[test]
+    #[should_panic(expected = "Only resolver or maker can claim before public withdrawal")]
+    fn test_public_claim_before_public_withdrawal() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_timelocked_claimable_order(
+            &mut contract,
+            &order_hash_of("publicearly"),
+            Timelocks::pack([0, 600, 3600, 7200]),
+        );
+        
+        let mut context = get_context(accounts(4));
+        testing_env!(context
+            .block_timestamp(1_300 * 1_000_000_000)
+            .build());
+        let _ = contract.claim_fusion_order(order_hash_of("publicearly"), preimage);
+    }
+
+    #[test]
+    #[should_panic(expected = "Claim window closed, order is cancellable")]
+    fn test_claim_after_cancellation_stage() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_timelocked_claimable_order(
+            &mut contract,
+            &order_hash_of("lateclaim"),
+            Timelocks::pack([0, 600, 3600, 7200]),
+        );
+
+        // Cancellation opened at 4_600, the maker can no longer be paid
+        let mut context = get_context(accounts(3));
+        testing_env!(context
+            .block_timestamp(4_600 * 1_000_000_000)
+            .build());
+        let _ = contract.claim_fusion_order(order_hash_of("lateclaim"), preimage);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid timelock stages")]
+    fn test_unordered_timelocks_rejected() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        // Every stage open at creation
+        create_order_with_timelocks(&mut contract, &order_hash_of("zerotimelocks"), Timelocks(0));
+    }
+
+    #[test]
+    fn test_public_withdrawal_pays_reward() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_timelocked_claimable_order(
+            &mut contract,
+            &order_hash_of("publicclaim"),
+            Timelocks::pack([0, 600, 3600, 7200]),
+        );
+        
+        // Resolver is offline, another account completes the claim
+        let mut context = get_context(accounts(4));
+        testing_env!(context
+            .block_timestamp(1_700 * 1_000_000_000)
+            .build());
+        let _ = contract.claim_fusion_order(order_hash_of("publicclaim"), preimage);
+        
+        let order = contract.get_order(order_hash_of("publicclaim")).unwrap();
+        assert_eq!(order.status, OrderStatus::Claimed);
+        let receivers: Vec<AccountId> = near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .collect();
+        assert!(receivers.contains(&accounts(3)));
+        assert!(receivers.contains(&accounts(2)));
+        assert!(receivers.contains(&accounts(4)));
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs[0].contains("\"event\":\"fusion_order_status_changed\""));
+        assert!(logs[1].contains(&format!("\"claimed_by\":\"{}\"", accounts(4))));
+    }
+    #[test]
+    #[should_panic(expected = "Only resolver or maker can cancel before public cancellation")]
+    fn test_public_cancel_before_public_cancellation() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_order_with_timelocks(&mut contract, &order_hash_of("publiccancelearly"), Timelocks::pack([0, 60, 3600, 7200]));
+        
+        // Resolver-only cancellation window
+        let mut context = get_context(accounts(4));
+        testing_env!(context
+            .block_timestamp(4_600 * 1_000_000_000)
+            .build());
+        let _ = contract.cancel_fusion_order(order_hash_of("publiccancelearly"));
+    }
+
+    #[test]
+    fn test_public_cancellation_pays_reward() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_order_with_timelocks(&mut contract, &order_hash_of("publiccancel"), Timelocks::pack([0, 60, 3600, 7200]));
+        
+        let mut context = get_context(accounts(4));
+        testing_env!(context
+            .block_timestamp(8_300 * 1_000_000_000)
+            .build());
+        let _ = contract.cancel_fusion_order(order_hash_of("publiccancel"));
+        
+        let order = contract.get_order(order_hash_of("publiccancel")).unwrap();
+        assert_eq!(order.status, OrderStatus::Refunded);
+        let receivers: Vec<AccountId> = near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .collect();
+        assert!(receivers.contains(&accounts(2)));
+        assert!(receivers.contains(&accounts(4)));
+    }
+    #[test]
+    #[should_panic(expected = "Invalid hashlock format")]
+    fn test_non_hex_hashlock_rejected() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(2))
+            .build());
+        
+        contract.execute_fusion_order(
+            order_hash_of("nonhex"),
+            "z".repeat(64),
+            accounts(3),
+            accounts(2),
+            U128(NearToken::from_near(1).as_yoctonear()),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            open_timelocks(),
+            11155111,
+            None,
+            None,
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn test_prefixed_hex_is_normalized() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = "11".repeat(32);
+        let hashlock = hex::encode(env::sha256(&hex::decode(&preimage).unwrap()[..]));
+        
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(2))
+            .build());
+        let order = contract.execute_fusion_order(
+            order_hash_of("prefixed"),
+            format!("0x{}", hashlock.to_uppercase()),
+            accounts(3),
+            accounts(2),
+            U128(NearToken::from_near(1).as_yoctonear()),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            open_timelocks(),
+            11155111,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert_eq!(order.hashlock, hashlock);
+        
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_fusion_order(order_hash_of("prefixed"), format!("0x{}", preimage));
+        let order = contract.get_order(order_hash_of("prefixed")).unwrap();
+        assert_eq!(order.status, OrderStatus::Claimed);
+        assert_eq!(order.preimage, Some(preimage));
+    }
+    #[test]
+    #[should_panic(expected = "Invalid order hash format")]
+    fn test_malformed_order_hash_rejected() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_claimable_order(&mut contract, "0xfusion1234567890");
+    }
+
+    #[test]
+    fn test_order_hash_is_canonicalized() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let canonical = order_hash_of("canonical");
+        let preimage = create_claimable_order(&mut contract, &canonical[2..].to_uppercase());
+        
+        let order = contract.get_order(canonical.to_uppercase().replace("0X", "0x")).unwrap();
+        assert_eq!(order.order_hash, canonical);
+        
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_fusion_order(canonical[2..].to_string(), preimage);
+        assert_eq!(contract.get_order(canonical).unwrap().status, OrderStatus::Claimed);
+    }
+
+    fn withdrawal_tx() -> chain_signatures::EthWithdrawalTx {
+        chain_signatures::EthWithdrawalTx {
+            chain_id: 11155111,
+            nonce: near_sdk::json_types::U64(0),
+            max_priority_fee_per_gas: U128(1_000_000_000),
+            max_fee_per_gas: U128(30_000_000_000),
+            gas_limit: near_sdk::json_types::U64(100_000),
+            escrow: format!("0x{}", "ab".repeat(20)),
+        }
+    }
+
+    #[test]
+    fn test_request_withdrawal_signature() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        contract.add_resolver(accounts(2));
+        contract.set_mpc_signer(Some(accounts(4)));
+        register_storage(&mut contract, accounts(2));
+        bond_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("mpcsign"));
+
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_fusion_order(order_hash_of("mpcsign"), preimage);
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context.attached_deposit(NearToken::from_yoctonear(1)).build());
+        let _ = contract.request_withdrawal_signature(order_hash_of("mpcsign"), withdrawal_tx());
+
+        let receivers: Vec<AccountId> = near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .collect();
+        assert!(receivers.contains(&accounts(4)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Preimage not revealed")]
+    fn test_withdrawal_signature_requires_preimage() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        contract.add_resolver(accounts(2));
+        contract.set_mpc_signer(Some(accounts(4)));
+        register_storage(&mut contract, accounts(2));
+        bond_resolver(&mut contract, accounts(2));
+        create_claimable_order(&mut contract, &order_hash_of("mpcunclaimed"));
+
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.request_withdrawal_signature(order_hash_of("mpcunclaimed"), withdrawal_tx());
+    }
+
+    /// Gas used by `f`, as metered by the mocked runtime
+    fn gas_used<T>(f: impl FnOnce() -> T) -> u64 {
+        let before = env::used_gas().as_gas();
+        f();
+        env::used_gas().as_gas() - before
+    }
+
+    #[test]
+    fn test_order_layout_gas() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let order_hash = order_hash_of("layoutgas");
+        create_claimable_order(&mut contract, &order_hash);
+        let order = contract.get_order(order_hash.clone()).unwrap();
+
+        // Before: whole order in an UnorderedMap
+        let mut legacy: UnorderedMap<String, FusionPlusOrder> = UnorderedMap::new(b"legacy".to_vec());
+        let legacy_insert = gas_used(|| legacy.insert(&order_hash, &order));
+        let legacy_read = gas_used(|| legacy.get(&order_hash));
+
+        // After: hot and cold parts in LookupMaps, transitions read the hot part
+        let hash = format!("0x{}", "ee".repeat(32));
+        let (state, metadata) = order.into_parts(contract.order_hashes.len());
+        let split_insert = gas_used(|| {
+            contract.orders.insert(&hash, &state);
+            contract.order_metadata.insert(&hash, &metadata);
+            contract.order_hashes.push(&hash);
+        });
+        let hot_read = gas_used(|| contract.orders.get(&hash));
+
+        assert!(split_insert < legacy_insert);
+        assert!(hot_read < legacy_read);
+    }
+
+    #[test]
+    #[should_panic(expected = "Resolver bond below minimum")]
+    fn test_execute_with_unbonding_resolver() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+
+        // Unbonding removes the stake from authorization right away
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        contract.start_unbonding(U128(NearToken::from_near(1).as_yoctonear()));
+
+        create_claimable_order(&mut contract, &order_hash_of("underbonded"));
+    }
+
+    #[test]
+    fn test_unbonding_delay_and_withdrawal() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let bond = contract.start_unbonding(U128(NearToken::from_near(2).as_yoctonear()));
+        assert_eq!(bond.amount.0, NearToken::from_near(3).as_yoctonear());
+        assert_eq!(bond.unbonding.0, NearToken::from_near(2).as_yoctonear());
+        assert_eq!(bond.unbonding_at, bonding::DEFAULT_UNBONDING_DELAY);
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .block_timestamp(bonding::DEFAULT_UNBONDING_DELAY * 1_000_000_000)
+            .build());
+        let _ = contract.withdraw_bond();
+        let bond = contract.get_resolver_bond(accounts(2)).unwrap();
+        assert_eq!(bond.unbonding.0, 0);
+
+        // A failed transfer restores the unbonding balance
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let amount = U128(NearToken::from_near(2).as_yoctonear());
+        assert!(!contract.on_bond_withdrawn(accounts(2), amount, Err(PromiseError::Failed)));
+        assert_eq!(contract.get_resolver_bond(accounts(2)).unwrap().unbonding, amount);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unbonding period not over")]
+    fn test_withdraw_bond_before_delay() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        contract.start_unbonding(U128(NearToken::from_near(1).as_yoctonear()));
+        let _ = contract.withdraw_bond();
+    }
+
+    #[test]
+    fn test_protocol_fee_accrues_on_claim() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        contract.set_protocol_fee(30, Some(accounts(5)));
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("protocolfee"));
+
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_fusion_order(order_hash_of("protocolfee"), preimage);
+
+        // 0.3% of the 1 NEAR order amount
+        let fee = NearToken::from_near(1).as_yoctonear() * 30 / 10_000;
+        assert_eq!(contract.get_accrued_fees(None).0, fee);
+        assert_eq!(contract.get_protocol_fee().fee_account, accounts(5));
+
+        let mut context = get_context(accounts(1));
+        testing_env!(context.attached_deposit(NearToken::from_yoctonear(1)).build());
+        let _ = contract.withdraw_fees(None);
+        assert_eq!(contract.get_accrued_fees(None).0, 0);
+
+        // A failed transfer puts the fees back
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        assert!(!contract.on_fees_withdrawn(None, accounts(5), U128(fee), Err(PromiseError::Failed)));
+        assert_eq!(contract.get_accrued_fees(None).0, fee);
+    }
+
+    #[test]
+    #[should_panic(expected = "Protocol fee too high")]
+    fn test_protocol_fee_capped() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        contract.set_protocol_fee(fees::MAX_PROTOCOL_FEE_BPS + 1, None);
+    }
+
+    /// Methods called on `receiver` by the receipts created so far
+    fn called_methods(receiver: &AccountId) -> Vec<String> {
+        near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .filter(|receipt| &receipt.receiver_id == receiver)
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                near_sdk::mock::MockAction::FunctionCallWeight { method_name, .. } => {
+                    Some(String::from_utf8(method_name).unwrap())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_native_payout_wrapped_on_preference() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        contract.set_wnear_contract(Some(accounts(4)));
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("wrapped"));
+
+        let context = get_context(accounts(3));
+        testing_env!(context.build());
+        contract.set_payout_preference(Some(NearAsset::Wrapped));
+
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_fusion_order(order_hash_of("wrapped"), preimage);
+
+        assert_eq!(called_methods(&accounts(4)), vec!["near_deposit", "ft_transfer"]);
+        let receivers: Vec<AccountId> = near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .collect();
+        assert!(!receivers.contains(&accounts(3)));
+    }
+
+    #[test]
+    fn test_wnear_payout_unwrapped_on_preference() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        contract.set_wnear_contract(Some(accounts(4)));
+        register_resolver(&mut contract, accounts(2));
+
+        let context = get_context(accounts(3));
+        testing_env!(context.build());
+        contract.set_payout_preference(Some(NearAsset::Native));
+        assert_eq!(contract.get_payout_preference(accounts(3)), Some(NearAsset::Native));
+
+        // Resolver escrows wNEAR through ft_transfer_call
+        let preimage = "11".repeat(32);
+        let context = get_context(accounts(4));
+        testing_env!(context.build());
+        let msg = serde_json::json!({
+            "order_hash": order_hash_of("unwrapped"),
+            "hashlock": hex::encode(env::sha256(&hex::decode(&preimage).unwrap()[..])),
+            "maker": accounts(3),
+            "amount": "1000000",
+            "resolver_fee": "10000",
+            "timelocks": open_timelocks(),
+            "source_chain_id": 11155111,
+        }).to_string();
+        let _ = contract.ft_on_transfer(accounts(2), U128(1_060_000), msg);
+
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_fusion_order(order_hash_of("unwrapped"), preimage);
+
+        // Maker's share is unwrapped, the resolver still receives wNEAR
+        assert_eq!(called_methods(&accounts(4)), vec!["near_withdraw", "ft_transfer"]);
+        assert!(called_methods(&accounts(0)).contains(&"on_unwrapped".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "wNEAR unwrap failed")]
+    fn test_failed_unwrap_is_not_forwarded() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = FusionPlusNear::new(500);
+
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let _ = contract.on_unwrapped(accounts(3), U128(1_000_000), Err(PromiseError::Failed));
+    }
+
+    #[test]
+    fn test_gasless_maker_claim_via_relayer() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("metatx"));
+
+        // NEP-366: the relayer signs the transaction, the maker's delegate action is the predecessor
+        let mut context = get_context(accounts(3));
+        testing_env!(context
+            .signer_account_id(accounts(5))
+            .build());
+        let _ = contract.claim_fusion_order(order_hash_of("metatx"), preimage);
+
+        let order = contract.get_order(order_hash_of("metatx")).unwrap();
+        assert_eq!(order.status, OrderStatus::Claimed);
+
+        // No public withdrawal reward for the maker, and nothing sent to the relayer
+        let receivers: Vec<AccountId> = near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .collect();
+        assert!(receivers.contains(&accounts(3)));
+        assert!(!receivers.contains(&accounts(5)));
+        assert_eq!(receivers.iter().filter(|receiver| **receiver == accounts(2)).count(), 1);
+    }
+
+    #[test]
+    fn test_cleanup_finalized_orders() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let labels = ["cleanup1", "cleanup2", "cleanup3"];
+        let mut preimages = Vec::new();
+        for label in labels {
+            preimages.push(create_claimable_order(&mut contract, &order_hash_of(label)));
+        }
+        let storage_before_claims = contract.storage_balance_of(accounts(2)).unwrap();
+
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_fusion_order(order_hash_of("cleanup1"), preimages[0].clone());
+        let _ = contract.claim_fusion_order(order_hash_of("cleanup3"), preimages[2].clone());
+
+        // Within the retention period nothing is deleted
+        let hashes: Vec<String> = labels.iter().map(|label| order_hash_of(label)).collect();
+        assert!(contract.cleanup_orders(hashes.clone()).is_empty());
+
+        let mut context = get_context(accounts(4));
+        testing_env!(context
+            .block_timestamp(cleanup::DEFAULT_ORDER_RETENTION * 1_000_000_000)
+            .build());
+        assert!(contract.cleanup_orders(hashes.clone()).is_empty());
+
+        // Orders are deleted once their safety deposits are released
+        let _ = contract.release_safety_deposit(order_hash_of("cleanup1"));
+        let _ = contract.release_safety_deposit(order_hash_of("cleanup3"));
+        let cleaned = contract.cleanup_orders(hashes);
+        assert_eq!(cleaned, vec![order_hash_of("cleanup1"), order_hash_of("cleanup3")]);
+
+        // Still-matched order survives and stays enumerable
+        assert!(contract.get_order(order_hash_of("cleanup1")).is_none());
+        let page = contract.get_orders(None, None);
+        assert_eq!(page.total, 1);
+        assert_eq!(page.orders[0].order_hash, order_hash_of("cleanup2"));
+        assert_eq!(contract.get_orders_by_maker(accounts(3), None, None).total, 1);
+        assert_eq!(contract.get_stats().claimed, 0);
+
+        // Freed storage goes back to the resolver's available balance
+        let storage_after = contract.storage_balance_of(accounts(2)).unwrap();
+        assert!(storage_after.available.0 > storage_before_claims.available.0);
+    }
+
+    #[test]
+    fn test_order_history_storage_charged_and_freed() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let registered = contract.storage_balance_of(accounts(2)).unwrap();
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("history"));
+        let created = contract.storage_balance_of(accounts(2)).unwrap();
+        assert!(created.available.0 < registered.available.0);
+
+        // The transition's history entry is charged to the resolver too
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("history"), preimage);
+        let claimed = contract.storage_balance_of(accounts(2)).unwrap();
+        assert!(claimed.available.0 < created.available.0);
+        assert_eq!(contract.get_order_history(order_hash_of("history")).len(), 2);
+
+        // Cleanup refunds every byte it frees, the history included
+        let mut context = get_context(accounts(4));
+        testing_env!(context
+            .block_timestamp(cleanup::DEFAULT_ORDER_RETENTION * 1_000_000_000)
+            .build());
+        let _ = contract.release_safety_deposit(order_hash_of("history"));
+        let before_cleanup = contract.storage_balance_of(accounts(2)).unwrap();
+        let initial_storage_usage = env::storage_usage();
+        assert_eq!(contract.cleanup_orders(vec![order_hash_of("history")]).len(), 1);
+        let freed_bytes = initial_storage_usage - env::storage_usage();
+        assert!(contract.get_order_history(order_hash_of("history")).is_empty());
+        let after_cleanup = contract.storage_balance_of(accounts(2)).unwrap();
+        assert_eq!(
+            after_cleanup.available.0 - before_cleanup.available.0,
+            freed_bytes as u128 * env::storage_byte_cost().as_yoctonear()
+        );
+    }
+
+    #[test]
+    fn test_export_orders_by_creation_index() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let labels = ["export1", "export2", "export3"];
+        let mut preimages = Vec::new();
+        for label in labels {
+            preimages.push(create_claimable_order(&mut contract, &order_hash_of(label)));
+        }
+
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_fusion_order(order_hash_of("export1"), preimages[0].clone());
+        let mut context = get_context(accounts(4));
+        testing_env!(context
+            .block_timestamp(cleanup::DEFAULT_ORDER_RETENTION * 1_000_000_000)
+            .build());
+        let _ = contract.release_safety_deposit(order_hash_of("export1"));
+        assert_eq!(contract.cleanup_orders(vec![order_hash_of("export1")]).len(), 1);
+
+        // Cleanup compacts `get_orders` but creation indexes stay put
+        assert_eq!(contract.get_orders(None, None).orders[0].order_hash, order_hash_of("export3"));
+        let page = contract.export_orders(None, Some(2));
+        assert_eq!(page.total, 3);
+        assert_eq!(page.next_index, 2);
+        assert_eq!(page.orders[0].creation_index, 0);
+        assert_eq!(page.orders[0].order_hash, order_hash_of("export1"));
+        assert!(page.orders[0].order.is_none());
+        assert_eq!(page.orders[1].order.as_ref().unwrap().order_hash, order_hash_of("export2"));
+
+        let page = contract.export_orders(Some(page.next_index), Some(2));
+        assert_eq!(page.next_index, 3);
+        assert_eq!(page.orders.len(), 1);
+        assert_eq!(page.orders[0].creation_index, 2);
+        assert_eq!(page.orders[0].order_hash, order_hash_of("export3"));
+
+        let page = contract.export_orders(Some(10), None);
+        assert!(page.orders.is_empty());
+        assert_eq!(page.next_index, 3);
+    }
+
+    #[test]
+    fn test_cleanup_keeps_orders_with_failed_payouts() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("cleanupfailed"));
+
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_fusion_order(order_hash_of("cleanupfailed"), preimage);
+
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        contract.on_payout(
+            order_hash_of("cleanupfailed"),
+            accounts(3),
+            U128(NearToken::from_near(1).as_yoctonear()),
+            true,
+            Err(PromiseError::Failed),
+        );
+
+        let mut context = get_context(accounts(4));
+        testing_env!(context
+            .block_timestamp(cleanup::DEFAULT_ORDER_RETENTION * 1_000_000_000)
+            .build());
+        assert!(contract.cleanup_orders(vec![order_hash_of("cleanupfailed")]).is_empty());
+        assert!(contract.get_order(order_hash_of("cleanupfailed")).is_some());
+    }
+
+    fn enable_escrow_mode(contract: &mut FusionPlusNear) {
+        let mut context = get_context(accounts(1)).build();
+        context.input = b"\0asm escrow".to_vec().into();
+        testing_env!(context);
+        contract.set_escrow_code();
+        contract.set_escrow_mode(true);
+    }
+
+    #[test]
+    fn test_escrow_mode_funds_order_sub_account() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        enable_escrow_mode(&mut contract);
+        register_resolver(&mut contract, accounts(2));
+        let order_hash = order_hash_of("escrowed");
+        let preimage = create_claimable_order(&mut contract, &order_hash);
+
+        let escrow: AccountId = format!("{}.{}", &order_hash[2..10], accounts(0)).parse().unwrap();
+        let order = contract.get_order(order_hash.clone()).unwrap();
+        assert_eq!(order.escrow_account, Some(escrow.clone()));
+        assert_eq!(called_methods(&escrow), vec!["new".to_string()]);
+
+        // Payouts are released by the escrow instead of this contract
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let _ = contract.claim_fusion_order(order_hash, preimage);
+        assert_eq!(called_methods(&escrow), vec!["release".to_string(), "release".to_string()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient escrow storage deposit")]
+    fn test_escrow_mode_requires_storage_deposit() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        enable_escrow_mode(&mut contract);
+        register_resolver(&mut contract, accounts(2));
+
+        // Exactly amount + resolver fee + safety deposit, nothing for the escrow
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(1_150))
+            .build());
+        contract.execute_fusion_order(
+            order_hash_of("escrowshort"),
+            "22".repeat(32),
+            accounts(3),
+            accounts(2),
+            U128(NearToken::from_near(1).as_yoctonear()),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            open_timelocks(),
+            11155111,
+            None,
+            None,
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn test_failed_escrow_creation_pays_from_contract() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        enable_escrow_mode(&mut contract);
+        register_resolver(&mut contract, accounts(2));
+        let order_hash = order_hash_of("escrowfail");
+        create_claimable_order(&mut contract, &order_hash);
+
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let storage_cost = contract.get_escrow_config().storage_cost;
+        assert_eq!(contract.get_solvency().fusion_locked.0, 0);
+        assert!(!contract.on_escrow_created(
+            order_hash.clone(),
+            accounts(2),
+            storage_cost,
+            Err(PromiseError::Failed),
+        ));
+        assert_eq!(contract.get_order(order_hash).unwrap().escrow_account, None);
+        // The refunded funds are now owed by the contract itself
+        assert_eq!(
+            contract.get_solvency().fusion_locked.0,
+            NearToken::from_millinear(1_150).as_yoctonear()
+        );
+    }
+
+    fn transfers_to(receiver: &AccountId) -> Vec<u128> {
+        near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .filter(|receipt| &receipt.receiver_id == receiver)
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                near_sdk::mock::MockAction::Transfer { deposit, .. } => Some(deposit.as_yoctonear()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cancel_compensates_maker_with_safety_deposit() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_order_with_timelocks(&mut contract, &order_hash_of("slashed"), Timelocks::pack([0, 60, 3600, 7200]));
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .block_timestamp(4_600 * 1_000_000_000)
+            .build());
+        let _ = contract.cancel_fusion_order(order_hash_of("slashed"));
+
+        // Safety deposit (5% of 1 NEAR) goes to the maker, amount + fee back to the resolver
+        assert_eq!(transfers_to(&accounts(3)), vec![NearToken::from_millinear(50).as_yoctonear()]);
+        assert_eq!(transfers_to(&accounts(2)), vec![NearToken::from_millinear(1_100).as_yoctonear()]);
+    }
+
+    #[test]
+    fn test_public_cancel_compensation_share() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        contract.set_maker_compensation_bps(5_000);
+        register_resolver(&mut contract, accounts(2));
+        create_order_with_timelocks(&mut contract, &order_hash_of("halfslash"), Timelocks::pack([0, 60, 3600, 7200]));
+
+        let mut context = get_context(accounts(4));
+        testing_env!(context
+            .block_timestamp(8_300 * 1_000_000_000)
+            .build());
+        let _ = contract.cancel_fusion_order(order_hash_of("halfslash"));
+
+        // 10% reward to the canceller, half of the remaining 45 mNEAR to the maker
+        assert_eq!(transfers_to(&accounts(4)), vec![NearToken::from_millinear(5).as_yoctonear()]);
+        assert_eq!(transfers_to(&accounts(3)), vec![NearToken::from_micronear(22_500).as_yoctonear()]);
+        assert_eq!(transfers_to(&accounts(2)), vec![NearToken::from_micronear(1_122_500).as_yoctonear()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid compensation ratio")]
+    fn test_compensation_ratio_bounded() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        contract.set_maker_compensation_bps(10_001);
+    }
+
+    fn batch_params(label: &str) -> FusionOrderParams {
+        FusionOrderParams {
+            order_hash: order_hash_of(label),
+            hashlock: "a".repeat(64),
+            maker: accounts(3),
+            amount: U128(NearToken::from_near(1).as_yoctonear()),
+            resolver_fee: U128(NearToken::from_millinear(100).as_yoctonear()),
+            timelocks: open_timelocks(),
+            source_chain_id: 11155111,
+            hash_algorithm: None,
+            parts: None,
+            destination: None,
+            payout_to: None,
+        }
+    }
+
+    #[test]
+    fn test_execute_fusion_orders_batch() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+
+        // 2 x (1 NEAR + 0.1 NEAR fee + 0.05 NEAR safety deposit)
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(2_300))
+            .build());
+        let orders = contract.execute_fusion_orders(
+            accounts(2),
+            vec![batch_params("batch1"), batch_params("batch2")],
+        );
+
+        assert_eq!(orders.len(), 2);
+        assert!(contract.get_order(order_hash_of("batch1")).is_some());
+        assert!(contract.get_order(order_hash_of("batch2")).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient deposit")]
+    fn test_execute_fusion_orders_aggregate_deposit() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+
+        // Covers the first order but not the second
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(2_200))
+            .build());
+        contract.execute_fusion_orders(
+            accounts(2),
+            vec![batch_params("short1"), batch_params("short2")],
+        );
+    }
+
+    #[test]
+    fn test_lifecycle_events() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_order_with_timelocks(&mut contract, &order_hash_of("lifecycle"), Timelocks::pack([0, 60, 3600, 7200]));
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .block_timestamp(4_600 * 1_000_000_000)
+            .build());
+        let _ = contract.cancel_fusion_order(order_hash_of("lifecycle"));
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs[0].contains("\"event\":\"fusion_order_status_changed\""));
+        assert!(logs[0].contains("\"old_status\":\"Matched\",\"new_status\":\"Refunded\""));
+        assert!(logs[1].contains("\"event\":\"fusion_order_refunded\""));
+
+        // Successful payouts are reported as well as failed ones
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        assert!(contract.on_payout(
+            order_hash_of("lifecycle"),
+            accounts(2),
+            U128(NearToken::from_millinear(1_100).as_yoctonear()),
+            true,
+            Ok(()),
+        ));
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs[0].contains("\"event\":\"payout_sent\""));
+        assert!(logs[0].contains(&format!("\"receiver\":\"{}\"", accounts(2))));
+    }
+
+    #[test]
+    fn test_maker_cancels_after_cancellation_stage() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_order_with_timelocks(&mut contract, &order_hash_of("makercancel"), Timelocks::pack([0, 60, 3600, 7200]));
+
+        // Resolver vanished; the maker forces the refund without waiting for public cancellation
+        let mut context = get_context(accounts(3));
+        testing_env!(context
+            .block_timestamp(4_600 * 1_000_000_000)
+            .build());
+        let _ = contract.cancel_fusion_order(order_hash_of("makercancel"));
+
+        let order = contract.get_order(order_hash_of("makercancel")).unwrap();
+        assert_eq!(order.status, OrderStatus::Refunded);
+        assert_eq!(transfers_to(&accounts(3)), vec![NearToken::from_millinear(50).as_yoctonear()]);
+        assert_eq!(transfers_to(&accounts(2)), vec![NearToken::from_millinear(1_100).as_yoctonear()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cancellation timelock not reached")]
+    fn test_maker_cancel_before_cancellation_stage() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_order_with_timelocks(&mut contract, &order_hash_of("makerearly"), Timelocks::pack([0, 60, 3600, 7200]));
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context
+            .block_timestamp(1_600 * 1_000_000_000)
+            .build());
+        let _ = contract.cancel_fusion_order(order_hash_of("makerearly"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Resolver must be the caller")]
+    fn test_execute_for_other_resolver_rejected() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+
+        // accounts(4) funds an order in the name of the authorized resolver
+        let mut context = get_context(accounts(4));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(2))
+            .build());
+        contract.execute_fusion_order(
+            order_hash_of("impersonated"),
+            "a".repeat(64),
+            accounts(3),
+            accounts(2),
+            U128(NearToken::from_near(1).as_yoctonear()),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            open_timelocks(),
+            11155111,
+            None,
+            None,
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Resolver must be the caller")]
+    fn test_batch_for_other_resolver_rejected() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+
+        let mut context = get_context(accounts(4));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(2))
+            .build());
+        contract.execute_fusion_orders(accounts(2), vec![batch_params("impersonatedbatch")]);
+    }
+
+    #[test]
+    fn test_excess_deposit_refunded() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+
+        // 2 NEAR attached, 1 + 0.1 fee + 0.05 safety deposit required
+        create_claimable_order(&mut contract, &order_hash_of("excess"));
+        assert_eq!(transfers_to(&accounts(2)), vec![NearToken::from_millinear(850).as_yoctonear()]);
+    }
+
+    #[test]
+    fn test_batch_excess_deposit_refunded() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(2_400))
+            .build());
+        contract.execute_fusion_orders(
+            accounts(2),
+            vec![batch_params("batchexcess1"), batch_params("batchexcess2")],
+        );
+        assert_eq!(transfers_to(&accounts(2)), vec![NearToken::from_millinear(100).as_yoctonear()]);
+    }
+
+    #[test]
+    fn test_exact_deposit_not_refunded() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(1_150))
+            .build());
+        contract.execute_fusion_orders(accounts(2), vec![batch_params("exact")]);
+        assert!(transfers_to(&accounts(2)).is_empty());
+    }
+
+    #[test]
+    fn test_check_preimage_and_active_stage() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let order_hash = order_hash_of("checkpreimage");
+        let preimage = create_claimable_order(&mut contract, &order_hash);
+
+        assert!(contract.check_preimage(order_hash.clone(), preimage.clone()));
+        assert!(contract.check_preimage(order_hash.to_uppercase().replacen("0X", "0x", 1), format!("0x{}", preimage)));
+        assert!(!contract.check_preimage(order_hash.clone(), "22".repeat(32)));
+        assert!(!contract.check_preimage(order_hash.clone(), "not hex".to_string()));
+        assert!(!contract.check_preimage(order_hash_of("unknown"), preimage));
+        assert_eq!(contract.get_active_stage(order_hash), Some(TimelockStage::Withdrawal));
+        assert_eq!(contract.get_active_stage(order_hash_of("unknown")), None);
+    }
+
+    #[test]
+    fn test_get_timelock_stages() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let order_hash = order_hash_of("stages");
+        create_order_with_timelocks(&mut contract, &order_hash, Timelocks::pack([60, 3600, 7200, 10800]));
+
+        assert_eq!(
+            contract.get_timelock_stages(order_hash.to_uppercase().replacen("0X", "0x", 1)),
+            Some(TimelockStages {
+                finality: 1_000,
+                withdrawal: 1_060,
+                public_withdrawal: 4_600,
+                cancellation: 8_200,
+                public_cancellation: 11_800,
+            })
+        );
+        assert_eq!(contract.get_timelock_stages(order_hash_of("unknown")), None);
+        assert_eq!(contract.get_timelock_stages("not hex".to_string()), None);
+    }
+
+    #[test]
+    fn test_get_stage_countdown() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let order_hash = order_hash_of("countdown");
+        create_order_with_timelocks(&mut contract, &order_hash, Timelocks::pack([60, 3600, 7200, 10800]));
+
+        assert_eq!(
+            contract.get_stage_countdown(order_hash.clone()),
+            Some(StageCountdown {
+                now: 1_000,
+                stage: None,
+                next_stage: Some(TimelockStage::Withdrawal),
+                next_stage_at: Some(1_060),
+                seconds_remaining: Some(60),
+            })
+        );
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context.block_timestamp(5_000 * 1_000_000_000).build());
+        assert_eq!(
+            contract.get_stage_countdown(order_hash.clone()),
+            Some(StageCountdown {
+                now: 5_000,
+                stage: Some(TimelockStage::PublicWithdrawal),
+                next_stage: Some(TimelockStage::Cancellation),
+                next_stage_at: Some(8_200),
+                seconds_remaining: Some(3_200),
+            })
+        );
+
+        testing_env!(context.block_timestamp(20_000 * 1_000_000_000).build());
+        let countdown = contract.get_stage_countdown(order_hash.clone()).unwrap();
+        assert_eq!(countdown.stage, Some(TimelockStage::PublicCancellation));
+        assert_eq!(countdown.next_stage, None);
+        assert_eq!(countdown.seconds_remaining, None);
+
+        let _ = contract.cancel_fusion_order(order_hash.clone());
+        assert_eq!(contract.get_stage_countdown(order_hash), None);
+        assert_eq!(contract.get_stage_countdown(order_hash_of("unknown")), None);
+    }
+
+    fn destination(address: String) -> DestinationInfo {
+        DestinationInfo {
+            chain_id: 11155111,
+            token: format!("0x{}", "ab".repeat(20)),
+            address,
+            amount: U128(1_000_000),
+        }
+    }
+
+    #[test]
+    fn test_order_destination_metadata() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(2))
+            .build());
+        let mut params = batch_params("destination");
+        params.destination = Some(destination(format!("0x{}", "cd".repeat(20))));
+        contract.execute_fusion_orders(accounts(2), vec![params]);
+
+        let order = contract.get_order(order_hash_of("destination")).unwrap();
+        assert_eq!(order.destination, Some(destination(format!("0x{}", "cd".repeat(20)))));
+    }
+
+    #[test]
+    #[should_panic(expected = "Destination field too long")]
+    fn test_order_destination_bounded() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(2))
+            .build());
+        let mut params = batch_params("longdestination");
+        params.destination = Some(destination("a".repeat(MAX_DESTINATION_FIELD_LEN + 1)));
+        contract.execute_fusion_orders(accounts(2), vec![params]);
+    }
+
+    #[test]
+    fn test_process_expired_refunds_with_bounty() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_order_with_timelocks(&mut contract, &order_hash_of("expired1"), Timelocks::pack([0, 60, 3600, 7200]));
+        create_order_with_timelocks(&mut contract, &order_hash_of("expired2"), Timelocks::pack([0, 60, 3600, 7200]));
+        create_order_with_timelocks(&mut contract, &order_hash_of("expired3"), Timelocks::pack([0, 60, 3600, 7200]));
+        create_order_with_timelocks(&mut contract, &order_hash_of("notexpired"), Timelocks::pack([0, 60, 3600, 72_000]));
+
+        let mut context = get_context(accounts(4));
+        testing_env!(context
+            .block_timestamp(8_300 * 1_000_000_000)
+            .build());
+        assert_eq!(contract.process_expired(Some(2)).len(), 2);
+        assert_eq!(
+            transfers_to(&accounts(4)),
+            vec![NearToken::from_millinear(5).as_yoctonear(); 2]
+        );
+
+        let processed = contract.process_expired(None);
+        assert_eq!(processed.len(), 1);
+        assert_eq!(contract.get_order(processed[0].clone()).unwrap().status, OrderStatus::Refunded);
+        assert_eq!(contract.get_order(order_hash_of("notexpired")).unwrap().status, OrderStatus::Matched);
+        assert!(contract.process_expired(None).is_empty());
+    }
+
+    #[test]
+    fn test_process_expired_by_deadline() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_order_with_timelocks(&mut contract, &order_hash_of("late"), Timelocks::pack([0, 60, 3600, 9000]));
+        create_order_with_timelocks(&mut contract, &order_hash_of("early"), Timelocks::pack([0, 60, 3600, 7200]));
+        create_order_with_timelocks(&mut contract, &order_hash_of("extended"), Timelocks::pack([0, 60, 3600, 7200]));
+
+        // Public cancellation of "extended" moves from 8_200 to 12_200
+        testing_env!(get_context(accounts(3)).block_timestamp(2_000 * 1_000_000_000).build());
+        contract.approve_timelock_extension(order_hash_of("extended"), 8_600);
+        testing_env!(get_context(accounts(2)).block_timestamp(2_000 * 1_000_000_000).build());
+        contract.extend_timelock(order_hash_of("extended"), 8_600);
+
+        // The earliest deadline goes first, whatever the creation order
+        testing_env!(get_context(accounts(5)).block_timestamp(10_500 * 1_000_000_000).build());
+        assert_eq!(contract.process_expired(Some(1)), vec![order_hash_of("early")]);
+        assert_eq!(contract.process_expired(None), vec![order_hash_of("late")]);
+        assert!(contract.process_expired(None).is_empty());
+
+        // The resolver's operator is a party and earns no bounty
+        testing_env!(get_context(accounts(2)).build());
+        contract.set_operator(Some(accounts(4)));
+        testing_env!(get_context(accounts(4)).block_timestamp(12_200 * 1_000_000_000).build());
+        assert_eq!(contract.process_expired(None), vec![order_hash_of("extended")]);
+        assert!(transfers_to(&accounts(4)).is_empty());
+        assert_eq!(contract.get_order(order_hash_of("extended")).unwrap().status, OrderStatus::Refunded);
+    }
+
+    #[test]
+    fn test_operator_claims_for_resolver() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        testing_env!(get_context(accounts(2)).build());
+        contract.set_operator(Some(accounts(4)));
+        assert_eq!(contract.get_operator(accounts(2)), Some(accounts(4)));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("operatorclaim"));
+
+        // accounts(4) only holds a key for the claim, the resolver is still paid
+        testing_env!(get_context(accounts(4)).attached_deposit(NearToken::from_yoctonear(0)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("operatorclaim"), preimage);
+
+        let order = contract.get_order(order_hash_of("operatorclaim")).unwrap();
+        assert_eq!(order.status, OrderStatus::Claimed);
+        let receivers: Vec<AccountId> = near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .collect();
+        assert!(receivers.contains(&accounts(2)));
+        assert!(!receivers.contains(&accounts(4)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Not an authorized resolver")]
+    fn test_set_operator_requires_resolver() {
+        testing_env!(get_context(accounts(1)).build());
+        let mut contract = FusionPlusNear::new(500);
+
+        testing_env!(get_context(accounts(4)).build());
+        contract.set_operator(Some(accounts(5)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn test_owner_methods_reject_function_call_keys() {
+        testing_env!(get_context(accounts(1)).build());
+        let mut contract = FusionPlusNear::new(500);
+
+        // Function-call access keys cannot attach a deposit
+        testing_env!(get_context(accounts(1)).attached_deposit(NearToken::from_yoctonear(0)).build());
+        contract.add_resolver(accounts(2));
+    }
+
+    fn setup_claim_prover(contract: &mut FusionPlusNear) {
+        testing_env!(get_context(accounts(1)).build());
+        contract.set_claim_prover(Some(accounts(5)));
+    }
+
+    #[test]
+    fn test_request_claim_resumed_with_proof() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        setup_claim_prover(&mut contract);
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("proofclaim"));
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.request_claim(order_hash_of("proofclaim"));
+        assert!(contract.pending_claims.get(&order_hash_of("proofclaim")).is_some());
+
+        // The prover's submit_claim_proof resumes the yield callback with the preimage
+        testing_env!(get_context(accounts(0)).build());
+        assert!(contract.on_claim_proof(order_hash_of("proofclaim"), Ok(preimage.clone())));
+
+        let order = contract.get_order(order_hash_of("proofclaim")).unwrap();
+        assert_eq!(order.status, OrderStatus::Claimed);
+        assert_eq!(order.preimage, Some(preimage));
+        assert!(contract.pending_claims.get(&order_hash_of("proofclaim")).is_none());
+    }
+
+    #[test]
+    fn test_timed_out_claim_request_can_be_retried() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        setup_claim_prover(&mut contract);
+        create_claimable_order(&mut contract, &order_hash_of("prooftimeout"));
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.request_claim(order_hash_of("prooftimeout"));
+
+        testing_env!(get_context(accounts(0)).build());
+        assert!(!contract.on_claim_proof(order_hash_of("prooftimeout"), Err(PromiseError::Failed)));
+        assert_eq!(
+            contract.get_order(order_hash_of("prooftimeout")).unwrap().status,
+            OrderStatus::Matched
+        );
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.request_claim(order_hash_of("prooftimeout"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only claim prover")]
+    fn test_claim_proof_only_from_prover() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        setup_claim_prover(&mut contract);
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("proofauth"));
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.request_claim(order_hash_of("proofauth"));
+        contract.submit_claim_proof(order_hash_of("proofauth"), preimage);
+    }
+
+    #[test]
+    #[should_panic(expected = "Preimage doesn't match hashlock")]
+    fn test_claim_proof_rejects_wrong_preimage() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        setup_claim_prover(&mut contract);
+        create_claimable_order(&mut contract, &order_hash_of("proofwrong"));
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.request_claim(order_hash_of("proofwrong"));
+
+        testing_env!(get_context(accounts(5)).build());
+        contract.submit_claim_proof(order_hash_of("proofwrong"), "22".repeat(32));
+    }
+
+    #[test]
+    fn test_order_history() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("history"));
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context
+            .block_timestamp(2_000 * 1_000_000_000)
+            .build());
+        let _ = contract.claim_fusion_order(order_hash_of("history"), preimage);
+
+        let history = contract.get_order_history(order_hash_of("history"));
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].status, OrderStatus::Matched);
+        assert_eq!(history[0].actor, accounts(2));
+        assert_eq!(history[1].status, OrderStatus::Claimed);
+        assert_eq!(history[1].actor, accounts(3));
+        assert_eq!(history[1].timestamp, 2_000);
+        assert!(contract.get_order_history(order_hash_of("unknown")).is_empty());
+    }
+
+    #[test]
+    fn test_claim_pays_payout_to() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = "11".repeat(32);
+        let mut params = batch_params("payoutto");
+        params.hashlock = hex::encode(env::sha256(&hex::decode(&preimage).unwrap()[..]));
+        params.payout_to = Some(accounts(4));
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_millinear(1_150))
+            .build());
+        contract.execute_fusion_orders(accounts(2), vec![params]);
+        assert_eq!(contract.get_order(order_hash_of("payoutto")).unwrap().payout_to, Some(accounts(4)));
+
+        testing_env!(get_context(accounts(3)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("payoutto"), preimage);
+
+        // The maker's share goes to the vault, not the maker
+        let receivers: Vec<AccountId> = near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .collect();
+        assert!(receivers.contains(&accounts(4)));
+        assert!(!receivers.contains(&accounts(3)));
+    }
+
+    #[test]
+    fn test_extend_timelock_with_consent() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_order_with_timelocks(&mut contract, &order_hash_of("extend"), Timelocks::pack([0, 60, 3600, 7200]));
+
+        // Cancellation opens at 4_600; the maker agrees to push it to 8_600
+        testing_env!(get_context(accounts(3)).block_timestamp(2_000 * 1_000_000_000).build());
+        contract.approve_timelock_extension(order_hash_of("extend"), 8_600);
+
+        testing_env!(get_context(accounts(2)).block_timestamp(2_000 * 1_000_000_000).build());
+        let timelocks = contract.extend_timelock(order_hash_of("extend"), 8_600);
+        assert_eq!(timelocks, U128(Timelocks::pack([0, 60, 7600, 11200]).0));
+        assert!(contract.get_timelock_extension(order_hash_of("extend")).is_none());
+
+        testing_env!(get_context(accounts(2)).block_timestamp(4_600 * 1_000_000_000).build());
+        assert_eq!(
+            contract.get_active_stage(order_hash_of("extend")),
+            Some(TimelockStage::PublicWithdrawal)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Counterparty has not approved this extension")]
+    fn test_extend_timelock_requires_counterparty() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_order_with_timelocks(&mut contract, &order_hash_of("selfextend"), Timelocks::pack([0, 60, 3600, 7200]));
+
+        // The resolver's own approval does not count
+        testing_env!(get_context(accounts(2)).build());
+        contract.approve_timelock_extension(order_hash_of("selfextend"), 8_600);
+        contract.extend_timelock(order_hash_of("selfextend"), 8_600);
+    }
+
+    #[test]
+    #[should_panic(expected = "Timelock can only be extended")]
+    fn test_timelock_cannot_be_shortened() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_order_with_timelocks(&mut contract, &order_hash_of("shorten"), Timelocks::pack([0, 60, 3600, 7200]));
+
+        testing_env!(get_context(accounts(3)).build());
+        contract.approve_timelock_extension(order_hash_of("shorten"), 3_000);
+    }
+
+    #[test]
+    fn test_reassign_resolver_with_maker_consent() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        testing_env!(get_context(accounts(1)).build());
+        register_resolver(&mut contract, accounts(4));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("reassign"));
+        let available = |contract: &FusionPlusNear, account_id| contract.storage_balance_of(account_id).unwrap().available.0;
+        let (previous_available, new_available) = (available(&contract, accounts(2)), available(&contract, accounts(4)));
+
+        testing_env!(get_context(accounts(3)).build());
+        contract.approve_resolver_reassignment(order_hash_of("reassign"), accounts(4));
+        assert_eq!(contract.get_resolver_reassignment(order_hash_of("reassign")), Some(accounts(4)));
+
+        testing_env!(get_context(accounts(4)).attached_deposit(NearToken::from_near(1)).build());
+        contract.reassign_resolver(order_hash_of("reassign"));
+        assert_eq!(contract.get_order(order_hash_of("reassign")).unwrap().resolver, accounts(4));
+        assert!(contract.get_resolver_reassignment(order_hash_of("reassign")).is_none());
+
+        // The previous resolver gets its safety deposit back, the surplus is refunded
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        assert!(receipts.iter().any(|receipt| receipt.receiver_id == accounts(2)));
+        assert!(receipts.iter().any(|receipt| receipt.receiver_id == accounts(4)));
+
+        // The order's storage is now charged to the new resolver
+        assert!(available(&contract, accounts(2)) > previous_available);
+        assert!(available(&contract, accounts(4)) < new_available);
+
+        testing_env!(get_context(accounts(4)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("reassign"), preimage);
+        assert_eq!(contract.get_order(order_hash_of("reassign")).unwrap().status, OrderStatus::Claimed);
+    }
+
+    #[test]
+    #[should_panic(expected = "Reassignment not approved")]
+    fn test_reassign_resolver_requires_approval() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        testing_env!(get_context(accounts(1)).build());
+        register_resolver(&mut contract, accounts(4));
+        create_claimable_order(&mut contract, &order_hash_of("unapproved"));
+
+        testing_env!(get_context(accounts(4)).attached_deposit(NearToken::from_near(1)).build());
+        contract.reassign_resolver(order_hash_of("unapproved"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner or maker can approve a reassignment")]
+    fn test_resolver_cannot_approve_reassignment() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_claimable_order(&mut contract, &order_hash_of("selfreassign"));
+
+        testing_env!(get_context(accounts(2)).build());
+        contract.approve_resolver_reassignment(order_hash_of("selfreassign"), accounts(4));
+    }
+
+    #[test]
+    fn test_aurora_xcc_accounts() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        let evm_address = format!("0x{}", "Ab".repeat(20));
+        assert!(contract.get_aurora_xcc_account(evm_address.clone()).is_none());
+
+        contract.set_aurora_engine(Some("aurora".parse().unwrap()));
+        let xcc_account: AccountId = format!("{}.aurora", "ab".repeat(20)).parse().unwrap();
+        assert_eq!(contract.get_aurora_xcc_account(evm_address), Some(xcc_account.clone()));
+        assert_eq!(
+            contract.get_aurora_evm_address(xcc_account),
+            Some(format!("0x{}", "ab".repeat(20)))
+        );
+        assert!(contract.get_aurora_xcc_account("0x1234".to_string()).is_none());
+        assert!(contract.get_aurora_evm_address(accounts(2)).is_none());
+    }
+
+    #[test]
+    fn test_aurora_xcc_maker_claims() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        contract.set_aurora_engine(Some("aurora".parse().unwrap()));
+        register_resolver(&mut contract, accounts(2));
+        let maker = contract.get_aurora_xcc_account(format!("0x{}", "cd".repeat(20))).unwrap();
+
+        let preimage = "11".repeat(32);
+        let hashlock = hex::encode(env::sha256(hex::decode(&preimage).unwrap()));
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_near(2)).build());
+        contract.execute_fusion_order(
+            order_hash_of("aurora"),
+            hashlock,
+            maker.clone(),
+            accounts(2),
+            U128(NearToken::from_near(1).as_yoctonear()),
+            U128(NearToken::from_millinear(100).as_yoctonear()),
+            open_timelocks(),
+            11155111,
+            None,
+            None,
+            None,
+            None,
+        );
+
+        // The EVM contract claims through its XCC router account
+        testing_env!(get_context(maker.clone()).build());
+        let _ = contract.claim_fusion_order(order_hash_of("aurora"), preimage);
+        assert_eq!(contract.get_order(order_hash_of("aurora")).unwrap().status, OrderStatus::Claimed);
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        assert!(receipts.iter().any(|receipt| receipt.receiver_id == maker));
+    }
+
+    #[test]
+    fn test_safety_deposit_held_for_challenge_window() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("held"));
+        let safety_deposit = contract.get_order(order_hash_of("held")).unwrap().safety_deposit;
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("held"), preimage);
+        let held = contract.get_held_deposit(order_hash_of("held")).unwrap();
+        assert_eq!(held.amount, safety_deposit);
+        assert_eq!(held.release_at, challenge::DEFAULT_CHALLENGE_WINDOW);
+
+        // Anyone can release it once the window has passed
+        testing_env!(get_context(accounts(4))
+            .block_timestamp(challenge::DEFAULT_CHALLENGE_WINDOW * 1_000_000_000)
+            .build());
+        let _ = contract.release_safety_deposit(order_hash_of("held"));
+        assert!(contract.get_held_deposit(order_hash_of("held")).is_none());
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        assert!(receipts.iter().any(|receipt| receipt.receiver_id == accounts(2)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Challenge window not over")]
+    fn test_safety_deposit_not_released_early() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("early"));
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("early"), preimage);
+        let _ = contract.release_safety_deposit(order_hash_of("early"));
+    }
+
+    #[test]
+    fn test_challenged_settlement_resolved_for_maker() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("challenged"));
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("challenged"), preimage);
+
+        testing_env!(get_context(accounts(3)).build());
+        contract.challenge_settlement(order_hash_of("challenged"));
+        assert_eq!(
+            contract.get_held_deposit(order_hash_of("challenged")).unwrap().challenged_by,
+            Some(accounts(3))
+        );
+
+        // A challenged deposit stays frozen after the window until the owner decides
+        testing_env!(get_context(accounts(1))
+            .block_timestamp(challenge::DEFAULT_CHALLENGE_WINDOW * 1_000_000_000)
+            .build());
+        let _ = contract.resolve_challenge(order_hash_of("challenged"), false);
+        assert!(contract.get_held_deposit(order_hash_of("challenged")).is_none());
+        let receivers: Vec<AccountId> = near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .filter(|receiver| *receiver != accounts(0))
+            .collect();
+        assert_eq!(receivers, vec![accounts(3)]);
+    }
+
+    #[test]
+    fn test_dispute_resolved_by_arbitrator() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        contract.set_arbitrator(Some(accounts(5)));
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("disputed"));
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("disputed"), preimage);
+        contract.flag_dispute(order_hash_of("disputed"), "ipfs://evidence".to_string());
+        let held = contract.get_held_deposit(order_hash_of("disputed")).unwrap();
+        assert_eq!(held.challenged_by, Some(accounts(2)));
+        assert_eq!(held.evidence_uri.as_deref(), Some("ipfs://evidence"));
+
+        testing_env!(get_context(accounts(5))
+            .block_timestamp(challenge::DEFAULT_CHALLENGE_WINDOW * 1_000_000_000)
+            .build());
+        let _ = contract.resolve_challenge(order_hash_of("disputed"), true);
+        assert!(contract.get_held_deposit(order_hash_of("disputed")).is_none());
+        let receivers: Vec<AccountId> = near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .filter(|receiver| *receiver != accounts(0))
+            .collect();
+        assert_eq!(receivers, vec![accounts(2)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only maker or resolver can flag a dispute")]
+    fn test_dispute_only_by_order_parties() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("outsider"));
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("outsider"), preimage);
+        testing_env!(get_context(accounts(4)).build());
+        contract.flag_dispute(order_hash_of("outsider"), "ipfs://evidence".to_string());
+    }
+
+    #[test]
+    fn test_no_challenge_window_pays_immediately() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        contract.set_challenge_window(0);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("nowindow"));
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("nowindow"), preimage);
+        assert!(contract.get_held_deposit(order_hash_of("nowindow")).is_none());
+    }
+
+    #[test]
+    fn test_emergency_cancel_after_delay() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_claimable_order(&mut contract, &order_hash_of("emergency"));
+
+        testing_env!(get_context(accounts(1)).build());
+        contract.pause();
+        testing_env!(get_context(accounts(1))
+            .block_timestamp(emergency::EMERGENCY_CANCEL_DELAY * 1_000_000_000)
+            .build());
+        let _ = contract.emergency_cancel(order_hash_of("emergency"), "Malformed hashlock".to_string());
+
+        assert_eq!(
+            contract.get_order(order_hash_of("emergency")).unwrap().status,
+            OrderStatus::Refunded
+        );
+        let dispute = contract.get_dispute(order_hash_of("emergency")).unwrap();
+        assert_eq!(dispute.reason, "Malformed hashlock");
+        assert_eq!(dispute.cancelled_by, accounts(1));
+        assert_eq!(dispute.cancelled_at, emergency::EMERGENCY_CANCEL_DELAY);
+
+        // Everything goes back to the resolver, the maker is not compensated
+        let receivers: Vec<AccountId> = near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .filter(|receiver| *receiver != accounts(0))
+            .collect();
+        assert_eq!(receivers, vec![accounts(2)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Emergency delay not over")]
+    fn test_emergency_cancel_requires_delay() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_claimable_order(&mut contract, &order_hash_of("tooearly"));
+
+        testing_env!(get_context(accounts(1)).build());
+        contract.pause();
+        let _ = contract.emergency_cancel(order_hash_of("tooearly"), "Malformed hashlock".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract not paused")]
+    fn test_emergency_cancel_requires_pause() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_claimable_order(&mut contract, &order_hash_of("notpaused"));
+
+        testing_env!(get_context(accounts(1)).build());
+        let _ = contract.emergency_cancel(order_hash_of("notpaused"), "Malformed hashlock".to_string());
+    }
+
+    #[test]
+    fn test_chain_registry() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let contract = FusionPlusNear::new(500);
+        let sepolia = contract.get_chain(11155111).unwrap();
+        assert_eq!(sepolia.name, "Ethereum Sepolia");
+        assert_eq!(sepolia.family, chains::ChainFamily::Evm);
+        assert_eq!(contract.get_chain(20001).unwrap().family, chains::ChainFamily::Bitcoin);
+        assert!(contract.get_chain(99).is_none());
+        assert_eq!(contract.get_chains().len(), chains::KNOWN_CHAINS.len());
+    }
+
+    #[test]
+    fn test_source_chain_id_above_u32() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let mut params = batch_params("widechain");
+        params.source_chain_id = u32::MAX as u64 + 1;
+
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_near(2)).build());
+        let orders = contract.execute_fusion_orders(accounts(2), vec![params]);
+        assert_eq!(orders[0].source_chain_id, u32::MAX as u64 + 1);
+        assert_eq!(
+            contract.get_order(order_hash_of("widechain")).unwrap().source_chain_id,
+            u32::MAX as u64 + 1
+        );
+    }
+
+    #[test]
+    fn test_tvl_tracks_native_and_token_orders() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("tvlnative"));
+        assert_eq!(
+            contract.get_tvl(None).locked.0,
+            NearToken::from_millinear(1_150).as_yoctonear()
+        );
+
+        testing_env!(get_context(accounts(4)).build());
+        let msg = serde_json::json!({
+            "order_hash": order_hash_of("tvltoken"),
+            "hashlock": "a".repeat(64),
+            "maker": accounts(3),
+            "amount": "1000000",
+            "resolver_fee": "10000",
+            "timelocks": open_timelocks(),
+            "source_chain_id": 11155111,
+        }).to_string();
+        let _ = contract.ft_on_transfer(accounts(2), U128(1_060_000), msg);
+        assert_eq!(contract.get_tvl(Some(accounts(4))), tvl::Tvl { locked: U128(1_060_000), cap: None });
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("tvlnative"), preimage);
+        assert_eq!(contract.get_tvl(None).locked.0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "TVL cap exceeded")]
+    fn test_tvl_cap_blocks_new_orders() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        testing_env!(get_context(accounts(1)).build());
+        contract.set_tvl_cap(None, Some(U128(NearToken::from_near(2).as_yoctonear())));
+        assert_eq!(
+            contract.get_tvl(None).cap,
+            Some(U128(NearToken::from_near(2).as_yoctonear()))
+        );
+
+        // 1.15 NEAR fits, a second order would lock 2.3 NEAR
+        create_claimable_order(&mut contract, &order_hash_of("tvl1"));
+        create_claimable_order(&mut contract, &order_hash_of("tvl2"));
+    }
+
+    #[test]
+    fn test_attested_resolver_executes_when_required() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        testing_env!(get_context(accounts(1)).build());
+        contract.set_require_attestation(true);
+        contract.register_solver_attestation(accounts(2), "ab".repeat(32), format!("0x{}", "CD".repeat(32)));
+
+        let attestation = contract.get_solver_attestation(accounts(2)).unwrap();
+        assert_eq!(attestation.code_measurement, "cd".repeat(32));
+        assert!(!attestation.revoked);
+        create_claimable_order(&mut contract, &order_hash_of("attested"));
+        assert!(contract.get_order(order_hash_of("attested")).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "Resolver has no valid TEE attestation")]
+    fn test_revoked_attestation_blocks_execution() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        testing_env!(get_context(accounts(1)).build());
+        contract.set_require_attestation(true);
+        contract.register_solver_attestation(accounts(2), "ab".repeat(32), "cd".repeat(32));
+        contract.revoke_solver_attestation(accounts(2));
+        assert!(contract.get_solver_attestation(accounts(2)).unwrap().revoked);
+
+        create_claimable_order(&mut contract, &order_hash_of("revoked"));
+    }
+
+    #[test]
+    fn test_order_metadata_stores_raw_hashes() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("rawhashes"));
+        let hashlock = env::sha256(hex::decode(&preimage).unwrap());
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("rawhashes"), preimage.clone());
+        let metadata = contract.order_metadata.get(&order_hash_of("rawhashes")).unwrap();
+        assert_eq!(metadata.hashlock.to_vec(), hashlock);
+        assert_eq!(metadata.preimage.map(hex::encode), Some(preimage.clone()));
+
+        // Views keep returning hex
+        let order = contract.get_order(order_hash_of("rawhashes")).unwrap();
+        assert_eq!(order.hashlock, hex::encode(hashlock));
+        assert_eq!(order.preimage, Some(preimage));
+    }
+
+    #[test]
+    fn test_get_resolvers_paginates() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        for resolver in [accounts(2), accounts(3), accounts(4)] {
+            contract.add_resolver(resolver);
+        }
+
+        let page = contract.get_resolvers(None, None);
+        assert_eq!(page.resolvers, vec![accounts(2), accounts(3), accounts(4)]);
+        assert_eq!(page.total, 3);
+        let page = contract.get_resolvers(Some(1), Some(1));
+        assert_eq!(page.resolvers, vec![accounts(3)]);
+        assert_eq!(page.total, 3);
+        assert!(contract.get_resolvers(Some(3), None).resolvers.is_empty());
+    }
+
+    #[test]
+    fn test_solvency_tracks_obligations() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("solvency"));
+        let solvency = contract.get_solvency();
+        assert_eq!(solvency.fusion_locked.0, NearToken::from_millinear(1_150).as_yoctonear());
+        assert_eq!(solvency.resolver_bonds.0, NearToken::from_near(5).as_yoctonear());
+        assert_eq!(solvency.total_obligations.0, NearToken::from_millinear(6_150).as_yoctonear());
+
+        // The safety deposit stays owed while held for challenges
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("solvency"), preimage);
+        let solvency = contract.get_solvency();
+        assert_eq!(solvency.fusion_locked.0, 0);
+        assert_eq!(solvency.held_deposits.0, NearToken::from_millinear(50).as_yoctonear());
+
+        // A failed payout comes back to the contract until it is retried
+        testing_env!(get_context(accounts(0)).build());
+        let maker_amount = U128(NearToken::from_near(1).as_yoctonear());
+        contract.on_payout(order_hash_of("solvency"), accounts(3), maker_amount, true, Err(PromiseError::Failed));
+        assert_eq!(contract.get_solvency().failed_payouts, maker_amount);
+        testing_env!(get_context(accounts(3)).build());
+        let _ = contract.retry_payouts(order_hash_of("solvency"));
+        assert_eq!(contract.get_solvency().failed_payouts.0, 0);
+
+        // Compare against the balance left after storage staking
+        let obligations = NearToken::from_millinear(5_050).as_yoctonear();
+        let storage_staked = 1_000 * env::storage_byte_cost().as_yoctonear();
+        testing_env!(get_context(accounts(4))
+            .attached_deposit(NearToken::from_yoctonear(0))
+            .storage_usage(1_000)
+            .account_balance(NearToken::from_yoctonear(storage_staked + obligations))
+            .build());
+        let solvency = contract.get_solvency();
+        assert_eq!(solvency.total_obligations.0, obligations);
+        assert!(solvency.solvent);
+        assert_eq!(solvency.surplus.0, 0);
+
+        testing_env!(get_context(accounts(4))
+            .attached_deposit(NearToken::from_yoctonear(0))
+            .storage_usage(1_000)
+            .account_balance(NearToken::from_yoctonear(storage_staked + obligations - 1))
+            .build());
+        let solvency = contract.get_solvency();
+        assert!(!solvency.solvent);
+        assert_eq!(solvency.deficit.0, 1);
+    }
+
+    #[test]
+    fn test_simulate_execute_fusion_order() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let deposit = U128(NearToken::from_millinear(1_150).as_yoctonear());
+        assert!(contract
+            .simulate_execute_fusion_order(accounts(2), batch_params("simulated"), deposit)
+            .is_empty());
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context.attached_deposit(NearToken::from_yoctonear(deposit.0)).build());
+        contract.execute_fusion_orders(accounts(2), vec![batch_params("simulated")]);
+        assert_eq!(
+            contract.simulate_execute_fusion_order(accounts(2), batch_params("simulated"), deposit),
+            vec![simulate::ExecutionFailure::OrderAlreadyExists]
+        );
+
+        // Every failing check is reported, not just the first
+        let mut params = batch_params("unregistered");
+        params.hashlock = "not-hex".to_string();
+        params.timelocks = U128(0);
+        assert_eq!(
+            contract.simulate_execute_fusion_order(accounts(4), params, U128(NearToken::from_near(1).as_yoctonear())),
+            vec![
+                simulate::ExecutionFailure::ResolverNotAuthorized,
+                simulate::ExecutionFailure::ResolverBondBelowMinimum,
+                simulate::ExecutionFailure::InsufficientDeposit,
+                simulate::ExecutionFailure::InvalidHashlock,
+                simulate::ExecutionFailure::InvalidTimelocks,
+                simulate::ExecutionFailure::StorageNotRegistered,
+            ]
+        );
+    }
+
+    fn usdc_swap() -> swap::ClaimSwap {
+        swap::ClaimSwap {
+            pool_id: 3,
+            token_out: "usdc.near".parse().unwrap(),
+            min_amount_out: U128(5_000_000),
+        }
+    }
+
+    #[test]
+    fn test_claim_swap_routes_maker_payout() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        contract.set_wnear_contract(Some(accounts(4)));
+        contract.set_ref_exchange(Some(accounts(5)));
+        register_resolver(&mut contract, accounts(2));
+        register_storage(&mut contract, accounts(3));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("swapped"));
+
+        testing_env!(get_context(accounts(3)).build());
+        contract.set_claim_swap(order_hash_of("swapped"), Some(usdc_swap()));
+        assert_eq!(contract.get_claim_swap(order_hash_of("swapped")), Some(usdc_swap()));
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("swapped"), preimage);
+        // The maker's share is wrapped and sent into Ref, the resolver is paid as usual
+        assert_eq!(called_methods(&accounts(4)), vec!["near_deposit", "ft_transfer_call"]);
+        assert!(called_methods(&accounts(0)).contains(&"on_claim_swap".to_string()));
+        assert!(transfers_to(&accounts(3)).is_empty());
+        assert!(!transfers_to(&accounts(2)).is_empty());
+
+        // Ref refunded the whole amount (slippage): unwrap it and keep it for retry
+        testing_env!(get_context(accounts(0)).build());
+        let maker_amount = U128(NearToken::from_near(1).as_yoctonear());
+        assert!(!contract.on_claim_swap(order_hash_of("swapped"), accounts(3), maker_amount, Ok(U128(0))));
+        assert_eq!(called_methods(&accounts(4)), vec!["near_withdraw"]);
+        let order = contract.get_order(order_hash_of("swapped")).unwrap();
+        assert_eq!(order.failed_payouts, vec![Payout { receiver: accounts(3), amount: maker_amount }]);
+        assert_eq!(contract.get_solvency().failed_payouts, maker_amount);
+    }
+
+    #[test]
+    fn test_claim_swap_storage_refunded_on_removal() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        contract.set_wnear_contract(Some(accounts(4)));
+        contract.set_ref_exchange(Some(accounts(5)));
+        register_resolver(&mut contract, accounts(2));
+        register_storage(&mut contract, accounts(3));
+        create_claimable_order(&mut contract, &order_hash_of("unswapped"));
+        let available = contract.storage_balance_of(accounts(3)).unwrap().available;
+
+        testing_env!(get_context(accounts(3)).build());
+        contract.set_claim_swap(order_hash_of("unswapped"), Some(usdc_swap()));
+        assert!(contract.storage_balance_of(accounts(3)).unwrap().available.0 < available.0);
+        contract.set_claim_swap(order_hash_of("unswapped"), None);
+        assert_eq!(contract.storage_balance_of(accounts(3)).unwrap().available, available);
+        assert_eq!(contract.get_claim_swap(order_hash_of("unswapped")), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the maker can set a claim swap")]
+    fn test_claim_swap_set_by_maker_only() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        contract.set_ref_exchange(Some(accounts(5)));
+        register_resolver(&mut contract, accounts(2));
+        create_claimable_order(&mut contract, &order_hash_of("notmaker"));
+
+        testing_env!(get_context(accounts(2)).build());
+        contract.set_claim_swap(order_hash_of("notmaker"), Some(usdc_swap()));
+    }
+
+    const ESCROW_FACTORY: &str = "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+
+    /// RLP header for a string (0x80) or list (0xc0) payload
+    fn rlp(offset: u8, payload: &[u8]) -> Vec<u8> {
+        let mut out = if payload.len() < 56 {
+            vec![offset + payload.len() as u8]
+        } else {
+            vec![offset + 56, payload.len() as u8]
+        };
+        out.extend_from_slice(payload);
+        out
+    }
+
+    /// RLP-encoded `EscrowSrcCreated` log of the factory for `order_hash`
+    fn escrow_src_log(address: &str, order_hash: &str, hashlock: &[u8]) -> Vec<u8> {
+        let topics: Vec<Vec<u8>> = vec![
+            env::keccak256(eth_proofs::ESCROW_SRC_CREATED_SIGNATURE.as_bytes()),
+            hex::decode(&order_hash[2..]).unwrap(),
+            [[0u8; 12].to_vec(), vec![0xe5; 20]].concat(),
+            [[0u8; 12].to_vec(), vec![0x3a; 20]].concat(),
+        ];
+        let data = [[0u8; 12].to_vec(), vec![0x7b; 20], hashlock.to_vec()].concat();
+        let fields = [
+            rlp(0x80, &hex::decode(address).unwrap()),
+            rlp(0xc0, &topics.iter().flat_map(|topic| rlp(0x80, topic)).collect::<Vec<u8>>()),
+            rlp(0x80, &data),
+        ]
+        .concat();
+        rlp(0xc0, &fields)
+    }
+
+    fn prove(contract: &mut FusionPlusNear, order_hash: &str, log: Vec<u8>) -> Promise {
+        contract.prove_source_escrow(
+            order_hash.to_string(),
+            0,
+            log.into(),
+            0,
+            vec![1].into(),
+            vec![2].into(),
+            vec![vec![3].into()],
+        )
+    }
+
+    fn enable_source_proofs(contract: &mut FusionPlusNear, require_proof: bool) {
+        testing_env!(get_context(accounts(1)).build());
+        contract.set_eth_proof_config(Some(eth_proofs::EthProofConfig {
+            prover: accounts(5),
+            escrow_factory: format!("0x{}", ESCROW_FACTORY.to_uppercase()),
+            require_proof,
+        }));
+    }
+
+    #[test]
+    fn test_parse_log_entry() {
+        let hashlock = [0x42u8; 32];
+        let log = eth_proofs::parse_log_entry(&escrow_src_log(ESCROW_FACTORY, &order_hash_of("rlp"), &hashlock)).unwrap();
+        assert_eq!(hex::encode(&log.address), ESCROW_FACTORY);
+        assert_eq!(log.topics.len(), 4);
+        assert_eq!(log.topics[1], env::sha256(b"rlp"));
+        assert_eq!(log.data[32..], hashlock);
+
+        // Trailing bytes, truncated input and a list in place of the address are rejected
+        let mut trailing = escrow_src_log(ESCROW_FACTORY, &order_hash_of("rlp"), &hashlock);
+        trailing.push(0);
+        assert!(eth_proofs::parse_log_entry(&trailing).is_none());
+        let full = escrow_src_log(ESCROW_FACTORY, &order_hash_of("rlp"), &hashlock);
+        assert!(eth_proofs::parse_log_entry(&full[..full.len() - 1]).is_none());
+        assert!(eth_proofs::parse_log_entry(&rlp(0xc0, &rlp(0xc0, &[]))).is_none());
+    }
+
+    #[test]
+    fn test_source_escrow_proof_gates_claim() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let order_hash = order_hash_of("proven");
+        let preimage = create_claimable_order(&mut contract, &order_hash);
+        enable_source_proofs(&mut contract, true);
+        assert_eq!(contract.get_eth_proof_config().unwrap().escrow_factory, ESCROW_FACTORY);
+
+        // The resolver proves the log, the prover is called before anything is recorded
+        testing_env!(get_context(accounts(2)).build());
+        let hashlock = env::sha256(&hex::decode(&preimage).unwrap()[..]);
+        let _ = prove(&mut contract, &order_hash, escrow_src_log(ESCROW_FACTORY, &order_hash, &hashlock));
+        assert_eq!(called_methods(&accounts(5)), vec!["verify_log_entry"]);
+        assert!(!contract.is_source_escrow_proven(order_hash.clone()));
+
+        // A rejected proof records nothing
+        testing_env!(get_context(accounts(0)).build());
+        assert!(!contract.on_source_escrow_proven(order_hash.clone(), accounts(2), Ok(false)));
+        assert!(!contract.on_source_escrow_proven(order_hash.clone(), accounts(2), Err(PromiseError::Failed)));
+        assert!(!contract.is_source_escrow_proven(order_hash.clone()));
+
+        let available = contract.storage_balance_of(accounts(2)).unwrap().available;
+        assert!(contract.on_source_escrow_proven(order_hash.clone(), accounts(2), Ok(true)));
+        assert!(contract.is_source_escrow_proven(order_hash.to_uppercase().replace("0X", "")));
+        assert!(contract.storage_balance_of(accounts(2)).unwrap().available.0 < available.0);
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.claim_fusion_order(order_hash.clone(), preimage);
+        assert_eq!(contract.get_order(order_hash).unwrap().status, OrderStatus::Claimed);
+    }
+
+    #[test]
+    #[should_panic(expected = "Source escrow not proven")]
+    fn test_claim_requires_source_proof() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("unproven"));
+        enable_source_proofs(&mut contract, true);
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("unproven"), preimage);
+    }
+
+    #[test]
+    #[should_panic(expected = "Source escrow hashlock mismatch")]
+    fn test_source_proof_rejects_other_hashlock() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let order_hash = order_hash_of("otherlock");
+        create_claimable_order(&mut contract, &order_hash);
+        enable_source_proofs(&mut contract, false);
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = prove(&mut contract, &order_hash, escrow_src_log(ESCROW_FACTORY, &order_hash, &[0x42; 32]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Log not emitted by the escrow factory")]
+    fn test_source_proof_rejects_other_emitter() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let order_hash = order_hash_of("otherfactory");
+        create_claimable_order(&mut contract, &order_hash);
+        enable_source_proofs(&mut contract, false);
+
+        testing_env!(get_context(accounts(2)).build());
+        let other_factory = "11".repeat(20);
+        let _ = prove(&mut contract, &order_hash, escrow_src_log(&other_factory, &order_hash, &[0x42; 32]));
+    }
+
+    fn intent_json(label: &str, asset_out: &str) -> serde_json::Value {
+        serde_json::json!({
+            "order_hash": order_hash_of(label),
+            "hashlock": "a".repeat(64),
+            "signer_id": accounts(3),
+            "defuse_asset_identifier_in": "11155111:0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            "exact_amount_in": "5000000",
+            "defuse_asset_identifier_out": asset_out,
+            "exact_amount_out": "1000000",
+            "source_address": "0x3a5b000000000000000000000000000000000001",
+            "solver_fee": "10000",
+            "timelocks": open_timelocks(),
+        })
+    }
+
+    #[test]
+    fn test_execute_intent_order_maps_fields() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+
+        let mut intent = intent_json("intent", "near");
+        intent["receiver_id"] = serde_json::json!(accounts(4));
+        let intent: intents::FusionIntent = serde_json::from_value(intent).unwrap();
+        let mut context = get_context(accounts(2));
+        testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+        contract.execute_intent_order(accounts(2), intent);
+
+        let order = contract.get_order(order_hash_of("intent")).unwrap();
+        assert_eq!(order.maker, accounts(3));
+        assert_eq!(order.token, None);
+        assert_eq!(order.amount.0, 1_000_000);
+        assert_eq!(order.resolver_fee.0, 10_000);
+        assert_eq!(order.source_chain_id, 11155111);
+        assert_eq!(order.payout_to, Some(accounts(4)));
+        assert_eq!(
+            order.destination,
+            Some(DestinationInfo {
+                chain_id: 11155111,
+                token: "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".to_string(),
+                address: "0x3a5b000000000000000000000000000000000001".to_string(),
+                amount: U128(5_000_000),
+            })
+        );
+    }
+
+    #[test]
+    fn test_ft_on_transfer_fills_intent() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+
+        testing_env!(get_context(accounts(4)).build());
+        let msg = serde_json::json!({ "intent": intent_json("ftintent", &format!("nep141:{}", accounts(4))) }).to_string();
+        // 1_000_000 + 10_000 fee + 50_000 safety deposit
+        let result = contract.ft_on_transfer(accounts(2), U128(1_060_000), msg);
+        assert!(matches!(result, near_sdk::PromiseOrValue::Value(U128(0))));
+
+        let order = contract.get_order(order_hash_of("ftintent")).unwrap();
+        assert_eq!(order.token, Some(accounts(4)));
+        assert_eq!(order.resolver, accounts(2));
+        assert_eq!(order.destination.unwrap().amount, U128(5_000_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "Intent asset does not match the transferred token")]
+    fn test_ft_on_transfer_rejects_intent_for_other_token() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+
+        testing_env!(get_context(accounts(4)).build());
+        let msg = serde_json::json!({ "intent": intent_json("othertoken", "nep141:usdc.near") }).to_string();
+        let _ = contract.ft_on_transfer(accounts(2), U128(1_060_000), msg);
+    }
+
+    #[test]
+    #[should_panic(expected = "Intent expired")]
+    fn test_execute_intent_order_rejects_expired() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+
+        let mut intent = intent_json("expired", "near");
+        intent["deadline_ms"] = serde_json::json!("1000");
+        let mut context = get_context(accounts(2));
+        testing_env!(context
+            .attached_deposit(NearToken::from_near(1))
+            .block_timestamp(1_000_000_000)
+            .build());
+        contract.execute_intent_order(accounts(2), serde_json::from_value(intent).unwrap());
+    }
+
+    fn limit_order() -> order_hash::LimitOrder {
+        order_hash::LimitOrder {
+            salt: "102412815611787935992271873344279698181002251432500613888978521074851540062603".to_string(),
+            maker: "0x00000000219ab540356cbb839cbe05303d7705fa".to_string(),
+            receiver: format!("0x{}", "0".repeat(40)),
+            maker_asset: "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".to_string(),
+            taker_asset: "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".to_string(),
+            making_amount: "1000000000000000000".to_string(),
+            taking_amount: "1420000000".to_string(),
+            maker_traits: "0x4e80000000000000000000000000000000006711c9a00000000000000000000".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_compute_order_hash() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        let contract = FusionPlusNear::new(500);
+
+        let hash = contract.compute_order_hash(limit_order(), 1, None).unwrap();
+        assert!(normalize_hex32(&hash).is_some() && hash.starts_with("0x"));
+        // The default verifying contract is the 1inch router
+        assert_eq!(
+            contract.compute_order_hash(limit_order(), 1, Some(order_hash::ONEINCH_ROUTER_V6.to_uppercase().replace("0X", "0x"))),
+            Some(hash.clone())
+        );
+
+        // Hex and decimal amounts and the address case do not change the hash
+        let mut order = limit_order();
+        order.making_amount = "0xde0b6b3a7640000".to_string();
+        order.maker = order.maker.to_uppercase().replace("0X", "0x");
+        assert_eq!(contract.compute_order_hash(order, 1, None), Some(hash.clone()));
+
+        // Every field and the domain are committed to
+        let mut order = limit_order();
+        order.taking_amount = "1420000001".to_string();
+        assert_ne!(contract.compute_order_hash(order, 1, None), Some(hash.clone()));
+        assert_ne!(contract.compute_order_hash(limit_order(), 11155111, None), Some(hash.clone()));
+        assert_ne!(
+            contract.compute_order_hash(limit_order(), 1, Some(format!("0x{}", "11".repeat(20)))),
+            Some(hash)
+        );
+
+        let mut order = limit_order();
+        order.receiver = "0x1234".to_string();
+        assert_eq!(contract.compute_order_hash(order, 1, None), None);
+        let mut order = limit_order();
+        order.salt = "-1".to_string();
+        assert_eq!(contract.compute_order_hash(order, 1, None), None);
+    }
+
+    #[test]
+    fn test_maker_allowlist_gates_fusion_orders() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        assert!(contract.is_allowed_maker(accounts(3)));
+
+        testing_env!(get_context(accounts(1)).build());
+        contract.set_maker_allowlist_enabled(true);
+        assert!(contract.get_maker_allowlist_enabled());
+        assert!(!contract.is_allowed_maker(accounts(3)));
+        let deposit = U128(NearToken::from_millinear(1_150).as_yoctonear());
+        assert_eq!(
+            contract.simulate_execute_fusion_order(accounts(2), batch_params("listed"), deposit),
+            vec![simulate::ExecutionFailure::MakerNotAllowlisted]
+        );
+
+        assert!(contract.add_maker(accounts(3)));
+        assert!(!contract.add_maker(accounts(3)));
+        assert!(contract.is_allowed_maker(accounts(3)));
+        create_claimable_order(&mut contract, &order_hash_of("listed"));
+        assert!(contract.get_order(order_hash_of("listed")).is_some());
+
+        // Removing the maker keeps its order but blocks new ones
+        testing_env!(get_context(accounts(1)).build());
+        assert!(contract.remove_maker(accounts(3)));
+        assert!(!contract.remove_maker(accounts(3)));
+        assert!(!contract.is_allowed_maker(accounts(3)));
+        assert!(contract.get_order(order_hash_of("listed")).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "Maker not allowlisted")]
+    fn test_maker_allowlist_rejects_unlisted_maker() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        testing_env!(get_context(accounts(1)).build());
+        contract.set_maker_allowlist_enabled(true);
+        create_claimable_order(&mut contract, &order_hash_of("unlisted"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner")]
+    fn test_add_maker_not_owner() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        testing_env!(get_context(accounts(2)).build());
+        contract.add_maker(accounts(3));
+    }
+
+    /// `ft_transfer` receivers and amounts on `token` in the receipts created so far
+    fn ft_transfers(token: &AccountId) -> Vec<(AccountId, u128)> {
+        near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .filter(|receipt| &receipt.receiver_id == token)
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                near_sdk::mock::MockAction::FunctionCallWeight { method_name, args, .. }
+                    if method_name == b"ft_transfer" =>
+                {
+                    let args: serde_json::Value = serde_json::from_slice(&args).unwrap();
+                    let amount: U128 = serde_json::from_value(args["amount"].clone()).unwrap();
+                    Some((args["receiver_id"].as_str().unwrap().parse().unwrap(), amount.0))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Accept accounts(5) for safety deposits at 120% and post `amount` of it for `order_hash`
+    fn post_token_safety_deposit(contract: &mut FusionPlusNear, order_hash: &str, amount: u128) {
+        testing_env!(get_context(accounts(1)).build());
+        contract.set_safety_deposit_token(accounts(5), Some(12_000));
+        testing_env!(get_context(accounts(5)).build());
+        let msg = serde_json::json!({ "safety_deposit_for": order_hash }).to_string();
+        let result = contract.ft_on_transfer(accounts(2), U128(amount), msg);
+        assert!(matches!(result, near_sdk::PromiseOrValue::Value(U128(0))));
+    }
+
+    #[test]
+    fn test_token_safety_deposit_slashed_on_cancel() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        // 5% of 1 NEAR at 120%
+        let staked = NearToken::from_millinear(60).as_yoctonear();
+        post_token_safety_deposit(&mut contract, &order_hash_of("stnear"), staked);
+        create_order_with_timelocks(&mut contract, &order_hash_of("stnear"), Timelocks::pack([0, 60, 3600, 7200]));
+        assert_eq!(contract.get_order(order_hash_of("stnear")).unwrap().safety_deposit, U128(0));
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context.block_timestamp(4_600 * 1_000_000_000).build());
+        let _ = contract.cancel_fusion_order(order_hash_of("stnear"));
+
+        // The maker is compensated in the token, amount + fee go back in NEAR
+        assert_eq!(ft_transfers(&accounts(5)), vec![(accounts(3), staked)]);
+        assert_eq!(transfers_to(&accounts(2)), vec![NearToken::from_millinear(1_100).as_yoctonear()]);
+        let deposit = contract.get_token_safety_deposit(accounts(2), order_hash_of("stnear")).unwrap();
+        assert_eq!(deposit.amount, U128(0));
+
+        // A failed transfer is kept for retry
+        testing_env!(get_context(accounts(0)).build());
+        assert!(!contract.on_token_deposit_payout(
+            order_hash_of("stnear"),
+            accounts(2),
+            accounts(5),
+            accounts(3),
+            U128(staked),
+            Err(PromiseError::Failed)
+        ));
+        testing_env!(get_context(accounts(3)).build());
+        let _ = contract.retry_token_deposit_payouts(accounts(2), order_hash_of("stnear"));
+        assert_eq!(ft_transfers(&accounts(5)), vec![(accounts(3), staked)]);
+    }
+
+    #[test]
+    fn test_token_safety_deposit_held_through_challenge_window() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let staked = NearToken::from_millinear(70).as_yoctonear();
+        post_token_safety_deposit(&mut contract, &order_hash_of("heldtoken"), staked);
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("heldtoken"));
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("heldtoken"), preimage);
+        assert!(ft_transfers(&accounts(5)).is_empty());
+        assert_eq!(contract.get_held_deposit(order_hash_of("heldtoken")).unwrap().amount, U128(0));
+
+        let mut context = get_context(accounts(4));
+        testing_env!(context
+            .block_timestamp((challenge::DEFAULT_CHALLENGE_WINDOW + 1) * 1_000_000_000)
+            .build());
+        let _ = contract.release_safety_deposit(order_hash_of("heldtoken"));
+        assert_eq!(ft_transfers(&accounts(5)), vec![(accounts(2), staked)]);
+        assert!(transfers_to(&accounts(2)).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient token safety deposit")]
+    fn test_token_safety_deposit_below_collateral_ratio() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        post_token_safety_deposit(&mut contract, &order_hash_of("short"), NearToken::from_millinear(50).as_yoctonear());
+        create_claimable_order(&mut contract, &order_hash_of("short"));
+    }
+
+    #[test]
+    fn test_withdraw_unused_token_safety_deposit() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let available = contract.storage_balance_of(accounts(2)).unwrap().available;
+        post_token_safety_deposit(&mut contract, &order_hash_of("unused"), 1_000);
+        assert!(contract.storage_balance_of(accounts(2)).unwrap().available.0 < available.0);
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.withdraw_token_safety_deposit(order_hash_of("unused"));
+        assert_eq!(ft_transfers(&accounts(5)), vec![(accounts(2), 1_000)]);
+        assert_eq!(contract.get_token_safety_deposit(accounts(2), order_hash_of("unused")), None);
+        assert_eq!(contract.storage_balance_of(accounts(2)).unwrap().available, available);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token not accepted for safety deposits")]
+    fn test_token_safety_deposit_requires_accepted_token() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        testing_env!(get_context(accounts(4)).build());
+        let msg = serde_json::json!({ "safety_deposit_for": order_hash_of("notaccepted") }).to_string();
+        let _ = contract.ft_on_transfer(accounts(2), U128(1_000), msg);
+    }
+
+    // Signatures of "alice:release:<order_hash_of("signedrelease")>"
+    const ED25519_RELEASE_KEY: &str = "8a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c";
+    const ED25519_RELEASE_SIGNATURE: &str = "14a4d40824db5d79ba0b0d8cbc020bc7414307fd4cf10741bb3c47f9c1bae170b6eb96a90cf51a3c2cdce71d23e76d1f2e0afb37bd9aa2475e2de1a7bf2de20b";
+    const SECP256K1_RELEASE_ADDRESS: &str = "0xAB615A1598370C1F9756290EA874FAA4B2185ABD";
+    const SECP256K1_RELEASE_SIGNATURE: &str = "0xed406e6c54200abaed9cd4f775cf19605c7471c8e927fbb46c316ed64569aec15ae6e8b14689bda0f173086ff2e9d60b862d40e16e42dc1ce55e256800bec9fd1c";
+
+    fn create_order_with_release_key(contract: &mut FusionPlusNear, release_key: ReleaseKey) -> String {
+        let order_hash = order_hash_of("signedrelease");
+        create_claimable_order(contract, &order_hash);
+        testing_env!(get_context(accounts(2)).build());
+        contract.set_release_key(order_hash.clone(), release_key);
+        order_hash
+    }
+
+    #[test]
+    fn test_release_with_ed25519_signature() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let order_hash =
+            create_order_with_release_key(&mut contract, ReleaseKey::Ed25519(ED25519_RELEASE_KEY.to_string()));
+        assert_eq!(contract.get_release_message(order_hash.clone()), format!("alice:release:{}", order_hash));
+
+        // Anyone can submit the signature, without a public withdrawal reward
+        testing_env!(get_context(accounts(4)).build());
+        let _ = contract.release_with_signature(order_hash.clone(), ED25519_RELEASE_SIGNATURE.to_string());
+        let order = contract.get_order(order_hash).unwrap();
+        assert_eq!(order.status, OrderStatus::Claimed);
+        assert!(order.preimage.is_none());
+        let receivers: Vec<AccountId> = near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .filter(|receiver| *receiver != accounts(0))
+            .collect();
+        assert_eq!(receivers, vec![accounts(3), accounts(2)]);
+    }
+
+    #[test]
+    fn test_release_with_secp256k1_signature() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let order_hash =
+            create_order_with_release_key(&mut contract, ReleaseKey::Secp256k1(SECP256K1_RELEASE_ADDRESS.to_string()));
+        assert_eq!(
+            contract.get_release_key(order_hash.clone()),
+            Some(ReleaseKey::Secp256k1(SECP256K1_RELEASE_ADDRESS.to_lowercase()))
+        );
+
+        testing_env!(get_context(accounts(3)).build());
+        let _ = contract.release_with_signature(order_hash.clone(), SECP256K1_RELEASE_SIGNATURE.to_string());
+        assert_eq!(contract.get_order(order_hash).unwrap().status, OrderStatus::Claimed);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid release signature")]
+    fn test_release_rejects_signature_of_other_key() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let order_hash =
+            create_order_with_release_key(&mut contract, ReleaseKey::Secp256k1(SECP256K1_RELEASE_ADDRESS.to_string()));
+
+        testing_env!(get_context(accounts(4)).build());
+        let _ = contract.release_with_signature(order_hash, ED25519_RELEASE_SIGNATURE.to_string());
+    }
+
+    #[test]
+    fn test_execute_fusion_order_call_notifies_creator() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let order_hash = order_hash_of("composable");
+
+        // accounts(2) stands in for a vault contract acting as resolver
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_near(2)).build());
+        let _ = contract.execute_fusion_order_call(batch_params("composable"), "vault-step-2".to_string());
+        assert_eq!(contract.get_calls_in_flight(accounts(2)), vec![order_hash.clone()]);
+        assert!(called_methods(&accounts(2)).contains(&"on_fusion_order_created".to_string()));
+
+        // A failed callback is reported, but the order stands
+        testing_env!(get_context(accounts(0)).build());
+        assert!(!contract.on_creation_notified(accounts(2), order_hash.clone(), Err(PromiseError::Failed)));
+        assert!(contract.get_calls_in_flight(accounts(2)).is_empty());
+        assert_eq!(contract.get_order(order_hash).unwrap().status, OrderStatus::Matched);
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs[0].contains("\"event\":\"order_creation_notified\""));
+        assert!(logs[0].contains("\"delivered\":false"));
+    }
+
+    #[test]
+    fn test_resolver_stats() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("statsclaim"));
+        create_order_with_timelocks(&mut contract, &order_hash_of("statsrefund"), Timelocks::pack([0, 60, 3600, 7200]));
+
+        testing_env!(get_context(accounts(2)).block_timestamp(1_200 * 1_000_000_000).build());
+        let _ = contract.claim_fusion_order(order_hash_of("statsclaim"), preimage);
+        testing_env!(get_context(accounts(2)).block_timestamp(4_600 * 1_000_000_000).build());
+        let _ = contract.cancel_fusion_order(order_hash_of("statsrefund"));
+
+        assert_eq!(
+            contract.get_resolver_stats(accounts(2)),
+            stats::ResolverStats {
+                executed: 2,
+                claimed: 1,
+                refunded: 1,
+                volume: U128(NearToken::from_near(2).as_yoctonear()),
+                average_time_to_claim: 1_200,
+            }
+        );
+        assert_eq!(contract.get_resolver_stats(accounts(4)).average_time_to_claim, 0);
+    }
+
+    #[test]
+    fn test_circuit_breaker_trips_when_insolvent() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        create_claimable_order(&mut contract, &order_hash_of("breaker"));
+
+        let obligations = contract.get_solvency().total_obligations.0;
+        let storage_staked = 1_000 * env::storage_byte_cost().as_yoctonear();
+        testing_env!(get_context(accounts(4))
+            .attached_deposit(NearToken::from_yoctonear(0))
+            .storage_usage(1_000)
+            .account_balance(NearToken::from_yoctonear(storage_staked + obligations))
+            .build());
+        assert!(contract.check_solvency());
+        assert!(!contract.is_paused());
+
+        testing_env!(get_context(accounts(4))
+            .attached_deposit(NearToken::from_yoctonear(0))
+            .storage_usage(1_000)
+            .account_balance(NearToken::from_yoctonear(storage_staked + obligations - 1))
+            .build());
+        assert!(!contract.check_solvency());
+        assert!(contract.is_paused());
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs[0].contains("\"event\":\"circuit_breaker_tripped\""));
+        assert!(logs[0].contains("\"reason\":\"insolvent\",\"deficit\":\"1\""));
+    }
+
+    #[test]
+    fn test_circuit_breaker_trips_on_repeated_payout_failures() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("failing"));
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("failing"), preimage);
+
+        let amount = U128(NearToken::from_millinear(1).as_yoctonear());
+        testing_env!(get_context(accounts(0)).build());
+        for _ in 1..circuit_breaker::PAYOUT_FAILURE_LIMIT {
+            contract.on_payout(order_hash_of("failing"), accounts(3), amount, true, Err(PromiseError::Failed));
+        }
+        // A success in between resets the count
+        contract.on_payout(order_hash_of("failing"), accounts(3), amount, true, Ok(()));
+        assert_eq!(contract.get_consecutive_payout_failures(), 0);
+        for _ in 1..circuit_breaker::PAYOUT_FAILURE_LIMIT {
+            contract.on_payout(order_hash_of("failing"), accounts(3), amount, true, Err(PromiseError::Failed));
+        }
+        assert!(!contract.is_paused());
+
+        contract.on_payout(order_hash_of("failing"), accounts(3), amount, true, Err(PromiseError::Failed));
+        assert!(contract.is_paused());
+        assert_eq!(contract.get_consecutive_payout_failures(), 0);
+    }
+
+    #[test]
+    fn test_retried_payout_failures_do_not_trip_circuit_breaker() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = FusionPlusNear::new(500);
+        register_resolver(&mut contract, accounts(2));
+        let preimage = create_claimable_order(&mut contract, &order_hash_of("retried"));
+        testing_env!(get_context(accounts(2)).build());
+        let _ = contract.claim_fusion_order(order_hash_of("retried"), preimage);
+
+        let amount = U128(NearToken::from_millinear(1).as_yoctonear());
+        testing_env!(get_context(accounts(0)).build());
+        contract.on_payout(order_hash_of("retried"), accounts(3), amount, true, Err(PromiseError::Failed));
+        assert_eq!(contract.get_consecutive_payout_failures(), 1);
+
+        // The maker re-sends the same failing payout over and over
+        for _ in 0..circuit_breaker::PAYOUT_FAILURE_LIMIT * 2 {
+            testing_env!(get_context(accounts(3)).build());
+            let _ = contract.retry_payouts(order_hash_of("retried"));
+            testing_env!(get_context(accounts(0)).build());
+            contract.on_payout(order_hash_of("retried"), accounts(3), amount, false, Err(PromiseError::Failed));
+        }
+        assert!(!contract.is_paused());
+        assert_eq!(contract.get_consecutive_payout_failures(), 1);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,53 @@
+use near_sdk::{near_bindgen, AccountId};
+
+use crate::events::{FusionEvent, MakerEvent};
+use crate::{FusionPlusNear, FusionPlusNearExt};
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Only allowlisted makers can be named in new Fusion+ orders or create
+    /// HTLC orders while enabled, e.g. during a guarded launch (owner only)
+    #[payable]
+    pub fn set_maker_allowlist_enabled(&mut self, enabled: bool) {
+        self.assert_owner();
+        self.maker_allowlist_enabled = enabled;
+    }
+
+    pub fn get_maker_allowlist_enabled(&self) -> bool {
+        self.maker_allowlist_enabled
+    }
+
+    /// Allowlist a maker (owner only), returns whether it was newly added
+    #[payable]
+    pub fn add_maker(&mut self, maker: AccountId) -> bool {
+        self.assert_owner();
+        let added = self.allowed_makers.insert(&maker, &true).is_none();
+        if added {
+            FusionEvent::MakerAdded(vec![MakerEvent { maker }]).emit();
+        }
+        added
+    }
+
+    /// Remove a maker from the allowlist (owner only), returns whether it was listed
+    /// Its existing orders are unaffected
+    #[payable]
+    pub fn remove_maker(&mut self, maker: AccountId) -> bool {
+        self.assert_owner();
+        let removed = self.allowed_makers.remove(&maker).is_some();
+        if removed {
+            FusionEvent::MakerRemoved(vec![MakerEvent { maker }]).emit();
+        }
+        removed
+    }
+
+    /// Whether `maker` can be named in new orders: always when the allowlist is disabled
+    pub fn is_allowed_maker(&self, maker: AccountId) -> bool {
+        !self.maker_allowlist_enabled || self.allowed_makers.get(&maker).is_some()
+    }
+}
+
+impl FusionPlusNear {
+    pub(crate) fn assert_allowed_maker(&self, maker: &AccountId) {
+        assert!(self.is_allowed_maker(maker.clone()), "Maker not allowlisted");
+    }
+}
@@ -0,0 +1,36 @@
+//! Partial-fill secret trees, from the shared `fusion-merkle` crate with
+//! NEAR's host keccak256
+
+use crate::hashlock::NearHasher;
+
+pub use fusion_merkle::{expected_secret_index, Node};
+
+/// Merkle leaf for the secret at `index`, as built by the 1inch SDK:
+/// `keccak256(uint64 index || secret_hash)`
+pub fn secret_leaf(index: u64, secret_hash: &Node) -> Node {
+    fusion_merkle::secret_leaf(&NearHasher, index, secret_hash)
+}
+
+/// Hash a pair of nodes in sorted order (OpenZeppelin `MerkleProof` layout)
+pub fn hash_pair(a: &Node, b: &Node) -> Node {
+    fusion_merkle::hash_pair(&NearHasher, a, b)
+}
+
+/// Fold `proof` into `leaf` and compare against `root`
+pub fn verify_proof(leaf: Node, proof: &[Node], root: &Node) -> bool {
+    fusion_merkle::verify_proof(&NearHasher, leaf, proof, root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_proof() {
+        let leaves: Vec<Node> = (0..2u64).map(|i| secret_leaf(i, &[i as u8; 32])).collect();
+        let root = hash_pair(&leaves[0], &leaves[1]);
+        assert!(verify_proof(leaves[0], &[leaves[1]], &root));
+        assert!(verify_proof(leaves[1], &[leaves[0]], &root));
+        assert!(!verify_proof(leaves[0], &[leaves[0]], &root));
+    }
+}
@@ -0,0 +1,192 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, TreeMap, UnorderedMap, Vector};
+use near_sdk::json_types::U128;
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::bonding::{DEFAULT_MIN_RESOLVER_BOND, DEFAULT_UNBONDING_DELAY};
+use crate::challenge::DEFAULT_CHALLENGE_WINDOW;
+use crate::cleanup::DEFAULT_ORDER_RETENTION;
+use crate::hashlock::{normalize_hex32, HashAlgorithm};
+use crate::htlc::{DEFAULT_MAX_OPEN_HTLC_ORDERS, DEFAULT_MIN_HTLC_ORDER_AMOUNT};
+use crate::solvency::Liabilities;
+use crate::stats::OrderTotals;
+use crate::{
+    FusionPlusNear, FusionPlusNearExt, FusionPlusOrder, OrderStatus, DEFAULT_MAKER_COMPENSATION_BPS,
+};
+
+/// Storage key holding the contract state layout version
+pub const STATE_VERSION_KEY: &[u8] = b"VERSION";
+/// Layout version written by this build
+/// Version 0 is the original deployment, which predates the version key
+pub const CURRENT_STATE_VERSION: u32 = 1;
+
+/// Order layout of state version 0
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct FusionPlusOrderV0 {
+    pub order_hash: String,
+    pub hashlock: String,
+    pub timelocks: U128,
+    pub maker: AccountId,
+    pub resolver: AccountId,
+    pub amount: U128,
+    pub resolver_fee: U128,
+    pub safety_deposit: U128,
+    pub status: OrderStatus,
+    pub preimage: Option<String>,
+    pub source_chain_id: u32,
+}
+
+/// Contract layout of state version 0
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct FusionPlusNearV0 {
+    pub orders: UnorderedMap<String, FusionPlusOrderV0>,
+    pub authorized_resolvers: UnorderedMap<AccountId, bool>,
+    pub owner: AccountId,
+    pub min_safety_deposit_bps: u16,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Upgrade the stored state to the current layout after deploying new code
+    /// Rejects state that is already current or of an unknown version
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let version = Self::state_version();
+        match version {
+            0 => Self::migrate_from_v0(),
+            CURRENT_STATE_VERSION => env::panic_str("State already at current version"),
+            _ => env::panic_str("Unknown state version"),
+        }
+    }
+}
+
+impl FusionPlusNear {
+    /// Version of the stored state layout
+    pub fn state_version() -> u32 {
+        env::storage_read(STATE_VERSION_KEY)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().expect("Invalid state version")))
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn write_state_version() {
+        env::storage_write(STATE_VERSION_KEY, &CURRENT_STATE_VERSION.to_le_bytes());
+    }
+
+    /// Version 0 orders predate hash algorithms, NEP-141 escrows, storage
+    /// accounting and partial fills. Timelock offsets are counted from the
+    /// migration, so no stage opens earlier than it would have before.
+    /// Order hashes are canonicalized where valid; malformed legacy hashes
+    /// are kept as-is and only reachable through `get_orders`.
+    fn migrate_from_v0() -> Self {
+        let mut old: FusionPlusNearV0 = env::state_read().expect("No state to migrate");
+        let old_orders: Vec<FusionPlusOrderV0> = old.orders.values().collect();
+        old.orders.clear();
+
+        let mut this = Self {
+            orders: LookupMap::new(b"o"),
+            order_metadata: LookupMap::new(b"c"),
+            order_hashes: Vector::new(b"i"),
+            authorized_resolvers: old.authorized_resolvers,
+            owner: old.owner,
+            pending_owner: None,
+            min_safety_deposit_bps: old.min_safety_deposit_bps,
+            storage_accounts: LookupMap::new(b"s"),
+            account_storage_usage: 0,
+            orders_by_maker: LookupMap::new(b"m"),
+            orders_by_status: LookupMap::new(b"t"),
+            totals: OrderTotals::default(),
+            guardian: None,
+            paused: false,
+            paused_at: 0,
+            htlc_orders: LookupMap::new(b"h"),
+            mpc_signer: None,
+            resolver_bonds: LookupMap::new(b"b"),
+            min_resolver_bond: DEFAULT_MIN_RESOLVER_BOND,
+            unbonding_delay: DEFAULT_UNBONDING_DELAY,
+            protocol_fee_bps: 0,
+            fee_account: None,
+            accrued_fees: LookupMap::new(b"f"),
+            wnear_contract: None,
+            payout_preferences: LookupMap::new(b"w"),
+            order_retention: DEFAULT_ORDER_RETENTION,
+            escrow_mode: false,
+            maker_compensation_bps: DEFAULT_MAKER_COMPENSATION_BPS,
+            resolver_operators: LookupMap::new(b"p"),
+            claim_prover: None,
+            pending_claims: LookupMap::new(b"y"),
+            order_history: LookupMap::new(b"l"),
+            open_htlc_orders: LookupMap::new(b"n"),
+            max_open_htlc_orders: DEFAULT_MAX_OPEN_HTLC_ORDERS,
+            min_htlc_order_amount: DEFAULT_MIN_HTLC_ORDER_AMOUNT,
+            timelock_extensions: LookupMap::new(b"x"),
+            resolver_reassignments: LookupMap::new(b"a"),
+            aurora_engine: None,
+            challenge_window: DEFAULT_CHALLENGE_WINDOW,
+            held_deposits: LookupMap::new(b"d"),
+            disputes: LookupMap::new(b"e"),
+            token_locked: LookupMap::new(b"k"),
+            htlc_locked: 0,
+            tvl_caps: LookupMap::new(b"v"),
+            solver_attestations: LookupMap::new(b"q"),
+            require_attestation: false,
+            liabilities: Liabilities::default(),
+            ref_exchange: None,
+            claim_swaps: LookupMap::new(b"g"),
+            eth_proof_config: None,
+            source_proofs: LookupMap::new(b"u"),
+            maker_allowlist_enabled: false,
+            allowed_makers: LookupMap::new(b"j"),
+            safety_deposit_tokens: LookupMap::new(b"Z"),
+            token_safety_deposits: LookupMap::new(b"z"),
+            order_log: Vector::new(b"C"),
+            arbitrator: None,
+            release_keys: LookupMap::new(b"R"),
+            calls_in_flight: LookupMap::new(b"F"),
+            resolver_stats: LookupMap::new(b"S"),
+            consecutive_payout_failures: 0,
+            expiring_orders: TreeMap::new(b"E"),
+            partial_fills: LookupMap::new(b"P"),
+        };
+        this.measure_account_storage_usage();
+
+        let created_at = Self::now_seconds();
+        for old_order in old_orders {
+            let order_hash = normalize_hex32(&old_order.order_hash)
+                .map(|digits| format!("0x{}", digits))
+                .unwrap_or(old_order.order_hash);
+            let order = FusionPlusOrder {
+                order_hash,
+                hashlock: old_order.hashlock,
+                hash_algorithm: HashAlgorithm::Sha256,
+                timelocks: old_order.timelocks,
+                maker: old_order.maker,
+                resolver: old_order.resolver,
+                token: None,
+                amount: old_order.amount,
+                resolver_fee: old_order.resolver_fee,
+                safety_deposit: old_order.safety_deposit,
+                status: old_order.status,
+                preimage: old_order.preimage,
+                source_chain_id: old_order.source_chain_id.into(),
+                created_at,
+                failed_payouts: Vec::new(),
+                parts: 0,
+                filled_amount: U128(0),
+                last_fill_index: None,
+                escrow_account: None,
+                destination: None,
+                payout_to: None,
+            };
+            let state = this.internal_insert_order(order);
+            if state.status == OrderStatus::Matched {
+                this.internal_record_locked(&state);
+            }
+        }
+
+        Self::write_state_version();
+        crate::events::init_event_nonce();
+        this
+    }
+
+}
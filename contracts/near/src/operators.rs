@@ -0,0 +1,41 @@
+use near_sdk::{assert_one_yocto, env, near_bindgen, AccountId};
+
+use crate::events::{FusionEvent, ResolverOperatorEvent};
+use crate::{FusionPlusNear, FusionPlusNearExt, OrderState};
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Set or clear the caller's operator account
+    /// The operator may claim and cancel the resolver's Fusion+ orders, so a
+    /// relayer can run on a function-call access key limited to
+    /// `claim_fusion_order`, `claim_partial_fill` and `cancel_fusion_order`.
+    /// Payouts still go to the resolver. Requires 1 yoctoNEAR, which
+    /// function-call access keys cannot attach.
+    #[payable]
+    pub fn set_operator(&mut self, operator: Option<AccountId>) {
+        assert_one_yocto();
+        let resolver = env::predecessor_account_id();
+        assert!(self.is_authorized_resolver(resolver.clone()), "Not an authorized resolver");
+        match &operator {
+            Some(operator) => {
+                assert_ne!(operator, &resolver, "Resolver cannot be its own operator");
+                self.resolver_operators.insert(&resolver, operator);
+            }
+            None => {
+                self.resolver_operators.remove(&resolver);
+            }
+        }
+        FusionEvent::ResolverOperatorSet(vec![ResolverOperatorEvent { resolver, operator }]).emit();
+    }
+
+    pub fn get_operator(&self, resolver: AccountId) -> Option<AccountId> {
+        self.resolver_operators.get(&resolver)
+    }
+}
+
+impl FusionPlusNear {
+    /// Whether `account` is the order's resolver or the resolver's operator
+    pub(crate) fn is_resolver_or_operator(&self, order: &OrderState, account: &AccountId) -> bool {
+        *account == order.resolver || self.resolver_operators.get(&order.resolver).as_ref() == Some(account)
+    }
+}
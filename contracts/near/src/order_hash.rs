@@ -0,0 +1,83 @@
+use fusion_order_hash::{parse_address, parse_uint256, Domain, Order};
+use near_sdk::near_bindgen;
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use crate::hashlock::NearHasher;
+use crate::{FusionPlusNear, FusionPlusNearExt};
+
+/// 1inch Aggregation Router v6, the Limit Order Protocol contract on every EVM chain
+pub const ONEINCH_ROUTER_V6: &str = "0x111111125421ca6dc452d289314280a0f8842a65";
+
+/// 1inch Limit Order Protocol v4 order, as built by the 1inch SDK
+/// `uint256` fields are decimal strings or `0x` hex, addresses are 20-byte hex
+#[derive(Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LimitOrder {
+    pub salt: String,
+    pub maker: String,
+    pub receiver: String,
+    pub maker_asset: String,
+    pub taker_asset: String,
+    pub making_amount: String,
+    pub taking_amount: String,
+    pub maker_traits: String,
+}
+
+impl LimitOrder {
+    /// Parsed order for the shared `fusion-order-hash` crate, `None` if a field is malformed
+    fn parse(&self) -> Option<Order> {
+        Some(Order {
+            salt: parse_uint256(&self.salt)?,
+            maker: parse_address(&self.maker)?,
+            receiver: parse_address(&self.receiver)?,
+            maker_asset: parse_address(&self.maker_asset)?,
+            taker_asset: parse_address(&self.taker_asset)?,
+            making_amount: parse_uint256(&self.making_amount)?,
+            taking_amount: parse_uint256(&self.taking_amount)?,
+            maker_traits: parse_uint256(&self.maker_traits)?,
+        })
+    }
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// EIP-712 hash of a 1inch order, as `hashOrder` of the Limit Order
+    /// Protocol on `chain_id` computes it. Lets clients check the order hash
+    /// a resolver submits matches the order they signed.
+    /// `verifying_contract` defaults to the 1inch Aggregation Router v6.
+    /// Returns `None` if a field is malformed.
+    pub fn compute_order_hash(
+        &self,
+        order: LimitOrder,
+        chain_id: u64,
+        verifying_contract: Option<String>,
+    ) -> Option<String> {
+        let verifying_contract = parse_address(verifying_contract.as_deref().unwrap_or(ONEINCH_ROUTER_V6))?;
+        let domain = Domain::limit_order_protocol(chain_id).with_verifying_contract(verifying_contract);
+        let hash = order.parse()?.hash(&NearHasher, &domain);
+        Some(format!("0x{}", hex::encode(hash)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_fixtures() {
+        let fixtures: serde_json::Value =
+            serde_json::from_str(include_str!("../../shared/fixtures/order_hashes.json")).unwrap();
+        let contract = FusionPlusNear::new(500);
+        for case in fixtures["cases"].as_array().unwrap() {
+            let order: LimitOrder = serde_json::from_value(case["order"].clone()).unwrap();
+            let verifying_contract = case["verifying_contract"].as_str().unwrap().to_string();
+            assert_eq!(
+                contract.compute_order_hash(order, case["chain_id"].as_u64().unwrap(), Some(verifying_contract)),
+                case["order_hash"].as_str().map(str::to_string),
+                "{}",
+                case["name"]
+            );
+        }
+    }
+}
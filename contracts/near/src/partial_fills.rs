@@ -0,0 +1,165 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::events::{FusionEvent, PartialFillExecutedEvent};
+use crate::ft::FusionOrderParams;
+use crate::hashlock::normalize_hex32;
+use crate::merkle;
+use crate::timelocks::{TimelockStage, Timelocks};
+use crate::{FusionPlusNear, FusionPlusNearExt, FusionPlusOrder, OrderStatus};
+
+/// Share of a multi-part order taken by a resolver as its own fill order
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PartialFill {
+    /// Multi-part order the share was taken from
+    pub order_hash: String,
+    /// Secret the fill is claimed with, fixed when the share is taken
+    pub secret_index: u32,
+}
+
+/// `ft_transfer_call` message taking a share of a NEP-141 multi-part order
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PartialFillMessage {
+    pub partial_fill_of: String,
+    pub fill_amount: U128,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Take `fill_amount` of a multi-part order as the caller's own fill, as
+    /// resolvers each fill their share of a 1inch Fusion+ order into their own escrow
+    /// The attached deposit funds the fill and its safety deposit like
+    /// `execute_fusion_order`; the share goes back to the order's resolver.
+    /// The fill reserves the next range of the order, so its secret index is
+    /// fixed now; it keeps the order's stage deadlines and is claimed whole
+    /// with `claim_partial_fill` or cancelled on its own.
+    /// Returns the fill order
+    #[payable]
+    pub fn execute_partial_fill(&mut self, order_hash: String, fill_amount: U128) -> FusionPlusOrder {
+        let resolver = env::predecessor_account_id();
+        let deposit = env::attached_deposit().as_yoctonear();
+        let fill = self.internal_execute_partial_fill(order_hash, fill_amount, resolver.clone(), None, deposit);
+        Self::internal_refund_excess(resolver, deposit - self.internal_order_deposit(&fill));
+        fill
+    }
+
+    /// Order a fill order was taken from, `None` for other orders
+    pub fn get_partial_fill(&self, order_hash: String) -> Option<PartialFill> {
+        normalize_hex32(&order_hash).and_then(|digits| self.partial_fills.get(&format!("0x{}", digits)))
+    }
+}
+
+impl FusionPlusNear {
+    /// Hash of the fill order revealing `secret_index` of `order_hash`
+    pub fn partial_fill_hash(order_hash: &str, secret_index: u32) -> String {
+        let preimage = format!("{}:{}", order_hash, secret_index);
+        format!("0x{}", hex::encode(env::sha256(preimage.as_bytes())))
+    }
+
+    /// Create `resolver`'s fill order for the next `fill_amount` of a
+    /// multi-part order, funded with `deposit` of the order's asset
+    pub(crate) fn internal_execute_partial_fill(
+        &mut self,
+        order_hash: String,
+        fill_amount: U128,
+        resolver: AccountId,
+        token: Option<AccountId>,
+        deposit: u128,
+    ) -> FusionPlusOrder {
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let mut order = self.orders.get(&order_hash).expect("Order not found");
+        let metadata = self.order_metadata.get(&order_hash).expect("Order not found");
+        assert_eq!(order.status, OrderStatus::Matched, "Order not fillable");
+        assert!(order.parts > 0, "Order does not allow partial fills");
+        assert!(self.partial_fills.get(&order_hash).is_none(), "Fill orders cannot be split");
+        assert_eq!(token, order.token, "Fill asset does not match the order");
+        let now = Self::now_seconds();
+        let timelocks = Timelocks(order.timelocks.0);
+        assert!(
+            now < timelocks.stage_start(TimelockStage::Cancellation, order.created_at),
+            "Claim window closed, order is cancellable"
+        );
+
+        let remaining = order.amount.0 - order.filled_amount.0;
+        assert!(
+            fill_amount.0 > 0 && fill_amount.0 <= remaining,
+            "Invalid fill amount"
+        );
+        let secret_index = merkle::expected_secret_index(
+            order.amount.0,
+            order.filled_amount.0,
+            fill_amount.0,
+            order.parts,
+        );
+        assert!(
+            order.last_fill_index.is_none_or(|last| secret_index > last),
+            "Secret already used"
+        );
+
+        // The range now belongs to the fill, the order's resolver gets its share back
+        order.filled_amount = U128(order.filled_amount.0 + fill_amount.0);
+        order.last_fill_index = Some(secret_index);
+        let completed = order.filled_amount == order.amount;
+        if completed {
+            self.internal_set_status(&order_hash, &mut order, OrderStatus::Claimed);
+        }
+        self.orders.insert(&order_hash, &order);
+        self.internal_record_fill_taken(&order, fill_amount.0);
+
+        let fill_hash = Self::partial_fill_hash(&order_hash, secret_index);
+        let params = FusionOrderParams {
+            order_hash: fill_hash.clone(),
+            hashlock: hex::encode(metadata.hashlock),
+            maker: order.maker.clone(),
+            amount: fill_amount,
+            resolver_fee: U128(0),
+            timelocks: U128(timelocks.rebased(order.created_at, now).0),
+            source_chain_id: metadata.source_chain_id,
+            hash_algorithm: Some(metadata.hash_algorithm),
+            parts: Some(order.parts),
+            destination: metadata.destination,
+            payout_to: metadata.payout_to,
+        };
+        let fill = self.internal_create_fusion_order(params, resolver.clone(), token, deposit);
+        let initial_storage_usage = env::storage_usage();
+        self.partial_fills.insert(
+            &fill_hash,
+            &PartialFill {
+                order_hash: order_hash.clone(),
+                secret_index,
+            },
+        );
+        self.internal_charge_storage(&resolver, initial_storage_usage);
+
+        FusionEvent::PartialFillExecuted(vec![PartialFillExecutedEvent {
+            order_hash: order_hash.clone(),
+            fill_order_hash: fill_hash,
+            resolver,
+            secret_index,
+            fill_amount,
+            filled_amount: order.filled_amount,
+        }])
+        .emit();
+
+        let _ = self.internal_payout(&order_hash, &order, order.resolver.clone(), fill_amount.0);
+        if completed {
+            // Nothing is left for the order's resolver to fill, it settles as claimed
+            self.internal_record_claim(&order, 0);
+            let _ = self.internal_pay_resolver_share(&order_hash, &order, order.resolver.clone(), true);
+        }
+        fill
+    }
+
+    /// Drop a fill order's link to its order, returning the storage to its resolver
+    pub(crate) fn internal_remove_partial_fill(&mut self, order_hash: &String, resolver: &AccountId) {
+        let initial_storage_usage = env::storage_usage();
+        if self.partial_fills.remove(order_hash).is_some() {
+            self.internal_release_storage(resolver, initial_storage_usage - env::storage_usage());
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Gas, NearToken, Promise, PromiseError};
+use schemars::JsonSchema;
+
+use crate::events::{FusionEvent, PayoutEvent};
+use crate::ft::{ext_ft, GAS_FOR_FT_TRANSFER};
+use crate::{FusionPlusNear, FusionPlusNearExt, OrderState};
+
+/// Gas reserved for the payout resolution callback
+pub const GAS_FOR_ON_PAYOUT: Gas = Gas::from_tgas(10);
+
+/// A transfer owed by the contract for a settled order
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Payout {
+    #[schemars(with = "String")]
+    pub receiver: AccountId,
+    #[schemars(with = "String")]
+    pub amount: U128,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Callback after each payout transfer
+    /// A failed transfer is recorded on the order so it can be retried
+    /// Both outcomes are emitted so indexers can follow every payout
+    /// Only `counted` failures move the circuit breaker, see `internal_payout`
+    #[private]
+    pub fn on_payout(
+        &mut self,
+        order_hash: String,
+        receiver: AccountId,
+        amount: U128,
+        counted: bool,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        if result.is_ok() || counted {
+            self.internal_record_payout_outcome(result.is_ok());
+        }
+        if result.is_ok() {
+            FusionEvent::PayoutSent(vec![PayoutEvent {
+                order_hash,
+                receiver,
+                amount,
+            }])
+            .emit();
+            return true;
+        }
+
+        self.internal_record_payout_failure(order_hash, receiver, amount);
+        false
+    }
+
+    /// Re-send payouts that failed for a settled order
+    /// Callable by the order's maker or resolver
+    pub fn retry_payouts(&mut self, order_hash: String) -> Promise {
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let order = self.orders.get(&order_hash).expect("Order not found");
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == order.maker || caller == order.resolver,
+            "Only maker or resolver can retry payouts"
+        );
+        let mut metadata = self.order_metadata.get(&order_hash).expect("Order not found");
+        assert!(!metadata.failed_payouts.is_empty(), "No failed payouts");
+
+        let payouts = std::mem::take(&mut metadata.failed_payouts);
+        self.order_metadata.insert(&order_hash, &metadata);
+        if Self::pays_native(&order) {
+            self.internal_record_retried_payouts(&payouts);
+        }
+
+        payouts
+            .into_iter()
+            .map(|payout| self.internal_send_payout(&order_hash, &order, payout.receiver, payout.amount.0, true))
+            .reduce(|all, next| all.and(next))
+            .unwrap()
+    }
+}
+
+impl FusionPlusNear {
+    /// Keep a failed payout on the order for `retry_payouts`
+    pub(crate) fn internal_record_payout_failure(&mut self, order_hash: String, receiver: AccountId, amount: U128) {
+        let mut metadata = self.order_metadata.get(&order_hash).expect("Order not found");
+        metadata.failed_payouts.push(Payout {
+            receiver: receiver.clone(),
+            amount,
+        });
+        self.order_metadata.insert(&order_hash, &metadata);
+        if self.orders.get(&order_hash).is_some_and(|order| Self::pays_native(&order)) {
+            self.internal_record_failed_payout(amount.0);
+        }
+
+        FusionEvent::PayoutFailed(vec![PayoutEvent {
+            order_hash,
+            receiver,
+            amount,
+        }])
+        .emit();
+    }
+
+    /// Transfer `amount` of the order's escrowed asset to `receiver`
+    /// NEP-141 transfers attach 1 yoctoNEAR; NEAR held by an escrow
+    /// sub-account is released by that account
+    pub(crate) fn internal_transfer(
+        &self,
+        order_hash: &str,
+        order: &OrderState,
+        receiver: AccountId,
+        amount: u128,
+    ) -> Promise {
+        match &order.token {
+            None if order.escrowed => self.internal_escrow_release(order_hash, receiver, amount),
+            None => Promise::new(receiver).transfer(NearToken::from_yoctonear(amount)),
+            Some(token) => ext_ft::ext(token.clone())
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(GAS_FOR_FT_TRANSFER)
+                .ft_transfer(receiver, U128(amount), Some(order_hash.to_string())),
+        }
+    }
+
+    /// Pay `amount` of the order's escrowed asset to `receiver`, wrapped or
+    /// unwrapped per the receiver's payout preference
+    /// Escrowed NEAR is always paid natively, the escrow cannot wrap it
+    /// The transfer resolves through `on_payout`
+    pub(crate) fn internal_payout(
+        &self,
+        order_hash: &str,
+        order: &OrderState,
+        receiver: AccountId,
+        amount: u128,
+    ) -> Promise {
+        self.internal_send_payout(order_hash, order, receiver, amount, false)
+    }
+
+    /// Send a payout, `retry` when it is re-sent through `retry_payouts`
+    /// Only a first-attempt NEAR transfer out of this contract or its escrow
+    /// counts toward the circuit breaker: NEP-141 and wNEAR transfers fail
+    /// routinely on unregistered storage, and a retry would let one party
+    /// replay the same failure until the contract pauses
+    fn internal_send_payout(
+        &self,
+        order_hash: &str,
+        order: &OrderState,
+        receiver: AccountId,
+        amount: u128,
+        retry: bool,
+    ) -> Promise {
+        let wrapped = (!order.escrowed)
+            .then(|| self.internal_wnear_transfer(order_hash, order, &receiver, amount))
+            .flatten();
+        let counted = !retry && wrapped.is_none() && order.token.is_none();
+        let transfer =
+            wrapped.unwrap_or_else(|| self.internal_transfer(order_hash, order, receiver.clone(), amount));
+        transfer.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_ON_PAYOUT)
+                .on_payout(order_hash.to_string(), receiver, U128(amount), counted),
+        )
+    }
+}
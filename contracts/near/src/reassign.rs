@@ -0,0 +1,89 @@
+use near_sdk::{assert_one_yocto, env, near_bindgen, AccountId, NearToken, Promise};
+
+use crate::events::{FusionEvent, ResolverReassignedEvent};
+use crate::hashlock::normalize_hex32;
+use crate::{FusionPlusNear, FusionPlusNearExt, OrderStatus};
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Consent to `new_resolver` taking over a Matched order whose resolver
+    /// stalled mid-swap (owner or maker). Replaces any earlier approval;
+    /// `new_resolver` completes the move with `reassign_resolver`.
+    /// Requires 1 yoctoNEAR.
+    #[payable]
+    pub fn approve_resolver_reassignment(&mut self, order_hash: String, new_resolver: AccountId) {
+        assert_one_yocto();
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let order = self.orders.get(&order_hash).expect("Order not found");
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == self.owner || caller == order.maker,
+            "Only owner or maker can approve a reassignment"
+        );
+        assert_eq!(order.status, OrderStatus::Matched, "Order not reassignable");
+        assert!(order.token.is_none(), "Only native NEAR orders can be reassigned");
+        assert!(
+            !self.has_token_safety_deposit(&order.resolver, &order_hash),
+            "Order has a token safety deposit"
+        );
+        assert_ne!(new_resolver, order.resolver, "Already the order's resolver");
+
+        self.resolver_reassignments.insert(&order_hash, &new_resolver);
+    }
+
+    /// Take over an order approved for the caller by `approve_resolver_reassignment`
+    /// The attached deposit replaces the order's safety deposit, which is
+    /// refunded to the previous resolver, and the order's storage charge moves
+    /// to the caller; any surplus is refunded. From now on the caller claims
+    /// or cancels the order and receives the resolver fee and safety deposit.
+    #[payable]
+    pub fn reassign_resolver(&mut self, order_hash: String) {
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let mut order = self.orders.get(&order_hash).expect("Order not found");
+        let resolver = env::predecessor_account_id();
+        assert_eq!(
+            self.resolver_reassignments.get(&order_hash).as_ref(),
+            Some(&resolver),
+            "Reassignment not approved"
+        );
+        assert_eq!(order.status, OrderStatus::Matched, "Order not reassignable");
+        self.assert_bonded_resolver(&resolver, "Not a 1inch authorized resolver");
+        let deposit = env::attached_deposit().as_yoctonear();
+        assert!(deposit >= order.safety_deposit.0, "Insufficient safety deposit");
+
+        // Move the order's storage charge along with the order
+        let previous_resolver = order.resolver.clone();
+        let metadata = self.order_metadata.get(&order_hash).expect("Order not found");
+        let initial_storage_usage = env::storage_usage();
+        self.orders.remove(&order_hash);
+        self.order_metadata.remove(&order_hash);
+        let release_key = self.release_keys.remove(&order_hash);
+        self.internal_release_storage(&previous_resolver, initial_storage_usage - env::storage_usage());
+
+        let initial_storage_usage = env::storage_usage();
+        order.resolver = resolver.clone();
+        self.orders.insert(&order_hash, &order);
+        self.order_metadata.insert(&order_hash, &metadata);
+        if let Some(release_key) = release_key {
+            self.release_keys.insert(&order_hash, &release_key);
+        }
+        self.internal_charge_storage(&resolver, initial_storage_usage);
+        self.resolver_reassignments.remove(&order_hash);
+
+        let _ = Promise::new(previous_resolver.clone()).transfer(NearToken::from_yoctonear(order.safety_deposit.0));
+        Self::internal_refund_excess(resolver.clone(), deposit - order.safety_deposit.0);
+
+        FusionEvent::ResolverReassigned(vec![ResolverReassignedEvent {
+            order_hash,
+            previous_resolver,
+            resolver,
+            safety_deposit: order.safety_deposit,
+        }])
+        .emit();
+    }
+
+    /// Resolver approved to take over the order, `None` for unknown or malformed hashes
+    pub fn get_resolver_reassignment(&self, order_hash: String) -> Option<AccountId> {
+        normalize_hex32(&order_hash).and_then(|digits| self.resolver_reassignments.get(&format!("0x{}", digits)))
+    }
+}
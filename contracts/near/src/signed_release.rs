@@ -0,0 +1,143 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Promise};
+use schemars::JsonSchema;
+
+use crate::aurora::normalize_evm_address;
+use crate::events::{FusionEvent, FusionOrderReleasedEvent, ReleaseKeySetEvent};
+use crate::hashlock::{decode_hex32, normalize_hex32};
+use crate::{FusionPlusNear, FusionPlusNearExt, OrderStatus};
+
+/// Key whose signature releases an order to its maker instead of the
+/// preimage, e.g. the maker's own key or a delivery or screening oracle
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ReleaseKey {
+    /// 32-byte ed25519 public key, hex
+    Ed25519(String),
+    /// Ethereum address of a secp256k1 key, hex
+    Secp256k1(String),
+}
+
+impl ReleaseKey {
+    /// Canonical form: lowercase hex, 0x-prefixed addresses; `None` if malformed
+    fn normalized(&self) -> Option<Self> {
+        match self {
+            ReleaseKey::Ed25519(key) => normalize_hex32(key).map(ReleaseKey::Ed25519),
+            ReleaseKey::Secp256k1(address) => {
+                normalize_evm_address(address).map(|digits| ReleaseKey::Secp256k1(format!("0x{}", digits)))
+            }
+        }
+    }
+
+    /// Whether `signature` (hex) signs `message` with this key
+    /// ed25519 signs the message bytes, secp256k1 its keccak256 as a 65-byte
+    /// `r || s || v` signature (`v` 0/1 or 27/28)
+    fn verify(&self, message: &[u8], signature: &str) -> bool {
+        let Ok(signature) = hex::decode(signature.strip_prefix("0x").unwrap_or(signature)) else {
+            return false;
+        };
+        match self {
+            ReleaseKey::Ed25519(key) => {
+                let (Ok(signature), Some(key)) = (<[u8; 64]>::try_from(signature), decode_hex32(key)) else {
+                    return false;
+                };
+                env::ed25519_verify(&signature, message, &key)
+            }
+            ReleaseKey::Secp256k1(address) => {
+                if signature.len() != 65 {
+                    return false;
+                }
+                let v = match signature[64] {
+                    27 | 28 => signature[64] - 27,
+                    v => v,
+                };
+                let Some(public_key) = env::ecrecover(&env::keccak256_array(message), &signature[..64], v, true) else {
+                    return false;
+                };
+                let signer = hex::encode(&env::keccak256_array(public_key)[12..]);
+                address.strip_prefix("0x") == Some(signer.as_str())
+            }
+        }
+    }
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Let a signature from `release_key` release a Matched single-fill order
+    /// to its maker, for flows where the secret can't be revealed (e.g. an
+    /// OFAC-screened release or oracle-confirmed delivery). Resolver only,
+    /// since it is the resolver's funds that are released; set once.
+    /// The record is charged to the resolver's storage balance
+    pub fn set_release_key(&mut self, order_hash: String, release_key: ReleaseKey) {
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let order = self.orders.get(&order_hash).expect("Order not found");
+        let resolver = env::predecessor_account_id();
+        assert_eq!(resolver, order.resolver, "Only resolver can set a release key");
+        assert_eq!(order.status, OrderStatus::Matched, "Order not claimable");
+        assert_eq!(order.parts, 0, "Release keys are for single-fill orders");
+        assert!(self.release_keys.get(&order_hash).is_none(), "Release key already set");
+        let release_key = release_key.normalized().expect("Invalid release key");
+
+        let initial_storage_usage = env::storage_usage();
+        self.release_keys.insert(&order_hash, &release_key);
+        self.internal_charge_storage(&resolver, initial_storage_usage);
+        FusionEvent::ReleaseKeySet(vec![ReleaseKeySetEvent { order_hash, release_key }]).emit();
+    }
+
+    /// Release an order to its maker with a signature of
+    /// `get_release_message(order_hash)` by its release key, from the
+    /// withdrawal stage. Callable by anyone: the signature authorizes the
+    /// release, so no public withdrawal reward is paid.
+    pub fn release_with_signature(&mut self, order_hash: String, signature: String) -> Promise {
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let mut order = self.orders.get(&order_hash).expect("Order not found");
+        let metadata = self.order_metadata.get(&order_hash).expect("Order not found");
+        let release_key = self.release_keys.get(&order_hash).expect("No release key");
+        assert_eq!(order.status, OrderStatus::Matched, "Order not claimable");
+        Self::assert_claim_window(&order, true);
+        self.assert_source_proven(&order_hash);
+        assert!(
+            release_key.verify(Self::release_message(&order_hash).as_bytes(), &signature),
+            "Invalid release signature"
+        );
+
+        self.internal_set_status(&order_hash, &mut order, OrderStatus::Claimed);
+        self.orders.insert(&order_hash, &order);
+        self.internal_record_claim(&order, order.amount.0);
+
+        let caller = env::predecessor_account_id();
+        FusionEvent::FusionOrderReleased(vec![FusionOrderReleasedEvent {
+            order_hash: order_hash.clone(),
+            resolver: order.resolver.clone(),
+            released_by: caller.clone(),
+            release_key,
+        }])
+        .emit();
+        self.internal_pay_claim(&order_hash, &order, &metadata, caller, true)
+    }
+
+    /// Release key of an order, `None` for unknown or malformed hashes
+    pub fn get_release_key(&self, order_hash: String) -> Option<ReleaseKey> {
+        normalize_hex32(&order_hash).and_then(|digits| self.release_keys.get(&format!("0x{}", digits)))
+    }
+
+    /// Message the release key signs to release an order, bound to this contract
+    pub fn get_release_message(&self, order_hash: String) -> String {
+        Self::release_message(&Self::normalize_order_hash(&order_hash))
+    }
+}
+
+impl FusionPlusNear {
+    fn release_message(order_hash: &str) -> String {
+        format!("{}:release:{}", env::current_account_id(), order_hash)
+    }
+
+    /// Drop a settled order's release key, returning its storage to the resolver
+    pub(crate) fn internal_remove_release_key(&mut self, order_hash: &String, resolver: &AccountId) {
+        let initial_storage_usage = env::storage_usage();
+        if self.release_keys.remove(order_hash).is_some() {
+            self.internal_release_storage(resolver, initial_storage_usage - env::storage_usage());
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::ft::FusionOrderParams;
+use crate::hashlock::normalize_hex32;
+use crate::timelocks::Timelocks;
+use crate::{FusionPlusNear, FusionPlusNearExt, MAX_DESTINATION_FIELD_LEN};
+
+/// Check `execute_fusion_order` would fail, listed in the order the contract runs them
+#[derive(Serialize, Deserialize, Clone, Copy, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ExecutionFailure {
+    /// Contract is paused
+    Paused,
+    InvalidOrderHash,
+    /// Maker allowlist is enabled and the maker is not on it
+    MakerNotAllowlisted,
+    /// Resolver is not on the 1inch allowlist
+    ResolverNotAuthorized,
+    ResolverBondBelowMinimum,
+    /// Attestations are required and the resolver has no valid one
+    ResolverNotAttested,
+    OrderAlreadyExists,
+    /// Deposit below amount + resolver fee
+    InsufficientDeposit,
+    /// Deposit below amount + resolver fee + safety deposit
+    InsufficientSafetyDeposit,
+    /// Posted token safety deposit below the safety deposit at the token's collateral ratio
+    InsufficientTokenSafetyDeposit,
+    /// Deposit does not also cover the escrow account's storage
+    InsufficientEscrowStorageDeposit,
+    InvalidHashlock,
+    /// Timelock stages out of order, see `Timelocks::is_ordered`
+    InvalidTimelocks,
+    DestinationFieldTooLong,
+    TvlCapExceeded,
+    /// Resolver has no NEP-145 storage balance to pay the order's storage
+    StorageNotRegistered,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Run the checks of `execute_fusion_order` for `resolver` attaching
+    /// `deposit` without changing state. Returns every failing check, an
+    /// empty list when the order would be created
+    /// Whether the storage balance covers the order is not checked, only
+    /// that the resolver registered one
+    pub fn simulate_execute_fusion_order(
+        &self,
+        resolver: AccountId,
+        params: FusionOrderParams,
+        deposit: U128,
+    ) -> Vec<ExecutionFailure> {
+        let mut failures = Vec::new();
+        if self.paused {
+            failures.push(ExecutionFailure::Paused);
+        }
+        let order_hash = normalize_hex32(&params.order_hash).map(|digits| format!("0x{}", digits));
+        if order_hash.is_none() {
+            failures.push(ExecutionFailure::InvalidOrderHash);
+        }
+        if !self.is_allowed_maker(params.maker.clone()) {
+            failures.push(ExecutionFailure::MakerNotAllowlisted);
+        }
+
+        if !self.authorized_resolvers.get(&resolver).unwrap_or(false) {
+            failures.push(ExecutionFailure::ResolverNotAuthorized);
+        }
+        let bonded = self.resolver_bonds.get(&resolver).map_or(0, |bond| bond.amount);
+        if bonded < self.min_resolver_bond {
+            failures.push(ExecutionFailure::ResolverBondBelowMinimum);
+        }
+        if !self.is_attested_resolver(&resolver) {
+            failures.push(ExecutionFailure::ResolverNotAttested);
+        }
+
+        if order_hash.as_ref().is_some_and(|order_hash| self.orders.get(order_hash).is_some()) {
+            failures.push(ExecutionFailure::OrderAlreadyExists);
+        }
+
+        let total_required = params.amount.0 + params.resolver_fee.0;
+        let mut safety_deposit = params.amount.0 * self.min_safety_deposit_bps as u128 / 10_000;
+        let token_deposit_hash = order_hash
+            .as_ref()
+            .filter(|order_hash| self.has_token_safety_deposit(&resolver, order_hash));
+        if let Some(order_hash) = token_deposit_hash {
+            if !self.token_safety_deposit_covers(&resolver, order_hash, safety_deposit) {
+                failures.push(ExecutionFailure::InsufficientTokenSafetyDeposit);
+            }
+            safety_deposit = 0;
+        }
+        if deposit.0 < total_required {
+            failures.push(ExecutionFailure::InsufficientDeposit);
+        } else if deposit.0 < total_required + safety_deposit {
+            failures.push(ExecutionFailure::InsufficientSafetyDeposit);
+        } else if self.escrow_mode
+            && deposit.0 < total_required + safety_deposit + self.internal_escrow_storage_cost()
+        {
+            failures.push(ExecutionFailure::InsufficientEscrowStorageDeposit);
+        }
+
+        if normalize_hex32(&params.hashlock).is_none() {
+            failures.push(ExecutionFailure::InvalidHashlock);
+        }
+        if !Timelocks(params.timelocks.0).is_ordered() {
+            failures.push(ExecutionFailure::InvalidTimelocks);
+        }
+        if params.destination.as_ref().is_some_and(|destination| {
+            destination.token.len() > MAX_DESTINATION_FIELD_LEN
+                || destination.address.len() > MAX_DESTINATION_FIELD_LEN
+        }) {
+            failures.push(ExecutionFailure::DestinationFieldTooLong);
+        }
+        if !self.within_tvl_cap(&None, total_required + safety_deposit) {
+            failures.push(ExecutionFailure::TvlCapExceeded);
+        }
+        if self.storage_accounts.get(&resolver).is_none() {
+            failures.push(ExecutionFailure::StorageNotRegistered);
+        }
+        failures
+    }
+}
@@ -0,0 +1,112 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+use schemars::JsonSchema;
+
+use crate::payout::Payout;
+use crate::{FusionPlusNear, FusionPlusNearExt, OrderState};
+
+/// NEAR held by this contract on behalf of others outside of the order totals,
+/// updated on every deposit and payout
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Liabilities {
+    /// Safety deposits of matched HTLC orders and best bids of open auctions
+    pub htlc_deposits: u128,
+    /// Safety deposits of claimed orders waiting out their challenge window
+    pub held_deposits: u128,
+    /// Failed payouts awaiting a retry
+    pub failed_payouts: u128,
+    /// Resolver bonds, including unbonding balances
+    pub resolver_bonds: u128,
+}
+
+/// NEAR the contract owes compared to what it holds
+/// NEAR held by escrow sub-accounts is neither owed nor held by this contract
+/// Unused NEP-145 storage balances are not counted and show up as surplus
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Solvency {
+    /// Account balance minus the NEAR staked for this contract's storage
+    #[schemars(with = "String")]
+    pub available_balance: U128,
+    /// Sum of all the obligations below
+    #[schemars(with = "String")]
+    pub total_obligations: U128,
+    /// Unsettled Fusion+ orders held by this contract
+    #[schemars(with = "String")]
+    pub fusion_locked: U128,
+    /// Maker funds of open HTLC orders
+    #[schemars(with = "String")]
+    pub htlc_locked: U128,
+    #[schemars(with = "String")]
+    pub htlc_deposits: U128,
+    #[schemars(with = "String")]
+    pub held_deposits: U128,
+    #[schemars(with = "String")]
+    pub failed_payouts: U128,
+    #[schemars(with = "String")]
+    pub resolver_bonds: U128,
+    /// Protocol fees accrued in NEAR
+    #[schemars(with = "String")]
+    pub accrued_fees: U128,
+    pub solvent: bool,
+    /// `available_balance - total_obligations`, 0 when insolvent
+    #[schemars(with = "String")]
+    pub surplus: U128,
+    /// `total_obligations - available_balance`, 0 when solvent
+    #[schemars(with = "String")]
+    pub deficit: U128,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Compare the NEAR this contract holds to what it owes, so monitoring
+    /// can catch accounting drift or stuck payouts as soon as they happen
+    pub fn get_solvency(&self) -> Solvency {
+        let storage_staked = env::storage_usage() as u128 * env::storage_byte_cost().as_yoctonear();
+        let available_balance = env::account_balance().as_yoctonear().saturating_sub(storage_staked);
+        let fusion_locked = self.totals.locked.saturating_sub(self.totals.escrowed);
+        let accrued_fees = self.accrued_fees.get(&None).unwrap_or(0);
+        let total_obligations = fusion_locked
+            + self.htlc_locked
+            + self.liabilities.htlc_deposits
+            + self.liabilities.held_deposits
+            + self.liabilities.failed_payouts
+            + self.liabilities.resolver_bonds
+            + accrued_fees;
+
+        Solvency {
+            available_balance: U128(available_balance),
+            total_obligations: U128(total_obligations),
+            fusion_locked: U128(fusion_locked),
+            htlc_locked: U128(self.htlc_locked),
+            htlc_deposits: U128(self.liabilities.htlc_deposits),
+            held_deposits: U128(self.liabilities.held_deposits),
+            failed_payouts: U128(self.liabilities.failed_payouts),
+            resolver_bonds: U128(self.liabilities.resolver_bonds),
+            accrued_fees: U128(accrued_fees),
+            solvent: available_balance >= total_obligations,
+            surplus: U128(available_balance.saturating_sub(total_obligations)),
+            deficit: U128(total_obligations.saturating_sub(available_balance)),
+        }
+    }
+}
+
+impl FusionPlusNear {
+    /// Whether NEAR paid out for `order` comes from this contract's balance
+    pub(crate) fn pays_native(order: &OrderState) -> bool {
+        order.token.is_none() && !order.escrowed
+    }
+
+    /// Record a failed native payout that now waits in this contract for a retry
+    pub(crate) fn internal_record_failed_payout(&mut self, amount: u128) {
+        self.liabilities.failed_payouts += amount;
+    }
+
+    /// Record failed payouts being sent again
+    pub(crate) fn internal_record_retried_payouts(&mut self, payouts: &[Payout]) {
+        let amount: u128 = payouts.iter().map(|payout| payout.amount.0).sum();
+        self.liabilities.failed_payouts = self.liabilities.failed_payouts.saturating_sub(amount);
+    }
+}
@@ -0,0 +1,206 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{near_bindgen, AccountId};
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use crate::{FusionPlusNear, FusionPlusNearExt, OrderState, OrderStatus};
+
+/// Running totals over native NEAR orders, updated on every transition
+/// NEP-141 orders are only reflected in the status counts
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct OrderTotals {
+    /// NEAR currently held in escrow (amount + resolver fee + safety deposit)
+    pub locked: u128,
+    /// Part of `locked` held by escrow sub-accounts rather than this contract
+    pub escrowed: u128,
+    /// NEAR released to makers
+    pub claimed_volume: u128,
+    /// Resolver fees paid out on completed orders
+    pub fees: u128,
+}
+
+/// Running counters of one resolver's orders
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct ResolverCounters {
+    pub executed: u64,
+    pub claimed: u64,
+    pub refunded: u64,
+    /// NEAR escrowed for makers by native orders
+    pub volume: u128,
+    /// Sum over claimed orders of the seconds from creation to claim
+    pub total_time_to_claim: u64,
+}
+
+/// Performance of a resolver, e.g. to curate the allowlist
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ResolverStats {
+    pub executed: u64,
+    pub claimed: u64,
+    pub refunded: u64,
+    /// NEAR escrowed for makers by native orders
+    #[schemars(with = "String")]
+    pub volume: U128,
+    /// Mean seconds from creation to claim, 0 before the first claim
+    pub average_time_to_claim: u64,
+}
+
+/// Contract-wide order statistics for monitoring
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FusionStats {
+    pub pending: u64,
+    pub matched: u64,
+    pub claimed: u64,
+    pub refunded: u64,
+    #[schemars(with = "String")]
+    pub total_locked: U128,
+    #[schemars(with = "String")]
+    pub claimed_volume: U128,
+    #[schemars(with = "String")]
+    pub total_fees: U128,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    pub fn get_stats(&self) -> FusionStats {
+        let count = |status: OrderStatus| {
+            self.orders_by_status
+                .get(&status)
+                .map_or(0, |order_hashes| order_hashes.len())
+        };
+        FusionStats {
+            pending: count(OrderStatus::Pending),
+            matched: count(OrderStatus::Matched),
+            claimed: count(OrderStatus::Claimed),
+            refunded: count(OrderStatus::Refunded),
+            total_locked: U128(self.totals.locked),
+            claimed_volume: U128(self.totals.claimed_volume),
+            total_fees: U128(self.totals.fees),
+        }
+    }
+
+    /// Orders `account_id` executed as resolver and their outcome; claims
+    /// and refunds count for the resolver of the order at that time
+    pub fn get_resolver_stats(&self, account_id: AccountId) -> ResolverStats {
+        let counters = self.resolver_stats.get(&account_id).unwrap_or_default();
+        ResolverStats {
+            executed: counters.executed,
+            claimed: counters.claimed,
+            refunded: counters.refunded,
+            volume: U128(counters.volume),
+            average_time_to_claim: counters
+                .total_time_to_claim
+                .checked_div(counters.claimed)
+                .unwrap_or(0),
+        }
+    }
+}
+
+impl FusionPlusNear {
+    /// Record NEAR (or tokens) escrowed by a new order
+    pub(crate) fn internal_record_locked(&mut self, order: &OrderState) {
+        let locked = order.amount.0 + order.resolver_fee.0 + order.safety_deposit.0;
+        match &order.token {
+            None => {
+                self.totals.locked += locked;
+                if order.escrowed {
+                    self.totals.escrowed += locked;
+                }
+            }
+            Some(token) => {
+                let token_locked = self.token_locked.get(token).unwrap_or(0);
+                self.token_locked.insert(token, &(token_locked + locked));
+            }
+        }
+    }
+
+    /// Count a new order for its resolver
+    pub(crate) fn internal_record_executed(&mut self, order: &OrderState) {
+        self.internal_update_resolver_stats(&order.resolver, |counters| {
+            counters.executed += 1;
+            if order.token.is_none() {
+                counters.volume += order.amount.0;
+            }
+        });
+    }
+
+    /// Record `amount` released to the maker and, once the order is
+    /// complete, its resolver fee and safety deposit
+    pub(crate) fn internal_record_claim(&mut self, order: &OrderState, amount: u128) {
+        let completed = order.status == OrderStatus::Claimed;
+        if completed {
+            let time_to_claim = Self::now_seconds().saturating_sub(order.created_at);
+            self.internal_update_resolver_stats(&order.resolver, |counters| {
+                counters.claimed += 1;
+                counters.total_time_to_claim += time_to_claim;
+            });
+        }
+        if order.token.is_none() {
+            self.totals.claimed_volume += amount;
+            if completed {
+                self.totals.fees += order.resolver_fee.0;
+                self.internal_release_native_locked(order, amount + order.resolver_fee.0 + order.safety_deposit.0);
+            } else {
+                self.internal_release_native_locked(order, amount);
+            }
+        } else if completed {
+            self.internal_release_token_locked(order, amount + order.resolver_fee.0 + order.safety_deposit.0);
+        } else {
+            self.internal_release_token_locked(order, amount);
+        }
+    }
+
+    /// Record NEAR (or tokens) refunded to the resolver
+    pub(crate) fn internal_record_refund(&mut self, order: &OrderState, amount: u128) {
+        self.internal_update_resolver_stats(&order.resolver, |counters| counters.refunded += 1);
+        if order.token.is_none() {
+            self.internal_release_native_locked(order, amount);
+        } else {
+            self.internal_release_token_locked(order, amount);
+        }
+    }
+
+    /// Record `amount` of a multi-part order taken by another resolver's fill
+    /// and returned to the order's resolver
+    pub(crate) fn internal_record_fill_taken(&mut self, order: &OrderState, amount: u128) {
+        if order.token.is_none() {
+            self.internal_release_native_locked(order, amount);
+        } else {
+            self.internal_release_token_locked(order, amount);
+        }
+    }
+
+    /// Record the unsettled NEAR of an order whose escrow account could not
+    /// be created as held by this contract
+    pub(crate) fn internal_record_unescrowed(&mut self, order: &OrderState) {
+        if order.token.is_none() && matches!(order.status, OrderStatus::Pending | OrderStatus::Matched) {
+            let unsettled = order.amount.0 - order.filled_amount.0 + order.resolver_fee.0 + order.safety_deposit.0;
+            self.totals.escrowed = self.totals.escrowed.saturating_sub(unsettled);
+        }
+    }
+
+    fn internal_update_resolver_stats(&mut self, resolver: &AccountId, update: impl FnOnce(&mut ResolverCounters)) {
+        let mut counters = self.resolver_stats.get(resolver).unwrap_or_default();
+        update(&mut counters);
+        self.resolver_stats.insert(resolver, &counters);
+    }
+
+    fn internal_release_native_locked(&mut self, order: &OrderState, amount: u128) {
+        self.totals.locked -= amount;
+        if order.escrowed {
+            self.totals.escrowed = self.totals.escrowed.saturating_sub(amount);
+        }
+    }
+
+    fn internal_release_token_locked(&mut self, order: &OrderState, amount: u128) {
+        let Some(token) = &order.token else {
+            return;
+        };
+        match self.token_locked.get(token).unwrap_or(0).saturating_sub(amount) {
+            0 => self.token_locked.remove(token),
+            locked => self.token_locked.insert(token, &locked),
+        };
+    }
+}
@@ -0,0 +1,207 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, AccountId, NearToken, Promise};
+use schemars::JsonSchema;
+
+use crate::{FusionPlusNear, FusionPlusNearExt};
+
+/// Per-account NEP-145 storage record
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct StorageAccount {
+    /// Total yoctoNEAR deposited for storage
+    pub deposit: u128,
+    /// Bytes currently paid for by this account (registration + orders)
+    pub used_bytes: u64,
+}
+
+/// NEP-145 storage balance
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalance {
+    #[schemars(with = "String")]
+    pub total: U128,
+    #[schemars(with = "String")]
+    pub available: U128,
+}
+
+/// NEP-145 storage balance bounds
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalanceBounds {
+    #[schemars(with = "String")]
+    pub min: U128,
+    #[schemars(with = "Option<String>")]
+    pub max: Option<U128>,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// NEP-145: deposit NEAR to cover storage of orders created by `account_id`
+    /// Order creators must register before executing orders
+    #[payable]
+    pub fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let amount = env::attached_deposit().as_yoctonear();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let min_balance = self.storage_balance_bounds().min.0;
+
+        let account = match self.storage_accounts.get(&account_id) {
+            Some(mut account) => {
+                if registration_only.unwrap_or(false) {
+                    // Already registered, refund the full deposit
+                    if amount > 0 {
+                        let _ = Promise::new(env::predecessor_account_id())
+                            .transfer(NearToken::from_yoctonear(amount));
+                    }
+                } else {
+                    account.deposit += amount;
+                }
+                account
+            }
+            None => {
+                assert!(amount >= min_balance, "Insufficient storage deposit");
+                let deposit = if registration_only.unwrap_or(false) {
+                    let refund = amount - min_balance;
+                    if refund > 0 {
+                        let _ = Promise::new(env::predecessor_account_id())
+                            .transfer(NearToken::from_yoctonear(refund));
+                    }
+                    min_balance
+                } else {
+                    amount
+                };
+                StorageAccount {
+                    deposit,
+                    used_bytes: self.account_storage_usage,
+                }
+            }
+        };
+
+        self.storage_accounts.insert(&account_id, &account);
+        self.internal_storage_balance(&account)
+    }
+
+    /// NEP-145: withdraw unused storage deposit
+    #[payable]
+    pub fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let mut account = self
+            .storage_accounts
+            .get(&account_id)
+            .expect("Account not registered");
+
+        let available = self.internal_storage_balance(&account).available.0;
+        let amount = amount.map(|a| a.0).unwrap_or(available);
+        assert!(amount <= available, "Amount exceeds available storage balance");
+
+        account.deposit -= amount;
+        self.storage_accounts.insert(&account_id, &account);
+        if amount > 0 {
+            let _ = Promise::new(account_id).transfer(NearToken::from_yoctonear(amount));
+        }
+
+        self.internal_storage_balance(&account)
+    }
+
+    /// NEP-145: unregister and refund the full storage deposit
+    /// Accounts still paying for stored orders cannot unregister, even with `force`
+    #[payable]
+    #[allow(unused_variables)]
+    pub fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        match self.storage_accounts.get(&account_id) {
+            Some(account) => {
+                assert_eq!(
+                    account.used_bytes, self.account_storage_usage,
+                    "Cannot unregister with stored orders"
+                );
+                self.storage_accounts.remove(&account_id);
+                let _ = Promise::new(account_id).transfer(NearToken::from_yoctonear(account.deposit));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: U128(self.account_storage_usage as u128 * env::storage_byte_cost().as_yoctonear()),
+            max: None,
+        }
+    }
+
+    pub fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_accounts
+            .get(&account_id)
+            .map(|account| self.internal_storage_balance(&account))
+    }
+}
+
+impl FusionPlusNear {
+    /// Measure the bytes needed to register one storage account
+    pub(crate) fn measure_account_storage_usage(&mut self) {
+        let initial_storage_usage = env::storage_usage();
+        let tmp_account_id: AccountId = "a".repeat(64).parse().unwrap();
+        self.storage_accounts.insert(
+            &tmp_account_id,
+            &StorageAccount {
+                deposit: 0,
+                used_bytes: 0,
+            },
+        );
+        self.account_storage_usage = env::storage_usage() - initial_storage_usage;
+        self.storage_accounts.remove(&tmp_account_id);
+    }
+
+    /// Charge the storage added since `initial_storage_usage` to `account_id`
+    pub(crate) fn internal_charge_storage(&mut self, account_id: &AccountId, initial_storage_usage: u64) {
+        let bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+        let mut account = self
+            .storage_accounts
+            .get(account_id)
+            .expect("Account not registered for storage");
+        account.used_bytes += bytes;
+        assert!(
+            account.used_bytes as u128 * env::storage_byte_cost().as_yoctonear() <= account.deposit,
+            "Insufficient storage deposit"
+        );
+        self.storage_accounts.insert(account_id, &account);
+    }
+
+    /// Charge like `internal_charge_storage` without requiring the deposit to
+    /// cover it, so a claim or refund is never blocked by the resolver's balance
+    /// A shortfall leaves nothing available until it is topped up
+    pub(crate) fn internal_charge_storage_owed(&mut self, account_id: &AccountId, initial_storage_usage: u64) {
+        let bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+        if let Some(mut account) = self.storage_accounts.get(account_id) {
+            account.used_bytes += bytes;
+            self.storage_accounts.insert(account_id, &account);
+        }
+    }
+
+    /// Release up to `bytes` charged to `account_id` (never its registration)
+    /// Returns the yoctoNEAR made available again
+    pub(crate) fn internal_release_storage(&mut self, account_id: &AccountId, bytes: u64) -> u128 {
+        let Some(mut account) = self.storage_accounts.get(account_id) else {
+            return 0;
+        };
+        let released = bytes.min(account.used_bytes.saturating_sub(self.account_storage_usage));
+        account.used_bytes -= released;
+        self.storage_accounts.insert(account_id, &account);
+        released as u128 * env::storage_byte_cost().as_yoctonear()
+    }
+
+    fn internal_storage_balance(&self, account: &StorageAccount) -> StorageBalance {
+        let locked = account.used_bytes as u128 * env::storage_byte_cost().as_yoctonear();
+        StorageBalance {
+            total: U128(account.deposit),
+            available: U128(account.deposit.saturating_sub(locked)),
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Gas, NearToken, Promise, PromiseError};
+use schemars::JsonSchema;
+
+use crate::events::{FusionEvent, PayoutEvent};
+use crate::ft::ext_ft;
+use crate::hashlock::normalize_hex32;
+use crate::wnear::{ext_wnear, GAS_FOR_WNEAR};
+use crate::{FusionPlusNear, FusionPlusNearExt, OrderState, OrderStatus};
+
+/// Gas attached to the `ft_transfer_call` into Ref Finance, which runs the swap
+pub const GAS_FOR_REF_SWAP: Gas = Gas::from_tgas(100);
+/// Gas reserved for the swap resolution callback
+pub const GAS_FOR_ON_CLAIM_SWAP: Gas = Gas::from_tgas(20);
+
+/// Ref Finance swap the maker's claim payout is routed through
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ClaimSwap {
+    /// Ref pool swapping the order's asset (wNEAR for native orders) into `token_out`
+    pub pool_id: u64,
+    #[schemars(with = "String")]
+    pub token_out: AccountId,
+    /// Least `token_out` accepted for the whole payout, the swap fails below it
+    #[schemars(with = "String")]
+    pub min_amount_out: U128,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Set the Ref Finance exchange (e.g. `v2.ref-finance.near`), `None` disables claim swaps
+    #[payable]
+    pub fn set_ref_exchange(&mut self, ref_exchange: Option<AccountId>) {
+        self.assert_owner();
+        self.ref_exchange = ref_exchange;
+    }
+
+    pub fn get_ref_exchange(&self) -> Option<AccountId> {
+        self.ref_exchange.clone()
+    }
+
+    /// Maker asks for its claim payout to be swapped into another token
+    /// through Ref Finance, `None` removes the swap. The payout receiver must
+    /// be registered on `token_out`. Only for single-part orders not held by
+    /// an escrow account; the entry is charged to the maker's storage balance.
+    /// A failed swap is recorded as a failed payout, retried in the order's asset.
+    pub fn set_claim_swap(&mut self, order_hash: String, claim_swap: Option<ClaimSwap>) {
+        let order_hash = Self::normalize_order_hash(&order_hash);
+        let order = self.orders.get(&order_hash).expect("Order not found");
+        assert_eq!(env::predecessor_account_id(), order.maker, "Only the maker can set a claim swap");
+        assert_eq!(order.status, OrderStatus::Matched, "Order not claimable");
+
+        let initial_storage_usage = env::storage_usage();
+        match claim_swap {
+            Some(claim_swap) => {
+                assert!(self.ref_exchange.is_some(), "Ref exchange not set");
+                assert!(order.parts == 0 && !order.escrowed, "Order does not support claim swaps");
+                let token_in = self.swap_token_in(&order).expect("wNEAR contract not set");
+                assert_ne!(claim_swap.token_out, token_in, "Swap output is the order's asset");
+                self.claim_swaps.insert(&order_hash, &claim_swap);
+            }
+            None => {
+                self.claim_swaps.remove(&order_hash);
+            }
+        }
+        if env::storage_usage() > initial_storage_usage {
+            self.internal_charge_storage(&order.maker, initial_storage_usage);
+        } else {
+            self.internal_release_storage(&order.maker, initial_storage_usage - env::storage_usage());
+        }
+    }
+
+    /// Claim swap of an order, `None` for unknown or malformed hashes
+    pub fn get_claim_swap(&self, order_hash: String) -> Option<ClaimSwap> {
+        normalize_hex32(&order_hash).and_then(|digits| self.claim_swaps.get(&format!("0x{}", digits)))
+    }
+
+    /// Callback after a claim swap, given the amount Ref Finance used
+    /// Any unused amount came back as the swapped asset and is recorded
+    /// as a failed payout; wNEAR of native orders is unwrapped first
+    #[private]
+    pub fn on_claim_swap(
+        &mut self,
+        order_hash: String,
+        receiver: AccountId,
+        amount: U128,
+        #[callback_result] result: Result<U128, PromiseError>,
+    ) -> bool {
+        // A failed `ft_transfer_call` leaves the whole amount with this contract
+        let (used, transferred) = match result {
+            Ok(used) => (used.0.min(amount.0), true),
+            Err(_) => (0, false),
+        };
+        if used == amount.0 {
+            FusionEvent::PayoutSent(vec![PayoutEvent {
+                order_hash,
+                receiver,
+                amount,
+            }])
+            .emit();
+            return true;
+        }
+
+        let refunded = amount.0 - used;
+        let native = self.orders.get(&order_hash).is_some_and(|order| order.token.is_none());
+        if native && transferred {
+            let wnear = self.wnear_contract.clone().expect("wNEAR contract not set");
+            let _ = ext_wnear::ext(wnear)
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(GAS_FOR_WNEAR)
+                .near_withdraw(U128(refunded));
+        }
+        self.internal_record_payout_failure(order_hash, receiver, U128(refunded));
+        false
+    }
+}
+
+impl FusionPlusNear {
+    /// Pay the maker's share of a claim, through its claim swap when one is set
+    pub(crate) fn internal_maker_payout(
+        &self,
+        order_hash: &String,
+        order: &OrderState,
+        receiver: AccountId,
+        amount: u128,
+    ) -> Promise {
+        let swap = self.claim_swaps.get(order_hash).zip(self.ref_exchange.clone());
+        match swap {
+            Some((claim_swap, ref_exchange)) if !order.escrowed => {
+                self.internal_swap_payout(order_hash, order, ref_exchange, claim_swap, receiver, amount)
+            }
+            _ => self.internal_payout(order_hash, order, receiver, amount),
+        }
+    }
+
+    /// Send `amount` into Ref Finance, which swaps it and pays `receiver`
+    fn internal_swap_payout(
+        &self,
+        order_hash: &str,
+        order: &OrderState,
+        ref_exchange: AccountId,
+        claim_swap: ClaimSwap,
+        receiver: AccountId,
+        amount: u128,
+    ) -> Promise {
+        let token_in = self.swap_token_in(order).expect("wNEAR contract not set");
+        let msg = serde_json::json!({
+            "actions": [{
+                "pool_id": claim_swap.pool_id,
+                "token_in": token_in,
+                "token_out": claim_swap.token_out,
+                "amount_in": U128(amount),
+                "min_amount_out": claim_swap.min_amount_out,
+            }],
+            "swap_out_recipient": receiver,
+        })
+        .to_string();
+        let swap = match &order.token {
+            // Wrap and swap in one batch so a failed call also reverts the deposit
+            None => Promise::new(token_in)
+                .function_call(
+                    "near_deposit".to_string(),
+                    Vec::new(),
+                    NearToken::from_yoctonear(amount),
+                    GAS_FOR_WNEAR,
+                )
+                .function_call(
+                    "ft_transfer_call".to_string(),
+                    serde_json::json!({
+                        "receiver_id": ref_exchange,
+                        "amount": U128(amount),
+                        "msg": msg,
+                    })
+                    .to_string()
+                    .into_bytes(),
+                    NearToken::from_yoctonear(1),
+                    GAS_FOR_REF_SWAP,
+                ),
+            Some(token) => ext_ft::ext(token.clone())
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(GAS_FOR_REF_SWAP)
+                .ft_transfer_call(ref_exchange, U128(amount), Some(order_hash.to_string()), msg),
+        };
+        swap.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_ON_CLAIM_SWAP)
+                .on_claim_swap(order_hash.to_string(), receiver, U128(amount)),
+        )
+    }
+
+    /// Token a claim swap sells: the order's token, or wNEAR for native orders
+    fn swap_token_in(&self, order: &OrderState) -> Option<AccountId> {
+        order.token.clone().or_else(|| self.wnear_contract.clone())
+    }
+
+    /// Drop an order's claim swap, returning its storage to the maker
+    pub(crate) fn internal_remove_claim_swap(&mut self, order_hash: &String, maker: &AccountId) {
+        let initial_storage_usage = env::storage_usage();
+        if self.claim_swaps.remove(order_hash).is_some() {
+            self.internal_release_storage(maker, initial_storage_usage - env::storage_usage());
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+/// Timelock stages packed into a fusion order's `timelocks`
+/// Each stage is a 32-bit offset in seconds from order creation, stage `i`
+/// stored at bits `i * 32`: the low 128 bits of the 1inch layout, packed
+/// and unpacked by the shared `fusion-timelocks` crate
+#[derive(Serialize, Deserialize, Clone, Copy, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum TimelockStage {
+    /// Resolver may claim with the preimage
+    Withdrawal = 0,
+    /// Anyone with the preimage may claim
+    PublicWithdrawal = 1,
+    /// Resolver may cancel and refund
+    Cancellation = 2,
+    /// Anyone may cancel and refund
+    PublicCancellation = 3,
+}
+
+impl From<TimelockStage> for fusion_timelocks::Stage {
+    fn from(stage: TimelockStage) -> Self {
+        match stage {
+            TimelockStage::Withdrawal => Self::SrcWithdrawal,
+            TimelockStage::PublicWithdrawal => Self::SrcPublicWithdrawal,
+            TimelockStage::Cancellation => Self::SrcCancellation,
+            TimelockStage::PublicCancellation => Self::SrcPublicCancellation,
+        }
+    }
+}
+
+/// Absolute start (seconds) of every stage of an order
+#[derive(Serialize, Deserialize, Clone, Copy, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TimelockStages {
+    /// Order creation, the finality lock runs until the withdrawal stage
+    pub finality: u64,
+    pub withdrawal: u64,
+    pub public_withdrawal: u64,
+    pub cancellation: u64,
+    pub public_cancellation: u64,
+}
+
+/// Packed 1inch-style timelocks (4 x 32-bit stage offsets)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Timelocks(pub u128);
+
+impl Timelocks {
+    /// Pack stage offsets (seconds) in `TimelockStage` order
+    pub fn pack(offsets: [u32; 4]) -> Self {
+        let mut stages = [0; fusion_timelocks::STAGE_COUNT];
+        stages[..4].copy_from_slice(&offsets);
+        Self(fusion_timelocks::Timelocks::new(stages, 0).low_u128())
+    }
+
+    /// Offset in seconds of `stage` relative to order creation
+    pub fn offset(&self, stage: TimelockStage) -> u32 {
+        fusion_timelocks::Timelocks::from_u128(self.0).offset(stage.into())
+    }
+
+    /// Absolute start (seconds) of `stage` for an order created at `created_at`
+    pub fn stage_start(&self, stage: TimelockStage, created_at: u64) -> u64 {
+        created_at + self.offset(stage) as u64
+    }
+
+    /// Whether the stages follow each other: withdrawal, public withdrawal,
+    /// then cancellation strictly later, then public cancellation
+    pub fn is_ordered(&self) -> bool {
+        let offset = |stage| self.offset(stage);
+        offset(TimelockStage::Withdrawal) <= offset(TimelockStage::PublicWithdrawal)
+            && offset(TimelockStage::PublicWithdrawal) < offset(TimelockStage::Cancellation)
+            && offset(TimelockStage::Cancellation) <= offset(TimelockStage::PublicCancellation)
+    }
+
+    /// Absolute start of every stage for an order created at `created_at`
+    pub fn stages(&self, created_at: u64) -> TimelockStages {
+        TimelockStages {
+            finality: created_at,
+            withdrawal: self.stage_start(TimelockStage::Withdrawal, created_at),
+            public_withdrawal: self.stage_start(TimelockStage::PublicWithdrawal, created_at),
+            cancellation: self.stage_start(TimelockStage::Cancellation, created_at),
+            public_cancellation: self.stage_start(TimelockStage::PublicCancellation, created_at),
+        }
+    }
+
+    /// The same absolute stages for an order created at `now` instead of
+    /// `created_at`, stages already started opening immediately
+    pub fn rebased(&self, created_at: u64, now: u64) -> Self {
+        let offset = |stage| self.stage_start(stage, created_at).saturating_sub(now) as u32;
+        Self::pack([
+            offset(TimelockStage::Withdrawal),
+            offset(TimelockStage::PublicWithdrawal),
+            offset(TimelockStage::Cancellation),
+            offset(TimelockStage::PublicCancellation),
+        ])
+    }
+
+    /// Move the cancellation stage to start at `cancellation_at` (seconds),
+    /// shifting public cancellation by the same delay
+    pub fn with_cancellation_at(&self, cancellation_at: u64, created_at: u64) -> Self {
+        let delay = cancellation_at - self.stage_start(TimelockStage::Cancellation, created_at);
+        let shift = |stage: TimelockStage| -> u32 {
+            (self.offset(stage) as u64 + delay)
+                .try_into()
+                .expect("Timelock too far in the future")
+        };
+        Self::pack([
+            self.offset(TimelockStage::Withdrawal),
+            self.offset(TimelockStage::PublicWithdrawal),
+            shift(TimelockStage::Cancellation),
+            shift(TimelockStage::PublicCancellation),
+        ])
+    }
+
+    /// Latest stage started at `now`, `None` before the withdrawal stage opens
+    pub fn active_stage(&self, now: u64, created_at: u64) -> Option<TimelockStage> {
+        [
+            TimelockStage::PublicCancellation,
+            TimelockStage::Cancellation,
+            TimelockStage::PublicWithdrawal,
+            TimelockStage::Withdrawal,
+        ]
+        .into_iter()
+        .find(|stage| now >= self.stage_start(*stage, created_at))
+    }
+
+    /// First stage still to start after `now` and its absolute start,
+    /// `None` once public cancellation is open
+    pub fn next_stage(&self, now: u64, created_at: u64) -> Option<(TimelockStage, u64)> {
+        [
+            TimelockStage::Withdrawal,
+            TimelockStage::PublicWithdrawal,
+            TimelockStage::Cancellation,
+            TimelockStage::PublicCancellation,
+        ]
+        .into_iter()
+        .map(|stage| (stage, self.stage_start(stage, created_at)))
+        .find(|(_, start)| *start > now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_and_unpack_stages() {
+        let timelocks = Timelocks::pack([60, 3600, 7200, 10800]);
+        assert_eq!(timelocks.offset(TimelockStage::Withdrawal), 60);
+        assert_eq!(timelocks.offset(TimelockStage::PublicWithdrawal), 3600);
+        assert_eq!(timelocks.offset(TimelockStage::Cancellation), 7200);
+        assert_eq!(timelocks.offset(TimelockStage::PublicCancellation), 10800);
+        assert_eq!(timelocks.stage_start(TimelockStage::Cancellation, 1_000), 8_200);
+    }
+
+    #[test]
+    fn test_stages() {
+        let stages = Timelocks::pack([60, 3600, 7200, 10800]).stages(1_000);
+        assert_eq!(
+            stages,
+            TimelockStages {
+                finality: 1_000,
+                withdrawal: 1_060,
+                public_withdrawal: 4_600,
+                cancellation: 8_200,
+                public_cancellation: 11_800,
+            }
+        );
+    }
+
+    #[test]
+    fn test_active_stage() {
+        let timelocks = Timelocks::pack([60, 3600, 7200, 10800]);
+        assert_eq!(timelocks.active_stage(1_059, 1_000), None);
+        assert_eq!(timelocks.active_stage(1_060, 1_000), Some(TimelockStage::Withdrawal));
+        assert_eq!(timelocks.active_stage(8_200, 1_000), Some(TimelockStage::Cancellation));
+        assert_eq!(timelocks.active_stage(20_000, 1_000), Some(TimelockStage::PublicCancellation));
+    }
+
+    #[test]
+    fn test_next_stage() {
+        let timelocks = Timelocks::pack([60, 3600, 7200, 10800]);
+        assert_eq!(timelocks.next_stage(1_000, 1_000), Some((TimelockStage::Withdrawal, 1_060)));
+        assert_eq!(timelocks.next_stage(1_060, 1_000), Some((TimelockStage::PublicWithdrawal, 4_600)));
+        assert_eq!(timelocks.next_stage(11_799, 1_000), Some((TimelockStage::PublicCancellation, 11_800)));
+        assert_eq!(timelocks.next_stage(11_800, 1_000), None);
+    }
+
+    #[test]
+    fn test_is_ordered() {
+        assert!(Timelocks::pack([60, 3600, 7200, 10800]).is_ordered());
+        assert!(Timelocks::pack([0, 0, 1, 1]).is_ordered());
+        assert!(!Timelocks(0).is_ordered());
+        assert!(!Timelocks::pack([60, 3600, 3600, 10800]).is_ordered());
+        assert!(!Timelocks::pack([3600, 60, 7200, 10800]).is_ordered());
+        assert!(!Timelocks::pack([60, 3600, 7200, 7100]).is_ordered());
+    }
+
+    #[test]
+    fn test_with_cancellation_at() {
+        let timelocks = Timelocks::pack([60, 3600, 7200, 10800]).with_cancellation_at(9_200, 1_000);
+        assert_eq!(timelocks, Timelocks::pack([60, 3600, 8200, 11800]));
+    }
+
+    #[test]
+    fn test_stage_bit_layout() {
+        // Cancellation lives in bits 64..96
+        let timelocks = Timelocks(5u128 << 64);
+        assert_eq!(timelocks.offset(TimelockStage::Withdrawal), 0);
+        assert_eq!(timelocks.offset(TimelockStage::Cancellation), 5);
+    }
+}
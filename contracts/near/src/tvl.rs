@@ -0,0 +1,61 @@
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::{FusionPlusNear, FusionPlusNearExt};
+
+/// Value locked in one asset and its ceiling
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Tvl {
+    #[schemars(with = "String")]
+    pub locked: U128,
+    /// `None` when the asset is uncapped
+    #[schemars(with = "Option<String>")]
+    pub cap: Option<U128>,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Cap the value locked in one asset (`None` for native NEAR), `None` removes the cap
+    /// Only new orders are checked; lowering the cap below the current TVL
+    /// blocks new orders until enough are settled
+    #[payable]
+    pub fn set_tvl_cap(&mut self, token: Option<AccountId>, cap: Option<U128>) {
+        self.assert_owner();
+        match cap {
+            Some(cap) => self.tvl_caps.insert(&token, &cap.0),
+            None => self.tvl_caps.remove(&token),
+        };
+    }
+
+    /// Value locked in `token` orders (native NEAR when omitted) and its cap
+    /// Native NEAR covers Fusion+ escrows and the maker funds of open HTLC orders
+    pub fn get_tvl(&self, token: Option<AccountId>) -> Tvl {
+        Tvl {
+            locked: U128(self.internal_tvl(&token)),
+            cap: self.tvl_caps.get(&token).map(U128),
+        }
+    }
+}
+
+impl FusionPlusNear {
+    fn internal_tvl(&self, token: &Option<AccountId>) -> u128 {
+        match token {
+            None => self.totals.locked + self.htlc_locked,
+            Some(token) => self.token_locked.get(token).unwrap_or(0),
+        }
+    }
+
+    /// Panic if locking `amount` more of `token` would exceed its cap
+    pub(crate) fn assert_tvl_cap(&self, token: &Option<AccountId>, amount: u128) {
+        assert!(self.within_tvl_cap(token, amount), "TVL cap exceeded");
+    }
+
+    pub(crate) fn within_tvl_cap(&self, token: &Option<AccountId>, amount: u128) -> bool {
+        self.tvl_caps
+            .get(token)
+            .is_none_or(|cap| self.internal_tvl(token) + amount <= cap)
+    }
+}
@@ -0,0 +1,311 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::UnorderedSet;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::events::{event_nonce, FusionEvent, FusionOrderStatusChangedEvent};
+use crate::hashlock::{normalize_hex32, HashlockExt};
+use crate::timelocks::{TimelockStage, TimelockStages, Timelocks};
+use crate::{FusionPlusNear, FusionPlusNearExt, FusionPlusOrder, OrderState, OrderStatus};
+
+/// Page size used when a paginated view is called without `limit`
+pub const DEFAULT_PAGE_LIMIT: u64 = 50;
+
+/// Where an order stands in its timelock schedule at `now` (seconds)
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StageCountdown {
+    pub now: u64,
+    /// `None` before the withdrawal stage opens
+    pub stage: Option<TimelockStage>,
+    /// `None` once public cancellation is open
+    pub next_stage: Option<TimelockStage>,
+    pub next_stage_at: Option<u64>,
+    /// Seconds until `next_stage` opens, i.e. left in the current stage
+    pub seconds_remaining: Option<u64>,
+}
+
+/// One page of orders plus the total number of matching orders
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OrdersPage {
+    pub orders: Vec<FusionPlusOrder>,
+    pub total: u64,
+}
+
+/// An order at its creation index, `order` is `None` once cleanup pruned it
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExportedOrder {
+    pub creation_index: u64,
+    pub order_hash: String,
+    pub order: Option<FusionPlusOrder>,
+}
+
+/// One page of `export_orders`, resume from `next_index`
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OrderExport {
+    pub orders: Vec<ExportedOrder>,
+    pub next_index: u64,
+    /// Number of orders ever created
+    pub total: u64,
+}
+
+/// One page of authorized resolvers plus the total number of resolvers
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ResolversPage {
+    #[schemars(with = "Vec<String>")]
+    pub resolvers: Vec<AccountId>,
+    pub total: u64,
+}
+
+/// One status transition of an order
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StatusChange {
+    pub status: OrderStatus,
+    /// Block timestamp (seconds) of the transition
+    pub timestamp: u64,
+    /// Account whose call made the transition
+    #[schemars(with = "String")]
+    pub actor: AccountId,
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Enumerate stored orders for indexer sync
+    pub fn get_orders(&self, from_index: Option<u64>, limit: Option<u64>) -> OrdersPage {
+        OrdersPage {
+            orders: self
+                .order_hashes
+                .iter()
+                .skip(from_index.unwrap_or(0) as usize)
+                .take(limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize)
+                .filter_map(|order_hash| self.internal_get_order(&order_hash))
+                .collect(),
+            total: self.order_hashes.len(),
+        }
+    }
+
+    /// Every order ever created, by creation index, for an indexer's initial
+    /// sync. Indexes never move, so paging from `next_index` has no gaps or
+    /// repeats; pruned orders are listed with their hash only
+    pub fn export_orders(&self, from_index: Option<u64>, limit: Option<u64>) -> OrderExport {
+        let total = self.order_log.len();
+        let from_index = from_index.unwrap_or(0).min(total);
+        let next_index = from_index.saturating_add(limit.unwrap_or(DEFAULT_PAGE_LIMIT)).min(total);
+        OrderExport {
+            orders: (from_index..next_index)
+                .map(|creation_index| {
+                    let order_hash = self.order_log.get(creation_index).expect("Order log entry missing");
+                    ExportedOrder {
+                        creation_index,
+                        order: self.internal_get_order(&order_hash),
+                        order_hash,
+                    }
+                })
+                .collect(),
+            next_index,
+            total,
+        }
+    }
+
+    /// Page through the authorized resolvers, e.g. to audit the allowlist
+    /// Removing a resolver moves the last one into its slot, so pages are
+    /// only stable while the allowlist is unchanged
+    pub fn get_resolvers(&self, from_index: Option<u64>, limit: Option<u64>) -> ResolversPage {
+        let resolvers = self.authorized_resolvers.keys_as_vector();
+        ResolversPage {
+            resolvers: resolvers
+                .iter()
+                .skip(from_index.unwrap_or(0) as usize)
+                .take(limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize)
+                .collect(),
+            total: resolvers.len(),
+        }
+    }
+
+    /// Look up several orders in one call, `None` for unknown or malformed hashes
+    pub fn batch_get_orders(&self, order_hashes: Vec<String>) -> Vec<Option<FusionPlusOrder>> {
+        order_hashes
+            .into_iter()
+            .map(|order_hash| self.get_order(order_hash))
+            .collect()
+    }
+
+    /// Orders addressed to `account_id` as maker
+    pub fn get_orders_by_maker(
+        &self,
+        account_id: AccountId,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> OrdersPage {
+        self.internal_orders_page(self.orders_by_maker.get(&account_id), from_index, limit)
+    }
+
+    /// Orders currently in `status`, e.g. `Matched` orders awaiting claim or refund
+    pub fn get_orders_by_status(
+        &self,
+        status: OrderStatus,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> OrdersPage {
+        self.internal_orders_page(self.orders_by_status.get(&status), from_index, limit)
+    }
+
+    /// Whether `preimage` unlocks the order's hashlock, so relayers can check
+    /// a secret before submitting a claim. `false` for unknown orders,
+    /// malformed input and multi-part orders (their hashlock is a Merkle root)
+    pub fn check_preimage(&self, order_hash: String, preimage: String) -> bool {
+        let Some(order_hash) = normalize_hex32(&order_hash).map(|digits| format!("0x{}", digits)) else {
+            return false;
+        };
+        let (Some(state), Some(metadata)) = (self.orders.get(&order_hash), self.order_metadata.get(&order_hash))
+        else {
+            return false;
+        };
+        state.parts == 0
+            && normalize_hex32(&preimage)
+                .is_some_and(|preimage| metadata.hash_algorithm.hashlock_for(&preimage) == hex::encode(metadata.hashlock))
+    }
+
+    /// Status transitions of an order, oldest first, starting with its creation
+    /// Empty for unknown orders and orders created before history was kept
+    pub fn get_order_history(&self, order_hash: String) -> Vec<StatusChange> {
+        normalize_hex32(&order_hash)
+            .and_then(|digits| self.order_history.get(&format!("0x{}", digits)))
+            .unwrap_or_default()
+    }
+
+    /// Timelock stage an order is in now, `None` for unknown orders and
+    /// before its withdrawal stage opens
+    pub fn get_active_stage(&self, order_hash: String) -> Option<TimelockStage> {
+        let order_hash = normalize_hex32(&order_hash).map(|digits| format!("0x{}", digits))?;
+        let state = self.orders.get(&order_hash)?;
+        Timelocks(state.timelocks.0).active_stage(Self::now_seconds(), state.created_at)
+    }
+
+    /// Current timelock stage of an open order and the time left until the
+    /// next one opens, for countdowns and keepers scheduling claims or
+    /// cancellations. `None` for unknown, malformed or settled orders
+    pub fn get_stage_countdown(&self, order_hash: String) -> Option<StageCountdown> {
+        let order_hash = normalize_hex32(&order_hash).map(|digits| format!("0x{}", digits))?;
+        let state = self.orders.get(&order_hash)?;
+        if matches!(state.status, OrderStatus::Claimed | OrderStatus::Refunded) {
+            return None;
+        }
+        let now = Self::now_seconds();
+        let timelocks = Timelocks(state.timelocks.0);
+        let next = timelocks.next_stage(now, state.created_at);
+        Some(StageCountdown {
+            now,
+            stage: timelocks.active_stage(now, state.created_at),
+            next_stage: next.map(|(stage, _)| stage),
+            next_stage_at: next.map(|(_, start)| start),
+            seconds_remaining: next.map(|(_, start)| start - now),
+        })
+    }
+
+    /// Absolute start (seconds) of each of an order's timelock stages,
+    /// including any extension, `None` for unknown or malformed hashes
+    pub fn get_timelock_stages(&self, order_hash: String) -> Option<TimelockStages> {
+        let order_hash = normalize_hex32(&order_hash).map(|digits| format!("0x{}", digits))?;
+        let state = self.orders.get(&order_hash)?;
+        Some(Timelocks(state.timelocks.0).stages(state.created_at))
+    }
+
+    /// Nonce of the last emitted event, 0 before the first one
+    /// Consumers streaming events resync from here after a gap in nonces
+    pub fn get_event_nonce(&self) -> u64 {
+        event_nonce()
+    }
+}
+
+impl FusionPlusNear {
+    /// Page through the orders of an index entry
+    fn internal_orders_page(
+        &self,
+        order_hashes: Option<UnorderedSet<String>>,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> OrdersPage {
+        let Some(order_hashes) = order_hashes else {
+            return OrdersPage {
+                orders: Vec::new(),
+                total: 0,
+            };
+        };
+        OrdersPage {
+            orders: order_hashes
+                .iter()
+                .skip(from_index.unwrap_or(0) as usize)
+                .take(limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize)
+                .filter_map(|order_hash| self.internal_get_order(&order_hash))
+                .collect(),
+            total: order_hashes.len(),
+        }
+    }
+
+    /// Add a newly stored order to its maker's index
+    pub(crate) fn internal_index_by_maker(&mut self, order_hash: &String, maker: &AccountId) {
+        let mut order_hashes = self.orders_by_maker.get(maker).unwrap_or_else(|| {
+            let prefix = [b"m".as_slice(), &env::sha256(maker.as_bytes())].concat();
+            UnorderedSet::new(prefix)
+        });
+        order_hashes.insert(order_hash);
+        self.orders_by_maker.insert(maker, &order_hashes);
+    }
+
+    /// Add an order to the index of `status`
+    pub(crate) fn internal_index_by_status(&mut self, order_hash: &String, status: &OrderStatus) {
+        let mut order_hashes = self.orders_by_status.get(status).unwrap_or_else(|| {
+            let prefix = borsh::to_vec(status).unwrap();
+            UnorderedSet::new([b"t".as_slice(), &prefix].concat())
+        });
+        order_hashes.insert(order_hash);
+        self.orders_by_status.insert(status, &order_hashes);
+    }
+
+    /// Append a transition to `status` by the current caller to the order's history
+    /// History storage is charged to the order's resolver with the order itself
+    pub(crate) fn internal_record_history(&mut self, order_hash: &String, status: &OrderStatus) {
+        let mut history = self.order_history.get(order_hash).unwrap_or_default();
+        history.push(StatusChange {
+            status: status.clone(),
+            timestamp: Self::now_seconds(),
+            actor: env::predecessor_account_id(),
+        });
+        self.order_history.insert(order_hash, &history);
+    }
+
+    /// Move an order to `status`, keeping the status and expiry indexes in sync and
+    /// emitting the transition. The caller still has to store the updated order
+    pub(crate) fn internal_set_status(
+        &mut self,
+        order_hash: &String,
+        order: &mut OrderState,
+        status: OrderStatus,
+    ) {
+        assert!(order.status.can_transition_to(&status), "Invalid status transition");
+        if let Some(mut order_hashes) = self.orders_by_status.get(&order.status) {
+            order_hashes.remove(order_hash);
+            self.orders_by_status.insert(&order.status, &order_hashes);
+        }
+        self.internal_unindex_expiry(order_hash, order);
+        let initial_storage_usage = env::storage_usage();
+        self.internal_record_history(order_hash, &status);
+        self.internal_charge_storage_owed(&order.resolver, initial_storage_usage);
+        let old_status = std::mem::replace(&mut order.status, status);
+        FusionEvent::FusionOrderStatusChanged(vec![FusionOrderStatusChangedEvent {
+            order_hash: order_hash.clone(),
+            old_status,
+            new_status: order.status.clone(),
+        }])
+        .emit();
+        self.internal_index_by_status(order_hash, &order.status);
+        self.internal_index_expiry(order_hash, order);
+    }
+}
@@ -0,0 +1,121 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, ext_contract, near_bindgen, AccountId, Gas, NearToken, Promise, PromiseError};
+use schemars::JsonSchema;
+
+use crate::ft::GAS_FOR_FT_TRANSFER;
+use crate::{FusionPlusNear, FusionPlusNearExt, OrderState};
+
+/// Gas attached to `near_deposit` / `near_withdraw` on the wNEAR contract
+pub const GAS_FOR_WNEAR: Gas = Gas::from_tgas(10);
+/// Gas reserved for the unwrap callback, which forwards the NEAR
+pub const GAS_FOR_ON_UNWRAPPED: Gas = Gas::from_tgas(10);
+
+/// Form in which an account wants NEAR-denominated payouts
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, JsonSchema, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum NearAsset {
+    /// Native NEAR
+    Native,
+    /// Wrapped NEAR (NEP-141)
+    Wrapped,
+}
+
+/// wNEAR (`wrap.near`) interface
+#[ext_contract(ext_wnear)]
+pub trait WrappedNear {
+    fn near_withdraw(&mut self, amount: U128);
+}
+
+#[near_bindgen]
+impl FusionPlusNear {
+    /// Set the wNEAR contract (e.g. `wrap.near`, `wrap.testnet`), `None` disables wrapping
+    #[payable]
+    pub fn set_wnear_contract(&mut self, wnear_contract: Option<AccountId>) {
+        self.assert_owner();
+        self.wnear_contract = wnear_contract;
+    }
+
+    pub fn get_wnear_contract(&self) -> Option<AccountId> {
+        self.wnear_contract.clone()
+    }
+
+    /// Receive NEAR and wNEAR payouts as `asset`, `None` keeps the escrowed asset
+    /// Wrapped payouts require the caller to be registered on the wNEAR contract
+    pub fn set_payout_preference(&mut self, asset: Option<NearAsset>) {
+        let account_id = env::predecessor_account_id();
+        match asset {
+            Some(asset) => self.payout_preferences.insert(&account_id, &asset),
+            None => self.payout_preferences.remove(&account_id),
+        };
+    }
+
+    pub fn get_payout_preference(&self, account_id: AccountId) -> Option<NearAsset> {
+        self.payout_preferences.get(&account_id)
+    }
+
+    /// Callback after unwrapping a payout, forwards the NEAR to `receiver`
+    /// Panics if the unwrap failed so the payout is recorded for retry
+    #[private]
+    pub fn on_unwrapped(
+        &mut self,
+        receiver: AccountId,
+        amount: U128,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> Promise {
+        assert!(result.is_ok(), "wNEAR unwrap failed");
+        Promise::new(receiver).transfer(NearToken::from_yoctonear(amount.0))
+    }
+}
+
+impl FusionPlusNear {
+    /// Transfer converting between NEAR and wNEAR when `receiver` asked for
+    /// the other form, `None` when the escrowed asset is paid as-is
+    pub(crate) fn internal_wnear_transfer(
+        &self,
+        order_hash: &str,
+        order: &OrderState,
+        receiver: &AccountId,
+        amount: u128,
+    ) -> Option<Promise> {
+        let wnear = self.wnear_contract.clone()?;
+        let preference = self.payout_preferences.get(receiver)?;
+        match (&order.token, preference) {
+            // Wrap and transfer in one batch so a failed transfer also reverts the deposit
+            (None, NearAsset::Wrapped) => Some(
+                Promise::new(wnear)
+                    .function_call(
+                        "near_deposit".to_string(),
+                        Vec::new(),
+                        NearToken::from_yoctonear(amount),
+                        GAS_FOR_WNEAR,
+                    )
+                    .function_call(
+                        "ft_transfer".to_string(),
+                        serde_json::json!({
+                            "receiver_id": receiver,
+                            "amount": U128(amount),
+                            "memo": order_hash,
+                        })
+                        .to_string()
+                        .into_bytes(),
+                        NearToken::from_yoctonear(1),
+                        GAS_FOR_FT_TRANSFER,
+                    ),
+            ),
+            (Some(token), NearAsset::Native) if *token == wnear => Some(
+                ext_wnear::ext(wnear)
+                    .with_attached_deposit(NearToken::from_yoctonear(1))
+                    .with_static_gas(GAS_FOR_WNEAR)
+                    .near_withdraw(U128(amount))
+                    .then(
+                        Self::ext(env::current_account_id())
+                            .with_static_gas(GAS_FOR_ON_UNWRAPPED)
+                            .on_unwrapped(receiver.clone(), U128(amount)),
+                    ),
+            ),
+            _ => None,
+        }
+    }
+}
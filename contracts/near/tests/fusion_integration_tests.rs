@@ -1,10 +1,14 @@
 use anyhow::Result;
 use near_workspaces::types::NearToken;
+use near_workspaces::{Account, Contract};
 use serde_json::json;
 use sha2::{Digest, Sha256};
 
-/// Integration tests for 1inch Fusion+ NEAR extension
-/// Tests the contract's integration with 1inch Fusion+ protocol
+// Integration tests for 1inch Fusion+ NEAR extension
+// Tests the contract's integration with 1inch Fusion+ protocol
+
+// Packed timelocks [0, 60, 3600, 7200]: claimable at once, cancellable after an hour
+const TIMELOCKS: &str = "570442770169111509339128526274560";
 
 // Helper function to get the compiled WASM
 async fn get_wasm() -> Result<Vec<u8>> {
@@ -16,12 +20,36 @@ async fn get_wasm() -> Result<Vec<u8>> {
     }
 }
 
+// Helper function to cover a resolver's order storage (NEP-145)
+async fn register_storage(contract: &Contract, account: &Account) -> Result<()> {
+    let outcome = account
+        .call(contract.id(), "storage_deposit")
+        .args_json(json!({}))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await?;
+    assert!(outcome.is_success());
+    Ok(())
+}
+
+// Helper function to post the minimum resolver bond
+async fn bond_resolver(contract: &Contract, account: &Account) -> Result<()> {
+    let outcome = account
+        .call(contract.id(), "bond_resolver")
+        .args_json(json!({}))
+        .deposit(NearToken::from_near(5))
+        .transact()
+        .await?;
+    assert!(outcome.is_success());
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_fusion_contract_deployment() -> Result<()> {
     let worker = near_workspaces::sandbox().await?;
     let wasm = &get_wasm().await?;
 
-    let contract = worker.dev_deploy(&wasm).await?;
+    let contract = worker.dev_deploy(wasm).await?;
 
     // Initialize with 5% min safety deposit (500 bps)
     let outcome = contract
@@ -53,7 +81,7 @@ async fn test_1inch_resolver_management() -> Result<()> {
     let worker = near_workspaces::sandbox().await?;
     let wasm = &get_wasm().await?;
 
-    let contract = worker.dev_deploy(&wasm).await?;
+    let contract = worker.dev_deploy(wasm).await?;
     let resolver_account = worker.dev_create_account().await?;
 
     // Initialize contract
@@ -72,6 +100,7 @@ async fn test_1inch_resolver_management() -> Result<()> {
         .args_json(json!({
             "resolver": resolver_account.id()
         }))
+        .deposit(NearToken::from_yoctonear(1))
         .transact()
         .await?;
     
@@ -96,7 +125,7 @@ async fn test_execute_fusion_order() -> Result<()> {
     let worker = near_workspaces::sandbox().await?;
     let wasm = &get_wasm().await?;
 
-    let contract = worker.dev_deploy(&wasm).await?;
+    let contract = worker.dev_deploy(wasm).await?;
     let resolver_account = worker.dev_create_account().await?;
     let user_account = worker.dev_create_account().await?;
 
@@ -116,9 +145,12 @@ async fn test_execute_fusion_order() -> Result<()> {
         .args_json(json!({
             "resolver": resolver_account.id()
         }))
+        .deposit(NearToken::from_yoctonear(1))
         .transact()
         .await?;
     assert!(outcome.is_success());
+    register_storage(&contract, &resolver_account).await?;
+    bond_resolver(&contract, &resolver_account).await?;
 
     // Execute Fusion+ order
     let order_hash = "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
@@ -137,7 +169,7 @@ async fn test_execute_fusion_order() -> Result<()> {
             "resolver": resolver_account.id(),
             "amount": amount.as_yoctonear().to_string(),
             "resolver_fee": resolver_fee.as_yoctonear().to_string(),
-            "timelocks": "0", // Simplified for testing
+            "timelocks": TIMELOCKS,
             "source_chain_id": 11155111 // Ethereum Sepolia
         }))
         .deposit(total_deposit)
@@ -174,7 +206,7 @@ async fn test_claim_fusion_order_with_preimage() -> Result<()> {
     let worker = near_workspaces::sandbox().await?;
     let wasm = &get_wasm().await?;
 
-    let contract = worker.dev_deploy(&wasm).await?;
+    let contract = worker.dev_deploy(wasm).await?;
     let resolver_account = worker.dev_create_account().await?;
     let user_account = worker.dev_create_account().await?;
 
@@ -193,9 +225,12 @@ async fn test_claim_fusion_order_with_preimage() -> Result<()> {
         .args_json(json!({
             "resolver": resolver_account.id()
         }))
+        .deposit(NearToken::from_yoctonear(1))
         .transact()
         .await?;
     assert!(outcome.is_success());
+    register_storage(&contract, &resolver_account).await?;
+    bond_resolver(&contract, &resolver_account).await?;
 
     // Generate real hashlock and preimage
     let preimage = "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
@@ -206,7 +241,7 @@ async fn test_claim_fusion_order_with_preimage() -> Result<()> {
     let hashlock = hex::encode(hash_result);
 
     // Execute order
-    let order_hash = "0xfusion1234567890";
+    let order_hash = "0xf0510000000000000000000000000000000000000000000000000000001234ab";
     let amount = NearToken::from_near(2);
     let resolver_fee = NearToken::from_millinear(100);
     let safety_deposit = NearToken::from_millinear(100);
@@ -221,7 +256,7 @@ async fn test_claim_fusion_order_with_preimage() -> Result<()> {
             "resolver": resolver_account.id(),
             "amount": amount.as_yoctonear().to_string(),
             "resolver_fee": resolver_fee.as_yoctonear().to_string(),
-            "timelocks": "0",
+            "timelocks": TIMELOCKS,
             "source_chain_id": 11155111
         }))
         .deposit(total_deposit)
@@ -233,7 +268,7 @@ async fn test_claim_fusion_order_with_preimage() -> Result<()> {
     let user_balance_before = user_account.view_account().await?.balance;
     let resolver_balance_before = resolver_account.view_account().await?.balance;
 
-    // Claim with preimage
+    // Claim with preimage (pays maker and resolver in the same call)
     let outcome = resolver_account
         .call(contract.id(), "claim_fusion_order")
         .args_json(json!({
@@ -248,28 +283,6 @@ async fn test_claim_fusion_order_with_preimage() -> Result<()> {
         panic!("Claim fusion order failed");
     }
 
-    // Transfer to maker
-    let transfer_outcome = resolver_account
-        .call(contract.id(), "transfer_to_maker")
-        .args_json(json!({
-            "order_hash": order_hash
-        }))
-        .transact()
-        .await?;
-
-    assert!(transfer_outcome.is_success());
-
-    // Claim resolver payment
-    let payment_outcome = resolver_account
-        .call(contract.id(), "claim_resolver_payment")
-        .args_json(json!({
-            "order_hash": order_hash
-        }))
-        .transact()
-        .await?;
-
-    assert!(payment_outcome.is_success());
-
     // Verify order was claimed
     let order: serde_json::Value = contract
         .view("get_order")
@@ -294,12 +307,107 @@ async fn test_claim_fusion_order_with_preimage() -> Result<()> {
     Ok(())
 }
 
+// Helper function to read an order's raw metadata record, stored under
+// the `c` prefix and the Borsh-encoded order hash
+async fn order_metadata_record(contract: &Contract, order_hash: &str) -> Result<Vec<u8>> {
+    let mut key = b"c".to_vec();
+    key.extend((order_hash.len() as u32).to_le_bytes());
+    key.extend(order_hash.as_bytes());
+    let state = contract.view_state().prefix(&key).await?;
+    Ok(state.get(&key).cloned().expect("Order metadata not stored"))
+}
+
+#[tokio::test]
+async fn test_order_storage_and_gas() -> Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let wasm = &get_wasm().await?;
+
+    let contract = worker.dev_deploy(wasm).await?;
+    let resolver_account = worker.dev_create_account().await?;
+    let user_account = worker.dev_create_account().await?;
+
+    let outcome = contract
+        .call("new")
+        .args_json(json!({
+            "min_safety_deposit_bps": 500
+        }))
+        .transact()
+        .await?;
+    assert!(outcome.is_success());
+    let outcome = contract
+        .call("add_resolver")
+        .args_json(json!({
+            "resolver": resolver_account.id()
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?;
+    assert!(outcome.is_success());
+    register_storage(&contract, &resolver_account).await?;
+    bond_resolver(&contract, &resolver_account).await?;
+
+    let preimage = "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
+    let hashlock = hex::encode(Sha256::digest(hex::decode(preimage)?));
+    let order_hash = "0xf0520000000000000000000000000000000000000000000000000000001234ab";
+
+    // Hashlock and preimage are stored as raw 32 bytes, 36 bytes less each than their hex strings
+    let storage_before = contract.view_account().await?.storage_usage;
+    let execute = resolver_account
+        .call(contract.id(), "execute_fusion_order")
+        .args_json(json!({
+            "order_hash": order_hash,
+            "hashlock": hashlock,
+            "maker": user_account.id(),
+            "resolver": resolver_account.id(),
+            "amount": NearToken::from_near(1).as_yoctonear().to_string(),
+            "resolver_fee": "0",
+            "timelocks": TIMELOCKS,
+            "source_chain_id": 11155111
+        }))
+        .deposit(NearToken::from_near(2))
+        .transact()
+        .await?;
+    assert!(execute.is_success());
+    let storage_after_execute = contract.view_account().await?.storage_usage;
+    let record_after_execute = order_metadata_record(&contract, order_hash).await?;
+
+    let claim = resolver_account
+        .call(contract.id(), "claim_fusion_order")
+        .args_json(json!({
+            "order_hash": order_hash,
+            "preimage": preimage
+        }))
+        .transact()
+        .await?;
+    assert!(claim.is_success());
+    let storage_after_claim = contract.view_account().await?.storage_usage;
+    let record_after_claim = order_metadata_record(&contract, order_hash).await?;
+
+    let order_bytes = storage_after_execute - storage_before;
+    println!("📏 Order storage: {} bytes, +{} bytes on claim", order_bytes, storage_after_claim - storage_after_execute);
+    println!(
+        "⛽ Gas burnt: execute {} Tgas, claim {} Tgas",
+        execute.outcome().gas_burnt.as_tgas(),
+        claim.outcome().gas_burnt.as_tgas()
+    );
+
+    // The hex layout stored the hashlock as a 4-byte length and 64 digits
+    assert_eq!(record_after_execute[..32], hex::decode(&hashlock)?[..]);
+    // and grew by 68 bytes when the preimage was revealed, the raw one by 32
+    let preimage_bytes = hex::decode(preimage)?;
+    assert_eq!(record_after_claim.len() - record_after_execute.len(), 32);
+    assert!(record_after_claim.windows(32).any(|window| window == &preimage_bytes[..]));
+    assert!(!record_after_claim.windows(64).any(|window| window == preimage.as_bytes()));
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_unauthorized_resolver_fails() -> Result<()> {
     let worker = near_workspaces::sandbox().await?;
     let wasm = &get_wasm().await?;
 
-    let contract = worker.dev_deploy(&wasm).await?;
+    let contract = worker.dev_deploy(wasm).await?;
     let unauthorized_account = worker.dev_create_account().await?;
     let user_account = worker.dev_create_account().await?;
 
@@ -317,13 +425,13 @@ async fn test_unauthorized_resolver_fails() -> Result<()> {
     let outcome = unauthorized_account
         .call(contract.id(), "execute_fusion_order")
         .args_json(json!({
-            "order_hash": "0xunauthorized",
+            "order_hash": format!("0x{}", "cd".repeat(32)),
             "hashlock": "a".repeat(64),
             "maker": user_account.id(),
             "resolver": unauthorized_account.id(),
             "amount": NearToken::from_near(1).as_yoctonear().to_string(),
             "resolver_fee": NearToken::from_millinear(100).as_yoctonear().to_string(),
-            "timelocks": "0",
+            "timelocks": TIMELOCKS,
             "source_chain_id": 11155111
         }))
         .deposit(NearToken::from_near(2))
@@ -345,7 +453,7 @@ async fn test_full_fusion_plus_integration() -> Result<()> {
     let worker = near_workspaces::sandbox().await?;
     let wasm = &get_wasm().await?;
 
-    let contract = worker.dev_deploy(&wasm).await?;
+    let contract = worker.dev_deploy(wasm).await?;
     let resolver_account = worker.dev_create_account().await?;
     let user_account = worker.dev_create_account().await?;
 
@@ -365,9 +473,12 @@ async fn test_full_fusion_plus_integration() -> Result<()> {
         .args_json(json!({
             "resolver": resolver_account.id()
         }))
+        .deposit(NearToken::from_yoctonear(1))
         .transact()
         .await?;
     assert!(outcome.is_success());
+    register_storage(&contract, &resolver_account).await?;
+    bond_resolver(&contract, &resolver_account).await?;
 
     println!("🔄 Starting 1inch Fusion+ NEAR integration test...");
 
@@ -379,7 +490,7 @@ async fn test_full_fusion_plus_integration() -> Result<()> {
     let hash_result = hasher.finalize();
     let hashlock = hex::encode(hash_result);
 
-    let order_hash = format!("0xfusion{}", hex::encode(&hash_result[0..16]));
+    let order_hash = format!("0x{}", hex::encode(Sha256::digest(hashlock.as_bytes())));
     let swap_amount = NearToken::from_near(5);
     let resolver_fee = NearToken::from_millinear(250);
     let safety_deposit = NearToken::from_millinear(250); // 5%
@@ -395,7 +506,7 @@ async fn test_full_fusion_plus_integration() -> Result<()> {
             "resolver": resolver_account.id(),
             "amount": swap_amount.as_yoctonear().to_string(),
             "resolver_fee": resolver_fee.as_yoctonear().to_string(),
-            "timelocks": "0", // Would be properly packed in production
+            "timelocks": TIMELOCKS,
             "source_chain_id": 11155111 // Ethereum Sepolia
         }))
         .deposit(total_deposit)
@@ -417,30 +528,7 @@ async fn test_full_fusion_plus_integration() -> Result<()> {
         .await?;
 
     assert!(claim_outcome.is_success());
-
-    // Step 3: Transfer to maker
-    println!("💸 Transferring tokens to maker...");
-    let transfer_outcome = resolver_account
-        .call(contract.id(), "transfer_to_maker")
-        .args_json(json!({
-            "order_hash": order_hash
-        }))
-        .transact()
-        .await?;
-
-    assert!(transfer_outcome.is_success());
-
-    // Step 4: Resolver claims their payment
-    println!("💰 Resolver claiming payment...");
-    let payment_outcome = resolver_account
-        .call(contract.id(), "claim_resolver_payment")
-        .args_json(json!({
-            "order_hash": order_hash
-        }))
-        .transact()
-        .await?;
-
-    assert!(payment_outcome.is_success());
+    println!("💸 Maker paid and resolver payment returned in the claim call");
     
     // Verify final state
     let order: serde_json::Value = contract
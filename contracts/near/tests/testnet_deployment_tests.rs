@@ -1,96 +1,39 @@
+use std::sync::{Arc, OnceLock};
+
 use anyhow::Result;
+use near_rpc::{HttpTransport, NearClient, NearRateLimitConfig, RateLimits};
+use near_sdk::serde::de::DeserializeOwned;
 use serde_json::{json, Value};
-use base64::{Engine as _, engine::general_purpose};
 
-/// Live testnet deployment tests for 1inch Fusion+ NEAR extension
-/// Uses direct RPC calls to avoid near-workspaces testnet connectivity issues
-/// 
-/// Contract: fusion-plus.demo.cuteharbor3573.testnet
-/// Network: NEAR Testnet  
-/// Deployment Date: July 23, 2025
+// Live testnet deployment tests for 1inch Fusion+ NEAR extension
+// Uses direct RPC calls to avoid near-workspaces testnet connectivity issues
+// 
+// Contract: fusion-plus.demo.cuteharbor3573.testnet
+// Network: NEAR Testnet  
+// Deployment Date: July 23, 2025
 
 const TESTNET_CONTRACT_ID: &str = "fusion-plus.demo.cuteharbor3573.testnet";
 const TESTNET_OWNER_ID: &str = "demo.cuteharbor3573.testnet";
 const NEAR_TESTNET_RPC: &str = "https://rpc.testnet.near.org";
 
-/// Helper to make RPC view calls to our deployed contract with rate limiting
-/// NEAR testnet RPC limit: 60 calls per minute (1 call per second)
-async fn rpc_view_call(method_name: &str, args: Value) -> Result<Value> {
-    // Wait longer before each call due to previous rate limit hits
-    tokio::time::sleep(std::time::Duration::from_millis(15000)).await;
-    
-    let client = reqwest::Client::new();
-    
-    let args_base64 = if args.is_null() {
-        String::new()
-    } else {
-        general_purpose::STANDARD.encode(args.to_string())
-    };
-
-    let request_body = json!({
-        "jsonrpc": "2.0",
-        "id": "dontcare",
-        "method": "query",
-        "params": {
-            "request_type": "call_function",
-            "finality": "final",
-            "account_id": TESTNET_CONTRACT_ID,
-            "method_name": method_name,
-            "args_base64": args_base64
-        }
-    });
-
-    // Retry logic for rate limiting - minimal retries to avoid overwhelming RPC
-    let mut retries = 1;
-    loop {
-        let response = client
-            .post(NEAR_TESTNET_RPC)
-            .json(&request_body)
-            .send()
-            .await?;
-
-        // Handle rate limiting - NEAR allows 60 calls/min (1 per second)
-        if response.status() == 429 {
-            if retries > 0 {
-                let delay = std::time::Duration::from_millis(10000); // Wait 10s before retry
-                println!("   Rate limited, waiting {}s before retry...", delay.as_secs());
-                tokio::time::sleep(delay).await;
-                retries -= 1;
-                continue;
-            } else {
-                anyhow::bail!("RPC request failed with status: {} (rate limited after retries)", response.status());
-            }
-        }
-
-        if !response.status().is_success() {
-            anyhow::bail!("RPC request failed with status: {}", response.status());
-        }
-
-        let response_json: Value = response.json().await?;
-        
-        if let Some(error) = response_json.get("error") {
-            anyhow::bail!("RPC error: {}", error);
-        }
-
-        // No additional delay needed since we already wait before each call
-        
-        return Ok(response_json);
-    }
+/// Rate limits shared by every test: together they stay under the testnet
+/// RPC's 60 calls per minute, and a view already answered isn't asked again
+fn limits() -> Arc<RateLimits> {
+    static LIMITS: OnceLock<Arc<RateLimits>> = OnceLock::new();
+    LIMITS
+        .get_or_init(|| {
+            Arc::new(RateLimits::new(&NearRateLimitConfig {
+                view_cache_ms: 600_000,
+                ..NearRateLimitConfig::default()
+            }))
+        })
+        .clone()
 }
 
-/// Helper to parse RPC result as string
-fn parse_rpc_result_as_string(response: &Value) -> Result<String> {
-    if let Some(result) = response.get("result") {
-        if let Some(result_data) = result.get("result") {
-            if let Some(result_array) = result_data.as_array() {
-                let result_bytes: Vec<u8> = result_array.iter()
-                    .filter_map(|v| v.as_u64().map(|n| n as u8))
-                    .collect();
-                return Ok(String::from_utf8(result_bytes)?);
-            }
-        }
-    }
-    anyhow::bail!("Could not parse RPC result")
+/// Helper to make view calls to our deployed contract
+async fn rpc_view_call<T: DeserializeOwned>(method_name: &str, args: Value) -> Result<T> {
+    let client = NearClient::with_limits(HttpTransport::new(NEAR_TESTNET_RPC), limits());
+    Ok(client.view(TESTNET_CONTRACT_ID, method_name, args).await?)
 }
 
 #[tokio::test]
@@ -98,17 +41,13 @@ async fn test_live_contract_initialization() -> Result<()> {
     println!("🔧 Testing live contract initialization...");
 
     // Test get_owner function
-    let response = rpc_view_call("get_owner", json!({})).await?;
-    let owner = parse_rpc_result_as_string(&response)?;
-    let owner_clean = owner.trim_matches('"');
+    let owner: String = rpc_view_call("get_owner", json!({})).await?;
     
-    assert_eq!(owner_clean, TESTNET_OWNER_ID, "Contract owner should match expected account");
-    println!("✅ Owner verification: {}", owner_clean);
+    assert_eq!(owner, TESTNET_OWNER_ID, "Contract owner should match expected account");
+    println!("✅ Owner verification: {}", owner);
 
     // Test get_min_safety_deposit_bps function
-    let response = rpc_view_call("get_min_safety_deposit_bps", json!({})).await?;
-    let min_deposit_str = parse_rpc_result_as_string(&response)?;
-    let min_deposit: u16 = min_deposit_str.parse()?;
+    let min_deposit: u16 = rpc_view_call("get_min_safety_deposit_bps", json!({})).await?;
     
     assert_eq!(min_deposit, 500, "Min safety deposit should be 500 bps (5%)");
     println!("✅ Safety deposit verification: {} bps", min_deposit);
@@ -122,21 +61,17 @@ async fn test_live_resolver_authorization() -> Result<()> {
     println!("🔐 Testing live resolver authorization...");
 
     // Test resolver authorization for the owner
-    let response = rpc_view_call("is_authorized_resolver", json!({
+    let is_authorized: bool = rpc_view_call("is_authorized_resolver", json!({
         "resolver": TESTNET_OWNER_ID
     })).await?;
-    let is_authorized_str = parse_rpc_result_as_string(&response)?;
-    let is_authorized: bool = is_authorized_str.parse()?;
     
     assert!(is_authorized, "Owner should be an authorized resolver");
     println!("✅ Owner resolver authorization: {}", is_authorized);
 
     // Test unauthorized resolver (should return false)
-    let response = rpc_view_call("is_authorized_resolver", json!({
+    let is_unauthorized: bool = rpc_view_call("is_authorized_resolver", json!({
         "resolver": "unauthorized.testnet"
     })).await?;
-    let is_unauthorized_str = parse_rpc_result_as_string(&response)?;
-    let is_unauthorized: bool = is_unauthorized_str.parse()?;
     
     assert!(!is_unauthorized, "Random account should not be authorized resolver");
     println!("✅ Unauthorized resolver check: {}", is_unauthorized);
@@ -152,7 +87,7 @@ async fn test_live_fusion_order_validation() -> Result<()> {
     // Test order retrieval for non-existent order (should handle gracefully)
     let test_order_hash = "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
     
-    let response = rpc_view_call("get_order", json!({
+    let response = rpc_view_call::<Value>("get_order", json!({
         "order_hash": test_order_hash
     })).await;
 
@@ -174,8 +109,7 @@ async fn test_live_contract_state_consistency() -> Result<()> {
     println!("🔍 Testing live contract state consistency...");
 
     // Test basic state validation with single call to avoid rate limiting
-    let owner_response = rpc_view_call("get_owner", json!({})).await?;
-    let owner = parse_rpc_result_as_string(&owner_response)?.trim_matches('"').to_string();
+    let owner: String = rpc_view_call("get_owner", json!({})).await?;
     println!("   Contract owner: {}", owner);
 
     // Verify owner matches expected account
@@ -191,8 +125,7 @@ async fn test_live_safety_deposit_calculations() -> Result<()> {
     println!("💰 Testing live safety deposit calculations...");
 
     // Get min safety deposit (should be 500 bps = 5%)
-    let response = rpc_view_call("get_min_safety_deposit_bps", json!({})).await?;
-    let min_deposit_bps: u16 = parse_rpc_result_as_string(&response)?.parse()?;
+    let min_deposit_bps: u16 = rpc_view_call("get_min_safety_deposit_bps", json!({})).await?;
 
     // Test various order amounts and verify safety deposit calculations
     let test_amounts = vec![
@@ -223,7 +156,7 @@ async fn test_live_contract_version_compatibility() -> Result<()> {
     ];
 
     for (method, args) in test_cases {
-        let response = rpc_view_call(method, args).await;
+        let response = rpc_view_call::<Value>(method, args).await;
         
         match response {
             Ok(_) => println!("   ✅ Method '{}' is available and callable", method),
@@ -248,16 +181,13 @@ async fn test_live_cross_chain_integration_readiness() -> Result<()> {
     println!("🌉 Testing live cross-chain integration readiness...");
 
     // Verify contract is ready for Ethereum integration
-    let owner_response = rpc_view_call("get_owner", json!({})).await?;
-    let owner = parse_rpc_result_as_string(&owner_response)?.trim_matches('"').to_string();
+    let owner: String = rpc_view_call("get_owner", json!({})).await?;
     
-    let deposit_response = rpc_view_call("get_min_safety_deposit_bps", json!({})).await?;
-    let min_deposit: u16 = parse_rpc_result_as_string(&deposit_response)?.parse()?;
+    let min_deposit: u16 = rpc_view_call("get_min_safety_deposit_bps", json!({})).await?;
     
-    let auth_response = rpc_view_call("is_authorized_resolver", json!({
+    let is_resolver_authorized: bool = rpc_view_call("is_authorized_resolver", json!({
         "resolver": TESTNET_OWNER_ID
     })).await?;
-    let is_resolver_authorized: bool = parse_rpc_result_as_string(&auth_response)?.parse()?;
 
     // Verify integration prerequisites
     assert_eq!(owner, TESTNET_OWNER_ID, "Owner should match deployment account");
@@ -280,7 +210,7 @@ async fn test_live_performance_metrics() -> Result<()> {
     // Measure view call performance (single call to avoid rate limiting)
     let start_time = std::time::Instant::now();
 
-    let _response = rpc_view_call("get_owner", json!({})).await?;
+    let _owner: String = rpc_view_call("get_owner", json!({})).await?;
 
     let duration = start_time.elapsed();
     let avg_call_time = duration.as_millis();
@@ -303,20 +233,17 @@ async fn test_comprehensive_fusion_plus_integration() -> Result<()> {
     println!("========================================");
 
     // 1. Test basic contract functionality
-    let response = rpc_view_call("get_owner", json!({})).await?;
-    let owner = parse_rpc_result_as_string(&response)?.trim_matches('"').to_string();
+    let owner: String = rpc_view_call("get_owner", json!({})).await?;
     println!("✅ Step 1: Contract responsive - owner: {}", owner);
 
     // 2. Test 1inch Fusion+ configuration
-    let response = rpc_view_call("get_min_safety_deposit_bps", json!({})).await?;
-    let min_deposit: u16 = parse_rpc_result_as_string(&response)?.parse()?;
+    let min_deposit: u16 = rpc_view_call("get_min_safety_deposit_bps", json!({})).await?;
     println!("✅ Step 2: Fusion+ config - safety deposit: {} bps", min_deposit);
 
     // 3. Test resolver network integration  
-    let response = rpc_view_call("is_authorized_resolver", json!({
+    let is_authorized: bool = rpc_view_call("is_authorized_resolver", json!({
         "resolver": TESTNET_OWNER_ID
     })).await?;
-    let is_authorized: bool = parse_rpc_result_as_string(&response)?.parse()?;
     println!("✅ Step 3: Resolver network - owner authorized: {}", is_authorized);
 
     // 4. Verify all core functionality is working
@@ -0,0 +1,126 @@
+//! Order model shared by the Fusion+ destination escrows (NEAR, Cosmos) and
+//! off-chain tools, so every component agrees on statuses, chain IDs and
+//! which status transitions are allowed
+
+use serde::{Deserialize, Serialize};
+
+/// Lifecycle of a Fusion+ order on the destination chain
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum OrderStatus {
+    /// Announced but not funded yet
+    Pending,
+    /// Funded by the resolver, waiting for the secret
+    Matched,
+    /// Secret revealed (or release authorized), funds paid to the maker
+    Claimed,
+    /// Cancelled, funds returned to the resolver
+    Refunded,
+}
+
+impl OrderStatus {
+    /// Whether an order in this status can move to `next`
+    /// Claimed and Refunded are final
+    pub fn can_transition_to(&self, next: &OrderStatus) -> bool {
+        matches!(
+            (self, next),
+            (OrderStatus::Pending, OrderStatus::Matched)
+                | (OrderStatus::Pending, OrderStatus::Refunded)
+                | (OrderStatus::Matched, OrderStatus::Claimed)
+                | (OrderStatus::Matched, OrderStatus::Refunded)
+        )
+    }
+
+    pub fn is_final(&self) -> bool {
+        matches!(self, OrderStatus::Claimed | OrderStatus::Refunded)
+    }
+}
+
+/// Family a chain ID belongs to, matching `ChainType` in `shared/src/types/chains.ts`
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum ChainFamily {
+    Evm,
+    Aptos,
+    Bitcoin,
+    Cosmos,
+    Near,
+}
+
+/// Chain IDs used across the project: EVM chain IDs as-is, other families
+/// in their own ranges (`ChainId` in `shared/src/types/chains.ts`)
+pub const KNOWN_CHAINS: &[(u64, &str, ChainFamily)] = &[
+    (1, "Ethereum Mainnet", ChainFamily::Evm),
+    (11155111, "Ethereum Sepolia", ChainFamily::Evm),
+    (10001, "Aptos Mainnet", ChainFamily::Aptos),
+    (10002, "Aptos Testnet", ChainFamily::Aptos),
+    (20001, "Bitcoin", ChainFamily::Bitcoin),
+    (20002, "Bitcoin Testnet", ChainFamily::Bitcoin),
+    (20003, "Dogecoin", ChainFamily::Bitcoin),
+    (20004, "Dogecoin Testnet", ChainFamily::Bitcoin),
+    (20005, "Litecoin", ChainFamily::Bitcoin),
+    (20006, "Litecoin Testnet", ChainFamily::Bitcoin),
+    (20007, "Bitcoin Cash", ChainFamily::Bitcoin),
+    (20008, "Bitcoin Cash Testnet", ChainFamily::Bitcoin),
+    (30001, "Cosmos Hub", ChainFamily::Cosmos),
+    (30002, "Cosmos Hub Testnet", ChainFamily::Cosmos),
+    (40001, "NEAR Protocol", ChainFamily::Near),
+    (40002, "NEAR Testnet", ChainFamily::Near),
+];
+
+/// Name and family of a known chain ID
+pub fn chain(chain_id: u64) -> Option<(&'static str, ChainFamily)> {
+    KNOWN_CHAINS
+        .iter()
+        .find(|(id, _, _)| *id == chain_id)
+        .map(|&(_, name, family)| (name, family))
+}
+
+/// Fields every destination escrow keeps for an order, in chain-neutral
+/// form: hashes as raw bytes, amounts in the smallest unit of the escrowed
+/// asset and `Account` the chain's account type
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct OrderFields<Account> {
+    /// 1inch order hash of the source-chain order
+    pub order_hash: [u8; 32],
+    pub hashlock: [u8; 32],
+    /// Receives the escrowed funds on claim
+    pub maker: Account,
+    /// Funds the escrow and gets the fee and safety deposit back
+    pub resolver: Account,
+    pub amount: u128,
+    pub resolver_fee: u128,
+    pub safety_deposit: u128,
+    /// Packed stage offsets, see the `fusion-timelocks` layout
+    pub timelocks: u128,
+    pub source_chain_id: u64,
+    pub status: OrderStatus,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_transitions() {
+        assert!(OrderStatus::Pending.can_transition_to(&OrderStatus::Matched));
+        assert!(OrderStatus::Matched.can_transition_to(&OrderStatus::Claimed));
+        assert!(OrderStatus::Matched.can_transition_to(&OrderStatus::Refunded));
+        assert!(!OrderStatus::Pending.can_transition_to(&OrderStatus::Claimed));
+        assert!(!OrderStatus::Matched.can_transition_to(&OrderStatus::Matched));
+        for status in [OrderStatus::Claimed, OrderStatus::Refunded] {
+            assert!(status.is_final());
+            for next in [OrderStatus::Pending, OrderStatus::Matched, OrderStatus::Claimed, OrderStatus::Refunded] {
+                assert!(!status.can_transition_to(&next));
+            }
+        }
+    }
+
+    #[test]
+    fn test_known_chains() {
+        assert_eq!(chain(11155111), Some(("Ethereum Sepolia", ChainFamily::Evm)));
+        assert_eq!(chain(40002), Some(("NEAR Testnet", ChainFamily::Near)));
+        assert_eq!(chain(5), None);
+    }
+}
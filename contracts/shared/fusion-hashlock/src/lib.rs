@@ -0,0 +1,133 @@
+//! Hashlocks for Fusion+ swaps: which hash an order uses and how a preimage
+//! is turned into bytes before hashing, so the same secret gives the same
+//! hashlock on EVM, CosmWasm and NEAR.
+//!
+//! A preimage is always hashed as raw bytes. Hex input (optional `0x`/`0X`
+//! prefix, either case) is decoded first, never hashed as text. Contracts
+//! plug in their chain's hash functions through [`Hasher`]; the `software`
+//! feature provides a pure-Rust one. Checked against
+//! `contracts/shared/fixtures/hashlocks.json`.
+
+#![cfg_attr(not(feature = "schemars"), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// Hash function used to derive an order's hashlock from its preimage
+/// Must match the algorithm used by the Ethereum-side escrow
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum HashAlgorithm {
+    /// Bitcoin-compatible, the default
+    #[default]
+    Sha256,
+    /// What 1inch escrows on EVM chains use
+    Keccak256,
+}
+
+/// Hash functions of the host chain
+pub trait Hasher {
+    fn sha256(&self, data: &[u8]) -> [u8; 32];
+    fn keccak256(&self, data: &[u8]) -> [u8; 32];
+}
+
+/// Pure-Rust [`Hasher`]
+#[cfg(feature = "software")]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct SoftwareHasher;
+
+#[cfg(feature = "software")]
+impl Hasher for SoftwareHasher {
+    fn sha256(&self, data: &[u8]) -> [u8; 32] {
+        use sha2::Digest;
+        sha2::Sha256::digest(data).into()
+    }
+
+    fn keccak256(&self, data: &[u8]) -> [u8; 32] {
+        use sha3::Digest;
+        sha3::Keccak256::digest(data).into()
+    }
+}
+
+impl HashAlgorithm {
+    /// Hashlock of raw preimage bytes
+    pub fn hashlock(&self, hasher: &impl Hasher, preimage: &[u8]) -> [u8; 32] {
+        match self {
+            HashAlgorithm::Sha256 => hasher.sha256(preimage),
+            HashAlgorithm::Keccak256 => hasher.keccak256(preimage),
+        }
+    }
+
+    /// Whether `preimage` unlocks `hashlock`
+    pub fn verify(&self, hasher: &impl Hasher, preimage: &[u8], hashlock: &[u8; 32]) -> bool {
+        self.hashlock(hasher, preimage) == *hashlock
+    }
+}
+
+/// Bytes of a hex string (optional `0x`/`0X` prefix, either case), `None` if malformed
+pub fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value)
+        .as_bytes();
+    if !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let high = (pair[0] as char).to_digit(16)?;
+            let low = (pair[1] as char).to_digit(16)?;
+            Some((high * 16 + low) as u8)
+        })
+        .collect()
+}
+
+/// Bytes of a 32-byte hex value such as a hashlock, `None` unless exactly 64 digits
+pub fn decode_hex32(value: &str) -> Option<[u8; 32]> {
+    decode_hex(value)?.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_fixtures() {
+        let fixtures: Value = serde_json::from_str(include_str!("../../fixtures/hashlocks.json")).unwrap();
+        for case in fixtures["cases"].as_array().unwrap() {
+            let name = case["name"].as_str().unwrap();
+            let preimage = decode_hex(case["preimage"].as_str().unwrap()).unwrap();
+            for (algorithm, key) in [(HashAlgorithm::Sha256, "sha256"), (HashAlgorithm::Keccak256, "keccak256")] {
+                let hashlock = decode_hex32(case[key].as_str().unwrap()).unwrap();
+                assert_eq!(algorithm.hashlock(&SoftwareHasher, &preimage), hashlock, "{} {}", name, key);
+                assert!(algorithm.verify(&SoftwareHasher, &preimage, &hashlock), "{} {}", name, key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_rejects_wrong_preimage() {
+        let hashlock = HashAlgorithm::Keccak256.hashlock(&SoftwareHasher, &[7; 32]);
+        assert!(!HashAlgorithm::Keccak256.verify(&SoftwareHasher, &[8; 32], &hashlock));
+        assert!(!HashAlgorithm::Sha256.verify(&SoftwareHasher, &[7; 32], &hashlock));
+    }
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex("0xABcd"), Some(std::vec![0xab, 0xcd]));
+        assert_eq!(decode_hex("0XABCD"), decode_hex("abcd"));
+        assert_eq!(decode_hex(""), Some(Vec::new()));
+        assert_eq!(decode_hex("abc"), None);
+        assert_eq!(decode_hex("zz"), None);
+        assert_eq!(decode_hex32(&"ab".repeat(32)), Some([0xab; 32]));
+        assert_eq!(decode_hex32(&"ab".repeat(31)), None);
+    }
+}
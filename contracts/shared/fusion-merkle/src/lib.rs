@@ -0,0 +1,201 @@
+//! Secret Merkle trees for partial fills, built like the 1inch SDK's
+//! `HashLock.forMultipleFills`: an order split in `N` parts commits to
+//! `N + 1` secrets, leaf `i` is `keccak256(uint64 i || hash(secret_i))` and
+//! the tree is an OpenZeppelin `SimpleMerkleTree` (sorted leaves, sorted
+//! pairs). Shared by the contracts, which verify proofs, and the resolver
+//! tooling, which builds trees and proofs.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use fusion_hashlock::{HashAlgorithm, Hasher};
+
+pub type Node = [u8; 32];
+
+/// Merkle leaf for the secret at `index`: `keccak256(uint64 index || secret_hash)`
+pub fn secret_leaf(hasher: &impl Hasher, index: u64, secret_hash: &Node) -> Node {
+    let mut packed = [0u8; 40];
+    packed[..8].copy_from_slice(&index.to_be_bytes());
+    packed[8..].copy_from_slice(secret_hash);
+    hasher.keccak256(&packed)
+}
+
+/// Hash a pair of nodes in sorted order (OpenZeppelin `MerkleProof` layout)
+pub fn hash_pair(hasher: &impl Hasher, a: &Node, b: &Node) -> Node {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut packed = [0u8; 64];
+    packed[..32].copy_from_slice(first);
+    packed[32..].copy_from_slice(second);
+    hasher.keccak256(&packed)
+}
+
+/// Fold `proof` into `leaf` and compare against `root`
+pub fn verify_proof(hasher: &impl Hasher, leaf: Node, proof: &[Node], root: &Node) -> bool {
+    proof.iter().fold(leaf, |node, sibling| hash_pair(hasher, &node, sibling)) == *root
+}
+
+/// 1inch packs the number of parts into the top 16 bits of a multi-fill
+/// hashlock: `root & (2^240 - 1) | parts << 240`
+pub fn root_with_parts(root: &Node, parts: u16) -> Node {
+    let mut hashlock = *root;
+    hashlock[..2].copy_from_slice(&parts.to_be_bytes());
+    hashlock
+}
+
+/// First filled amount belonging to part `k`: `ceil(k * total / parts)`
+/// Split so the product cannot overflow u128
+fn part_start(total: u128, parts: u128, k: u128) -> u128 {
+    (total / parts) * k + ((total % parts) * k).div_ceil(parts)
+}
+
+/// Index of the secret that must be revealed to fill `fill` more of an order
+/// of `total` split in `parts`, with `filled` already filled
+/// Mirrors 1inch `_isValidPartialFill`: a fill ending in part `i` reveals
+/// secret `i`, and the fill completing the order reveals the extra secret `parts`
+pub fn expected_secret_index(total: u128, filled: u128, fill: u128, parts: u32) -> u32 {
+    let new_filled = filled + fill;
+    if new_filled == total {
+        return parts;
+    }
+    // Largest k with k <= (new_filled - 1) * parts / total
+    let (mut low, mut high) = (0u128, parts as u128 - 1);
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if part_start(total, parts as u128, mid) < new_filled {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    low as u32
+}
+
+/// Merkle tree over the secrets of an order split in `secrets.len() - 1` parts
+/// Nodes are stored like OpenZeppelin's `SimpleMerkleTree`: root at 0,
+/// children of `i` at `2i + 1` and `2i + 2`, sorted leaves filling the end
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SecretTree {
+    nodes: Vec<Node>,
+    /// Position in `nodes` of the leaf for secret `i`
+    leaf_positions: Vec<usize>,
+}
+
+impl SecretTree {
+    /// Tree over the hashes of `secrets` (hashed with `algorithm`, keccak256
+    /// for 1inch orders); panics unless there are at least two secrets
+    pub fn from_secrets(hasher: &impl Hasher, algorithm: HashAlgorithm, secrets: &[Node]) -> Self {
+        let hashes: Vec<Node> = secrets.iter().map(|secret| algorithm.hashlock(hasher, secret)).collect();
+        Self::from_secret_hashes(hasher, &hashes)
+    }
+
+    pub fn from_secret_hashes(hasher: &impl Hasher, secret_hashes: &[Node]) -> Self {
+        assert!(secret_hashes.len() >= 2, "A partial-fill order needs at least two secrets");
+        let leaves: Vec<Node> = secret_hashes
+            .iter()
+            .enumerate()
+            .map(|(i, hash)| secret_leaf(hasher, i as u64, hash))
+            .collect();
+        let mut sorted: Vec<usize> = (0..leaves.len()).collect();
+        sorted.sort_by_key(|&i| leaves[i]);
+
+        let count = leaves.len();
+        let mut nodes = alloc::vec![[0u8; 32]; 2 * count - 1];
+        let mut leaf_positions = alloc::vec![0; count];
+        for (rank, &i) in sorted.iter().enumerate() {
+            let position = nodes.len() - 1 - rank;
+            nodes[position] = leaves[i];
+            leaf_positions[i] = position;
+        }
+        for i in (0..count - 1).rev() {
+            nodes[i] = hash_pair(hasher, &nodes[2 * i + 1], &nodes[2 * i + 2]);
+        }
+        Self { nodes, leaf_positions }
+    }
+
+    pub fn root(&self) -> Node {
+        self.nodes[0]
+    }
+
+    /// Number of parts the order is split in, one less than the secrets
+    pub fn parts(&self) -> u32 {
+        self.leaf_positions.len() as u32 - 1
+    }
+
+    pub fn leaf(&self, index: usize) -> Node {
+        self.nodes[self.leaf_positions[index]]
+    }
+
+    /// Sibling path from the leaf of secret `index` to the root
+    pub fn proof(&self, index: usize) -> Vec<Node> {
+        let mut position = self.leaf_positions[index];
+        let mut proof = Vec::new();
+        while position > 0 {
+            let sibling = if position % 2 == 1 { position + 1 } else { position - 1 };
+            proof.push(self.nodes[sibling]);
+            position = (position - 1) / 2;
+        }
+        proof
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fusion_hashlock::SoftwareHasher;
+
+    fn secrets(count: u8) -> Vec<Node> {
+        (1..=count).map(|i| [i * 0x11; 32]).collect()
+    }
+
+    #[test]
+    fn test_expected_secret_index() {
+        // 100 split in 4 parts: 25 per part, secret 4 completes the order
+        assert_eq!(expected_secret_index(100, 0, 10, 4), 0);
+        assert_eq!(expected_secret_index(100, 10, 15, 4), 0);
+        assert_eq!(expected_secret_index(100, 25, 30, 4), 2);
+        assert_eq!(expected_secret_index(100, 55, 45, 4), 4);
+        // Uneven parts follow floor((filled - 1) * parts / total)
+        assert_eq!(expected_secret_index(10, 0, 7, 3), 1);
+        assert_eq!(expected_secret_index(10, 0, 8, 3), 2);
+        // Amounts whose product with parts exceeds u128
+        let total = u128::MAX / 2;
+        assert_eq!(expected_secret_index(total, 0, total / 2, 10), 4);
+    }
+
+    #[test]
+    fn test_every_proof_verifies() {
+        for count in 2..=9 {
+            let tree = SecretTree::from_secrets(&SoftwareHasher, HashAlgorithm::Keccak256, &secrets(count));
+            assert_eq!(tree.parts(), count as u32 - 1);
+            for index in 0..count as usize {
+                let proof = tree.proof(index);
+                assert!(verify_proof(&SoftwareHasher, tree.leaf(index), &proof, &tree.root()));
+                // A proof only works for its own leaf
+                let other = tree.leaf((index + 1) % count as usize);
+                assert!(!verify_proof(&SoftwareHasher, other, &proof, &tree.root()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_two_secret_tree() {
+        let hashes = [[1u8; 32], [2u8; 32]];
+        let tree = SecretTree::from_secret_hashes(&SoftwareHasher, &hashes);
+        let leaves = [
+            secret_leaf(&SoftwareHasher, 0, &hashes[0]),
+            secret_leaf(&SoftwareHasher, 1, &hashes[1]),
+        ];
+        assert_eq!(tree.root(), hash_pair(&SoftwareHasher, &leaves[0], &leaves[1]));
+        assert_eq!(tree.proof(0), alloc::vec![leaves[1]]);
+        assert_eq!(tree.proof(1), alloc::vec![leaves[0]]);
+    }
+
+    #[test]
+    fn test_root_with_parts() {
+        let hashlock = root_with_parts(&[0xff; 32], 4);
+        assert_eq!(&hashlock[..2], &[0, 4]);
+        assert_eq!(&hashlock[2..], &[0xff; 30]);
+    }
+}
@@ -0,0 +1,266 @@
+//! EIP-712 hashing of 1inch Limit Order Protocol v4 orders, the order hash
+//! Fusion+ escrows are keyed by. Matches `hashOrder` of the Aggregation
+//! Router v6 and is checked against `contracts/shared/fixtures/order_hashes.json`.
+//! Hashing goes through [`fusion_hashlock::Hasher`] so contracts can use
+//! their host keccak256.
+
+#![no_std]
+
+use fusion_hashlock::Hasher;
+
+pub type Word = [u8; 32];
+pub type Address = [u8; 20];
+
+/// 1inch Aggregation Router v6, the Limit Order Protocol contract on every EVM chain
+pub const ONEINCH_ROUTER_V6: Address = [
+    0x11, 0x11, 0x11, 0x12, 0x54, 0x21, 0xca, 0x6d, 0xc4, 0x52, 0xd2, 0x89, 0x31, 0x42, 0x80, 0xa0, 0xf8, 0x84,
+    0x2a, 0x65,
+];
+/// EIP-712 domain of the 1inch Limit Order Protocol v4
+pub const LOP_DOMAIN_NAME: &str = "1inch Aggregation Router";
+pub const LOP_DOMAIN_VERSION: &str = "6";
+const EIP712_DOMAIN_TYPE: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+const ORDER_TYPE: &str = "Order(uint256 salt,address maker,address receiver,address makerAsset,address takerAsset,uint256 makingAmount,uint256 takingAmount,uint256 makerTraits)";
+
+/// EIP-712 domain an order is signed for
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Domain<'a> {
+    pub name: &'a str,
+    pub version: &'a str,
+    pub chain_id: u64,
+    pub verifying_contract: Address,
+}
+
+impl Domain<'static> {
+    /// The Limit Order Protocol on `chain_id`, at the Aggregation Router v6
+    pub fn limit_order_protocol(chain_id: u64) -> Self {
+        Self {
+            name: LOP_DOMAIN_NAME,
+            version: LOP_DOMAIN_VERSION,
+            chain_id,
+            verifying_contract: ONEINCH_ROUTER_V6,
+        }
+    }
+}
+
+impl<'a> Domain<'a> {
+    /// Same domain at another deployment, e.g. a testnet or local router
+    pub fn with_verifying_contract(mut self, verifying_contract: Address) -> Self {
+        self.verifying_contract = verifying_contract;
+        self
+    }
+
+    pub fn separator(&self, hasher: &impl Hasher) -> Word {
+        keccak_words(
+            hasher,
+            &[
+                hasher.keccak256(EIP712_DOMAIN_TYPE.as_bytes()),
+                hasher.keccak256(self.name.as_bytes()),
+                hasher.keccak256(self.version.as_bytes()),
+                u64_word(self.chain_id),
+                address_word(&self.verifying_contract),
+            ],
+        )
+    }
+}
+
+/// 1inch Limit Order Protocol v4 order
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Order {
+    pub salt: Word,
+    pub maker: Address,
+    pub receiver: Address,
+    pub maker_asset: Address,
+    pub taker_asset: Address,
+    pub making_amount: Word,
+    pub taking_amount: Word,
+    pub maker_traits: Word,
+}
+
+impl Order {
+    /// EIP-712 `hashStruct(order)`
+    pub fn struct_hash(&self, hasher: &impl Hasher) -> Word {
+        keccak_words(
+            hasher,
+            &[
+                hasher.keccak256(ORDER_TYPE.as_bytes()),
+                self.salt,
+                address_word(&self.maker),
+                address_word(&self.receiver),
+                address_word(&self.maker_asset),
+                address_word(&self.taker_asset),
+                self.making_amount,
+                self.taking_amount,
+                self.maker_traits,
+            ],
+        )
+    }
+
+    /// EIP-712 digest, `hashOrder` on the domain's chain
+    pub fn hash(&self, hasher: &impl Hasher, domain: &Domain) -> Word {
+        let mut digest = [0u8; 66];
+        digest[..2].copy_from_slice(b"\x19\x01");
+        digest[2..34].copy_from_slice(&domain.separator(hasher));
+        digest[34..].copy_from_slice(&self.struct_hash(hasher));
+        hasher.keccak256(&digest)
+    }
+}
+
+/// keccak256 of `abi.encode` of up to nine 32-byte words
+fn keccak_words(hasher: &impl Hasher, words: &[Word]) -> Word {
+    let mut encoded = [0u8; 9 * 32];
+    for (i, word) in words.iter().enumerate() {
+        encoded[i * 32..(i + 1) * 32].copy_from_slice(word);
+    }
+    hasher.keccak256(&encoded[..words.len() * 32])
+}
+
+fn u64_word(value: u64) -> Word {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// ABI word of an address, left-padded with zeros
+fn address_word(address: &Address) -> Word {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(address);
+    word
+}
+
+/// 20-byte address from hex (optional `0x`/`0X` prefix, any case), `None` if malformed
+pub fn parse_address(value: &str) -> Option<Address> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    if digits.len() != 40 {
+        return None;
+    }
+    let mut address = [0u8; 20];
+    parse_hex_into(digits, &mut address)?;
+    Some(address)
+}
+
+/// Big-endian `uint256` from a decimal string or `0x` hex, `None` if malformed or too large
+pub fn parse_uint256(value: &str) -> Option<Word> {
+    let mut word = [0u8; 32];
+    if let Some(digits) = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        if digits.is_empty() || digits.len() > 64 || !digits.is_ascii() {
+            return None;
+        }
+        // Right-align, an odd digit count leaves a lone high nibble
+        let start = 32 - digits.len().div_ceil(2);
+        if digits.len() % 2 == 1 {
+            word[start] = (digits.as_bytes()[0] as char).to_digit(16)? as u8;
+            parse_hex_into(&digits[1..], &mut word[start + 1..])?;
+        } else {
+            parse_hex_into(digits, &mut word[start..])?;
+        }
+        return Some(word);
+    }
+    if value.is_empty() {
+        return None;
+    }
+    for digit in value.bytes() {
+        let mut carry = (digit as char).to_digit(10)?;
+        for byte in word.iter_mut().rev() {
+            let product = *byte as u32 * 10 + carry;
+            *byte = product as u8;
+            carry = product >> 8;
+        }
+        if carry != 0 {
+            return None;
+        }
+    }
+    Some(word)
+}
+
+/// Decode `digits` (even count) into `out`, which must be half as long
+fn parse_hex_into(digits: &str, out: &mut [u8]) -> Option<()> {
+    let digits = digits.as_bytes();
+    if !digits.is_ascii() || digits.len() != out.len() * 2 {
+        return None;
+    }
+    for (byte, pair) in out.iter_mut().zip(digits.chunks(2)) {
+        let high = (pair[0] as char).to_digit(16)?;
+        let low = (pair[1] as char).to_digit(16)?;
+        *byte = (high * 16 + low) as u8;
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use fusion_hashlock::{decode_hex32, SoftwareHasher};
+    use serde_json::Value;
+    use std::format;
+
+    fn order_from_json(order: &Value) -> Order {
+        let uint = |key: &str| parse_uint256(order[key].as_str().unwrap()).unwrap();
+        let address = |key: &str| parse_address(order[key].as_str().unwrap()).unwrap();
+        Order {
+            salt: uint("salt"),
+            maker: address("maker"),
+            receiver: address("receiver"),
+            maker_asset: address("maker_asset"),
+            taker_asset: address("taker_asset"),
+            making_amount: uint("making_amount"),
+            taking_amount: uint("taking_amount"),
+            maker_traits: uint("maker_traits"),
+        }
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let fixtures: Value = serde_json::from_str(include_str!("../../fixtures/order_hashes.json")).unwrap();
+        for case in fixtures["cases"].as_array().unwrap() {
+            let name = case["name"].as_str().unwrap();
+            let domain = Domain::limit_order_protocol(case["chain_id"].as_u64().unwrap())
+                .with_verifying_contract(parse_address(case["verifying_contract"].as_str().unwrap()).unwrap());
+            let order = order_from_json(&case["order"]);
+            let expected = |key: &str| decode_hex32(case[key].as_str().unwrap()).unwrap();
+
+            assert_eq!(domain.separator(&SoftwareHasher), expected("domain_separator"), "{}", name);
+            assert_eq!(order.struct_hash(&SoftwareHasher), expected("struct_hash"), "{}", name);
+            assert_eq!(order.hash(&SoftwareHasher, &domain), expected("order_hash"), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_parse_uint256() {
+        let mut expected = [0u8; 32];
+        expected[29..].copy_from_slice(&[0x01, 0xe2, 0x40]);
+        assert_eq!(parse_uint256("123456"), Some(expected));
+        assert_eq!(parse_uint256("0x1e240"), Some(expected));
+        assert_eq!(parse_uint256("0x01E240"), Some(expected));
+        assert_eq!(parse_uint256("0"), Some([0u8; 32]));
+
+        // 2^256 - 1 fits, 2^256 does not
+        let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        assert_eq!(parse_uint256(max), Some([0xff; 32]));
+        assert_eq!(
+            parse_uint256("115792089237316195423570985008687907853269984665640564039457584007913129639936"),
+            None
+        );
+        assert_eq!(parse_uint256(&format!("0x{}", "f".repeat(64))), Some([0xff; 32]));
+        assert_eq!(parse_uint256(&format!("0x{}", "f".repeat(65))), None);
+        assert_eq!(parse_uint256(""), None);
+        assert_eq!(parse_uint256("0x"), None);
+        assert_eq!(parse_uint256("0xg"), None);
+        assert_eq!(parse_uint256("-1"), None);
+        assert_eq!(parse_uint256("12a"), None);
+    }
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(parse_address("0x111111125421CA6DC452D289314280A0F8842A65"), Some(ONEINCH_ROUTER_V6));
+        assert_eq!(parse_address("111111125421ca6dc452d289314280a0f8842a65"), Some(ONEINCH_ROUTER_V6));
+        assert_eq!(parse_address("0x111111125421ca6dc452d289314280a0f8842a6"), None);
+        assert_eq!(parse_address(&format!("0x{}", "z".repeat(40))), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! 1inch packed timelocks: seven 32-bit stage offsets and the deployment
+//! timestamp in one uint256, laid out like `TimelocksLib.sol` in
+//! cross-chain-swap and `packTimelocks` in `shared/src/utils/fusion-plus.ts`.
+//! Stage `i` sits at bits `i * 32`, `deployedAt` at bits 224..256.
+//!
+//! `no_std` and dependency-free so every contract can unpack stages the
+//! same way. Checked against `contracts/shared/fixtures/timelocks.json`.
+
+#![no_std]
+
+/// Stages of a 1inch cross-chain swap, in packing order
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stage {
+    SrcWithdrawal = 0,
+    SrcPublicWithdrawal = 1,
+    SrcCancellation = 2,
+    SrcPublicCancellation = 3,
+    DstWithdrawal = 4,
+    DstPublicWithdrawal = 5,
+    DstCancellation = 6,
+}
+
+pub const STAGE_COUNT: usize = 7;
+
+/// Word holding `deployedAt`
+const DEPLOYED_AT_WORD: usize = 7;
+
+/// Packed timelocks as eight 32-bit words, word `i` at bits `i * 32`
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Timelocks([u32; 8]);
+
+impl Timelocks {
+    /// Pack stage offsets (seconds) and the deployment timestamp
+    pub fn new(offsets: [u32; STAGE_COUNT], deployed_at: u32) -> Self {
+        let mut words = [0; 8];
+        words[..STAGE_COUNT].copy_from_slice(&offsets);
+        words[DEPLOYED_AT_WORD] = deployed_at;
+        Self(words)
+    }
+
+    /// Offset in seconds of `stage` from deployment
+    pub fn offset(&self, stage: Stage) -> u32 {
+        self.0[stage as usize]
+    }
+
+    pub fn offsets(&self) -> [u32; STAGE_COUNT] {
+        let mut offsets = [0; STAGE_COUNT];
+        offsets.copy_from_slice(&self.0[..STAGE_COUNT]);
+        offsets
+    }
+
+    pub fn deployed_at(&self) -> u32 {
+        self.0[DEPLOYED_AT_WORD]
+    }
+
+    /// Same offsets with a new deployment timestamp (`setDeployedAt`)
+    pub fn with_deployed_at(mut self, deployed_at: u32) -> Self {
+        self.0[DEPLOYED_AT_WORD] = deployed_at;
+        self
+    }
+
+    /// Absolute start of `stage` (`get`); widened so it can't overflow
+    pub fn get(&self, stage: Stage) -> u64 {
+        self.deployed_at() as u64 + self.offset(stage) as u64
+    }
+
+    /// When funds can be rescued, `rescue_delay` seconds after deployment (`rescueStart`)
+    pub fn rescue_start(&self, rescue_delay: u64) -> u64 {
+        self.deployed_at() as u64 + rescue_delay
+    }
+
+    /// From the 32-byte big-endian (ABI) encoding
+    pub fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        let mut words = [0; 8];
+        for (i, word) in words.iter_mut().enumerate() {
+            let start = (7 - i) * 4;
+            *word = u32::from_be_bytes([bytes[start], bytes[start + 1], bytes[start + 2], bytes[start + 3]]);
+        }
+        Self(words)
+    }
+
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        for (i, word) in self.0.iter().enumerate() {
+            let start = (7 - i) * 4;
+            bytes[start..start + 4].copy_from_slice(&word.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// From a `0x`-prefixed or bare hex string of at most 64 digits
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let digits = hex.strip_prefix("0x").unwrap_or(hex).as_bytes();
+        if digits.is_empty() || digits.len() > 64 {
+            return None;
+        }
+        let mut bytes = [0u8; 32];
+        for (i, digit) in digits.iter().rev().enumerate() {
+            let nibble = (*digit as char).to_digit(16)? as u8;
+            bytes[31 - i / 2] |= nibble << ((i % 2) * 4);
+        }
+        Some(Self::from_be_bytes(bytes))
+    }
+
+    /// From a decimal string, as `packTimelocks` returns; `None` above 2^256 - 1
+    pub fn from_decimal(decimal: &str) -> Option<Self> {
+        if decimal.is_empty() {
+            return None;
+        }
+        let mut words = [0u32; 8];
+        for digit in decimal.chars() {
+            let mut carry = digit.to_digit(10)? as u64;
+            for word in words.iter_mut() {
+                let value = *word as u64 * 10 + carry;
+                *word = value as u32;
+                carry = value >> 32;
+            }
+            if carry != 0 {
+                return None;
+            }
+        }
+        Some(Self(words))
+    }
+
+    /// The low 128 bits, i.e. the four source-chain stages
+    pub fn low_u128(&self) -> u128 {
+        self.0[..4]
+            .iter()
+            .enumerate()
+            .fold(0, |acc, (i, word)| acc | ((*word as u128) << (i * 32)))
+    }
+
+    /// From the low 128 bits, the destination stages and `deployedAt` left at zero
+    pub fn from_u128(low: u128) -> Self {
+        let mut words = [0; 8];
+        for (i, word) in words.iter_mut().take(4).enumerate() {
+            *word = (low >> (i * 32)) as u32;
+        }
+        Self(words)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use serde_json::Value;
+    use std::vec::Vec;
+
+    const STAGES: [Stage; STAGE_COUNT] = [
+        Stage::SrcWithdrawal,
+        Stage::SrcPublicWithdrawal,
+        Stage::SrcCancellation,
+        Stage::SrcPublicCancellation,
+        Stage::DstWithdrawal,
+        Stage::DstPublicWithdrawal,
+        Stage::DstCancellation,
+    ];
+
+    fn fixtures() -> Vec<Value> {
+        let fixtures: Value = serde_json::from_str(include_str!("../../fixtures/timelocks.json")).unwrap();
+        fixtures["cases"].as_array().unwrap().clone()
+    }
+
+    #[test]
+    fn test_fixtures() {
+        for case in fixtures() {
+            let name = case["name"].as_str().unwrap();
+            let offsets: Vec<u32> = case["stages"].as_array().unwrap().iter().map(|v| v.as_u64().unwrap() as u32).collect();
+            let deployed_at = case["deployed_at"].as_u64().unwrap() as u32;
+            let timelocks = Timelocks::new(offsets.clone().try_into().unwrap(), deployed_at);
+
+            assert_eq!(Timelocks::from_hex(case["packed"].as_str().unwrap()), Some(timelocks), "{}", name);
+            assert_eq!(Timelocks::from_decimal(case["decimal"].as_str().unwrap()), Some(timelocks), "{}", name);
+            assert_eq!(Timelocks::from_be_bytes(timelocks.to_be_bytes()), timelocks, "{}", name);
+            assert_eq!(timelocks.offsets().as_slice(), offsets.as_slice(), "{}", name);
+            assert_eq!(timelocks.deployed_at(), deployed_at, "{}", name);
+            for (stage, start) in STAGES.iter().zip(case["stage_starts"].as_array().unwrap()) {
+                assert_eq!(timelocks.get(*stage), start.as_u64().unwrap(), "{} {:?}", name, stage);
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_deployed_at() {
+        let timelocks = Timelocks::new([10, 20, 30, 40, 5, 15, 25], 0).with_deployed_at(1_000);
+        assert_eq!(timelocks.get(Stage::DstCancellation), 1_025);
+        assert_eq!(timelocks.offset(Stage::SrcCancellation), 30);
+        assert_eq!(timelocks.rescue_start(86_400), 87_400);
+    }
+
+    #[test]
+    fn test_low_u128() {
+        let timelocks = Timelocks::new([10, 20, 30, 40, 5, 15, 25], 1_000);
+        let low = timelocks.low_u128();
+        assert_eq!(low, 10 | (20 << 32) | (30 << 64) | (40 << 96));
+        assert_eq!(Timelocks::from_u128(low), Timelocks::new([10, 20, 30, 40, 0, 0, 0], 0));
+    }
+
+    #[test]
+    fn test_invalid_encodings() {
+        assert_eq!(Timelocks::from_hex(""), None);
+        assert_eq!(Timelocks::from_hex("0xzz"), None);
+        assert_eq!(Timelocks::from_hex(&"f".repeat(65)), None);
+        assert_eq!(Timelocks::from_decimal("12a"), None);
+        // 2^256
+        assert_eq!(
+            Timelocks::from_decimal("115792089237316195423570985008687907853269984665640564039457584007913129639936"),
+            None
+        );
+    }
+}
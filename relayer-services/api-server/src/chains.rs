@@ -0,0 +1,120 @@
+//! Read-only access to the Fusion+ contracts the status is assembled from
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use near_rpc::{NearClient, RateLimits};
+use relayer::chain::OrderView;
+use relayer::cosmos::CosmosClient;
+use relayer::ethereum::{factory_order, FactoryOrder};
+use relayer::provider::Provider;
+use relayer::rpc::JsonRpcClient;
+use relayer::Result;
+use serde_json::json;
+
+use crate::config::{CosmosEndpoint, EthereumEndpoint, NearEndpoint};
+
+/// Chain orders are announced on
+#[async_trait]
+pub trait OrderSource: Send + Sync {
+    fn chain_id(&self) -> u64;
+
+    /// The factory's record of `order_hash`, `None` if it never saw it
+    async fn order(&self, order_hash: &str) -> Result<Option<FactoryOrder>>;
+}
+
+/// Chain orders settle on through an escrow
+#[async_trait]
+pub trait EscrowChain: Send + Sync {
+    fn chain_id(&self) -> u64;
+
+    /// The escrow for `order_hash`, `None` before one is created
+    async fn escrow(&self, order_hash: &str) -> Result<Option<OrderView>>;
+}
+
+pub struct EthereumFactory {
+    rpc: JsonRpcClient,
+    chain_id: u64,
+    factory: String,
+}
+
+impl EthereumFactory {
+    pub fn new(config: &EthereumEndpoint) -> Self {
+        let urls = std::iter::once(config.rpc_url.clone()).chain(config.fallback_rpc_urls.clone());
+        Self {
+            rpc: JsonRpcClient::with_failover(Provider::new(urls, &config.failover), "eth_blockNumber"),
+            chain_id: config.chain_id,
+            factory: config.factory.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl OrderSource for EthereumFactory {
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    async fn order(&self, order_hash: &str) -> Result<Option<FactoryOrder>> {
+        factory_order(&self.rpc, &self.factory, order_hash).await
+    }
+}
+
+pub struct NearContract {
+    client: NearClient,
+    chain_id: u64,
+    contract_id: String,
+}
+
+impl NearContract {
+    pub fn new(config: &NearEndpoint) -> Self {
+        let urls = std::iter::once(config.rpc_url.clone()).chain(config.fallback_rpc_urls.clone());
+        let rpc = JsonRpcClient::with_failover(Provider::new(urls, &config.failover), "status");
+        Self {
+            client: NearClient::with_limits(rpc, Arc::new(RateLimits::new(&config.rate_limit))),
+            chain_id: config.chain_id,
+            contract_id: config.contract_id.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl EscrowChain for NearContract {
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    async fn escrow(&self, order_hash: &str) -> Result<Option<OrderView>> {
+        let args = json!({ "order_hash": order_hash });
+        Ok(self.client.view(&self.contract_id, "get_order", args).await?)
+    }
+}
+
+pub struct CosmosContract {
+    client: CosmosClient,
+    chain_id: u64,
+    contract: String,
+}
+
+impl CosmosContract {
+    pub fn new(config: &CosmosEndpoint) -> Self {
+        let urls = std::iter::once(&config.rest_url).chain(&config.fallback_rest_urls);
+        let provider = Provider::new(urls.map(|url| url.trim_end_matches('/').to_string()), &config.failover);
+        Self {
+            client: CosmosClient::new(provider),
+            chain_id: config.chain_id,
+            contract: config.contract.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl EscrowChain for CosmosContract {
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    async fn escrow(&self, order_hash: &str) -> Result<Option<OrderView>> {
+        self.client.order(&self.contract, order_hash).await
+    }
+}
@@ -0,0 +1,126 @@
+//! API server settings, read from a TOML file. Only read access is needed,
+//! so there are no keys
+
+use std::net::SocketAddr;
+use std::path::Path;
+
+use near_rpc::NearRateLimitConfig;
+use relayer::config::FailoverConfig;
+use relayer::{RelayerError, Result};
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ApiConfig {
+    #[serde(default)]
+    pub server: ServerConfig,
+    pub ethereum: EthereumEndpoint,
+    pub near: Option<NearEndpoint>,
+    pub cosmos: Option<CosmosEndpoint>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub listen: SocketAddr,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            listen: SocketAddr::from(([127, 0, 0, 1], 8080)),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct EthereumEndpoint {
+    pub rpc_url: String,
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
+    #[serde(default)]
+    pub failover: FailoverConfig,
+    pub chain_id: u64,
+    /// `OneInchFusionPlusFactory` address
+    pub factory: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct NearEndpoint {
+    pub rpc_url: String,
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
+    #[serde(default)]
+    pub failover: FailoverConfig,
+    #[serde(default)]
+    pub rate_limit: NearRateLimitConfig,
+    pub chain_id: u64,
+    pub contract_id: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct CosmosEndpoint {
+    pub rest_url: String,
+    #[serde(default)]
+    pub fallback_rest_urls: Vec<String>,
+    #[serde(default)]
+    pub failover: FailoverConfig,
+    pub chain_id: u64,
+    pub contract: String,
+}
+
+impl ApiConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| RelayerError::Config(format!("{}: {}", path.display(), error)))?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        let config: ApiConfig = toml::from_str(contents).map_err(|error| RelayerError::Config(error.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        check_family(self.ethereum.chain_id, fusion_core::ChainFamily::Evm)?;
+        if let Some(near) = &self.near {
+            check_family(near.chain_id, fusion_core::ChainFamily::Near)?;
+        }
+        if let Some(cosmos) = &self.cosmos {
+            check_family(cosmos.chain_id, fusion_core::ChainFamily::Cosmos)?;
+        }
+        Ok(())
+    }
+}
+
+fn check_family(chain_id: u64, family: fusion_core::ChainFamily) -> Result<()> {
+    match fusion_core::chain(chain_id) {
+        Some((_, actual)) if actual == family => Ok(()),
+        _ => Err(RelayerError::Config(format!(
+            "chain {} is not a known {:?} chain",
+            chain_id, family
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../api-server.example.toml");
+
+    #[test]
+    fn test_example_config() {
+        let config = ApiConfig::parse(EXAMPLE).unwrap();
+        assert_eq!(config.server.listen.port(), 8080);
+        assert_eq!(config.ethereum.chain_id, 11155111);
+        assert_eq!(config.near.unwrap().chain_id, 40002);
+        assert_eq!(config.cosmos.unwrap().chain_id, 30002);
+    }
+
+    #[test]
+    fn test_rejects_wrong_chain_family() {
+        let config = EXAMPLE.replace("chain_id = 30002", "chain_id = 40001");
+        assert!(matches!(ApiConfig::parse(&config), Err(RelayerError::Config(_))));
+    }
+}
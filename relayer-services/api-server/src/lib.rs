@@ -0,0 +1,8 @@
+//! Fusion+ order status API: one endpoint reporting where an order stands
+//! on Ethereum and on every NEAR and Cosmos chain it may settle on, so
+//! wallets and dashboards don't query three chains themselves
+
+pub mod chains;
+pub mod config;
+pub mod routes;
+pub mod status;
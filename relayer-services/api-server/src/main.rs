@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use clap::Parser;
+use tracing_subscriber::EnvFilter;
+
+use api_server::chains::{CosmosContract, EscrowChain, EthereumFactory, NearContract};
+use api_server::config::ApiConfig;
+use api_server::routes::router;
+use api_server::status::StatusService;
+use relayer::RelayerError;
+
+#[derive(Parser)]
+#[command(about = "REST API serving Fusion+ order status across Ethereum, NEAR and Cosmos")]
+struct Args {
+    /// Path to the TOML configuration
+    #[arg(long, default_value = "api-server.toml")]
+    config: PathBuf,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .init();
+
+    let args = Args::parse();
+    let config = ApiConfig::load(&args.config)?;
+
+    tracing::info!(factory = %config.ethereum.factory, chain = config.ethereum.chain_id, "reading orders from Ethereum");
+    let mut destinations: Vec<Box<dyn EscrowChain>> = Vec::new();
+    if let Some(near) = &config.near {
+        tracing::info!(contract = %near.contract_id, chain = near.chain_id, "reading escrows from NEAR");
+        destinations.push(Box::new(NearContract::new(near)));
+    }
+    if let Some(cosmos) = &config.cosmos {
+        tracing::info!(contract = %cosmos.contract, chain = cosmos.chain_id, "reading escrows from Cosmos");
+        destinations.push(Box::new(CosmosContract::new(cosmos)));
+    }
+    let service = StatusService::new(Box::new(EthereumFactory::new(&config.ethereum)), destinations);
+
+    let listen = config.server.listen;
+    tracing::info!(%listen, "serving");
+    axum::Server::try_bind(&listen)
+        .map_err(|error| RelayerError::Config(format!("{}: {}", listen, error)))?
+        .serve(router(Arc::new(service)).into_make_service())
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+            tracing::info!("shutting down");
+        })
+        .await?;
+    Ok(())
+}
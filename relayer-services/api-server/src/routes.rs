@@ -0,0 +1,124 @@
+//! HTTP routes
+//!
+//! `GET /orders/:hash` answers with the order's [`crate::status::OrderReport`]: 200 when
+//! the order was found, 404 when no chain has it, and 503 when it wasn't
+//! found but some chain couldn't be read. `GET /health` is for load
+//! balancers. Every response allows any origin, the API is read-only and
+//! meant to be called from browser wallets and dashboards.
+
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{middleware, Router};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::status::{Stage, StatusService};
+
+pub fn router(service: Arc<StatusService>) -> Router {
+    Router::new()
+        .route("/orders/:hash", get(order))
+        .route("/health", get(health))
+        .layer(middleware::map_response(allow_any_origin))
+        .with_state(service)
+}
+
+async fn allow_any_origin(mut response: Response) -> Response {
+    response
+        .headers_mut()
+        .insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+    response
+}
+
+fn json(status: StatusCode, body: &impl Serialize) -> Response {
+    let body = serde_json::to_vec(body).expect("responses serialize");
+    (status, [(header::CONTENT_TYPE, "application/json")], body).into_response()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or(0)
+}
+
+async fn order(State(service): State<Arc<StatusService>>, Path(hash): Path<String>) -> Response {
+    let Some(hash) = fusion_hashlock::decode_hex32(&hash) else {
+        let error = json!({ "error": "order hash must be 32 bytes hex" });
+        return json(StatusCode::BAD_REQUEST, &error);
+    };
+    let report = service.report(&format!("0x{}", hex::encode(hash)), unix_now()).await;
+    let status = match report.stage {
+        Stage::NotFound => StatusCode::NOT_FOUND,
+        Stage::Unknown => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::OK,
+    };
+    json(status, &report)
+}
+
+async fn health() -> Response {
+    json(StatusCode::OK, &json!({ "status": "ok" }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::Request;
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::status::tests::{factory_order, order_view, service, HASH};
+
+    async fn get(service: StatusService, uri: &str) -> (StatusCode, Option<HeaderValue>, Value) {
+        let request = Request::get(uri).body(Body::empty()).unwrap();
+        let response = router(Arc::new(service)).oneshot(request).await.unwrap();
+        let status = response.status();
+        let origin = response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).cloned();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, origin, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_order() {
+        let service = service(Some(factory_order(true)), Some(order_view("Matched")), false);
+        // Hash case and prefix are normalized
+        let uri = format!("/orders/{}", HASH.trim_start_matches("0x").to_uppercase());
+        let (status, origin, body) = get(service, &uri).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(origin.unwrap(), "*");
+        assert_eq!(body["order_hash"], HASH);
+        assert_eq!(body["stage"], "escrow_created");
+        assert_eq!(body["source"]["order"]["destination_chain_id"], 40002);
+        assert_eq!(body["destinations"][0]["escrow"]["status"], "Matched");
+        assert!(body["destinations"][0]["escrow"]["deadlines"]["public_cancellation"].is_u64());
+        assert!(body["destinations"][1].get("error").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_order_errors() {
+        let (status, origin, body) = get(service(None, None, false), "/orders/0x1234").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(origin.unwrap(), "*");
+        assert!(body["error"].is_string());
+
+        let uri = format!("/orders/{}", HASH);
+        let (status, _, body) = get(service(None, None, false), &uri).await;
+        assert_eq!((status, &body["stage"]), (StatusCode::NOT_FOUND, &json!("not_found")));
+        let (status, _, body) = get(service(None, None, true), &uri).await;
+        assert_eq!(
+            (status, &body["stage"]),
+            (StatusCode::SERVICE_UNAVAILABLE, &json!("unknown"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_health() {
+        let (status, _, body) = get(service(None, None, false), "/health").await;
+        assert_eq!((status, body), (StatusCode::OK, json!({ "status": "ok" })));
+    }
+}
@@ -0,0 +1,435 @@
+//! An order's status across chains: what the factory on Ethereum recorded,
+//! the escrow on each destination chain, and the stage that adds up to
+//!
+//! Chains are read concurrently. One that can't be read is reported with
+//! its error while the rest still answer; the overall stage is `unknown`
+//! only when nothing was found and some chain could not be asked.
+
+use fusion_core::OrderStatus;
+use futures::future::join_all;
+use relayer::chain::{Deadlines, OrderView};
+use relayer::ethereum::FactoryOrder;
+use serde::Serialize;
+
+use crate::chains::{EscrowChain, OrderSource};
+
+/// Where an order stands as a whole
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    /// On no chain that could be read
+    NotFound,
+    /// Not found, and some chain could not be read
+    Unknown,
+    /// Open on the source chain, no destination escrow yet
+    Announced,
+    /// Open on the source chain past its expiry, no destination escrow
+    Expired,
+    /// Destination escrow created, waiting for the maker's secret
+    EscrowCreated,
+    /// Claimed on the destination, source side not completed yet
+    SecretRevealed,
+    /// Claimed on the destination, closed on the source
+    Completed,
+    /// Destination escrow refunded to the resolver
+    Refunded,
+    /// Closed on the source chain without a destination escrow
+    Closed,
+}
+
+/// Timelock window an escrow is in
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Window {
+    /// Before anyone can withdraw
+    Locked,
+    /// Only the resolver can withdraw for the maker
+    Withdrawal,
+    /// Anyone with the secret can withdraw for the maker
+    PublicWithdrawal,
+    /// Only the resolver can cancel
+    Cancellation,
+    /// Anyone can cancel
+    PublicCancellation,
+}
+
+impl Window {
+    pub fn at(deadlines: &Deadlines, now: u64) -> Self {
+        if now >= deadlines.public_cancellation {
+            Window::PublicCancellation
+        } else if now >= deadlines.cancellation {
+            Window::Cancellation
+        } else if now >= deadlines.public_withdrawal {
+            Window::PublicWithdrawal
+        } else if now >= deadlines.withdrawal {
+            Window::Withdrawal
+        } else {
+            Window::Locked
+        }
+    }
+}
+
+/// Response of `GET /orders/:hash`
+#[derive(Serialize, Debug)]
+pub struct OrderReport {
+    pub order_hash: String,
+    pub stage: Stage,
+    pub source: SourceReport,
+    pub destinations: Vec<DestinationReport>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SourceReport {
+    pub chain_id: u64,
+    pub chain: Option<&'static str>,
+    pub order: Option<SourceOrder>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Amounts are decimal strings, they don't fit JSON numbers
+#[derive(Serialize, Debug)]
+pub struct SourceOrder {
+    pub maker: String,
+    pub source_token: String,
+    pub source_amount: String,
+    pub destination_chain_id: u64,
+    pub destination_token: String,
+    pub destination_amount: String,
+    pub destination_address: String,
+    pub resolver_fee: String,
+    pub expiry_time: u64,
+    /// Neither completed nor cancelled
+    pub active: bool,
+    pub expired: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DestinationReport {
+    pub chain_id: u64,
+    pub chain: Option<&'static str>,
+    pub escrow: Option<EscrowReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct EscrowReport {
+    pub status: OrderStatus,
+    pub window: Window,
+    pub maker: String,
+    pub resolver: Option<String>,
+    pub amount: String,
+    pub hashlock: String,
+    /// Set once a claim revealed it
+    pub preimage: Option<String>,
+    pub created_at: u64,
+    pub deadlines: Deadlines,
+}
+
+fn chain_name(chain_id: u64) -> Option<&'static str> {
+    fusion_core::chain(chain_id).map(|(name, _)| name)
+}
+
+fn source_order(order: FactoryOrder, now: u64) -> SourceOrder {
+    SourceOrder {
+        maker: order.maker,
+        source_token: order.source_token,
+        source_amount: order.source_amount.to_string(),
+        destination_chain_id: order.destination_chain_id,
+        destination_token: order.destination_token,
+        destination_amount: order.destination_amount.to_string(),
+        destination_address: order.destination_address,
+        resolver_fee: order.resolver_fee.to_string(),
+        expiry_time: order.expiry_time,
+        active: order.is_active,
+        expired: now >= order.expiry_time,
+    }
+}
+
+fn escrow_report(view: OrderView, now: u64) -> relayer::Result<EscrowReport> {
+    let deadlines = view.deadlines()?;
+    Ok(EscrowReport {
+        status: view.status,
+        window: Window::at(&deadlines, now),
+        maker: view.maker,
+        resolver: view.resolver,
+        amount: view.amount,
+        hashlock: view.hashlock,
+        preimage: view.preimage,
+        created_at: view.created_at,
+        deadlines,
+    })
+}
+
+/// Overall stage from the source order and the destination escrow it
+/// settles through
+fn stage(source: Option<&SourceOrder>, escrow: Option<&EscrowReport>) -> Stage {
+    match (source, escrow) {
+        (_, Some(escrow)) => match escrow.status {
+            OrderStatus::Pending | OrderStatus::Matched => Stage::EscrowCreated,
+            OrderStatus::Claimed if source.is_some_and(|order| order.active) => Stage::SecretRevealed,
+            OrderStatus::Claimed => Stage::Completed,
+            OrderStatus::Refunded => Stage::Refunded,
+        },
+        (Some(order), None) if !order.active => Stage::Closed,
+        (Some(order), None) if order.expired => Stage::Expired,
+        (Some(_), None) => Stage::Announced,
+        (None, None) => Stage::NotFound,
+    }
+}
+
+pub struct StatusService {
+    source: Box<dyn OrderSource>,
+    destinations: Vec<Box<dyn EscrowChain>>,
+}
+
+impl StatusService {
+    pub fn new(source: Box<dyn OrderSource>, destinations: Vec<Box<dyn EscrowChain>>) -> Self {
+        Self { source, destinations }
+    }
+
+    /// Status of `order_hash` (`0x` hex) at unix time `now`
+    pub async fn report(&self, order_hash: &str, now: u64) -> OrderReport {
+        let (order, escrows) = futures::join!(
+            self.source.order(order_hash),
+            join_all(self.destinations.iter().map(|chain| chain.escrow(order_hash)))
+        );
+
+        let source = match order {
+            Ok(order) => SourceReport {
+                chain_id: self.source.chain_id(),
+                chain: chain_name(self.source.chain_id()),
+                order: order.map(|order| source_order(order, now)),
+                error: None,
+            },
+            Err(error) => SourceReport {
+                chain_id: self.source.chain_id(),
+                chain: chain_name(self.source.chain_id()),
+                order: None,
+                error: Some(error.to_string()),
+            },
+        };
+        let destinations: Vec<_> = self
+            .destinations
+            .iter()
+            .zip(escrows)
+            .map(|(chain, escrow)| {
+                let (escrow, error) =
+                    match escrow.and_then(|view| view.map(|view| escrow_report(view, now)).transpose()) {
+                        Ok(escrow) => (escrow, None),
+                        Err(error) => (None, Some(error.to_string())),
+                    };
+                DestinationReport {
+                    chain_id: chain.chain_id(),
+                    chain: chain_name(chain.chain_id()),
+                    escrow,
+                    error,
+                }
+            })
+            .collect();
+
+        // The escrow on the chain the order names, any escrow without a source order
+        let escrow = destinations
+            .iter()
+            .filter(|destination| {
+                source
+                    .order
+                    .as_ref()
+                    .is_none_or(|order| order.destination_chain_id == destination.chain_id)
+            })
+            .find_map(|destination| destination.escrow.as_ref());
+        let mut stage = stage(source.order.as_ref(), escrow);
+        let unreadable = source.error.is_some() || destinations.iter().any(|destination| destination.error.is_some());
+        if stage == Stage::NotFound && unreadable {
+            stage = Stage::Unknown;
+        }
+
+        OrderReport {
+            order_hash: order_hash.to_string(),
+            stage,
+            source,
+            destinations,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::HashMap;
+
+    use async_trait::async_trait;
+    use relayer::{RelayerError, Result};
+    use serde_json::json;
+
+    use super::*;
+
+    pub const HASH: &str = "0x0101010101010101010101010101010101010101010101010101010101010101";
+    pub const NOW: u64 = 1_700_000_000;
+
+    pub struct MockSource(pub HashMap<String, FactoryOrder>);
+
+    #[async_trait]
+    impl OrderSource for MockSource {
+        fn chain_id(&self) -> u64 {
+            11155111
+        }
+
+        async fn order(&self, order_hash: &str) -> Result<Option<FactoryOrder>> {
+            Ok(self.0.get(order_hash).cloned())
+        }
+    }
+
+    pub struct MockChain {
+        pub chain_id: u64,
+        pub escrows: HashMap<String, OrderView>,
+        pub down: bool,
+    }
+
+    #[async_trait]
+    impl EscrowChain for MockChain {
+        fn chain_id(&self) -> u64 {
+            self.chain_id
+        }
+
+        async fn escrow(&self, order_hash: &str) -> Result<Option<OrderView>> {
+            if self.down {
+                return Err(RelayerError::Unavailable(
+                    "http://near returned 503 Service Unavailable".into(),
+                ));
+            }
+            Ok(self.escrows.get(order_hash).cloned())
+        }
+    }
+
+    pub fn factory_order(is_active: bool) -> FactoryOrder {
+        FactoryOrder {
+            maker: format!("0x{}", "aa".repeat(20)),
+            source_token: format!("0x{}", "cc".repeat(20)),
+            source_amount: 1_000,
+            destination_chain_id: 40002,
+            destination_token: "near".into(),
+            destination_amount: 5_000_000_000_000_000_000_000_000,
+            destination_address: "alice.testnet".into(),
+            resolver_fee: 25,
+            expiry_time: NOW + 3_600,
+            is_active,
+        }
+    }
+
+    pub fn order_view(status: &str) -> OrderView {
+        let timelocks = fusion_timelocks::Timelocks::new([60, 1_800, 3_600, 7_200, 0, 0, 0], 0).low_u128();
+        serde_json::from_value(json!({
+            "hashlock": "ab".repeat(32),
+            "maker": "alice.testnet",
+            "resolver": "resolver.testnet",
+            "amount": "5000000000000000000000000",
+            "timelocks": timelocks.to_string(),
+            "created_at": NOW - 100,
+            "status": status,
+            "preimage": (status == "Claimed").then(|| "cd".repeat(32)),
+        }))
+        .unwrap()
+    }
+
+    pub fn service(source: Option<FactoryOrder>, escrow: Option<OrderView>, near_down: bool) -> StatusService {
+        let near = MockChain {
+            chain_id: 40002,
+            escrows: escrow.into_iter().map(|view| (HASH.to_string(), view)).collect(),
+            down: near_down,
+        };
+        let cosmos = MockChain {
+            chain_id: 30002,
+            escrows: HashMap::new(),
+            down: false,
+        };
+        StatusService::new(
+            Box::new(MockSource(
+                source.into_iter().map(|order| (HASH.to_string(), order)).collect(),
+            )),
+            vec![Box::new(near), Box::new(cosmos)],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_stages() {
+        let cases = [
+            (None, None, Stage::NotFound),
+            (Some(factory_order(true)), None, Stage::Announced),
+            (Some(factory_order(false)), None, Stage::Closed),
+            (Some(factory_order(true)), Some("Matched"), Stage::EscrowCreated),
+            (Some(factory_order(true)), Some("Claimed"), Stage::SecretRevealed),
+            (Some(factory_order(false)), Some("Claimed"), Stage::Completed),
+            (None, Some("Claimed"), Stage::Completed),
+            (Some(factory_order(true)), Some("Refunded"), Stage::Refunded),
+        ];
+        for (order, escrow, expected) in cases {
+            let report = service(order, escrow.map(order_view), false).report(HASH, NOW).await;
+            assert_eq!(report.stage, expected, "{:?}", escrow);
+        }
+
+        let mut expired = factory_order(true);
+        expired.expiry_time = NOW;
+        assert_eq!(
+            service(Some(expired), None, false).report(HASH, NOW).await.stage,
+            Stage::Expired
+        );
+    }
+
+    #[tokio::test]
+    async fn test_report() {
+        let report = service(Some(factory_order(true)), Some(order_view("Matched")), false)
+            .report(HASH, NOW)
+            .await;
+        let order = report.source.order.as_ref().unwrap();
+        assert_eq!(report.source.chain, Some("Ethereum Sepolia"));
+        assert_eq!(order.destination_amount, "5000000000000000000000000");
+        assert!(order.active && !order.expired);
+
+        let [near, cosmos] = &report.destinations[..] else {
+            panic!("expected both destinations");
+        };
+        assert!(cosmos.escrow.is_none());
+        let escrow = near.escrow.as_ref().unwrap();
+        assert_eq!(near.chain, Some("NEAR Testnet"));
+        assert_eq!(escrow.window, Window::Withdrawal);
+        assert_eq!(escrow.deadlines.cancellation, NOW - 100 + 3_600);
+        assert_eq!(escrow.resolver.as_deref(), Some("resolver.testnet"));
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_chain() {
+        let report = service(None, None, true).report(HASH, NOW).await;
+        assert_eq!(report.stage, Stage::Unknown);
+        assert!(report.destinations[0].error.as_deref().unwrap().contains("503"));
+        assert!(report.destinations[1].error.is_none());
+
+        // What could be read still decides
+        let report = service(Some(factory_order(true)), None, true).report(HASH, NOW).await;
+        assert_eq!(report.stage, Stage::Announced);
+    }
+
+    #[test]
+    fn test_window() {
+        let deadlines = Deadlines {
+            withdrawal: 10,
+            public_withdrawal: 20,
+            cancellation: 30,
+            public_cancellation: 40,
+        };
+        let windows: Vec<_> = [0, 10, 25, 30, 99]
+            .iter()
+            .map(|now| Window::at(&deadlines, *now))
+            .collect();
+        assert_eq!(
+            windows,
+            [
+                Window::Locked,
+                Window::Withdrawal,
+                Window::PublicWithdrawal,
+                Window::Cancellation,
+                Window::PublicCancellation
+            ]
+        );
+    }
+}
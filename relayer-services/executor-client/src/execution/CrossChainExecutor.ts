@@ -482,8 +482,8 @@ export class CrossChainExecutor extends EventEmitter {
         // In a real implementation, this would:
         // 1. Connect to NEAR wallet
         // 2. Call execute_fusion_order with safety deposit
-        // 3. Call claim_fusion_order with the secret/preimage
-        // 4. Call transfer_to_maker to send tokens to user
+        // 3. Call claim_fusion_order with the secret/preimage, which also
+        //    pays the user and returns the resolver fee + safety deposit
         
         // For now, return simulated transaction hashes
         const mockTransactions = [
@@ -0,0 +1,26 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum NearRpcError {
+    #[error("http error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("rpc error {code}: {message}")]
+    Rpc { code: i64, message: String },
+    #[error("unexpected response: {0}")]
+    Decode(String),
+    /// Endpoint down or overloaded
+    #[error("endpoint unavailable: {0}")]
+    Unavailable(String),
+    #[error("rate limited by {0}")]
+    RateLimited(String),
+    #[error("transaction failed: {0}")]
+    TxFailed(String),
+}
+
+pub type Result<T> = std::result::Result<T, NearRpcError>;
+
+impl NearRpcError {
+    pub fn decode(what: impl std::fmt::Display) -> Self {
+        NearRpcError::Decode(what.to_string())
+    }
+}
@@ -0,0 +1,199 @@
+//! NEAR JSON-RPC client paced for public RPC limits
+//!
+//! [`NearClient`] reads contract views and sends signed transactions
+//! through any [`RpcTransport`]: the relayer's failover client, or a plain
+//! [`HttpTransport`] to one endpoint. Every request keeps to the
+//! [`limits::RateLimits`] it was built with, which may be shared between
+//! clients.
+
+pub mod error;
+pub mod limits;
+pub mod transport;
+
+use std::sync::Arc;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+pub use error::{NearRpcError, Result};
+pub use limits::{NearRateLimitConfig, RateLimits};
+pub use transport::{HttpTransport, RpcTransport};
+
+use limits::ViewCache;
+
+#[derive(Deserialize)]
+struct CallResult {
+    result: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct AccessKeyView {
+    nonce: u64,
+}
+
+/// JSON-RPC access to a NEAR node, paced by [`RateLimits`]
+pub struct NearClient {
+    rpc: Box<dyn RpcTransport>,
+    limits: Arc<RateLimits>,
+}
+
+impl NearClient {
+    pub fn new(rpc: impl RpcTransport + 'static) -> Self {
+        Self::with_limits(rpc, Arc::new(RateLimits::default()))
+    }
+
+    /// Client keeping to `limits` together with every other client sharing them
+    pub fn with_limits(rpc: impl RpcTransport + 'static, limits: Arc<RateLimits>) -> Self {
+        Self {
+            rpc: Box::new(rpc),
+            limits,
+        }
+    }
+
+    /// Send once a token is free, retrying 429s with backoff
+    async fn call<R: DeserializeOwned>(&self, method: &str, params: Value) -> Result<R> {
+        let mut attempt = 0;
+        loop {
+            self.limits.bucket.acquire().await;
+            match self.rpc.call(method, &params).await {
+                Err(NearRpcError::RateLimited(url)) => {
+                    attempt += 1;
+                    let Some(wait) = self.limits.backoff(attempt) else {
+                        return Err(NearRpcError::RateLimited(url));
+                    };
+                    tracing::warn!(%url, method, attempt, ?wait, "rate limited, backing off");
+                    tokio::time::sleep(wait).await;
+                }
+                result => return serde_json::from_value(result?).map_err(NearRpcError::decode),
+            }
+        }
+    }
+
+    /// Call a view method at final finality and decode its JSON result,
+    /// reusing a recent result of the same call
+    pub async fn view<R: DeserializeOwned>(&self, contract_id: &str, method: &str, args: Value) -> Result<R> {
+        let args = args.to_string();
+        let key = ViewCache::key(contract_id, method, &args);
+        let result = match self.limits.views.get(&key) {
+            Some(result) => result,
+            None => {
+                let response: CallResult = self
+                    .call(
+                        "query",
+                        json!({
+                            "request_type": "call_function",
+                            "finality": "final",
+                            "account_id": contract_id,
+                            "method_name": method,
+                            "args_base64": BASE64.encode(&args),
+                        }),
+                    )
+                    .await?;
+                self.limits.views.insert(key, response.result.clone());
+                response.result
+            }
+        };
+        serde_json::from_slice(&result).map_err(NearRpcError::decode)
+    }
+
+    /// Last nonce used with `public_key` on `account_id`
+    pub async fn access_key_nonce(&self, account_id: &str, public_key: &str) -> Result<u64> {
+        let access_key: AccessKeyView = self
+            .call(
+                "query",
+                json!({
+                    "request_type": "view_access_key",
+                    "finality": "final",
+                    "account_id": account_id,
+                    "public_key": public_key,
+                }),
+            )
+            .await?;
+        Ok(access_key.nonce)
+    }
+
+    /// Hash of the latest final block, for transactions to reference
+    pub async fn final_block_hash(&self) -> Result<[u8; 32]> {
+        let block: Value = self.call("block", json!({ "finality": "final" })).await?;
+        block["header"]["hash"]
+            .as_str()
+            .and_then(|hash| bs58::decode(hash).into_vec().ok())
+            .and_then(|hash| hash.try_into().ok())
+            .ok_or_else(|| NearRpcError::decode("invalid block hash"))
+    }
+
+    /// Send a signed transaction and wait for its outcome
+    pub async fn send_transaction(&self, signed: &[u8]) -> Result<()> {
+        let outcome: Value = self.call("broadcast_tx_commit", json!([BASE64.encode(signed)])).await?;
+        // Views cached before no longer reflect the contract
+        self.limits.views.clear();
+        if let Some(failure) = outcome["status"].get("Failure") {
+            return Err(NearRpcError::TxFailed(failure.to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+
+    use super::*;
+
+    /// Answers with `responses` in order
+    struct Scripted {
+        responses: Arc<Mutex<VecDeque<Result<Value>>>>,
+    }
+
+    #[async_trait]
+    impl RpcTransport for Scripted {
+        async fn call(&self, _method: &str, _params: &Value) -> Result<Value> {
+            self.responses.lock().unwrap().pop_front().expect("unexpected request")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_view_retries_rate_limited() {
+        let owner = json!({ "result": b"\"owner.testnet\"".to_vec() });
+        let responses = Arc::new(Mutex::new(VecDeque::from([
+            Err(NearRpcError::RateLimited("rpc".into())),
+            Ok(owner),
+        ])));
+        let limits = Arc::new(RateLimits::new(&NearRateLimitConfig {
+            backoff_ms: 1,
+            view_cache_ms: 60_000,
+            ..NearRateLimitConfig::default()
+        }));
+        let client = NearClient::with_limits(
+            Scripted {
+                responses: responses.clone(),
+            },
+            limits,
+        );
+        let owner: String = client.view("fusion.testnet", "get_owner", json!({})).await.unwrap();
+        assert_eq!(owner, "owner.testnet");
+        // Answered from the cache, the script is used up
+        let owner: String = client.view("fusion.testnet", "get_owner", json!({})).await.unwrap();
+        assert_eq!(owner, "owner.testnet");
+        assert!(responses.lock().unwrap().is_empty());
+
+        responses
+            .lock()
+            .unwrap()
+            .extend([1, 2].map(|_| Err(NearRpcError::RateLimited("rpc".into()))));
+        let limits = Arc::new(RateLimits::new(&NearRateLimitConfig {
+            max_retries: 1,
+            backoff_ms: 1,
+            ..NearRateLimitConfig::default()
+        }));
+        let client = NearClient::with_limits(Scripted { responses }, limits);
+        let result = client.view::<String>("fusion.testnet", "get_owner", json!({})).await;
+        assert!(matches!(result, Err(NearRpcError::RateLimited(_))));
+    }
+}
@@ -0,0 +1,215 @@
+//! Staying under a NEAR RPC provider's rate limit
+//!
+//! Public endpoints allow about a request a second and answer 429 beyond
+//! it. Every request takes a token from a bucket refilled at
+//! `requests_per_minute`, view results are reused for `view_cache_ms`, and
+//! a 429 that survives failover is retried with exponential backoff. The
+//! limits are shared by every client built from them, so separate clients
+//! (one per test, say) still keep to one budget. Only `std` locks are
+//! held, never across an await, so the limits work from any runtime.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+/// Longest wait between retries of a rate-limited request
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Pacing of NEAR RPC requests, the `[near.rate_limit]` table of the
+/// relayer and api-server configs
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct NearRateLimitConfig {
+    pub requests_per_minute: u32,
+    /// Requests that may go out back to back after a quiet spell
+    pub burst: u32,
+    /// How long a view result is reused, 0 to always ask the node
+    pub view_cache_ms: u64,
+    /// Retries of a request still answered with 429 after failover
+    pub max_retries: u32,
+    /// First retry delay, doubled on every further one
+    pub backoff_ms: u64,
+}
+
+impl Default for NearRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: 60,
+            burst: 5,
+            view_cache_ms: 2_000,
+            max_retries: 5,
+            backoff_ms: 1_000,
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Requests allowed per second with bursts of up to `capacity`
+pub struct TokenBucket {
+    capacity: f64,
+    per_second: f64,
+    bucket: Mutex<Bucket>,
+}
+
+impl TokenBucket {
+    pub fn new(requests_per_minute: u32, burst: u32) -> Self {
+        let capacity = burst.max(1) as f64;
+        Self {
+            capacity,
+            per_second: requests_per_minute.max(1) as f64 / 60.0,
+            bucket: Mutex::new(Bucket {
+                tokens: capacity,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Wait for a token and take it
+    pub async fn acquire(&self) {
+        while let Some(wait) = self.try_take(Instant::now()) {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take a token, or how long until one is available
+    fn try_take(&self, now: Instant) -> Option<Duration> {
+        let mut bucket = self.bucket.lock().unwrap();
+        let elapsed = now.saturating_duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.capacity);
+        bucket.refilled_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return None;
+        }
+        Some(Duration::from_secs_f64((1.0 - bucket.tokens) / self.per_second))
+    }
+}
+
+/// Raw view results by contract, method and arguments
+pub struct ViewCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, Vec<u8>)>>,
+}
+
+impl ViewCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn key(contract_id: &str, method: &str, args: &str) -> String {
+        format!("{}/{}/{}", contract_id, method, args)
+    }
+
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let entries = self.entries.lock().unwrap();
+        let (stored_at, result) = entries.get(key)?;
+        (stored_at.elapsed() < self.ttl).then(|| result.clone())
+    }
+
+    pub fn insert(&self, key: String, result: Vec<u8>) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (stored_at, _)| stored_at.elapsed() < self.ttl);
+        entries.insert(key, (Instant::now(), result));
+    }
+
+    /// Forget every result, after a transaction changed contract state
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+/// Everything a [`crate::NearClient`] paces itself with
+pub struct RateLimits {
+    pub bucket: TokenBucket,
+    pub views: ViewCache,
+    max_retries: u32,
+    backoff: Duration,
+}
+
+impl RateLimits {
+    pub fn new(config: &NearRateLimitConfig) -> Self {
+        Self {
+            bucket: TokenBucket::new(config.requests_per_minute, config.burst),
+            views: ViewCache::new(Duration::from_millis(config.view_cache_ms)),
+            max_retries: config.max_retries,
+            backoff: Duration::from_millis(config.backoff_ms),
+        }
+    }
+
+    /// Wait before retry `attempt` (from 1) of a rate-limited request,
+    /// `None` once retries are used up
+    pub fn backoff(&self, attempt: u32) -> Option<Duration> {
+        (attempt <= self.max_retries).then(|| {
+            self.backoff
+                .saturating_mul(1 << (attempt - 1).min(16))
+                .min(MAX_BACKOFF)
+        })
+    }
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self::new(&NearRateLimitConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let bucket = TokenBucket::new(60, 2);
+        let start = Instant::now();
+        assert_eq!(bucket.try_take(start), None);
+        assert_eq!(bucket.try_take(start), None);
+        assert_eq!(bucket.try_take(start), Some(Duration::from_secs(1)));
+        let wait = bucket.try_take(start + Duration::from_millis(500)).unwrap();
+        assert!((wait.as_secs_f64() - 0.5).abs() < 1e-9);
+        assert_eq!(bucket.try_take(start + Duration::from_secs(1)), None);
+        // Refills to the burst size, not beyond
+        let later = start + Duration::from_secs(60);
+        assert_eq!(bucket.try_take(later), None);
+        assert_eq!(bucket.try_take(later), None);
+        assert!(bucket.try_take(later).is_some());
+    }
+
+    #[test]
+    fn test_view_cache() {
+        let cache = ViewCache::new(Duration::from_secs(60));
+        let key = ViewCache::key("fusion.testnet", "get_owner", "{}");
+        assert_eq!(cache.get(&key), None);
+        cache.insert(key.clone(), b"\"owner.testnet\"".to_vec());
+        assert_eq!(cache.get(&key).unwrap(), b"\"owner.testnet\"");
+        assert_eq!(cache.get(&ViewCache::key("fusion.testnet", "get_owner", "{\"a\":1}")), None);
+        cache.clear();
+        assert_eq!(cache.get(&key), None);
+
+        let disabled = ViewCache::new(Duration::ZERO);
+        disabled.insert(key.clone(), Vec::new());
+        assert_eq!(disabled.get(&key), None);
+    }
+
+    #[test]
+    fn test_backoff() {
+        let limits = RateLimits::new(&NearRateLimitConfig {
+            max_retries: 7,
+            backoff_ms: 1_000,
+            ..NearRateLimitConfig::default()
+        });
+        let waits: Vec<_> = (1..=8).map(|attempt| limits.backoff(attempt).map(|wait| wait.as_secs())).collect();
+        assert_eq!(waits, [Some(1), Some(2), Some(4), Some(8), Some(16), Some(30), Some(30), None]);
+    }
+}
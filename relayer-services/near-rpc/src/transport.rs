@@ -0,0 +1,81 @@
+//! Where a [`crate::NearClient`] sends its JSON-RPC requests
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::error::{NearRpcError, Result};
+
+/// JSON-RPC 2.0 endpoint, answering a 429 that should be retried with
+/// [`NearRpcError::RateLimited`]
+#[async_trait]
+pub trait RpcTransport: Send + Sync {
+    async fn call(&self, method: &str, params: &Value) -> Result<Value>;
+}
+
+#[derive(Deserialize)]
+struct Response {
+    result: Option<Value>,
+    error: Option<ErrorObject>,
+}
+
+#[derive(Deserialize)]
+struct ErrorObject {
+    code: i64,
+    message: String,
+    #[serde(default)]
+    data: Option<Value>,
+}
+
+/// A single HTTP endpoint, without failover
+pub struct HttpTransport {
+    url: String,
+    http: reqwest::Client,
+    next_id: AtomicU64,
+}
+
+impl HttpTransport {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            http: reqwest::Client::new(),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+#[async_trait]
+impl RpcTransport for HttpTransport {
+    async fn call(&self, method: &str, params: &Value) -> Result<Value> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        });
+        let response = self.http.post(&self.url).json(&request).send().await?;
+        match response.status().as_u16() {
+            429 => return Err(NearRpcError::RateLimited(self.url.clone())),
+            502..=504 => {
+                let status = response.status();
+                return Err(NearRpcError::Unavailable(format!("{} returned {}", self.url, status)));
+            }
+            _ => {}
+        }
+        let response: Response = response.json().await?;
+        if let Some(error) = response.error {
+            let message = match error.data {
+                Some(data) => format!("{} ({})", error.message, data),
+                None => error.message,
+            };
+            return Err(NearRpcError::Rpc { code: error.code, message });
+        }
+        Ok(response.result.unwrap_or(Value::Null))
+    }
+}
@@ -0,0 +1,160 @@
+use async_trait::async_trait;
+use fusion_core::OrderStatus;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{RelayerError, Result};
+use crate::order::{SourceEvent, SwapOrder};
+
+/// Chain orders are announced on (Ethereum)
+#[async_trait]
+pub trait Source: Send {
+    /// Fusion+ chain ID of the source chain
+    fn chain_id(&self) -> u64;
+
+    /// Events since the last poll, oldest first
+    async fn poll(&mut self) -> Result<Vec<SourceEvent>>;
+
+    /// Reveal `secret` on the source chain so the resolver is paid,
+    /// returns the transaction hash
+    async fn complete_order(&self, order_hash: &str, secret: &[u8; 32]) -> Result<String>;
+
+    /// Whether the order is still open (neither completed nor cancelled)
+    async fn is_active(&self, order_hash: &str) -> Result<bool>;
+
+    /// Newest block whose events have all been reported as final, where a
+    /// restarted relayer can resume from
+    fn finalized_block(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// Chain the relayer mirrors orders to (NEAR, Cosmos)
+#[async_trait]
+pub trait Destination: Send + Sync {
+    /// Fusion+ chain ID this destination serves
+    fn chain_id(&self) -> u64;
+
+    /// Create and fund the destination escrow, returns the transaction hash
+    async fn execute_order(&self, order: &SwapOrder) -> Result<String>;
+
+    /// The destination escrow for `order` as it stands on chain, if created
+    async fn escrow(&self, order: &SwapOrder) -> Result<Option<Escrow>>;
+
+    /// Whether the destination escrow for `order` has been created
+    async fn escrow_exists(&self, order: &SwapOrder) -> Result<bool> {
+        Ok(self.escrow(order).await?.is_some())
+    }
+
+    /// Secret revealed by a claim on the destination escrow, if any
+    async fn revealed_secret(&self, order: &SwapOrder) -> Result<Option<[u8; 32]>> {
+        Ok(self.escrow(order).await?.and_then(|escrow| escrow.preimage))
+    }
+
+    /// Claim the destination escrow for the maker with `secret`, returns the transaction hash
+    async fn claim_order(&self, order: &SwapOrder, secret: &[u8; 32]) -> Result<String>;
+}
+
+/// A destination escrow read back from its contract
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Escrow {
+    pub hashlock: [u8; 32],
+    /// Account the claim pays out to
+    pub maker: String,
+    pub amount: u128,
+    pub status: OrderStatus,
+    /// Set once a claim revealed it
+    pub preimage: Option<[u8; 32]>,
+    /// Unix time from which the maker can be paid
+    pub withdrawal_at: u64,
+    /// Unix time from which the resolver can take the funds back
+    pub cancellation_at: u64,
+}
+
+/// Unix times an escrow's timelock stages begin
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Deadlines {
+    pub withdrawal: u64,
+    pub public_withdrawal: u64,
+    pub cancellation: u64,
+    pub public_cancellation: u64,
+}
+
+/// `get_order` view of the NEAR and CosmWasm Fusion+ contracts
+#[derive(Deserialize, Clone, Debug)]
+pub struct OrderView {
+    pub hashlock: String,
+    pub maker: String,
+    #[serde(default)]
+    pub resolver: Option<String>,
+    pub amount: String,
+    /// Packed 1inch timelocks, decimal
+    pub timelocks: String,
+    /// Unix time the stage offsets count from
+    pub created_at: u64,
+    pub status: OrderStatus,
+    #[serde(default)]
+    pub preimage: Option<String>,
+}
+
+impl OrderView {
+    /// Stage start times; the escrow timelocks use the first four stages
+    /// (withdrawal, public withdrawal, cancellation, public cancellation)
+    pub fn deadlines(&self) -> Result<Deadlines> {
+        let low: u128 = self.timelocks.parse().map_err(RelayerError::decode)?;
+        let offsets = fusion_timelocks::Timelocks::from_u128(low).offsets();
+        let at = |stage: usize| self.created_at + offsets[stage] as u64;
+        Ok(Deadlines {
+            withdrawal: at(0),
+            public_withdrawal: at(1),
+            cancellation: at(2),
+            public_cancellation: at(3),
+        })
+    }
+
+    /// The escrow described by the view
+    pub fn into_escrow(self) -> Result<Escrow> {
+        let deadlines = self.deadlines()?;
+        Ok(Escrow {
+            hashlock: fusion_hashlock::decode_hex32(&self.hashlock)
+                .ok_or_else(|| RelayerError::decode(format!("bad hashlock {}", self.hashlock)))?,
+            maker: self.maker,
+            amount: self.amount.parse().map_err(RelayerError::decode)?,
+            status: self.status,
+            preimage: self.preimage.as_deref().and_then(fusion_hashlock::decode_hex32),
+            withdrawal_at: deadlines.withdrawal,
+            cancellation_at: deadlines.cancellation,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_order_view_into_escrow() {
+        let timelocks = fusion_timelocks::Timelocks::new([60, 1_800, 3_600, 7_200, 0, 0, 0], 0).low_u128();
+        let view: OrderView = serde_json::from_value(json!({
+            "order_hash": "0x01",
+            "hashlock": "ab".repeat(32),
+            "maker": "maker.testnet",
+            "resolver": "resolver.testnet",
+            "amount": "5000",
+            "timelocks": timelocks.to_string(),
+            "created_at": 1_000,
+            "status": "Matched",
+            "preimage": null,
+        }))
+        .unwrap();
+        assert_eq!(view.resolver.as_deref(), Some("resolver.testnet"));
+        assert_eq!(view.deadlines().unwrap().public_cancellation, 8_200);
+        let escrow = view.into_escrow().unwrap();
+        assert_eq!(escrow.hashlock, [0xab; 32]);
+        assert_eq!(escrow.amount, 5_000);
+        assert_eq!(escrow.status, OrderStatus::Matched);
+        assert_eq!(escrow.preimage, None);
+        assert_eq!((escrow.withdrawal_at, escrow.cancellation_at), (1_060, 4_600));
+    }
+}
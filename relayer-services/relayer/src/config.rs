@@ -0,0 +1,359 @@
+//! Relayer settings, read from a TOML file. Keys are not part of it: they
+//! come from the `ETHEREUM_PRIVATE_KEY`, `NEAR_PRIVATE_KEY` (plus optional
+//! comma-separated `NEAR_FUNCTION_CALL_KEYS`) and `COSMOS_PRIVATE_KEY`
+//! environment variables, and the vault's from `VAULT_MASTER_KEY` or
+//! `VAULT_TOKEN`
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use fusion_hashlock::HashAlgorithm;
+use near_rpc::{NearClient, RateLimits};
+use serde::Deserialize;
+
+pub use near_rpc::NearRateLimitConfig;
+
+use crate::error::{RelayerError, Result};
+use crate::provider::Provider;
+use crate::rpc::JsonRpcClient;
+use crate::vault::RevealPolicy;
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Config {
+    #[serde(default)]
+    pub relayer: RelayerConfig,
+    pub ethereum: EthereumConfig,
+    pub near: Option<NearConfig>,
+    pub cosmos: Option<CosmosConfig>,
+    pub vault: Option<VaultConfig>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct RelayerConfig {
+    pub poll_interval_ms: u64,
+    /// Log of in-flight orders, see [`crate::store`]
+    pub state_path: PathBuf,
+    /// Hash the source orders' hashlocks were derived with
+    pub hash_algorithm: HashAlgorithm,
+}
+
+impl Default for RelayerConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_ms: 10_000,
+            state_path: PathBuf::from("relayer-state.jsonl"),
+            hash_algorithm: HashAlgorithm::Sha256,
+        }
+    }
+}
+
+impl RelayerConfig {
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms)
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct EthereumConfig {
+    pub rpc_url: String,
+    /// Tried in order when `rpc_url` fails, see [`crate::provider`]
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
+    #[serde(default)]
+    pub failover: FailoverConfig,
+    pub chain_id: u64,
+    /// `OneInchFusionPlusFactory` address
+    pub factory: String,
+    /// Blocks an event must be buried under before the relayer acts on it,
+    /// reorgs deeper than this are not rolled back
+    #[serde(default = "default_finality_depth")]
+    pub finality_depth: u64,
+    /// First block to scan, the current head when unset
+    #[serde(default)]
+    pub start_block: Option<u64>,
+    /// Largest `eth_getLogs` range, public RPCs cap it
+    #[serde(default = "default_max_block_range")]
+    pub max_block_range: u64,
+    /// Seconds a sent claim may go unmined before it is replaced with
+    /// higher fees, see [`crate::ethereum::broadcaster`]
+    #[serde(default = "default_replace_after_secs")]
+    pub replace_after_secs: u64,
+    /// Percent both fees are raised by per replacement, at least 10
+    #[serde(default = "default_fee_bump_percent")]
+    pub fee_bump_percent: u64,
+    /// Ceiling on `maxFeePerGas`, in gwei
+    #[serde(default = "default_max_fee_gwei")]
+    pub max_fee_gwei: u64,
+    /// Replacements before a claim is left pending
+    #[serde(default = "default_max_replacements")]
+    pub max_replacements: u32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct NearConfig {
+    pub rpc_url: String,
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
+    #[serde(default)]
+    pub failover: FailoverConfig,
+    /// Fusion+ chain ID, 40001 (mainnet) or 40002 (testnet)
+    pub chain_id: u64,
+    pub contract_id: String,
+    /// Resolver account signing the transactions
+    pub account_id: String,
+    /// Stage offsets in seconds (withdrawal, public withdrawal, cancellation,
+    /// public cancellation) given to `execute_fusion_order`
+    pub timelocks: [u32; 4],
+    #[serde(default = "default_near_gas")]
+    pub gas: u64,
+    #[serde(default)]
+    pub rate_limit: NearRateLimitConfig,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct CosmosConfig {
+    /// REST (LCD) endpoint
+    pub rest_url: String,
+    #[serde(default)]
+    pub fallback_rest_urls: Vec<String>,
+    #[serde(default)]
+    pub failover: FailoverConfig,
+    /// Fusion+ chain ID, e.g. 30001
+    pub chain_id: u64,
+    /// Cosmos SDK chain ID, e.g. `pion-1`
+    pub network_id: String,
+    pub contract: String,
+    /// Bech32 account prefix, e.g. `neutron`
+    pub prefix: String,
+    pub denom: String,
+    /// Fee per unit of gas in `denom`, e.g. 0.025
+    pub gas_price: f64,
+    /// Multiplier on simulated gas
+    #[serde(default = "default_gas_adjustment")]
+    pub gas_adjustment: f64,
+    /// Most gas a transaction may use, simulations needing more are refused
+    #[serde(default = "default_cosmos_gas_limit")]
+    pub gas_limit: u64,
+    /// Account paying fees through an `x/feegrant` allowance
+    #[serde(default)]
+    pub fee_granter: Option<String>,
+    /// Seconds until the escrow can be cancelled
+    pub timeout_seconds: u64,
+    /// Safety deposit the contract requires, in basis points of the amount
+    pub min_safety_deposit_bps: u16,
+}
+
+/// When an endpoint is given up on for the next one, see [`crate::provider`]
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct FailoverConfig {
+    /// Transport failures or slow responses in a row before an endpoint is benched
+    pub max_failures: u32,
+    /// Seconds a benched endpoint is skipped before it is probed again
+    pub cooldown_secs: u64,
+    /// Responses slower than this count as failures
+    pub slow_request_ms: u64,
+    pub request_timeout_secs: u64,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            max_failures: 3,
+            cooldown_secs: 30,
+            slow_request_ms: 5_000,
+            request_timeout_secs: 30,
+        }
+    }
+}
+
+impl FailoverConfig {
+    pub fn cooldown(&self) -> Duration {
+        Duration::from_secs(self.cooldown_secs)
+    }
+
+    pub fn slow_request(&self) -> Duration {
+        Duration::from_millis(self.slow_request_ms)
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+}
+
+impl EthereumConfig {
+    pub fn provider(&self) -> Provider {
+        Provider::new(std::iter::once(self.rpc_url.clone()).chain(self.fallback_rpc_urls.clone()), &self.failover)
+    }
+}
+
+impl NearConfig {
+    pub fn provider(&self) -> Provider {
+        Provider::new(std::iter::once(self.rpc_url.clone()).chain(self.fallback_rpc_urls.clone()), &self.failover)
+    }
+
+    /// Client over every configured endpoint, paced by `rate_limit`
+    pub fn client(&self) -> NearClient {
+        let rpc = JsonRpcClient::with_failover(self.provider(), "status");
+        NearClient::with_limits(rpc, Arc::new(RateLimits::new(&self.rate_limit)))
+    }
+}
+
+impl CosmosConfig {
+    pub fn provider(&self) -> Provider {
+        let urls = std::iter::once(&self.rest_url).chain(&self.fallback_rest_urls);
+        Provider::new(urls.map(|url| url.trim_end_matches('/').to_string()), &self.failover)
+    }
+}
+
+/// Encrypted preimage store, see [`crate::vault`]
+#[derive(Deserialize, Clone, Debug)]
+pub struct VaultConfig {
+    #[serde(default = "default_vault_path")]
+    pub path: PathBuf,
+    #[serde(default = "default_audit_path")]
+    pub audit_path: PathBuf,
+    /// HashiCorp Vault address whose transit engine wraps the data key
+    /// (token in `VAULT_TOKEN`); without it the key is wrapped locally with
+    /// `VAULT_MASTER_KEY`
+    #[serde(default)]
+    pub transit_url: Option<String>,
+    /// Transit key name
+    #[serde(default = "default_transit_key")]
+    pub transit_key: String,
+    #[serde(default)]
+    pub reveal: RevealPolicy,
+}
+
+fn default_vault_path() -> PathBuf {
+    PathBuf::from("relayer-vault.jsonl")
+}
+
+fn default_audit_path() -> PathBuf {
+    PathBuf::from("relayer-vault-audit.jsonl")
+}
+
+fn default_transit_key() -> String {
+    "fusion-relayer".into()
+}
+
+fn default_finality_depth() -> u64 {
+    12
+}
+
+fn default_max_block_range() -> u64 {
+    2_000
+}
+
+fn default_replace_after_secs() -> u64 {
+    60
+}
+
+fn default_fee_bump_percent() -> u64 {
+    25
+}
+
+fn default_max_fee_gwei() -> u64 {
+    500
+}
+
+fn default_max_replacements() -> u32 {
+    5
+}
+
+fn default_near_gas() -> u64 {
+    100_000_000_000_000
+}
+
+fn default_gas_adjustment() -> f64 {
+    1.3
+}
+
+fn default_cosmos_gas_limit() -> u64 {
+    400_000
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| RelayerError::Config(format!("{}: {}", path.display(), error)))?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        let config: Config = toml::from_str(contents).map_err(|error| RelayerError::Config(error.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// How long a finished order's actions are kept in the store: until the
+    /// last timelock of the escrows the relayer creates
+    pub fn action_retention(&self) -> Duration {
+        let near = self.near.as_ref().map_or(0, |near| near.timelocks[3] as u64);
+        let cosmos = self.cosmos.as_ref().map_or(0, |cosmos| cosmos.timeout_seconds);
+        Duration::from_secs(near.max(cosmos))
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.near.is_none() && self.cosmos.is_none() {
+            return Err(RelayerError::Config("configure at least one of [near] and [cosmos]".into()));
+        }
+        if self.ethereum.fee_bump_percent < 10 {
+            return Err(RelayerError::Config("ethereum.fee_bump_percent must be at least 10".into()));
+        }
+        if let Some(near) = &self.near {
+            check_family(near.chain_id, fusion_core::ChainFamily::Near)?;
+        }
+        if let Some(cosmos) = &self.cosmos {
+            check_family(cosmos.chain_id, fusion_core::ChainFamily::Cosmos)?;
+        }
+        Ok(())
+    }
+}
+
+fn check_family(chain_id: u64, family: fusion_core::ChainFamily) -> Result<()> {
+    match fusion_core::chain(chain_id) {
+        Some((_, actual)) if actual == family => Ok(()),
+        _ => Err(RelayerError::Config(format!("chain {} is not a known {:?} chain", chain_id, family))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../relayer.example.toml");
+
+    #[test]
+    fn test_example_config() {
+        let config = Config::parse(EXAMPLE).unwrap();
+        assert_eq!(config.ethereum.chain_id, 11155111);
+        assert_eq!(config.ethereum.finality_depth, 12);
+        assert_eq!(config.ethereum.fee_bump_percent, 25);
+        assert_eq!(config.ethereum.fallback_rpc_urls.len(), 2);
+        assert_eq!(config.ethereum.provider().metrics().len(), 3);
+        let near = config.near.unwrap();
+        assert_eq!(near.chain_id, 40002);
+        assert_eq!((near.failover.slow_request_ms, near.failover.max_failures), (15_000, 3));
+        assert_eq!(near.rate_limit.requests_per_minute, 60);
+        assert_eq!(config.cosmos.unwrap().prefix, "neutron");
+        assert_eq!(config.relayer.hash_algorithm, HashAlgorithm::Sha256);
+        let vault = config.vault.unwrap();
+        assert_eq!(vault.transit_url, None);
+        assert_eq!(vault.reveal, RevealPolicy::default());
+    }
+
+    #[test]
+    fn test_rejects_wrong_chain_family() {
+        let config = EXAMPLE.replace("chain_id = 40002", "chain_id = 30001");
+        assert!(matches!(Config::parse(&config), Err(RelayerError::Config(_))));
+    }
+
+    #[test]
+    fn test_rejects_small_fee_bump() {
+        let config = EXAMPLE.replace("fee_bump_percent = 25", "fee_bump_percent = 5");
+        assert!(matches!(Config::parse(&config), Err(RelayerError::Config(_))));
+    }
+}
@@ -0,0 +1,70 @@
+//! BIP-173 bech32 encoding, enough to derive account addresses
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+fn polymod(values: impl IntoIterator<Item = u8>) -> u32 {
+    values.into_iter().fold(1, |checksum, value| {
+        let top = checksum >> 25;
+        let mut checksum = ((checksum & 0x1ff_ffff) << 5) ^ value as u32;
+        for (bit, generator) in GENERATOR.iter().enumerate() {
+            if (top >> bit) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+        checksum
+    })
+}
+
+fn expand_hrp(hrp: &str) -> impl Iterator<Item = u8> + '_ {
+    hrp.bytes().map(|b| b >> 5).chain([0]).chain(hrp.bytes().map(|b| b & 31))
+}
+
+/// Regroup 8-bit bytes into 5-bit words, zero-padding the last one
+fn to_words(data: &[u8]) -> Vec<u8> {
+    let mut words = Vec::with_capacity(data.len() * 8 / 5 + 1);
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for &byte in data {
+        acc = (acc << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            words.push(((acc >> bits) & 31) as u8);
+        }
+    }
+    if bits > 0 {
+        words.push(((acc << (5 - bits)) & 31) as u8);
+    }
+    words
+}
+
+/// Encode `data` under the lowercase human-readable part `hrp`
+pub fn encode(hrp: &str, data: &[u8]) -> String {
+    let words = to_words(data);
+    let checksum = polymod(expand_hrp(hrp).chain(words.iter().copied()).chain([0; 6])) ^ 1;
+    let checksum_words = (0..6).map(|i| ((checksum >> (5 * (5 - i))) & 31) as u8);
+    let mut address = format!("{}1", hrp);
+    address.extend(words.into_iter().chain(checksum_words).map(|word| CHARSET[word as usize] as char));
+    address
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bip173_vectors() {
+        // Valid checksums from BIP-173 with no data
+        assert_eq!(encode("a", &[]), "a12uel5l");
+        assert_eq!(
+            encode("abcdef", &[0x00, 0x44, 0x32, 0x14, 0xc7, 0x42, 0x54, 0xb6, 0x35, 0xcf, 0x84, 0x65, 0x3a, 0x56, 0xd7, 0xc6, 0x75, 0xbe, 0x77, 0xdf]),
+            "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw"
+        );
+    }
+
+    #[test]
+    fn test_cosmos_address() {
+        // cosmos1 address of the all-zero 20-byte hash
+        assert_eq!(encode("cosmos", &[0; 20]), "cosmos1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqnrql8a");
+    }
+}
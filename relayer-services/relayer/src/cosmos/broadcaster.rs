@@ -0,0 +1,192 @@
+//! Signs and broadcasts contract executions for one account
+//!
+//! Broadcasts are serialized and the account sequence is tracked locally,
+//! so back-to-back executions don't wait for a block between them. Gas is
+//! simulated per transaction. A sequence mismatch (another client used the
+//! account, or a transaction was dropped from the mempool) resyncs the
+//! sequence and retries.
+
+use tokio::sync::Mutex;
+
+use super::tx::{Coin, CosmosSigner, Fee, MsgExecuteContract};
+use super::CosmosClient;
+use crate::config::CosmosConfig;
+use crate::error::{RelayerError, Result};
+
+/// Broadcast attempts before a sequence mismatch is given up on
+const MAX_ATTEMPTS: usize = 4;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Account {
+    number: u64,
+    sequence: u64,
+}
+
+pub struct Broadcaster {
+    client: CosmosClient,
+    signer: CosmosSigner,
+    network_id: String,
+    denom: String,
+    gas_price: f64,
+    gas_adjustment: f64,
+    max_gas: u64,
+    fee_granter: Option<String>,
+    /// `None` until fetched, and again after an error that leaves the
+    /// sequence unknown
+    account: Mutex<Option<Account>>,
+}
+
+impl Broadcaster {
+    pub fn new(client: CosmosClient, signer: CosmosSigner, config: &CosmosConfig) -> Self {
+        Self {
+            client,
+            signer,
+            network_id: config.network_id.clone(),
+            denom: config.denom.clone(),
+            gas_price: config.gas_price,
+            gas_adjustment: config.gas_adjustment,
+            max_gas: config.gas_limit,
+            fee_granter: config.fee_granter.clone(),
+            account: Mutex::new(None),
+        }
+    }
+
+    pub fn client(&self) -> &CosmosClient {
+        &self.client
+    }
+
+    pub fn address(&self) -> &str {
+        &self.signer.address
+    }
+
+    /// Execute `msg` on `contract` with `funds` attached, returns the
+    /// transaction hash once it passed `CheckTx`
+    pub async fn execute(&self, contract: &str, msg: serde_json::Value, funds: Vec<Coin>) -> Result<String> {
+        let msg = MsgExecuteContract {
+            sender: self.signer.address.clone(),
+            contract: contract.to_string(),
+            msg: msg.to_string().into_bytes(),
+            funds,
+        };
+
+        let mut account = self.account.lock().await;
+        let mut attempt = 1;
+        loop {
+            let current = match *account {
+                Some(current) => current,
+                None => {
+                    let (number, sequence) = self.client.account(&self.signer.address).await?;
+                    Account { number, sequence }
+                }
+            };
+            match self.sign_and_broadcast(&msg, current).await {
+                Ok(txhash) => {
+                    *account = Some(Account {
+                        sequence: current.sequence + 1,
+                        ..current
+                    });
+                    return Ok(txhash);
+                }
+                Err(error) => {
+                    let Some(message) = sequence_mismatch(&error) else {
+                        // Rejected transactions don't consume the sequence,
+                        // transport errors leave it unknown
+                        *account = match error {
+                            RelayerError::TxFailed(_) | RelayerError::Rpc { .. } => Some(current),
+                            _ => None,
+                        };
+                        return Err(error);
+                    };
+                    *account = expected_sequence(message).map(|sequence| Account { sequence, ..current });
+                    if attempt == MAX_ATTEMPTS {
+                        return Err(error);
+                    }
+                    tracing::warn!(address = %self.signer.address, attempt, %error, "sequence mismatch, retrying");
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    async fn sign_and_broadcast(&self, msg: &MsgExecuteContract, account: Account) -> Result<String> {
+        let probe = self.sign(msg, self.max_gas, account);
+        let gas_used = self.client.simulate(&probe).await?;
+        let gas = adjusted_gas(gas_used, self.gas_adjustment);
+        if gas > self.max_gas {
+            return Err(RelayerError::TxFailed(format!(
+                "needs {} gas, above the configured limit of {}",
+                gas, self.max_gas
+            )));
+        }
+        self.client.broadcast(&self.sign(msg, gas, account)).await
+    }
+
+    fn sign(&self, msg: &MsgExecuteContract, gas_limit: u64, account: Account) -> Vec<u8> {
+        let fee = Fee {
+            amount: vec![Coin {
+                denom: self.denom.clone(),
+                amount: fee_amount(gas_limit, self.gas_price).to_string(),
+            }],
+            gas_limit,
+            granter: self.fee_granter.clone().unwrap_or_default(),
+        };
+        self.signer
+            .sign(msg.clone(), fee, &self.network_id, account.number, account.sequence)
+    }
+}
+
+fn adjusted_gas(gas_used: u64, adjustment: f64) -> u64 {
+    (gas_used as f64 * adjustment).ceil() as u64
+}
+
+fn fee_amount(gas_limit: u64, gas_price: f64) -> u128 {
+    (gas_limit as f64 * gas_price).ceil() as u128
+}
+
+/// Message of an "account sequence mismatch" error, from `CheckTx` or from
+/// simulation
+fn sequence_mismatch(error: &RelayerError) -> Option<&str> {
+    let message = match error {
+        RelayerError::TxFailed(message) | RelayerError::Rpc { message, .. } => message,
+        _ => return None,
+    };
+    message.contains("account sequence mismatch").then_some(message.as_str())
+}
+
+/// `N` from "account sequence mismatch, expected N, got M"
+fn expected_sequence(message: &str) -> Option<u64> {
+    let rest = &message[message.find("expected ")? + "expected ".len()..];
+    rest.split(|c: char| !c.is_ascii_digit()).next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence_mismatch() {
+        let error = RelayerError::TxFailed(
+            "account sequence mismatch, expected 17, got 15: incorrect account sequence".into(),
+        );
+        let message = sequence_mismatch(&error).unwrap();
+        assert_eq!(expected_sequence(message), Some(17));
+
+        let simulation = RelayerError::Rpc {
+            code: 32,
+            message: "account sequence mismatch, expected 3, got 2: incorrect account sequence [cosmos/cosmos-sdk@v0.47.10/x/auth/ante/sigverify.go:269] with gas used: '35369'".into(),
+        };
+        assert_eq!(sequence_mismatch(&simulation).and_then(expected_sequence), Some(3));
+
+        assert!(sequence_mismatch(&RelayerError::TxFailed("out of gas".into())).is_none());
+        assert!(sequence_mismatch(&RelayerError::Decode("account sequence mismatch".into())).is_none());
+        assert_eq!(expected_sequence("account sequence mismatch"), None);
+    }
+
+    #[test]
+    fn test_gas_and_fee() {
+        assert_eq!(adjusted_gas(100_000, 1.3), 130_000);
+        assert_eq!(adjusted_gas(1, 1.5), 2);
+        assert_eq!(fee_amount(130_000, 0.025), 3_250);
+        assert_eq!(fee_amount(1, 0.025), 1);
+    }
+}
@@ -0,0 +1,256 @@
+//! Cosmos side: creates `execute_fusion_order` escrows on the CosmWasm
+//! Fusion+ contract through a chain's REST (LCD) endpoint
+
+pub mod bech32;
+pub mod broadcaster;
+pub mod tx;
+
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use secp256k1::SecretKey;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::chain::{Destination, Escrow, OrderView};
+use crate::config::CosmosConfig;
+use crate::error::{RelayerError, Result};
+use crate::order::SwapOrder;
+use crate::provider::{status_error, Provider};
+use broadcaster::Broadcaster;
+use tx::{Coin, CosmosSigner};
+
+#[derive(Deserialize)]
+struct AccountResponse {
+    account: BaseAccount,
+}
+
+#[derive(Deserialize)]
+struct BaseAccount {
+    account_number: String,
+    sequence: String,
+}
+
+#[derive(Deserialize)]
+struct BroadcastResponse {
+    tx_response: TxResponse,
+}
+
+#[derive(Deserialize)]
+struct TxResponse {
+    txhash: String,
+    code: u32,
+    #[serde(default)]
+    raw_log: String,
+}
+
+#[derive(Deserialize)]
+struct SimulateResponse {
+    gas_info: GasInfo,
+}
+
+#[derive(Deserialize)]
+struct GasInfo {
+    gas_used: String,
+}
+
+#[derive(Deserialize)]
+struct SmartQueryResponse<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+struct SyncingResponse {
+    syncing: bool,
+}
+
+/// gRPC-gateway error body
+#[derive(Deserialize)]
+struct ErrorResponse {
+    code: i64,
+    message: String,
+}
+
+/// Cosmos SDK REST (LCD) access
+/// Cosmos SDK REST (LCD) access, failing over across the provider's
+/// endpoints
+pub struct CosmosClient {
+    provider: Provider,
+}
+
+impl CosmosClient {
+    pub fn new(provider: Provider) -> Self {
+        Self { provider }
+    }
+
+    pub fn provider(&self) -> &Provider {
+        &self.provider
+    }
+
+    async fn decode<R: DeserializeOwned>(response: reqwest::Response) -> Result<R> {
+        if let Some(error) = status_error(response.url().as_str(), response.status()) {
+            return Err(error);
+        }
+        if !response.status().is_success() {
+            let error: ErrorResponse = response.json().await?;
+            return Err(RelayerError::Rpc {
+                code: error.code,
+                message: error.message,
+            });
+        }
+        Ok(response.json().await?)
+    }
+
+    async fn get<R: DeserializeOwned>(&self, path: &str) -> Result<R> {
+        let http = self.provider.http();
+        self.provider
+            .request(
+                |url| async move { Self::decode(http.get(format!("{}{}", url, path)).send().await?).await },
+                |url| self.probe(url),
+            )
+            .await
+    }
+
+    async fn post<R: DeserializeOwned>(&self, path: &str, body: Value) -> Result<R> {
+        let (http, body) = (self.provider.http(), &body);
+        self.provider
+            .request(
+                |url| async move { Self::decode(http.post(format!("{}{}", url, path)).json(body).send().await?).await },
+                |url| self.probe(url),
+            )
+            .await
+    }
+
+    /// Health check of a benched endpoint: reachable and caught up
+    async fn probe(&self, url: String) -> Result<()> {
+        let response = self
+            .provider
+            .http()
+            .get(format!("{}/cosmos/base/tendermint/v1beta1/syncing", url))
+            .send()
+            .await?;
+        let syncing: SyncingResponse = Self::decode(response).await?;
+        if syncing.syncing {
+            return Err(RelayerError::Unavailable(format!("{} is still syncing", url)));
+        }
+        Ok(())
+    }
+
+    /// Account number and sequence of `address`
+    pub async fn account(&self, address: &str) -> Result<(u64, u64)> {
+        let response: AccountResponse = self.get(&format!("/cosmos/auth/v1beta1/accounts/{}", address)).await?;
+        Ok((
+            response.account.account_number.parse().map_err(RelayerError::decode)?,
+            response.account.sequence.parse().map_err(RelayerError::decode)?,
+        ))
+    }
+
+    /// Smart query against `contract`
+    pub async fn query<R: DeserializeOwned>(&self, contract: &str, query: Value) -> Result<R> {
+        let path = format!("/cosmwasm/wasm/v1/contract/{}/smart/{}", contract, BASE64.encode(query.to_string()));
+        let response: SmartQueryResponse<R> = self.get(&path).await?;
+        Ok(response.data)
+    }
+
+    /// The contract's `get_order` view of `order_hash`, `None` before the
+    /// escrow is created
+    pub async fn order(&self, contract: &str, order_hash: &str) -> Result<Option<OrderView>> {
+        self.query(contract, json!({ "get_order": { "order_hash": order_hash } })).await
+    }
+
+    /// Gas a signed transaction would use
+    pub async fn simulate(&self, tx_bytes: &[u8]) -> Result<u64> {
+        let response: SimulateResponse = self
+            .post("/cosmos/tx/v1beta1/simulate", json!({ "tx_bytes": BASE64.encode(tx_bytes) }))
+            .await?;
+        response.gas_info.gas_used.parse().map_err(RelayerError::decode)
+    }
+
+    /// Broadcast a signed transaction, returns its hash once it passed `CheckTx`
+    pub async fn broadcast(&self, tx_bytes: &[u8]) -> Result<String> {
+        let response: BroadcastResponse = self
+            .post(
+                "/cosmos/tx/v1beta1/txs",
+                json!({ "tx_bytes": BASE64.encode(tx_bytes), "mode": "BROADCAST_MODE_SYNC" }),
+            )
+            .await?;
+        if response.tx_response.code != 0 {
+            return Err(RelayerError::TxFailed(response.tx_response.raw_log));
+        }
+        Ok(response.tx_response.txhash)
+    }
+}
+
+/// CosmWasm Fusion+ contract as a relayer destination
+pub struct CosmosDestination {
+    broadcaster: Broadcaster,
+    config: CosmosConfig,
+    /// Fusion+ chain ID orders come from
+    source_chain_id: u64,
+}
+
+impl CosmosDestination {
+    /// `private_key` is the hex-encoded secp256k1 account key
+    pub fn new(config: CosmosConfig, source_chain_id: u64, private_key: &str) -> Result<Self> {
+        let key_bytes = hex::decode(private_key.trim_start_matches("0x")).map_err(|e| RelayerError::Key(e.to_string()))?;
+        let key = SecretKey::from_slice(&key_bytes).map_err(|e| RelayerError::Key(e.to_string()))?;
+        let broadcaster = Broadcaster::new(
+            CosmosClient::new(config.provider()),
+            CosmosSigner::new(key, &config.prefix),
+            &config,
+        );
+        Ok(Self {
+            broadcaster,
+            config,
+            source_chain_id,
+        })
+    }
+
+    pub fn address(&self) -> &str {
+        self.broadcaster.address()
+    }
+
+    fn coin(&self, amount: u128) -> Coin {
+        Coin {
+            denom: self.config.denom.clone(),
+            amount: amount.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Destination for CosmosDestination {
+    fn chain_id(&self) -> u64 {
+        self.config.chain_id
+    }
+
+    async fn execute_order(&self, order: &SwapOrder) -> Result<String> {
+        let safety_deposit = order.destination_amount * self.config.min_safety_deposit_bps as u128 / 10_000;
+        let funds = vec![self.coin(order.destination_amount + order.resolver_fee + safety_deposit)];
+        let msg = json!({
+            "execute_fusion_order": {
+                "order_hash": order.order_hash,
+                "hashlock": order.hashlock_hex(),
+                "maker": order.destination_address,
+                "amount": order.destination_amount.to_string(),
+                "resolver_fee": order.resolver_fee.to_string(),
+                "source_chain_id": self.source_chain_id,
+                "timeout_seconds": self.config.timeout_seconds,
+            }
+        });
+        self.broadcaster.execute(&self.config.contract, msg, funds).await
+    }
+
+    async fn escrow(&self, order: &SwapOrder) -> Result<Option<Escrow>> {
+        let view = self.broadcaster.client().order(&self.config.contract, &order.order_hash).await?;
+        view.map(OrderView::into_escrow).transpose()
+    }
+
+    async fn claim_order(&self, order: &SwapOrder, secret: &[u8; 32]) -> Result<String> {
+        let msg = json!({
+            "claim_fusion_order": { "order_hash": order.order_hash, "preimage": hex::encode(secret) }
+        });
+        self.broadcaster.execute(&self.config.contract, msg, Vec::new()).await
+    }
+}
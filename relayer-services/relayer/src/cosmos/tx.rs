@@ -0,0 +1,233 @@
+//! Protobuf messages for a `MsgExecuteContract` transaction signed in
+//! `SIGN_MODE_DIRECT` with a secp256k1 account key
+
+use prost::Message;
+use ripemd::Ripemd160;
+use secp256k1::{Message as SecpMessage, PublicKey, Secp256k1, SecretKey};
+use sha2::{Digest, Sha256};
+
+use super::bech32;
+
+const SIGN_MODE_DIRECT: i32 = 1;
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Any {
+    #[prost(string, tag = "1")]
+    pub type_url: String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub value: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Coin {
+    #[prost(string, tag = "1")]
+    pub denom: String,
+    #[prost(string, tag = "2")]
+    pub amount: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgExecuteContract {
+    #[prost(string, tag = "1")]
+    pub sender: String,
+    #[prost(string, tag = "2")]
+    pub contract: String,
+    #[prost(bytes = "vec", tag = "3")]
+    pub msg: Vec<u8>,
+    #[prost(message, repeated, tag = "5")]
+    pub funds: Vec<Coin>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Secp256k1PubKey {
+    #[prost(bytes = "vec", tag = "1")]
+    key: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct TxBody {
+    #[prost(message, repeated, tag = "1")]
+    messages: Vec<Any>,
+    #[prost(string, tag = "2")]
+    memo: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Single {
+    #[prost(int32, tag = "1")]
+    mode: i32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ModeInfo {
+    #[prost(message, optional, tag = "1")]
+    single: Option<Single>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct SignerInfo {
+    #[prost(message, optional, tag = "1")]
+    public_key: Option<Any>,
+    #[prost(message, optional, tag = "2")]
+    mode_info: Option<ModeInfo>,
+    #[prost(uint64, tag = "3")]
+    sequence: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Fee {
+    #[prost(message, repeated, tag = "1")]
+    pub amount: Vec<Coin>,
+    #[prost(uint64, tag = "2")]
+    pub gas_limit: u64,
+    /// Account whose fee grant pays the fee, empty for the signer
+    #[prost(string, tag = "4")]
+    pub granter: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct AuthInfo {
+    #[prost(message, repeated, tag = "1")]
+    signer_infos: Vec<SignerInfo>,
+    #[prost(message, optional, tag = "2")]
+    fee: Option<Fee>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct SignDoc {
+    #[prost(bytes = "vec", tag = "1")]
+    body_bytes: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    auth_info_bytes: Vec<u8>,
+    #[prost(string, tag = "3")]
+    chain_id: String,
+    #[prost(uint64, tag = "4")]
+    account_number: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct TxRaw {
+    #[prost(bytes = "vec", tag = "1")]
+    body_bytes: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    auth_info_bytes: Vec<u8>,
+    #[prost(bytes = "vec", repeated, tag = "3")]
+    signatures: Vec<Vec<u8>>,
+}
+
+/// Account key signing Cosmos SDK transactions
+pub struct CosmosSigner {
+    key: SecretKey,
+    public_key: [u8; 33],
+    pub address: String,
+}
+
+impl CosmosSigner {
+    pub fn new(key: SecretKey, prefix: &str) -> Self {
+        let public_key = PublicKey::from_secret_key(&Secp256k1::signing_only(), &key).serialize();
+        let account = Ripemd160::digest(Sha256::digest(public_key));
+        Self {
+            key,
+            public_key,
+            address: bech32::encode(prefix, &account),
+        }
+    }
+
+    /// `TxRaw` bytes for `msg`, ready for `/cosmos/tx/v1beta1/txs`
+    pub fn sign(&self, msg: MsgExecuteContract, fee: Fee, chain_id: &str, account_number: u64, sequence: u64) -> Vec<u8> {
+        let body = TxBody {
+            messages: vec![Any {
+                type_url: "/cosmwasm.wasm.v1.MsgExecuteContract".into(),
+                value: msg.encode_to_vec(),
+            }],
+            memo: String::new(),
+        };
+        let auth_info = AuthInfo {
+            signer_infos: vec![SignerInfo {
+                public_key: Some(Any {
+                    type_url: "/cosmos.crypto.secp256k1.PubKey".into(),
+                    value: Secp256k1PubKey { key: self.public_key.to_vec() }.encode_to_vec(),
+                }),
+                mode_info: Some(ModeInfo {
+                    single: Some(Single { mode: SIGN_MODE_DIRECT }),
+                }),
+                sequence,
+            }],
+            fee: Some(fee),
+        };
+        let sign_doc = SignDoc {
+            body_bytes: body.encode_to_vec(),
+            auth_info_bytes: auth_info.encode_to_vec(),
+            chain_id: chain_id.to_string(),
+            account_number,
+        };
+        let digest: [u8; 32] = Sha256::digest(sign_doc.encode_to_vec()).into();
+        // libsecp256k1 signatures are already low-S, as the SDK requires
+        let signature = Secp256k1::signing_only()
+            .sign_ecdsa(&SecpMessage::from_slice(&digest).expect("32-byte digest"), &self.key)
+            .serialize_compact();
+        TxRaw {
+            body_bytes: sign_doc.body_bytes,
+            auth_info_bytes: sign_doc.auth_info_bytes,
+            signatures: vec![signature.to_vec()],
+        }
+        .encode_to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::ecdsa::Signature;
+
+    fn signer() -> CosmosSigner {
+        CosmosSigner::new(SecretKey::from_slice(&[1; 32]).unwrap(), "neutron")
+    }
+
+    #[test]
+    fn test_address() {
+        let signer = signer();
+        assert!(signer.address.starts_with("neutron1"));
+        // 20-byte account hash: 32 data characters plus 6 checksum characters
+        assert_eq!(signer.address.len(), "neutron1".len() + 32 + 6);
+    }
+
+    #[test]
+    fn test_signature_covers_sign_doc() {
+        let signer = signer();
+        let msg = MsgExecuteContract {
+            sender: signer.address.clone(),
+            contract: "neutron1contract".into(),
+            msg: br#"{"claim_fusion_order":{}}"#.to_vec(),
+            funds: vec![Coin {
+                denom: "untrn".into(),
+                amount: "10".into(),
+            }],
+        };
+        let fee = Fee {
+            amount: vec![],
+            gas_limit: 200_000,
+            granter: "neutron1granter".into(),
+        };
+        let raw = TxRaw::decode(signer.sign(msg.clone(), fee, "pion-1", 7, 3).as_slice()).unwrap();
+
+        let body = TxBody::decode(raw.body_bytes.as_slice()).unwrap();
+        assert_eq!(MsgExecuteContract::decode(body.messages[0].value.as_slice()).unwrap(), msg);
+        let auth_info = AuthInfo::decode(raw.auth_info_bytes.as_slice()).unwrap();
+        assert_eq!(auth_info.signer_infos[0].sequence, 3);
+        assert_eq!(auth_info.fee.unwrap().granter, "neutron1granter");
+
+        let sign_doc = SignDoc {
+            body_bytes: raw.body_bytes.clone(),
+            auth_info_bytes: raw.auth_info_bytes.clone(),
+            chain_id: "pion-1".into(),
+            account_number: 7,
+        };
+        let digest: [u8; 32] = Sha256::digest(sign_doc.encode_to_vec()).into();
+        let signature = Signature::from_compact(&raw.signatures[0]).unwrap();
+        let public_key = PublicKey::from_slice(&signer.public_key).unwrap();
+        assert!(Secp256k1::verification_only()
+            .verify_ecdsa(&SecpMessage::from_slice(&digest).unwrap(), &signature, &public_key)
+            .is_ok());
+    }
+}
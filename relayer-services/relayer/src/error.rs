@@ -0,0 +1,51 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RelayerError {
+    #[error("invalid config: {0}")]
+    Config(String),
+    #[error("invalid key: {0}")]
+    Key(String),
+    #[error("http error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("rpc error {code}: {message}")]
+    Rpc { code: i64, message: String },
+    #[error("unexpected response: {0}")]
+    Decode(String),
+    /// Endpoint down or overloaded, see [`crate::provider`]
+    #[error("endpoint unavailable: {0}")]
+    Unavailable(String),
+    #[error("rate limited by {0}")]
+    RateLimited(String),
+    #[error("transaction failed: {0}")]
+    TxFailed(String),
+    /// Sent but not seen mined, may still land
+    #[error("transaction not mined: {0}")]
+    NotMined(String),
+    #[error("state store error: {0}")]
+    Store(String),
+    #[error("secret vault error: {0}")]
+    Vault(String),
+}
+
+pub type Result<T> = std::result::Result<T, RelayerError>;
+
+impl From<near_rpc::NearRpcError> for RelayerError {
+    fn from(error: near_rpc::NearRpcError) -> Self {
+        use near_rpc::NearRpcError;
+        match error {
+            NearRpcError::Http(error) => RelayerError::Http(error),
+            NearRpcError::Rpc { code, message } => RelayerError::Rpc { code, message },
+            NearRpcError::Decode(message) => RelayerError::Decode(message),
+            NearRpcError::Unavailable(message) => RelayerError::Unavailable(message),
+            NearRpcError::RateLimited(url) => RelayerError::RateLimited(url),
+            NearRpcError::TxFailed(message) => RelayerError::TxFailed(message),
+        }
+    }
+}
+
+impl RelayerError {
+    pub fn decode(what: impl std::fmt::Display) -> Self {
+        RelayerError::Decode(what.to_string())
+    }
+}
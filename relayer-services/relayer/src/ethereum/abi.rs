@@ -0,0 +1,144 @@
+//! Just enough Solidity ABI for the factory's events and calls
+
+use sha3::{Digest, Keccak256};
+
+use crate::error::{RelayerError, Result};
+
+pub type Word = [u8; 32];
+
+pub fn keccak256(data: &[u8]) -> Word {
+    Keccak256::digest(data).into()
+}
+
+/// Topic of an event, e.g. `Transfer(address,address,uint256)`
+pub fn event_topic(signature: &str) -> Word {
+    keccak256(signature.as_bytes())
+}
+
+/// 4-byte selector of a function, e.g. `transfer(address,uint256)`
+pub fn selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Calldata for a function taking static arguments only
+pub fn encode_call(signature: &str, words: &[Word]) -> Vec<u8> {
+    let mut data = selector(signature).to_vec();
+    for word in words {
+        data.extend_from_slice(word);
+    }
+    data
+}
+
+pub fn address_word(address: &[u8; 20]) -> Word {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(address);
+    word
+}
+
+/// Reads head words and dynamic `bytes` out of ABI-encoded data
+pub struct Decoder<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub fn word(&self, index: usize) -> Result<Word> {
+        self.data
+            .get(index * 32..(index + 1) * 32)
+            .map(|word| word.try_into().unwrap())
+            .ok_or_else(|| RelayerError::decode(format!("ABI data too short for word {}", index)))
+    }
+
+    pub fn address(&self, index: usize) -> Result<String> {
+        Ok(format!("0x{}", hex::encode(&self.word(index)?[12..])))
+    }
+
+    pub fn uint128(&self, index: usize) -> Result<u128> {
+        word_to_u128(&self.word(index)?)
+    }
+
+    pub fn uint64(&self, index: usize) -> Result<u64> {
+        u64::try_from(self.uint128(index)?).map_err(|_| RelayerError::decode("uint256 does not fit u64"))
+    }
+
+    /// Dynamic `bytes` whose offset is in head word `index`
+    pub fn bytes(&self, index: usize) -> Result<&'a [u8]> {
+        let offset = usize::try_from(self.uint128(index)?).map_err(RelayerError::decode)?;
+        let length_word: Word = self
+            .data
+            .get(offset..offset + 32)
+            .ok_or_else(|| RelayerError::decode("ABI bytes offset out of range"))?
+            .try_into()
+            .unwrap();
+        let length = usize::try_from(word_to_u128(&length_word)?).map_err(RelayerError::decode)?;
+        self.data
+            .get(offset + 32..offset + 32 + length)
+            .ok_or_else(|| RelayerError::decode("ABI bytes length out of range"))
+    }
+}
+
+pub fn word_to_u128(word: &Word) -> Result<u128> {
+    if word[..16].iter().any(|byte| *byte != 0) {
+        return Err(RelayerError::decode("uint256 does not fit u128"));
+    }
+    Ok(u128::from_be_bytes(word[16..].try_into().unwrap()))
+}
+
+pub fn u128_word(value: u128) -> Word {
+    let mut word = [0u8; 32];
+    word[16..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// 32-byte value from `0x` hex, as JSON-RPC returns hashes and topics
+pub fn parse_word(value: &str) -> Result<Word> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    let mut word = [0u8; 32];
+    hex::decode_to_slice(digits, &mut word).map_err(RelayerError::decode)?;
+    Ok(word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selector() {
+        // Well-known ERC-20 selector and event topic
+        assert_eq!(selector("transfer(address,uint256)"), [0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(
+            hex::encode(event_topic("Transfer(address,address,uint256)")),
+            "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        );
+    }
+
+    #[test]
+    fn test_decode_dynamic_bytes() {
+        // abi.encode(uint256 7, bytes "near.testnet", uint256 9)
+        let mut data = Vec::new();
+        data.extend_from_slice(&u128_word(7));
+        data.extend_from_slice(&u128_word(96));
+        data.extend_from_slice(&u128_word(9));
+        data.extend_from_slice(&u128_word(12));
+        let mut padded = b"near.testnet".to_vec();
+        padded.resize(32, 0);
+        data.extend_from_slice(&padded);
+
+        let decoder = Decoder::new(&data);
+        assert_eq!(decoder.uint128(0).unwrap(), 7);
+        assert_eq!(decoder.bytes(1).unwrap(), b"near.testnet");
+        assert_eq!(decoder.uint64(2).unwrap(), 9);
+        assert!(decoder.word(5).is_err());
+    }
+
+    #[test]
+    fn test_word_to_u128_rejects_overflow() {
+        let mut word = [0u8; 32];
+        word[15] = 1;
+        assert!(word_to_u128(&word).is_err());
+    }
+}
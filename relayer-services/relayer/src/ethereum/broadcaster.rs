@@ -0,0 +1,320 @@
+//! Signs and sends EIP-1559 calls and sees them mined
+//!
+//! A call is sent at the account's `latest` nonce, so a transaction of ours
+//! still stuck in the mempool (from before a restart) is replaced rather
+//! than queued behind. If no sent version is mined within `replace_after`,
+//! the same nonce is signed again with both fees raised by at least
+//! `fee_bump_percent` (nodes reject replacements under 10 %) and at least to
+//! the current market, up to `max_fee_per_gas`. Whichever version gets
+//! mined is the result. Sends are serialized, one nonce is in flight at a
+//! time.
+
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::Mutex;
+
+use super::abi::keccak256;
+use super::tx::Eip1559Transaction;
+use super::{parse_quantity, parse_quantity_u128, EthereumClient};
+use crate::config::EthereumConfig;
+use crate::error::{RelayerError, Result};
+
+/// How often sent transactions are checked for a receipt
+const RECEIPT_POLL: Duration = Duration::from_secs(3);
+
+const GWEI: u128 = 1_000_000_000;
+
+/// EIP-1559 fees of one transaction, in wei per gas
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Fees {
+    pub max_priority_fee_per_gas: u128,
+    pub max_fee_per_gas: u128,
+}
+
+impl Fees {
+    /// The usual wallet heuristic: twice the base fee plus the tip
+    pub fn market(base_fee: u128, tip: u128) -> Self {
+        Self {
+            max_priority_fee_per_gas: tip,
+            max_fee_per_gas: 2 * base_fee + tip,
+        }
+    }
+
+    /// Lowered to `cap`, the tip never above the max fee
+    fn capped(self, cap: u128) -> Self {
+        let max_fee_per_gas = self.max_fee_per_gas.min(cap);
+        Self {
+            max_priority_fee_per_gas: self.max_priority_fee_per_gas.min(max_fee_per_gas),
+            max_fee_per_gas,
+        }
+    }
+
+    /// Fees to replace a transaction sent with `self`: both raised by at
+    /// least `percent` and at least to `market`, `None` above `cap`
+    pub fn bumped(&self, market: Fees, percent: u64, cap: u128) -> Option<Self> {
+        let raise = |fee: u128| (fee * (100 + percent as u128)).div_ceil(100).max(fee + 1);
+        let max_priority_fee_per_gas = raise(self.max_priority_fee_per_gas).max(market.max_priority_fee_per_gas);
+        let max_fee_per_gas = raise(self.max_fee_per_gas)
+            .max(market.max_fee_per_gas)
+            .max(max_priority_fee_per_gas);
+        (max_fee_per_gas <= cap).then_some(Self {
+            max_priority_fee_per_gas,
+            max_fee_per_gas,
+        })
+    }
+}
+
+/// Fields of `eth_getTransactionReceipt` the broadcaster reads
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Receipt {
+    status: String,
+    block_number: String,
+}
+
+/// How the node answered `eth_sendRawTransaction`, as far as replacement goes
+#[derive(PartialEq, Eq, Debug)]
+enum Rejection {
+    /// The exact transaction is already in the mempool
+    Known,
+    /// Another transaction with this nonce pays more
+    Underpriced,
+    /// The nonce was used by a mined transaction
+    NonceTooLow,
+}
+
+fn rejection(error: &RelayerError) -> Option<Rejection> {
+    let RelayerError::Rpc { message, .. } = error else {
+        return None;
+    };
+    let message = message.to_lowercase();
+    if message.contains("already known") || message.contains("already imported") {
+        Some(Rejection::Known)
+    } else if message.contains("underpriced") {
+        Some(Rejection::Underpriced)
+    } else if message.contains("nonce too low") {
+        Some(Rejection::NonceTooLow)
+    } else {
+        None
+    }
+}
+
+pub struct Broadcaster {
+    client: EthereumClient,
+    replace_after: Duration,
+    fee_bump_percent: u64,
+    max_fee_per_gas: u128,
+    max_replacements: u32,
+    sending: Mutex<()>,
+}
+
+impl Broadcaster {
+    pub fn new(client: EthereumClient, config: &EthereumConfig) -> Self {
+        Self {
+            client,
+            replace_after: Duration::from_secs(config.replace_after_secs),
+            fee_bump_percent: config.fee_bump_percent,
+            max_fee_per_gas: config.max_fee_gwei as u128 * GWEI,
+            max_replacements: config.max_replacements,
+            sending: Mutex::new(()),
+        }
+    }
+
+    pub fn client(&self) -> &EthereumClient {
+        &self.client
+    }
+
+    /// Sign and send a call to `to`, returns the hash of the version that
+    /// was mined
+    /// Fails with `NotMined` rather than `TxFailed` once every replacement
+    /// went unmined, since one may still land
+    pub async fn send_call(&self, to: &str, data: Vec<u8>) -> Result<String> {
+        let to_bytes: [u8; 20] = hex::decode(to.trim_start_matches("0x"))
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| RelayerError::Config(format!("invalid address {}", to)))?;
+        let _sending = self.sending.lock().await;
+        let from = self.client.address();
+        let call = json!({ "from": from, "to": to, "data": format!("0x{}", hex::encode(&data)) });
+
+        let nonce: String = self.client.rpc.call("eth_getTransactionCount", json!([from, "latest"])).await?;
+        let gas: String = self.client.rpc.call("eth_estimateGas", json!([call])).await?;
+        let mut transaction = Eip1559Transaction {
+            chain_id: self.client.chain_id,
+            nonce: parse_quantity(&nonce)?,
+            max_priority_fee_per_gas: 0,
+            max_fee_per_gas: 0,
+            // Headroom over the estimate, state can change before inclusion
+            gas_limit: parse_quantity(&gas)? * 12 / 10,
+            to: to_bytes,
+            value: 0,
+            data,
+        };
+        let mut fees = Some(self.market_fees().await?.capped(self.max_fee_per_gas));
+        let mut sent = Vec::new();
+        let mut replacements = 0;
+        loop {
+            if let Some(next) = fees.take() {
+                transaction.max_priority_fee_per_gas = next.max_priority_fee_per_gas;
+                transaction.max_fee_per_gas = next.max_fee_per_gas;
+                match self.send(&transaction).await {
+                    Ok(hash) => {
+                        tracing::info!(tx = %hash, nonce = transaction.nonce, fees = ?next, "sent");
+                        sent.push(hash);
+                    }
+                    Err(error) => match rejection(&error) {
+                        Some(Rejection::Known) => sent.push(transaction_hash(&transaction.sign(&self.client.key))),
+                        // A version from before a restart pays more, outbid it right away
+                        Some(Rejection::Underpriced) if replacements < self.max_replacements => {
+                            replacements += 1;
+                            fees = self.replacement(next).await?;
+                            if fees.is_some() {
+                                continue;
+                            }
+                        }
+                        Some(Rejection::NonceTooLow) => return self.mined(&sent).await?.ok_or(error),
+                        _ if sent.is_empty() => return Err(error),
+                        _ => tracing::warn!(nonce = transaction.nonce, %error, "replacement not accepted"),
+                    },
+                }
+            }
+
+            if let Some(hash) = self.wait_mined(&sent).await? {
+                return Ok(hash);
+            }
+            if replacements == self.max_replacements {
+                return Err(RelayerError::NotMined(format!(
+                    "nonce {} after {} replacements, last sent {:?}",
+                    transaction.nonce,
+                    replacements,
+                    sent.last()
+                )));
+            }
+            replacements += 1;
+            let current = Fees {
+                max_priority_fee_per_gas: transaction.max_priority_fee_per_gas,
+                max_fee_per_gas: transaction.max_fee_per_gas,
+            };
+            fees = self.replacement(current).await?;
+            if fees.is_none() {
+                tracing::warn!(nonce = transaction.nonce, ?current, "fees at the configured cap, waiting");
+            }
+        }
+    }
+
+    async fn market_fees(&self) -> Result<Fees> {
+        let tip: String = self.client.rpc.call("eth_maxPriorityFeePerGas", json!([])).await?;
+        let block: serde_json::Value = self
+            .client
+            .rpc
+            .call("eth_getBlockByNumber", json!(["latest", false]))
+            .await?;
+        let base_fee = block["baseFeePerGas"]
+            .as_str()
+            .ok_or_else(|| RelayerError::decode("latest block has no baseFeePerGas"))?;
+        Ok(Fees::market(parse_quantity_u128(base_fee)?, parse_quantity_u128(&tip)?))
+    }
+
+    async fn replacement(&self, current: Fees) -> Result<Option<Fees>> {
+        let market = self.market_fees().await?;
+        Ok(current.bumped(market, self.fee_bump_percent, self.max_fee_per_gas))
+    }
+
+    async fn send(&self, transaction: &Eip1559Transaction) -> Result<String> {
+        let raw = format!("0x{}", hex::encode(transaction.sign(&self.client.key)));
+        self.client.rpc.call("eth_sendRawTransaction", json!([raw])).await
+    }
+
+    /// Poll until one of `sent` is mined or `replace_after` passes
+    async fn wait_mined(&self, sent: &[String]) -> Result<Option<String>> {
+        let deadline = Instant::now() + self.replace_after;
+        loop {
+            if let Some(hash) = self.mined(sent).await? {
+                return Ok(Some(hash));
+            }
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+            tokio::time::sleep(RECEIPT_POLL.min(deadline - Instant::now())).await;
+        }
+    }
+
+    /// The mined one of `sent`, `TxFailed` if it reverted
+    async fn mined(&self, sent: &[String]) -> Result<Option<String>> {
+        for hash in sent {
+            let receipt: Option<Receipt> = self.client.rpc.call("eth_getTransactionReceipt", json!([hash])).await?;
+            if let Some(receipt) = receipt {
+                if parse_quantity(&receipt.status)? != 1 {
+                    return Err(RelayerError::TxFailed(format!(
+                        "{} reverted in block {}",
+                        hash,
+                        parse_quantity(&receipt.block_number)?
+                    )));
+                }
+                return Ok(Some(hash.clone()));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Hash of a signed transaction, as the node reports it
+fn transaction_hash(raw: &[u8]) -> String {
+    format!("0x{}", hex::encode(keccak256(raw)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAP: u128 = 500 * GWEI;
+
+    #[test]
+    fn test_bumped_fees() {
+        let sent = Fees::market(10 * GWEI, 2 * GWEI);
+        assert_eq!(sent.max_fee_per_gas, 22 * GWEI);
+
+        // A quiet market: the minimum raise on both
+        let quiet = Fees::market(5 * GWEI, GWEI);
+        let bumped = sent.bumped(quiet, 10, CAP).unwrap();
+        assert_eq!(bumped.max_priority_fee_per_gas, 2_200_000_000);
+        assert_eq!(bumped.max_fee_per_gas, 24_200_000_000);
+
+        // A busier market than the raise: follow it
+        let busy = Fees::market(40 * GWEI, 3 * GWEI);
+        assert_eq!(sent.bumped(busy, 10, CAP), Some(busy));
+
+        // Rounded up, and never left equal
+        let dust = Fees {
+            max_priority_fee_per_gas: 0,
+            max_fee_per_gas: 7,
+        };
+        let bumped = dust.bumped(Fees::market(0, 0), 10, CAP).unwrap();
+        assert_eq!((bumped.max_priority_fee_per_gas, bumped.max_fee_per_gas), (1, 8));
+
+        assert_eq!(sent.bumped(busy, 10, 80 * GWEI), None);
+    }
+
+    #[test]
+    fn test_capped_fees() {
+        let fees = Fees::market(400 * GWEI, 2 * GWEI).capped(CAP);
+        assert_eq!(fees.max_fee_per_gas, CAP);
+        assert_eq!(fees.max_priority_fee_per_gas, 2 * GWEI);
+        assert_eq!(Fees::market(0, 2 * GWEI).capped(GWEI).max_priority_fee_per_gas, GWEI);
+    }
+
+    #[test]
+    fn test_rejection() {
+        let rpc = |message: &str| RelayerError::Rpc {
+            code: -32000,
+            message: message.into(),
+        };
+        assert_eq!(rejection(&rpc("already known")), Some(Rejection::Known));
+        assert_eq!(rejection(&rpc("replacement transaction underpriced")), Some(Rejection::Underpriced));
+        assert_eq!(rejection(&rpc("nonce too low: next nonce 8, tx nonce 7")), Some(Rejection::NonceTooLow));
+        assert_eq!(rejection(&rpc("insufficient funds for gas * price + value")), None);
+        assert_eq!(rejection(&RelayerError::TxFailed("already known".into())), None);
+    }
+}
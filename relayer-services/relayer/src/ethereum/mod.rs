@@ -0,0 +1,467 @@
+//! Ethereum side: watches the Fusion+ factory for orders and reveals
+//! secrets with `completeFusionOrder`
+
+pub mod abi;
+pub mod broadcaster;
+pub mod tx;
+pub mod watcher;
+
+use async_trait::async_trait;
+use secp256k1::SecretKey;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::chain::Source;
+use crate::config::EthereumConfig;
+use crate::error::{RelayerError, Result};
+use crate::order::{SourceEvent, SwapOrder};
+use crate::rpc::JsonRpcClient;
+use abi::{parse_word, Decoder};
+use broadcaster::Broadcaster;
+use watcher::Watcher;
+
+pub const ORDER_CREATED_EVENT: &str =
+    "FusionOrderCreated(bytes32,address,address,uint256,uint256,bytes,uint256,bytes,uint256,uint256,bytes32)";
+pub const ORDER_COMPLETED_EVENT: &str = "FusionOrderCompleted(bytes32,address,bytes32)";
+pub const ORDER_CANCELLED_EVENT: &str = "FusionOrderCancelled(bytes32,address)";
+const COMPLETE_ORDER_FUNCTION: &str = "completeFusionOrder(bytes32,bytes32)";
+/// Public getter of the factory's `orders` mapping
+const ORDERS_FUNCTION: &str = "orders(bytes32)";
+
+/// Log as returned by `eth_getLogs`
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Log {
+    pub topics: Vec<String>,
+    pub data: String,
+    pub block_number: String,
+    pub block_hash: String,
+    #[serde(default)]
+    pub transaction_hash: Option<String>,
+    #[serde(default)]
+    pub removed: bool,
+}
+
+impl Log {
+    pub fn block(&self) -> Result<u64> {
+        parse_quantity(&self.block_number)
+    }
+}
+
+/// Decode a factory log, `None` for events the relayer ignores
+pub fn decode_log(log: &Log) -> Result<Option<SourceEvent>> {
+    let topic = |index: usize| -> Result<[u8; 32]> {
+        log.topics
+            .get(index)
+            .ok_or_else(|| RelayerError::decode("missing log topic"))
+            .and_then(|topic| parse_word(topic))
+    };
+    let data = hex::decode(log.data.trim_start_matches("0x")).map_err(RelayerError::decode)?;
+    let decoder = Decoder::new(&data);
+    let signature = topic(0)?;
+    let order_hash = format!("0x{}", hex::encode(topic(1)?));
+
+    if signature == abi::event_topic(ORDER_CREATED_EVENT) {
+        let maker = format!("0x{}", hex::encode(&topic(2)?[12..]));
+        Ok(Some(SourceEvent::Created(SwapOrder {
+            order_hash,
+            maker,
+            source_token: decoder.address(0)?,
+            source_amount: decoder.uint128(1)?,
+            destination_chain_id: decoder.uint64(2)?,
+            destination_token: String::from_utf8_lossy(decoder.bytes(3)?).into_owned(),
+            destination_amount: decoder.uint128(4)?,
+            destination_address: String::from_utf8_lossy(decoder.bytes(5)?).into_owned(),
+            resolver_fee: decoder.uint128(6)?,
+            expiry_time: decoder.uint64(7)?,
+            hashlock: decoder.word(8)?,
+            block_number: log.block()?,
+        })))
+    } else if signature == abi::event_topic(ORDER_COMPLETED_EVENT) {
+        Ok(Some(SourceEvent::Completed {
+            order_hash,
+            secret: decoder.word(0)?,
+        }))
+    } else if signature == abi::event_topic(ORDER_CANCELLED_EVENT) {
+        Ok(Some(SourceEvent::Cancelled { order_hash }))
+    } else {
+        Ok(None)
+    }
+}
+
+/// An order as the factory's `orders` getter returns it
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FactoryOrder {
+    pub maker: String,
+    pub source_token: String,
+    pub source_amount: u128,
+    pub destination_chain_id: u64,
+    pub destination_token: String,
+    pub destination_amount: u128,
+    pub destination_address: String,
+    pub resolver_fee: u128,
+    pub expiry_time: u64,
+    /// Neither completed nor cancelled
+    pub is_active: bool,
+}
+
+/// Decode the `orders` getter's return data, `None` for an unknown order
+/// (the stored order hash is zero)
+pub fn decode_factory_order(data: &[u8]) -> Result<Option<FactoryOrder>> {
+    let decoder = Decoder::new(data);
+    if decoder.word(0)? == [0; 32] {
+        return Ok(None);
+    }
+    Ok(Some(FactoryOrder {
+        maker: decoder.address(1)?,
+        source_token: decoder.address(2)?,
+        source_amount: decoder.uint128(3)?,
+        destination_chain_id: decoder.uint64(4)?,
+        destination_token: String::from_utf8_lossy(decoder.bytes(5)?).into_owned(),
+        destination_amount: decoder.uint128(6)?,
+        destination_address: String::from_utf8_lossy(decoder.bytes(7)?).into_owned(),
+        resolver_fee: decoder.uint128(8)?,
+        expiry_time: decoder.uint64(9)?,
+        is_active: decoder.word(11)?[31] == 1,
+    }))
+}
+
+/// Read `order_hash` from the factory at `factory`
+pub async fn factory_order(rpc: &JsonRpcClient, factory: &str, order_hash: &str) -> Result<Option<FactoryOrder>> {
+    let data = abi::encode_call(ORDERS_FUNCTION, &[parse_word(order_hash)?]);
+    decode_factory_order(&eth_call(rpc, factory, data).await?)
+}
+
+/// `eth_call` against the latest block
+async fn eth_call(rpc: &JsonRpcClient, to: &str, data: Vec<u8>) -> Result<Vec<u8>> {
+    let result: String = rpc
+        .call("eth_call", json!([{ "to": to, "data": format!("0x{}", hex::encode(data)) }, "latest"]))
+        .await?;
+    hex::decode(result.trim_start_matches("0x")).map_err(RelayerError::decode)
+}
+
+pub fn parse_quantity(value: &str) -> Result<u64> {
+    u64::from_str_radix(value.trim_start_matches("0x"), 16).map_err(RelayerError::decode)
+}
+
+pub(crate) fn parse_quantity_u128(value: &str) -> Result<u128> {
+    u128::from_str_radix(value.trim_start_matches("0x"), 16).map_err(RelayerError::decode)
+}
+
+/// JSON-RPC access to an Ethereum node plus the resolver's signing key
+pub struct EthereumClient {
+    pub rpc: JsonRpcClient,
+    pub chain_id: u64,
+    key: SecretKey,
+    address: [u8; 20],
+}
+
+impl EthereumClient {
+    pub fn new(rpc: JsonRpcClient, chain_id: u64, private_key: &str) -> Result<Self> {
+        let key_bytes = hex::decode(private_key.trim_start_matches("0x")).map_err(|e| RelayerError::Key(e.to_string()))?;
+        let key = SecretKey::from_slice(&key_bytes).map_err(|e| RelayerError::Key(e.to_string()))?;
+        Ok(Self {
+            rpc,
+            chain_id,
+            address: tx::address_of(&key),
+            key,
+        })
+    }
+
+    pub fn address(&self) -> String {
+        format!("0x{}", hex::encode(self.address))
+    }
+
+    pub async fn block_number(&self) -> Result<u64> {
+        let block: String = self.rpc.call("eth_blockNumber", json!([])).await?;
+        parse_quantity(&block)
+    }
+
+    /// Hash of block `number`, `None` past the head
+    pub async fn block_hash(&self, number: u64) -> Result<Option<String>> {
+        let block: serde_json::Value = self
+            .rpc
+            .call("eth_getBlockByNumber", json!([format!("0x{:x}", number), false]))
+            .await?;
+        Ok(block["hash"].as_str().map(str::to_lowercase))
+    }
+
+    pub async fn logs(&self, address: &str, topics: &[[u8; 32]], from: u64, to: u64) -> Result<Vec<Log>> {
+        let topics: Vec<String> = topics.iter().map(|topic| format!("0x{}", hex::encode(topic))).collect();
+        self.rpc
+            .call(
+                "eth_getLogs",
+                json!([{
+                    "address": address,
+                    "fromBlock": format!("0x{:x}", from),
+                    "toBlock": format!("0x{:x}", to),
+                    "topics": [topics],
+                }]),
+            )
+            .await
+    }
+
+    /// `eth_call` against the latest block
+    pub async fn call(&self, to: &str, data: Vec<u8>) -> Result<Vec<u8>> {
+        eth_call(&self.rpc, to, data).await
+    }
+
+}
+
+/// Polls the factory for order events, see [`Watcher`] for how finality
+/// and reorgs are reported
+pub struct EthereumSource {
+    client: Broadcaster,
+    factory: String,
+    max_block_range: u64,
+    next_block: Option<u64>,
+    watcher: Watcher,
+    finality_depth: u64,
+    /// Newest block whose events have all been finalized
+    finalized: Option<u64>,
+}
+
+impl EthereumSource {
+    pub fn new(config: &EthereumConfig, private_key: &str) -> Result<Self> {
+        Ok(Self {
+            client: Broadcaster::new(
+                EthereumClient::new(
+                    JsonRpcClient::with_failover(config.provider(), "eth_blockNumber"),
+                    config.chain_id,
+                    private_key,
+                )?,
+                config,
+            ),
+            factory: config.factory.clone(),
+            max_block_range: config.max_block_range.max(1),
+            next_block: config.start_block,
+            watcher: Watcher::new(config.finality_depth),
+            finality_depth: config.finality_depth,
+            finalized: None,
+        })
+    }
+
+    pub fn client(&self) -> &EthereumClient {
+        self.client.client()
+    }
+}
+
+impl EthereumSource {
+    /// Compare the checkpoints against the chain, newest first, and roll
+    /// back to the newest one that is still canonical
+    /// Returns the first replaced block, `None` without a reorg
+    async fn detect_reorg(&mut self) -> Result<Option<u64>> {
+        let checkpoints: Vec<_> = self.watcher.checkpoints().cloned().collect();
+        if checkpoints.is_empty() {
+            return Ok(None);
+        }
+        let mut ancestor = None;
+        for (index, checkpoint) in checkpoints.iter().enumerate() {
+            if self.client().block_hash(checkpoint.number).await?.as_deref() == Some(checkpoint.hash.as_str()) {
+                if index == 0 {
+                    return Ok(None);
+                }
+                ancestor = Some(checkpoint.number);
+                break;
+            }
+        }
+        if ancestor.is_none() {
+            tracing::error!("reorg deeper than the finality depth, rolling back every pending event");
+        }
+        Ok(self.watcher.rollback(ancestor))
+    }
+}
+
+#[async_trait]
+impl Source for EthereumSource {
+    fn chain_id(&self) -> u64 {
+        self.client().chain_id
+    }
+
+    async fn poll(&mut self) -> Result<Vec<SourceEvent>> {
+        let mut events = Vec::new();
+        if let Some(from_block) = self.detect_reorg().await? {
+            tracing::warn!(from_block, "chain reorganized");
+            events.push(SourceEvent::Reorged { from_block });
+            self.next_block = Some(from_block);
+        }
+
+        let head = self.client().block_number().await?;
+        // Without a configured start block, begin at the current head
+        let from = *self.next_block.get_or_insert(head);
+        if from <= head {
+            let to = head.min(from + self.max_block_range - 1);
+            let topics = [
+                abi::event_topic(ORDER_CREATED_EVENT),
+                abi::event_topic(ORDER_COMPLETED_EVENT),
+                abi::event_topic(ORDER_CANCELLED_EVENT),
+            ];
+            let logs = self.client().logs(&self.factory, &topics, from, to).await?;
+            for log in logs.iter().filter(|log| !log.removed) {
+                match decode_log(log) {
+                    Ok(Some(event)) => events.extend(self.watcher.observe(log.block()?, &log.block_hash, event)),
+                    Ok(None) => {}
+                    Err(error) => tracing::warn!(?error, tx = ?log.transaction_hash, "skipping undecodable factory log"),
+                }
+            }
+            // Anchor the scanned range even when it had no logs
+            let to_hash = self
+                .client()
+                .block_hash(to)
+                .await?
+                .ok_or_else(|| RelayerError::decode(format!("block {} not found", to)))?;
+            self.watcher.checkpoint(to, &to_hash);
+            self.next_block = Some(to + 1);
+        }
+
+        events.extend(self.watcher.finalize(head));
+        if let (Some(final_block), Some(next_block)) = (head.checked_sub(self.finality_depth), self.next_block) {
+            self.finalized = Some(final_block.min(next_block - 1));
+        }
+        Ok(events)
+    }
+
+    fn finalized_block(&self) -> Option<u64> {
+        self.finalized
+    }
+
+    async fn is_active(&self, order_hash: &str) -> Result<bool> {
+        let order = factory_order(&self.client().rpc, &self.factory, order_hash).await?;
+        Ok(order.is_some_and(|order| order.is_active))
+    }
+
+    async fn complete_order(&self, order_hash: &str, secret: &[u8; 32]) -> Result<String> {
+        let data = abi::encode_call(COMPLETE_ORDER_FUNCTION, &[parse_word(order_hash)?, *secret]);
+        self.client.send_call(&self.factory, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use abi::u128_word;
+
+    fn topic_hex(word: [u8; 32]) -> String {
+        format!("0x{}", hex::encode(word))
+    }
+
+    /// `FusionOrderCreated` log as the factory emits it
+    fn order_created_log(order_hash: [u8; 32], destination_chain_id: u64, destination: &str) -> Log {
+        let mut maker = [0u8; 32];
+        maker[12..].copy_from_slice(&[0xaa; 20]);
+        let mut head = vec![
+            abi::address_word(&[0xcc; 20]),
+            u128_word(1_000),
+            u128_word(destination_chain_id as u128),
+            u128_word(9 * 32),
+            u128_word(5_000),
+            u128_word(11 * 32),
+            u128_word(25),
+            u128_word(1_700_000_000),
+            [0x42; 32],
+        ];
+        // destinationToken "near", destinationAddress `destination`
+        head.push(u128_word(4));
+        let mut token = b"near".to_vec();
+        token.resize(32, 0);
+        head.push(token.try_into().unwrap());
+        head.push(u128_word(destination.len() as u128));
+        let mut address = destination.as_bytes().to_vec();
+        address.resize(destination.len().div_ceil(32) * 32, 0);
+        let data = [head.concat(), address].concat();
+
+        Log {
+            topics: vec![topic_hex(abi::event_topic(ORDER_CREATED_EVENT)), topic_hex(order_hash), topic_hex(maker)],
+            data: format!("0x{}", hex::encode(data)),
+            block_number: "0x10".to_string(),
+            block_hash: topic_hex([0x10; 32]),
+            transaction_hash: None,
+            removed: false,
+        }
+    }
+
+    #[test]
+    fn test_decode_order_created() {
+        let log = order_created_log([1; 32], 40002, "alice.testnet");
+        let Some(SourceEvent::Created(order)) = decode_log(&log).unwrap() else {
+            panic!("expected an order");
+        };
+        assert_eq!(order.order_hash, format!("0x{}", "01".repeat(32)));
+        assert_eq!(order.maker, format!("0x{}", "aa".repeat(20)));
+        assert_eq!(order.source_token, format!("0x{}", "cc".repeat(20)));
+        assert_eq!(order.destination_chain_id, 40002);
+        assert_eq!(order.destination_token, "near");
+        assert_eq!(order.destination_amount, 5_000);
+        assert_eq!(order.destination_address, "alice.testnet");
+        assert_eq!(order.resolver_fee, 25);
+        assert_eq!(order.hashlock, [0x42; 32]);
+        assert_eq!(order.block_number, 16);
+    }
+
+    #[test]
+    fn test_decode_order_completed() {
+        let log = Log {
+            topics: vec![
+                topic_hex(abi::event_topic(ORDER_COMPLETED_EVENT)),
+                topic_hex([2; 32]),
+                topic_hex([0; 32]),
+            ],
+            data: topic_hex([9; 32]),
+            block_number: "0x1".to_string(),
+            block_hash: topic_hex([0x01; 32]),
+            transaction_hash: None,
+            removed: false,
+        };
+        assert_eq!(
+            decode_log(&log).unwrap(),
+            Some(SourceEvent::Completed {
+                order_hash: format!("0x{}", "02".repeat(32)),
+                secret: [9; 32],
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_factory_order() {
+        let padded = |text: &str| {
+            let mut bytes = text.as_bytes().to_vec();
+            bytes.resize(32, 0);
+            [u128_word(text.len() as u128), bytes.try_into().unwrap()]
+        };
+        let head = [
+            [1; 32],
+            abi::address_word(&[0xaa; 20]),
+            abi::address_word(&[0xcc; 20]),
+            u128_word(1_000),
+            u128_word(40002),
+            u128_word(12 * 32),
+            u128_word(5_000),
+            u128_word(14 * 32),
+            u128_word(25),
+            u128_word(1_700_000_000),
+            u128_word(16 * 32),
+            u128_word(1),
+        ];
+        let tails = [padded("near"), padded("alice.testnet"), [u128_word(0), [0; 32]]].concat();
+        let data = [head.concat(), tails.concat()].concat();
+
+        let order = decode_factory_order(&data).unwrap().unwrap();
+        assert_eq!(order.maker, format!("0x{}", "aa".repeat(20)));
+        assert_eq!(order.destination_chain_id, 40002);
+        assert_eq!(order.destination_token, "near");
+        assert_eq!(order.destination_address, "alice.testnet");
+        assert_eq!((order.source_amount, order.destination_amount, order.resolver_fee), (1_000, 5_000, 25));
+        assert_eq!(order.expiry_time, 1_700_000_000);
+        assert!(order.is_active);
+
+        // Unknown orders read back as all zeros
+        assert_eq!(decode_factory_order(&[0; 12 * 32]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_ignores_other_events() {
+        let mut log = order_created_log([1; 32], 40002, "alice.testnet");
+        log.topics[0] = topic_hex(abi::event_topic("ResolverAuthorized(address)"));
+        assert_eq!(decode_log(&log).unwrap(), None);
+    }
+}
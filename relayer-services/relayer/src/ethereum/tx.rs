@@ -0,0 +1,149 @@
+//! EIP-1559 transactions, RLP-encoded and signed with secp256k1
+
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+
+use super::abi::keccak256;
+
+/// Type-2 (EIP-1559) transaction with an empty access list
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Eip1559Transaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub max_priority_fee_per_gas: u128,
+    pub max_fee_per_gas: u128,
+    pub gas_limit: u64,
+    pub to: [u8; 20],
+    pub value: u128,
+    pub data: Vec<u8>,
+}
+
+impl Eip1559Transaction {
+    fn fields(&self) -> Vec<Vec<u8>> {
+        vec![
+            rlp_uint(self.chain_id as u128),
+            rlp_uint(self.nonce as u128),
+            rlp_uint(self.max_priority_fee_per_gas),
+            rlp_uint(self.max_fee_per_gas),
+            rlp_uint(self.gas_limit as u128),
+            rlp_bytes(&self.to),
+            rlp_uint(self.value),
+            rlp_bytes(&self.data),
+            rlp_list(&[]),
+        ]
+    }
+
+    /// Hash the sender signs: `keccak256(0x02 || rlp(fields))`
+    pub fn signing_hash(&self) -> [u8; 32] {
+        keccak256(&[&[2u8][..], &rlp_list(&self.fields())].concat())
+    }
+
+    /// Raw signed transaction for `eth_sendRawTransaction`
+    pub fn sign(&self, key: &SecretKey) -> Vec<u8> {
+        let message = Message::from_slice(&self.signing_hash()).expect("32-byte hash");
+        let signature = Secp256k1::signing_only().sign_ecdsa_recoverable(&message, key);
+        let (recovery_id, compact) = signature.serialize_compact();
+
+        let mut fields = self.fields();
+        fields.push(rlp_uint(recovery_id.to_i32() as u128));
+        fields.push(rlp_bytes(trim_leading_zeros(&compact[..32])));
+        fields.push(rlp_bytes(trim_leading_zeros(&compact[32..])));
+        [&[2u8][..], &rlp_list(&fields)].concat()
+    }
+}
+
+/// Ethereum address of a secp256k1 key
+pub fn address_of(key: &SecretKey) -> [u8; 20] {
+    let public_key = PublicKey::from_secret_key(&Secp256k1::signing_only(), key);
+    let hash = keccak256(&public_key.serialize_uncompressed()[1..]);
+    hash[12..].try_into().unwrap()
+}
+
+fn trim_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|byte| *byte != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+fn rlp_uint(value: u128) -> Vec<u8> {
+    rlp_bytes(trim_leading_zeros(&value.to_be_bytes()))
+}
+
+fn rlp_bytes(bytes: &[u8]) -> Vec<u8> {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        return bytes.to_vec();
+    }
+    [rlp_length(bytes.len(), 0x80), bytes.to_vec()].concat()
+}
+
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload = items.concat();
+    [rlp_length(payload.len(), 0xc0), payload].concat()
+}
+
+fn rlp_length(length: usize, offset: u8) -> Vec<u8> {
+    if length < 56 {
+        return vec![offset + length as u8];
+    }
+    let length_bytes = trim_leading_zeros(&length.to_be_bytes()).to_vec();
+    [vec![offset + 55 + length_bytes.len() as u8], length_bytes].concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+
+    #[test]
+    fn test_rlp() {
+        // Examples from the Ethereum RLP specification
+        assert_eq!(rlp_bytes(b"dog"), vec![0x83, b'd', b'o', b'g']);
+        assert_eq!(rlp_list(&[rlp_bytes(b"cat"), rlp_bytes(b"dog")]), b"\xc8\x83cat\x83dog".to_vec());
+        assert_eq!(rlp_bytes(b""), vec![0x80]);
+        assert_eq!(rlp_uint(0), vec![0x80]);
+        assert_eq!(rlp_uint(15), vec![0x0f]);
+        assert_eq!(rlp_uint(1024), vec![0x82, 0x04, 0x00]);
+        let long = [b'a'; 56];
+        assert_eq!(&rlp_bytes(&long)[..2], &[0xb8, 56]);
+    }
+
+    #[test]
+    fn test_address_of() {
+        // Hardhat's first default account
+        let key = SecretKey::from_slice(
+            &hex::decode("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(hex::encode(address_of(&key)), "f39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+    }
+
+    #[test]
+    fn test_signature_recovers_sender() {
+        let key = SecretKey::from_slice(&[7u8; 32]).unwrap();
+        let tx = Eip1559Transaction {
+            chain_id: 11155111,
+            nonce: 3,
+            max_priority_fee_per_gas: 1_500_000_000,
+            max_fee_per_gas: 40_000_000_000,
+            gas_limit: 120_000,
+            to: [0x11; 20],
+            value: 0,
+            data: vec![0xde, 0xad, 0xbe, 0xef],
+        };
+        let raw = tx.sign(&key);
+        assert_eq!(raw[0], 2);
+
+        // The last three RLP items are y_parity, r and s (r and s are 32 bytes
+        // unless they have leading zeros, which this key and nonce avoid)
+        let s = &raw[raw.len() - 32..];
+        let r = &raw[raw.len() - 65..raw.len() - 33];
+        let y_parity = raw[raw.len() - 67];
+        let y_parity = if y_parity == 0x80 { 0 } else { y_parity as i32 };
+        let signature = RecoverableSignature::from_compact(
+            &[r, s].concat(),
+            RecoveryId::from_i32(y_parity).unwrap(),
+        )
+        .unwrap();
+        let message = Message::from_slice(&tx.signing_hash()).unwrap();
+        let public_key = Secp256k1::new().recover_ecdsa(&message, &signature).unwrap();
+        assert_eq!(public_key, PublicKey::from_secret_key(&Secp256k1::new(), &key));
+    }
+}
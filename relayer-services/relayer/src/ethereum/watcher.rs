@@ -0,0 +1,178 @@
+//! Finality and reorg bookkeeping for the factory log stream
+//!
+//! `FusionOrderCreated` is reported as soon as its block is seen, so the
+//! relayer can track the order as pending, and followed by
+//! [`SourceEvent::Finalized`] once it is `finality_depth` blocks deep.
+//! Completions and cancellations are only reported once final. Until then
+//! the watcher remembers the hash of every block it took events from (plus
+//! the last scanned block), which is what a reorg is detected against.
+
+use std::collections::VecDeque;
+
+use crate::order::SourceEvent;
+
+/// Block the watcher has seen, by number and hash
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Checkpoint {
+    pub number: u64,
+    pub hash: String,
+}
+
+#[derive(Default)]
+pub struct Watcher {
+    finality_depth: u64,
+    /// Not yet final, ascending by number
+    checkpoints: VecDeque<Checkpoint>,
+    /// Events from non-final blocks, in log order
+    pending: Vec<(u64, SourceEvent)>,
+}
+
+impl Watcher {
+    pub fn new(finality_depth: u64) -> Self {
+        Self {
+            finality_depth,
+            ..Self::default()
+        }
+    }
+
+    /// Non-final checkpoints, newest first, the order a reorg is searched in
+    pub fn checkpoints(&self) -> impl Iterator<Item = &Checkpoint> {
+        self.checkpoints.iter().rev()
+    }
+
+    /// Remember `hash` as the canonical hash of block `number`
+    pub fn checkpoint(&mut self, number: u64, hash: &str) {
+        if self.checkpoints.back().is_some_and(|last| last.number >= number) {
+            return;
+        }
+        self.checkpoints.push_back(Checkpoint {
+            number,
+            hash: hash.to_lowercase(),
+        });
+    }
+
+    /// Record an event found in block `number`, returns what to report now
+    pub fn observe(&mut self, number: u64, hash: &str, event: SourceEvent) -> Option<SourceEvent> {
+        self.checkpoint(number, hash);
+        let now = match &event {
+            SourceEvent::Created(_) => Some(event.clone()),
+            _ => None,
+        };
+        self.pending.push((number, event));
+        now
+    }
+
+    /// Forget everything after `ancestor`, the newest block still canonical
+    /// (`None` when no checkpoint is). Returns the first block to rescan
+    pub fn rollback(&mut self, ancestor: Option<u64>) -> Option<u64> {
+        let first_dropped = match ancestor {
+            Some(ancestor) => ancestor + 1,
+            None => self.checkpoints.front()?.number,
+        };
+        self.checkpoints.retain(|checkpoint| checkpoint.number < first_dropped);
+        self.pending.retain(|(number, _)| *number < first_dropped);
+        Some(first_dropped)
+    }
+
+    /// Report events that are `finality_depth` deep at `head`, oldest first
+    pub fn finalize(&mut self, head: u64) -> Vec<SourceEvent> {
+        let Some(final_block) = head.checked_sub(self.finality_depth) else {
+            return Vec::new();
+        };
+        let (done, pending) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|(number, _)| *number <= final_block);
+        self.pending = pending;
+        // Keep the newest final checkpoint so a deep reorg still has an anchor
+        while self.checkpoints.len() > 1 && self.checkpoints[1].number <= final_block {
+            self.checkpoints.pop_front();
+        }
+        done.into_iter()
+            .map(|(_, event)| match event {
+                SourceEvent::Created(order) => SourceEvent::Finalized {
+                    order_hash: order.order_hash,
+                },
+                event => event,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn created(order_hash: &str, block_number: u64) -> SourceEvent {
+        SourceEvent::Created(crate::order::SwapOrder {
+            order_hash: order_hash.into(),
+            maker: String::new(),
+            source_token: String::new(),
+            source_amount: 1,
+            destination_chain_id: 40002,
+            destination_token: String::new(),
+            destination_amount: 1,
+            destination_address: String::new(),
+            resolver_fee: 0,
+            expiry_time: 0,
+            hashlock: [0; 32],
+            block_number,
+        })
+    }
+
+    fn finalized(order_hash: &str) -> SourceEvent {
+        SourceEvent::Finalized {
+            order_hash: order_hash.into(),
+        }
+    }
+
+    #[test]
+    fn test_created_reported_then_finalized() {
+        let mut watcher = Watcher::new(3);
+        assert_eq!(watcher.observe(10, "0xA", created("0x01", 10)), Some(created("0x01", 10)));
+        let cancelled = SourceEvent::Cancelled { order_hash: "0x01".into() };
+        assert_eq!(watcher.observe(11, "0xb", cancelled.clone()), None);
+
+        assert!(watcher.finalize(12).is_empty());
+        assert_eq!(watcher.finalize(13), [finalized("0x01")]);
+        // Hashes are kept lowercase, as nodes are not consistent about case
+        assert_eq!(watcher.checkpoints().last().unwrap().hash, "0xa");
+        assert_eq!(watcher.finalize(14), [cancelled]);
+        assert!(watcher.finalize(20).is_empty());
+    }
+
+    #[test]
+    fn test_rollback_drops_orphaned_events() {
+        let mut watcher = Watcher::new(5);
+        watcher.observe(10, "0xa", created("0x01", 10));
+        watcher.observe(12, "0xc", created("0x02", 12));
+        watcher.checkpoint(13, "0xd");
+        let newest: Vec<u64> = watcher.checkpoints().map(|checkpoint| checkpoint.number).collect();
+        assert_eq!(newest, [13, 12, 10]);
+
+        // Block 12 was replaced, 10 is still canonical
+        assert_eq!(watcher.rollback(Some(10)), Some(11));
+        assert_eq!(watcher.checkpoints().count(), 1);
+        assert_eq!(watcher.finalize(20), [finalized("0x01")]);
+    }
+
+    #[test]
+    fn test_rollback_without_anchor() {
+        let mut watcher = Watcher::new(5);
+        assert_eq!(watcher.rollback(None), None);
+        watcher.observe(10, "0xa", created("0x01", 10));
+        watcher.checkpoint(11, "0xb");
+        assert_eq!(watcher.rollback(None), Some(10));
+        assert!(watcher.finalize(20).is_empty());
+    }
+
+    #[test]
+    fn test_finalize_keeps_anchor() {
+        let mut watcher = Watcher::new(2);
+        watcher.checkpoint(10, "0xa");
+        watcher.checkpoint(11, "0xb");
+        watcher.checkpoint(12, "0xc");
+        watcher.finalize(13);
+        let kept: Vec<u64> = watcher.checkpoints().map(|checkpoint| checkpoint.number).collect();
+        assert_eq!(kept, [12, 11]);
+    }
+}
@@ -0,0 +1,22 @@
+//! Whether to fill an order, for relayers run as a competing resolver
+//!
+//! Without a [`FillPolicy`] the relayer executes every finalized order it
+//! has a destination for. With one, each finalized order is put to the
+//! policy until it says to fill or to decline.
+
+use crate::order::SwapOrder;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Decision {
+    /// Execute the destination escrow now
+    Fill,
+    /// Not now, ask again next tick (prices move)
+    Wait { reason: String },
+    /// Never fill this order
+    Decline { reason: String },
+}
+
+pub trait FillPolicy: Send {
+    /// Decision on `order` at unix time `now`
+    fn decide(&self, order: &SwapOrder, now: u64) -> Decision;
+}
@@ -0,0 +1,20 @@
+//! Fusion+ relayer: watches the Ethereum factory for orders, mirrors them
+//! as escrows on NEAR and Cosmos, and completes both sides once the secret
+//! is revealed
+
+pub mod chain;
+pub mod config;
+pub mod cosmos;
+pub mod error;
+pub mod ethereum;
+pub mod fill;
+pub mod near;
+pub mod order;
+pub mod provider;
+pub mod relayer;
+pub mod rpc;
+pub mod store;
+pub mod transmission;
+pub mod vault;
+
+pub use error::{RelayerError, Result};
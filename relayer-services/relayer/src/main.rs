@@ -0,0 +1,143 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use fusion_hashlock::decode_hex32;
+use tracing_subscriber::EnvFilter;
+use zeroize::Zeroizing;
+
+use relayer::chain::Destination;
+use relayer::config::{Config, VaultConfig};
+use relayer::cosmos::CosmosDestination;
+use relayer::ethereum::EthereumSource;
+use relayer::near::NearDestination;
+use relayer::relayer::Relayer;
+use relayer::store::OrderStore;
+use relayer::vault::{KeyProvider, LocalKeyProvider, PreimageVault, TransitKeyProvider};
+use relayer::{RelayerError, Result};
+
+#[derive(Parser)]
+#[command(about = "Fusion+ relayer for Ethereum to NEAR and Cosmos swaps")]
+struct Args {
+    /// Path to the TOML configuration
+    #[arg(long, default_value = "relayer.toml", global = true)]
+    config: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the relayer (the default)
+    Run,
+    /// Seal a maker's preimage into the vault, read as hex from stdin
+    StoreSecret {
+        /// Order hash the preimage unlocks
+        order_hash: String,
+    },
+    /// Call a view method of the configured NEAR contract and print the result
+    NearView {
+        method: String,
+        /// JSON arguments
+        #[arg(default_value = "{}")]
+        args: String,
+    },
+}
+
+fn env_key(name: &str) -> Result<String> {
+    std::env::var(name).map_err(|_| RelayerError::Key(format!("{} is not set", name)))
+}
+
+async fn open_vault(config: &VaultConfig) -> Result<PreimageVault> {
+    let keys: Box<dyn KeyProvider> = match &config.transit_url {
+        Some(url) => Box::new(TransitKeyProvider::new(url, &config.transit_key, env_key("VAULT_TOKEN")?)),
+        None => Box::new(LocalKeyProvider::new(&env_key("VAULT_MASTER_KEY")?)?),
+    };
+    PreimageVault::open(&config.path, &config.audit_path, keys.as_ref(), config.reveal).await
+}
+
+async fn store_secret(config: &Config, order_hash: &str) -> Result<()> {
+    let vault_config = config
+        .vault
+        .as_ref()
+        .ok_or_else(|| RelayerError::Config("no [vault] configured".into()))?;
+    let order_hash = decode_hex32(order_hash)
+        .map(|hash| format!("0x{}", hex::encode(hash)))
+        .ok_or_else(|| RelayerError::Config("order hash must be 32 bytes hex".into()))?;
+    let mut line = Zeroizing::new(String::new());
+    std::io::stdin()
+        .read_line(&mut line)
+        .map_err(|error| RelayerError::Vault(error.to_string()))?;
+    let preimage = Zeroizing::new(
+        decode_hex32(line.trim()).ok_or_else(|| RelayerError::Vault("preimage must be 32 bytes hex".into()))?,
+    );
+    open_vault(vault_config).await?.store(&order_hash, &preimage, "cli")?;
+    tracing::info!(order = %order_hash, "preimage stored");
+    Ok(())
+}
+
+async fn near_view(config: &Config, method: &str, args: &str) -> Result<()> {
+    let near = config
+        .near
+        .as_ref()
+        .ok_or_else(|| RelayerError::Config("no [near] configured".into()))?;
+    let args: serde_json::Value =
+        serde_json::from_str(args).map_err(|error| RelayerError::Config(format!("view arguments: {}", error)))?;
+    let result: serde_json::Value = near.client().view(&near.contract_id, method, args).await?;
+    println!("{}", result);
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .init();
+
+    let args = Args::parse();
+    let mut config = Config::load(&args.config)?;
+    match &args.command {
+        Some(Command::StoreSecret { order_hash }) => return store_secret(&config, order_hash).await,
+        Some(Command::NearView { method, args }) => return near_view(&config, method, args).await,
+        Some(Command::Run) | None => {}
+    }
+    let source_chain_id = config.ethereum.chain_id;
+
+    let store = OrderStore::open(&config.relayer.state_path, config.action_retention())?;
+    if let Some(block) = store.cursor() {
+        tracing::info!(block, orders = store.orders().len(), "resuming from stored state");
+        config.ethereum.start_block = Some(block + 1);
+    }
+
+    let source = EthereumSource::new(&config.ethereum, &env_key("ETHEREUM_PRIVATE_KEY")?)?;
+    tracing::info!(address = %source.client().address(), chain = source_chain_id, "watching Ethereum");
+
+    let mut destinations: Vec<Box<dyn Destination>> = Vec::new();
+    if let Some(near) = config.near.clone() {
+        tracing::info!(contract = %near.contract_id, account = %near.account_id, "relaying to NEAR");
+        let function_call_keys: Vec<String> = std::env::var("NEAR_FUNCTION_CALL_KEYS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(str::to_string)
+            .collect();
+        let destination = NearDestination::new(near, source_chain_id, &env_key("NEAR_PRIVATE_KEY")?, &function_call_keys)?;
+        destinations.push(Box::new(destination));
+    }
+    if let Some(cosmos) = config.cosmos.clone() {
+        tracing::info!(contract = %cosmos.contract, network = %cosmos.network_id, "relaying to Cosmos");
+        destinations.push(Box::new(CosmosDestination::new(cosmos, source_chain_id, &env_key("COSMOS_PRIVATE_KEY")?)?));
+    }
+
+    let mut relayer = Relayer::new(Box::new(source), destinations, config.relayer.hash_algorithm, store);
+    if let Some(vault) = &config.vault {
+        tracing::info!(path = %vault.path.display(), "releasing vault preimages to verified escrows");
+        relayer = relayer.with_vault(open_vault(vault).await?);
+    }
+    relayer.recover().await?;
+    let shutdown = async {
+        let _ = tokio::signal::ctrl_c().await;
+        tracing::info!("shutting down");
+    };
+    relayer.run(config.relayer.poll_interval(), shutdown).await
+}
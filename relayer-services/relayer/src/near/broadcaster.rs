@@ -0,0 +1,249 @@
+//! Signs and sends function calls for one account over several access keys
+//!
+//! Calls with a deposit go through the full-access key, since function-call
+//! keys can't attach one. Zero-deposit calls (claims) are spread round-robin
+//! over the function-call keys, each with its own locally reserved nonces,
+//! so many can be in flight in the same block. A key whose nonce is stale is
+//! resynced and the call moves on to the next key; a key out of allowance
+//! is retired.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use near_rpc::NearClient;
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use super::tx::{FunctionCall, NearSigner};
+use crate::error::{RelayerError, Result};
+
+/// Keys tried before a call is given up on
+const MAX_ATTEMPTS: usize = 4;
+
+struct KeySlot {
+    signer: NearSigner,
+    /// Last nonce used, `None` until read from the chain
+    nonce: Mutex<Option<u64>>,
+    /// Out of allowance, no longer used
+    retired: AtomicBool,
+}
+
+impl KeySlot {
+    fn new(signer: NearSigner) -> Self {
+        Self {
+            signer,
+            nonce: Mutex::new(None),
+            retired: AtomicBool::new(false),
+        }
+    }
+}
+
+/// Why a transaction was rejected before execution, as far as keys go
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum KeyError {
+    /// The key's nonce on chain is already `ak_nonce`
+    InvalidNonce { ak_nonce: u64 },
+    NotEnoughAllowance,
+    /// The block hash is too old
+    Expired,
+}
+
+pub struct Broadcaster {
+    client: NearClient,
+    full_access: KeySlot,
+    function_call: Vec<KeySlot>,
+    next: AtomicUsize,
+}
+
+impl Broadcaster {
+    pub fn new(client: NearClient, full_access: NearSigner, function_call: Vec<NearSigner>) -> Self {
+        Self {
+            client,
+            full_access: KeySlot::new(full_access),
+            function_call: function_call.into_iter().map(KeySlot::new).collect(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn client(&self) -> &NearClient {
+        &self.client
+    }
+
+    pub fn account_id(&self) -> &str {
+        &self.full_access.signer.account_id
+    }
+
+    /// Sign and send a function call, waiting for its outcome
+    /// Returns the transaction hash
+    pub async fn call(&self, contract_id: &str, method: &str, args: Value, gas: u64, deposit: u128) -> Result<String> {
+        let call = FunctionCall {
+            method_name: method.to_string(),
+            args: args.to_string().into_bytes(),
+            gas,
+            deposit,
+        };
+        let mut attempt = 1;
+        loop {
+            let slot = self.pick(deposit);
+            let nonce = self.reserve_nonce(slot).await?;
+            let block_hash = self.client.final_block_hash().await?;
+            let (signed, hash) = slot.signer.sign_function_call(contract_id, nonce, block_hash, call.clone());
+            let error = match self.client.send_transaction(&signed).await {
+                Ok(()) => return Ok(bs58::encode(hash).into_string()),
+                Err(error) => RelayerError::from(error),
+            };
+            let Some(key_error) = key_error(&error) else {
+                return Err(error);
+            };
+            let public_key = slot.signer.public_key();
+            match key_error {
+                KeyError::InvalidNonce { ak_nonce } => {
+                    let mut nonce = slot.nonce.lock().await;
+                    *nonce = Some(nonce.unwrap_or(0).max(ak_nonce));
+                }
+                KeyError::NotEnoughAllowance if !std::ptr::eq(slot, &self.full_access) => {
+                    tracing::warn!(%public_key, "access key out of allowance, retiring it");
+                    slot.retired.store(true, Ordering::Relaxed);
+                }
+                KeyError::NotEnoughAllowance | KeyError::Expired => {}
+            }
+            if attempt == MAX_ATTEMPTS {
+                return Err(error);
+            }
+            tracing::warn!(%public_key, attempt, ?key_error, "transaction rejected, retrying");
+            attempt += 1;
+        }
+    }
+
+    /// Full-access key for deposits, otherwise the next live function-call
+    /// key, falling back to the full-access key when none is left
+    fn pick(&self, deposit: u128) -> &KeySlot {
+        if deposit == 0 && !self.function_call.is_empty() {
+            let start = self.next.fetch_add(1, Ordering::Relaxed);
+            let live = (0..self.function_call.len())
+                .map(|offset| &self.function_call[(start + offset) % self.function_call.len()])
+                .find(|slot| !slot.retired.load(Ordering::Relaxed));
+            if let Some(slot) = live {
+                return slot;
+            }
+        }
+        &self.full_access
+    }
+
+    async fn reserve_nonce(&self, slot: &KeySlot) -> Result<u64> {
+        let mut nonce = slot.nonce.lock().await;
+        let last = match *nonce {
+            Some(last) => last,
+            None => {
+                self.client
+                    .access_key_nonce(&slot.signer.account_id, &slot.signer.public_key())
+                    .await?
+            }
+        };
+        *nonce = Some(last + 1);
+        Ok(last + 1)
+    }
+}
+
+/// Classify an RPC rejection, from the `InvalidTxError` nearcore puts in the
+/// error data
+fn key_error(error: &RelayerError) -> Option<KeyError> {
+    let RelayerError::Rpc { message, .. } = error else {
+        return None;
+    };
+    if message.contains("InvalidNonce") {
+        let rest = &message[message.find("\"ak_nonce\":")? + "\"ak_nonce\":".len()..];
+        let ak_nonce = rest.split(|c: char| !c.is_ascii_digit()).next()?.parse().ok()?;
+        Some(KeyError::InvalidNonce { ak_nonce })
+    } else if message.contains("NotEnoughAllowance") {
+        Some(KeyError::NotEnoughAllowance)
+    } else if message.contains("Expired") {
+        Some(KeyError::Expired)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+    use crate::rpc::JsonRpcClient;
+
+    fn signer(seed: u8) -> NearSigner {
+        let key = SigningKey::from_bytes(&[seed; 32]);
+        NearSigner::new("resolver.testnet", &format!("ed25519:{}", bs58::encode(key.to_bytes()).into_string())).unwrap()
+    }
+
+    fn with_keys(function_call_keys: u8) -> Broadcaster {
+        Broadcaster::new(
+            NearClient::new(JsonRpcClient::new("http://localhost:3030")),
+            signer(1),
+            (2..2 + function_call_keys).map(signer).collect(),
+        )
+    }
+
+    #[test]
+    fn test_key_error() {
+        let invalid_nonce = RelayerError::Rpc {
+            code: -32000,
+            message: r#"Server error ({"TxExecutionError":{"InvalidTxError":{"InvalidNonce":{"ak_nonce":112233,"tx_nonce":112230}}}})"#.into(),
+        };
+        assert_eq!(key_error(&invalid_nonce), Some(KeyError::InvalidNonce { ak_nonce: 112233 }));
+
+        let allowance = RelayerError::Rpc {
+            code: -32000,
+            message: r#"Server error ({"TxExecutionError":{"InvalidTxError":{"InvalidAccessKeyError":{"NotEnoughAllowance":{"account_id":"resolver.testnet","allowance":"1","cost":"2","public_key":"ed25519:x"}}}}})"#.into(),
+        };
+        assert_eq!(key_error(&allowance), Some(KeyError::NotEnoughAllowance));
+
+        let expired = RelayerError::Rpc {
+            code: -32000,
+            message: r#"Server error ({"TxExecutionError":{"InvalidTxError":"Expired"}})"#.into(),
+        };
+        assert_eq!(key_error(&expired), Some(KeyError::Expired));
+
+        assert_eq!(key_error(&RelayerError::TxFailed("InvalidNonce".into())), None);
+        let other = RelayerError::Rpc {
+            code: -32000,
+            message: "Server error (Timeout)".into(),
+        };
+        assert_eq!(key_error(&other), None);
+    }
+
+    #[test]
+    fn test_pick_round_robin() {
+        let broadcaster = with_keys(3);
+        let keys: Vec<String> = (0..4).map(|_| broadcaster.pick(0).signer.public_key()).collect();
+        assert_eq!(keys[0], keys[3]);
+        assert_ne!(keys[0], keys[1]);
+        assert_ne!(keys[1], keys[2]);
+        assert!(!keys.contains(&broadcaster.full_access.signer.public_key()));
+
+        // Deposits always use the full-access key
+        assert!(std::ptr::eq(broadcaster.pick(1), &broadcaster.full_access));
+    }
+
+    #[test]
+    fn test_pick_skips_retired_keys() {
+        let broadcaster = with_keys(2);
+        broadcaster.function_call[0].retired.store(true, Ordering::Relaxed);
+        for _ in 0..3 {
+            assert!(std::ptr::eq(broadcaster.pick(0), &broadcaster.function_call[1]));
+        }
+        broadcaster.function_call[1].retired.store(true, Ordering::Relaxed);
+        assert!(std::ptr::eq(broadcaster.pick(0), &broadcaster.full_access));
+
+        // Without function-call keys everything goes through the full-access key
+        let single = with_keys(0);
+        assert!(std::ptr::eq(single.pick(0), &single.full_access));
+    }
+
+    #[tokio::test]
+    async fn test_reserve_nonce_is_local() {
+        let broadcaster = with_keys(1);
+        let slot = &broadcaster.function_call[0];
+        *slot.nonce.lock().await = Some(41);
+        assert_eq!(broadcaster.reserve_nonce(slot).await.unwrap(), 42);
+        assert_eq!(broadcaster.reserve_nonce(slot).await.unwrap(), 43);
+    }
+}
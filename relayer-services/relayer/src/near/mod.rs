@@ -0,0 +1,95 @@
+//! NEAR side: creates `execute_fusion_order` escrows on the Fusion+
+//! contract and reads revealed preimages back
+
+pub mod broadcaster;
+pub mod tx;
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::chain::{Destination, Escrow, OrderView};
+use crate::config::NearConfig;
+use crate::error::Result;
+use crate::order::SwapOrder;
+use broadcaster::Broadcaster;
+use tx::NearSigner;
+
+/// Gas attached to `claim_fusion_order`, execution uses the configured gas
+const CLAIM_GAS: u64 = 50_000_000_000_000;
+
+/// Fusion+ contract on NEAR as a relayer destination
+pub struct NearDestination {
+    broadcaster: Broadcaster,
+    config: NearConfig,
+    /// Fusion+ chain ID orders come from
+    source_chain_id: u64,
+}
+
+impl NearDestination {
+    /// `secret_key` is the account's full-access key, `function_call_keys`
+    /// optional function-call keys on the contract used for claims
+    pub fn new(config: NearConfig, source_chain_id: u64, secret_key: &str, function_call_keys: &[String]) -> Result<Self> {
+        let signer = NearSigner::new(config.account_id.clone(), secret_key)?;
+        let function_call = function_call_keys
+            .iter()
+            .map(|key| NearSigner::new(config.account_id.clone(), key))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            broadcaster: Broadcaster::new(config.client(), signer, function_call),
+            config,
+            source_chain_id,
+        })
+    }
+
+    fn timelocks(&self) -> u128 {
+        let mut stages = [0; fusion_timelocks::STAGE_COUNT];
+        stages[..4].copy_from_slice(&self.config.timelocks);
+        fusion_timelocks::Timelocks::new(stages, 0).low_u128()
+    }
+}
+
+#[async_trait]
+impl Destination for NearDestination {
+    fn chain_id(&self) -> u64 {
+        self.config.chain_id
+    }
+
+    async fn execute_order(&self, order: &SwapOrder) -> Result<String> {
+        let bps: u16 = self
+            .broadcaster
+            .client()
+            .view(&self.config.contract_id, "get_min_safety_deposit_bps", json!({}))
+            .await?;
+        let safety_deposit = order.destination_amount * bps as u128 / 10_000;
+        let deposit = order.destination_amount + order.resolver_fee + safety_deposit;
+        let args = json!({
+            "order_hash": order.order_hash,
+            "hashlock": order.hashlock_hex(),
+            "maker": order.destination_address,
+            "resolver": self.config.account_id,
+            "amount": order.destination_amount.to_string(),
+            "resolver_fee": order.resolver_fee.to_string(),
+            "timelocks": self.timelocks().to_string(),
+            "source_chain_id": self.source_chain_id,
+        });
+        self.broadcaster
+            .call(&self.config.contract_id, "execute_fusion_order", args, self.config.gas, deposit)
+            .await
+    }
+
+    async fn escrow(&self, order: &SwapOrder) -> Result<Option<Escrow>> {
+        let view: Option<OrderView> = self
+            .broadcaster
+            .client()
+            .view(&self.config.contract_id, "get_order", json!({ "order_hash": order.order_hash }))
+            .await?;
+        view.map(OrderView::into_escrow).transpose()
+    }
+
+    async fn claim_order(&self, order: &SwapOrder, secret: &[u8; 32]) -> Result<String> {
+        let args = json!({ "order_hash": order.order_hash, "preimage": hex::encode(secret) });
+        self.broadcaster
+            .call(&self.config.contract_id, "claim_fusion_order", args, CLAIM_GAS, 0)
+            .await
+    }
+}
@@ -0,0 +1,161 @@
+//! NEAR transactions in their borsh wire format, signed with ed25519
+
+use borsh::BorshSerialize;
+use ed25519_dalek::{Signer, SigningKey};
+use sha2::{Digest, Sha256};
+
+use crate::error::{RelayerError, Result};
+
+#[derive(BorshSerialize)]
+enum PublicKey {
+    Ed25519([u8; 32]),
+}
+
+#[derive(BorshSerialize)]
+enum Signature {
+    Ed25519([u8; 64]),
+}
+
+#[derive(BorshSerialize, Clone, PartialEq, Eq, Debug)]
+pub struct FunctionCall {
+    pub method_name: String,
+    pub args: Vec<u8>,
+    pub gas: u64,
+    pub deposit: u128,
+}
+
+/// Only the action the relayer sends
+enum Action {
+    FunctionCall(FunctionCall),
+}
+
+impl BorshSerialize for Action {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        match self {
+            // Index of `FunctionCall` in nearcore's `Action`
+            Action::FunctionCall(call) => {
+                2u8.serialize(writer)?;
+                call.serialize(writer)
+            }
+        }
+    }
+}
+
+#[derive(BorshSerialize)]
+struct Transaction {
+    signer_id: String,
+    public_key: PublicKey,
+    nonce: u64,
+    receiver_id: String,
+    block_hash: [u8; 32],
+    actions: Vec<Action>,
+}
+
+#[derive(BorshSerialize)]
+struct SignedTransaction {
+    transaction: Transaction,
+    signature: Signature,
+}
+
+/// Account and full-access or function-call key signing transactions
+pub struct NearSigner {
+    pub account_id: String,
+    key: SigningKey,
+}
+
+impl NearSigner {
+    /// `secret_key` as NEAR CLI stores it: `ed25519:<base58 of seed || public key>`
+    pub fn new(account_id: impl Into<String>, secret_key: &str) -> Result<Self> {
+        let encoded = secret_key
+            .strip_prefix("ed25519:")
+            .ok_or_else(|| RelayerError::Key("expected an ed25519: key".into()))?;
+        let bytes = bs58::decode(encoded).into_vec().map_err(|e| RelayerError::Key(e.to_string()))?;
+        let seed: [u8; 32] = bytes
+            .get(..32)
+            .and_then(|seed| seed.try_into().ok())
+            .ok_or_else(|| RelayerError::Key("ed25519 key too short".into()))?;
+        let key = SigningKey::from_bytes(&seed);
+        if bytes.len() == 64 && bytes[32..] != key.verifying_key().to_bytes() {
+            return Err(RelayerError::Key("ed25519 public key does not match the seed".into()));
+        }
+        Ok(Self {
+            account_id: account_id.into(),
+            key,
+        })
+    }
+
+    /// `ed25519:<base58>` public key, as `view_access_key` expects it
+    pub fn public_key(&self) -> String {
+        format!("ed25519:{}", bs58::encode(self.key.verifying_key().to_bytes()).into_string())
+    }
+
+    /// Borsh-encoded signed transaction calling `call` on `receiver_id`,
+    /// and its hash
+    pub fn sign_function_call(
+        &self,
+        receiver_id: &str,
+        nonce: u64,
+        block_hash: [u8; 32],
+        call: FunctionCall,
+    ) -> (Vec<u8>, [u8; 32]) {
+        let transaction = Transaction {
+            signer_id: self.account_id.clone(),
+            public_key: PublicKey::Ed25519(self.key.verifying_key().to_bytes()),
+            nonce,
+            receiver_id: receiver_id.to_string(),
+            block_hash,
+            actions: vec![Action::FunctionCall(call)],
+        };
+        let hash: [u8; 32] = Sha256::digest(borsh::to_vec(&transaction).unwrap()).into();
+        let signature = Signature::Ed25519(self.key.sign(&hash).to_bytes());
+        let signed = SignedTransaction { transaction, signature };
+        (borsh::to_vec(&signed).unwrap(), hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Verifier, VerifyingKey};
+
+    fn signer() -> NearSigner {
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let secret = [key.to_bytes(), key.verifying_key().to_bytes()].concat();
+        NearSigner::new("resolver.testnet", &format!("ed25519:{}", bs58::encode(secret).into_string())).unwrap()
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert!(NearSigner::new("a.testnet", "secp256k1:abc").is_err());
+        let bad_public = [[1u8; 32], [2u8; 32]].concat();
+        assert!(NearSigner::new("a.testnet", &format!("ed25519:{}", bs58::encode(bad_public).into_string())).is_err());
+        assert!(signer().public_key().starts_with("ed25519:"));
+    }
+
+    #[test]
+    fn test_transaction_layout() {
+        let signer = signer();
+        let call = FunctionCall {
+            method_name: "claim_fusion_order".into(),
+            args: b"{}".to_vec(),
+            gas: 30_000_000_000_000,
+            deposit: 1,
+        };
+        let (bytes, hash) = signer.sign_function_call("fusion.testnet", 7, [3; 32], call);
+
+        // signer_id, then the key type tag and key
+        assert_eq!(&bytes[..4], &16u32.to_le_bytes());
+        assert_eq!(&bytes[4..20], b"resolver.testnet");
+        assert_eq!(bytes[20], 0);
+        // One action, tagged FunctionCall (2)
+        let after_block_hash = 20 + 1 + 32 + 8 + 4 + 14 + 32;
+        assert_eq!(&bytes[after_block_hash..after_block_hash + 4], &1u32.to_le_bytes());
+        assert_eq!(bytes[after_block_hash + 4], 2);
+
+        // The trailing signature signs the transaction hash
+        let signature: [u8; 64] = bytes[bytes.len() - 64..].try_into().unwrap();
+        assert_eq!(bytes[bytes.len() - 65], 0);
+        let public_key = VerifyingKey::from_bytes(&bytes[21..53].try_into().unwrap()).unwrap();
+        assert!(public_key.verify(&hash, &ed25519_dalek::Signature::from_bytes(&signature)).is_ok());
+    }
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+/// A Fusion+ order announced on Ethereum, as needed to mirror it on the
+/// destination chain
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct SwapOrder {
+    /// `0x`-prefixed lowercase hex
+    pub order_hash: String,
+    /// Maker's Ethereum address, `0x`-prefixed
+    pub maker: String,
+    pub source_token: String,
+    pub source_amount: u128,
+    /// Fusion+ chain ID of the destination (see `fusion_core::KNOWN_CHAINS`)
+    pub destination_chain_id: u64,
+    pub destination_token: String,
+    pub destination_amount: u128,
+    /// Account receiving the funds on the destination chain
+    pub destination_address: String,
+    pub resolver_fee: u128,
+    /// Unix seconds
+    pub expiry_time: u64,
+    pub hashlock: [u8; 32],
+    /// Ethereum block the order was announced in
+    pub block_number: u64,
+}
+
+impl SwapOrder {
+    /// Hashlock as unprefixed lowercase hex, the form the destination contracts store
+    pub fn hashlock_hex(&self) -> String {
+        hex::encode(self.hashlock)
+    }
+}
+
+/// Something that happened to an order on the source chain
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SourceEvent {
+    /// Seen in a block that is not final yet
+    Created(SwapOrder),
+    /// The block that created the order is final, the order can be acted on
+    Finalized { order_hash: String },
+    /// The secret was revealed on the source chain
+    Completed { order_hash: String, secret: [u8; 32] },
+    Cancelled { order_hash: String },
+    /// Blocks from `from_block` on were replaced, orders they created are void
+    Reorged { from_block: u64 },
+}
+
+/// Where the relayer is with an order
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub enum OrderPhase {
+    /// Seen on Ethereum in a block that may still be reorged out
+    Pending,
+    /// Creation is final, destination escrow not created yet
+    Finalized,
+    /// Destination escrow created in `tx`, `None` when the escrow was found
+    /// on chain while recovering
+    Executed { tx: Option<String> },
+    /// Secret known, claims still to be completed
+    SecretRevealed { secret: [u8; 32] },
+    /// Claimed on both chains
+    Completed,
+    /// Cancelled on the source chain
+    Cancelled,
+    /// Left to other resolvers by the fill policy, or filled by one of them
+    Declined { reason: String },
+}
+
+impl OrderPhase {
+    pub fn is_final(&self) -> bool {
+        matches!(self, OrderPhase::Completed | OrderPhase::Cancelled | OrderPhase::Declined { .. })
+    }
+}
+
+/// Order the relayer is working on
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct TrackedOrder {
+    pub order: SwapOrder,
+    pub phase: OrderPhase,
+    /// Whether the destination escrow has paid the maker
+    pub destination_claimed: bool,
+    /// Whether the source escrow has paid the resolver
+    pub source_claimed: bool,
+}
+
+impl TrackedOrder {
+    pub fn new(order: SwapOrder) -> Self {
+        Self {
+            order,
+            phase: OrderPhase::Pending,
+            destination_claimed: false,
+            source_claimed: false,
+        }
+    }
+}
@@ -0,0 +1,368 @@
+//! Several endpoints for one chain, so a single flaky RPC can't halt the
+//! relayer
+//!
+//! Endpoints are tried in configured order and the first healthy one serves
+//! every request, which keeps reads of the chain head on one node while it
+//! works. A request that fails at the transport level (connection, timeout,
+//! HTTP 429 or 502-504) moves on to the next endpoint; errors the node
+//! itself returned don't, another node would answer the same. An endpoint
+//! that fails `max_failures` times in a row, counting responses slower than
+//! `slow_request`, is benched for `cooldown` and must pass a health probe
+//! before it serves again. Benched endpoints are still tried when nothing
+//! else is left.
+
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::config::FailoverConfig;
+use crate::error::{RelayerError, Result};
+
+/// Weight of the newest sample in the latency average
+const LATENCY_WEIGHT: f64 = 0.2;
+
+/// Counters and health of one endpoint
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct EndpointMetrics {
+    pub url: String,
+    pub healthy: bool,
+    pub requests: u64,
+    pub failures: u64,
+    /// Requests slower than `slow_request`, successful or not
+    pub slow: u64,
+    /// Moving average over successful requests
+    pub latency_ms: Option<f64>,
+    pub last_error: Option<String>,
+}
+
+struct Endpoint {
+    url: String,
+    state: Mutex<State>,
+}
+
+struct State {
+    metrics: EndpointMetrics,
+    consecutive_failures: u32,
+    /// Benched until then, probed before serving again
+    benched_until: Option<Instant>,
+}
+
+impl Endpoint {
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+}
+
+/// Whether `error` says the endpoint, not the request, is at fault
+pub fn is_unavailable(error: &RelayerError) -> bool {
+    matches!(
+        error,
+        RelayerError::Http(_) | RelayerError::Unavailable(_) | RelayerError::RateLimited(_)
+    )
+}
+
+/// Error for HTTP statuses meaning `url` is overloaded or down
+pub fn status_error(url: &str, status: reqwest::StatusCode) -> Option<RelayerError> {
+    match status.as_u16() {
+        429 => Some(RelayerError::RateLimited(url.to_string())),
+        502..=504 => Some(RelayerError::Unavailable(format!("{} returned {}", url, status))),
+        _ => None,
+    }
+}
+
+pub struct Provider {
+    endpoints: Vec<Endpoint>,
+    http: reqwest::Client,
+    max_failures: u32,
+    cooldown: Duration,
+    slow_request: Duration,
+}
+
+impl Provider {
+    /// `urls` in order of preference
+    pub fn new(urls: impl IntoIterator<Item = String>, config: &FailoverConfig) -> Self {
+        let endpoints = urls
+            .into_iter()
+            .map(|url| Endpoint {
+                state: Mutex::new(State {
+                    metrics: EndpointMetrics {
+                        url: url.clone(),
+                        healthy: true,
+                        ..Default::default()
+                    },
+                    consecutive_failures: 0,
+                    benched_until: None,
+                }),
+                url,
+            })
+            .collect();
+        Self {
+            endpoints,
+            http: reqwest::Client::builder()
+                .timeout(config.request_timeout())
+                .build()
+                .expect("static client configuration"),
+            max_failures: config.max_failures.max(1),
+            cooldown: config.cooldown(),
+            slow_request: config.slow_request(),
+        }
+    }
+
+    /// HTTP client with the configured request timeout
+    pub fn http(&self) -> &reqwest::Client {
+        &self.http
+    }
+
+    /// First endpoint, for logs
+    pub fn url(&self) -> &str {
+        &self.endpoints[0].url
+    }
+
+    pub fn metrics(&self) -> Vec<EndpointMetrics> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| endpoint.state().metrics.clone())
+            .collect()
+    }
+
+    /// Run `call` against the first endpoint able to serve it, `probe`
+    /// checks a benched endpoint whose cooldown is over
+    pub async fn request<R, C, CF, P, PF>(&self, call: C, probe: P) -> Result<R>
+    where
+        C: Fn(String) -> CF,
+        CF: Future<Output = Result<R>>,
+        P: Fn(String) -> PF,
+        PF: Future<Output = Result<()>>,
+    {
+        let mut last_error = None;
+        let mut benched = Vec::new();
+        for endpoint in &self.endpoints {
+            let benched_until = endpoint.state().benched_until;
+            if let Some(until) = benched_until {
+                if Instant::now() < until {
+                    benched.push(endpoint);
+                    continue;
+                }
+                if let Err(error) = self.attempt(endpoint, &probe).await {
+                    tracing::debug!(url = %endpoint.url, %error, "health probe failed");
+                    last_error = Some(error);
+                    continue;
+                }
+            }
+            match self.attempt(endpoint, &call).await {
+                Err(error) if is_unavailable(&error) => last_error = Some(error),
+                result => return result,
+            }
+        }
+        // Nothing healthy left, a benched endpoint may have recovered
+        for endpoint in benched {
+            match self.attempt(endpoint, &call).await {
+                Err(error) if is_unavailable(&error) => last_error = Some(error),
+                result => return result,
+            }
+        }
+        Err(last_error.unwrap_or_else(|| RelayerError::Unavailable("no endpoints configured".into())))
+    }
+
+    async fn attempt<R, C, CF>(&self, endpoint: &Endpoint, call: &C) -> Result<R>
+    where
+        C: Fn(String) -> CF,
+        CF: Future<Output = Result<R>>,
+    {
+        let started = Instant::now();
+        let result = call(endpoint.url.clone()).await;
+        self.record(endpoint, started.elapsed(), result.as_ref().err());
+        result
+    }
+
+    fn record(&self, endpoint: &Endpoint, elapsed: Duration, error: Option<&RelayerError>) {
+        let mut state = endpoint.state();
+        let was_healthy = state.metrics.healthy;
+        state.metrics.requests += 1;
+        let unavailable = error.filter(|error| is_unavailable(error));
+        let slow = elapsed > self.slow_request;
+        if slow {
+            state.metrics.slow += 1;
+        }
+        if let Some(error) = unavailable {
+            state.metrics.failures += 1;
+            state.metrics.last_error = Some(error.to_string());
+        } else {
+            let sample = elapsed.as_secs_f64() * 1000.0;
+            state.metrics.latency_ms = Some(match state.metrics.latency_ms {
+                Some(average) => average + LATENCY_WEIGHT * (sample - average),
+                None => sample,
+            });
+        }
+
+        if unavailable.is_some() || slow {
+            state.consecutive_failures += 1;
+            if state.consecutive_failures >= self.max_failures {
+                state.metrics.healthy = false;
+                state.benched_until = Some(Instant::now() + self.cooldown);
+            }
+        } else {
+            state.consecutive_failures = 0;
+            state.metrics.healthy = true;
+            state.benched_until = None;
+        }
+
+        if was_healthy != state.metrics.healthy {
+            let metrics = &state.metrics;
+            if metrics.healthy {
+                tracing::info!(url = %endpoint.url, latency_ms = ?metrics.latency_ms, "endpoint recovered");
+            } else {
+                tracing::warn!(
+                    url = %endpoint.url,
+                    requests = metrics.requests,
+                    failures = metrics.failures,
+                    slow = metrics.slow,
+                    error = ?metrics.last_error,
+                    "endpoint benched, failing over"
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn config() -> FailoverConfig {
+        FailoverConfig {
+            max_failures: 2,
+            cooldown_secs: 0,
+            ..FailoverConfig::default()
+        }
+    }
+
+    fn provider(config: &FailoverConfig) -> Provider {
+        Provider::new(["http://a".to_string(), "http://b".to_string()], config)
+    }
+
+    fn down() -> RelayerError {
+        RelayerError::Unavailable("503".into())
+    }
+
+    /// Endpoints answering from a shared script, recording who was called
+    #[derive(Default)]
+    struct Script {
+        down: Mutex<Vec<&'static str>>,
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl Script {
+        async fn call(self: Arc<Self>, url: String) -> Result<String> {
+            self.calls.lock().unwrap().push(url.clone());
+            if self.down.lock().unwrap().iter().any(|down| url.ends_with(down)) {
+                return Err(down());
+            }
+            Ok(url)
+        }
+
+        fn take_calls(&self) -> Vec<String> {
+            std::mem::take(&mut self.calls.lock().unwrap())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_and_back() {
+        let provider = provider(&config());
+        let script = Arc::new(Script::default());
+        let call = |url| script.clone().call(url);
+        let probe = |url| {
+            let script = script.clone();
+            async move { script.call(url).await.map(|_| ()) }
+        };
+
+        assert_eq!(provider.request(call, probe).await.unwrap(), "http://a");
+
+        script.down.lock().unwrap().push("a");
+        script.take_calls();
+        assert_eq!(provider.request(call, probe).await.unwrap(), "http://b");
+        assert_eq!(provider.request(call, probe).await.unwrap(), "http://b");
+        assert_eq!(script.take_calls(), ["http://a", "http://b", "http://a", "http://b"]);
+        let metrics = provider.metrics();
+        assert!(!metrics[0].healthy);
+        assert_eq!((metrics[0].requests, metrics[0].failures), (3, 2));
+        assert!(metrics[1].healthy && metrics[1].latency_ms.is_some());
+
+        // Benched: only probed, and served again once the probe passes
+        provider.request(call, probe).await.unwrap();
+        assert_eq!(script.take_calls(), ["http://a", "http://b"]);
+        script.down.lock().unwrap().clear();
+        assert_eq!(provider.request(call, probe).await.unwrap(), "http://a");
+        assert_eq!(script.take_calls(), ["http://a", "http://a"]);
+        assert!(provider.metrics()[0].healthy);
+    }
+
+    #[tokio::test]
+    async fn test_node_errors_dont_fail_over() {
+        let provider = provider(&config());
+        let calls = Mutex::new(0);
+        let result: Result<()> = provider
+            .request(
+                |_| async {
+                    *calls.lock().unwrap() += 1;
+                    Err(RelayerError::Rpc {
+                        code: -32000,
+                        message: "nonce too low".into(),
+                    })
+                },
+                |_| async { Ok(()) },
+            )
+            .await;
+        assert!(matches!(result, Err(RelayerError::Rpc { .. })));
+        assert_eq!(*calls.lock().unwrap(), 1);
+        assert!(provider.metrics()[0].healthy);
+    }
+
+    #[tokio::test]
+    async fn test_benched_endpoints_are_a_last_resort() {
+        let provider = provider(&FailoverConfig {
+            max_failures: 1,
+            cooldown_secs: 3600,
+            ..FailoverConfig::default()
+        });
+        let script = Arc::new(Script::default());
+        *script.down.lock().unwrap() = vec!["a", "b"];
+        let call = |url| script.clone().call(url);
+        let probe = |_| async { Ok(()) };
+        assert!(matches!(provider.request(call, probe).await, Err(RelayerError::Unavailable(_))));
+        assert!(provider.metrics().iter().all(|metrics| !metrics.healthy));
+
+        script.down.lock().unwrap().clear();
+        script.take_calls();
+        assert_eq!(provider.request(call, probe).await.unwrap(), "http://a");
+        assert_eq!(script.take_calls(), ["http://a"]);
+        assert!(provider.metrics()[0].healthy);
+    }
+
+    #[tokio::test]
+    async fn test_slow_responses_bench() {
+        let provider = provider(&FailoverConfig {
+            max_failures: 1,
+            slow_request_ms: 0,
+            cooldown_secs: 3600,
+            ..FailoverConfig::default()
+        });
+        let result = provider
+            .request(
+                |url| async move {
+                    tokio::time::sleep(Duration::from_millis(2)).await;
+                    Ok(url)
+                },
+                |_| async { Ok(()) },
+            )
+            .await;
+        assert_eq!(result.unwrap(), "http://a");
+        let metrics = provider.metrics();
+        assert!(!metrics[0].healthy);
+        assert_eq!((metrics[0].slow, metrics[0].failures), (1, 0));
+    }
+}
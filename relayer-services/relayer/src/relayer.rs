@@ -0,0 +1,1018 @@
+//! The relayer loop: mirrors source orders to their destination chain,
+//! waits for the secret and completes both sides
+//!
+//! Every step is written to the [`OrderStore`] as soon as it is taken, so a
+//! restart picks up where the last run stopped. [`Relayer::recover`] then
+//! checks the steps that may have reached the chain without being recorded.
+//! Each transaction is also recorded by (chain, order, action) before it is
+//! sent, so neither a restart nor a duplicate event sends it twice.
+//!
+//! With a [`PreimageVault`] the relayer can also settle orders whose maker
+//! handed it the preimage: once the destination escrow is verified against
+//! the order (see [`crate::transmission`]) and the vault's reveal policy
+//! allows it, the preimage is decrypted and used to claim the escrow for
+//! the maker.
+//!
+//! Run as a competing resolver, a [`FillPolicy`] picks the orders worth
+//! filling, and an order whose destination escrow shows up before the
+//! relayer sent one was won by another resolver and is let go.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use fusion_hashlock::{HashAlgorithm, SoftwareHasher};
+
+use crate::chain::{Destination, Source};
+use crate::error::{RelayerError, Result};
+use crate::fill::{Decision, FillPolicy};
+use crate::order::{OrderPhase, SourceEvent, TrackedOrder};
+use crate::store::{Action, ActionKey, ActionStatus, OrderStore};
+use crate::transmission::{verify_escrow, EscrowMismatch};
+use crate::vault::{Preimage, PreimageVault, RevealConditions};
+
+/// How long a sent transaction that hasn't shown up on chain is waited for
+/// before it is sent again
+const RESEND_AFTER: Duration = Duration::from_secs(600);
+
+/// Time that must be left before a destination escrow can be cancelled for
+/// the maker's preimage to be released to claim it
+const REVEAL_MARGIN: Duration = Duration::from_secs(900);
+
+/// Accessor name in the vault's audit log
+const VAULT_ACCESSOR: &str = "relayer";
+
+pub struct Relayer {
+    source: Box<dyn Source>,
+    destinations: Vec<Box<dyn Destination>>,
+    hash_algorithm: HashAlgorithm,
+    store: OrderStore,
+    vault: Option<PreimageVault>,
+    fill_policy: Option<Box<dyn FillPolicy>>,
+}
+
+impl Relayer {
+    pub fn new(
+        source: Box<dyn Source>,
+        destinations: Vec<Box<dyn Destination>>,
+        hash_algorithm: HashAlgorithm,
+        store: OrderStore,
+    ) -> Self {
+        Self {
+            source,
+            destinations,
+            hash_algorithm,
+            store,
+            vault: None,
+            fill_policy: None,
+        }
+    }
+
+    /// Only fill the orders `policy` picks, as a resolver competing for them
+    pub fn with_fill_policy(mut self, policy: Box<dyn FillPolicy>) -> Self {
+        self.fill_policy = Some(policy);
+        self
+    }
+
+    /// Claim destination escrows with the preimages held in `vault`
+    pub fn with_vault(mut self, vault: PreimageVault) -> Self {
+        self.vault = Some(vault);
+        self
+    }
+
+    /// Orders still in progress, keyed by order hash
+    pub fn orders(&self) -> &HashMap<String, TrackedOrder> {
+        self.store.orders()
+    }
+
+    /// Reconcile stored orders with the chains after a restart
+    /// A crash between sending a transaction and recording it leaves the
+    /// order one step behind; the escrow and claim state on chain tell
+    /// whether that step happened, so it is not repeated
+    pub async fn recover(&mut self) -> Result<()> {
+        let orders: Vec<TrackedOrder> = self.store.orders().values().cloned().collect();
+        for mut tracked in orders {
+            let order_hash = tracked.order.order_hash.clone();
+            let Some(destination) = self.destination(tracked.order.destination_chain_id) else {
+                tracing::warn!(%order_hash, chain = tracked.order.destination_chain_id, "no destination configured for stored order");
+                continue;
+            };
+            if tracked.phase == OrderPhase::Finalized && destination.escrow_exists(&tracked.order).await? {
+                tracked.phase = OrderPhase::Executed { tx: None };
+            }
+            if matches!(tracked.phase, OrderPhase::SecretRevealed { .. }) {
+                if !tracked.destination_claimed && destination.revealed_secret(&tracked.order).await?.is_some() {
+                    tracked.destination_claimed = true;
+                }
+                if !tracked.source_claimed && !self.source.is_active(&order_hash).await? {
+                    tracked.source_claimed = true;
+                }
+            }
+            tracing::info!(%order_hash, phase = ?tracked.phase, "resuming order");
+            self.store.put(tracked)?;
+        }
+        Ok(())
+    }
+
+    /// Poll every `poll_interval` until `shutdown` resolves
+    /// Chain errors are logged and retried on the next tick; a store error
+    /// stops the relayer, as carrying on would leave steps unrecorded
+    pub async fn run(&mut self, poll_interval: Duration, shutdown: impl Future<Output = ()>) -> Result<()> {
+        tokio::pin!(shutdown);
+        let mut interval = tokio::time::interval(poll_interval);
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                _ = interval.tick() => match self.tick().await {
+                    Err(error @ RelayerError::Store(_)) => return Err(error),
+                    Err(error) => tracing::warn!(%error, "relayer tick failed"),
+                    Ok(()) => {}
+                },
+            }
+        }
+        tracing::info!(pending = self.store.orders().len(), "relayer stopped");
+        Ok(())
+    }
+
+    /// Apply new source events, then move every pending order on as far as
+    /// it goes without waiting
+    pub async fn tick(&mut self) -> Result<()> {
+        for event in self.source.poll().await? {
+            self.apply(event)?;
+        }
+
+        let mut orders: Vec<TrackedOrder> = self.store.orders().values().cloned().collect();
+        orders.sort_by_key(|tracked| tracked.order.block_number);
+        for mut tracked in orders {
+            loop {
+                let before = tracked.clone();
+                if let Err(error) = self.advance(&mut tracked).await {
+                    tracing::warn!(order = %tracked.order.order_hash, %error, "order step failed");
+                    break;
+                }
+                if tracked == before {
+                    break;
+                }
+                self.store.put(tracked.clone())?;
+                if tracked.phase.is_final() {
+                    tracing::info!(order = %tracked.order.order_hash, phase = ?tracked.phase, "order done");
+                    if let Some(vault) = &mut self.vault {
+                        if let Err(error) = vault.forget(&tracked.order.order_hash, VAULT_ACCESSOR) {
+                            tracing::warn!(order = %tracked.order.order_hash, %error, "could not drop preimage");
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+
+        if let Some(block) = self.source.finalized_block() {
+            self.store.set_cursor(block)?;
+        }
+        Ok(())
+    }
+
+    fn destination(&self, chain_id: u64) -> Option<&dyn Destination> {
+        self.destinations
+            .iter()
+            .find(|destination| destination.chain_id() == chain_id)
+            .map(|destination| destination.as_ref())
+    }
+
+    fn verifies(&self, tracked: &TrackedOrder, secret: &[u8; 32]) -> bool {
+        self.hash_algorithm.verify(&SoftwareHasher, secret, &tracked.order.hashlock)
+    }
+
+    fn apply(&mut self, event: SourceEvent) -> Result<()> {
+        match event {
+            SourceEvent::Created(order) => {
+                if self.destination(order.destination_chain_id).is_none() {
+                    tracing::debug!(order = %order.order_hash, chain = order.destination_chain_id, "no destination configured");
+                    return Ok(());
+                }
+                // Seen again when rescanning after a restart or a reorg
+                if self.store.orders().contains_key(&order.order_hash) {
+                    return Ok(());
+                }
+                tracing::info!(order = %order.order_hash, chain = order.destination_chain_id, "order pending finality");
+                self.store.put(TrackedOrder::new(order))
+            }
+            SourceEvent::Finalized { order_hash } => {
+                let Some(mut tracked) = self.store.orders().get(&order_hash).cloned() else {
+                    return Ok(());
+                };
+                if tracked.phase != OrderPhase::Pending {
+                    return Ok(());
+                }
+                tracing::info!(%order_hash, "order finalized");
+                tracked.phase = OrderPhase::Finalized;
+                self.store.put(tracked)
+            }
+            SourceEvent::Reorged { from_block } => {
+                let mut orphaned = Vec::new();
+                for (order_hash, tracked) in self.store.orders() {
+                    if tracked.order.block_number < from_block {
+                        continue;
+                    }
+                    if tracked.phase != OrderPhase::Pending {
+                        // Already acted on, a person has to look at it
+                        tracing::error!(%order_hash, from_block, "reorg past finality orphaned an order in progress");
+                        continue;
+                    }
+                    tracing::warn!(%order_hash, from_block, "order orphaned by reorg");
+                    orphaned.push(order_hash.clone());
+                }
+                orphaned.iter().try_for_each(|order_hash| self.store.remove(order_hash))
+            }
+            SourceEvent::Completed { order_hash, secret } => {
+                let Some(mut tracked) = self.store.orders().get(&order_hash).cloned() else {
+                    return Ok(());
+                };
+                if !self.verifies(&tracked, &secret) {
+                    tracing::warn!(%order_hash, "source revealed a secret that does not match the hashlock");
+                    return Ok(());
+                }
+                tracked.source_claimed = true;
+                if !matches!(tracked.phase, OrderPhase::SecretRevealed { .. }) {
+                    tracked.phase = OrderPhase::SecretRevealed { secret };
+                }
+                self.store.put(tracked)
+            }
+            SourceEvent::Cancelled { order_hash } => {
+                let Some(mut tracked) = self.store.orders().get(&order_hash).cloned() else {
+                    return Ok(());
+                };
+                tracked.phase = OrderPhase::Cancelled;
+                self.store.put(tracked)
+            }
+        }
+    }
+
+    /// Take the order's next step, at most one transaction
+    async fn advance(&mut self, tracked: &mut TrackedOrder) -> Result<()> {
+        let Some(destination) = self.destination(tracked.order.destination_chain_id) else {
+            return Ok(());
+        };
+        match tracked.phase.clone() {
+            OrderPhase::Pending => {}
+            OrderPhase::Finalized => {
+                if let Some(decision) = self.hold_back(tracked).await? {
+                    if let Decision::Decline { reason } = decision {
+                        tracing::info!(order = %tracked.order.order_hash, %reason, "not filling order");
+                        tracked.phase = OrderPhase::Declined { reason };
+                    }
+                    return Ok(());
+                }
+                if let Outcome::Done { tx } = self.perform(tracked, Step::Execute).await? {
+                    tracing::info!(order = %tracked.order.order_hash, ?tx, "destination escrow created");
+                    tracked.phase = OrderPhase::Executed { tx };
+                }
+            }
+            OrderPhase::Executed { .. } => {
+                if let Some(secret) = destination.revealed_secret(&tracked.order).await? {
+                    if !self.verifies(tracked, &secret) {
+                        tracing::warn!(order = %tracked.order.order_hash, "destination revealed a mismatching secret");
+                        return Ok(());
+                    }
+                    tracing::info!(order = %tracked.order.order_hash, "secret revealed on destination");
+                    // The escrow only reveals the preimage by paying out
+                    tracked.destination_claimed = true;
+                    tracked.phase = OrderPhase::SecretRevealed { secret };
+                } else if let Some(secret) = self.release_preimage(tracked).await? {
+                    // Only recorded once the claim is out, so the plaintext never sits in the log unrevealed
+                    if let Outcome::Done { tx } = self.perform(tracked, Step::ClaimDestination(&secret)).await? {
+                        tracing::info!(order = %tracked.order.order_hash, ?tx, "destination claimed with vault preimage");
+                        tracked.destination_claimed = true;
+                        tracked.phase = OrderPhase::SecretRevealed { secret: *secret };
+                    }
+                }
+            }
+            OrderPhase::SecretRevealed { secret } => {
+                if !tracked.destination_claimed {
+                    if let Outcome::Done { tx } = self.perform(tracked, Step::ClaimDestination(&secret)).await? {
+                        tracing::info!(order = %tracked.order.order_hash, ?tx, "destination claimed");
+                        tracked.destination_claimed = true;
+                    }
+                } else if !tracked.source_claimed {
+                    if let Outcome::Done { tx } = self.perform(tracked, Step::CompleteSource(&secret)).await? {
+                        tracing::info!(order = %tracked.order.order_hash, ?tx, "source completed");
+                        tracked.source_claimed = true;
+                    }
+                } else {
+                    tracked.phase = OrderPhase::Completed;
+                }
+            }
+            OrderPhase::Completed | OrderPhase::Cancelled | OrderPhase::Declined { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// Why the fill policy keeps a finalized order from being executed now,
+    /// `None` to go ahead; only consulted until the first attempt is recorded
+    async fn hold_back(&self, tracked: &TrackedOrder) -> Result<Option<Decision>> {
+        let Some(policy) = &self.fill_policy else {
+            return Ok(None);
+        };
+        let order = &tracked.order;
+        let Some(destination) = self.destination(order.destination_chain_id) else {
+            return Ok(None);
+        };
+        let key = ActionKey {
+            chain_id: destination.chain_id(),
+            order_hash: order.order_hash.clone(),
+            action: Action::Execute,
+        };
+        if self.store.action(&key).is_some() {
+            return Ok(None);
+        }
+        // Nothing sent yet, so an escrow already there is someone else's
+        if destination.escrow_exists(order).await? {
+            return Ok(Some(Decision::Decline {
+                reason: "filled by another resolver".into(),
+            }));
+        }
+        match policy.decide(order, now()) {
+            Decision::Fill => Ok(None),
+            Decision::Wait { reason } => {
+                tracing::debug!(order = %order.order_hash, %reason, "not filling yet");
+                Ok(Some(Decision::Wait { reason }))
+            }
+            decline => Ok(Some(decline)),
+        }
+    }
+
+    /// The order's preimage from the vault, if one is held and the reveal
+    /// policy allows it now
+    async fn release_preimage(&mut self, tracked: &TrackedOrder) -> Result<Option<Preimage>> {
+        let order = &tracked.order;
+        let Some(vault) = &mut self.vault else {
+            return Ok(None);
+        };
+        if !vault.holds(&order.order_hash)? {
+            return Ok(None);
+        }
+        let Some(destination) = self.destination(order.destination_chain_id) else {
+            return Ok(None);
+        };
+        let escrow = destination.escrow(order).await?;
+        let verified = match verify_escrow(order, escrow.as_ref(), now(), REVEAL_MARGIN.as_secs()) {
+            Ok(()) => true,
+            Err(EscrowMismatch::Missing) => false,
+            Err(mismatch) => {
+                tracing::warn!(order = %order.order_hash, %mismatch, "destination escrow not verified, withholding preimage");
+                false
+            }
+        };
+        let conditions = RevealConditions {
+            finalized: tracked.phase != OrderPhase::Pending,
+            escrow_verified: verified,
+        };
+        let vault = self.vault.as_mut().expect("checked above");
+        let Some(secret) = vault.reveal(&order.order_hash, &conditions, VAULT_ACCESSOR)? else {
+            return Ok(None);
+        };
+        if !self.verifies(tracked, &secret) {
+            tracing::warn!(order = %order.order_hash, "vault preimage does not match the hashlock");
+            return Ok(None);
+        }
+        Ok(Some(secret))
+    }
+
+    /// Send the transaction for `step` unless it already went through: one
+    /// recorded as confirmed is never sent again, otherwise the chain is
+    /// checked first, and a recent attempt whose outcome is unknown is
+    /// given time to land
+    async fn perform(&mut self, tracked: &TrackedOrder, step: Step<'_>) -> Result<Outcome> {
+        let order = &tracked.order;
+        let destination = self
+            .destination(order.destination_chain_id)
+            .ok_or_else(|| RelayerError::Config(format!("no destination for chain {}", order.destination_chain_id)))?;
+        let key = ActionKey {
+            chain_id: match step {
+                Step::CompleteSource(_) => self.source.chain_id(),
+                _ => destination.chain_id(),
+            },
+            order_hash: order.order_hash.clone(),
+            action: step.action(),
+        };
+
+        let status = self.store.action(&key).cloned();
+        if let Some(ActionStatus::Confirmed { tx }) = status {
+            tracing::info!(order = %order.order_hash, action = ?key.action, "already confirmed, not sending again");
+            return Ok(Outcome::Done { tx });
+        }
+        let landed = match step {
+            Step::Execute => destination.escrow_exists(order).await?,
+            Step::ClaimDestination(_) => destination.revealed_secret(order).await?.is_some(),
+            Step::CompleteSource(_) => !self.source.is_active(&order.order_hash).await?,
+        };
+        let earlier_tx = match &status {
+            Some(ActionStatus::Pending { tx, .. }) => tx.clone(),
+            _ => None,
+        };
+        if landed {
+            tracing::info!(order = %order.order_hash, action = ?key.action, "found on chain, not sending");
+            self.store
+                .set_action(key, ActionStatus::Confirmed { tx: earlier_tx.clone() })?;
+            return Ok(Outcome::Done { tx: earlier_tx });
+        }
+        if let Some(ActionStatus::Pending { since, .. }) = status {
+            if now() < since + RESEND_AFTER.as_secs() {
+                tracing::debug!(order = %order.order_hash, action = ?key.action, tx = ?earlier_tx, "waiting for earlier attempt");
+                return Ok(Outcome::Waiting);
+            }
+            tracing::warn!(order = %order.order_hash, action = ?key.action, tx = ?earlier_tx, "earlier attempt never landed, sending again");
+        }
+
+        // Recorded before sending, so a crash mid-send is not mistaken for never sent
+        self.store
+            .set_action(key.clone(), ActionStatus::Pending { tx: None, since: now() })?;
+        let destination = self.destination(order.destination_chain_id).expect("checked above");
+        let result = match step {
+            Step::Execute => destination.execute_order(order).await,
+            Step::ClaimDestination(secret) => destination.claim_order(order, secret).await,
+            Step::CompleteSource(secret) => self.source.complete_order(&order.order_hash, secret).await,
+        };
+        match result {
+            Ok(tx) => {
+                let status = ActionStatus::Pending {
+                    tx: Some(tx.clone()),
+                    since: now(),
+                };
+                self.store.set_action(key, status)?;
+                Ok(Outcome::Done { tx: Some(tx) })
+            }
+            // Rejected outright, nothing reached the chain
+            Err(error @ RelayerError::TxFailed(_)) => {
+                let status = ActionStatus::Failed {
+                    error: error.to_string(),
+                };
+                self.store.set_action(key, status)?;
+                Err(error)
+            }
+            // Anything else may or may not have been sent, stays pending
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// A transaction to send for an order
+#[derive(Clone, Copy)]
+enum Step<'a> {
+    Execute,
+    ClaimDestination(&'a [u8; 32]),
+    CompleteSource(&'a [u8; 32]),
+}
+
+impl Step<'_> {
+    fn action(&self) -> Action {
+        match self {
+            Step::Execute => Action::Execute,
+            Step::ClaimDestination(_) => Action::ClaimDestination,
+            Step::CompleteSource(_) => Action::CompleteSource,
+        }
+    }
+}
+
+enum Outcome {
+    /// Sent now, or found to have been sent before
+    Done { tx: Option<String> },
+    /// An earlier attempt may still land
+    Waiting,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use fusion_hashlock::Hasher;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::chain::Escrow;
+    use crate::order::SwapOrder;
+    use crate::vault::{AuditEvent, AuditLog, LocalKeyProvider, RevealPolicy};
+
+    const SECRET: [u8; 32] = [7; 32];
+
+    /// What the mocks were asked to do, and the on-chain state they report
+    #[derive(Default)]
+    struct Chains {
+        executed: Vec<String>,
+        claimed: Vec<String>,
+        completed: Vec<String>,
+        revealed: Option<[u8; 32]>,
+        escrow_exists: bool,
+        /// Amount the escrow reports instead of the order's
+        escrow_amount: Option<u128>,
+        source_inactive: bool,
+        reject_execution: bool,
+    }
+
+    type Shared = Arc<Mutex<Chains>>;
+
+    struct MockSource {
+        batches: VecDeque<Vec<SourceEvent>>,
+        chains: Shared,
+    }
+
+    #[async_trait]
+    impl Source for MockSource {
+        fn chain_id(&self) -> u64 {
+            11155111
+        }
+
+        async fn poll(&mut self) -> Result<Vec<SourceEvent>> {
+            Ok(self.batches.pop_front().unwrap_or_default())
+        }
+
+        async fn complete_order(&self, order_hash: &str, _secret: &[u8; 32]) -> Result<String> {
+            self.chains.lock().unwrap().completed.push(order_hash.to_string());
+            Ok("0xsource".into())
+        }
+
+        async fn is_active(&self, _order_hash: &str) -> Result<bool> {
+            Ok(!self.chains.lock().unwrap().source_inactive)
+        }
+
+        fn finalized_block(&self) -> Option<u64> {
+            Some(100 - self.batches.len() as u64)
+        }
+    }
+
+    struct MockDestination {
+        chains: Shared,
+    }
+
+    #[async_trait]
+    impl Destination for MockDestination {
+        fn chain_id(&self) -> u64 {
+            40002
+        }
+
+        async fn execute_order(&self, order: &SwapOrder) -> Result<String> {
+            let mut chains = self.chains.lock().unwrap();
+            if chains.reject_execution {
+                return Err(RelayerError::TxFailed("NotEnoughBalance".into()));
+            }
+            chains.executed.push(order.order_hash.clone());
+            Ok("execute-tx".into())
+        }
+
+        async fn escrow(&self, order: &SwapOrder) -> Result<Option<Escrow>> {
+            let chains = self.chains.lock().unwrap();
+            if !chains.escrow_exists {
+                return Ok(None);
+            }
+            Ok(Some(Escrow {
+                hashlock: order.hashlock,
+                maker: order.destination_address.clone(),
+                amount: chains.escrow_amount.unwrap_or(order.destination_amount),
+                status: fusion_core::OrderStatus::Matched,
+                preimage: chains.revealed,
+                withdrawal_at: now(),
+                cancellation_at: now() + 3_600,
+            }))
+        }
+
+        async fn escrow_exists(&self, _order: &SwapOrder) -> Result<bool> {
+            Ok(self.chains.lock().unwrap().escrow_exists)
+        }
+
+        async fn revealed_secret(&self, _order: &SwapOrder) -> Result<Option<[u8; 32]>> {
+            Ok(self.chains.lock().unwrap().revealed)
+        }
+
+        async fn claim_order(&self, order: &SwapOrder, _secret: &[u8; 32]) -> Result<String> {
+            self.chains.lock().unwrap().claimed.push(order.order_hash.clone());
+            Ok("claim-tx".into())
+        }
+    }
+
+    fn order(order_hash: &str, destination_chain_id: u64) -> SwapOrder {
+        SwapOrder {
+            order_hash: order_hash.into(),
+            maker: "0x".to_string() + &"aa".repeat(20),
+            source_token: "0x".to_string() + &"cc".repeat(20),
+            source_amount: 1_000,
+            destination_chain_id,
+            destination_token: "native".into(),
+            destination_amount: 5_000,
+            destination_address: "maker.testnet".into(),
+            resolver_fee: 25,
+            expiry_time: now() + 86_400,
+            hashlock: SoftwareHasher.sha256(&SECRET),
+            block_number: 1,
+        }
+    }
+
+    fn created(order_hash: &str) -> Vec<SourceEvent> {
+        vec![
+            SourceEvent::Created(order(order_hash, 40002)),
+            SourceEvent::Finalized {
+                order_hash: order_hash.into(),
+            },
+        ]
+    }
+
+    struct Harness {
+        relayer: Relayer,
+        chains: Shared,
+        directory: TempDir,
+    }
+
+    impl Harness {
+        fn new(batches: Vec<Vec<SourceEvent>>) -> Self {
+            let directory = tempfile::tempdir().unwrap();
+            let chains = Shared::default();
+            Self {
+                relayer: Self::relayer(&directory, &chains, batches),
+                chains,
+                directory,
+            }
+        }
+
+        fn relayer(directory: &TempDir, chains: &Shared, batches: Vec<Vec<SourceEvent>>) -> Relayer {
+            let source = MockSource {
+                batches: batches.into(),
+                chains: chains.clone(),
+            };
+            let destination = MockDestination { chains: chains.clone() };
+            let store = OrderStore::open(directory.path().join("state.jsonl"), Duration::from_secs(3_600)).unwrap();
+            Relayer::new(Box::new(source), vec![Box::new(destination)], HashAlgorithm::Sha256, store)
+        }
+
+        /// Drop the relayer and start a new one on the same store
+        fn restart(&mut self) {
+            self.relayer = Self::relayer(&self.directory, &self.chains, Vec::new());
+        }
+
+        fn phase(&self, order_hash: &str) -> &OrderPhase {
+            &self.relayer.orders()[order_hash].phase
+        }
+
+        async fn vault(&self) -> PreimageVault {
+            let keys = LocalKeyProvider::new(&"42".repeat(32)).unwrap();
+            let path = self.directory.path().join("vault.jsonl");
+            let audit = self.directory.path().join("audit.jsonl");
+            PreimageVault::open(path, audit, &keys, RevealPolicy::default()).await.unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_destination_reveal_completes_source() {
+        let mut harness = Harness::new(vec![created("0x01")]);
+
+        harness.relayer.tick().await.unwrap();
+        assert_eq!(harness.phase("0x01"), &OrderPhase::Executed { tx: Some("execute-tx".into()) });
+        harness.relayer.tick().await.unwrap();
+        assert!(matches!(harness.phase("0x01"), OrderPhase::Executed { .. }));
+
+        harness.chains.lock().unwrap().revealed = Some(SECRET);
+        harness.relayer.tick().await.unwrap();
+        assert!(harness.relayer.orders().is_empty());
+
+        let chains = harness.chains.lock().unwrap();
+        assert_eq!(chains.executed, ["0x01"]);
+        // The destination claim revealed the secret, only the source is left
+        assert!(chains.claimed.is_empty());
+        assert_eq!(chains.completed, ["0x01"]);
+    }
+
+    #[tokio::test]
+    async fn test_source_reveal_claims_destination() {
+        let mut harness = Harness::new(vec![
+            created("0x01"),
+            vec![SourceEvent::Completed {
+                order_hash: "0x01".into(),
+                secret: SECRET,
+            }],
+        ]);
+        harness.relayer.tick().await.unwrap();
+        harness.relayer.tick().await.unwrap();
+        assert!(harness.relayer.orders().is_empty());
+
+        let chains = harness.chains.lock().unwrap();
+        assert_eq!(chains.claimed, ["0x01"]);
+        assert!(chains.completed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_wrong_secret() {
+        let mut harness = Harness::new(vec![created("0x01")]);
+        harness.relayer.tick().await.unwrap();
+        harness.chains.lock().unwrap().revealed = Some([8; 32]);
+        harness.relayer.tick().await.unwrap();
+        assert!(matches!(harness.phase("0x01"), OrderPhase::Executed { .. }));
+        assert!(harness.chains.lock().unwrap().completed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_and_unknown_destination() {
+        let mut harness = Harness::new(vec![
+            vec![
+                SourceEvent::Created(order("0x01", 40002)),
+                SourceEvent::Created(order("0x02", 30001)),
+                SourceEvent::Finalized { order_hash: "0x01".into() },
+            ],
+            vec![SourceEvent::Cancelled { order_hash: "0x01".into() }],
+        ]);
+        harness.relayer.tick().await.unwrap();
+        assert_eq!(harness.relayer.orders().len(), 1);
+        harness.relayer.tick().await.unwrap();
+        assert!(harness.relayer.orders().is_empty());
+        assert_eq!(harness.chains.lock().unwrap().executed, ["0x01"]);
+    }
+
+    #[tokio::test]
+    async fn test_waits_for_finality() {
+        let mut harness = Harness::new(vec![
+            vec![SourceEvent::Created(order("0x01", 40002))],
+            vec![],
+            vec![SourceEvent::Finalized { order_hash: "0x01".into() }],
+        ]);
+        harness.relayer.tick().await.unwrap();
+        harness.relayer.tick().await.unwrap();
+        assert_eq!(harness.phase("0x01"), &OrderPhase::Pending);
+        assert!(harness.chains.lock().unwrap().executed.is_empty());
+
+        harness.relayer.tick().await.unwrap();
+        assert!(matches!(harness.phase("0x01"), OrderPhase::Executed { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_reorg_drops_pending_orders() {
+        let mut late = order("0x02", 40002);
+        late.block_number = 5;
+        let mut batch = created("0x01");
+        batch.push(SourceEvent::Created(late));
+        let mut harness = Harness::new(vec![batch, vec![SourceEvent::Reorged { from_block: 1 }]]);
+        harness.relayer.tick().await.unwrap();
+        harness.relayer.tick().await.unwrap();
+
+        // The finalized order survives a reorg it should never have seen
+        assert_eq!(harness.relayer.orders().len(), 1);
+        assert!(harness.relayer.orders().contains_key("0x01"));
+        assert_eq!(harness.chains.lock().unwrap().executed, ["0x01"]);
+    }
+
+    #[tokio::test]
+    async fn test_restart_resumes_orders() {
+        let mut harness = Harness::new(vec![created("0x01"), vec![]]);
+        harness.relayer.tick().await.unwrap();
+        harness.restart();
+        assert!(matches!(harness.phase("0x01"), OrderPhase::Executed { .. }));
+        assert_eq!(harness.relayer.store.cursor(), Some(99));
+
+        harness.relayer.recover().await.unwrap();
+        harness.chains.lock().unwrap().revealed = Some(SECRET);
+        harness.relayer.tick().await.unwrap();
+        assert!(harness.relayer.orders().is_empty());
+
+        // Nothing was sent twice
+        let chains = harness.chains.lock().unwrap();
+        assert_eq!(chains.executed, ["0x01"]);
+        assert_eq!(chains.completed, ["0x01"]);
+    }
+
+    #[tokio::test]
+    async fn test_recover_unrecorded_steps() {
+        let mut harness = Harness::new(Vec::new());
+        // Crashed after sending the execution, before recording it
+        let mut executed = TrackedOrder::new(order("0x01", 40002));
+        executed.phase = OrderPhase::Finalized;
+        // Crashed after completing on the source, before recording it
+        let mut completing = TrackedOrder::new(order("0x02", 40002));
+        completing.phase = OrderPhase::SecretRevealed { secret: SECRET };
+        completing.destination_claimed = true;
+        harness.relayer.store.put(executed).unwrap();
+        harness.relayer.store.put(completing).unwrap();
+        {
+            let mut chains = harness.chains.lock().unwrap();
+            chains.escrow_exists = true;
+            chains.source_inactive = true;
+        }
+
+        harness.restart();
+        harness.relayer.recover().await.unwrap();
+        assert_eq!(harness.phase("0x01"), &OrderPhase::Executed { tx: None });
+        assert!(harness.relayer.orders()["0x02"].source_claimed);
+
+        harness.relayer.tick().await.unwrap();
+        assert!(!harness.relayer.orders().contains_key("0x02"));
+        let chains = harness.chains.lock().unwrap();
+        assert!(chains.executed.is_empty());
+        assert!(chains.completed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_events_not_resent() {
+        let completed = SourceEvent::Completed {
+            order_hash: "0x01".into(),
+            secret: SECRET,
+        };
+        let mut harness = Harness::new(vec![created("0x01"), vec![completed.clone()], created("0x01"), vec![completed]]);
+        harness.relayer.tick().await.unwrap();
+        harness.relayer.tick().await.unwrap();
+        assert!(harness.relayer.orders().is_empty());
+        {
+            // What the chains show after the swap
+            let mut chains = harness.chains.lock().unwrap();
+            chains.escrow_exists = true;
+            chains.revealed = Some(SECRET);
+            chains.source_inactive = true;
+        }
+
+        // The same order announced again is walked through without sending
+        harness.relayer.tick().await.unwrap();
+        harness.relayer.tick().await.unwrap();
+        assert!(harness.relayer.orders().is_empty());
+
+        let chains = harness.chains.lock().unwrap();
+        assert_eq!(chains.executed, ["0x01"]);
+        assert_eq!(chains.claimed, ["0x01"]);
+        assert!(chains.completed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_outcome_checked_on_chain() {
+        let mut harness = Harness::new(vec![created("0x01")]);
+        let key = ActionKey {
+            chain_id: 40002,
+            order_hash: "0x01".into(),
+            action: Action::Execute,
+        };
+        // Crashed mid-send a moment ago
+        let pending = ActionStatus::Pending { tx: None, since: now() };
+        harness.relayer.store.set_action(key.clone(), pending).unwrap();
+
+        harness.relayer.tick().await.unwrap();
+        assert_eq!(harness.phase("0x01"), &OrderPhase::Finalized);
+        assert!(harness.chains.lock().unwrap().executed.is_empty());
+
+        harness.chains.lock().unwrap().escrow_exists = true;
+        harness.relayer.tick().await.unwrap();
+        assert_eq!(harness.phase("0x01"), &OrderPhase::Executed { tx: None });
+        assert_eq!(harness.relayer.store.action(&key), Some(&ActionStatus::Confirmed { tx: None }));
+        assert!(harness.chains.lock().unwrap().executed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stale_attempt_resent() {
+        let mut harness = Harness::new(vec![created("0x01")]);
+        let key = ActionKey {
+            chain_id: 40002,
+            order_hash: "0x01".into(),
+            action: Action::Execute,
+        };
+        let stale = ActionStatus::Pending {
+            tx: Some("dropped".into()),
+            since: now() - RESEND_AFTER.as_secs() - 1,
+        };
+        harness.relayer.store.set_action(key, stale).unwrap();
+
+        harness.relayer.tick().await.unwrap();
+        assert_eq!(harness.phase("0x01"), &OrderPhase::Executed { tx: Some("execute-tx".into()) });
+    }
+
+    #[tokio::test]
+    async fn test_rejected_transaction_retried() {
+        let mut harness = Harness::new(vec![created("0x01")]);
+        harness.chains.lock().unwrap().reject_execution = true;
+        harness.relayer.tick().await.unwrap();
+        let key = ActionKey {
+            chain_id: 40002,
+            order_hash: "0x01".into(),
+            action: Action::Execute,
+        };
+        assert!(matches!(harness.relayer.store.action(&key), Some(ActionStatus::Failed { .. })));
+
+        harness.chains.lock().unwrap().reject_execution = false;
+        harness.relayer.tick().await.unwrap();
+        assert!(matches!(harness.phase("0x01"), OrderPhase::Executed { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_vault_preimage_claims_destination() {
+        let mut harness = Harness::new(vec![created("0x01")]);
+        let mut vault = harness.vault().await;
+        vault.store("0x01", &SECRET, "cli").unwrap();
+        harness.relayer.vault = Some(vault);
+
+        // The escrow isn't visible yet, so the policy keeps the preimage sealed
+        harness.relayer.tick().await.unwrap();
+        assert!(matches!(harness.phase("0x01"), OrderPhase::Executed { .. }));
+        assert!(harness.chains.lock().unwrap().claimed.is_empty());
+
+        harness.chains.lock().unwrap().escrow_exists = true;
+        harness.relayer.tick().await.unwrap();
+        assert!(harness.relayer.orders().is_empty());
+        {
+            let chains = harness.chains.lock().unwrap();
+            assert_eq!(chains.claimed, ["0x01"]);
+            assert_eq!(chains.completed, ["0x01"]);
+        }
+        assert!(!harness.relayer.vault.as_mut().unwrap().holds("0x01").unwrap());
+
+        let events: Vec<AuditEvent> = AuditLog::read(&harness.directory.path().join("audit.jsonl"))
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.event)
+            .collect();
+        assert_eq!(
+            events,
+            [
+                AuditEvent::Stored,
+                AuditEvent::Denied {
+                    reason: "destination escrow not verified".into()
+                },
+                AuditEvent::Revealed,
+                AuditEvent::Forgotten,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mismatched_escrow_withholds_preimage() {
+        let mut harness = Harness::new(vec![created("0x01")]);
+        let mut vault = harness.vault().await;
+        vault.store("0x01", &SECRET, "cli").unwrap();
+        harness.relayer.vault = Some(vault);
+        {
+            let mut chains = harness.chains.lock().unwrap();
+            chains.escrow_exists = true;
+            chains.escrow_amount = Some(4_999);
+        }
+
+        harness.relayer.tick().await.unwrap();
+        assert!(matches!(harness.phase("0x01"), OrderPhase::Executed { .. }));
+        assert!(harness.chains.lock().unwrap().claimed.is_empty());
+        let entries = AuditLog::read(&harness.directory.path().join("audit.jsonl")).unwrap();
+        assert!(matches!(entries.last().unwrap().event, AuditEvent::Denied { .. }));
+
+        harness.chains.lock().unwrap().escrow_amount = None;
+        harness.relayer.tick().await.unwrap();
+        assert_eq!(harness.chains.lock().unwrap().claimed, ["0x01"]);
+    }
+
+    /// Fill policy replaying scripted decisions, then filling
+    struct ScriptedPolicy(Mutex<VecDeque<Decision>>);
+
+    impl FillPolicy for ScriptedPolicy {
+        fn decide(&self, _order: &SwapOrder, _now: u64) -> Decision {
+            self.0.lock().unwrap().pop_front().unwrap_or(Decision::Fill)
+        }
+    }
+
+    fn scripted(decisions: Vec<Decision>) -> Box<dyn FillPolicy> {
+        Box::new(ScriptedPolicy(Mutex::new(decisions.into())))
+    }
+
+    #[tokio::test]
+    async fn test_fill_policy_waits_and_declines() {
+        // Later block, so it is decided on after the first
+        let mut second = order("0x02", 40002);
+        second.block_number = 2;
+        let mut harness = Harness::new(vec![
+            vec![SourceEvent::Created(order("0x01", 40002)), SourceEvent::Created(second)],
+            vec![SourceEvent::Finalized { order_hash: "0x01".into() }],
+            vec![SourceEvent::Finalized { order_hash: "0x02".into() }],
+        ]);
+        let wait = Decision::Wait {
+            reason: "below spread".into(),
+        };
+        let decline = Decision::Decline {
+            reason: "no market".into(),
+        };
+        harness.relayer.fill_policy = Some(scripted(vec![wait, Decision::Fill, decline]));
+
+        harness.relayer.tick().await.unwrap();
+        harness.relayer.tick().await.unwrap();
+        assert_eq!(harness.phase("0x01"), &OrderPhase::Finalized);
+        harness.relayer.tick().await.unwrap();
+        assert!(matches!(harness.phase("0x01"), OrderPhase::Executed { .. }));
+        // Declined orders are dropped
+        assert!(!harness.relayer.orders().contains_key("0x02"));
+        assert_eq!(harness.chains.lock().unwrap().executed, ["0x01"]);
+    }
+
+    #[tokio::test]
+    async fn test_order_filled_by_another_resolver() {
+        let mut harness = Harness::new(vec![created("0x01")]);
+        harness.relayer.fill_policy = Some(scripted(Vec::new()));
+        harness.chains.lock().unwrap().escrow_exists = true;
+
+        harness.relayer.tick().await.unwrap();
+        assert!(harness.relayer.orders().is_empty());
+        assert!(harness.chains.lock().unwrap().executed.is_empty());
+    }
+}
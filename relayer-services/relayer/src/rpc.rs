@@ -0,0 +1,121 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use near_rpc::{NearRpcError, RpcTransport};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::FailoverConfig;
+use crate::error::{RelayerError, Result};
+use crate::provider::{status_error, Provider};
+
+/// JSON-RPC 2.0 over HTTP, as spoken by Ethereum and NEAR nodes
+pub struct JsonRpcClient {
+    provider: Provider,
+    /// Parameterless method a benched endpoint must answer before it
+    /// serves again
+    probe: Option<&'static str>,
+    next_id: AtomicU64,
+}
+
+#[derive(Serialize)]
+struct Request<'a, P> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: P,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    result: Option<Value>,
+    error: Option<ErrorObject>,
+}
+
+#[derive(Deserialize)]
+struct ErrorObject {
+    code: i64,
+    message: String,
+    #[serde(default)]
+    data: Option<Value>,
+}
+
+impl JsonRpcClient {
+    /// Single endpoint
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            provider: Provider::new([url.into()], &FailoverConfig::default()),
+            probe: None,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Fails over across `provider`'s endpoints, probing benched ones with
+    /// `probe`
+    pub fn with_failover(provider: Provider, probe: &'static str) -> Self {
+        Self {
+            provider,
+            probe: Some(probe),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        self.provider.url()
+    }
+
+    pub fn provider(&self) -> &Provider {
+        &self.provider
+    }
+
+    pub async fn call<P: Serialize, R: DeserializeOwned>(&self, method: &str, params: P) -> Result<R> {
+        let params = serde_json::to_value(params).map_err(RelayerError::decode)?;
+        let probe = |url: String| async move {
+            match self.probe {
+                Some(probe) => self.post::<Value>(&url, probe, &Value::Array(Vec::new())).await.map(drop),
+                None => Ok(()),
+            }
+        };
+        let params = &params;
+        self.provider
+            .request(|url: String| async move { self.post(&url, method, params).await }, probe)
+            .await
+    }
+
+    async fn post<R: DeserializeOwned>(&self, url: &str, method: &str, params: &Value) -> Result<R> {
+        let request = Request {
+            jsonrpc: "2.0",
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            method,
+            params,
+        };
+        let response = self.provider.http().post(url).json(&request).send().await?;
+        if let Some(error) = status_error(url, response.status()) {
+            return Err(error);
+        }
+        let response: Response = response.json().await?;
+        if let Some(error) = response.error {
+            let message = match error.data {
+                Some(data) => format!("{} ({})", error.message, data),
+                None => error.message,
+            };
+            return Err(RelayerError::Rpc { code: error.code, message });
+        }
+        serde_json::from_value(response.result.unwrap_or(Value::Null)).map_err(RelayerError::decode)
+    }
+}
+
+/// Lets a [`near_rpc::NearClient`] fail over across this client's endpoints
+#[async_trait]
+impl RpcTransport for JsonRpcClient {
+    async fn call(&self, method: &str, params: &Value) -> near_rpc::Result<Value> {
+        JsonRpcClient::call(self, method, params).await.map_err(|error| match error {
+            RelayerError::Http(error) => NearRpcError::Http(error),
+            RelayerError::Rpc { code, message } => NearRpcError::Rpc { code, message },
+            RelayerError::RateLimited(url) => NearRpcError::RateLimited(url),
+            RelayerError::Decode(message) => NearRpcError::Decode(message),
+            other => NearRpcError::Unavailable(other.to_string()),
+        })
+    }
+}
@@ -0,0 +1,458 @@
+//! Crash-safe record of the orders in flight, of every transaction the
+//! relayer sends for them, and of how far the source chain has been
+//! processed
+//!
+//! An append-only JSON-lines log: every state change is one line, flushed
+//! with `fsync` before the relayer moves on, so a restart replays to exactly
+//! the last step taken. Opening the log compacts it to the live state (via
+//! a temporary file renamed over the log). A line torn by a crash mid-write
+//! can only be the last one and is dropped.
+//!
+//! Actions outlive their orders: a duplicate or replayed event that brings
+//! a finished order back still finds what was already sent for it. Once the
+//! order is past its expiry and the action retention (its escrows' last
+//! timelock), compaction drops them.
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{RelayerError, Result};
+use crate::order::TrackedOrder;
+
+/// Appends after which the log is compacted again
+const COMPACT_AFTER: usize = 10_000;
+
+/// Transaction the relayer sends for an order
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Create and fund the destination escrow
+    Execute,
+    /// Claim the destination escrow for the maker
+    ClaimDestination,
+    /// Reveal the secret on the source chain
+    CompleteSource,
+}
+
+/// Idempotency key of a transaction
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ActionKey {
+    /// Chain the transaction goes to
+    pub chain_id: u64,
+    pub order_hash: String,
+    pub action: Action,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionStatus {
+    /// About to be sent, or sent as `tx`, since the unix time `since`;
+    /// whether it landed is not known yet
+    Pending { tx: Option<String>, since: u64 },
+    /// Seen on chain, never to be sent again
+    Confirmed { tx: Option<String> },
+    /// Rejected before reaching the chain, safe to send again
+    Failed { error: String },
+}
+
+// Externally tagged: internal tagging buffers values and can't carry the u128 amounts
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Record {
+    Order(Box<TrackedOrder>),
+    Action { key: ActionKey, status: ActionStatus },
+    /// The order finished, its actions may be dropped from the unix time `prune_after`
+    Retired { order_hash: String, prune_after: u64 },
+    /// Every source block up to `block` has been applied
+    Cursor { block: u64 },
+}
+
+#[derive(Default)]
+struct State {
+    orders: HashMap<String, TrackedOrder>,
+    actions: HashMap<ActionKey, ActionStatus>,
+    /// Finished orders with actions, keyed by order hash, to when they may be dropped
+    retired: HashMap<String, u64>,
+    cursor: Option<u64>,
+}
+
+impl State {
+    fn apply(&mut self, record: Record) {
+        match record {
+            Record::Order(tracked) if tracked.phase.is_final() => {
+                self.orders.remove(&tracked.order.order_hash);
+            }
+            Record::Order(tracked) => {
+                // Brought back, its actions are live again
+                self.retired.remove(&tracked.order.order_hash);
+                self.orders.insert(tracked.order.order_hash.clone(), *tracked);
+            }
+            Record::Action { key, status } => {
+                self.actions.insert(key, status);
+            }
+            Record::Retired { order_hash, prune_after } => {
+                self.retired.insert(order_hash, prune_after);
+            }
+            Record::Cursor { block } => self.cursor = Some(block),
+        }
+    }
+
+    /// Drop the actions of orders retired before `now`
+    fn prune(&mut self, now: u64) {
+        self.retired.retain(|_, prune_after| *prune_after > now);
+        let (orders, retired) = (&self.orders, &self.retired);
+        self.actions
+            .retain(|key, _| orders.contains_key(&key.order_hash) || retired.contains_key(&key.order_hash));
+    }
+
+    /// Records that rebuild this state
+    fn records(&self) -> impl Iterator<Item = Record> + '_ {
+        let orders = self.orders.values().map(|tracked| Record::Order(Box::new(tracked.clone())));
+        let actions = self.actions.iter().map(|(key, status)| Record::Action {
+            key: key.clone(),
+            status: status.clone(),
+        });
+        let retired = self.retired.iter().map(|(order_hash, prune_after)| Record::Retired {
+            order_hash: order_hash.clone(),
+            prune_after: *prune_after,
+        });
+        let cursor = self.cursor.map(|block| Record::Cursor { block });
+        orders.chain(actions).chain(retired).chain(cursor)
+    }
+}
+
+pub struct OrderStore {
+    path: PathBuf,
+    log: File,
+    state: State,
+    appended: usize,
+    action_retention: Duration,
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> RelayerError {
+    RelayerError::Store(format!("{}: {}", path.display(), error))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+impl OrderStore {
+    /// Open the log at `path`, creating it if missing. The actions of an
+    /// order are kept for `action_retention` after it finishes, and at least
+    /// until its expiry
+    pub fn open(path: impl Into<PathBuf>, action_retention: Duration) -> Result<Self> {
+        let path = path.into();
+        let mut state = Self::replay(&path)?;
+        // Actions logged before orders were retired are kept for one more retention
+        let prune_after = now() + action_retention.as_secs();
+        let unretired: Vec<String> = state
+            .actions
+            .keys()
+            .map(|key| &key.order_hash)
+            .filter(|order_hash| !state.orders.contains_key(*order_hash) && !state.retired.contains_key(*order_hash))
+            .cloned()
+            .collect();
+        for order_hash in unretired {
+            state.retired.insert(order_hash, prune_after);
+        }
+        state.prune(now());
+        Ok(Self {
+            log: Self::write_compacted(&path, &state)?,
+            path,
+            state,
+            appended: 0,
+            action_retention,
+        })
+    }
+
+    fn replay(path: &Path) -> Result<State> {
+        let mut state = State::default();
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(state),
+            Err(error) => return Err(io_error(path, error)),
+        };
+        let lines: Vec<String> = BufReader::new(file)
+            .lines()
+            .collect::<std::io::Result<_>>()
+            .map_err(|error| io_error(path, error))?;
+        for (index, line) in lines.iter().enumerate() {
+            match serde_json::from_str(line) {
+                Ok(record) => state.apply(record),
+                Err(_) if index + 1 == lines.len() => {
+                    tracing::warn!(path = %path.display(), "dropping torn last record");
+                }
+                Err(error) => {
+                    return Err(RelayerError::Store(format!("{} line {}: {}", path.display(), index + 1, error)))
+                }
+            }
+        }
+        Ok(state)
+    }
+
+    /// Rewrite the log with only the live state, returns it opened for appending
+    fn write_compacted(path: &Path, state: &State) -> Result<File> {
+        let temporary = path.with_extension("compacting");
+        let mut file = File::create(&temporary).map_err(|error| io_error(&temporary, error))?;
+        let mut contents = String::new();
+        for record in state.records() {
+            contents += &serde_json::to_string(&record).map_err(RelayerError::decode)?;
+            contents.push('\n');
+        }
+        file.write_all(contents.as_bytes())
+            .and_then(|_| file.sync_all())
+            .map_err(|error| io_error(&temporary, error))?;
+        std::fs::rename(&temporary, path).map_err(|error| io_error(path, error))?;
+        // Make the rename itself durable
+        if let Some(directory) = path.parent().filter(|directory| !directory.as_os_str().is_empty()) {
+            File::open(directory)
+                .and_then(|directory| directory.sync_all())
+                .map_err(|error| io_error(directory, error))?;
+        }
+        OpenOptions::new()
+            .append(true)
+            .open(path)
+            .map_err(|error| io_error(path, error))
+    }
+
+    /// Write `record` durably, then apply it
+    fn append(&mut self, record: Record) -> Result<()> {
+        let mut line = serde_json::to_string(&record).map_err(RelayerError::decode)?;
+        line.push('\n');
+        self.log
+            .write_all(line.as_bytes())
+            .and_then(|_| self.log.sync_data())
+            .map_err(|error| io_error(&self.path, error))?;
+        self.state.apply(record);
+        self.appended += 1;
+        if self.appended >= COMPACT_AFTER {
+            self.state.prune(now());
+            self.log = Self::write_compacted(&self.path, &self.state)?;
+            self.appended = 0;
+        }
+        Ok(())
+    }
+
+    /// Orders in flight, keyed by order hash
+    pub fn orders(&self) -> &HashMap<String, TrackedOrder> {
+        &self.state.orders
+    }
+
+    /// Last source block fully applied
+    pub fn cursor(&self) -> Option<u64> {
+        self.state.cursor
+    }
+
+    /// Record the order's new state, a final phase removes it and retires its actions
+    /// A no-op when nothing changed
+    pub fn put(&mut self, tracked: TrackedOrder) -> Result<()> {
+        let current = self.state.orders.get(&tracked.order.order_hash);
+        if current == Some(&tracked) || (current.is_none() && tracked.phase.is_final()) {
+            return Ok(());
+        }
+        let order_hash = &tracked.order.order_hash;
+        let has_actions = self.state.actions.keys().any(|key| &key.order_hash == order_hash);
+        let retired = (tracked.phase.is_final() && has_actions).then(|| Record::Retired {
+            order_hash: order_hash.clone(),
+            prune_after: tracked.order.expiry_time.max(now() + self.action_retention.as_secs()),
+        });
+        self.append(Record::Order(Box::new(tracked)))?;
+        retired.map_or(Ok(()), |record| self.append(record))
+    }
+
+    /// Drop an order without it reaching a final phase (orphaned by a reorg)
+    pub fn remove(&mut self, order_hash: &str) -> Result<()> {
+        let Some(mut tracked) = self.state.orders.get(order_hash).cloned() else {
+            return Ok(());
+        };
+        tracked.phase = crate::order::OrderPhase::Cancelled;
+        self.put(tracked)
+    }
+
+    pub fn set_cursor(&mut self, block: u64) -> Result<()> {
+        if self.state.cursor == Some(block) {
+            return Ok(());
+        }
+        self.append(Record::Cursor { block })
+    }
+
+    /// Last recorded status of the transaction `key`
+    pub fn action(&self, key: &ActionKey) -> Option<&ActionStatus> {
+        self.state.actions.get(key)
+    }
+
+    pub fn set_action(&mut self, key: ActionKey, status: ActionStatus) -> Result<()> {
+        if self.state.actions.get(&key) == Some(&status) {
+            return Ok(());
+        }
+        self.append(Record::Action { key, status })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::{OrderPhase, SwapOrder};
+
+    const RETENTION: Duration = Duration::from_secs(3_600);
+
+    fn tracked(order_hash: &str) -> TrackedOrder {
+        TrackedOrder::new(SwapOrder {
+            order_hash: order_hash.into(),
+            maker: "0xaa".into(),
+            source_token: "0xcc".into(),
+            source_amount: 1_000,
+            destination_chain_id: 40002,
+            destination_token: "near".into(),
+            destination_amount: 5_000,
+            destination_address: "maker.testnet".into(),
+            resolver_fee: 25,
+            expiry_time: 1_700_000_000,
+            hashlock: [7; 32],
+            block_number: 3,
+        })
+    }
+
+    #[test]
+    fn test_resume_after_restart() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("state.jsonl");
+        {
+            let mut store = OrderStore::open(&path, RETENTION).unwrap();
+            store.put(tracked("0x01")).unwrap();
+            store.put(tracked("0x02")).unwrap();
+            let mut revealed = tracked("0x01");
+            revealed.phase = OrderPhase::SecretRevealed { secret: [9; 32] };
+            revealed.destination_claimed = true;
+            store.put(revealed).unwrap();
+            let mut done = tracked("0x02");
+            done.phase = OrderPhase::Completed;
+            store.put(done).unwrap();
+            store.set_cursor(42).unwrap();
+        }
+
+        let store = OrderStore::open(&path, RETENTION).unwrap();
+        assert_eq!(store.cursor(), Some(42));
+        assert_eq!(store.orders().len(), 1);
+        let order = &store.orders()["0x01"];
+        assert_eq!(order.phase, OrderPhase::SecretRevealed { secret: [9; 32] });
+        assert!(order.destination_claimed);
+
+        // Compacted to the one live order and the cursor
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+    }
+
+    #[test]
+    fn test_actions_outlive_orders() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("state.jsonl");
+        let key = ActionKey {
+            chain_id: 40002,
+            order_hash: "0x01".into(),
+            action: Action::Execute,
+        };
+        {
+            let mut store = OrderStore::open(&path, RETENTION).unwrap();
+            store.put(tracked("0x01")).unwrap();
+            store
+                .set_action(key.clone(), ActionStatus::Pending { tx: None, since: 1 })
+                .unwrap();
+            store
+                .set_action(key.clone(), ActionStatus::Confirmed { tx: Some("tx".into()) })
+                .unwrap();
+            store.remove("0x01").unwrap();
+        }
+
+        let store = OrderStore::open(&path, RETENTION).unwrap();
+        assert!(store.orders().is_empty());
+        assert_eq!(store.action(&key), Some(&ActionStatus::Confirmed { tx: Some("tx".into()) }));
+        let other = ActionKey {
+            action: Action::ClaimDestination,
+            ..key
+        };
+        assert_eq!(store.action(&other), None);
+    }
+
+    #[test]
+    fn test_actions_pruned_once_retired() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("state.jsonl");
+        let key = |order_hash: &str| ActionKey {
+            chain_id: 40002,
+            order_hash: order_hash.into(),
+            action: Action::Execute,
+        };
+        {
+            let mut store = OrderStore::open(&path, Duration::ZERO).unwrap();
+            // 0x01 expired long ago, 0x02 expires in an hour
+            let mut unexpired = tracked("0x02");
+            unexpired.order.expiry_time = now() + 3_600;
+            store.put(tracked("0x01")).unwrap();
+            store.put(unexpired.clone()).unwrap();
+            for order_hash in ["0x01", "0x02"] {
+                store
+                    .set_action(key(order_hash), ActionStatus::Confirmed { tx: None })
+                    .unwrap();
+            }
+            store.remove("0x01").unwrap();
+            unexpired.phase = OrderPhase::Completed;
+            store.put(unexpired).unwrap();
+        }
+
+        let store = OrderStore::open(&path, Duration::ZERO).unwrap();
+        assert_eq!(store.action(&key("0x01")), None);
+        assert_eq!(store.action(&key("0x02")), Some(&ActionStatus::Confirmed { tx: None }));
+        // The kept action and its retirement
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+
+        // Actions logged before orders were retired get one retention
+        let legacy = r#"{"action":{"key":{"chain_id":40002,"order_hash":"0x03","action":"execute"},"status":{"confirmed":{"tx":null}}}}"#;
+        std::fs::write(&path, format!("{}\n", legacy)).unwrap();
+        let store = OrderStore::open(&path, RETENTION).unwrap();
+        assert_eq!(store.action(&key("0x03")), Some(&ActionStatus::Confirmed { tx: None }));
+        assert!(std::fs::read_to_string(&path).unwrap().contains(r#"{"retired":{"order_hash":"0x03""#));
+    }
+
+    #[test]
+    fn test_unchanged_state_not_written() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("state.jsonl");
+        let mut store = OrderStore::open(&path, RETENTION).unwrap();
+        store.put(tracked("0x01")).unwrap();
+        store.put(tracked("0x01")).unwrap();
+        store.set_cursor(1).unwrap();
+        store.set_cursor(1).unwrap();
+        store.remove("0x02").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+
+        store.remove("0x01").unwrap();
+        assert!(store.orders().is_empty());
+        assert!(OrderStore::open(&path, RETENTION).unwrap().orders().is_empty());
+    }
+
+    #[test]
+    fn test_torn_last_record() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("state.jsonl");
+        {
+            let mut store = OrderStore::open(&path, RETENTION).unwrap();
+            store.put(tracked("0x01")).unwrap();
+        }
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"order":{"order":{"order_ha"#).unwrap();
+        assert_eq!(OrderStore::open(&path, RETENTION).unwrap().orders().len(), 1);
+
+        // Corruption anywhere else is not silently skipped
+        std::fs::write(&path, "garbage\n{\"cursor\":{\"block\":1}}\n").unwrap();
+        assert!(matches!(OrderStore::open(&path, RETENTION), Err(RelayerError::Store(_))));
+    }
+}
@@ -0,0 +1,162 @@
+//! Conditional secret transmission: a maker's preimage is handed to the
+//! resolver only once the destination escrow, queried on chain, is shown
+//! to pay the maker on the order's terms
+//!
+//! Checked: the escrow exists and is funded, locks the order's hashlock,
+//! pays the order's destination address at least the order's amount, and
+//! its timelocks let the maker be paid now, with enough time before the
+//! resolver can cancel, and end before the source order expires (so the
+//! resolver, once it has the secret, can still complete the source side).
+
+use fusion_core::OrderStatus;
+use thiserror::Error;
+
+use crate::chain::Escrow;
+use crate::order::SwapOrder;
+
+/// Why an escrow doesn't qualify for the preimage
+#[derive(Clone, PartialEq, Eq, Debug, Error)]
+pub enum EscrowMismatch {
+    #[error("no destination escrow")]
+    Missing,
+    #[error("escrow is {0:?}, not funded and open")]
+    NotOpen(OrderStatus),
+    #[error("escrow locks a different hashlock")]
+    Hashlock,
+    #[error("escrow pays {found}, not the maker's {expected}")]
+    Maker { expected: String, found: String },
+    #[error("escrow holds {found}, less than the order's {expected}")]
+    Amount { expected: u128, found: u128 },
+    #[error("maker can't be paid before {withdrawal_at}")]
+    NotWithdrawable { withdrawal_at: u64 },
+    #[error("escrow can be cancelled at {cancellation_at}, too soon to claim safely")]
+    CancelsTooSoon { cancellation_at: u64 },
+    #[error("escrow can be cancelled at {cancellation_at}, after the source order expires at {expiry_time}")]
+    OutlivesSource { cancellation_at: u64, expiry_time: u64 },
+}
+
+/// Check `escrow` against `order` at unix time `now`; `margin` is how many
+/// seconds must be left before the escrow can be cancelled
+pub fn verify_escrow(order: &SwapOrder, escrow: Option<&Escrow>, now: u64, margin: u64) -> Result<(), EscrowMismatch> {
+    let escrow = escrow.ok_or(EscrowMismatch::Missing)?;
+    if escrow.status != OrderStatus::Matched || escrow.preimage.is_some() {
+        return Err(EscrowMismatch::NotOpen(escrow.status.clone()));
+    }
+    if escrow.hashlock != order.hashlock {
+        return Err(EscrowMismatch::Hashlock);
+    }
+    if escrow.maker != order.destination_address {
+        return Err(EscrowMismatch::Maker {
+            expected: order.destination_address.clone(),
+            found: escrow.maker.clone(),
+        });
+    }
+    if escrow.amount < order.destination_amount {
+        return Err(EscrowMismatch::Amount {
+            expected: order.destination_amount,
+            found: escrow.amount,
+        });
+    }
+    if now < escrow.withdrawal_at {
+        return Err(EscrowMismatch::NotWithdrawable {
+            withdrawal_at: escrow.withdrawal_at,
+        });
+    }
+    if escrow.cancellation_at < now + margin {
+        return Err(EscrowMismatch::CancelsTooSoon {
+            cancellation_at: escrow.cancellation_at,
+        });
+    }
+    if escrow.cancellation_at >= order.expiry_time {
+        return Err(EscrowMismatch::OutlivesSource {
+            cancellation_at: escrow.cancellation_at,
+            expiry_time: order.expiry_time,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_800_000_000;
+    const MARGIN: u64 = 900;
+
+    fn order() -> SwapOrder {
+        SwapOrder {
+            order_hash: "0x01".into(),
+            maker: "0x".to_string() + &"aa".repeat(20),
+            source_token: "0x".to_string() + &"cc".repeat(20),
+            source_amount: 1_000,
+            destination_chain_id: 40002,
+            destination_token: "native".into(),
+            destination_amount: 5_000,
+            destination_address: "maker.testnet".into(),
+            resolver_fee: 25,
+            expiry_time: NOW + 7_200,
+            hashlock: [1; 32],
+            block_number: 1,
+        }
+    }
+
+    fn escrow() -> Escrow {
+        Escrow {
+            hashlock: [1; 32],
+            maker: "maker.testnet".into(),
+            amount: 5_000,
+            status: OrderStatus::Matched,
+            preimage: None,
+            withdrawal_at: NOW - 60,
+            cancellation_at: NOW + 3_600,
+        }
+    }
+
+    fn check(change: impl FnOnce(&mut Escrow)) -> Result<(), EscrowMismatch> {
+        let mut escrow = escrow();
+        change(&mut escrow);
+        verify_escrow(&order(), Some(&escrow), NOW, MARGIN)
+    }
+
+    #[test]
+    fn test_matching_escrow() {
+        assert_eq!(check(|_| {}), Ok(()));
+        // Overfunding only helps the maker
+        assert_eq!(check(|escrow| escrow.amount = 6_000), Ok(()));
+    }
+
+    #[test]
+    fn test_terms_mismatch() {
+        assert_eq!(verify_escrow(&order(), None, NOW, MARGIN), Err(EscrowMismatch::Missing));
+        assert_eq!(
+            check(|escrow| escrow.status = OrderStatus::Refunded),
+            Err(EscrowMismatch::NotOpen(OrderStatus::Refunded))
+        );
+        assert!(matches!(check(|escrow| escrow.preimage = Some([2; 32])), Err(EscrowMismatch::NotOpen(_))));
+        assert_eq!(check(|escrow| escrow.hashlock = [2; 32]), Err(EscrowMismatch::Hashlock));
+        assert!(matches!(check(|escrow| escrow.maker = "mallory.testnet".into()), Err(EscrowMismatch::Maker { .. })));
+        assert_eq!(
+            check(|escrow| escrow.amount = 4_999),
+            Err(EscrowMismatch::Amount {
+                expected: 5_000,
+                found: 4_999
+            })
+        );
+    }
+
+    #[test]
+    fn test_timelocks_mismatch() {
+        assert!(matches!(
+            check(|escrow| escrow.withdrawal_at = NOW + 1),
+            Err(EscrowMismatch::NotWithdrawable { .. })
+        ));
+        assert!(matches!(
+            check(|escrow| escrow.cancellation_at = NOW + MARGIN - 1),
+            Err(EscrowMismatch::CancelsTooSoon { .. })
+        ));
+        assert!(matches!(
+            check(|escrow| escrow.cancellation_at = NOW + 7_200),
+            Err(EscrowMismatch::OutlivesSource { .. })
+        ));
+    }
+}
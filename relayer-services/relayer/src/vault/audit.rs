@@ -0,0 +1,89 @@
+//! Append-only record of every access to the vault's preimages
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{RelayerError, Result};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// Preimage sealed into the vault
+    Stored,
+    /// Store refused, e.g. a preimage was already held for the order
+    Rejected { reason: String },
+    /// Preimage decrypted and handed out
+    Revealed,
+    /// Reveal refused by the policy, nothing was decrypted
+    Denied { reason: String },
+    /// Preimage dropped once the order was over
+    Forgotten,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct AuditEntry {
+    /// Unix time in seconds
+    pub at: u64,
+    pub order_hash: String,
+    /// Who asked, e.g. `relayer` or `cli`
+    pub accessor: String,
+    pub event: AuditEvent,
+}
+
+pub struct AuditLog {
+    path: PathBuf,
+    file: File,
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> RelayerError {
+    RelayerError::Vault(format!("{}: {}", path.display(), error))
+}
+
+impl AuditLog {
+    /// Open the log at `path` for appending, creating it if missing
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|error| io_error(&path, error))?;
+        Ok(Self { path, file })
+    }
+
+    /// Write an entry durably; the access it describes only goes ahead if
+    /// this succeeds
+    pub fn record(&mut self, order_hash: &str, accessor: &str, event: AuditEvent) -> Result<()> {
+        let entry = AuditEntry {
+            at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+            order_hash: order_hash.to_string(),
+            accessor: accessor.to_string(),
+            event,
+        };
+        let mut line = serde_json::to_string(&entry).map_err(RelayerError::decode)?;
+        line.push('\n');
+        self.file
+            .write_all(line.as_bytes())
+            .and_then(|_| self.file.sync_data())
+            .map_err(|error| io_error(&self.path, error))
+    }
+
+    /// Every entry in the log at `path`, oldest first
+    pub fn read(path: &Path) -> Result<Vec<AuditEntry>> {
+        let file = File::open(path).map_err(|error| io_error(path, error))?;
+        BufReader::new(file)
+            .lines()
+            .map(|line| {
+                let line = line.map_err(|error| io_error(path, error))?;
+                serde_json::from_str(&line).map_err(RelayerError::decode)
+            })
+            .collect()
+    }
+}
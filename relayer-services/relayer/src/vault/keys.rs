@@ -0,0 +1,188 @@
+//! Wrapping of the vault's data key by a master key that never touches disk
+
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::Deserialize;
+use serde_json::json;
+use zeroize::Zeroizing;
+
+use crate::error::{RelayerError, Result};
+
+/// Data key as handed out by a [`KeyProvider`], wiped when dropped
+pub type DataKey = Zeroizing<[u8; 32]>;
+
+/// Master key holder: encrypts ("wraps") and decrypts the data key
+#[async_trait]
+pub trait KeyProvider: Send + Sync {
+    /// Short name stored next to the wrapped key
+    fn name(&self) -> &'static str;
+
+    async fn wrap(&self, key: &DataKey) -> Result<String>;
+
+    async fn unwrap(&self, wrapped: &str) -> Result<DataKey>;
+}
+
+fn vault_error(what: impl std::fmt::Display) -> RelayerError {
+    RelayerError::Vault(what.to_string())
+}
+
+/// Fresh random 256-bit key
+pub fn generate_key() -> Result<DataKey> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    SystemRandom::new()
+        .fill(key.as_mut())
+        .map_err(|_| vault_error("system randomness unavailable"))?;
+    Ok(key)
+}
+
+/// Master key from the environment (`VAULT_MASTER_KEY`, 32 bytes hex),
+/// wrapping with AES-256-GCM in-process
+pub struct LocalKeyProvider {
+    master: LessSafeKey,
+}
+
+impl LocalKeyProvider {
+    pub fn new(master_key_hex: &str) -> Result<Self> {
+        let bytes = Zeroizing::new(hex::decode(master_key_hex.trim_start_matches("0x")).map_err(vault_error)?);
+        let key = UnboundKey::new(&AES_256_GCM, &bytes).map_err(|_| vault_error("master key must be 32 bytes"))?;
+        Ok(Self {
+            master: LessSafeKey::new(key),
+        })
+    }
+}
+
+#[async_trait]
+impl KeyProvider for LocalKeyProvider {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn wrap(&self, key: &DataKey) -> Result<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| vault_error("system randomness unavailable"))?;
+        let mut sealed = key.to_vec();
+        self.master
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut sealed)
+            .map_err(|_| vault_error("wrapping failed"))?;
+        Ok(BASE64.encode([nonce.as_slice(), &sealed].concat()))
+    }
+
+    async fn unwrap(&self, wrapped: &str) -> Result<DataKey> {
+        let bytes = BASE64.decode(wrapped).map_err(vault_error)?;
+        if bytes.len() < NONCE_LEN {
+            return Err(vault_error("wrapped key too short"));
+        }
+        let (nonce, sealed) = bytes.split_at(NONCE_LEN);
+        let mut sealed = Zeroizing::new(sealed.to_vec());
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| vault_error("bad nonce"))?;
+        let plain = self
+            .master
+            .open_in_place(nonce, Aad::empty(), &mut sealed)
+            .map_err(|_| vault_error("wrong master key or corrupted vault"))?;
+        let mut key = Zeroizing::new([0u8; 32]);
+        if plain.len() != 32 {
+            return Err(vault_error("wrapped key has the wrong length"));
+        }
+        key.copy_from_slice(plain);
+        Ok(key)
+    }
+}
+
+#[derive(Deserialize)]
+struct TransitResponse {
+    data: TransitData,
+}
+
+#[derive(Deserialize)]
+struct TransitData {
+    #[serde(default)]
+    ciphertext: Option<String>,
+    #[serde(default)]
+    plaintext: Option<String>,
+}
+
+/// HashiCorp Vault's transit engine as the KMS: the master key stays in
+/// Vault and only encrypt/decrypt calls leave the relayer
+pub struct TransitKeyProvider {
+    http: reqwest::Client,
+    url: String,
+    key_name: String,
+    token: String,
+}
+
+impl TransitKeyProvider {
+    pub fn new(url: &str, key_name: &str, token: String) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+            key_name: key_name.to_string(),
+            token,
+        }
+    }
+
+    async fn transit(&self, operation: &str, body: serde_json::Value) -> Result<TransitData> {
+        let response = self
+            .http
+            .post(format!("{}/v1/transit/{}/{}", self.url, operation, self.key_name))
+            .header("X-Vault-Token", &self.token)
+            .json(&body)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(vault_error(format!("transit {} failed: {}", operation, response.status())));
+        }
+        Ok(response.json::<TransitResponse>().await?.data)
+    }
+}
+
+#[async_trait]
+impl KeyProvider for TransitKeyProvider {
+    fn name(&self) -> &'static str {
+        "transit"
+    }
+
+    async fn wrap(&self, key: &DataKey) -> Result<String> {
+        let data = self
+            .transit("encrypt", json!({ "plaintext": BASE64.encode(key.as_slice()) }))
+            .await?;
+        data.ciphertext.ok_or_else(|| vault_error("transit returned no ciphertext"))
+    }
+
+    async fn unwrap(&self, wrapped: &str) -> Result<DataKey> {
+        let data = self.transit("decrypt", json!({ "ciphertext": wrapped })).await?;
+        let plain = Zeroizing::new(
+            BASE64
+                .decode(data.plaintext.ok_or_else(|| vault_error("transit returned no plaintext"))?)
+                .map_err(vault_error)?,
+        );
+        let key: [u8; 32] = plain
+            .as_slice()
+            .try_into()
+            .map_err(|_| vault_error("unwrapped key has the wrong length"))?;
+        Ok(Zeroizing::new(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_wrap_roundtrip() {
+        let provider = LocalKeyProvider::new(&"11".repeat(32)).unwrap();
+        let key = generate_key().unwrap();
+        let wrapped = provider.wrap(&key).await.unwrap();
+        assert_eq!(*provider.unwrap(&wrapped).await.unwrap(), *key);
+        // Fresh nonce per wrap
+        assert_ne!(provider.wrap(&key).await.unwrap(), wrapped);
+
+        let other = LocalKeyProvider::new(&"22".repeat(32)).unwrap();
+        assert!(matches!(other.unwrap(&wrapped).await, Err(RelayerError::Vault(_))));
+        assert!(LocalKeyProvider::new("1234").is_err());
+    }
+}
@@ -0,0 +1,458 @@
+//! Preimages held on behalf of makers, encrypted at rest and only released
+//! once the order is safe to settle
+//!
+//! Envelope encryption: each preimage is sealed with AES-256-GCM under a
+//! random data key, with the order hash as associated data so a sealed
+//! preimage can't be moved to another order. The data key is stored only
+//! wrapped by a [`KeyProvider`] (a KMS, or a master key from the
+//! environment) and unwrapped into memory when the vault is opened.
+//!
+//! The vault file is append-only JSON lines, `fsync`ed like the
+//! [`crate::store`] log. A line torn by a crash mid-write is cut off when
+//! the vault is opened, in place since other processes may have the file
+//! open, so the next record starts on a line of its own. A reveal decrypts only when the [`RevealPolicy`]
+//! holds for the order, and every store, reveal, refusal and deletion is
+//! written to the [`AuditLog`] before it takes effect.
+
+pub mod audit;
+pub mod keys;
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::error::{RelayerError, Result};
+
+pub use audit::{AuditEntry, AuditEvent, AuditLog};
+pub use keys::{KeyProvider, LocalKeyProvider, TransitKeyProvider};
+
+/// A decrypted preimage, wiped when dropped
+pub type Preimage = Zeroizing<[u8; 32]>;
+
+/// What has to be true of an order before its preimage is decrypted
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(default)]
+pub struct RevealPolicy {
+    /// The source order is past `finality_depth`
+    pub require_finality: bool,
+    /// The destination escrow exists and was checked against the order
+    pub require_escrow_verified: bool,
+}
+
+impl Default for RevealPolicy {
+    fn default() -> Self {
+        Self {
+            require_finality: true,
+            require_escrow_verified: true,
+        }
+    }
+}
+
+/// What is known of an order when its preimage is asked for
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct RevealConditions {
+    pub finalized: bool,
+    pub escrow_verified: bool,
+}
+
+impl RevealPolicy {
+    /// Why `conditions` don't allow a reveal, `None` if they do
+    pub fn refusal(&self, conditions: &RevealConditions) -> Option<&'static str> {
+        if self.require_finality && !conditions.finalized {
+            return Some("source order not final");
+        }
+        if self.require_escrow_verified && !conditions.escrow_verified {
+            return Some("destination escrow not verified");
+        }
+        None
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Record {
+    /// The data key, wrapped by the named provider; always the first line
+    Key { provider: String, wrapped: String },
+    Secret {
+        order_hash: String,
+        /// Hex AES-GCM nonce
+        nonce: String,
+        /// Hex ciphertext followed by the tag
+        sealed: String,
+    },
+    Forgotten { order_hash: String },
+}
+
+struct Sealed {
+    nonce: [u8; NONCE_LEN],
+    sealed: Vec<u8>,
+}
+
+pub struct PreimageVault {
+    path: PathBuf,
+    file: File,
+    cipher: LessSafeKey,
+    secrets: HashMap<String, Sealed>,
+    policy: RevealPolicy,
+    audit: AuditLog,
+}
+
+fn vault_error(what: impl std::fmt::Display) -> RelayerError {
+    RelayerError::Vault(what.to_string())
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> RelayerError {
+    vault_error(format!("{}: {}", path.display(), error))
+}
+
+impl PreimageVault {
+    /// Open the vault at `path`, creating it with a fresh data key wrapped
+    /// by `keys` if missing
+    pub async fn open(
+        path: impl Into<PathBuf>,
+        audit_path: impl Into<PathBuf>,
+        keys: &dyn KeyProvider,
+        policy: RevealPolicy,
+    ) -> Result<Self> {
+        let path = path.into();
+        let (records, intact_len) = Self::read(&path)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|error| io_error(&path, error))?;
+        let len = file.metadata().map_err(|error| io_error(&path, error))?.len();
+        if len > intact_len {
+            file.set_len(intact_len)
+                .and_then(|_| file.sync_data())
+                .map_err(|error| io_error(&path, error))?;
+        }
+        let data_key = match records.first() {
+            Some(Record::Key { provider, wrapped }) => {
+                if provider != keys.name() {
+                    return Err(vault_error(format!("vault key is wrapped by {}, not {}", provider, keys.name())));
+                }
+                keys.unwrap(wrapped).await?
+            }
+            Some(_) => return Err(vault_error(format!("{}: missing data key", path.display()))),
+            None => {
+                let data_key = keys::generate_key()?;
+                let record = Record::Key {
+                    provider: keys.name().to_string(),
+                    wrapped: keys.wrap(&data_key).await?,
+                };
+                Self::write(&path, &mut file, &record)?;
+                data_key
+            }
+        };
+        let cipher = LessSafeKey::new(
+            UnboundKey::new(&AES_256_GCM, data_key.as_slice()).map_err(|_| vault_error("bad data key"))?,
+        );
+        let mut vault = Self {
+            path,
+            file,
+            cipher,
+            secrets: HashMap::new(),
+            policy,
+            audit: AuditLog::open(audit_path)?,
+        };
+        vault.apply(records)?;
+        Ok(vault)
+    }
+
+    /// Records in the vault file and the length of the file they span,
+    /// short of a torn last line
+    fn read(path: &Path) -> Result<(Vec<Record>, u64)> {
+        let contents = match std::fs::read(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok((Vec::new(), 0)),
+            Err(error) => return Err(io_error(path, error)),
+        };
+        let lines: Vec<&[u8]> = contents.split_inclusive(|byte| *byte == b'\n').collect();
+        let mut records = Vec::with_capacity(lines.len());
+        let mut intact_len = 0;
+        for (index, line) in lines.iter().enumerate() {
+            match serde_json::from_slice(line) {
+                // Only a line written out to its newline was acknowledged
+                Ok(record) if line.ends_with(b"\n") => {
+                    records.push(record);
+                    intact_len += line.len() as u64;
+                }
+                Err(error) if index + 1 < lines.len() => {
+                    return Err(vault_error(format!("{} line {}: {}", path.display(), index + 1, error)))
+                }
+                // Torn by a crash mid-write
+                _ => tracing::warn!(path = %path.display(), "dropping torn last record"),
+            }
+        }
+        Ok((records, intact_len))
+    }
+
+    fn write(path: &Path, file: &mut File, record: &Record) -> Result<()> {
+        let mut line = serde_json::to_string(record).map_err(RelayerError::decode)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())
+            .and_then(|_| file.sync_data())
+            .map_err(|error| io_error(path, error))
+    }
+
+    fn apply(&mut self, records: Vec<Record>) -> Result<()> {
+        self.secrets.clear();
+        for record in records {
+            match record {
+                Record::Key { .. } => {}
+                Record::Secret {
+                    order_hash,
+                    nonce,
+                    sealed,
+                } => {
+                    let nonce = hex::decode(nonce)
+                        .ok()
+                        .and_then(|nonce| nonce.try_into().ok())
+                        .ok_or_else(|| vault_error(format!("bad nonce for {}", order_hash)))?;
+                    let sealed = hex::decode(sealed).map_err(vault_error)?;
+                    self.secrets.insert(order_hash, Sealed { nonce, sealed });
+                }
+                Record::Forgotten { order_hash } => {
+                    self.secrets.remove(&order_hash);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Pick up preimages stored by another process since the vault was opened
+    fn reload(&mut self) -> Result<()> {
+        let (records, _) = Self::read(&self.path)?;
+        self.apply(records)
+    }
+
+    /// Whether a preimage is held for the order, rereading the vault file
+    /// if it isn't known yet
+    pub fn holds(&mut self, order_hash: &str) -> Result<bool> {
+        if !self.secrets.contains_key(order_hash) {
+            self.reload()?;
+        }
+        Ok(self.secrets.contains_key(order_hash))
+    }
+
+    /// Seal `preimage` for the order; one preimage per order, never replaced
+    pub fn store(&mut self, order_hash: &str, preimage: &[u8; 32], accessor: &str) -> Result<()> {
+        if self.holds(order_hash)? {
+            let reason = "a preimage is already stored for this order";
+            self.audit.record(order_hash, accessor, AuditEvent::Rejected { reason: reason.into() })?;
+            return Err(vault_error(reason));
+        }
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| vault_error("system randomness unavailable"))?;
+        let mut sealed = preimage.to_vec();
+        self.cipher
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(order_hash.as_bytes()),
+                &mut sealed,
+            )
+            .map_err(|_| vault_error("sealing failed"))?;
+
+        self.audit.record(order_hash, accessor, AuditEvent::Stored)?;
+        let record = Record::Secret {
+            order_hash: order_hash.to_string(),
+            nonce: hex::encode(nonce),
+            sealed: hex::encode(&sealed),
+        };
+        Self::write(&self.path, &mut self.file, &record)?;
+        self.secrets.insert(order_hash.to_string(), Sealed { nonce, sealed });
+        Ok(())
+    }
+
+    /// Decrypt the order's preimage if the policy allows it under
+    /// `conditions`; `None` if none is held or the reveal was refused
+    pub fn reveal(
+        &mut self,
+        order_hash: &str,
+        conditions: &RevealConditions,
+        accessor: &str,
+    ) -> Result<Option<Preimage>> {
+        if !self.holds(order_hash)? {
+            return Ok(None);
+        }
+        if let Some(reason) = self.policy.refusal(conditions) {
+            tracing::info!(order = %order_hash, reason, "preimage reveal refused");
+            self.audit.record(order_hash, accessor, AuditEvent::Denied { reason: reason.into() })?;
+            return Ok(None);
+        }
+        self.audit.record(order_hash, accessor, AuditEvent::Revealed)?;
+        let entry = &self.secrets[order_hash];
+        let mut sealed = Zeroizing::new(entry.sealed.clone());
+        let plain = self
+            .cipher
+            .open_in_place(
+                Nonce::assume_unique_for_key(entry.nonce),
+                Aad::from(order_hash.as_bytes()),
+                &mut sealed,
+            )
+            .map_err(|_| vault_error(format!("preimage for {} failed authentication", order_hash)))?;
+        let preimage: [u8; 32] = plain
+            .try_into()
+            .map_err(|_| vault_error(format!("preimage for {} has the wrong length", order_hash)))?;
+        Ok(Some(Zeroizing::new(preimage)))
+    }
+
+    /// Drop the order's preimage, once it is public or no longer needed
+    pub fn forget(&mut self, order_hash: &str, accessor: &str) -> Result<()> {
+        if !self.secrets.contains_key(order_hash) {
+            return Ok(());
+        }
+        self.audit.record(order_hash, accessor, AuditEvent::Forgotten)?;
+        let record = Record::Forgotten {
+            order_hash: order_hash.to_string(),
+        };
+        Self::write(&self.path, &mut self.file, &record)?;
+        self.secrets.remove(order_hash);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const READY: RevealConditions = RevealConditions {
+        finalized: true,
+        escrow_verified: true,
+    };
+
+    fn master() -> LocalKeyProvider {
+        LocalKeyProvider::new(&"42".repeat(32)).unwrap()
+    }
+
+    async fn open(directory: &TempDir) -> PreimageVault {
+        let path = directory.path().join("vault.jsonl");
+        let audit = directory.path().join("audit.jsonl");
+        PreimageVault::open(path, audit, &master(), RevealPolicy::default()).await.unwrap()
+    }
+
+    fn events(directory: &TempDir) -> Vec<AuditEvent> {
+        AuditLog::read(&directory.path().join("audit.jsonl"))
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.event)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_reveal_follows_policy() {
+        let directory = tempfile::tempdir().unwrap();
+        let mut vault = open(&directory).await;
+        vault.store("0x01", &[7; 32], "cli").unwrap();
+
+        let unverified = RevealConditions {
+            finalized: true,
+            escrow_verified: false,
+        };
+        assert!(vault.reveal("0x01", &unverified, "relayer").unwrap().is_none());
+        assert!(vault.reveal("0x01", &RevealConditions::default(), "relayer").unwrap().is_none());
+        assert_eq!(*vault.reveal("0x01", &READY, "relayer").unwrap().unwrap(), [7; 32]);
+        assert!(vault.reveal("0x02", &READY, "relayer").unwrap().is_none());
+
+        assert_eq!(
+            events(&directory),
+            [
+                AuditEvent::Stored,
+                AuditEvent::Denied {
+                    reason: "destination escrow not verified".into()
+                },
+                AuditEvent::Denied {
+                    reason: "source order not final".into()
+                },
+                AuditEvent::Revealed,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_at_rest_and_reopened() {
+        let directory = tempfile::tempdir().unwrap();
+        let mut vault = open(&directory).await;
+        vault.store("0x01", &[0xab; 32], "cli").unwrap();
+        assert!(vault.store("0x01", &[0xcd; 32], "cli").is_err());
+        drop(vault);
+
+        let contents = std::fs::read_to_string(directory.path().join("vault.jsonl")).unwrap();
+        assert!(!contents.contains(&"ab".repeat(32)));
+
+        let mut vault = open(&directory).await;
+        assert_eq!(*vault.reveal("0x01", &READY, "relayer").unwrap().unwrap(), [0xab; 32]);
+        vault.forget("0x01", "relayer").unwrap();
+        drop(vault);
+        assert!(!open(&directory).await.holds("0x01").unwrap());
+
+        // A different master key can't open the vault
+        let other = LocalKeyProvider::new(&"43".repeat(32)).unwrap();
+        let path = directory.path().join("vault.jsonl");
+        let audit = directory.path().join("audit.jsonl");
+        assert!(PreimageVault::open(path, audit, &other, RevealPolicy::default()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sees_preimages_stored_by_another_process() {
+        let directory = tempfile::tempdir().unwrap();
+        let mut relayer = open(&directory).await;
+        let mut cli = open(&directory).await;
+        cli.store("0x01", &[9; 32], "cli").unwrap();
+        assert!(relayer.holds("0x01").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_torn_last_record_cut_off() {
+        let directory = tempfile::tempdir().unwrap();
+        let mut vault = open(&directory).await;
+        vault.store("0x01", &[1; 32], "cli").unwrap();
+        drop(vault);
+        let path = directory.path().join("vault.jsonl");
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"secret":{"order_ha"#).unwrap();
+
+        let mut vault = open(&directory).await;
+        assert!(vault.holds("0x01").unwrap());
+        vault.store("0x02", &[2; 32], "cli").unwrap();
+        drop(vault);
+
+        // The store after the tear landed on a line of its own
+        let mut vault = open(&directory).await;
+        assert_eq!(*vault.reveal("0x02", &READY, "relayer").unwrap().unwrap(), [2; 32]);
+        vault.store("0x03", &[3; 32], "cli").unwrap();
+        drop(vault);
+        let mut vault = open(&directory).await;
+        for order_hash in ["0x01", "0x02", "0x03"] {
+            assert!(vault.holds(order_hash).unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sealed_preimage_bound_to_order() {
+        let directory = tempfile::tempdir().unwrap();
+        let mut vault = open(&directory).await;
+        vault.store("0x01", &[7; 32], "cli").unwrap();
+        drop(vault);
+
+        // Replay the sealed line under another order hash
+        let path = directory.path().join("vault.jsonl");
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let moved = contents.lines().last().unwrap().replace("0x01", "0x02");
+        std::fs::write(&path, format!("{}{}\n", contents, moved)).unwrap();
+
+        let mut vault = open(&directory).await;
+        assert!(matches!(vault.reveal("0x02", &READY, "relayer"), Err(RelayerError::Vault(_))));
+    }
+}
@@ -0,0 +1,169 @@
+//! Pricing settings, read from the same TOML file as the relayer's
+//! [`relayer::config::Config`]
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use relayer::{RelayerError, Result};
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct BotConfig {
+    #[serde(default)]
+    pub pricing: PricingConfig,
+    #[serde(default)]
+    pub oracle: OracleConfig,
+    /// Price feed IDs of each asset markets refer to, keyed by symbol
+    #[serde(default)]
+    pub assets: HashMap<String, AssetFeeds>,
+    pub markets: Vec<Market>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PricingConfig {
+    /// Margin an order must pay over the market price, in basis points
+    pub spread_bps: u32,
+    /// Orders expiring sooner than this are left alone, seconds
+    pub min_time_to_expiry_secs: u64,
+    /// Largest order to fill, in USD of what it pays out; needs oracle
+    /// prices for the destination asset
+    pub max_fill_usd: Option<f64>,
+}
+
+impl Default for PricingConfig {
+    fn default() -> Self {
+        Self {
+            spread_bps: 30,
+            min_time_to_expiry_secs: 3_600,
+            max_fill_usd: None,
+        }
+    }
+}
+
+/// Feeds the oracle reads and the bounds their prices must meet, see
+/// [`crate::oracle`]
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct OracleConfig {
+    /// Pyth Hermes endpoint
+    pub pyth_url: Option<String>,
+    /// JSON-RPC endpoint of the chain the Chainlink aggregators are on
+    pub chainlink_rpc_url: Option<String>,
+    pub coingecko_url: Option<String>,
+    /// Readings older than this are ignored, seconds
+    pub max_age_secs: u64,
+    /// Furthest a reading may be from the median of all fresh readings
+    pub max_deviation_bps: u32,
+    /// Fresh readings needed for a price
+    pub min_sources: usize,
+    pub refresh_interval_ms: u64,
+}
+
+impl Default for OracleConfig {
+    fn default() -> Self {
+        Self {
+            pyth_url: None,
+            chainlink_rpc_url: None,
+            coingecko_url: None,
+            max_age_secs: 120,
+            max_deviation_bps: 200,
+            min_sources: 1,
+            refresh_interval_ms: 15_000,
+        }
+    }
+}
+
+/// Where each feed finds an asset's USD price
+#[derive(Deserialize, Clone, Default, Debug)]
+pub struct AssetFeeds {
+    /// Pyth price feed ID
+    pub pyth: Option<String>,
+    /// Chainlink aggregator address
+    pub chainlink: Option<String>,
+    /// CoinGecko coin ID
+    pub coingecko: Option<String>,
+}
+
+/// A source token to destination token pair the bot fills
+#[derive(Deserialize, Clone, Debug)]
+pub struct Market {
+    /// ERC-20 address on Ethereum
+    pub source_token: String,
+    pub destination_chain_id: u64,
+    /// Token as named in the order, e.g. `native`
+    pub destination_token: String,
+    pub source_decimals: u32,
+    pub destination_decimals: u32,
+    /// Symbols in `[assets]`, priced by the oracle
+    #[serde(default)]
+    pub source_asset: Option<String>,
+    #[serde(default)]
+    pub destination_asset: Option<String>,
+    /// Fixed destination tokens per source token, in place of oracle
+    /// prices (e.g. for testnet tokens no feed covers)
+    #[serde(default)]
+    pub price: Option<f64>,
+    /// Largest order to fill, in destination tokens
+    pub max_fill: f64,
+    /// Overrides `[pricing] spread_bps` for this market
+    #[serde(default)]
+    pub spread_bps: Option<u32>,
+}
+
+impl BotConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| RelayerError::Config(format!("{}: {}", path.display(), error)))?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        let config: BotConfig = toml::from_str(contents).map_err(|error| RelayerError::Config(error.to_string()))?;
+        if config.markets.is_empty() {
+            return Err(RelayerError::Config("configure at least one [[markets]] entry".into()));
+        }
+        for market in &config.markets {
+            let name = format!("market {} -> {}", market.source_token, market.destination_token);
+            if !(market.max_fill > 0.0 && market.price.is_none_or(|price| price > 0.0)) {
+                return Err(RelayerError::Config(format!("{}: price and max_fill must be positive", name)));
+            }
+            let assets = [&market.source_asset, &market.destination_asset];
+            if market.price.is_none() && assets.iter().any(|asset| asset.is_none()) {
+                return Err(RelayerError::Config(format!("{}: set price or both assets", name)));
+            }
+            for asset in assets.into_iter().flatten() {
+                if !config.assets.contains_key(asset) {
+                    return Err(RelayerError::Config(format!("{}: asset {} not in [assets]", name, asset)));
+                }
+            }
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../resolver-bot.example.toml");
+
+    #[test]
+    fn test_example_config() {
+        let config = BotConfig::parse(EXAMPLE).unwrap();
+        assert_eq!(config.pricing.spread_bps, 50);
+        assert_eq!(config.markets.len(), 2);
+        assert_eq!(config.assets["NEAR"].coingecko.as_deref(), Some("near"));
+        assert_eq!(config.oracle.min_sources, 2);
+        // The relayer sections of the same file still parse
+        assert!(relayer::config::Config::parse(EXAMPLE).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_bad_market() {
+        let config = EXAMPLE.replacen("price = 2.5", "price = 0.0", 1);
+        assert!(matches!(BotConfig::parse(&config), Err(RelayerError::Config(_))));
+        let config = EXAMPLE.replacen("destination_asset = \"NEAR\"", "destination_asset = \"BTC\"", 1);
+        assert!(matches!(BotConfig::parse(&config), Err(RelayerError::Config(_))));
+    }
+}
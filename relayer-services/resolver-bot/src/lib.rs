@@ -0,0 +1,8 @@
+//! Reference Fusion+ resolver: the relayer's order pipeline with a pricing
+//! policy in front, so only orders that pay the configured spread are
+//! filled. Meant to be forked: swap [`pricing::SpreadPricer`] for your own
+//! [`relayer::fill::FillPolicy`].
+
+pub mod config;
+pub mod oracle;
+pub mod pricing;
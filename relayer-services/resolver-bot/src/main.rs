@@ -0,0 +1,88 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use tracing_subscriber::EnvFilter;
+
+use relayer::chain::Destination;
+use relayer::config::Config;
+use relayer::cosmos::CosmosDestination;
+use relayer::ethereum::EthereumSource;
+use relayer::near::NearDestination;
+use relayer::relayer::Relayer;
+use relayer::store::OrderStore;
+use relayer::{RelayerError, Result};
+use resolver_bot::config::BotConfig;
+use resolver_bot::oracle::PriceOracle;
+use resolver_bot::pricing::SpreadPricer;
+
+#[derive(Parser)]
+#[command(about = "Reference Fusion+ resolver filling Ethereum orders on NEAR and Cosmos")]
+struct Args {
+    /// Path to the TOML configuration
+    #[arg(long, default_value = "resolver-bot.toml")]
+    config: PathBuf,
+}
+
+fn env_key(name: &str) -> Result<String> {
+    std::env::var(name).map_err(|_| RelayerError::Key(format!("{} is not set", name)))
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .init();
+
+    let args = Args::parse();
+    let mut config = Config::load(&args.config)?;
+    let bot = BotConfig::load(&args.config)?;
+    let source_chain_id = config.ethereum.chain_id;
+
+    let store = OrderStore::open(&config.relayer.state_path, config.action_retention())?;
+    if let Some(block) = store.cursor() {
+        tracing::info!(block, orders = store.orders().len(), "resuming from stored state");
+        config.ethereum.start_block = Some(block + 1);
+    }
+
+    let source = EthereumSource::new(&config.ethereum, &env_key("ETHEREUM_PRIVATE_KEY")?)?;
+    tracing::info!(address = %source.client().address(), chain = source_chain_id, "watching Ethereum");
+
+    let mut destinations: Vec<Box<dyn Destination>> = Vec::new();
+    if let Some(near) = config.near.clone() {
+        tracing::info!(contract = %near.contract_id, account = %near.account_id, "filling on NEAR");
+        let function_call_keys: Vec<String> = std::env::var("NEAR_FUNCTION_CALL_KEYS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(str::to_string)
+            .collect();
+        let destination = NearDestination::new(near, source_chain_id, &env_key("NEAR_PRIVATE_KEY")?, &function_call_keys)?;
+        destinations.push(Box::new(destination));
+    }
+    if let Some(cosmos) = config.cosmos.clone() {
+        tracing::info!(contract = %cosmos.contract, network = %cosmos.network_id, "filling on Cosmos");
+        destinations.push(Box::new(CosmosDestination::new(cosmos, source_chain_id, &env_key("COSMOS_PRIVATE_KEY")?)?));
+    }
+
+    let prices = match PriceOracle::from_config(&bot) {
+        Some(oracle) => {
+            tracing::info!(assets = bot.assets.len(), "starting price oracle");
+            oracle.refresh().await;
+            let board = oracle.board();
+            tokio::spawn(oracle.run());
+            Some(board)
+        }
+        None => None,
+    };
+    tracing::info!(markets = bot.markets.len(), spread_bps = bot.pricing.spread_bps, "pricing orders");
+    let pricer = SpreadPricer::new(bot.pricing, bot.markets, prices);
+    let mut resolver = Relayer::new(Box::new(source), destinations, config.relayer.hash_algorithm, store)
+        .with_fill_policy(Box::new(pricer));
+    resolver.recover().await?;
+    let shutdown = async {
+        let _ = tokio::signal::ctrl_c().await;
+        tracing::info!("shutting down");
+    };
+    resolver.run(config.relayer.poll_interval(), shutdown).await
+}
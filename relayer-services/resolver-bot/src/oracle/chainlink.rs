@@ -0,0 +1,83 @@
+//! Chainlink aggregators read with `eth_call`
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use relayer::ethereum::abi::{self, Decoder};
+use relayer::rpc::JsonRpcClient;
+use relayer::{RelayerError, Result};
+use serde_json::json;
+
+use super::{Observation, PriceFeed};
+
+const LATEST_ROUND_DATA: &str = "latestRoundData()";
+const DECIMALS: &str = "decimals()";
+
+pub struct ChainlinkFeed {
+    rpc: JsonRpcClient,
+    /// Aggregator address of each asset's USD feed
+    feeds: HashMap<String, String>,
+}
+
+impl ChainlinkFeed {
+    pub fn new(rpc_url: &str, feeds: HashMap<String, String>) -> Self {
+        Self {
+            rpc: JsonRpcClient::new(rpc_url),
+            feeds,
+        }
+    }
+
+    async fn call(&self, to: &str, signature: &str) -> Result<Vec<u8>> {
+        let data = format!("0x{}", hex::encode(abi::encode_call(signature, &[])));
+        let result: String = self.rpc.call("eth_call", json!([{ "to": to, "data": data }, "latest"])).await?;
+        hex::decode(result.trim_start_matches("0x")).map_err(RelayerError::decode)
+    }
+}
+
+/// `(roundId, answer, startedAt, updatedAt, answeredInRound)` with `decimals`
+fn parse(round: &[u8], decimals: &[u8]) -> Result<Observation> {
+    let round = Decoder::new(round);
+    // A negative answer doesn't fit and is rejected
+    let answer = round.uint128(1)?;
+    let decimals = Decoder::new(decimals).uint64(0)?;
+    Ok(Observation {
+        usd: answer as f64 / 10f64.powi(decimals as i32),
+        published_at: round.uint64(3)?,
+    })
+}
+
+#[async_trait]
+impl PriceFeed for ChainlinkFeed {
+    fn name(&self) -> &'static str {
+        "chainlink"
+    }
+
+    async fn observe(&self, asset: &str) -> Result<Option<Observation>> {
+        let Some(feed) = self.feeds.get(asset) else {
+            return Ok(None);
+        };
+        let round = self.call(feed, LATEST_ROUND_DATA).await?;
+        let decimals = self.call(feed, DECIMALS).await?;
+        parse(&round, &decimals).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_round() {
+        let round: Vec<u8> = [1, 99_980_000, 1_799_999_990, 1_800_000_000, 1]
+            .into_iter()
+            .flat_map(abi::u128_word)
+            .collect();
+        let observation = parse(&round, &abi::u128_word(8)).unwrap();
+        assert!((observation.usd - 0.9998).abs() < 1e-12);
+        assert_eq!(observation.published_at, 1_800_000_000);
+
+        let mut negative = round.clone();
+        negative[32..48].fill(0xff);
+        assert!(parse(&negative, &abi::u128_word(8)).is_err());
+    }
+}
@@ -0,0 +1,83 @@
+//! CoinGecko's `simple/price`, the fallback when on-chain feeds are missing
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use relayer::{RelayerError, Result};
+use serde::Deserialize;
+
+use super::{Observation, PriceFeed};
+
+#[derive(Deserialize)]
+struct SimplePrice {
+    usd: f64,
+    last_updated_at: u64,
+}
+
+pub struct CoinGeckoFeed {
+    http: reqwest::Client,
+    url: String,
+    /// CoinGecko coin ID of each asset, e.g. `near`
+    ids: HashMap<String, String>,
+    /// Sent as `x-cg-pro-api-key`
+    api_key: Option<String>,
+}
+
+impl CoinGeckoFeed {
+    pub fn new(url: &str, ids: HashMap<String, String>, api_key: Option<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+            ids,
+            api_key,
+        }
+    }
+}
+
+fn parse(mut prices: HashMap<String, SimplePrice>, id: &str) -> Result<Observation> {
+    let price = prices
+        .remove(id)
+        .ok_or_else(|| RelayerError::decode(format!("no CoinGecko price for {}", id)))?;
+    Ok(Observation {
+        usd: price.usd,
+        published_at: price.last_updated_at,
+    })
+}
+
+#[async_trait]
+impl PriceFeed for CoinGeckoFeed {
+    fn name(&self) -> &'static str {
+        "coingecko"
+    }
+
+    async fn observe(&self, asset: &str) -> Result<Option<Observation>> {
+        let Some(id) = self.ids.get(asset) else {
+            return Ok(None);
+        };
+        let mut request = self
+            .http
+            .get(format!("{}/api/v3/simple/price", self.url))
+            .query(&[("ids", id.as_str()), ("vs_currencies", "usd"), ("include_last_updated_at", "true")]);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("x-cg-pro-api-key", api_key);
+        }
+        let prices = request.send().await?.error_for_status()?.json().await?;
+        parse(prices, id).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_simple_price() {
+        let prices = serde_json::from_str(r#"{"near":{"usd":5.12,"last_updated_at":1800000000}}"#).unwrap();
+        let observation = parse(prices, "near").unwrap();
+        assert_eq!(observation.usd, 5.12);
+        assert_eq!(observation.published_at, 1_800_000_000);
+
+        let prices = serde_json::from_str("{}").unwrap();
+        assert!(parse(prices, "near").is_err());
+    }
+}
@@ -0,0 +1,279 @@
+//! USD prices for the pricer and the risk limits, from several feeds at once
+//!
+//! Each [`PriceFeed`] (Pyth, Chainlink, CoinGecko, or your own) reports
+//! what it has for an asset. Readings older than `max_age_secs` are
+//! dropped; the rest must number at least `min_sources`, and all lie within
+//! `max_deviation_bps` of their median, which becomes the asset's price.
+//! Otherwise the asset has no price and no order depending on it is
+//! filled. [`PriceOracle::run`] refreshes the [`PriceBoard`] the pricer
+//! reads from, which checks the age again on every read.
+
+pub mod chainlink;
+pub mod coingecko;
+pub mod pyth;
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use relayer::Result;
+use thiserror::Error;
+
+use crate::config::{BotConfig, OracleConfig};
+
+/// A USD price as one feed reports it
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Observation {
+    pub usd: f64,
+    /// Unix time the feed published it
+    pub published_at: u64,
+}
+
+#[async_trait]
+pub trait PriceFeed: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Latest price of `asset` (a key of `[assets]`), `None` if the feed
+    /// doesn't cover it
+    async fn observe(&self, asset: &str) -> Result<Option<Observation>>;
+}
+
+/// Agreed USD price of an asset
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Quote {
+    pub usd: f64,
+    /// Publication time of the oldest reading used
+    pub at: u64,
+    pub sources: usize,
+}
+
+#[derive(Clone, PartialEq, Debug, Error)]
+pub enum PriceError {
+    #[error("no price for {0}")]
+    Unknown(String),
+    #[error("{asset}: {fresh} fresh price(s), {required} required")]
+    TooFewSources { asset: String, fresh: usize, required: usize },
+    #[error("{asset}: feeds disagree, {low} to {high} USD")]
+    Diverged { asset: String, low: f64, high: f64 },
+    #[error("{asset}: last price from {at} is stale")]
+    Stale { asset: String, at: u64 },
+}
+
+/// The price of `asset` at unix time `now` from `observations`
+pub fn aggregate(asset: &str, observations: &[Observation], now: u64, rules: &OracleConfig) -> std::result::Result<Quote, PriceError> {
+    let mut fresh: Vec<Observation> = observations
+        .iter()
+        .filter(|observation| observation.usd > 0.0 && now.saturating_sub(observation.published_at) <= rules.max_age_secs)
+        .copied()
+        .collect();
+    let required = rules.min_sources.max(1);
+    if fresh.len() < required {
+        return Err(PriceError::TooFewSources {
+            asset: asset.to_string(),
+            fresh: fresh.len(),
+            required,
+        });
+    }
+    fresh.sort_by(|a, b| a.usd.total_cmp(&b.usd));
+    let middle = fresh.len() / 2;
+    let median = if fresh.len() % 2 == 1 {
+        fresh[middle].usd
+    } else {
+        (fresh[middle - 1].usd + fresh[middle].usd) / 2.0
+    };
+    let (low, high) = (fresh[0].usd, fresh[fresh.len() - 1].usd);
+    let deviation = |usd: f64| (usd - median).abs() / median * 10_000.0;
+    if deviation(low).max(deviation(high)) > rules.max_deviation_bps as f64 {
+        return Err(PriceError::Diverged {
+            asset: asset.to_string(),
+            low,
+            high,
+        });
+    }
+    Ok(Quote {
+        usd: median,
+        at: fresh.iter().map(|observation| observation.published_at).min().unwrap_or(now),
+        sources: fresh.len(),
+    })
+}
+
+/// Latest price, or why there is none, of every asset
+pub struct PriceBoard {
+    max_age_secs: u64,
+    quotes: RwLock<HashMap<String, std::result::Result<Quote, PriceError>>>,
+}
+
+impl PriceBoard {
+    pub fn new(max_age_secs: u64) -> Self {
+        Self {
+            max_age_secs,
+            quotes: RwLock::default(),
+        }
+    }
+
+    pub fn set(&self, asset: &str, quote: std::result::Result<Quote, PriceError>) {
+        self.quotes.write().unwrap().insert(asset.to_string(), quote);
+    }
+
+    /// USD price of `asset`, if fresh at unix time `now`
+    pub fn usd(&self, asset: &str, now: u64) -> std::result::Result<f64, PriceError> {
+        match self.quotes.read().unwrap().get(asset) {
+            None => Err(PriceError::Unknown(asset.to_string())),
+            Some(Err(error)) => Err(error.clone()),
+            Some(Ok(quote)) if now.saturating_sub(quote.at) > self.max_age_secs => Err(PriceError::Stale {
+                asset: asset.to_string(),
+                at: quote.at,
+            }),
+            Some(Ok(quote)) => Ok(quote.usd),
+        }
+    }
+}
+
+pub struct PriceOracle {
+    feeds: Vec<Box<dyn PriceFeed>>,
+    assets: Vec<String>,
+    rules: OracleConfig,
+    board: Arc<PriceBoard>,
+}
+
+impl PriceOracle {
+    pub fn new(feeds: Vec<Box<dyn PriceFeed>>, assets: Vec<String>, rules: OracleConfig) -> Self {
+        Self {
+            board: Arc::new(PriceBoard::new(rules.max_age_secs)),
+            feeds,
+            assets,
+            rules,
+        }
+    }
+
+    /// Oracle over the feeds configured in `[oracle]` for the `[assets]`,
+    /// `None` without assets; `COINGECKO_API_KEY` is used when set
+    pub fn from_config(config: &BotConfig) -> Option<Self> {
+        if config.assets.is_empty() {
+            return None;
+        }
+        let ids = |feed: fn(&crate::config::AssetFeeds) -> &Option<String>| -> HashMap<String, String> {
+            config
+                .assets
+                .iter()
+                .filter_map(|(asset, feeds)| feed(feeds).clone().map(|id| (asset.clone(), id)))
+                .collect()
+        };
+        let oracle = &config.oracle;
+        let mut feeds: Vec<Box<dyn PriceFeed>> = Vec::new();
+        if let Some(url) = &oracle.pyth_url {
+            feeds.push(Box::new(pyth::PythFeed::new(url, ids(|feeds| &feeds.pyth))));
+        }
+        if let Some(url) = &oracle.chainlink_rpc_url {
+            feeds.push(Box::new(chainlink::ChainlinkFeed::new(url, ids(|feeds| &feeds.chainlink))));
+        }
+        if let Some(url) = &oracle.coingecko_url {
+            let api_key = std::env::var("COINGECKO_API_KEY").ok();
+            feeds.push(Box::new(coingecko::CoinGeckoFeed::new(url, ids(|feeds| &feeds.coingecko), api_key)));
+        }
+        let mut assets: Vec<String> = config.assets.keys().cloned().collect();
+        assets.sort();
+        Some(Self::new(feeds, assets, oracle.clone()))
+    }
+
+    /// Where the latest prices are published
+    pub fn board(&self) -> Arc<PriceBoard> {
+        self.board.clone()
+    }
+
+    /// Ask every feed for every asset and publish the results
+    pub async fn refresh(&self) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        for asset in &self.assets {
+            let mut observations = Vec::new();
+            for feed in &self.feeds {
+                match feed.observe(asset).await {
+                    Ok(Some(observation)) => observations.push(observation),
+                    Ok(None) => {}
+                    Err(error) => tracing::warn!(feed = feed.name(), %asset, %error, "price feed failed"),
+                }
+            }
+            let quote = aggregate(asset, &observations, now, &self.rules);
+            match &quote {
+                Ok(quote) => tracing::debug!(%asset, usd = quote.usd, sources = quote.sources, "price updated"),
+                Err(error) => tracing::warn!(%error, "no usable price"),
+            }
+            self.board.set(asset, quote);
+        }
+    }
+
+    /// Refresh every `refresh_interval_ms`, forever
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(Duration::from_millis(self.rules.refresh_interval_ms));
+        loop {
+            interval.tick().await;
+            self.refresh().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_800_000_000;
+
+    fn rules(min_sources: usize) -> OracleConfig {
+        OracleConfig {
+            min_sources,
+            ..OracleConfig::default()
+        }
+    }
+
+    fn at(usd: f64, age: u64) -> Observation {
+        Observation {
+            usd,
+            published_at: NOW - age,
+        }
+    }
+
+    #[test]
+    fn test_median_of_fresh_feeds() {
+        let quote = aggregate("NEAR", &[at(5.00, 10), at(5.02, 0), at(9.99, 3_600)], NOW, &rules(2)).unwrap();
+        assert_eq!(quote.usd, 5.01);
+        assert_eq!((quote.sources, quote.at), (2, NOW - 10));
+        let quote = aggregate("NEAR", &[at(5.00, 0), at(5.01, 0), at(5.05, 0)], NOW, &rules(1)).unwrap();
+        assert_eq!(quote.usd, 5.01);
+    }
+
+    #[test]
+    fn test_refuses_stale_or_single_source() {
+        assert_eq!(
+            aggregate("NEAR", &[at(5.0, 10), at(5.0, 3_600)], NOW, &rules(2)),
+            Err(PriceError::TooFewSources {
+                asset: "NEAR".into(),
+                fresh: 1,
+                required: 2
+            })
+        );
+        assert!(aggregate("NEAR", &[], NOW, &rules(0)).is_err());
+    }
+
+    #[test]
+    fn test_refuses_diverging_feeds() {
+        // Default bound is 200 bps around the median
+        assert!(aggregate("USDC", &[at(1.0, 0), at(1.01, 0), at(1.015, 0)], NOW, &rules(1)).is_ok());
+        assert!(matches!(
+            aggregate("USDC", &[at(1.0, 0), at(1.01, 0), at(1.05, 0)], NOW, &rules(1)),
+            Err(PriceError::Diverged { .. })
+        ));
+    }
+
+    #[test]
+    fn test_board_goes_stale() {
+        let board = PriceBoard::new(60);
+        assert_eq!(board.usd("NEAR", NOW), Err(PriceError::Unknown("NEAR".into())));
+        board.set("NEAR", Ok(Quote { usd: 5.0, at: NOW, sources: 2 }));
+        assert_eq!(board.usd("NEAR", NOW + 60), Ok(5.0));
+        assert!(matches!(board.usd("NEAR", NOW + 61), Err(PriceError::Stale { .. })));
+    }
+}
@@ -0,0 +1,101 @@
+//! Pyth prices from a Hermes endpoint
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use relayer::{RelayerError, Result};
+use serde::Deserialize;
+
+use super::{Observation, PriceFeed};
+
+#[derive(Deserialize)]
+struct Updates {
+    parsed: Vec<Update>,
+}
+
+#[derive(Deserialize)]
+struct Update {
+    id: String,
+    price: PythPrice,
+}
+
+/// `price * 10^expo` USD
+#[derive(Deserialize)]
+struct PythPrice {
+    price: String,
+    expo: i32,
+    publish_time: u64,
+}
+
+pub struct PythFeed {
+    http: reqwest::Client,
+    url: String,
+    /// Price feed ID (hex) of each asset
+    ids: HashMap<String, String>,
+}
+
+impl PythFeed {
+    pub fn new(url: &str, ids: HashMap<String, String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+            ids,
+        }
+    }
+}
+
+fn normalize(id: &str) -> String {
+    id.trim_start_matches("0x").to_ascii_lowercase()
+}
+
+fn parse(updates: Updates, id: &str) -> Result<Observation> {
+    let update = updates
+        .parsed
+        .into_iter()
+        .find(|update| normalize(&update.id) == normalize(id))
+        .ok_or_else(|| RelayerError::decode(format!("no Pyth update for {}", id)))?;
+    let price: i64 = update.price.price.parse().map_err(RelayerError::decode)?;
+    Ok(Observation {
+        usd: price as f64 * 10f64.powi(update.price.expo),
+        published_at: update.price.publish_time,
+    })
+}
+
+#[async_trait]
+impl PriceFeed for PythFeed {
+    fn name(&self) -> &'static str {
+        "pyth"
+    }
+
+    async fn observe(&self, asset: &str) -> Result<Option<Observation>> {
+        let Some(id) = self.ids.get(asset) else {
+            return Ok(None);
+        };
+        let updates: Updates = self
+            .http
+            .get(format!("{}/v2/updates/price/latest", self.url))
+            .query(&[("ids[]", id.as_str()), ("parsed", "true")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        parse(updates, id).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hermes_update() {
+        let body = r#"{"binary":{"encoding":"hex","data":[]},"parsed":[{"id":"c415de8d2eba7db216527dff4b60e8f3a5311c740dadb233e13e12547e226750",
+            "price":{"price":"512345678","conf":"250000","expo":-8,"publish_time":1800000000},
+            "ema_price":{"price":"510000000","conf":"250000","expo":-8,"publish_time":1800000000}}]}"#;
+        let updates: Updates = serde_json::from_str(body).unwrap();
+        let observation = parse(updates, "0xC415DE8D2EBA7DB216527DFF4B60E8F3A5311C740DADB233E13E12547E226750").unwrap();
+        assert!((observation.usd - 5.12345678).abs() < 1e-12);
+        assert_eq!(observation.published_at, 1_800_000_000);
+    }
+}
@@ -0,0 +1,238 @@
+//! Fill decisions from market prices and a required spread
+//!
+//! An order pays the resolver `source_amount + resolver_fee` of the source
+//! token on Ethereum, for `destination_amount` of the destination token
+//! paid out to the maker. It is filled when, at the market's price, what
+//! the resolver gets is worth at least the spread more than what it pays.
+//! Prices come from the [`PriceBoard`], or are fixed per market.
+//!
+//! Orders below the spread, or whose assets have no usable price right
+//! now, are asked about again every tick; orders without a market, above
+//! `max_fill` or `max_fill_usd`, or too close to expiry are declined.
+
+use std::sync::Arc;
+
+use relayer::fill::{Decision, FillPolicy};
+use relayer::order::SwapOrder;
+
+use crate::config::{Market, PricingConfig};
+use crate::oracle::PriceBoard;
+
+pub struct SpreadPricer {
+    pricing: PricingConfig,
+    markets: Vec<Market>,
+    prices: Option<Arc<PriceBoard>>,
+}
+
+impl SpreadPricer {
+    /// `prices` is needed by markets without a fixed price
+    pub fn new(pricing: PricingConfig, markets: Vec<Market>, prices: Option<Arc<PriceBoard>>) -> Self {
+        Self {
+            pricing,
+            markets,
+            prices,
+        }
+    }
+
+    fn usd(&self, asset: &str, now: u64) -> Result<f64, String> {
+        let prices = self.prices.as_ref().ok_or("no price oracle configured")?;
+        prices.usd(asset, now).map_err(|error| error.to_string())
+    }
+
+    /// Destination tokens per source token at unix time `now`
+    fn price(&self, market: &Market, now: u64) -> Result<f64, String> {
+        if let Some(price) = market.price {
+            return Ok(price);
+        }
+        let (Some(source), Some(destination)) = (&market.source_asset, &market.destination_asset) else {
+            return Err("market has neither a price nor assets".into());
+        };
+        Ok(self.usd(source, now)? / self.usd(destination, now)?)
+    }
+
+    fn market(&self, order: &SwapOrder) -> Option<&Market> {
+        self.markets.iter().find(|market| {
+            market.source_token.eq_ignore_ascii_case(&order.source_token)
+                && market.destination_chain_id == order.destination_chain_id
+                && market.destination_token == order.destination_token
+        })
+    }
+
+    /// What filling `order` earns over its cost at `price`, in basis points of the cost
+    pub fn margin_bps(market: &Market, order: &SwapOrder, price: f64) -> f64 {
+        let received = (order.source_amount + order.resolver_fee) as f64 / 10f64.powi(market.source_decimals as i32);
+        let paid = order.destination_amount as f64 / 10f64.powi(market.destination_decimals as i32);
+        (received * price / paid - 1.0) * 10_000.0
+    }
+}
+
+impl FillPolicy for SpreadPricer {
+    fn decide(&self, order: &SwapOrder, now: u64) -> Decision {
+        let Some(market) = self.market(order) else {
+            return Decision::Decline {
+                reason: format!(
+                    "no market for {} to {} on chain {}",
+                    order.source_token, order.destination_token, order.destination_chain_id
+                ),
+            };
+        };
+        if order.expiry_time < now + self.pricing.min_time_to_expiry_secs {
+            return Decision::Decline {
+                reason: format!("expires at {}, too soon", order.expiry_time),
+            };
+        }
+        let paid = order.destination_amount as f64 / 10f64.powi(market.destination_decimals as i32);
+        if paid > market.max_fill {
+            return Decision::Decline {
+                reason: format!("pays out {} tokens, above max_fill {}", paid, market.max_fill),
+            };
+        }
+        if let (Some(limit), Some(asset)) = (self.pricing.max_fill_usd, &market.destination_asset) {
+            match self.usd(asset, now) {
+                Ok(usd) if paid * usd > limit => {
+                    return Decision::Decline {
+                        reason: format!("pays out {:.2} USD, above max_fill_usd {}", paid * usd, limit),
+                    }
+                }
+                Ok(_) => {}
+                Err(reason) => return Decision::Wait { reason },
+            }
+        }
+        let price = match self.price(market, now) {
+            Ok(price) => price,
+            Err(reason) => return Decision::Wait { reason },
+        };
+        let spread = market.spread_bps.unwrap_or(self.pricing.spread_bps);
+        let margin = Self::margin_bps(market, order, price);
+        if margin >= spread as f64 {
+            tracing::info!(order = %order.order_hash, margin_bps = margin, "order pays the spread, filling");
+            Decision::Fill
+        } else {
+            Decision::Wait {
+                reason: format!("margin {:.1} bps below the {} bps spread", margin, spread),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::{PriceError, Quote};
+
+    const NOW: u64 = 1_800_000_000;
+    const USDC: &str = "0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238";
+
+    fn market() -> Market {
+        Market {
+            source_token: USDC.into(),
+            destination_chain_id: 40002,
+            destination_token: "native".into(),
+            source_decimals: 6,
+            destination_decimals: 24,
+            source_asset: None,
+            destination_asset: None,
+            price: Some(0.25),
+            max_fill: 1_000.0,
+            spread_bps: None,
+        }
+    }
+
+    fn pricer() -> SpreadPricer {
+        let pricing = PricingConfig {
+            spread_bps: 50,
+            min_time_to_expiry_secs: 3_600,
+            max_fill_usd: None,
+        };
+        SpreadPricer::new(pricing, vec![market()], None)
+    }
+
+    /// Market priced by the oracle at 1 USD per USDC and 4 USD per NEAR
+    fn oracle_pricer(max_fill_usd: Option<f64>) -> (SpreadPricer, Arc<PriceBoard>) {
+        let board = Arc::new(PriceBoard::new(120));
+        board.set("USDC", Ok(Quote { usd: 1.0, at: NOW, sources: 2 }));
+        board.set("NEAR", Ok(Quote { usd: 4.0, at: NOW, sources: 2 }));
+        let mut market = market();
+        market.price = None;
+        market.source_asset = Some("USDC".into());
+        market.destination_asset = Some("NEAR".into());
+        let mut pricing = pricer().pricing;
+        pricing.max_fill_usd = max_fill_usd;
+        (SpreadPricer::new(pricing, vec![market], Some(board.clone())), board)
+    }
+
+    /// 100 USDC plus a 0.1 USDC fee for `near` NEAR
+    fn order(near: f64) -> SwapOrder {
+        SwapOrder {
+            order_hash: "0x01".into(),
+            maker: "0x".to_string() + &"aa".repeat(20),
+            source_token: USDC.to_lowercase(),
+            source_amount: 100_000_000,
+            destination_chain_id: 40002,
+            destination_token: "native".into(),
+            destination_amount: (near * 1e24) as u128,
+            destination_address: "maker.testnet".into(),
+            resolver_fee: 100_000,
+            expiry_time: NOW + 7_200,
+            hashlock: [1; 32],
+            block_number: 1,
+        }
+    }
+
+    #[test]
+    fn test_fills_above_spread() {
+        // 100.1 USDC is worth 25.025 NEAR, 24.9 NEAR out leaves ~50.2 bps
+        assert_eq!(pricer().decide(&order(24.9), NOW), Decision::Fill);
+        assert!(matches!(pricer().decide(&order(24.95), NOW), Decision::Wait { .. }));
+    }
+
+    #[test]
+    fn test_market_spread_override() {
+        let mut tight = market();
+        tight.spread_bps = Some(10);
+        let pricer = SpreadPricer::new(pricer().pricing, vec![tight], None);
+        assert_eq!(pricer.decide(&order(24.95), NOW), Decision::Fill);
+    }
+
+    #[test]
+    fn test_declines() {
+        let pricer = pricer();
+        let mut other = order(24.0);
+        other.destination_chain_id = 30001;
+        assert!(matches!(pricer.decide(&other, NOW), Decision::Decline { .. }));
+        let mut expiring = order(24.0);
+        expiring.expiry_time = NOW + 3_599;
+        assert!(matches!(pricer.decide(&expiring, NOW), Decision::Decline { .. }));
+        assert!(matches!(pricer.decide(&order(1_001.0), NOW), Decision::Decline { .. }));
+    }
+
+    #[test]
+    fn test_oracle_prices() {
+        let (pricer, board) = oracle_pricer(None);
+        assert_eq!(pricer.decide(&order(24.9), NOW), Decision::Fill);
+
+        // NEAR up 1%: the same order no longer pays the spread
+        board.set("NEAR", Ok(Quote { usd: 4.04, at: NOW, sources: 2 }));
+        assert!(matches!(pricer.decide(&order(24.9), NOW), Decision::Wait { .. }));
+
+        // No quoting on feeds that disagree or went quiet
+        let diverged = PriceError::Diverged {
+            asset: "NEAR".into(),
+            low: 3.0,
+            high: 4.0,
+        };
+        board.set("NEAR", Err(diverged));
+        assert!(matches!(pricer.decide(&order(20.0), NOW), Decision::Wait { .. }));
+        board.set("NEAR", Ok(Quote { usd: 4.0, at: NOW, sources: 2 }));
+        assert!(matches!(pricer.decide(&order(20.0), NOW + 121), Decision::Wait { .. }));
+    }
+
+    #[test]
+    fn test_max_fill_usd() {
+        let (pricer, _) = oracle_pricer(Some(90.0));
+        // 24.9 NEAR is 99.6 USD
+        assert!(matches!(pricer.decide(&order(24.9), NOW), Decision::Decline { .. }));
+        let (pricer, _) = oracle_pricer(Some(100.0));
+        assert_eq!(pricer.decide(&order(24.9), NOW), Decision::Fill);
+    }
+}